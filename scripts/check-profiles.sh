@@ -0,0 +1,35 @@
+#!/bin/bash
+set -e
+
+echo "Checking slicer profiles and material mapping"
+
+# Check if uv environment exists
+if [ ! -d ".venv" ]; then
+    echo "ERROR: uv environment not found. Run ./scripts/setup.sh first"
+    exit 1
+fi
+
+SKIP_PROFILE_VALIDATION=true uv run python -c "
+import sys
+
+from orca_quote_machine._rust_core import lint_profiles, list_unmapped_materials
+from orca_quote_machine.core.config import get_settings
+from orca_quote_machine.models.quote import MaterialType
+
+profiles = get_settings().slicer_profiles
+report = lint_profiles(str(profiles.base_dir), [str(d) for d in profiles.system_dirs])
+for issue in report.issues:
+    print(issue)
+print(report)
+
+unmapped = list_unmapped_materials(
+    str(profiles.base_dir / 'filament'),
+    [m.value for m in MaterialType],
+    str(profiles.material_map),
+)
+if unmapped:
+    print('Unmapped materials: ' + ', '.join(unmapped))
+if unmapped or not report.is_ok:
+    sys.exit(1)
+print('All profiles are valid and every material resolves to a filament profile')
+"
@@ -0,0 +1,384 @@
+use flate2::read::GzDecoder;
+use pyo3::prelude::*;
+use sanitize_filename::sanitize;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use zip::ZipArchive;
+
+use crate::panic_boundary;
+use crate::workspace::JobWorkspace;
+use crate::{model_info, ModelInfo, OrcaError, ValidationError, MODEL_EXTENSIONS};
+
+/// An archive with more entries than this is refused before any is read.
+const MAX_ENTRIES: usize = 1000;
+const DEFAULT_MAX_UNPACKED_BYTES: u64 = 512 * 1024 * 1024;
+const DEFAULT_MAX_RATIO: u64 = 100;
+/// Small entries can compress extremely well legitimately, a flat mesh or
+/// padding, so the ratio is only held against entries larger than this.
+const RATIO_GRACE_BYTES: u64 = 8 * 1024 * 1024;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Archives a model upload has no business carrying; 3MF and zipped AMF are
+/// ZIPs too, but are models.
+const NESTED_ARCHIVE_EXTENSIONS: &[&str] =
+    &["zip", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "tar"];
+
+/// Largest model a ZIP or gzip upload may unpack to.
+static MAX_UNPACKED_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MAX_UNPACKED_BYTES);
+/// How many times its compressed size an entry may inflate to.
+static MAX_RATIO: AtomicU64 = AtomicU64::new(DEFAULT_MAX_RATIO);
+
+fn max_unpacked_bytes() -> u64 {
+    MAX_UNPACKED_BYTES.load(Ordering::Relaxed)
+}
+
+/// Carried inside an `io::Error` of kind `InvalidData` when an archive looks
+/// built to exhaust memory or disk, so callers can tell it apart from a
+/// merely corrupt one.
+#[derive(Debug)]
+pub struct SuspiciousArchive(String);
+
+impl fmt::Display for SuspiciousArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for SuspiciousArchive {}
+
+fn suspicious(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, SuspiciousArchive(message))
+}
+
+/// Why an archive was refused as a likely decompression bomb, when that is
+/// what `err` is.
+pub fn suspicion(err: &io::Error) -> Option<String> {
+    err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<SuspiciousArchive>())
+        .map(|inner| inner.0.clone())
+}
+
+/// Reads a compressed entry, failing once it has inflated past the unpack
+/// limit, or past the allowed ratio of its compressed size. Declared sizes
+/// can lie, so this is what holds an entry to them.
+pub(crate) struct InflateGuard<R> {
+    reader: R,
+    name: String,
+    compressed: u64,
+    inflated: u64,
+}
+
+impl<R: Read> InflateGuard<R> {
+    pub(crate) fn new(reader: R, name: &str, compressed: u64) -> Self {
+        InflateGuard {
+            reader,
+            name: name.to_string(),
+            compressed,
+            inflated: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for InflateGuard<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.inflated += read as u64;
+        if self.inflated > max_unpacked_bytes() {
+            return Err(suspicious(too_large(&self.name)));
+        }
+        let ratio = MAX_RATIO.load(Ordering::Relaxed);
+        if self.inflated > RATIO_GRACE_BYTES
+            && self.inflated > self.compressed.max(1).saturating_mul(ratio)
+        {
+            return Err(suspicious(format!(
+                "{} inflates to over {} times its compressed size",
+                self.name, ratio
+            )));
+        }
+        Ok(read)
+    }
+}
+
+fn too_large(name: &str) -> String {
+    format!(
+        "{} is larger than {} MB unpacked",
+        name,
+        max_unpacked_bytes() / (1024 * 1024)
+    )
+}
+
+/// How an upload wraps the model it carries.
+#[derive(Clone, Copy)]
+enum Packing {
+    Zip,
+    /// A single gzip-compressed model, named like `part.stl.gz`.
+    Gzip,
+}
+
+impl Packing {
+    fn of(path: &Path) -> Option<Packing> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "zip" => Some(Packing::Zip),
+            "gz" => Some(Packing::Gzip),
+            _ => None,
+        }
+    }
+
+    fn file_type(self) -> &'static str {
+        match self {
+            Packing::Zip => "zip",
+            Packing::Gzip => "gz",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Packing::Zip => "ZIP",
+            Packing::Gzip => "gzip",
+        }
+    }
+}
+
+/// Whether an upload is a ZIP archive or gzip file to take a model out of.
+pub fn is_archive(path: &Path) -> bool {
+    Packing::of(path).is_some()
+}
+
+fn is_nested_archive(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| {
+            NESTED_ARCHIVE_EXTENSIONS
+                .iter()
+                .any(|archive| ext.eq_ignore_ascii_case(archive))
+        })
+}
+
+fn is_model_name(file_name: &str) -> bool {
+    Path::new(file_name)
+        .extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| {
+            MODEL_EXTENSIONS
+                .iter()
+                .any(|model| ext.eq_ignore_ascii_case(model))
+        })
+}
+
+/// The first supported model in an archive, read into memory.
+struct ArchivedModel {
+    /// Entry name as stored, e.g. `parts/bracket.stl`.
+    entry_name: String,
+    /// The entry's own file name, safe to write anywhere.
+    file_name: String,
+    data: Vec<u8>,
+}
+
+/// Find and read the first model in archive order; `Err` with the reason
+/// when the archive holds none that can be taken out.
+fn read_zipped_model(path: &Path) -> io::Result<Result<ArchivedModel, String>> {
+    let Ok(mut archive) = ZipArchive::new(File::open(path)?) else {
+        return Ok(Err("not a ZIP archive".to_string()));
+    };
+    if archive.len() > MAX_ENTRIES {
+        return Ok(Err(format!(
+            "{} entries, more than the {} allowed",
+            archive.len(),
+            MAX_ENTRIES
+        )));
+    }
+    // An archive inside the upload is how bombs nest; none is unpacked.
+    if let Some(nested) = archive.file_names().find(|name| is_nested_archive(name)) {
+        return Err(suspicious(format!("holds another archive, {}", nested)));
+    }
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        // Names that would escape the extraction directory are never used.
+        let Some(file_name) = entry
+            .enclosed_name()
+            .and_then(|name| name.file_name())
+            .map(|name| sanitize(name.to_string_lossy()))
+        else {
+            continue;
+        };
+        // Folders, macOS resource forks and hidden files are not models.
+        if entry.is_dir()
+            || entry.name().starts_with("__MACOSX/")
+            || file_name.starts_with('.')
+            || !is_model_name(&file_name)
+        {
+            continue;
+        }
+        let entry_name = entry.name().to_string();
+        if entry.size() > max_unpacked_bytes() {
+            return Ok(Err(too_large(&entry_name)));
+        }
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        let compressed = entry.compressed_size();
+        InflateGuard::new(entry, &entry_name, compressed).read_to_end(&mut data)?;
+        return Ok(Ok(ArchivedModel {
+            entry_name,
+            file_name,
+            data,
+        }));
+    }
+    Ok(Err(format!(
+        "no {} file inside",
+        MODEL_EXTENSIONS.join("/").to_uppercase()
+    )))
+}
+
+/// Decompress a gzip upload into `dir` under its name without `.gz`, which
+/// has to name a model, streaming and capped.
+fn extract_gzip(path: &Path, dir: &Path) -> io::Result<Result<(String, PathBuf), String>> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 2];
+    if file.read_exact(&mut magic).is_err() || magic != GZIP_MAGIC {
+        return Ok(Err("not gzip data".to_string()));
+    }
+    let inner = path
+        .file_stem()
+        .map(|stem| sanitize(stem.to_string_lossy()))
+        .filter(|name| is_model_name(name));
+    let Some(inner) = inner else {
+        return Ok(Err(
+            "the name does not say what is inside, e.g. part.stl.gz".to_string(),
+        ));
+    };
+    file.rewind()?;
+    let compressed = file.metadata()?.len();
+    let target = dir.join(&inner);
+    let copied = io::copy(
+        &mut InflateGuard::new(GzDecoder::new(BufReader::new(file)), &inner, compressed),
+        &mut File::create(&target)?,
+    );
+    match copied {
+        Ok(_) => Ok(Ok((inner, target))),
+        Err(e) if suspicion(&e).is_some() => Err(e),
+        // A truncated or corrupt stream is a bad upload, not an I/O failure.
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::InvalidData
+                    | io::ErrorKind::InvalidInput
+                    | io::ErrorKind::UnexpectedEof
+            ) =>
+        {
+            Ok(Err(format!("corrupt gzip data: {}", e)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Take the model out of a ZIP or gzip upload into `dir`, under its own file
+/// name. Returns the archive entry (or decompressed name) and the file.
+pub fn extract_model(path: &Path, dir: &Path) -> io::Result<Result<(String, PathBuf), String>> {
+    match Packing::of(path) {
+        Some(Packing::Gzip) => extract_gzip(path, dir),
+        _ => {
+            let model = match read_zipped_model(path)? {
+                Ok(model) => model,
+                Err(message) => return Ok(Err(message)),
+            };
+            let target = dir.join(&model.file_name);
+            fs::write(&target, &model.data)?;
+            Ok(Ok((model.entry_name, target)))
+        }
+    }
+}
+
+/// `extract_model`, with an upload that holds no usable model as an error.
+pub fn unpack(path: &Path, dir: &Path) -> Result<(String, PathBuf), OrcaError> {
+    let label = Packing::of(path).unwrap_or(Packing::Zip).label();
+    extract_model(path, dir)
+        .map_err(|e| match suspicion(&e) {
+            Some(reason) => OrcaError::SuspiciousArchive(format!("{} {}", label, reason)),
+            None => OrcaError::IoError(e),
+        })?
+        .map_err(|message| OrcaError::InvalidModel(format!("Invalid {} - {}", label, message)))
+}
+
+/// Validate the model in a ZIP or gzip upload, reporting which file it was.
+///
+/// The model is validated from a scratch copy that is removed afterwards.
+pub fn archive_info(path: &Path) -> Result<ModelInfo, ValidationError> {
+    let packing = Packing::of(path).unwrap_or(Packing::Zip);
+    let invalid = |file_size, message| ModelInfo::invalid(packing.file_type(), file_size, message);
+    if !path.exists() {
+        return Ok(invalid(0, "File not found".to_string()));
+    }
+    let file_size = fs::metadata(path)?.len();
+    let workspace = JobWorkspace::create(None, None)?;
+    let info = match extract_model(path, Path::new(&workspace.model_dir)) {
+        Ok(Ok((entry_name, model))) => model_info(&model).map(|info| ModelInfo {
+            archive_entry: Some(entry_name),
+            ..info
+        }),
+        Ok(Err(message)) => Ok(invalid(
+            file_size,
+            format!("Invalid {} - {}", packing.label(), message),
+        )),
+        Err(e) => Ok(invalid(
+            file_size,
+            format!("Invalid {} - {}", packing.label(), e),
+        )),
+    };
+    workspace.release();
+    info
+}
+
+/// Cap what a ZIP or gzip upload may unpack to, in bytes, and how many times
+/// its compressed size
+///
+/// A ZIP's model is read into memory whole; a gzip upload is streamed to disk.
+/// Either way, more than this and the upload is rejected. The ratio also holds
+/// for the parts of 3MF and compressed AMF files, past their first 8 MB. 0
+/// restores the default of 512 MB, or 100 times.
+#[pyfunction]
+#[pyo3(signature = (max_bytes=0, max_ratio=0))]
+pub fn set_unpack_limit(max_bytes: u64, max_ratio: u64) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        let max_bytes = if max_bytes == 0 {
+            DEFAULT_MAX_UNPACKED_BYTES
+        } else {
+            max_bytes
+        };
+        let max_ratio = if max_ratio == 0 {
+            DEFAULT_MAX_RATIO
+        } else {
+            max_ratio
+        };
+        MAX_UNPACKED_BYTES.store(max_bytes, Ordering::Relaxed);
+        MAX_RATIO.store(max_ratio, Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Take the model out of a ZIP or gzip upload into `dest_dir`
+///
+/// From a ZIP, the first supported model is taken; folders, `__MACOSX`
+/// resource forks and hidden files are passed over, and the archive may hold
+/// at most 1000 entries and no other archive. A gzip upload is decompressed under its name less
+/// `.gz`, e.g. `part.stl.gz` to `part.stl`. Either way the model may unpack to
+/// at most 512 MB, and to at most 100 times its compressed size, unless
+/// `set_unpack_limit` says otherwise. Returns the entry used and the extracted
+/// file's path. Raises ValueError when the upload holds no model that can be
+/// taken out, or looks like a decompression bomb.
+#[pyfunction]
+pub fn extract_archived_model(
+    py: Python<'_>,
+    archive_path: String,
+    dest_dir: String,
+) -> PyResult<(String, String)> {
+    panic_boundary::catch(|| {
+        let (entry_name, model) =
+            py.allow_threads(|| unpack(Path::new(&archive_path), Path::new(&dest_dir)))?;
+        Ok((entry_name, model.to_string_lossy().into_owned()))
+    })
+}
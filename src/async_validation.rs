@@ -0,0 +1,109 @@
+use pyo3::prelude::*;
+use pyo3_asyncio::tokio::future_into_py;
+use std::path::Path;
+
+use crate::mesh_formats::{amf_info, ply_info, three_mf_info};
+use crate::validation_cache::{self, hashed};
+use crate::validation_limits::ValidationLimits;
+use crate::{obj_info, panic_boundary, step_info, stl_info};
+use crate::{ModelInfo, ValidationError};
+
+/// An awaitable running `info` on the blocking pool, so a slow read (such as
+/// from a network-mounted upload directory) does not stall the event loop.
+fn validate_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+    info: fn(&Path) -> Result<ModelInfo, ValidationError>,
+) -> PyResult<&PyAny> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        future_into_py(
+            py,
+            panic_boundary::catch_future(async move {
+                let info = panic_boundary::spawn_blocking(move || {
+                    limits.validate(Path::new(&file_path), info)
+                })
+                .await?;
+                Ok(info?)
+            }),
+        )
+    })
+}
+
+/// `validate_stl`, awaitable
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_stl_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<&PyAny> {
+    validate_async(py, file_path, limits, |path| hashed(path, stl_info))
+}
+
+/// `validate_obj`, awaitable
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_obj_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<&PyAny> {
+    validate_async(py, file_path, limits, |path| hashed(path, obj_info))
+}
+
+/// `validate_step`, awaitable
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_step_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<&PyAny> {
+    validate_async(py, file_path, limits, |path| hashed(path, step_info))
+}
+
+/// `validate_3mf`, awaitable
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_3mf_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<&PyAny> {
+    validate_async(py, file_path, limits, |path| hashed(path, three_mf_info))
+}
+
+/// `validate_amf`, awaitable
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_amf_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<&PyAny> {
+    validate_async(py, file_path, limits, |path| hashed(path, amf_info))
+}
+
+/// `validate_ply`, awaitable
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_ply_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<&PyAny> {
+    validate_async(py, file_path, limits, |path| hashed(path, ply_info))
+}
+
+/// `validate_3d_model`, awaitable; shares its validation cache
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_3d_model_async(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<&PyAny> {
+    validate_async(py, file_path, limits, validation_cache::cached_model_info)
+}
@@ -0,0 +1,42 @@
+use serde_json::{json, Map, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch as a float, the timestamp format used in audit records
+pub fn unix_timestamp(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
+
+/// Append one event to a JSON-lines audit log, creating the file if needed.
+///
+/// Each line is `{"timestamp": <unix secs>, "event": <name>, ...fields}`; object
+/// payloads are flattened into the record, anything else is stored under `data`.
+pub fn append_event(log_path: &Path, event: &str, payload: Value) -> std::io::Result<()> {
+    let mut record = Map::new();
+    record.insert("timestamp".to_string(), json!(unix_timestamp(SystemTime::now())));
+    record.insert("event".to_string(), json!(event));
+    match payload {
+        Value::Object(fields) => record.extend(fields),
+        Value::Null => {}
+        other => {
+            record.insert("data".to_string(), other);
+        }
+    }
+
+    if let Some(parent) = log_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let mut line = Value::Object(record).to_string();
+    line.push('\n');
+
+    // A single write_all on an O_APPEND handle keeps concurrent writers from
+    // interleaving partial lines.
+    let mut file = OpenOptions::new().create(true).append(true).open(log_path)?;
+    file.write_all(line.as_bytes())
+}
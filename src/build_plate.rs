@@ -0,0 +1,81 @@
+use pyo3::prelude::*;
+use std::path::Path;
+
+use crate::geometry::model_dimensions;
+use crate::panic_boundary;
+use crate::profile_compat::load_resolved;
+use crate::OrcaError;
+
+/// Whether a model fits a build volume, allowing a 90° turn on the bed.
+/// An unknown bed size or height is assumed to fit.
+pub(crate) fn fits_build_volume(
+    (x, y, z): (f64, f64, f64),
+    bed_size: Option<(f64, f64)>,
+    max_height: Option<f64>,
+) -> bool {
+    let fits_bed = bed_size
+        .is_none_or(|(width, depth)| (x <= width && y <= depth) || (y <= width && x <= depth));
+    fits_bed && max_height.is_none_or(|height| z <= height)
+}
+
+fn millimetres(values: &[f64]) -> String {
+    let sizes: Vec<String> = values.iter().map(|value| format!("{:.1}", value)).collect();
+    format!("{} mm", sizes.join(" x "))
+}
+
+/// `Err(ModelTooLarge)` when a model of `dimensions` would not fit `machine`'s
+/// build volume.
+pub(crate) fn check_fits(
+    dimensions: (f64, f64, f64),
+    machine: &str,
+    bed_size: Option<(f64, f64)>,
+    max_height: Option<f64>,
+) -> Result<(), OrcaError> {
+    if fits_build_volume(dimensions, bed_size, max_height) {
+        return Ok(());
+    }
+    let build_volume = match (bed_size, max_height) {
+        (Some((width, depth)), Some(height)) => millimetres(&[width, depth, height]),
+        (Some((width, depth)), None) => millimetres(&[width, depth]),
+        (None, height) => format!("{} high", millimetres(&[height.unwrap_or_default()])),
+    };
+    Err(OrcaError::ModelTooLarge {
+        model: millimetres(&[dimensions.0, dimensions.1, dimensions.2]),
+        machine: machine.to_string(),
+        build_volume,
+    })
+}
+
+/// Check a model fits the build volume of a machine profile
+///
+/// The profile is resolved through its `inherits` chain, then the model's
+/// bounding box is compared with `printable_area` and `printable_height`,
+/// allowing a 90° turn on the bed. Returns the model's width, depth and
+/// height in mm, or None for formats that can't be measured (STEP). Raises
+/// ValueError when the model is too large.
+#[pyfunction]
+pub fn check_fits_build_plate(
+    py: Python<'_>,
+    model_path: String,
+    machine_profile_path: String,
+) -> PyResult<Option<(f64, f64, f64)>> {
+    panic_boundary::catch(|| {
+        py.allow_threads(|| {
+            let model = Path::new(&model_path);
+            if !model.exists() {
+                return Err(OrcaError::FileNotFound(model_path.clone()).into());
+            }
+            let machine = load_resolved(Path::new(&machine_profile_path), &[])?;
+            let dimensions = model_dimensions(model)?;
+            if let Some(dimensions) = dimensions {
+                check_fits(
+                    dimensions,
+                    &machine.name,
+                    machine.bed_size,
+                    machine.printable_height,
+                )?;
+            }
+            Ok(dimensions)
+        })
+    })
+}
@@ -0,0 +1,213 @@
+use pyo3::prelude::*;
+use std::time::SystemTime;
+
+use crate::audit::unix_timestamp;
+use crate::ledger::utc_datetime;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// How far ahead a promise is looked for before the calendar is deemed to have
+/// no working hours at all, e.g. every day of the coming years a holiday.
+const MAX_DAYS: u32 = 3660;
+
+/// When the shop works: the hours handling and hand-over can happen in
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct BusinessCalendar {
+    /// Shop time zone, e.g. 8.0 for Singapore.
+    #[pyo3(get)]
+    pub utc_offset_hours: f64,
+    /// Local hour the shop opens, 0-23.
+    #[pyo3(get)]
+    pub open_hour: u32,
+    /// Local hour the shop closes, 1-24.
+    #[pyo3(get)]
+    pub close_hour: u32,
+    /// 0 = Monday to 6 = Sunday.
+    #[pyo3(get)]
+    pub working_days: Vec<u32>,
+    /// Closed dates, as "YYYY-MM-DD" in the shop's time zone.
+    #[pyo3(get)]
+    pub holidays: Vec<String>,
+    /// `holidays` as days since the Unix epoch.
+    holiday_days: Vec<i64>,
+}
+
+#[pymethods]
+impl BusinessCalendar {
+    fn __str__(&self) -> String {
+        format!(
+            "BusinessCalendar(hours={:02}-{:02}, days={:?}, holidays={}, utc_offset={}h)",
+            self.open_hour,
+            self.close_hour,
+            self.working_days,
+            self.holidays.len(),
+            self.utc_offset_hours
+        )
+    }
+}
+
+/// Days since the Unix epoch of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Howard Hinnant's days_from_civil.
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Days since the Unix epoch of a "YYYY-MM-DD" date; `None` unless it is a real date.
+fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let days = days_from_civil(year, month, day);
+    // 2025-02-30 would otherwise quietly become 2025-03-02.
+    let (y, m, d, ..) = utc_datetime(u64::try_from(days).ok()? * 86_400);
+    ((y, m, d) == (year, month, day)).then_some(days)
+}
+
+impl BusinessCalendar {
+    fn seconds_offset(&self) -> f64 {
+        self.utc_offset_hours * 3600.0
+    }
+
+    fn is_working_day(&self, day: i64) -> bool {
+        // The epoch was a Thursday.
+        let weekday = (day + 3).rem_euclid(7) as u32;
+        self.working_days.contains(&weekday) && !self.holiday_days.contains(&day)
+    }
+
+    /// The moment `work_seconds` of working hours have been spent, counting from
+    /// the first working moment at or after `start` (seconds since the Unix
+    /// epoch); `None` when there are no working hours in the next ten years.
+    pub fn promise(&self, start: f64, work_seconds: f64) -> Option<f64> {
+        let offset = self.seconds_offset();
+        let mut local = start + offset;
+        let mut remaining = work_seconds.max(0.0);
+        for _ in 0..MAX_DAYS {
+            let day = (local / 86_400.0).floor();
+            let midnight = day * 86_400.0;
+            if self.is_working_day(day as i64) {
+                let from = local.max(midnight + self.open_hour as f64 * 3600.0);
+                let close = midnight + self.close_hour as f64 * 3600.0;
+                if from < close {
+                    if remaining <= close - from {
+                        return Some(from + remaining - offset);
+                    }
+                    remaining -= close - from;
+                }
+            }
+            local = midnight + 86_400.0;
+        }
+        None
+    }
+
+    /// "YYYY-MM-DD" of a moment, in the shop's time zone.
+    pub fn local_date(&self, at: f64) -> String {
+        let (year, month, day, ..) = utc_datetime((at + self.seconds_offset()).max(0.0) as u64);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+fn invalid(path: &str, message: String) -> OrcaError {
+    OrcaError::InvalidConfig {
+        path: path.to_string(),
+        message,
+    }
+}
+
+/// Working hours, weekdays and holidays for promised completion dates
+///
+/// `working_days` counts 0 for Monday to 6 for Sunday; `holidays` are
+/// "YYYY-MM-DD" dates in the shop's time zone.
+#[pyfunction]
+#[pyo3(signature = (
+    utc_offset_hours=8.0,
+    open_hour=9,
+    close_hour=18,
+    working_days=vec![0, 1, 2, 3, 4],
+    holidays=Vec::new(),
+))]
+pub fn create_business_calendar(
+    utc_offset_hours: f64,
+    open_hour: u32,
+    close_hour: u32,
+    working_days: Vec<u32>,
+    holidays: Vec<String>,
+) -> PyResult<BusinessCalendar> {
+    panic_boundary::catch(|| {
+        if !(-14.0..=14.0).contains(&utc_offset_hours) {
+            return Err(invalid(
+                "utc_offset_hours",
+                format!("{} is not a UTC offset", utc_offset_hours),
+            )
+            .into());
+        }
+        if open_hour >= close_hour || close_hour > 24 {
+            return Err(invalid(
+                "open_hour",
+                format!("{}-{} is not a range of hours", open_hour, close_hour),
+            )
+            .into());
+        }
+        if let Some(day) = working_days.iter().find(|day| **day > 6) {
+            return Err(invalid(
+                "working_days",
+                format!("{} is not a weekday (0 = Monday to 6 = Sunday)", day),
+            )
+            .into());
+        }
+        if working_days.is_empty() {
+            return Err(invalid("working_days", "is empty".to_string()).into());
+        }
+        let holiday_days = holidays
+            .iter()
+            .map(|date| {
+                parse_date(date).ok_or_else(|| {
+                    invalid("holidays", format!("{:?} is not a YYYY-MM-DD date", date))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BusinessCalendar {
+            utc_offset_hours,
+            open_hour,
+            close_hour,
+            working_days,
+            holidays,
+            holiday_days,
+        })
+    })
+}
+
+/// Promise a completion time for a job, in seconds since the Unix epoch
+///
+/// Machine time (`busy_seconds`: queue wait plus print) runs around the clock
+/// from `start` (default now); `handling_seconds` of post-processing and packing
+/// is then done in working hours only, so a job that finishes overnight is
+/// promised during the next working day.
+#[pyfunction]
+#[pyo3(signature = (calendar, busy_seconds, handling_seconds=0.0, start=None))]
+pub fn promised_completion(
+    calendar: PyRef<'_, BusinessCalendar>,
+    busy_seconds: f64,
+    handling_seconds: f64,
+    start: Option<f64>,
+) -> PyResult<f64> {
+    panic_boundary::catch(|| {
+        let start = start.unwrap_or_else(|| unix_timestamp(SystemTime::now()));
+        calendar
+            .promise(start + busy_seconds.max(0.0), handling_seconds)
+            .ok_or_else(|| {
+                invalid(
+                    "business calendar",
+                    "has no working hours in the next ten years".to_string(),
+                )
+                .into()
+            })
+    })
+}
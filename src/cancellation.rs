@@ -0,0 +1,80 @@
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+use crate::panic_boundary;
+use crate::OrcaError;
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// Cancels slicer runs and quote pipelines it is passed to; copies share it
+#[derive(Clone, Default)]
+#[pyclass]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+impl CancellationToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn trigger(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.notify.notify_waiters();
+    }
+
+    /// `Cancelled` naming `what` once the token is cancelled.
+    pub fn check(&self, what: &str) -> Result<(), OrcaError> {
+        if self.is_cancelled() {
+            Err(OrcaError::Cancelled(what.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves once the token is cancelled.
+    pub async fn wait_cancelled(&self) {
+        loop {
+            // Registered before the check, so a cancel in between still wakes it.
+            let notified = self.state.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[pymethods]
+impl CancellationToken {
+    /// Cancel every run holding this token; calling it again does nothing.
+    fn cancel(&self) {
+        self.trigger();
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.is_cancelled()
+    }
+
+    fn __str__(&self) -> String {
+        format!("CancellationToken(cancelled={})", self.is_cancelled())
+    }
+}
+
+/// Create a token for abandoning a slice or quote in progress
+///
+/// Pass it to `execute_slicer_async` or `run_quote_pipeline` and call
+/// `cancel()` from any thread, e.g. when the customer disconnects: the slicer
+/// and anything it started are killed, the job's workspace is removed, and
+/// the call raises `asyncio.CancelledError`.
+#[pyfunction]
+pub fn create_cancellation_token() -> PyResult<CancellationToken> {
+    panic_boundary::catch(|| Ok(CancellationToken::default()))
+}
@@ -0,0 +1,187 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::geometry::{model_triangles, Triangle};
+use crate::panic_boundary;
+use crate::stl_convert::{ensure_distinct, write_binary_stl};
+use crate::OrcaError;
+
+/// Fewer than this and there is no solid left to print.
+pub(crate) const MIN_TARGET: u64 = 4;
+/// Finest grid tried, in cells along the model's longest side.
+const MAX_RESOLUTION: u32 = 1 << 16;
+
+/// How far a mesh was simplified, and where the result was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct Decimation {
+    #[pyo3(get)]
+    pub triangles_before: u64,
+    #[pyo3(get)]
+    pub triangles_after: u64,
+    /// Edge of the grid cells whose corners were merged, in mm; 0 when the
+    /// mesh was already small enough.
+    #[pyo3(get)]
+    pub cell_size_mm: f64,
+    #[pyo3(get)]
+    pub output_path: String,
+}
+
+#[pymethods]
+impl Decimation {
+    fn __str__(&self) -> String {
+        format!(
+            "Decimation(triangles={}->{}, cell={:.3}mm)",
+            self.triangles_before, self.triangles_after, self.cell_size_mm
+        )
+    }
+}
+
+/// Merge every corner inside one cube of a `cell`-sized grid into the cell's
+/// average corner, dropping triangles that collapse and repeats.
+fn cluster(triangles: &[Triangle], origin: [f32; 3], cell: f32) -> Vec<Triangle> {
+    let key = |corner: [f32; 3]| -> [i64; 3] {
+        [0, 1, 2].map(|axis| ((corner[axis] - origin[axis]) / cell).floor() as i64)
+    };
+    let mut sums: HashMap<[i64; 3], ([f64; 3], u32)> = HashMap::new();
+    for corner in triangles.iter().flatten() {
+        let (sum, count) = sums.entry(key(*corner)).or_insert(([0.0; 3], 0));
+        for axis in 0..3 {
+            sum[axis] += corner[axis] as f64;
+        }
+        *count += 1;
+    }
+    let ids: HashMap<[i64; 3], usize> = sums.keys().enumerate().map(|(i, k)| (*k, i)).collect();
+    let mut centers = vec![[0f32; 3]; ids.len()];
+    for (cell_key, (sum, count)) in &sums {
+        centers[ids[cell_key]] = sum.map(|total| (total / *count as f64) as f32);
+    }
+
+    let mut seen: HashSet<[usize; 3]> = HashSet::new();
+    let mut kept = Vec::new();
+    for triangle in triangles {
+        let face = triangle.map(|corner| ids[&key(corner)]);
+        if face[0] == face[1] || face[1] == face[2] || face[0] == face[2] {
+            continue;
+        }
+        let mut sorted = face;
+        sorted.sort_unstable();
+        if seen.insert(sorted) {
+            kept.push(face.map(|id| centers[id]));
+        }
+    }
+    kept
+}
+
+/// Simplify `triangles` to at most `target` by vertex clustering, on the
+/// finest grid that gets there. Returns the triangles and the cell size, or
+/// the input and 0 when it is already small enough or no grid leaves at least
+/// `MIN_TARGET` triangles within target.
+pub fn decimate(triangles: &[Triangle], target: u64) -> (Vec<Triangle>, f64) {
+    if triangles.len() as u64 <= target {
+        return (triangles.to_vec(), 0.0);
+    }
+    let (mut low, mut high) = ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]);
+    for corner in triangles.iter().flatten() {
+        for axis in 0..3 {
+            low[axis] = low[axis].min(corner[axis]);
+            high[axis] = high[axis].max(corner[axis]);
+        }
+    }
+    // A little over the longest side, so one cell holds the whole model.
+    let extent = (0..3)
+        .map(|axis| high[axis] - low[axis])
+        .fold(0f32, f32::max)
+        .max(f32::EPSILON)
+        * 1.001;
+
+    // Coarser grids keep fewer triangles; find the finest one within target.
+    let (mut coarse, mut fine) = (1u32, MAX_RESOLUTION);
+    // A grid too coarse collapses the model to nothing, so the input stands
+    // until one within target leaves a solid.
+    let mut best = triangles.to_vec();
+    let mut best_cell = 0.0;
+    while coarse < fine {
+        let resolution = coarse + (fine - coarse).div_ceil(2);
+        let cell = extent / resolution as f32;
+        let kept = cluster(triangles, low, cell);
+        if kept.len() as u64 <= target {
+            coarse = resolution;
+            if kept.len() as u64 >= MIN_TARGET {
+                best = kept;
+                best_cell = cell;
+            }
+        } else {
+            fine = resolution - 1;
+        }
+    }
+    (best, best_cell as f64)
+}
+
+/// Simplify a model file's mesh into a binary STL at `output`; `None` for
+/// formats without a mesh (STEP).
+pub fn decimate_file(
+    input: &Path,
+    output: &Path,
+    target: u64,
+) -> Result<Option<Decimation>, OrcaError> {
+    if target < MIN_TARGET {
+        return Err(OrcaError::InvalidConfig {
+            path: "target_triangles".to_string(),
+            message: format!("{} is fewer than the {} a solid needs", target, MIN_TARGET),
+        });
+    }
+    if !input.exists() {
+        return Err(OrcaError::FileNotFound(input.display().to_string()));
+    }
+    ensure_distinct(input, output)?;
+    let Some(triangles) = model_triangles(input)? else {
+        return Ok(None);
+    };
+    let (decimated, cell_size_mm) = decimate(&triangles, target);
+    write_binary_stl(output, &decimated)?;
+    Ok(Some(Decimation {
+        triangles_before: triangles.len() as u64,
+        triangles_after: decimated.len() as u64,
+        cell_size_mm,
+        output_path: output.to_string_lossy().into_owned(),
+    }))
+}
+
+/// `<stem>.decimated.stl` beside the model.
+fn default_output(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map_or("model".into(), |stem| stem.to_string_lossy());
+    path.with_file_name(format!("{}.decimated.stl", stem))
+}
+
+/// Simplify a mesh to at most `target_triangles` triangles
+///
+/// Corners are merged on the finest grid that brings the count within
+/// target, so detail is lost evenly across the model and its size is kept.
+/// Meant for 3D scans with millions of faces, which the slicer can take
+/// longer to load than to slice. The result is written as a binary STL to
+/// `output_path`, by default `<stem>.decimated.stl` beside the model; a mesh
+/// already within target, or one that no grid brings within it without
+/// collapsing, is written unchanged. Raises ValueError for formats without a
+/// mesh (STEP), targets under 4 and an `output_path` that is `path`.
+#[pyfunction]
+#[pyo3(signature = (path, target_triangles, output_path=None))]
+pub fn decimate_mesh(
+    py: Python<'_>,
+    path: String,
+    target_triangles: u64,
+    output_path: Option<String>,
+) -> PyResult<Decimation> {
+    panic_boundary::catch(|| {
+        let input = PathBuf::from(&path);
+        let output = output_path.map_or_else(|| default_output(&input), PathBuf::from);
+        let decimation = py.allow_threads(|| decimate_file(&input, &output, target_triangles))?;
+        decimation.ok_or_else(|| {
+            OrcaError::InvalidModel(format!("{} has no mesh to decimate", path)).into()
+        })
+    })
+}
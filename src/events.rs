@@ -0,0 +1,499 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::audit::unix_timestamp;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Event types consumers can subscribe to.
+pub const EVENT_TYPES: [&str; 11] = [
+    "quote.created",
+    "quote.failed",
+    "printer.assigned",
+    "notification.sent",
+    "job.sent",
+    "job.failed",
+    "payment.succeeded",
+    "payment.failed",
+    "quote.approved",
+    "quote.rejected",
+    "quote.requoted",
+];
+
+/// Something that happened to a quote, as delivered to every sink
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct QuoteEvent {
+    /// One of `EVENT_TYPES`, e.g. "quote.created" or "job.sent".
+    #[pyo3(get)]
+    pub event_type: String,
+    #[pyo3(get)]
+    pub quote_id: Option<String>,
+    /// Seconds since the Unix epoch.
+    #[pyo3(get)]
+    pub timestamp: f64,
+    data: Value,
+}
+
+impl QuoteEvent {
+    fn body(&self) -> Value {
+        json!({
+            "type": self.event_type,
+            "quote_id": self.quote_id,
+            "timestamp": self.timestamp,
+            "data": self.data,
+        })
+    }
+}
+
+#[pymethods]
+impl QuoteEvent {
+    /// Event-specific fields, e.g. the total of a created quote.
+    #[getter]
+    fn data(&self, py: Python<'_>) -> PyResult<PyObject> {
+        py.import("json")?
+            .call_method1("loads", (self.data.to_string(),))
+            .map(Into::into)
+    }
+
+    /// The JSON document webhooks and MQTT subscribers receive.
+    fn to_json(&self) -> String {
+        self.body().to_string()
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "QuoteEvent(type={}, quote_id={:?})",
+            self.event_type, self.quote_id
+        )
+    }
+}
+
+struct MqttSink {
+    host: String,
+    port: u16,
+    topic_prefix: String,
+    /// Topic templates by event type; an empty template drops that type.
+    topics: HashMap<String, String>,
+    retain: bool,
+    client_id: String,
+    username: Option<String>,
+    password: Option<String>,
+    timeout: Duration,
+}
+
+enum Sink {
+    Webhook {
+        url: String,
+        secret: Option<String>,
+        timeout: Duration,
+    },
+    Mqtt(MqttSink),
+    Callback(PyObject),
+}
+
+// Written by hand so secrets never end up in a log line.
+impl fmt::Debug for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sink::Webhook { url, .. } => write!(f, "webhook {}", url),
+            Sink::Mqtt(mqtt) => write!(f, "mqtt {}:{}/{}", mqtt.host, mqtt.port, mqtt.topic_prefix),
+            Sink::Callback(_) => write!(f, "callback"),
+        }
+    }
+}
+
+const DEFAULT_TOPIC: &str = "{prefix}/{type}";
+
+static SINKS: Lazy<Mutex<Vec<Arc<Sink>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Deliveries still running on background threads, for `flush_events`.
+static IN_FLIGHT: Lazy<(Mutex<usize>, Condvar)> = Lazy::new(|| (Mutex::new(0), Condvar::new()));
+
+fn add_sink(sink: Sink) {
+    SINKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(Arc::new(sink));
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.iter().map(|b| b ^ byte).collect::<Vec<u8>>();
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize()
+        .to_vec()
+}
+
+fn post_webhook(
+    url: &str,
+    secret: Option<&str>,
+    timeout: Duration,
+    event: &QuoteEvent,
+) -> Result<(), OrcaError> {
+    let body = event.to_json();
+    let mut request = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .build()
+        .post(url)
+        .set("Content-Type", "application/json")
+        .set("X-Orca-Event", &event.event_type);
+    if let Some(secret) = secret {
+        let signature: String = hmac_sha256(secret.as_bytes(), body.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        request = request.set("X-Orca-Signature", &format!("sha256={}", signature));
+    }
+    request
+        .send_string(&body)
+        .map(|_| ())
+        .map_err(|e| OrcaError::EventDeliveryFailed(format!("{}: {}", url, e)))
+}
+
+fn mqtt_string(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// MQTT control packet: type byte, variable-length remaining length, body.
+fn mqtt_packet(kind: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![kind];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Placeholder values must not add topic levels or wildcards.
+fn topic_level(value: &str) -> String {
+    value.replace(['/', '+', '#'], "_")
+}
+
+impl MqttSink {
+    /// Topic for an event, or `None` when its template is empty.
+    fn topic(&self, event: &QuoteEvent) -> Option<String> {
+        let prefix = self.topic_prefix.trim_end_matches('/');
+        let template = match self.topics.get(&event.event_type) {
+            Some(template) if template.is_empty() => return None,
+            Some(template) => template.as_str(),
+            None => DEFAULT_TOPIC,
+        };
+        let printer = event.data.get("printer").and_then(Value::as_str);
+        Some(
+            template
+                .replace("{prefix}", prefix)
+                .replace("{type}", &event.event_type)
+                .replace(
+                    "{quote_id}",
+                    &topic_level(event.quote_id.as_deref().unwrap_or("none")),
+                )
+                .replace("{printer}", &topic_level(printer.unwrap_or("unassigned"))),
+        )
+    }
+}
+
+/// Publish at QoS 0 over a short-lived MQTT 3.1.1 connection.
+fn publish_mqtt(mqtt: &MqttSink, event: &QuoteEvent) -> Result<(), OrcaError> {
+    let Some(topic) = mqtt.topic(event) else {
+        return Ok(());
+    };
+    let failed = |e: &dyn fmt::Display| {
+        OrcaError::EventDeliveryFailed(format!("mqtt://{}:{}: {}", mqtt.host, mqtt.port, e))
+    };
+    let address = (mqtt.host.as_str(), mqtt.port)
+        .to_socket_addrs()
+        .map_err(|e| failed(&e))?
+        .next()
+        .ok_or_else(|| failed(&"host not found"))?;
+    let mut stream = TcpStream::connect_timeout(&address, mqtt.timeout).map_err(|e| failed(&e))?;
+    stream.set_read_timeout(Some(mqtt.timeout))?;
+    stream.set_write_timeout(Some(mqtt.timeout))?;
+
+    let mut connect = Vec::new();
+    mqtt_string(&mut connect, "MQTT");
+    connect.push(4); // protocol level 3.1.1
+    let mut flags = 0x02; // clean session
+    if mqtt.username.is_some() {
+        flags |= 0x80;
+    }
+    if mqtt.password.is_some() {
+        flags |= 0x40;
+    }
+    connect.push(flags);
+    connect.extend_from_slice(&60u16.to_be_bytes()); // keep-alive
+    mqtt_string(&mut connect, &mqtt.client_id);
+    if let Some(username) = &mqtt.username {
+        mqtt_string(&mut connect, username);
+    }
+    if let Some(password) = &mqtt.password {
+        mqtt_string(&mut connect, password);
+    }
+    stream.write_all(&mqtt_packet(0x10, &connect))?;
+
+    let mut connack = [0u8; 4];
+    stream.read_exact(&mut connack)?;
+    if connack[0] != 0x20 || connack[3] != 0 {
+        return Err(failed(&format!("connection refused (code {})", connack[3])));
+    }
+
+    let mut publish = Vec::new();
+    mqtt_string(&mut publish, &topic);
+    publish.extend_from_slice(event.to_json().as_bytes());
+    let retain = if mqtt.retain { 0x01 } else { 0x00 };
+    stream.write_all(&mqtt_packet(0x30 | retain, &publish))?;
+    stream.write_all(&mqtt_packet(0xE0, &[]))?;
+    Ok(())
+}
+
+/// Deliver on a background thread so a slow consumer never holds up a quote.
+fn deliver_in_background(sink: Arc<Sink>, event: QuoteEvent) {
+    let (count, _) = &*IN_FLIGHT;
+    *count.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+    thread::spawn(move || {
+        let result = panic_boundary::catch_panic(|| match &*sink {
+            Sink::Webhook {
+                url,
+                secret,
+                timeout,
+            } => post_webhook(url, secret.as_deref(), *timeout, &event),
+            Sink::Mqtt(mqtt) => publish_mqtt(mqtt, &event),
+            Sink::Callback(_) => Ok(()),
+        });
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!(sink = ?sink, error = %e, "event not delivered"),
+            Err(panic) => {
+                tracing::warn!(sink = ?sink, error = %panic.message, "event not delivered")
+            }
+        }
+        let (count, done) = &*IN_FLIGHT;
+        *count.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+        done.notify_all();
+    });
+}
+
+/// Send an event to every sink. Callbacks run now, on the calling thread; a
+/// failing sink is logged and never reaches the caller.
+pub fn emit(event_type: &str, quote_id: Option<&str>, data: Value) -> QuoteEvent {
+    let event = QuoteEvent {
+        event_type: event_type.to_string(),
+        quote_id: quote_id.map(str::to_string),
+        timestamp: unix_timestamp(SystemTime::now()),
+        data,
+    };
+    // Deliver outside the lock so a callback may emit or add sinks itself.
+    let sinks = SINKS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    for sink in sinks {
+        if let Sink::Callback(callback) = &*sink {
+            Python::with_gil(|py| {
+                if let Err(e) = callback.call1(py, (event.clone(),)) {
+                    tracing::warn!(error = %e, event = event_type, "event callback failed");
+                }
+            });
+        } else {
+            deliver_in_background(sink, event.clone());
+        }
+    }
+    event
+}
+
+fn check_url(url: &str) -> Result<(), OrcaError> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(OrcaError::InvalidConfig {
+            path: url.to_string(),
+            message: "webhook URL must start with http:// or https://".to_string(),
+        })
+    }
+}
+
+/// POST every event as JSON to `url`
+///
+/// With a `secret`, each request carries `X-Orca-Signature: sha256=<hex>`, the
+/// HMAC-SHA256 of the body, so the receiver can check where it came from.
+#[pyfunction]
+#[pyo3(signature = (url, secret=None, timeout_secs=5.0))]
+pub fn add_webhook_sink(url: String, secret: Option<String>, timeout_secs: f64) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        check_url(&url)?;
+        add_sink(Sink::Webhook {
+            url,
+            secret: secret.filter(|s| !s.is_empty()),
+            timeout: Duration::from_secs_f64(timeout_secs.max(0.0)),
+        });
+        Ok(())
+    })
+}
+
+fn check_topics(topics: &HashMap<String, String>) -> Result<(), OrcaError> {
+    for (event_type, template) in topics {
+        let message = if !EVENT_TYPES.contains(&event_type.as_str()) {
+            format!(
+                "unknown event type {:?} (expected one of {:?})",
+                event_type, EVENT_TYPES
+            )
+        } else if template.contains(['+', '#']) {
+            format!("topic {:?} contains an MQTT wildcard", template)
+        } else {
+            continue;
+        };
+        return Err(OrcaError::InvalidConfig {
+            path: "mqtt topics".to_string(),
+            message,
+        });
+    }
+    Ok(())
+}
+
+/// Publish every event to an MQTT broker (QoS 0), by default to `<topic_prefix>/<event type>`
+///
+/// `topics` maps event types to topic templates for existing dashboards, e.g.
+/// `{"printer.assigned": "farm/{printer}/quotes"}`. Templates may use `{prefix}`,
+/// `{type}`, `{quote_id}` and `{printer}`; an empty template stops that type being
+/// published. With `retain`, the broker keeps the last message on each topic.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (host, port=1883, topic_prefix="orca-quote".to_string(), topics=None, retain=false, client_id=None, username=None, password=None, timeout_secs=5.0))]
+pub fn add_mqtt_sink(
+    host: String,
+    port: u16,
+    topic_prefix: String,
+    topics: Option<HashMap<String, String>>,
+    retain: bool,
+    client_id: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    timeout_secs: f64,
+) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        let topics = topics.unwrap_or_default();
+        check_topics(&topics)?;
+        add_sink(Sink::Mqtt(MqttSink {
+            host,
+            port,
+            topic_prefix,
+            topics,
+            retain,
+            client_id: client_id.unwrap_or_else(|| format!("orca-quote-{}", std::process::id())),
+            username: username.filter(|u| !u.is_empty()),
+            password: password.filter(|p| !p.is_empty()),
+            timeout: Duration::from_secs_f64(timeout_secs.max(0.0)),
+        }));
+        Ok(())
+    })
+}
+
+/// Call `callback(event)` in this process for every event
+#[pyfunction]
+pub fn add_event_callback(py: Python<'_>, callback: PyObject) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        if !callback.as_ref(py).is_callable() {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "callback is not callable",
+            ));
+        }
+        add_sink(Sink::Callback(callback));
+        Ok(())
+    })
+}
+
+/// Remove all event sinks
+#[pyfunction]
+pub fn clear_event_sinks() -> PyResult<()> {
+    panic_boundary::catch(|| {
+        SINKS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        Ok(())
+    })
+}
+
+/// Emit a pipeline event to every sink; returns the event sent
+#[pyfunction]
+#[pyo3(signature = (event_type, quote_id=None, data=None))]
+pub fn emit_event(
+    py: Python<'_>,
+    event_type: String,
+    quote_id: Option<String>,
+    data: Option<&PyDict>,
+) -> PyResult<QuoteEvent> {
+    panic_boundary::catch(|| {
+        if !EVENT_TYPES.contains(&event_type.as_str()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "events".to_string(),
+                message: format!(
+                    "unknown event type {:?} (expected one of {:?})",
+                    event_type, EVENT_TYPES
+                ),
+            }
+            .into());
+        }
+        let data = match data {
+            Some(data) => {
+                let text: String = py
+                    .import("json")?
+                    .call_method1("dumps", (data,))?
+                    .extract()?;
+                serde_json::from_str(&text)
+                    .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?
+            }
+            None => json!({}),
+        };
+        Ok(emit(&event_type, quote_id.as_deref(), data))
+    })
+}
+
+/// Wait for background deliveries to finish; False if some are still running
+#[pyfunction]
+#[pyo3(signature = (timeout_secs=5.0))]
+pub fn flush_events(py: Python<'_>, timeout_secs: f64) -> PyResult<bool> {
+    panic_boundary::catch(|| {
+        let deadline = Instant::now() + Duration::from_secs_f64(timeout_secs.max(0.0));
+        Ok(py.allow_threads(|| {
+            let (count, done) = &*IN_FLIGHT;
+            let mut pending = count.lock().unwrap_or_else(|e| e.into_inner());
+            while *pending > 0 {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    return false;
+                }
+                pending = done
+                    .wait_timeout(pending, left)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0;
+            }
+            true
+        }))
+    })
+}
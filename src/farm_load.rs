@@ -0,0 +1,492 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::audit::unix_timestamp;
+use crate::business_calendar::BusinessCalendar;
+use crate::fleet::{Fleet, FleetPrinter};
+use crate::geometry::model_dimensions;
+use crate::http_upload::{read_json, request_error};
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// What a fleet printer is doing right now, as reported by its host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PrinterStatus {
+    #[pyo3(get)]
+    pub printer: String,
+    /// "idle", "printing", "paused", "error", "offline" or "unknown" (not monitored,
+    /// assumed idle).
+    #[pyo3(get)]
+    pub state: String,
+    /// Time left on the current job, when the host reports progress.
+    #[pyo3(get)]
+    pub remaining_seconds: Option<f64>,
+    /// Jobs waiting in the host's queue (Moonraker job queue or a custom provider).
+    #[pyo3(get)]
+    pub queued_jobs: u64,
+    /// Seconds until the printer could start a new job; `None` when it cannot take work.
+    #[pyo3(get)]
+    pub available_in_seconds: Option<f64>,
+}
+
+#[pymethods]
+impl PrinterStatus {
+    fn __str__(&self) -> String {
+        format!(
+            "PrinterStatus(printer={}, state={}, available_in={:?}s)",
+            self.printer, self.state, self.available_in_seconds
+        )
+    }
+}
+
+/// When a quoted job is likely to be ready, given what the farm is doing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct LeadTime {
+    /// Capable printer expected to free up first.
+    #[pyo3(get)]
+    pub printer: String,
+    #[pyo3(get)]
+    pub wait_seconds: f64,
+    #[pyo3(get)]
+    pub print_seconds: f64,
+    /// Post-processing and packing time added to every job.
+    #[pyo3(get)]
+    pub handling_seconds: f64,
+    #[pyo3(get)]
+    pub lead_time_seconds: f64,
+    /// Seconds since the Unix epoch.
+    #[pyo3(get)]
+    pub ready_at: f64,
+    /// When the customer is promised the job: handling done in working hours
+    /// after the print, by the monitor's business calendar; `None` without one.
+    #[pyo3(get)]
+    pub promised_at: Option<f64>,
+    /// `promised_at` as "YYYY-MM-DD" in the shop's time zone.
+    #[pyo3(get)]
+    pub promised_date: Option<String>,
+    /// Status of every printer that could run the job.
+    #[pyo3(get)]
+    pub printers: Vec<PrinterStatus>,
+}
+
+#[pymethods]
+impl LeadTime {
+    fn __str__(&self) -> String {
+        format!(
+            "LeadTime(printer={}, lead_time={:.1}h)",
+            self.printer,
+            self.lead_time_seconds / 3600.0
+        )
+    }
+}
+
+/// Fleet printers polled for their current load, and how lead times are worked out
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct FarmMonitor {
+    #[pyo3(get)]
+    pub fleet: Fleet,
+    /// Called with a `FleetPrinter`; returns a status dict, or None to poll the host.
+    provider: Option<PyObject>,
+    /// How long a polled status is reused before the host is asked again.
+    #[pyo3(get)]
+    pub max_age_secs: f64,
+    #[pyo3(get)]
+    pub timeout_secs: f64,
+    /// Assumed length of a queued job, and of a running one without progress.
+    #[pyo3(get)]
+    pub queued_job_hours: f64,
+    #[pyo3(get)]
+    pub handling_hours: f64,
+    /// Working hours and holidays for promised dates; `None` promises nothing.
+    #[pyo3(get)]
+    pub calendar: Option<BusinessCalendar>,
+}
+
+#[pymethods]
+impl FarmMonitor {
+    fn __str__(&self) -> String {
+        let monitored = self
+            .fleet
+            .printers
+            .iter()
+            .filter(|p| p.status_url.is_some())
+            .count();
+        format!(
+            "FarmMonitor(printers={}, monitored={}, handling={}h)",
+            self.fleet.printers.len(),
+            monitored,
+            self.handling_hours
+        )
+    }
+}
+
+/// Polled statuses by printer name.
+static STATUS_CACHE: Lazy<Mutex<HashMap<String, (Instant, PrinterStatus)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// What the printer's host reported: state, time left and queued jobs.
+type Report = (&'static str, Option<f64>, u64);
+
+fn moonraker_state(state: &str) -> &'static str {
+    match state {
+        "printing" => "printing",
+        "paused" => "paused",
+        "error" => "error",
+        // standby, complete and cancelled all leave the bed free
+        _ => "idle",
+    }
+}
+
+fn octoprint_state(state: &str) -> &'static str {
+    match state {
+        "Operational" => "idle",
+        "Paused" | "Pausing" => "paused",
+        "Printing" | "Starting" | "Resuming" | "Finishing" | "Cancelling" => "printing",
+        s if s.contains("Error") => "error",
+        _ => "offline",
+    }
+}
+
+fn get_json(
+    agent: &ureq::Agent,
+    url: &str,
+    api_key: Option<&str>,
+) -> Result<Option<Value>, OrcaError> {
+    let mut request = agent.get(url);
+    if let Some(key) = api_key {
+        request = request.set("X-Api-Key", key);
+    }
+    match request.call() {
+        Ok(response) => read_json(url, response).map(Some),
+        Err(ureq::Error::Status(404, _)) => Ok(None),
+        Err(e) => Err(request_error(url, e)),
+    }
+}
+
+fn poll_moonraker(
+    agent: &ureq::Agent,
+    base: &str,
+    api_key: Option<&str>,
+) -> Result<Report, OrcaError> {
+    let url = format!(
+        "{}/printer/objects/query?print_stats=state,print_duration&virtual_sdcard=progress",
+        base
+    );
+    let body = get_json(agent, &url, api_key)?
+        .ok_or_else(|| OrcaError::UploadFailed(format!("{}: HTTP 404", url)))?;
+    let stats = |field: &str| body.pointer(&format!("/result/status/{}", field)).cloned();
+    let state = moonraker_state(
+        stats("print_stats/state")
+            .as_ref()
+            .and_then(Value::as_str)
+            .unwrap_or(""),
+    );
+    let elapsed = stats("print_stats/print_duration").and_then(|v| v.as_f64());
+    let progress = stats("virtual_sdcard/progress").and_then(|v| v.as_f64());
+    let remaining = match (state, elapsed, progress) {
+        ("printing" | "paused", Some(elapsed), Some(progress)) if progress > 0.0 => {
+            Some(elapsed * (1.0 - progress.min(1.0)) / progress)
+        }
+        _ => None,
+    };
+    // The job queue component is optional; without it nothing is queued.
+    let queue_url = format!("{}/server/job_queue/status", base);
+    let queued = get_json(agent, &queue_url, api_key)?
+        .and_then(|body| {
+            body.pointer("/result/queued_jobs")
+                .and_then(Value::as_array)
+                .map(Vec::len)
+        })
+        .unwrap_or(0);
+    Ok((state, remaining, queued as u64))
+}
+
+fn poll_octoprint(
+    agent: &ureq::Agent,
+    base: &str,
+    api_key: Option<&str>,
+) -> Result<Report, OrcaError> {
+    let url = format!("{}/api/job", base);
+    let body = get_json(agent, &url, api_key)?
+        .ok_or_else(|| OrcaError::UploadFailed(format!("{}: HTTP 404", url)))?;
+    let state = octoprint_state(body.get("state").and_then(Value::as_str).unwrap_or(""));
+    let remaining = body
+        .pointer("/progress/printTimeLeft")
+        .and_then(Value::as_f64)
+        .filter(|_| matches!(state, "printing" | "paused"));
+    Ok((state, remaining, 0))
+}
+
+fn poll_host(printer: &FleetPrinter, timeout: Duration) -> Result<Option<Report>, OrcaError> {
+    let (Some(provider), Some(url)) = (&printer.status_provider, &printer.status_url) else {
+        return Ok(None);
+    };
+    let api_key = printer
+        .api_key_env
+        .as_deref()
+        .and_then(|name| env::var(name).ok())
+        .filter(|key| !key.is_empty());
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(timeout)
+        .timeout_read(timeout)
+        .build();
+    let base = url.trim_end_matches('/');
+    match provider.as_str() {
+        "octoprint" => poll_octoprint(&agent, base, api_key.as_deref()).map(Some),
+        _ => poll_moonraker(&agent, base, api_key.as_deref()).map(Some),
+    }
+}
+
+fn call_provider(provider: &PyObject, printer: &FleetPrinter) -> Result<Option<Report>, OrcaError> {
+    Python::with_gil(|py| {
+        let answer = provider.call1(py, (printer.clone(),))?;
+        let Ok(answer) = answer.downcast::<PyDict>(py) else {
+            return Ok(None);
+        };
+        let state: String = match answer.get_item("state")? {
+            Some(state) => state.extract()?,
+            None => "unknown".to_string(),
+        };
+        let state = ["idle", "printing", "paused", "error", "offline", "unknown"]
+            .into_iter()
+            .find(|known| *known == state)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!("unknown state {:?}", state))
+            })?;
+        let remaining: Option<f64> = match answer.get_item("remaining_seconds")? {
+            Some(value) => value.extract()?,
+            None => None,
+        };
+        let queued: u64 = match answer.get_item("queued_jobs")? {
+            Some(value) => value.extract()?,
+            None => 0,
+        };
+        Ok::<_, PyErr>(Some((state, remaining, queued)))
+    })
+    .map_err(|e| OrcaError::InvalidConfig {
+        path: format!("status provider for {}", printer.name),
+        message: e.to_string(),
+    })
+}
+
+fn status_of(monitor: &FarmMonitor, printer: &FleetPrinter) -> PrinterStatus {
+    let job_seconds = monitor.queued_job_hours.max(0.0) * 3600.0;
+    let timeout = Duration::from_secs_f64(monitor.timeout_secs.max(0.0));
+    let report = match &monitor.provider {
+        Some(provider) => match call_provider(provider, printer) {
+            Ok(None) => poll_host(printer, timeout),
+            other => other,
+        },
+        None => poll_host(printer, timeout),
+    };
+    let (state, remaining, queued_jobs) = match report {
+        Ok(Some(report)) => report,
+        Ok(None) => ("unknown", None, 0),
+        Err(e) => {
+            tracing::warn!(printer = %printer.name, error = %e, "printer status unavailable");
+            ("offline", None, 0)
+        }
+    };
+    let current = match state {
+        "idle" | "unknown" => Some(0.0),
+        "printing" | "paused" => Some(remaining.unwrap_or(job_seconds)),
+        _ => None,
+    };
+    PrinterStatus {
+        printer: printer.name.clone(),
+        state: state.to_string(),
+        remaining_seconds: remaining,
+        queued_jobs,
+        available_in_seconds: current.map(|seconds| seconds + queued_jobs as f64 * job_seconds),
+    }
+}
+
+fn cached_status(monitor: &FarmMonitor, printer: &FleetPrinter) -> PrinterStatus {
+    let max_age = Duration::from_secs_f64(monitor.max_age_secs.max(0.0));
+    if let Some((polled, status)) = STATUS_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&printer.name)
+    {
+        if polled.elapsed() < max_age {
+            return status.clone();
+        }
+    }
+    let status = status_of(monitor, printer);
+    STATUS_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(printer.name.clone(), (Instant::now(), status.clone()));
+    status
+}
+
+/// Statuses of `printers`, polled in parallel so one slow host costs one timeout.
+fn statuses(monitor: &FarmMonitor, printers: &[&FleetPrinter]) -> Vec<PrinterStatus> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = printers
+            .iter()
+            .map(|printer| scope.spawn(move || cached_status(monitor, printer)))
+            .collect();
+        handles
+            .into_iter()
+            .zip(printers)
+            .map(|(handle, printer)| {
+                handle.join().unwrap_or_else(|_| PrinterStatus {
+                    printer: printer.name.clone(),
+                    state: "offline".to_string(),
+                    remaining_seconds: None,
+                    queued_jobs: 0,
+                    available_in_seconds: None,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Lead time on the capable printer that frees up first; `None` when none of
+/// them can take work.
+pub fn lead_time(
+    monitor: &FarmMonitor,
+    print_seconds: f64,
+    material: Option<&str>,
+    dimensions: Option<(f64, f64, f64)>,
+) -> Option<LeadTime> {
+    let capable: Vec<&FleetPrinter> = match material {
+        Some(material) => monitor.fleet.candidates(material, dimensions).collect(),
+        None => monitor
+            .fleet
+            .printers
+            .iter()
+            .filter(|p| dimensions.is_none_or(|dims| p.fits(dims)))
+            .collect(),
+    };
+    let printers = statuses(monitor, &capable);
+    let (printer, wait_seconds) = printers
+        .iter()
+        .filter_map(|s| Some((s.printer.clone(), s.available_in_seconds?)))
+        .fold(
+            None,
+            |best: Option<(String, f64)>, (name, wait)| match best {
+                Some((_, best_wait)) if best_wait <= wait => best,
+                _ => Some((name, wait)),
+            },
+        )?;
+    let handling_seconds = monitor.handling_hours.max(0.0) * 3600.0;
+    let lead_time_seconds = wait_seconds + print_seconds + handling_seconds;
+    let now = unix_timestamp(SystemTime::now());
+    // Printers run around the clock; handling waits for the shop to be open.
+    let promised_at = monitor.calendar.as_ref().and_then(|calendar| {
+        calendar.promise(now + wait_seconds + print_seconds, handling_seconds)
+    });
+    Some(LeadTime {
+        printer,
+        wait_seconds,
+        print_seconds,
+        handling_seconds,
+        lead_time_seconds,
+        ready_at: now + lead_time_seconds,
+        promised_date: monitor
+            .calendar
+            .as_ref()
+            .zip(promised_at)
+            .map(|(calendar, at)| calendar.local_date(at)),
+        promised_at,
+        printers,
+    })
+}
+
+/// Watch a fleet's printers for lead-time estimates
+///
+/// Printers with `moonraker_url` or `octoprint_url` in the fleet file are polled;
+/// `provider(printer)` may instead return `{"state", "remaining_seconds",
+/// "queued_jobs"}` for other hosts, or None to fall back to polling. With a
+/// `calendar`, lead times also carry a promised completion date.
+#[pyfunction]
+#[pyo3(signature = (fleet, provider=None, max_age_secs=30.0, timeout_secs=5.0, queued_job_hours=2.0, handling_hours=0.0, calendar=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn create_farm_monitor(
+    py: Python<'_>,
+    fleet: Fleet,
+    provider: Option<PyObject>,
+    max_age_secs: f64,
+    timeout_secs: f64,
+    queued_job_hours: f64,
+    handling_hours: f64,
+    calendar: Option<BusinessCalendar>,
+) -> PyResult<FarmMonitor> {
+    panic_boundary::catch(|| {
+        if let Some(provider) = &provider {
+            if !provider.as_ref(py).is_callable() {
+                return Err(pyo3::exceptions::PyTypeError::new_err(
+                    "provider is not callable",
+                ));
+            }
+        }
+        Ok(FarmMonitor {
+            fleet,
+            provider,
+            max_age_secs,
+            timeout_secs,
+            queued_job_hours,
+            handling_hours,
+            calendar,
+        })
+    })
+}
+
+/// Current status of every printer in the monitored fleet
+#[pyfunction]
+pub fn farm_status(
+    py: Python<'_>,
+    monitor: PyRef<'_, FarmMonitor>,
+) -> PyResult<Vec<PrinterStatus>> {
+    panic_boundary::catch(|| {
+        let monitor = monitor.clone();
+        Ok(py.allow_threads(|| {
+            let printers: Vec<&FleetPrinter> = monitor.fleet.printers.iter().collect();
+            statuses(&monitor, &printers)
+        }))
+    })
+}
+
+/// Estimate when a job of `print_minutes` would be ready, given the farm's current load
+///
+/// Only printers that support `material` and fit the model at `model_path` are
+/// considered. Returns None when none of them can take work.
+#[pyfunction]
+#[pyo3(signature = (monitor, print_minutes, material=None, model_path=None))]
+pub fn estimate_lead_time(
+    py: Python<'_>,
+    monitor: PyRef<'_, FarmMonitor>,
+    print_minutes: f64,
+    material: Option<String>,
+    model_path: Option<String>,
+) -> PyResult<Option<LeadTime>> {
+    panic_boundary::catch(|| {
+        let monitor = monitor.clone();
+        Ok(py.allow_threads(|| {
+            let dimensions = match model_path {
+                Some(path) => model_dimensions(Path::new(&path))?,
+                None => None,
+            };
+            Ok::<_, OrcaError>(lead_time(
+                &monitor,
+                print_minutes.max(0.0) * 60.0,
+                material.as_deref(),
+                dimensions,
+            ))
+        })?)
+    })
+}
@@ -0,0 +1,184 @@
+use memchr::memmem;
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::panic_boundary;
+use crate::stream_validation::binary_stl_size;
+use crate::ModelInfo;
+
+/// Bytes from the start of a file its type is told from.
+pub(crate) const SNIFF_BYTES: usize = 512;
+
+/// Signatures at the very start of a file.
+const MAGIC: &[(&[u8], &str)] = &[
+    (b"\x1f\x8b", "gzip"),
+    (b"MZ", "exe"),
+    (b"\x7fELF", "elf"),
+    (b"%PDF-", "pdf"),
+    (b"ply\n", "ply"),
+    (b"ply\r\n", "ply"),
+];
+
+/// Words an OBJ line can start with.
+const OBJ_KEYWORDS: &[&str] = &[
+    "v", "vt", "vn", "vp", "f", "l", "o", "g", "s", "mtllib", "usemtl",
+];
+
+/// The type the start of a file looks like, or "unknown".
+///
+/// `complete` is whether `head` is the whole file; when it is not, its last
+/// line may be cut short and is not looked at. Binary STLs have no signature
+/// and are "unknown" here.
+fn sniff(head: &[u8], complete: bool) -> &'static str {
+    if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+        // 3MF parts live under 3D/, named in the entries' headers.
+        return if memmem::find(head, b"3D/").is_some() {
+            "3mf"
+        } else {
+            "zip"
+        };
+    }
+    if let Some((_, kind)) = MAGIC.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return kind;
+    }
+    // The rest are text formats; binary STLs have zeros in their counts.
+    if head.contains(&0) {
+        return "unknown";
+    }
+    let text = String::from_utf8_lossy(head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head));
+    let text = text.trim_start();
+    if text.starts_with("ISO-10303-21") {
+        return "step";
+    }
+    if text.split_whitespace().next() == Some("solid") {
+        return "stl";
+    }
+    if text.starts_with('<') {
+        let lower = text.to_ascii_lowercase();
+        return if lower.starts_with("<!doctype html") || lower.contains("<html") {
+            "html"
+        } else if lower.contains("<amf") {
+            "amf"
+        } else {
+            "xml"
+        };
+    }
+    let whole_lines = if complete {
+        text
+    } else {
+        text.rsplit_once('\n').map_or("", |(whole, _)| whole)
+    };
+    // The first line that is not an OBJ comment tells OBJ from G-code.
+    for line in whole_lines.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let word = line.split_whitespace().next().unwrap_or_default();
+        if line.starts_with(';') || is_gcode_command(word) {
+            return "gcode";
+        }
+        if OBJ_KEYWORDS.contains(&word) {
+            return "obj";
+        }
+        break;
+    }
+    "unknown"
+}
+
+/// `G1`, `M104`, `T0` and the like.
+fn is_gcode_command(word: &str) -> bool {
+    let mut chars = word.chars();
+    matches!(chars.next(), Some('G' | 'M' | 'T'))
+        && !chars.as_str().is_empty()
+        && chars.all(|c| c.is_ascii_digit())
+}
+
+fn describe(kind: &str) -> &'static str {
+    match kind {
+        "stl" => "an STL model",
+        "obj" => "an OBJ model",
+        "step" => "a STEP model",
+        "3mf" => "a 3MF package",
+        "amf" => "an AMF document",
+        "ply" => "a PLY mesh",
+        "zip" => "a ZIP archive",
+        "gzip" => "a gzip file",
+        "gcode" => "G-code",
+        "html" => "an HTML page",
+        "xml" => "an XML document",
+        "exe" => "a Windows executable",
+        "elf" => "a Linux executable",
+        "pdf" => "a PDF document",
+        _ => "something else",
+    }
+}
+
+/// An invalid result for an upload whose content is plainly not what its
+/// extension says, from the first `SNIFF_BYTES` of it (fewer for a shorter
+/// file); `None` when it matches or cannot be told.
+pub(crate) fn check_content(path: &Path, head: &[u8], file_size: u64) -> Option<ModelInfo> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let accepted: &[&str] = match ext.as_str() {
+        "stl" => &["stl"],
+        "obj" => &["obj"],
+        "step" | "stp" => &["step"],
+        "3mf" => &["3mf", "zip"],
+        "amf" => &["amf", "xml", "zip", "gzip"],
+        "ply" => &["ply"],
+        // Archives check their own signature, and name what they hold.
+        _ => return None,
+    };
+    let kind = sniff(head, head.len() < SNIFF_BYTES);
+    if kind == "unknown" || accepted.contains(&kind) {
+        return None;
+    }
+    let file_type = if ext == "stp" { "step" } else { &ext };
+    Some(ModelInfo::invalid(
+        file_type,
+        file_size,
+        format!(
+            "File is named .{} but its content is {}",
+            ext,
+            describe(kind)
+        ),
+    ))
+}
+
+/// `check_content` for a saved file; a missing file is left to its validator.
+pub(crate) fn content_mismatch(path: &Path) -> io::Result<Option<ModelInfo>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let file_size = file.metadata()?.len();
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    file.take(SNIFF_BYTES as u64).read_to_end(&mut head)?;
+    Ok(check_content(path, &head, file_size))
+}
+
+/// The type of a file from its content, whatever it is named
+///
+/// One of "stl", "obj", "step", "3mf", "amf", "ply", "zip", "gzip", "gcode",
+/// "html", "xml", "exe", "elf", "pdf", or "unknown". A binary STL is told
+/// from its length matching its triangle count, so pass the whole file.
+#[pyfunction]
+pub fn detect_file_type(contents: &[u8]) -> PyResult<String> {
+    panic_boundary::catch(|| {
+        let kind = match sniff(contents, true) {
+            "unknown" if is_binary_stl(contents) => "stl",
+            kind => kind,
+        };
+        Ok(kind.to_string())
+    })
+}
+
+fn is_binary_stl(contents: &[u8]) -> bool {
+    let Some(count) = contents.get(80..84) else {
+        return false;
+    };
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]);
+    binary_stl_size(count) == contents.len() as u64
+}
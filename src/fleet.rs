@@ -0,0 +1,235 @@
+use pyo3::prelude::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::build_plate::fits_build_volume;
+use crate::geometry::model_dimensions;
+use crate::panic_boundary;
+use crate::profiles::{index_profiles, load_profile_file, resolve_profile_file};
+use crate::OrcaError;
+
+/// One `[[printer]]` entry of a fleet file; unset capabilities come from the machine profile.
+#[derive(Debug, Deserialize)]
+struct PrinterEntry {
+    name: String,
+    machine: String,
+    process: Option<String>,
+    #[serde(default)]
+    materials: Vec<String>,
+    nozzle_diameter: Option<f64>,
+    bed_size: Option<(f64, f64)>,
+    max_height: Option<f64>,
+    moonraker_url: Option<String>,
+    octoprint_url: Option<String>,
+    api_key_env: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FleetFile {
+    #[serde(default, rename = "printer")]
+    printers: Vec<PrinterEntry>,
+}
+
+/// A physical printer with the machine profile that drives it and what it can print
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct FleetPrinter {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub machine_profile: String,
+    /// Process profile path, or `None` to use the configured default.
+    #[pyo3(get)]
+    pub process_profile: Option<String>,
+    #[pyo3(get)]
+    pub nozzle_diameter: Option<f64>,
+    #[pyo3(get)]
+    pub bed_size: Option<(f64, f64)>,
+    #[pyo3(get)]
+    pub max_height: Option<f64>,
+    /// Canonical material names; empty means any material.
+    #[pyo3(get)]
+    pub materials: Vec<String>,
+    /// "moonraker" or "octoprint" when the printer's load can be polled.
+    #[pyo3(get)]
+    pub status_provider: Option<String>,
+    #[pyo3(get)]
+    pub status_url: Option<String>,
+    /// Environment variable holding the host's API key, so keys stay out of the fleet file.
+    pub api_key_env: Option<String>,
+}
+
+impl FleetPrinter {
+    pub fn supports_material(&self, material: &str) -> bool {
+        self.materials.is_empty()
+            || self
+                .materials
+                .iter()
+                .any(|m| m.eq_ignore_ascii_case(material.trim()))
+    }
+
+    /// Whether a model fits the build volume, allowing a 90° turn on the bed.
+    /// Unknown capabilities are assumed to fit.
+    pub fn fits(&self, dimensions: (f64, f64, f64)) -> bool {
+        fits_build_volume(dimensions, self.bed_size, self.max_height)
+    }
+
+    fn bed_area(&self) -> f64 {
+        self.bed_size
+            .map_or(f64::INFINITY, |(width, depth)| width * depth)
+    }
+}
+
+#[pymethods]
+impl FleetPrinter {
+    fn __str__(&self) -> String {
+        format!(
+            "FleetPrinter(name={}, nozzle={:?}, bed={:?}, materials={:?})",
+            self.name, self.nozzle_diameter, self.bed_size, self.materials
+        )
+    }
+}
+
+/// Printers available for quoting, in configuration order
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct Fleet {
+    #[pyo3(get)]
+    pub printers: Vec<FleetPrinter>,
+}
+
+/// Profile file names are relative to `<profiles_dir>/<profile_type>`.
+fn profile_path(profiles_dir: &Path, profile_type: &str, file: &str) -> PathBuf {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        profiles_dir.join(profile_type).join(path)
+    }
+}
+
+impl Fleet {
+    /// Load a TOML fleet file made of `[[printer]]` tables.
+    ///
+    /// ```toml
+    /// [[printer]]
+    /// name = "V-Core 400"
+    /// machine = "RatRig V-Core 3 400 0.5 nozzle.json"
+    /// materials = ["PLA", "PETG", "ASA"]
+    /// ```
+    pub fn load(path: &Path, profiles_dir: &Path) -> Result<Self, OrcaError> {
+        if !path.is_file() {
+            return Err(OrcaError::FileNotFound(path.display().to_string()));
+        }
+        let contents = fs::read_to_string(path)?;
+        let file: FleetFile = toml::from_str(&contents).map_err(|e| OrcaError::InvalidConfig {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+
+        let index = index_profiles(&[profiles_dir.to_path_buf()]);
+        let mut printers = Vec::new();
+        for entry in file.printers {
+            let machine_path = profile_path(profiles_dir, "machine", &entry.machine);
+            // Parents outside the tree are tolerated, as in profile discovery.
+            let machine = resolve_profile_file(&machine_path, &index)
+                .or_else(|_| load_profile_file(&machine_path))?;
+            let (status_provider, status_url) = match (entry.moonraker_url, entry.octoprint_url) {
+                (Some(_), Some(_)) => {
+                    return Err(OrcaError::InvalidConfig {
+                        path: path.display().to_string(),
+                        message: format!(
+                            "printer {:?} sets both moonraker_url and octoprint_url",
+                            entry.name
+                        ),
+                    })
+                }
+                (Some(url), None) => (Some("moonraker".to_string()), Some(url)),
+                (None, Some(url)) => (Some("octoprint".to_string()), Some(url)),
+                (None, None) => (None, None),
+            };
+            printers.push(FleetPrinter {
+                name: entry.name,
+                machine_profile: machine.path,
+                process_profile: entry.process.map(|file| {
+                    profile_path(profiles_dir, "process", &file)
+                        .to_string_lossy()
+                        .into_owned()
+                }),
+                nozzle_diameter: entry.nozzle_diameter.or(machine.nozzle_diameter),
+                bed_size: entry.bed_size.or(machine.bed_size),
+                max_height: entry.max_height.or(machine.printable_height),
+                materials: entry
+                    .materials
+                    .iter()
+                    .map(|m| m.trim().to_uppercase())
+                    .collect(),
+                status_provider,
+                status_url,
+                api_key_env: entry.api_key_env,
+            });
+        }
+        Ok(Fleet { printers })
+    }
+
+    /// Printers that support the material and fit the model, in configuration order.
+    /// Unknown dimensions match any capable printer.
+    pub fn candidates<'a: 'm, 'm>(
+        &'a self,
+        material: &'m str,
+        dimensions: Option<(f64, f64, f64)>,
+    ) -> impl Iterator<Item = &'a FleetPrinter> + 'm {
+        self.printers.iter().filter(move |printer| {
+            printer.supports_material(material) && dimensions.is_none_or(|dims| printer.fits(dims))
+        })
+    }
+
+    /// Pick the printer for a material and model size.
+    ///
+    /// Among the candidates, the one with the smallest bed wins so large machines
+    /// stay free for large parts; ties keep configuration order.
+    pub fn select(
+        &self,
+        material: &str,
+        dimensions: Option<(f64, f64, f64)>,
+    ) -> Result<&FleetPrinter, OrcaError> {
+        let mut best: Option<&FleetPrinter> = None;
+        for printer in self.candidates(material, dimensions) {
+            if best.is_none_or(|current| printer.bed_area() < current.bed_area()) {
+                best = Some(printer);
+            }
+        }
+
+        best.ok_or_else(|| {
+            let size = dimensions
+                .map(|(x, y, z)| format!(" ({:.1} x {:.1} x {:.1} mm)", x, y, z))
+                .unwrap_or_default();
+            OrcaError::NoSuitablePrinter(format!("{}{}", material, size))
+        })
+    }
+}
+
+#[pymethods]
+impl Fleet {
+    /// Select the best printer for a material, checking the model's size when a path is given
+    #[pyo3(signature = (material, model_path=None))]
+    fn select_printer(&self, material: &str, model_path: Option<String>) -> PyResult<FleetPrinter> {
+        let dimensions = match model_path {
+            Some(path) => model_dimensions(Path::new(&path)).map_err(OrcaError::IoError)?,
+            None => None,
+        };
+        Ok(self.select(material, dimensions)?.clone())
+    }
+
+    fn __str__(&self) -> String {
+        let names: Vec<&str> = self.printers.iter().map(|p| p.name.as_str()).collect();
+        format!("Fleet(printers={:?})", names)
+    }
+}
+
+/// Load a fleet file, filling printer capabilities from machine profiles under `profiles_dir`
+#[pyfunction]
+pub fn load_fleet(path: String, profiles_dir: String) -> PyResult<Fleet> {
+    panic_boundary::catch(|| Ok(Fleet::load(Path::new(&path), Path::new(&profiles_dir))?))
+}
@@ -0,0 +1,243 @@
+use pyo3::prelude::*;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::panic_boundary;
+use crate::profile_compat::load_resolved;
+use crate::validation_cache::file_digest;
+use crate::OrcaError;
+
+/// Content-addressed store of sliced G-code: one directory per key under `dir`,
+/// evicting the least recently used entries once the total exceeds `max_bytes`
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct GcodeCache {
+    #[pyo3(get)]
+    pub dir: String,
+    /// Size budget for all entries; 0 means unbounded.
+    #[pyo3(get)]
+    pub max_bytes: u64,
+}
+
+/// One cached slicing run, as found while scanning the cache directory.
+struct Entry {
+    path: PathBuf,
+    bytes: u64,
+    last_used: SystemTime,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Keys are hex digests; anything else could escape the cache directory.
+fn is_key(key: &str) -> bool {
+    key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+pub(crate) fn gcode_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("gcode"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn to_strings(paths: Vec<PathBuf>) -> Vec<String> {
+    paths
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Mark an entry as used now; eviction goes by directory modification time.
+fn touch(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)?.set_modified(SystemTime::now())
+}
+
+impl GcodeCache {
+    fn entry_dir(&self, key: &str) -> PathBuf {
+        Path::new(&self.dir).join(key)
+    }
+
+    /// Hash of the model contents and the fully resolved (inherited and
+    /// overridden) settings of each profile, in order.
+    pub fn key_for(&self, model_path: &Path, profiles: &[&Path]) -> Result<String, OrcaError> {
+        let mut hasher = Sha256::new();
+        hasher.update(file_digest(model_path)?);
+        for profile in profiles {
+            let resolved = load_resolved(profile, &[])?;
+            // serde_json maps are ordered, so equal settings serialize identically.
+            let settings = Value::Object(resolved.settings).to_string();
+            hasher.update((settings.len() as u64).to_le_bytes());
+            hasher.update(settings.as_bytes());
+        }
+        Ok(to_hex(&hasher.finalize()))
+    }
+
+    /// G-code files stored under `key`, refreshing the entry's last use.
+    pub fn lookup(&self, key: &str) -> Option<Vec<PathBuf>> {
+        if !is_key(key) {
+            return None;
+        }
+        let dir = self.entry_dir(key);
+        let files = gcode_files(&dir).ok().filter(|files| !files.is_empty())?;
+        let _ = touch(&dir);
+        Some(files)
+    }
+
+    /// Copy every G-code file in `output_dir` into the cache under `key` and
+    /// return the cached paths. Entries larger than the whole budget are not kept.
+    pub fn insert(&self, key: &str, output_dir: &Path) -> Result<Vec<PathBuf>, OrcaError> {
+        if !is_key(key) {
+            return Err(OrcaError::InvalidConfig {
+                path: self.dir.clone(),
+                message: format!("'{}' is not a G-code cache key", key),
+            });
+        }
+        if let Some(files) = self.lookup(key) {
+            return Ok(files);
+        }
+        let sources = gcode_files(output_dir)?;
+        if sources.is_empty() {
+            return Err(OrcaError::FileNotFound(format!(
+                "no G-code files in {}",
+                output_dir.display()
+            )));
+        }
+
+        // Copy into a private directory and rename it into place, so readers never
+        // see a half-written entry and concurrent workers don't clash.
+        fs::create_dir_all(&self.dir)?;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let staging =
+            Path::new(&self.dir).join(format!(".tmp-{}-{}-{}", key, std::process::id(), nanos));
+        fs::create_dir(&staging)?;
+        let copied = sources.iter().try_for_each(|source| {
+            let name = source.file_name().unwrap_or_default();
+            fs::copy(source, staging.join(name)).map(|_| ())
+        });
+        let target = self.entry_dir(key);
+        let placed = copied.and_then(|()| fs::rename(&staging, &target));
+        if let Err(e) = placed {
+            let _ = fs::remove_dir_all(&staging);
+            // Another worker stored the same key first.
+            if let Some(files) = self.lookup(key) {
+                return Ok(files);
+            }
+            return Err(e.into());
+        }
+
+        self.evict()?;
+        self.lookup(key).ok_or_else(|| OrcaError::InvalidConfig {
+            path: self.dir.clone(),
+            message: format!("G-code for {} exceeds the cache budget", key),
+        })
+    }
+
+    fn entries(&self) -> io::Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir)?.flatten() {
+            let path = dir_entry.path();
+            let name = dir_entry.file_name();
+            if !path.is_dir() || !is_key(&name.to_string_lossy()) {
+                continue;
+            }
+            let bytes = fs::read_dir(&path)?
+                .flatten()
+                .filter_map(|file| file.metadata().ok())
+                .map(|metadata| metadata.len())
+                .sum();
+            let last_used = fs::metadata(&path)?.modified()?;
+            entries.push(Entry {
+                path,
+                bytes,
+                last_used,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Remove least recently used entries until the cache fits its budget.
+    fn evict(&self) -> io::Result<()> {
+        if self.max_bytes == 0 {
+            return Ok(());
+        }
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|entry| entry.last_used);
+        let mut total: u64 = entries.iter().map(|entry| entry.bytes).sum();
+        for entry in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            match fs::remove_dir_all(&entry.path) {
+                Ok(()) => total -= entry.bytes,
+                // A concurrent eviction got there first.
+                Err(e) if e.kind() == io::ErrorKind::NotFound => total -= entry.bytes,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl GcodeCache {
+    /// Cache key for a model sliced with the given machine, process and filament profiles
+    fn key(
+        &self,
+        model_path: &str,
+        machine: &str,
+        process: &str,
+        filament: &str,
+    ) -> PyResult<String> {
+        Ok(self.key_for(
+            Path::new(model_path),
+            &[Path::new(machine), Path::new(process), Path::new(filament)],
+        )?)
+    }
+
+    /// Store the G-code files in `output_dir` under `key`; returns the cached paths
+    fn store(&self, key: &str, output_dir: &str) -> PyResult<Vec<String>> {
+        Ok(to_strings(self.insert(key, Path::new(output_dir))?))
+    }
+
+    /// Cached G-code paths for `key`, or None when it was never stored or was evicted
+    fn get(&self, key: &str) -> Option<Vec<String>> {
+        self.lookup(key).map(to_strings)
+    }
+
+    /// Total size in bytes of all cached G-code
+    fn size_bytes(&self) -> PyResult<u64> {
+        match self.entries() {
+            Ok(entries) => Ok(entries.iter().map(|entry| entry.bytes).sum()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(OrcaError::IoError(e).into()),
+        }
+    }
+
+    fn __str__(&self) -> String {
+        format!("GcodeCache(dir={}, max_bytes={})", self.dir, self.max_bytes)
+    }
+}
+
+/// Open a G-code cache in `cache_dir` holding at most `max_bytes` (0 = unbounded)
+#[pyfunction]
+#[pyo3(signature = (cache_dir, max_bytes=0))]
+pub fn create_gcode_cache(cache_dir: String, max_bytes: u64) -> PyResult<GcodeCache> {
+    panic_boundary::catch(|| {
+        Ok(GcodeCache {
+            dir: cache_dir,
+            max_bytes,
+        })
+    })
+}
@@ -0,0 +1,81 @@
+use memchr::{memchr, memmem};
+use std::io::{self, Read};
+
+use crate::memory_limits::Budget;
+use crate::GcodeMetadata;
+
+/// Bytes read from the file at a time.
+const CHUNK_BYTES: usize = 256 * 1024;
+
+/// Every metadata comment the parser understands starts with "; ".
+const METADATA_MARKER: &[u8] = b"; ";
+
+fn trim_cr(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Call `on_line` with every line of `reader`, without its line ending.
+///
+/// The file is read in fixed-size chunks and split with `memchr`, so a line is
+/// only copied when it straddles two chunks; nothing is allocated per line. A
+/// line longer than the budget fails with a memory-limit error.
+pub fn for_each_line<R: Read>(
+    mut reader: R,
+    budget: Budget,
+    mut on_line: impl FnMut(&[u8]),
+) -> io::Result<()> {
+    let limit = budget.limit();
+    let over_budget = |len: usize| limit > 0 && len as u64 > limit;
+    let mut chunk = vec![0u8; CHUNK_BYTES];
+    // The unfinished tail of the previous chunk.
+    let mut carry: Vec<u8> = Vec::new();
+
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let mut rest = &chunk[..read];
+        while let Some(end) = memchr(b'\n', rest) {
+            if carry.is_empty() {
+                if over_budget(end) {
+                    return Err(budget.exceeded(limit));
+                }
+                on_line(trim_cr(&rest[..end]));
+            } else {
+                carry.extend_from_slice(&rest[..end]);
+                if over_budget(carry.len()) {
+                    return Err(budget.exceeded(limit));
+                }
+                on_line(trim_cr(&carry));
+                carry.clear();
+            }
+            rest = &rest[end + 1..];
+        }
+        carry.extend_from_slice(rest);
+        if over_budget(carry.len()) {
+            return Err(budget.exceeded(limit));
+        }
+    }
+    if !carry.is_empty() {
+        on_line(trim_cr(&carry));
+    }
+    Ok(())
+}
+
+/// Slicer metadata from every comment in a G-code file, header and footer alike.
+///
+/// Moves and per-feature comments (`;TYPE:`, `;WIDTH:`) are rejected with one
+/// substring search; only candidate comment lines are decoded.
+pub fn scan_metadata<R: Read>(reader: R) -> io::Result<GcodeMetadata> {
+    let marker = memmem::Finder::new(METADATA_MARKER);
+    let mut metadata = GcodeMetadata::default();
+    for_each_line(reader, Budget::GcodeParse, |line| {
+        if marker.find(line).is_some() {
+            metadata.feed(&String::from_utf8_lossy(line));
+        }
+    })?;
+    Ok(metadata)
+}
@@ -0,0 +1,340 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use crate::mapped_file::ModelFile;
+use crate::memory_limits::{self, Budget};
+use crate::mesh_formats::{amf_triangles, ply_triangles, three_mf_triangles};
+use crate::mesh_warnings::MeshWarnings;
+use crate::overhangs::{Overhangs, SUPPORT_AREA_MM2};
+use crate::stability::{assess, Balance, Stability};
+
+/// Axis-aligned bounds of every vertex seen so far.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl BoundingBox {
+    pub(crate) fn empty() -> Self {
+        BoundingBox {
+            min: [f64::INFINITY; 3],
+            max: [f64::NEG_INFINITY; 3],
+        }
+    }
+
+    pub(crate) fn include(&mut self, point: [f64; 3]) {
+        for (axis, value) in point.into_iter().enumerate() {
+            self.min[axis] = self.min[axis].min(value);
+            self.max[axis] = self.max[axis].max(value);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.min[0] > self.max[0]
+    }
+
+    /// Width, depth and height in model units (mm for STL/OBJ exported for printing).
+    pub fn size(&self) -> (f64, f64, f64) {
+        (
+            self.max[0] - self.min[0],
+            self.max[1] - self.min[1],
+            self.max[2] - self.min[2],
+        )
+    }
+}
+
+pub(crate) fn parse_point<'a>(mut parts: impl Iterator<Item = &'a str>) -> Option<[f64; 3]> {
+    Some([
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+    ])
+}
+
+/// Bounds, overhangs, balance and export problems of a mesh, gathered one
+/// triangle at a time while it is validated.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SurfaceScan {
+    pub bounds: BoundingBox,
+    overhangs: Overhangs,
+    balance: Balance,
+    pub warnings: MeshWarnings,
+    pub triangles: u64,
+}
+
+impl SurfaceScan {
+    pub fn new() -> Self {
+        SurfaceScan {
+            bounds: BoundingBox::empty(),
+            overhangs: Overhangs::new(),
+            balance: Balance::new(),
+            warnings: MeshWarnings::default(),
+            triangles: 0,
+        }
+    }
+
+    /// Add a triangle; its corners are added to `bounds` separately.
+    pub fn include(&mut self, triangle: [[f64; 3]; 3]) {
+        self.overhangs.include(triangle);
+        self.balance.include(triangle);
+        self.warnings.include(triangle);
+        self.triangles += 1;
+    }
+
+    /// `overhang_area_mm2`, `needs_supports` and `stability` for a valid model.
+    pub fn fields(&self, is_valid: bool) -> (Option<f64>, Option<bool>, Option<Stability>) {
+        if !is_valid || self.bounds.is_empty() {
+            return (None, None, None);
+        }
+        let area = self.overhangs.area(self.bounds.min[2]);
+        (
+            Some(area),
+            Some(area > SUPPORT_AREA_MM2),
+            assess(&self.bounds, &self.overhangs, &self.balance),
+        )
+    }
+}
+
+/// Add one 50-byte binary STL record: a normal followed by three vertices,
+/// all little-endian f32.
+pub(crate) fn include_stl_record(surface: &mut SurfaceScan, record: &[u8]) {
+    let mut triangle = [[0f64; 3]; 3];
+    for (vertex, point) in triangle.iter_mut().enumerate() {
+        let offset = 12 + vertex * 12;
+        for (axis, value) in point.iter_mut().enumerate() {
+            let start = offset + axis * 4;
+            let bytes = [
+                record[start],
+                record[start + 1],
+                record[start + 2],
+                record[start + 3],
+            ];
+            *value = f32::from_le_bytes(bytes) as f64;
+        }
+        surface.bounds.include(*point);
+    }
+    surface.include(triangle);
+}
+
+/// A binary STL's triangles scanned in one pass.
+pub(crate) fn binary_stl_scan(path: &Path, triangle_count: u32) -> std::io::Result<SurfaceScan> {
+    let source = ModelFile::open(path)?;
+    let mut reader = source.reader();
+    let mut header = [0u8; 84];
+    reader.read_exact(&mut header)?;
+
+    let mut surface = SurfaceScan::new();
+    let mut record = [0u8; 50];
+    for _ in 0..triangle_count {
+        reader.read_exact(&mut record)?;
+        include_stl_record(&mut surface, &record);
+    }
+    Ok(surface)
+}
+
+fn binary_stl_bounds(path: &Path, triangle_count: u32) -> std::io::Result<BoundingBox> {
+    binary_stl_scan(path, triangle_count).map(|surface| surface.bounds)
+}
+
+/// Bounds of the vertices in text formats, found via a line prefix
+/// (`vertex` for ASCII STL, `v` for OBJ).
+fn text_bounds(path: &Path, prefix: &str) -> std::io::Result<BoundingBox> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut bounds = BoundingBox::empty();
+    for line in memory_limits::lines(reader, Budget::MeshAnalysis) {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        if parts.next() == Some(prefix) {
+            if let Some(point) = parse_point(parts) {
+                bounds.include(point);
+            }
+        }
+    }
+    Ok(bounds)
+}
+
+/// Triangle count of a binary STL; `None` for ASCII STL.
+pub(crate) fn binary_stl_count(path: &Path) -> std::io::Result<Option<u32>> {
+    let file_size = fs::metadata(path)?.len();
+    let mut header = [0u8; 84];
+    let read = File::open(path)?.read(&mut header)?;
+
+    // Binary files may also start with "solid", so trust the size check first.
+    if read == 84 {
+        let triangle_count = u32::from_le_bytes([header[80], header[81], header[82], header[83]]);
+        if file_size == 84 + triangle_count as u64 * 50 {
+            return Ok(Some(triangle_count));
+        }
+    }
+    Ok(None)
+}
+
+fn triangle_bounds(triangles: &[Triangle]) -> BoundingBox {
+    let mut bounds = BoundingBox::empty();
+    for corner in triangles.iter().flatten() {
+        bounds.include(corner.map(|v| v as f64));
+    }
+    bounds
+}
+
+fn stl_bounds(path: &Path) -> std::io::Result<BoundingBox> {
+    match binary_stl_count(path)? {
+        Some(triangle_count) => binary_stl_bounds(path, triangle_count),
+        None => text_bounds(path, "vertex"),
+    }
+}
+
+/// Bounding box of a mesh model, or `None` for formats without readable vertices (STEP).
+pub fn model_bounds(path: &Path) -> std::io::Result<Option<BoundingBox>> {
+    let extension = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_lowercase());
+    let bounds = match extension.as_deref() {
+        Some("stl") => stl_bounds(path)?,
+        Some("obj") => text_bounds(path, "v")?,
+        Some("3mf") => triangle_bounds(&three_mf_triangles(path)?),
+        Some("amf") => triangle_bounds(&amf_triangles(path)?),
+        Some("ply") => triangle_bounds(&ply_triangles(path)?),
+        _ => return Ok(None),
+    };
+    Ok(if bounds.is_empty() {
+        None
+    } else {
+        Some(bounds)
+    })
+}
+
+/// A triangle's three corners.
+pub type Triangle = [[f32; 3]; 3];
+
+fn binary_stl_triangles(path: &Path, triangle_count: u32) -> std::io::Result<Vec<Triangle>> {
+    let source = ModelFile::open(path)?;
+    let mut reader = source.reader();
+    let mut header = [0u8; 84];
+    reader.read_exact(&mut header)?;
+
+    let mut triangles = Vec::with_capacity(triangle_count as usize);
+    let mut record = [0u8; 50];
+    for _ in 0..triangle_count {
+        reader.read_exact(&mut record)?;
+        let mut triangle = [[0f32; 3]; 3];
+        for (vertex, corner) in triangle.iter_mut().enumerate() {
+            for (axis, value) in corner.iter_mut().enumerate() {
+                let start = 12 + vertex * 12 + axis * 4;
+                let bytes = [
+                    record[start],
+                    record[start + 1],
+                    record[start + 2],
+                    record[start + 3],
+                ];
+                *value = f32::from_le_bytes(bytes);
+            }
+        }
+        triangles.push(triangle);
+    }
+    Ok(triangles)
+}
+
+/// Split a polygon into a fan of triangles around its first corner.
+pub(crate) fn fan(corners: &[[f32; 3]], triangles: &mut Vec<Triangle>) {
+    for pair in corners.windows(2).skip(1) {
+        triangles.push([corners[0], pair[0], pair[1]]);
+    }
+}
+
+fn ascii_stl_triangles(path: &Path) -> std::io::Result<Vec<Triangle>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut triangles = Vec::new();
+    let mut corners = Vec::with_capacity(3);
+    for line in memory_limits::lines(reader, Budget::MeshAnalysis) {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("vertex") => corners.extend(parse_point(parts).map(|p| p.map(|v| v as f32))),
+            Some("endloop") => {
+                fan(&corners, &mut triangles);
+                corners.clear();
+            }
+            _ => {}
+        }
+    }
+    Ok(triangles)
+}
+
+/// Indices into the `vertex_count` vertices read so far of an OBJ face
+/// line's corners, from the words after "f"; out-of-range corners are left out.
+pub(crate) fn obj_face_indices<'a>(
+    parts: impl Iterator<Item = &'a str>,
+    vertex_count: usize,
+) -> Vec<usize> {
+    // "f 1 2 3", "f 1/1/1 2/2/2 3/3/3"; negative indices count back from the end.
+    parts
+        .filter_map(|part| part.split('/').next()?.parse::<i64>().ok())
+        .filter_map(|index| {
+            let index = if index < 0 {
+                vertex_count as i64 + index
+            } else {
+                index - 1
+            };
+            usize::try_from(index)
+                .ok()
+                .filter(|&index| index < vertex_count)
+        })
+        .collect()
+}
+
+/// The corners of an OBJ face line's polygon, from the words after "f".
+pub(crate) fn obj_face<'a>(
+    parts: impl Iterator<Item = &'a str>,
+    vertices: &[[f32; 3]],
+) -> Vec<[f32; 3]> {
+    obj_face_indices(parts, vertices.len())
+        .into_iter()
+        .map(|index| vertices[index])
+        .collect()
+}
+
+fn obj_triangles(path: &Path) -> std::io::Result<Vec<Triangle>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let mut triangles = Vec::new();
+    for line in memory_limits::lines(reader, Budget::MeshAnalysis) {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => vertices.extend(parse_point(parts).map(|p| p.map(|v| v as f32))),
+            Some("f") => fan(&obj_face(parts, &vertices), &mut triangles),
+            _ => {}
+        }
+    }
+    Ok(triangles)
+}
+
+/// Every triangle of a mesh model, or `None` for formats without readable
+/// vertices (STEP).
+pub fn model_triangles(path: &Path) -> std::io::Result<Option<Vec<Triangle>>> {
+    let extension = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_lowercase());
+    let triangles = match extension.as_deref() {
+        Some("stl") => match binary_stl_count(path)? {
+            Some(triangle_count) => binary_stl_triangles(path, triangle_count)?,
+            None => ascii_stl_triangles(path)?,
+        },
+        Some("obj") => obj_triangles(path)?,
+        Some("3mf") => three_mf_triangles(path)?,
+        Some("amf") => amf_triangles(path)?,
+        Some("ply") => ply_triangles(path)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(triangles).filter(|t| !t.is_empty()))
+}
+
+/// Width, depth and height of a mesh model, when they can be determined.
+pub fn model_dimensions(path: &Path) -> std::io::Result<Option<(f64, f64, f64)>> {
+    Ok(model_bounds(path)?.map(|bounds| bounds.size()))
+}
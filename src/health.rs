@@ -0,0 +1,253 @@
+use pyo3::prelude::*;
+use pyo3_asyncio::tokio::future_into_py;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::panic_boundary;
+use crate::pipeline::PipelineConfig;
+use crate::profile_compat::load_resolved;
+use crate::profiles::load_profile_file;
+
+const TELEGRAM_API: &str = "https://api.telegram.org";
+
+/// Result of checking one dependency
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct DependencyStatus {
+    #[pyo3(get)]
+    pub name: String,
+    /// "ok", "error" or "skipped" (not configured).
+    #[pyo3(get)]
+    pub status: String,
+    #[pyo3(get)]
+    pub detail: String,
+    #[pyo3(get)]
+    pub duration_ms: f64,
+}
+
+#[pymethods]
+impl DependencyStatus {
+    fn __str__(&self) -> String {
+        format!(
+            "DependencyStatus(name={}, status={}, detail={})",
+            self.name, self.status, self.detail
+        )
+    }
+}
+
+/// Status of every dependency the quote service needs
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct HealthReport {
+    #[pyo3(get)]
+    pub checks: Vec<DependencyStatus>,
+}
+
+#[pymethods]
+impl HealthReport {
+    /// True when no check failed; skipped checks count as healthy.
+    #[getter]
+    pub fn healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.status != "error")
+    }
+
+    fn __str__(&self) -> String {
+        let checks: Vec<String> = self
+            .checks
+            .iter()
+            .map(|check| format!("{}={}", check.name, check.status))
+            .collect();
+        format!(
+            "HealthReport(healthy={}, {})",
+            self.healthy(),
+            checks.join(", ")
+        )
+    }
+}
+
+/// Run `check`, timing it; `Ok(None)` means the dependency is not configured.
+fn timed(name: &str, check: impl FnOnce() -> Result<Option<String>, String>) -> DependencyStatus {
+    let started = Instant::now();
+    let (status, detail) = match check() {
+        Ok(Some(detail)) => ("ok", detail),
+        Ok(None) => ("skipped", "not configured".to_string()),
+        Err(detail) => ("error", detail),
+    };
+    DependencyStatus {
+        name: name.to_string(),
+        status: status.to_string(),
+        detail,
+        duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+    }
+}
+
+/// The slicer must start and exit on `--help` within the timeout.
+fn check_slicer(cli_path: &str, timeout: Duration) -> Result<Option<String>, String> {
+    let mut child = Command::new(cli_path)
+        .arg("--help")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("could not start {}: {}", cli_path, e))?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(Some(format!("--help exited with {}", status))),
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("--help did not exit within {:?}", timeout));
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+/// The default and fleet machine/process profiles resolve, and every filament parses.
+fn check_profiles(config: &PipelineConfig) -> Result<Option<String>, String> {
+    let profiles_dir = Path::new(&config.profiles_dir);
+    let mut paths = vec![
+        profiles_dir.join("machine").join(&config.machine_profile),
+        profiles_dir.join("process").join(&config.process_profile),
+    ];
+    for printer in config.fleet.iter().flat_map(|fleet| &fleet.printers) {
+        paths.push(PathBuf::from(&printer.machine_profile));
+        paths.extend(printer.process_profile.as_ref().map(PathBuf::from));
+    }
+    for path in &paths {
+        load_resolved(path, &[]).map_err(|e| e.to_string())?;
+    }
+
+    let filament_dir = profiles_dir.join("filament");
+    let entries =
+        fs::read_dir(&filament_dir).map_err(|e| format!("{}: {}", filament_dir.display(), e))?;
+    let mut filaments = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            load_profile_file(&path).map_err(|e| e.to_string())?;
+            filaments += 1;
+        }
+    }
+    if filaments == 0 {
+        return Err(format!(
+            "no filament profiles in {}",
+            filament_dir.display()
+        ));
+    }
+    Ok(Some(format!(
+        "{} machine/process and {} filament profiles parsed",
+        paths.len(),
+        filaments
+    )))
+}
+
+/// Uploads land in `upload_dir`, so a probe file must be writable and removable there.
+fn check_storage(upload_dir: &Path) -> Result<Option<String>, String> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let probe = upload_dir.join(format!(".health-{}-{}", std::process::id(), nanos));
+    fs::write(&probe, b"ok")
+        .and_then(|()| fs::remove_file(&probe))
+        .map_err(|e| format!("{} is not writable: {}", upload_dir.display(), e))?;
+    Ok(Some(format!("{} is writable", upload_dir.display())))
+}
+
+/// Telegram's `getMe` confirms the bot token; errors never echo the token-bearing URL.
+fn check_telegram(
+    token: Option<&str>,
+    api_base: &str,
+    timeout: Duration,
+) -> Result<Option<String>, String> {
+    let Some(token) = token.filter(|t| !t.is_empty()) else {
+        return Ok(None);
+    };
+    let url = format!("{}/bot{}/getMe", api_base.trim_end_matches('/'), token);
+    let response = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .build()
+        .get(&url)
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => format!("getMe returned HTTP {}", code),
+            ureq::Error::Transport(transport) => format!("getMe failed: {}", transport.kind()),
+        })?;
+    let body: serde_json::Value = response
+        .into_string()
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+        .map_err(|e| format!("getMe returned invalid JSON: {}", e))?;
+    if body.get("ok").and_then(serde_json::Value::as_bool) != Some(true) {
+        return Err("getMe did not return ok".to_string());
+    }
+    let username = body
+        .pointer("/result/username")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("bot");
+    Ok(Some(format!("@{}", username)))
+}
+
+/// Check the slicer, profiles, upload directory and Telegram bot concurrently
+#[pyfunction]
+#[pyo3(signature = (config, upload_dir, telegram_bot_token=None, timeout_secs=10.0, telegram_api=TELEGRAM_API))]
+pub fn health_check<'py>(
+    py: Python<'py>,
+    config: PyRef<'_, PipelineConfig>,
+    upload_dir: String,
+    telegram_bot_token: Option<String>,
+    timeout_secs: f64,
+    telegram_api: &str,
+) -> PyResult<&'py PyAny> {
+    panic_boundary::catch(|| {
+        let config = config.clone();
+        let telegram_api = telegram_api.to_string();
+        let timeout = Duration::from_secs_f64(timeout_secs.max(0.0));
+        future_into_py(
+            py,
+            panic_boundary::catch_future(async move {
+                let slicer_path = config.slicer_path.clone();
+                let slicer = tokio::task::spawn_blocking(move || {
+                    timed("slicer", || check_slicer(&slicer_path, timeout))
+                });
+                let profiles = tokio::task::spawn_blocking(move || {
+                    timed("profiles", || check_profiles(&config))
+                });
+                let storage = tokio::task::spawn_blocking(move || {
+                    timed("storage", || check_storage(Path::new(&upload_dir)))
+                });
+                let telegram = tokio::task::spawn_blocking(move || {
+                    timed("telegram", || {
+                        check_telegram(telegram_bot_token.as_deref(), &telegram_api, timeout)
+                    })
+                });
+
+                let (slicer, profiles, storage, telegram) =
+                    tokio::join!(slicer, profiles, storage, telegram);
+                let checks = [
+                    ("slicer", slicer),
+                    ("profiles", profiles),
+                    ("storage", storage),
+                    ("telegram", telegram),
+                ]
+                .into_iter()
+                .map(|(name, joined)| {
+                    joined.unwrap_or_else(|e| DependencyStatus {
+                        name: name.to_string(),
+                        status: "error".to_string(),
+                        detail: format!("check panicked: {}", e),
+                        duration_ms: 0.0,
+                    })
+                })
+                .collect();
+                Ok(HealthReport { checks })
+            }),
+        )
+    })
+}
@@ -0,0 +1,93 @@
+use serde_json::Value;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::OrcaError;
+
+/// Longest error response kept for the exception message.
+const MAX_ERROR_BODY: u64 = 2048;
+
+/// File name sent with an upload; quotes would end the header value early.
+pub fn upload_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().replace('"', ""))
+        .unwrap_or_else(|| "quote.gcode".to_string())
+}
+
+fn form_field(boundary: &str, name: &str, value: &str) -> String {
+    format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+        boundary, name, value
+    )
+}
+
+/// Turn a failed request into `UploadFailed`, keeping the start of the server's
+/// explanation for HTTP errors.
+pub fn request_error(url: &str, error: ureq::Error) -> OrcaError {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let mut detail = String::new();
+            let _ = response
+                .into_reader()
+                .take(MAX_ERROR_BODY)
+                .read_to_string(&mut detail);
+            OrcaError::UploadFailed(format!("{}: HTTP {} {}", url, code, detail.trim()))
+        }
+        ureq::Error::Transport(transport) => {
+            OrcaError::UploadFailed(format!("{}: {}", url, transport))
+        }
+    }
+}
+
+pub fn read_json(url: &str, response: ureq::Response) -> Result<Value, OrcaError> {
+    response
+        .into_string()
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+        .map_err(|e| OrcaError::UploadFailed(format!("{}: invalid response: {}", url, e)))
+}
+
+/// POST `fields` and then `path` as the `file` part of a multipart form. The
+/// file is streamed from disk, so G-code of any size uploads in constant memory.
+pub fn post_file(
+    request: ureq::Request,
+    fields: &[(&str, &str)],
+    path: &Path,
+) -> Result<ureq::Response, OrcaError> {
+    let file =
+        fs::File::open(path).map_err(|_| OrcaError::FileNotFound(path.display().to_string()))?;
+    let file_len = file.metadata()?.len();
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let boundary = format!("orca-quote-{:x}", nanos);
+    let mut head: String = fields
+        .iter()
+        .map(|(name, value)| form_field(&boundary, name, value))
+        .collect();
+    head.push_str(&format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        boundary,
+        upload_name(path)
+    ));
+    let tail = format!("\r\n--{}--\r\n", boundary);
+    let content_length = head.len() as u64 + file_len + tail.len() as u64;
+    let body = Cursor::new(head.into_bytes())
+        .chain(file)
+        .chain(Cursor::new(tail.into_bytes()));
+
+    let url = request.url().to_string();
+    request
+        .set(
+            "Content-Type",
+            &format!("multipart/form-data; boundary={}", boundary),
+        )
+        .set("Content-Length", &content_length.to_string())
+        .send(body)
+        .map_err(|e| request_error(&url, e))
+}
@@ -0,0 +1,381 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::http_upload::{read_json, request_error};
+use crate::materials::MaterialCatalog;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Spools as Spoolman reported them, and when.
+type Polled = (Instant, Vec<StockLevel>);
+
+/// Spoolman answers, by URL, reused for `max_age_secs`.
+static SPOOLMAN_CACHE: Lazy<Mutex<HashMap<String, Polled>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Filament on hand of one material, and color when stock is kept per color
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct StockLevel {
+    #[pyo3(get)]
+    pub material: String,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub color: Option<String>,
+    #[pyo3(get)]
+    pub remaining_grams: f64,
+}
+
+#[pymethods]
+impl StockLevel {
+    fn __str__(&self) -> String {
+        format!(
+            "StockLevel(material={}, color={:?}, remaining={:.0}g)",
+            self.material, self.color, self.remaining_grams
+        )
+    }
+}
+
+/// Whether a material can be quoted, and how much of it is on hand
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MaterialAvailability {
+    #[pyo3(get)]
+    pub name: String,
+    /// `None` when the inventory does not track the material.
+    #[pyo3(get)]
+    pub remaining_grams: Option<f64>,
+    #[pyo3(get)]
+    pub in_stock: bool,
+    /// In stock, but with less than the inventory's `low_stock_grams` left.
+    #[pyo3(get)]
+    pub low_stock: bool,
+    /// Colors with filament left; empty when stock is kept per material.
+    #[pyo3(get)]
+    pub colors_in_stock: Vec<String>,
+}
+
+#[pymethods]
+impl MaterialAvailability {
+    fn __str__(&self) -> String {
+        let state = match (self.in_stock, self.low_stock) {
+            (false, _) => "out of stock",
+            (true, true) => "low stock",
+            (true, false) => "in stock",
+        };
+        format!("MaterialAvailability(name={}, {})", self.name, state)
+    }
+}
+
+/// Filament on hand, from a local stock table or a Spoolman server
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct Inventory {
+    /// TOML file of `[[stock]]` entries: `material`, optional `color`, `remaining_grams`.
+    #[pyo3(get)]
+    pub table_path: Option<String>,
+    /// Spoolman server; its unarchived spools are counted per material.
+    #[pyo3(get)]
+    pub spoolman_url: Option<String>,
+    #[pyo3(get)]
+    pub low_stock_grams: f64,
+    /// How long a Spoolman answer is reused before the server is asked again.
+    #[pyo3(get)]
+    pub max_age_secs: f64,
+    #[pyo3(get)]
+    pub timeout_secs: f64,
+}
+
+#[derive(Deserialize)]
+struct StockTable {
+    #[serde(default)]
+    stock: Vec<StockLevel>,
+}
+
+fn load_table(path: &Path) -> Result<Vec<StockLevel>, OrcaError> {
+    if !path.is_file() {
+        return Err(OrcaError::FileNotFound(path.display().to_string()));
+    }
+    let table: StockTable =
+        toml::from_str(&fs::read_to_string(path)?).map_err(|e| OrcaError::InvalidConfig {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+    Ok(table.stock)
+}
+
+fn poll_spoolman(base: &str, timeout: Duration) -> Result<Vec<StockLevel>, OrcaError> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(timeout)
+        .timeout_read(timeout)
+        .build();
+    let url = format!("{}/api/v1/spool", base.trim_end_matches('/'));
+    let response = agent.get(&url).call().map_err(|e| request_error(&url, e))?;
+    let spools = read_json(&url, response)?;
+    Ok(spools
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|spool| !spool["archived"].as_bool().unwrap_or(false))
+        .filter_map(|spool| {
+            Some(StockLevel {
+                material: spool.pointer("/filament/material")?.as_str()?.to_string(),
+                color: None,
+                remaining_grams: spool
+                    .get("remaining_weight")
+                    .and_then(Value::as_f64)
+                    .unwrap_or(0.0),
+            })
+        })
+        .collect())
+}
+
+/// Catalog name of a material or alias, e.g. "pla+" -> "PLA".
+fn catalog_name(catalog: &MaterialCatalog, material: &str) -> String {
+    catalog
+        .find(material)
+        .map_or_else(|| material.trim().to_uppercase(), |m| m.name.clone())
+}
+
+/// Stock of `material`, with the entries counted under its catalog name.
+fn availability(
+    levels: &[StockLevel],
+    catalog: &MaterialCatalog,
+    material: &str,
+    low_stock_grams: f64,
+) -> MaterialAvailability {
+    let name = catalog_name(catalog, material);
+    let entries: Vec<&StockLevel> = levels
+        .iter()
+        .filter(|level| catalog_name(catalog, &level.material) == name)
+        .collect();
+    if entries.is_empty() {
+        return MaterialAvailability {
+            name,
+            remaining_grams: None,
+            in_stock: true,
+            low_stock: false,
+            colors_in_stock: Vec::new(),
+        };
+    }
+    let remaining: f64 = entries.iter().map(|e| e.remaining_grams.max(0.0)).sum();
+    let colors: BTreeSet<String> = entries
+        .iter()
+        .filter(|e| e.remaining_grams > 0.0)
+        .filter_map(|e| e.color.clone())
+        .collect();
+    MaterialAvailability {
+        name,
+        remaining_grams: Some(remaining),
+        in_stock: remaining > 0.0,
+        low_stock: remaining > 0.0 && remaining < low_stock_grams,
+        colors_in_stock: colors.into_iter().collect(),
+    }
+}
+
+impl Inventory {
+    /// Every stock entry, from the table and Spoolman together.
+    pub fn levels(&self) -> Result<Vec<StockLevel>, OrcaError> {
+        let mut levels = match &self.table_path {
+            Some(path) => load_table(Path::new(path))?,
+            None => Vec::new(),
+        };
+        if let Some(url) = &self.spoolman_url {
+            let max_age = Duration::from_secs_f64(self.max_age_secs.max(0.0));
+            let cached = SPOOLMAN_CACHE
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(url)
+                .filter(|(polled, _)| polled.elapsed() < max_age)
+                .map(|(_, spools)| spools.clone());
+            let spools = match cached {
+                Some(spools) => spools,
+                None => {
+                    let spools = poll_spoolman(url, Duration::from_secs_f64(self.timeout_secs))?;
+                    SPOOLMAN_CACHE
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(url.clone(), (Instant::now(), spools.clone()));
+                    spools
+                }
+            };
+            levels.extend(spools);
+        }
+        Ok(levels)
+    }
+
+    /// Refuse a material, or a color of it, without `grams` on hand. Materials
+    /// the inventory does not track are never refused, and an unreachable
+    /// inventory refuses nothing.
+    pub fn check(
+        &self,
+        catalog: &MaterialCatalog,
+        material: &str,
+        color: Option<&str>,
+        grams: f64,
+    ) -> Result<(), OrcaError> {
+        let levels = match self.levels() {
+            Ok(levels) => levels,
+            Err(e) => {
+                tracing::warn!(error = %e, "could not read inventory");
+                return Ok(());
+            }
+        };
+        let stock = availability(&levels, catalog, material, self.low_stock_grams);
+        let Some(total) = stock.remaining_grams else {
+            return Ok(());
+        };
+        let name = stock.name;
+        let per_color: Vec<&StockLevel> = levels
+            .iter()
+            .filter(|level| level.color.is_some() && catalog_name(catalog, &level.material) == name)
+            .collect();
+        let (label, remaining) = match color {
+            Some(color) if !per_color.is_empty() => {
+                let remaining: f64 = per_color
+                    .iter()
+                    .filter(|level| {
+                        level
+                            .color
+                            .as_deref()
+                            .is_some_and(|c| c.eq_ignore_ascii_case(color))
+                    })
+                    .map(|level| level.remaining_grams.max(0.0))
+                    .sum();
+                (format!("{} {}", name, color), remaining)
+            }
+            _ => (name, total),
+        };
+        if remaining <= 0.0 {
+            return Err(OrcaError::OutOfStock(label));
+        }
+        if grams > remaining {
+            return Err(OrcaError::InsufficientStock {
+                material: label,
+                needed_grams: grams,
+                remaining_grams: remaining,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Inventory {
+    /// Every stock entry, from the table and Spoolman together
+    #[pyo3(name = "levels")]
+    fn py_levels(&self, py: Python<'_>) -> PyResult<Vec<StockLevel>> {
+        Ok(py.allow_threads(|| self.levels())?)
+    }
+
+    /// Raise ValueError ("PETG out of stock") unless `grams` of the material,
+    /// in `color` when stock is kept per color, are on hand
+    #[pyo3(name = "check", signature = (catalog, material, color=None, grams=0.0))]
+    fn py_check(
+        &self,
+        py: Python<'_>,
+        catalog: PyRef<'_, MaterialCatalog>,
+        material: &str,
+        color: Option<&str>,
+        grams: f64,
+    ) -> PyResult<()> {
+        let catalog: &MaterialCatalog = &catalog;
+        Ok(py.allow_threads(|| self.check(catalog, material, color, grams))?)
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "Inventory(table={:?}, spoolman={:?}, low_stock={}g)",
+            self.table_path, self.spoolman_url, self.low_stock_grams
+        )
+    }
+}
+
+/// Read stock levels from a TOML table, a Spoolman server, or both
+///
+/// The table lists `[[stock]]` entries with `material`, an optional `color` and
+/// `remaining_grams`; Spoolman's unarchived spools are counted per material.
+/// Materials are matched by catalog name or alias.
+#[pyfunction]
+#[pyo3(signature = (table_path=None, spoolman_url=None, low_stock_grams=200.0, max_age_secs=60.0, timeout_secs=5.0))]
+pub fn create_inventory(
+    table_path: Option<String>,
+    spoolman_url: Option<String>,
+    low_stock_grams: f64,
+    max_age_secs: f64,
+    timeout_secs: f64,
+) -> PyResult<Inventory> {
+    panic_boundary::catch(|| {
+        if table_path.is_none() && spoolman_url.is_none() {
+            return Err(OrcaError::InvalidConfig {
+                path: "inventory".to_string(),
+                message: "needs a table_path or a spoolman_url".to_string(),
+            }
+            .into());
+        }
+        if !(timeout_secs > 0.0 && timeout_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "timeout_secs".to_string(),
+                message: format!("{} is not a positive number of seconds", timeout_secs),
+            }
+            .into());
+        }
+        if let Some(path) = &table_path {
+            load_table(Path::new(path))?;
+        }
+        Ok(Inventory {
+            table_path,
+            spoolman_url,
+            low_stock_grams,
+            max_age_secs,
+            timeout_secs,
+        })
+    })
+}
+
+/// Materials a customer can choose, with their stock
+///
+/// The catalog's materials and the materials of `profile_names` (filament
+/// profile file stems, collapsed into catalog names where they are aliases),
+/// sorted by name. Without an inventory every material is in stock; when the
+/// inventory cannot be read, every material is reported in stock.
+#[pyfunction]
+#[pyo3(signature = (catalog, profile_names=Vec::new(), inventory=None))]
+pub fn discover_available_materials(
+    py: Python<'_>,
+    catalog: PyRef<'_, MaterialCatalog>,
+    profile_names: Vec<String>,
+    inventory: Option<PyRef<'_, Inventory>>,
+) -> PyResult<Vec<MaterialAvailability>> {
+    panic_boundary::catch(|| {
+        let catalog: &MaterialCatalog = &catalog;
+        let inventory = inventory.as_deref();
+        Ok(py.allow_threads(|| {
+            let names: BTreeSet<String> = catalog
+                .materials
+                .iter()
+                .map(|m| m.name.clone())
+                .chain(profile_names.iter().map(|stem| catalog_name(catalog, stem)))
+                .collect();
+            let levels = match inventory.map(Inventory::levels).transpose() {
+                Ok(levels) => levels.unwrap_or_default(),
+                Err(e) => {
+                    tracing::warn!(error = %e, "could not read inventory");
+                    Vec::new()
+                }
+            };
+            let low_stock_grams = inventory.map_or(0.0, |i| i.low_stock_grams);
+            names
+                .iter()
+                .map(|name| availability(&levels, catalog, name, low_stock_grams))
+                .collect()
+        }))
+    })
+}
@@ -0,0 +1,226 @@
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::audit::unix_timestamp;
+use crate::panic_boundary;
+use crate::pipeline::{PipelineConfig, QuoteResult};
+use crate::OrcaError;
+
+const MANIFEST: &str = "manifest.json";
+const JOB_SHEET: &str = "job_sheet.html";
+const PREVIEW: &str = "preview.png";
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A printable page with what the person at the printers needs: the job, its
+/// settings, files, price and when it is due.
+fn job_sheet(quote_id: &str, quote: &QuoteResult, gcode: &[String], preview: bool) -> String {
+    let file_name = |path: &str| {
+        Path::new(path).file_name().map_or_else(
+            || path.to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        )
+    };
+    let minutes = quote.slicing.print_time_minutes;
+    let mut rows: Vec<(&str, String)> = vec![
+        ("Material", quote.material.clone()),
+        (
+            "Printer",
+            quote.printer.clone().unwrap_or_else(|| "any".to_string()),
+        ),
+        ("Machine profile", file_name(&quote.machine_profile)),
+        ("Process profile", file_name(&quote.process_profile)),
+        ("Filament profile", file_name(&quote.filament_profile)),
+        ("Print time", format!("{}h {}m", minutes / 60, minutes % 60)),
+        (
+            "Filament",
+            format!("{:.1}g", quote.slicing.filament_weight_grams),
+        ),
+    ];
+    if let Some((x, y, z)) = quote.dimensions {
+        rows.push(("Size", format!("{:.1} x {:.1} x {:.1} mm", x, y, z)));
+    }
+    let options = &quote.options;
+    if let Some(layer_height) = options.layer_height {
+        rows.push(("Layer height", format!("{} mm", layer_height)));
+    }
+    if let Some(infill) = options.infill_percent {
+        rows.push(("Infill", format!("{}%", infill)));
+    }
+    if let Some(supports) = options.supports {
+        rows.push(("Supports", if supports { "yes" } else { "no" }.to_string()));
+    }
+    if let Some(color) = &options.color {
+        rows.push(("Color", color.clone()));
+    }
+    if let Some(date) = quote
+        .lead_time
+        .as_ref()
+        .and_then(|l| l.promised_date.clone())
+    {
+        rows.push(("Promised by", date));
+    }
+    rows.push(("Total", format!("S${:.2}", quote.cost.total_cost)));
+    for adjustment in &quote.adjustments {
+        rows.push((
+            "Adjustment",
+            format!("S${:+.2} ({})", adjustment.amount, adjustment.reason),
+        ));
+    }
+    if !quote.gcode_transforms.is_empty() {
+        rows.push(("Post-processing", quote.gcode_transforms.join(", ")));
+    }
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Job {id}</title>\n\
+         <style>body{{font-family:sans-serif;margin:2em}}th{{text-align:left;padding-right:1em}}\
+         img{{max-width:300px;float:right}}</style>\n</head>\n<body>\n<h1>Job {id}</h1>\n",
+        id = escape_html(quote_id)
+    );
+    if preview {
+        html.push_str(&format!(
+            "<img src=\"{}\" alt=\"Model preview\">\n",
+            PREVIEW
+        ));
+    }
+    html.push_str("<table>\n");
+    for (label, value) in rows {
+        html.push_str(&format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            label,
+            escape_html(&value)
+        ));
+    }
+    html.push_str("</table>\n<h2>G-code</h2>\n<ol>\n");
+    for name in gcode {
+        html.push_str(&format!("<li>{}</li>\n", escape_html(name)));
+    }
+    html.push_str("</ol>\n</body>\n</html>\n");
+    html
+}
+
+/// Zip the stored quote's G-code, a job sheet, its preview and a manifest.
+pub fn write_bundle(
+    config: &PipelineConfig,
+    quote_id: &str,
+    output: Option<&Path>,
+) -> Result<PathBuf, OrcaError> {
+    let store = config
+        .quote_store
+        .as_ref()
+        .ok_or_else(|| OrcaError::InvalidConfig {
+            path: "quote_store".to_string(),
+            message: "is not configured".to_string(),
+        })?;
+    let quote = store
+        .load(quote_id)?
+        .ok_or_else(|| OrcaError::FileNotFound(format!("no stored quote {}", quote_id)))?;
+    let gcode_files = config
+        .gcode_cache
+        .as_ref()
+        .zip(quote.slicing.gcode_cache_key.as_deref())
+        .and_then(|(cache, key)| cache.lookup(key))
+        .ok_or_else(|| {
+            OrcaError::FileNotFound(format!("G-code for quote {} is not cached", quote_id))
+        })?;
+
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    for path in &gcode_files {
+        let name = path.file_name().map_or_else(
+            || "plate.gcode".to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        entries.push((format!("gcode/{}", name), fs::read(path)?));
+    }
+    let gcode_names: Vec<String> = entries.iter().map(|(name, _)| name.clone()).collect();
+    if let Some(png) = &quote.preview_png {
+        entries.push((PREVIEW.to_string(), png.clone()));
+    }
+    let sheet = job_sheet(quote_id, &quote, &gcode_names, quote.preview_png.is_some());
+    entries.push((JOB_SHEET.to_string(), sheet.into_bytes()));
+
+    let files: Vec<Value> = entries
+        .iter()
+        .map(|(name, data)| {
+            let digest: String = Sha256::digest(data)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            json!({ "name": name, "bytes": data.len(), "sha256": digest })
+        })
+        .collect();
+    let manifest = json!({
+        "quote_id": quote_id,
+        "created_at": unix_timestamp(SystemTime::now()),
+        "version": env!("CARGO_PKG_VERSION"),
+        "files": files,
+        "quote": quote,
+    });
+    entries.push((
+        MANIFEST.to_string(),
+        serde_json::to_vec_pretty(&manifest).map_err(io::Error::from)?,
+    ));
+
+    let output = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| Path::new(&store.dir).join(format!("{}.zip", quote_id)));
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let zip_error = |e: zip::result::ZipError| OrcaError::IoError(io::Error::other(e));
+    let mut writer = ZipWriter::new(File::create(&output)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, data) in &entries {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(zip_error)?;
+        writer.write_all(data)?;
+    }
+    writer.finish().map_err(zip_error)?;
+    Ok(output)
+}
+
+/// Bundle an accepted quote for the print floor; returns the zip's path
+///
+/// The zip holds the G-code from the cache under `gcode/`, `job_sheet.html`
+/// (material, printer, profiles, options, time, price, adjustments and due
+/// date), `preview.png` when the quote has one, and `manifest.json` with the
+/// full quote and each file's size and SHA-256. The quote must have been kept
+/// by the config's quote store and its G-code by its cache. Without
+/// `output_path` the zip is written to `<quote store>/<quote_id>.zip`.
+#[pyfunction]
+#[pyo3(signature = (quote_id, config, output_path=None))]
+pub fn export_job_bundle(
+    py: Python<'_>,
+    quote_id: String,
+    config: PyRef<'_, PipelineConfig>,
+    output_path: Option<String>,
+) -> PyResult<String> {
+    panic_boundary::catch(|| {
+        let config: &PipelineConfig = &config;
+        let path = py.allow_threads(|| {
+            write_bundle(config, &quote_id, output_path.as_deref().map(Path::new))
+        })?;
+        Ok(path.to_string_lossy().into_owned())
+    })
+}
@@ -0,0 +1,113 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::panic_boundary;
+use crate::slicer::slot_usage;
+
+/// Slice durations averaged for the wait estimate.
+const RECENT_SLICES: usize = 20;
+
+static RECENT_SLICE_SECONDS: Lazy<Mutex<VecDeque<f64>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_SLICES)));
+
+/// Remember how long a slice took in this process.
+pub fn record_slice_seconds(seconds: f64) {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return;
+    }
+    let mut recent = RECENT_SLICE_SECONDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if recent.len() == RECENT_SLICES {
+        recent.pop_front();
+    }
+    recent.push_back(seconds);
+}
+
+fn average(samples: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = samples
+        .into_iter()
+        .fold((0.0, 0u32), |(sum, count), s| (sum + s, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Seconds until a job submitted now is sliced: every `parallelism` jobs ahead of it
+/// (queued or running) take one average slice, then its own slice.
+fn estimate_wait(queued: u64, running: u64, parallelism: u64, avg_slice_seconds: f64) -> f64 {
+    let rounds_ahead = (queued + running) / parallelism.max(1);
+    (rounds_ahead + 1) as f64 * avg_slice_seconds
+}
+
+/// Jobs ahead of a new quote and how long it will likely take
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct QueueStatus {
+    #[pyo3(get)]
+    pub queued: u64,
+    #[pyo3(get)]
+    pub running: u64,
+    /// Jobs that can be sliced at the same time.
+    #[pyo3(get)]
+    pub parallelism: u64,
+    /// Mean of recent slice durations, or `None` before any slice finished.
+    #[pyo3(get)]
+    pub avg_slice_seconds: Option<f64>,
+    #[pyo3(get)]
+    pub estimated_wait_seconds: Option<f64>,
+}
+
+#[pymethods]
+impl QueueStatus {
+    fn __str__(&self) -> String {
+        format!(
+            "QueueStatus(queued={}, running={}, parallelism={}, wait={:?}s)",
+            self.queued,
+            self.running,
+            self.parallelism,
+            self.estimated_wait_seconds.map(f64::round)
+        )
+    }
+}
+
+/// Queued and running jobs with an estimated wait for a new quote
+///
+/// Counts default to this process's slicer slots (waiting and running) and the
+/// average to its recent slices; pass them in when jobs run elsewhere, e.g. the
+/// broker's queue depth and durations reported by the workers.
+#[pyfunction]
+#[pyo3(signature = (queued=None, running=None, parallelism=None, recent_slice_seconds=None))]
+pub fn queue_status(
+    queued: Option<u64>,
+    running: Option<u64>,
+    parallelism: Option<u64>,
+    recent_slice_seconds: Option<Vec<f64>>,
+) -> PyResult<QueueStatus> {
+    panic_boundary::catch(|| {
+        let (in_use, waiting, limit) = slot_usage();
+        let queued = queued.unwrap_or(waiting as u64);
+        let running = running.unwrap_or(in_use as u64);
+        let parallelism = parallelism
+            .unwrap_or(if limit > 0 { limit as u64 } else { running })
+            .max(1);
+        let avg_slice_seconds = match recent_slice_seconds {
+            Some(samples) => average(samples),
+            None => average(
+                RECENT_SLICE_SECONDS
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .iter()
+                    .copied(),
+            ),
+        };
+        Ok(QueueStatus {
+            queued,
+            running,
+            parallelism,
+            avg_slice_seconds,
+            estimated_wait_seconds: avg_slice_seconds
+                .map(|avg| estimate_wait(queued, running, parallelism, avg)),
+        })
+    })
+}
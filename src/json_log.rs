@@ -0,0 +1,187 @@
+use once_cell::sync::{Lazy, OnceCell};
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::audit::unix_timestamp;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Where JSON log lines go; `None` until logging is initialised.
+static SINK: Lazy<Mutex<Option<Box<dyn Write + Send>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Most verbose level written, as `level_rank` of a `Level`; 0 writes nothing.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+static INSTALLED: OnceCell<()> = OnceCell::new();
+
+fn level_rank(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 1,
+        Level::WARN => 2,
+        Level::INFO => 3,
+        Level::DEBUG => 4,
+        Level::TRACE => 5,
+    }
+}
+
+fn parse_level(level: &str) -> Option<u8> {
+    let level = match level.to_ascii_lowercase().as_str() {
+        "off" => return Some(0),
+        "error" => Level::ERROR,
+        "warn" | "warning" => Level::WARN,
+        "info" => Level::INFO,
+        "debug" => Level::DEBUG,
+        "trace" => Level::TRACE,
+        _ => return None,
+    };
+    Some(level_rank(&level))
+}
+
+/// Span fields kept in the span's extensions so events can inherit them.
+struct SpanFields(Map<String, Value>);
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+}
+
+/// Writes each event as one flat JSON object: timestamp, level, target, the fields
+/// of every enclosing span (quote_id, stage, ...) and the event's own fields.
+struct JsonLayer;
+
+impl<S> Layer<S> for JsonLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // The level can change at runtime, so never let tracing cache the decision.
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        level_rank(metadata.level()) <= MAX_LEVEL.load(Ordering::Relaxed)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor(&mut fields));
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(fields));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+                values.record(&mut JsonVisitor(&mut fields.0));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut record = Map::new();
+        record.insert(
+            "timestamp".to_string(),
+            json!(unix_timestamp(SystemTime::now())),
+        );
+        record.insert("level".to_string(), json!(metadata.level().as_str()));
+        record.insert("target".to_string(), json!(metadata.target()));
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<SpanFields>() {
+                    record.extend(fields.0.clone());
+                }
+            }
+        }
+        event.record(&mut JsonVisitor(&mut record));
+
+        let mut line = Value::Object(record).to_string();
+        line.push('\n');
+        let mut sink = SINK.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(writer) = sink.as_mut() {
+            // Logging must never fail a quote; a broken sink just drops the line.
+            let _ = writer
+                .write_all(line.as_bytes())
+                .and_then(|()| writer.flush());
+        }
+    }
+}
+
+fn open_sink(sink: &str) -> io::Result<Box<dyn Write + Send>> {
+    Ok(match sink {
+        "stderr" => Box::new(io::stderr()),
+        "stdout" => Box::new(io::stdout()),
+        path => Box::new(OpenOptions::new().create(true).append(true).open(path)?),
+    })
+}
+
+/// Write Rust-side events as JSON lines to `sink`: "stderr", "stdout" or a file path.
+///
+/// The first call installs the global tracing subscriber; later calls only swap the
+/// sink and level. Pass `level="off"` to stop writing.
+pub fn init(sink: &str, level: &str) -> Result<(), OrcaError> {
+    let rank = parse_level(level).ok_or_else(|| OrcaError::InvalidConfig {
+        path: "log level".to_string(),
+        message: format!(
+            "unknown level '{}'; use trace, debug, info, warn, error or off",
+            level
+        ),
+    })?;
+    let writer = open_sink(sink)?;
+
+    INSTALLED.get_or_try_init(|| {
+        let subscriber = tracing_subscriber::registry().with(JsonLayer);
+        tracing::subscriber::set_global_default(subscriber).map_err(|e| OrcaError::InvalidConfig {
+            path: "tracing".to_string(),
+            message: e.to_string(),
+        })
+    })?;
+
+    *SINK.lock().unwrap_or_else(|e| e.into_inner()) = Some(writer);
+    MAX_LEVEL.store(rank, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Emit Rust-side events (quote stages, durations, outcomes) as JSON lines
+#[pyfunction]
+#[pyo3(signature = (sink="stderr", level="info"))]
+pub fn init_json_logging(sink: &str, level: &str) -> PyResult<()> {
+    panic_boundary::catch(|| Ok(init(sink, level)?))
+}
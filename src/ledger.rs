@@ -0,0 +1,497 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use ring::rand::SystemRandom;
+use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::http_upload::{read_json, request_error};
+use crate::panic_boundary;
+use crate::{CostBreakdown, OrcaError};
+
+const COLUMNS: [&str; 12] = [
+    "timestamp",
+    "quote_id",
+    "customer",
+    "file",
+    "material",
+    "filament_grams",
+    "print_time_minutes",
+    "material_cost",
+    "time_cost",
+    "total_cost",
+    "minimum_applied",
+    "payment_url",
+];
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+/// Refresh access tokens this long before Google says they expire.
+const TOKEN_MARGIN: Duration = Duration::from_secs(60);
+
+/// Access tokens by service account e-mail, with the time they stop being usable.
+static TOKENS: Lazy<Mutex<HashMap<String, (String, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone)]
+struct ServiceAccount {
+    client_email: String,
+    token_uri: String,
+    /// PKCS#8 DER of the account's RSA key.
+    private_key: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+enum Backend {
+    Csv {
+        dir: PathBuf,
+        rotation: String,
+    },
+    Sheets {
+        spreadsheet_id: String,
+        sheet: String,
+        account: ServiceAccount,
+        api_base: String,
+        timeout_secs: f64,
+    },
+}
+
+// Written by hand so the private key never ends up in a log line.
+impl fmt::Debug for ServiceAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceAccount")
+            .field("client_email", &self.client_email)
+            .field("token_uri", &self.token_uri)
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Where completed quotes are booked: a rotating CSV file or a Google Sheet
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct LedgerConfig {
+    backend: Backend,
+}
+
+#[pymethods]
+impl LedgerConfig {
+    /// "csv" or "google_sheets".
+    #[getter]
+    fn backend(&self) -> &'static str {
+        match self.backend {
+            Backend::Csv { .. } => "csv",
+            Backend::Sheets { .. } => "google_sheets",
+        }
+    }
+
+    fn __str__(&self) -> String {
+        match &self.backend {
+            Backend::Csv { dir, rotation } => {
+                format!("LedgerConfig(csv={}, rotation={})", dir.display(), rotation)
+            }
+            Backend::Sheets {
+                spreadsheet_id,
+                sheet,
+                ..
+            } => format!(
+                "LedgerConfig(spreadsheet={}, sheet={})",
+                spreadsheet_id, sheet
+            ),
+        }
+    }
+}
+
+/// Calendar date and time (UTC) for seconds since the Unix epoch.
+pub(crate) fn utc_datetime(secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Howard Hinnant's civil_from_days.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (
+        year,
+        month,
+        day,
+        (rem / 3600) as u32,
+        (rem % 3600 / 60) as u32,
+        (rem % 60) as u32,
+    )
+}
+
+/// Keep spreadsheet apps from running customer-supplied text as a formula.
+fn text_cell(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// One ledger row, in `COLUMNS` order.
+#[derive(Debug, Clone)]
+pub struct LedgerRow {
+    pub timestamp: String,
+    pub quote_id: String,
+    pub customer: String,
+    pub file: String,
+    pub cost: CostBreakdown,
+    pub payment_url: String,
+}
+
+impl LedgerRow {
+    fn json_values(&self) -> Vec<Value> {
+        vec![
+            json!(self.timestamp),
+            json!(text_cell(&self.quote_id)),
+            json!(text_cell(&self.customer)),
+            json!(text_cell(&self.file)),
+            json!(self.cost.material_type),
+            json!((self.cost.filament_grams as f64 * 10.0).round() / 10.0),
+            json!(self.cost.print_time_minutes),
+            json!((self.cost.material_cost * 100.0).round() / 100.0),
+            json!((self.cost.time_cost * 100.0).round() / 100.0),
+            json!((self.cost.total_cost * 100.0).round() / 100.0),
+            json!(self.cost.minimum_applied),
+            json!(self.payment_url),
+        ]
+    }
+
+    fn csv_line(&self) -> String {
+        let fields: Vec<String> = self
+            .json_values()
+            .into_iter()
+            .map(|value| match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect();
+        csv_record(&fields)
+    }
+}
+
+fn csv_record<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Ledger file for `now`: `quotes-2026-10.csv` when rotating monthly,
+/// `quotes-2026.csv` yearly, `quotes.csv` otherwise.
+fn csv_path(dir: &Path, rotation: &str, now: SystemTime) -> PathBuf {
+    let secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (year, month, ..) = utc_datetime(secs);
+    let name = match rotation {
+        "monthly" => format!("quotes-{}-{:02}.csv", year, month),
+        "yearly" => format!("quotes-{}.csv", year),
+        _ => "quotes.csv".to_string(),
+    };
+    dir.join(name)
+}
+
+fn append_csv(dir: &Path, rotation: &str, row: &LedgerRow) -> Result<String, OrcaError> {
+    fs::create_dir_all(dir)?;
+    let path = csv_path(dir, rotation, SystemTime::now());
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let mut record = String::new();
+    if file.metadata()?.len() == 0 {
+        record.push_str(&csv_record(&COLUMNS));
+    }
+    record.push_str(&row.csv_line());
+    // One write on an O_APPEND handle, as for the audit log.
+    file.write_all(record.as_bytes())?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn load_service_account(path: &Path) -> Result<ServiceAccount, OrcaError> {
+    let invalid = |message: String| OrcaError::InvalidConfig {
+        path: path.display().to_string(),
+        message,
+    };
+    let text = fs::read_to_string(path)
+        .map_err(|_| OrcaError::FileNotFound(path.display().to_string()))?;
+    let key: Value = serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+    let field = |name: &str| {
+        key.get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| invalid(format!("missing {}", name)))
+    };
+    let pem = field("private_key")?;
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let private_key = STANDARD
+        .decode(body.trim())
+        .map_err(|e| invalid(format!("private_key is not PEM: {}", e)))?;
+    RsaKeyPair::from_pkcs8(&private_key)
+        .map_err(|e| invalid(format!("private_key is not a PKCS#8 RSA key: {}", e)))?;
+    Ok(ServiceAccount {
+        client_email: field("client_email")?,
+        token_uri: field("token_uri")
+            .unwrap_or_else(|_| "https://oauth2.googleapis.com/token".to_string()),
+        private_key,
+    })
+}
+
+/// Signed JWT asking for a Sheets access token (RFC 7523).
+fn token_assertion(account: &ServiceAccount) -> Result<String, OrcaError> {
+    let iat = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let header = URL_SAFE_NO_PAD.encode(json!({"alg": "RS256", "typ": "JWT"}).to_string());
+    let claims = URL_SAFE_NO_PAD.encode(
+        json!({
+            "iss": account.client_email,
+            "scope": SHEETS_SCOPE,
+            "aud": account.token_uri,
+            "iat": iat,
+            "exp": iat + 3600,
+        })
+        .to_string(),
+    );
+    let message = format!("{}.{}", header, claims);
+    let signing_error = |e: &dyn fmt::Display| {
+        OrcaError::LedgerFailed(format!("could not sign token request: {}", e))
+    };
+    let key = RsaKeyPair::from_pkcs8(&account.private_key).map_err(|e| signing_error(&e))?;
+    let mut signature = vec![0u8; key.public().modulus_len()];
+    key.sign(
+        &RSA_PKCS1_SHA256,
+        &SystemRandom::new(),
+        message.as_bytes(),
+        &mut signature,
+    )
+    .map_err(|e| signing_error(&e))?;
+    Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+fn access_token(agent: &ureq::Agent, account: &ServiceAccount) -> Result<String, OrcaError> {
+    if let Some((token, valid_until)) = TOKENS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&account.client_email)
+    {
+        if Instant::now() < *valid_until {
+            return Ok(token.clone());
+        }
+    }
+    let url = &account.token_uri;
+    let assertion = token_assertion(account)?;
+    let response = agent
+        .post(url)
+        .send_form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", &assertion),
+        ])
+        .map_err(|e| ledger_error(request_error(url, e)))?;
+    let body = read_json(url, response).map_err(ledger_error)?;
+    let token = body
+        .get("access_token")
+        .and_then(Value::as_str)
+        .ok_or_else(|| OrcaError::LedgerFailed(format!("{}: response has no access_token", url)))?
+        .to_string();
+    let expires_in = Duration::from_secs(
+        body.get("expires_in")
+            .and_then(Value::as_u64)
+            .unwrap_or(3600),
+    );
+    TOKENS.lock().unwrap_or_else(|e| e.into_inner()).insert(
+        account.client_email.clone(),
+        (
+            token.clone(),
+            Instant::now() + expires_in.saturating_sub(TOKEN_MARGIN),
+        ),
+    );
+    Ok(token)
+}
+
+fn ledger_error(error: OrcaError) -> OrcaError {
+    match error {
+        OrcaError::UploadFailed(message) => OrcaError::LedgerFailed(message),
+        other => other,
+    }
+}
+
+/// Append via `values:append`, adding the header row to an empty sheet first.
+fn append_sheet(
+    spreadsheet_id: &str,
+    sheet: &str,
+    account: &ServiceAccount,
+    api_base: &str,
+    timeout_secs: f64,
+    row: &LedgerRow,
+) -> Result<String, OrcaError> {
+    let timeout = Duration::from_secs_f64(timeout_secs.max(0.0));
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let token = access_token(&agent, account)?;
+    let values_url = format!(
+        "{}/v4/spreadsheets/{}/values/{}",
+        api_base.trim_end_matches('/'),
+        spreadsheet_id,
+        url_segment(&format!("{}!A1", sheet))
+    );
+    let auth = format!("Bearer {}", token);
+
+    let existing = agent
+        .get(&values_url)
+        .set("Authorization", &auth)
+        .call()
+        .map_err(|e| ledger_error(request_error(&values_url, e)))?;
+    let existing = read_json(&values_url, existing).map_err(ledger_error)?;
+    let mut values = Vec::new();
+    if existing.get("values").is_none() {
+        values.push(json!(COLUMNS));
+    }
+    values.push(Value::Array(row.json_values()));
+
+    let append_url = format!("{}:append", values_url);
+    let response = agent
+        .post(&append_url)
+        .query("valueInputOption", "USER_ENTERED")
+        .query("insertDataOption", "INSERT_ROWS")
+        .set("Authorization", &auth)
+        .set("Content-Type", "application/json")
+        .send_string(&json!({ "values": values }).to_string())
+        .map_err(|e| ledger_error(request_error(&append_url, e)))?;
+    let body = read_json(&append_url, response).map_err(ledger_error)?;
+    Ok(body
+        .pointer("/updates/updatedRange")
+        .and_then(Value::as_str)
+        .unwrap_or(sheet)
+        .to_string())
+}
+
+/// Percent-encode a sheet range for use as one path segment.
+fn url_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'!' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Book one quote; returns the CSV file or the sheet range written to.
+pub fn append(config: &LedgerConfig, row: &LedgerRow) -> Result<String, OrcaError> {
+    match &config.backend {
+        Backend::Csv { dir, rotation } => append_csv(dir, rotation, row),
+        Backend::Sheets {
+            spreadsheet_id,
+            sheet,
+            account,
+            api_base,
+            timeout_secs,
+        } => append_sheet(spreadsheet_id, sheet, account, api_base, *timeout_secs, row),
+    }
+}
+
+/// Create a ledger that appends quotes to CSV files in `directory`
+///
+/// `rotation` is "monthly" (one file per month), "yearly" or "none".
+#[pyfunction]
+#[pyo3(signature = (directory, rotation="monthly".to_string()))]
+pub fn create_csv_ledger(directory: String, rotation: String) -> PyResult<LedgerConfig> {
+    panic_boundary::catch(|| {
+        if !["monthly", "yearly", "none"].contains(&rotation.as_str()) {
+            return Err(OrcaError::InvalidConfig {
+                path: directory,
+                message: format!("unknown ledger rotation {:?}", rotation),
+            }
+            .into());
+        }
+        Ok(LedgerConfig {
+            backend: Backend::Csv {
+                dir: PathBuf::from(directory),
+                rotation,
+            },
+        })
+    })
+}
+
+/// Create a ledger that appends quotes to a Google Sheet as a service account
+///
+/// Share the spreadsheet with the account's e-mail address (as an editor).
+#[pyfunction]
+#[pyo3(signature = (spreadsheet_id, service_account_path, sheet="Quotes".to_string(), api_base="https://sheets.googleapis.com".to_string(), timeout_secs=30.0))]
+pub fn create_sheets_ledger(
+    spreadsheet_id: String,
+    service_account_path: String,
+    sheet: String,
+    api_base: String,
+    timeout_secs: f64,
+) -> PyResult<LedgerConfig> {
+    panic_boundary::catch(|| {
+        let account = load_service_account(Path::new(&service_account_path))?;
+        Ok(LedgerConfig {
+            backend: Backend::Sheets {
+                spreadsheet_id,
+                sheet,
+                account,
+                api_base,
+                timeout_secs,
+            },
+        })
+    })
+}
+
+/// Append a completed quote to the ledger; returns the file or sheet range written
+#[pyfunction]
+#[pyo3(signature = (ledger_config, quote_id, cost_breakdown, customer_name=None, filename=None, payment_url=None))]
+pub fn append_quote_to_ledger(
+    py: Python<'_>,
+    ledger_config: PyRef<'_, LedgerConfig>,
+    quote_id: String,
+    cost_breakdown: PyRef<'_, CostBreakdown>,
+    customer_name: Option<String>,
+    filename: Option<String>,
+    payment_url: Option<String>,
+) -> PyResult<String> {
+    panic_boundary::catch(|| {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let (year, month, day, hour, minute, second) = utc_datetime(secs);
+        let row = LedgerRow {
+            timestamp: format!(
+                "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+                year, month, day, hour, minute, second
+            ),
+            quote_id,
+            customer: customer_name.unwrap_or_default(),
+            file: filename.unwrap_or_default(),
+            cost: cost_breakdown.clone(),
+            payment_url: payment_url.unwrap_or_default(),
+        };
+        let config = ledger_config.clone();
+        Ok(py.allow_threads(|| append(&config, &row))?)
+    })
+}
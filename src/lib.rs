@@ -1,15 +1,192 @@
 use pyo3::prelude::*;
 use pyo3_asyncio::tokio::future_into_py;
+use rayon::prelude::*;
 use regex::Regex;
 use once_cell::sync::Lazy;
 use sanitize_filename::sanitize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
-use tokio::fs::File;
-use tokio::io::{AsyncBufReadExt, BufReader as AsyncBufReader};
+
+mod archives;
+mod async_validation;
+mod audit;
+mod build_plate;
+mod business_calendar;
+mod cancellation;
+mod events;
+mod farm_load;
+mod file_sniffing;
+mod fleet;
+mod gcode_cache;
+mod gcode_scan;
+mod geometry;
+mod decimation;
+mod health;
+mod http_upload;
+mod inventory;
+mod job_bundle;
+mod job_queue;
+mod json_log;
+mod ledger;
+mod malware_scan;
+mod material_comparison;
+mod mapped_file;
+mod materials;
+mod memory_limits;
+mod mesh_formats;
+mod mesh_integrity;
+mod mesh_repair;
+mod mesh_statistics;
+mod mesh_warnings;
+mod metrics;
+mod moonraker;
+mod obj_materials;
+mod obj_objects;
+mod octoprint;
+mod overhangs;
+mod order;
+mod panic_boundary;
+mod paynow;
+mod portal_token;
+mod png;
+mod postprocess;
+mod preview;
+mod print_history;
+mod quote_store;
+mod payments;
+mod profile_discovery;
+mod profile_lint;
+mod profile_mapping;
+mod profile_selection;
+mod pipeline;
+mod plating;
+mod qr;
+mod rate_limit;
+mod requoting;
+mod schemas;
+mod shipping;
+mod process_override;
+mod profile_bundle;
+mod profile_cache;
+mod profile_compat;
+mod profiles;
+mod slicer;
+mod slicer_discovery;
+mod slicer_failure;
+mod slicer_limits;
+mod slicer_pool;
+mod slicer_retry;
+mod stability;
+mod step_entities;
+mod stream_validation;
+mod stl_convert;
+mod time_of_use;
+mod upload_index;
+mod upload_session;
+mod validation_cache;
+mod validation_limits;
+mod vendor_sync;
+mod warmup;
+mod webhooks;
+mod workspace;
+mod xml_scan;
+
+use archives::{extract_archived_model, set_unpack_limit};
+use async_validation::{
+    validate_3d_model_async, validate_3mf_async, validate_amf_async, validate_obj_async,
+    validate_ply_async, validate_step_async, validate_stl_async,
+};
+use build_plate::check_fits_build_plate;
+use business_calendar::{create_business_calendar, promised_completion, BusinessCalendar};
+use cancellation::{create_cancellation_token, CancellationToken};
+use events::{
+    add_event_callback, add_mqtt_sink, add_webhook_sink, clear_event_sinks, emit_event,
+    flush_events, QuoteEvent,
+};
+use farm_load::{
+    create_farm_monitor, estimate_lead_time, farm_status, FarmMonitor, LeadTime, PrinterStatus,
+};
+use file_sniffing::detect_file_type;
+use fleet::{load_fleet, Fleet, FleetPrinter};
+use gcode_cache::{create_gcode_cache, GcodeCache};
+use geometry::{binary_stl_scan, SurfaceScan};
+use stability::Stability;
+use step_entities::StepSummary;
+use stream_validation::{
+    binary_stl_info, binary_stl_size, create_streaming_validator, AsciiStlScan, ObjScan, StepScan,
+    StreamingValidator,
+};
+use health::{health_check, DependencyStatus, HealthReport};
+use inventory::{
+    create_inventory, discover_available_materials, Inventory, MaterialAvailability, StockLevel,
+};
+use job_bundle::export_job_bundle;
+use job_queue::{queue_status, QueueStatus};
+use json_log::init_json_logging;
+use ledger::{append_quote_to_ledger, create_csv_ledger, create_sheets_ledger, LedgerConfig};
+use mapped_file::{set_mmap_threshold, ModelFile};
+use materials::{load_material_catalog, Material, MaterialCatalog};
+use memory_limits::{set_memory_limits, Budget};
+use mesh_formats::{validate_3mf, validate_amf, validate_ply};
+use mesh_integrity::{check_mesh_integrity, MeshIntegrity};
+use mesh_repair::{repair_mesh, MeshRepair};
+use mesh_statistics::{mesh_stats, MeshStats};
+use decimation::{decimate_mesh, Decimation};
+use metrics::{enable_metrics, gather_metrics, record_quote_metric, serve_metrics, set_queue_depth};
+use moonraker::{create_moonraker_config, send_to_moonraker, MoonrakerConfig, MoonrakerUpload};
+use obj_materials::ObjMaterials;
+use obj_objects::{list_obj_objects, split_obj_objects, ObjObject};
+use octoprint::{create_octoprint_config, send_to_octoprint, OctoPrintConfig, OctoPrintUpload};
+use malware_scan::{create_malware_scanner, scan_upload, MalwareScanner};
+use material_comparison::{quote_materials, MaterialComparison, MaterialQuote};
+use order::{run_order_pipeline, OrderPart, OrderQuote};
+use rate_limit::{create_rate_limiter, RateLimiter};
+use requoting::{requote, Requote};
+use plating::{plan_plates, PlatePlan};
+use postprocess::{create_postprocess_config, postprocess_gcode, PostProcessConfig};
+use preview::{render_model_preview, render_preview};
+use stl_convert::convert_stl;
+use print_history::{create_print_history, CorrectionFactor, EstimateCalibration, PrintHistory};
+use quote_store::{create_quote_store, PriceAdjustment, QuoteStore};
+use panic_boundary::InternalError;
+use payments::{create_payment_link, create_stripe_config, StripeConfig};
+use paynow::{generate_paynow_qr, paynow_payload};
+use portal_token::{sign_quote_token, verify_quote_token, QuoteToken};
+use process_override::{generate_process_override, PrintOptions};
+use profile_bundle::{export_profile_bundle, import_profile_bundle, BundleImport};
+use profile_cache::{create_profile_cache, ProfileCache};
+use profile_compat::{check_compatibility, CompatibilityReport};
+use profile_discovery::{discover_machines, discover_processes, MachineListing, ProcessListing};
+use profile_lint::{lint_profiles, LintIssue, ProfileLintReport};
+use profile_mapping::{list_unmapped_materials, resolve_filament_profile, FilamentResolution};
+use pipeline::{create_pipeline_config, run_quote_pipeline, PipelineConfig, QuoteResult};
+use profile_selection::{resolve_profile_paths, ProfilePaths};
+use profiles::{load_profile, resolve_profile, Profile};
+use schemas::{export_schemas, to_dict};
+use shipping::{
+    create_callback_shipping, create_easypost_shipping, get_shipping_rates, Parcel, ShippingConfig,
+    ShippingRate,
+};
+use slicer::{acquire_slicer_slot, execute_slicer_async, set_slicer_concurrency, SlicerPermit};
+use slicer_discovery::{detect_slicer, SlicerInfo};
+use slicer_failure::{SlicerFailedError, SlicerFailure};
+use slicer_limits::{create_slicer_limits, SlicerLimits};
+use slicer_pool::{create_slicer_pool, SlicerJob, SlicerPool, SlicerPoolStats};
+use slicer_retry::{create_slicer_retry_policy, SlicerAttempt, SlicerRetryPolicy};
+use time_of_use::{create_time_of_use_pricing, quote_off_peak, OffPeakPrice, TimeOfUsePricing};
+use upload_index::{find_duplicate, record_upload};
+use upload_session::{create_upload_session_store, UploadSession, UploadSessionStore};
+use validation_cache::{configure_validation_cache, validation_cache_stats, ValidationCacheStats};
+use validation_limits::{create_validation_limits, ValidationLimits};
+use vendor_sync::{sync_vendor_profiles, VendorSync};
+use warmup::{warm_up, WarmUpReport};
+use webhooks::{parse_stripe_webhook, parse_telegram_webhook, WebhookEvent};
+use workspace::{create_job_workspace, JobWorkspace};
 
 #[derive(Error, Debug)]
 pub enum ValidationError {
@@ -23,11 +200,126 @@ pub enum ValidationError {
 
 impl From<ValidationError> for PyErr {
     fn from(err: ValidationError) -> PyErr {
-        pyo3::exceptions::PyValueError::new_err(err.to_string())
+        match err {
+            ValidationError::IoError(e) if memory_limits::is_limit_error(&e) => {
+                pyo3::exceptions::PyMemoryError::new_err(e.to_string())
+            }
+            // Keep OS errors as OSError subclasses (PermissionError, ...).
+            ValidationError::IoError(e) => e.into(),
+            other => pyo3::exceptions::PyValueError::new_err(other.to_string()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum OrcaError {
+    #[error("Profile not found: {0}")]
+    ProfileNotFound(String),
+    #[error("Invalid profile {path}: {message}")]
+    InvalidProfile { path: String, message: String },
+    #[error("Invalid configuration {path}: {message}")]
+    InvalidConfig { path: String, message: String },
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+    #[error("No {profile_type} profile for a {requested} mm nozzle (available: {available:?})")]
+    NozzleUnavailable { profile_type: String, requested: f64, available: Vec<f64> },
+    #[error("Invalid profile bundle {path}: {message}")]
+    InvalidBundle { path: String, message: String },
+    #[error("Checksum mismatch for {path}: expected {expected}, got {actual}")]
+    ChecksumMismatch { path: String, expected: String, actual: String },
+    #[error("Download failed: {0}")]
+    DownloadFailed(String),
+    #[error("Upload failed: {0}")]
+    UploadFailed(String),
+    #[error("Payment link failed: {0}")]
+    PaymentFailed(String),
+    #[error("Shipping rates failed: {0}")]
+    ShippingFailed(String),
+    #[error("Invalid payment request: {0}")]
+    InvalidPaymentRequest(String),
+    #[error("Ledger update failed: {0}")]
+    LedgerFailed(String),
+    #[error("Event delivery failed: {0}")]
+    EventDeliveryFailed(String),
+    #[error("Invalid quote token: {0}")]
+    InvalidToken(String),
+    #[error("Invalid webhook signature: {0}")]
+    InvalidSignature(String),
+    #[error("Invalid webhook payload: {0}")]
+    InvalidWebhook(String),
+    #[error("Incompatible profiles:\n{0}")]
+    IncompatibleProfiles(String),
+    #[error("Invalid override: {0}")]
+    InvalidOverride(String),
+    #[error("Invalid model: {0}")]
+    InvalidModel(String),
+    #[error("{0} out of stock")]
+    OutOfStock(String),
+    #[error("Not enough {material} in stock: {needed_grams:.0} g needed, {remaining_grams:.0} g left")]
+    InsufficientStock { material: String, needed_grams: f64, remaining_grams: f64 },
+    #[error("Model is {model}, larger than the {machine} build volume of {build_volume}")]
+    ModelTooLarge { model: String, machine: String, build_volume: String },
+    #[error("No printer in the fleet can print {0}")]
+    NoSuitablePrinter(String),
+    #[error("Suspicious archive: {0}")]
+    SuspiciousArchive(String),
+    #[error("Upload {file} is infected: {signature}")]
+    MalwareFound { file: String, signature: String },
+    #[error("Malware scan failed: {0}")]
+    ScanFailed(String),
+    #[error("Invalid upload: {0}")]
+    InvalidUpload(String),
+    #[error("Slicer failed: {0}")]
+    SlicerFailed(String),
+    /// The slicer ran and exited with an error, classified from its output.
+    #[error("Slicer failed: {0}")]
+    SlicerExited(Box<SlicerFailure>),
+    #[error("Slicer did not finish within {seconds}s: {stderr}")]
+    SlicerTimeout { seconds: f64, stderr: String },
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+impl From<OrcaError> for PyErr {
+    fn from(err: OrcaError) -> PyErr {
+        match err {
+            OrcaError::IoError(ref e) if memory_limits::is_limit_error(e) => {
+                pyo3::exceptions::PyMemoryError::new_err(err.to_string())
+            }
+            OrcaError::ProfileNotFound(_) | OrcaError::FileNotFound(_) => {
+                pyo3::exceptions::PyFileNotFoundError::new_err(err.to_string())
+            }
+            OrcaError::IoError(_)
+            | OrcaError::DownloadFailed(_)
+            | OrcaError::UploadFailed(_)
+            | OrcaError::PaymentFailed(_)
+            | OrcaError::ShippingFailed(_)
+            | OrcaError::LedgerFailed(_)
+            | OrcaError::EventDeliveryFailed(_)
+            | OrcaError::ScanFailed(_) => {
+                pyo3::exceptions::PyOSError::new_err(err.to_string())
+            }
+            OrcaError::SlicerFailed(_) => pyo3::exceptions::PyRuntimeError::new_err(err.to_string()),
+            OrcaError::SlicerExited(ref failure) => Python::with_gil(|py| {
+                let exception = SlicerFailedError::new_err(err.to_string());
+                // As with InternalError: a missing attribute loses only the structured copy.
+                let _ = exception.value(py).setattr("failure", (**failure).clone().into_py(py));
+                exception
+            }),
+            OrcaError::SlicerTimeout { .. } => {
+                pyo3::exceptions::PyTimeoutError::new_err(err.to_string())
+            }
+            OrcaError::Cancelled(_) => {
+                pyo3::exceptions::asyncio::CancelledError::new_err(err.to_string())
+            }
+            _ => pyo3::exceptions::PyValueError::new_err(err.to_string()),
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct ModelInfo {
     #[pyo3(get)]
@@ -38,6 +330,57 @@ pub struct ModelInfo {
     pub is_valid: bool,
     #[pyo3(get)]
     pub error_message: Option<String>,
+    /// For a ZIP or gzip upload, the file inside that was validated.
+    #[pyo3(get)]
+    pub archive_entry: Option<String>,
+    /// Width, depth and height of a valid STL's bounding box, read while it
+    /// was validated; `None` for other formats.
+    #[pyo3(get)]
+    pub dimensions_mm: Option<(f64, f64, f64)>,
+    /// Area of a valid STL's downward-facing surface more than 45° from
+    /// vertical, leaving out faces on the bed, in mm²; `None` for other
+    /// formats.
+    #[pyo3(get)]
+    pub overhang_area_mm2: Option<f64>,
+    /// Whether that overhang is more than 10 mm², enough that the print
+    /// will need supports.
+    #[pyo3(get)]
+    pub needs_supports: Option<bool>,
+    /// Centre of mass, footprint and tipping risk of a valid STL; `None` for
+    /// other formats.
+    #[pyo3(get)]
+    pub stability: Option<Stability>,
+    /// Material libraries and materials a valid OBJ names, with those
+    /// libraries missing from beside it; `None` for other formats and OBJs
+    /// that name none.
+    #[pyo3(get)]
+    pub obj_materials: Option<ObjMaterials>,
+    /// Products, assembly placements and solids of a valid STEP file; `None`
+    /// for other formats.
+    #[pyo3(get)]
+    pub step_summary: Option<StepSummary>,
+    /// Problems that leave a valid STL or OBJ printable but point at its
+    /// export settings: triangles with no area, and for OBJ, repeated or
+    /// unused vertices.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Hex SHA-256 of a valid upload's bytes, for spotting the same model
+    /// uploaded again; `None` for invalid files.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Triangles in a valid mesh, with polygons counted as the triangles
+    /// they split into (for PLY, the faces its header declares); `None` for
+    /// STEP.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub triangle_count: Option<u64>,
+    /// Lines of a valid ASCII STL, OBJ or STEP file, up to the one ending
+    /// the model; `None` for binary and XML formats.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub line_count: Option<u64>,
 }
 
 #[pymethods]
@@ -50,18 +393,60 @@ impl ModelInfo {
     }
 }
 
+impl ModelInfo {
+    /// A valid file of `file_type`, with nothing yet read from it.
+    pub(crate) fn valid(file_type: &str, file_size: u64) -> Self {
+        ModelInfo {
+            file_type: file_type.to_string(),
+            file_size,
+            is_valid: true,
+            error_message: None,
+            archive_entry: None,
+            dimensions_mm: None,
+            overhang_area_mm2: None,
+            needs_supports: None,
+            stability: None,
+            obj_materials: None,
+            step_summary: None,
+            warnings: Vec::new(),
+            sha256: None,
+            triangle_count: None,
+            line_count: None,
+        }
+    }
+
+    /// A file of `file_type` rejected for `message`.
+    pub(crate) fn invalid(file_type: &str, file_size: u64, message: String) -> Self {
+        ModelInfo {
+            is_valid: false,
+            error_message: Some(message),
+            ..Self::valid(file_type, file_size)
+        }
+    }
+}
+
 /// Fast validation for STL files
 #[pyfunction]
-fn validate_stl(file_path: String) -> PyResult<ModelInfo> {
-    let path = Path::new(&file_path);
+#[pyo3(signature = (file_path, limits=None))]
+fn validate_stl(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<ModelInfo> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            limits.validate(Path::new(&file_path), |path| {
+                validation_cache::hashed(path, stl_info)
+            })
+        })?)
+    })
+}
+
+fn stl_info(path: &Path) -> Result<ModelInfo, ValidationError> {
 
     if !path.exists() {
-        return Ok(ModelInfo {
-            file_type: "stl".to_string(),
-            file_size: 0,
-            is_valid: false,
-            error_message: Some("File not found".to_string()),
-        });
+        return Ok(ModelInfo::invalid("stl", 0, "File not found".to_string()));
     }
 
     let file_size = fs::metadata(path)?.len();
@@ -71,46 +456,28 @@ fn validate_stl(file_path: String) -> PyResult<ModelInfo> {
     let mut header = [0u8; 5];
     if file.read_exact(&mut header).is_err() {
         // File is too small to be a valid STL of any kind.
-        return Ok(ModelInfo {
-            file_type: "stl".to_string(),
+        return Ok(ModelInfo::invalid(
+            "stl",
             file_size,
-            is_valid: false,
-            error_message: Some("File too small to be valid STL".to_string()),
-        });
+            "File too small to be valid STL".to_string(),
+        ));
     }
 
     if header.starts_with(b"solid") {
-        // ASCII STL: Use a buffered reader on the existing file handle.
-        // We must seek back to the start to read from the beginning.
-        file.seek(SeekFrom::Start(0))?;
-        let reader = BufReader::new(file);
-        let mut found_endsolid = false;
-        for line in reader.lines() {
-            if line?.trim().starts_with("endsolid") {
-                found_endsolid = true;
+        // ASCII STL: read the existing file handle again from the start,
+        // from a memory map when it is large.
+        let source = ModelFile::new(file)?;
+        let mut scan = AsciiStlScan::new();
+        for line in source.lines(Budget::MeshAnalysis) {
+            if scan.line(line?.trim()) {
                 break;
             }
         }
-        
-        Ok(ModelInfo {
-            file_type: "stl".to_string(),
-            file_size,
-            is_valid: found_endsolid,
-            error_message: if found_endsolid { 
-                None 
-            } else { 
-                Some("Invalid ASCII STL format - missing endsolid".to_string()) 
-            },
-        })
+        Ok(scan.finish(file_size))
     } else {
         // Binary STL: Efficiently validate without reading the whole file.
         if file_size < 84 {
-            return Ok(ModelInfo {
-                file_type: "stl".to_string(),
-                file_size,
-                is_valid: false,
-                error_message: Some("Binary STL too small".to_string()),
-            });
+            return Ok(ModelInfo::invalid("stl", file_size, "Binary STL too small".to_string()));
         }
 
         // Read only the triangle count from bytes 80-83.
@@ -119,174 +486,151 @@ fn validate_stl(file_path: String) -> PyResult<ModelInfo> {
         file.read_exact(&mut count_buffer)?;
         let triangle_count = u32::from_le_bytes(count_buffer);
 
-        let expected_size = 84u64.saturating_add(triangle_count as u64 * 50);
-
-        if file_size != expected_size {
-            Ok(ModelInfo {
-                file_type: "stl".to_string(),
-                file_size,
-                is_valid: false,
-                error_message: Some(format!(
-                    "Binary STL size mismatch. Expected {}, got {}",
-                    expected_size,
-                    file_size
-                )),
-            })
+        // The triangles are only read when the size is right.
+        let surface = if file_size == binary_stl_size(triangle_count) {
+            binary_stl_scan(path, triangle_count)?
         } else {
-            Ok(ModelInfo {
-                file_type: "stl".to_string(),
-                file_size,
-                is_valid: true,
-                error_message: None,
-            })
-        }
+            SurfaceScan::new()
+        };
+        Ok(binary_stl_info(file_size, triangle_count, &surface))
     }
 }
 
 /// Basic validation for OBJ files
 #[pyfunction]
-fn validate_obj(file_path: String) -> PyResult<ModelInfo> {
-    let path = Path::new(&file_path);
-    
+#[pyo3(signature = (file_path, limits=None))]
+fn validate_obj(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<ModelInfo> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            limits.validate(Path::new(&file_path), |path| {
+                validation_cache::hashed(path, obj_info)
+            })
+        })?)
+    })
+}
+
+fn obj_info(path: &Path) -> Result<ModelInfo, ValidationError> {
     if !path.exists() {
-        return Ok(ModelInfo {
-            file_type: "obj".to_string(),
-            file_size: 0,
-            is_valid: false,
-            error_message: Some("File not found".to_string()),
-        });
+        return Ok(ModelInfo::invalid("obj", 0, "File not found".to_string()));
     }
 
     let file_size = fs::metadata(path)?.len();
-    let file = fs::File::open(path)?;
-    let reader = BufReader::new(file);
-    
-    // Basic OBJ validation - check for vertices and faces using buffered reading
-    let mut has_vertices = false;
-    let mut has_faces = false;
-    
-    for line in reader.lines() {
-        let line = line?;
-        let trimmed = line.trim();
-        
-        if trimmed.starts_with("v ") {
-            has_vertices = true;
-        } else if trimmed.starts_with("f ") {
-            has_faces = true;
-        }
-        
-        // Early exit once both are found
-        if has_vertices && has_faces {
-            break;
-        }
-    }
-    
-    if has_vertices && has_faces {
-        Ok(ModelInfo {
-            file_type: "obj".to_string(),
-            file_size,
-            is_valid: true,
-            error_message: None,
-        })
-    } else {
-        Ok(ModelInfo {
-            file_type: "obj".to_string(),
-            file_size,
-            is_valid: false,
-            error_message: Some("Invalid OBJ format - missing vertices or faces".to_string()),
-        })
+    let source = ModelFile::open(path)?;
+    let mut scan = ObjScan::default();
+    for line in source.lines(Budget::MeshAnalysis) {
+        scan.line(line?.trim());
     }
+    Ok(scan.finish(path, file_size))
 }
 
 /// Basic validation for STEP files
 #[pyfunction]
-fn validate_step(file_path: String) -> PyResult<ModelInfo> {
-    let path = Path::new(&file_path);
-    
+#[pyo3(signature = (file_path, limits=None))]
+fn validate_step(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<ModelInfo> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            limits.validate(Path::new(&file_path), |path| {
+                validation_cache::hashed(path, step_info)
+            })
+        })?)
+    })
+}
+
+fn step_info(path: &Path) -> Result<ModelInfo, ValidationError> {
     if !path.exists() {
-        return Ok(ModelInfo {
-            file_type: "step".to_string(),
-            file_size: 0,
-            is_valid: false,
-            error_message: Some("File not found".to_string()),
-        });
+        return Ok(ModelInfo::invalid("step", 0, "File not found".to_string()));
     }
 
     let file_size = fs::metadata(path)?.len();
-    let file = fs::File::open(path)?;
-    let reader = BufReader::new(file);
-    
-    // Basic STEP validation - check for required headers using buffered reading
-    let mut has_iso_header = false;
-    let mut has_header_section = false;
-    let mut has_data_section = false;
-    let mut has_end_iso = false;
-    let mut first_line = true;
-    
-    for line in reader.lines() {
-        let line = line?;
-        let trimmed = line.trim();
-        
-        // Check first line for ISO header
-        if first_line {
-            has_iso_header = trimmed.starts_with("ISO-10303");
-            first_line = false;
-        }
-        
-        // Check for required sections
-        if trimmed == "HEADER;" {
-            has_header_section = true;
-        } else if trimmed == "DATA;" {
-            has_data_section = true;
-        } else if trimmed.starts_with("END-ISO-10303") {
-            has_end_iso = true;
-            break; // This should be near the end, so we can stop here
+    let source = ModelFile::open(path)?;
+    let mut scan = StepScan::default();
+    for line in source.lines(Budget::MeshAnalysis) {
+        if scan.line(line?.trim()) {
+            break;
         }
     }
-    
-    if has_iso_header && has_header_section && has_data_section && has_end_iso {
-        Ok(ModelInfo {
-            file_type: "step".to_string(),
-            file_size,
-            is_valid: true,
-            error_message: None,
-        })
-    } else {
-        let mut missing_parts = Vec::new();
-        if !has_iso_header { missing_parts.push("ISO header"); }
-        if !has_header_section { missing_parts.push("HEADER section"); }
-        if !has_data_section { missing_parts.push("DATA section"); }
-        if !has_end_iso { missing_parts.push("END-ISO section"); }
-        
-        Ok(ModelInfo {
-            file_type: "step".to_string(),
-            file_size,
-            is_valid: false,
-            error_message: Some(format!("Invalid STEP format - missing: {}", missing_parts.join(", "))),
-        })
-    }
+    Ok(scan.finish(file_size))
 }
 
 /// Validate 3D model file based on extension
 #[pyfunction]
-fn validate_3d_model(file_path: String) -> PyResult<ModelInfo> {
-    let path = Path::new(&file_path);
-    
+#[pyo3(signature = (file_path, limits=None))]
+fn validate_3d_model(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<ModelInfo> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            limits.validate(Path::new(&file_path), validation_cache::cached_model_info)
+        })?)
+    })
+}
+
+/// Extensions of the model files `model_info` validates, archives aside.
+pub(crate) const MODEL_EXTENSIONS: &[&str] = &["stl", "obj", "step", "stp", "3mf", "amf", "ply"];
+
+pub(crate) fn model_info(path: &Path) -> Result<ModelInfo, ValidationError> {
+    if let Some(info) = file_sniffing::content_mismatch(path)? {
+        return Ok(info);
+    }
     match path.extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase()) {
-        Some(ext) if ext == "stl" => validate_stl(file_path),
-        Some(ext) if ext == "obj" => validate_obj(file_path),
-        Some(ext) if ext == "step" || ext == "stp" => validate_step(file_path),
-        _ => Ok(ModelInfo {
-            file_type: "unknown".to_string(),
-            file_size: 0,
-            is_valid: false,
-            error_message: Some("Unsupported file type".to_string()),
-        }),
+        Some(ext) if ext == "stl" => stl_info(path),
+        Some(ext) if ext == "obj" => obj_info(path),
+        Some(ext) if ext == "step" || ext == "stp" => step_info(path),
+        Some(ext) if ext == "3mf" => mesh_formats::three_mf_info(path),
+        Some(ext) if ext == "amf" => mesh_formats::amf_info(path),
+        Some(ext) if ext == "ply" => mesh_formats::ply_info(path),
+        Some(ext) if ext == "zip" || ext == "gz" => archives::archive_info(path),
+        _ => Ok(ModelInfo::invalid("unknown", 0, "Unsupported file type".to_string())),
     }
 }
 
+/// Validate many model files in parallel, releasing the GIL
+///
+/// Results keep the order of `paths`; a file that cannot be read is reported as
+/// invalid instead of failing the whole batch.
+#[pyfunction]
+#[pyo3(signature = (paths, limits=None))]
+fn validate_many(
+    py: Python<'_>,
+    paths: Vec<String>,
+    limits: Option<ValidationLimits>,
+) -> PyResult<Vec<ModelInfo>> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            paths
+                .par_iter()
+                .map(|file_path| {
+                    let path = Path::new(file_path);
+                    let info = limits.validate(path, validation_cache::cached_model_info);
+                    info.unwrap_or_else(|err| {
+                        let file_type = path
+                            .extension()
+                            .and_then(|s| s.to_str())
+                            .map_or_else(|| "unknown".to_string(), |s| s.to_lowercase());
+                        ModelInfo::invalid(&file_type, 0, err.to_string())
+                    })
+                })
+                .collect()
+        }))
+    })
+}
+
 /// Enhanced slicing result with performance-critical calculations in Rust
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct SlicingResult {
     #[pyo3(get)]
@@ -295,6 +639,17 @@ pub struct SlicingResult {
     pub filament_weight_grams: f32,
     #[pyo3(get)]
     pub layer_count: Option<u32>,
+    /// Extruded filament length, when the slicer reports it.
+    #[pyo3(get)]
+    pub filament_length_mm: Option<f32>,
+    /// Key of the G-code kept in the G-code cache, when caching is on.
+    #[pyo3(get, set)]
+    pub gcode_cache_key: Option<String>,
+    /// Each run of the slicer it took, more than one when a crash was retried.
+    /// Missing from quotes stored before it.
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub slicer_attempts: Vec<SlicerAttempt>,
 }
 
 #[pymethods]
@@ -308,27 +663,88 @@ impl SlicingResult {
 }
 
 /// File cleanup statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[pyclass]
 pub struct CleanupStats {
     #[pyo3(get)]
     pub files_cleaned: u32,
     #[pyo3(get)]
     pub bytes_freed: u64,
+    #[pyo3(get)]
+    pub files_by_extension: HashMap<String, u32>,
+    #[pyo3(get)]
+    pub bytes_by_extension: HashMap<String, u64>,
+    #[pyo3(get)]
+    pub oldest_deleted: Option<f64>,
+    #[pyo3(get)]
+    pub newest_deleted: Option<f64>,
+    #[pyo3(get)]
+    pub errors: Vec<String>,
+}
+
+impl CleanupStats {
+    /// Account for a deleted file; files without an extension are grouped under "".
+    pub fn record_removal(&mut self, path: &Path, metadata: &fs::Metadata) {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        self.files_cleaned += 1;
+        self.bytes_freed += metadata.len();
+        *self.files_by_extension.entry(extension.clone()).or_insert(0) += 1;
+        *self.bytes_by_extension.entry(extension).or_insert(0) += metadata.len();
+
+        // Timestamps are the modification times of the deleted files, as Unix seconds.
+        if let Ok(modified) = metadata.modified() {
+            let timestamp = audit::unix_timestamp(modified);
+            self.oldest_deleted = Some(self.oldest_deleted.map_or(timestamp, |t| t.min(timestamp)));
+            self.newest_deleted = Some(self.newest_deleted.map_or(timestamp, |t| t.max(timestamp)));
+        }
+    }
+
+    pub fn record_error(&mut self, path: &Path, err: &std::io::Error) {
+        self.errors.push(format!("{}: {}", path.display(), err));
+    }
+
+    /// Append the sweep to the audit log; a failure to is kept in `errors`
+    /// rather than raised, since the files are already gone by then.
+    fn audit(&mut self, log_path: &str, upload_dir: &str, max_age_hours: u64) {
+        let payload = self.to_audit_payload(upload_dir, max_age_hours);
+        if let Err(e) = audit::append_event(Path::new(log_path), "cleanup", payload) {
+            self.record_error(Path::new(log_path), &e);
+        }
+    }
+
+    fn to_audit_payload(&self, upload_dir: &str, max_age_hours: u64) -> serde_json::Value {
+        serde_json::json!({
+            "upload_dir": upload_dir,
+            "max_age_hours": max_age_hours,
+            "files_cleaned": self.files_cleaned,
+            "bytes_freed": self.bytes_freed,
+            "files_by_extension": self.files_by_extension,
+            "bytes_by_extension": self.bytes_by_extension,
+            "oldest_deleted": self.oldest_deleted,
+            "newest_deleted": self.newest_deleted,
+            "errors": self.errors,
+        })
+    }
 }
 
 #[pymethods]
 impl CleanupStats {
     fn __str__(&self) -> String {
         format!(
-            "CleanupStats(files={}, bytes={})",
-            self.files_cleaned, self.bytes_freed
+            "CleanupStats(files={}, bytes={}, errors={})",
+            self.files_cleaned,
+            self.bytes_freed,
+            self.errors.len()
         )
     }
 }
 
 /// Cost breakdown calculation performed in Rust for enhanced performance
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct CostBreakdown {
     #[pyo3(get)]
@@ -374,6 +790,20 @@ static TIME_MINUTE_ONLY_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+)$")
 static FILAMENT_WEIGHT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+\.?\d*)\s*g").unwrap());
 static LAYER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+)").unwrap());
 
+/// Compile every regex now instead of on the first quote that needs it.
+pub(crate) fn init_regexes() {
+    for regex in [
+        &TIME_HOUR_REGEX,
+        &TIME_MINUTE_REGEX,
+        &TIME_MINUTE_ONLY_REGEX,
+        &FILAMENT_WEIGHT_REGEX,
+        &LAYER_REGEX,
+    ] {
+        Lazy::force(regex);
+    }
+    profile_discovery::init_regexes();
+}
+
 /// Parse time string to minutes using Rust regex for performance
 fn parse_time_string_to_minutes(time_str: &str) -> u32 {
     let clean_str = time_str.trim().to_lowercase();
@@ -413,81 +843,159 @@ fn parse_filament_weight(line: &str) -> Option<f32> {
     }
 }
 
-/// High-performance G-code and metadata parsing in Rust
-#[pyfunction]
-fn parse_slicer_output(py: Python, output_dir: String) -> PyResult<&PyAny> {
-    future_into_py(py, async move {
-        let dir_path = PathBuf::from(output_dir);
-        let mut gcode_path: Option<PathBuf> = None;
-        
-        // Find the first .gcode file
-        let mut entries = tokio::fs::read_dir(&dir_path).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            if entry.path().extension().and_then(|s| s.to_str()) == Some("gcode") {
-                gcode_path = Some(entry.path());
-                break;
+/// Filament length from `; filament used [mm] = 1234.5, 0.0`, summed over extruders.
+fn parse_filament_length(line: &str) -> Option<f32> {
+    let values = line.rsplit(['=', ':']).next()?;
+    values
+        .split(',')
+        .map(|value| value.trim().parse::<f32>().ok())
+        .sum()
+}
+
+/// Filament properties used to turn an extruded length into a weight.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FilamentSpec {
+    /// g/cm³
+    pub density: f64,
+    /// mm
+    pub diameter: f64,
+}
+
+impl FilamentSpec {
+    /// From a filament profile's `filament_density`, assuming 1.75 mm when no diameter is set.
+    pub(crate) fn from_profile(profile: &Profile) -> Option<Self> {
+        Self::new(profile.filament_density, profile.filament_diameter)
+    }
+
+    /// From a catalog material's density and diameter.
+    pub(crate) fn from_material(material: &Material) -> Option<Self> {
+        Self::new(Some(material.density), Some(material.diameter))
+    }
+
+    pub(crate) fn new(density: Option<f64>, diameter: Option<f64>) -> Option<Self> {
+        let density = density.filter(|d| *d > 0.0)?;
+        let diameter = diameter.filter(|d| *d > 0.0).unwrap_or(1.75);
+        Some(FilamentSpec { density, diameter })
+    }
+
+    pub(crate) fn grams_for_length(&self, length_mm: f64) -> f64 {
+        let radius = self.diameter / 2.0;
+        // mm³ → cm³
+        std::f64::consts::PI * radius * radius * length_mm / 1000.0 * self.density
+    }
+}
+
+/// Accumulates print metadata from G-code comments; later values win, so the
+/// totals OrcaSlicer writes after the last move override header estimates.
+#[derive(Debug, Default)]
+pub(crate) struct GcodeMetadata {
+    print_time_minutes: u32,
+    filament_weight_grams: f32,
+    layer_count: Option<u32>,
+    filament_length_mm: Option<f32>,
+}
+
+impl GcodeMetadata {
+    pub(crate) fn feed(&mut self, line: &str) {
+        let lower_line = line.to_lowercase();
+
+        // Parse print time
+        if lower_line.contains("; estimated printing time") || lower_line.contains("; print time") {
+            if let Some(time_part) = line.split(':').next_back() {
+                self.print_time_minutes = parse_time_string_to_minutes(time_part.trim());
             }
         }
-        
-        let gcode_path = gcode_path.ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::NotFound, "No .gcode file found")
-        })?;
-        
-        let file = File::open(gcode_path).await?;
-        let reader = AsyncBufReader::new(file);
-        let mut lines = reader.lines();
-        
-        let mut print_time_minutes = 0u32;
-        let mut filament_weight_grams = 0.0f32;
-        let mut layer_count: Option<u32> = None;
-        
-        // Read first 200 lines for metadata (increased from 100 for better coverage)
-        for _ in 0..200 {
-            if let Some(line) = lines.next_line().await? {
-                let lower_line = line.to_lowercase();
-                
-                // Parse print time
-                if lower_line.contains("; estimated printing time") || lower_line.contains("; print time") {
-                    if let Some(time_part) = line.split(':').last() {
-                        print_time_minutes = parse_time_string_to_minutes(time_part.trim());
-                    }
-                }
-                // Parse filament usage
-                else if lower_line.contains("; filament used") || lower_line.contains("; material volume") {
-                    if let Some(weight) = parse_filament_weight(&line) {
-                        filament_weight_grams = weight;
-                    }
-                }
-                // Parse layer count
-                else if lower_line.contains("; layer_count") || lower_line.contains("; total layers") {
-                    if let Some(cap) = LAYER_REGEX.captures(&line) {
-                        layer_count = cap[1].parse::<u32>().ok();
-                    }
-                }
-            } else {
-                break;
+        // Parse filament usage
+        else if lower_line.contains("; filament used [mm]") {
+            if let Some(length) = parse_filament_length(line) {
+                self.filament_length_mm = Some(length);
+            }
+        }
+        else if lower_line.contains("; filament used") || lower_line.contains("; material volume") {
+            if let Some(weight) = parse_filament_weight(line) {
+                self.filament_weight_grams = weight;
+            }
+        }
+        // Parse layer count
+        else if lower_line.contains("; layer_count") || lower_line.contains("; total layers") {
+            if let Some(cap) = LAYER_REGEX.captures(line) {
+                self.layer_count = cap[1].parse::<u32>().ok();
+            }
+        }
+    }
+
+    /// A missing weight is derived from the filament length when the filament's density
+    /// is known.
+    pub(crate) fn finish(mut self, filament: Option<FilamentSpec>) -> SlicingResult {
+        if self.filament_weight_grams == 0.0 {
+            if let (Some(length), Some(filament)) = (self.filament_length_mm, filament) {
+                self.filament_weight_grams = filament.grams_for_length(length as f64) as f32;
             }
         }
-        
+
         // Set defaults if parsing failed
-        if print_time_minutes == 0 {
-            print_time_minutes = 60; // 1 hour default
+        if self.print_time_minutes == 0 {
+            self.print_time_minutes = 60; // 1 hour default
         }
-        if filament_weight_grams == 0.0 {
-            filament_weight_grams = 20.0; // 20g default
+        if self.filament_weight_grams == 0.0 {
+            self.filament_weight_grams = 20.0; // 20g default
         }
-        
-        Ok(SlicingResult {
-            print_time_minutes,
-            filament_weight_grams,
-            layer_count,
-        })
-    })
+
+        SlicingResult {
+            print_time_minutes: self.print_time_minutes,
+            filament_weight_grams: self.filament_weight_grams,
+            layer_count: self.layer_count,
+            filament_length_mm: self.filament_length_mm,
+            gcode_cache_key: None,
+            slicer_attempts: Vec::new(),
+        }
+    }
 }
 
-/// Enhanced pricing calculation in Rust for performance
+/// First `.gcode` file in a slicer output directory.
+pub(crate) fn find_gcode_file(dir_path: &Path) -> std::io::Result<PathBuf> {
+    for entry in fs::read_dir(dir_path)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("gcode") {
+            return Ok(path);
+        }
+    }
+    Err(std::io::Error::new(std::io::ErrorKind::NotFound, "No .gcode file found"))
+}
+
+/// Blocking counterpart of `parse_slicer_output` for use inside Rust pipelines.
+pub(crate) fn parse_slicer_output_dir(
+    dir_path: &Path,
+    filament: Option<FilamentSpec>,
+) -> std::io::Result<SlicingResult> {
+    let file = fs::File::open(find_gcode_file(dir_path)?)?;
+    Ok(gcode_scan::scan_metadata(file)?.finish(filament))
+}
+
+/// High-performance G-code and metadata parsing in Rust; with a filament density,
+/// a G-code file that only reports length gets its weight from the length
 #[pyfunction]
-fn calculate_quote_rust(
+#[pyo3(signature = (output_dir, filament_density=None, filament_diameter=None))]
+fn parse_slicer_output(
+    py: Python<'_>,
+    output_dir: String,
+    filament_density: Option<f64>,
+    filament_diameter: Option<f64>,
+) -> PyResult<&PyAny> {
+    panic_boundary::catch(|| {
+        let filament = FilamentSpec::new(filament_density, filament_diameter);
+        future_into_py(py, panic_boundary::catch_future(async move {
+            let parsed = panic_boundary::spawn_blocking(move || {
+                parse_slicer_output_dir(Path::new(&output_dir), filament)
+            })
+            .await?;
+            Ok(parsed.map_err(OrcaError::IoError)?)
+        }))
+    })
+}
+
+/// Pricing shared by `calculate_quote_rust` and the quote pipeline.
+pub(crate) fn compute_cost_breakdown(
     print_time_minutes: u32,
     filament_weight_grams: f32,
     material_type: String,
@@ -495,7 +1003,7 @@ fn calculate_quote_rust(
     additional_time_hours: f64,
     price_multiplier: f64,
     minimum_price: f64,
-) -> PyResult<CostBreakdown> {
+) -> CostBreakdown {
     // Convert grams to kg
     let filament_kg = filament_weight_grams as f64 / 1000.0;
     
@@ -516,7 +1024,7 @@ fn calculate_quote_rust(
     // Calculate markup percentage
     let markup_percentage = (price_multiplier - 1.0) * 100.0;
     
-    Ok(CostBreakdown {
+    CostBreakdown {
         material_type,
         filament_kg,
         filament_grams: filament_weight_grams,
@@ -529,67 +1037,412 @@ fn calculate_quote_rust(
         total_cost,
         minimum_applied,
         markup_percentage,
+    }
+}
+
+/// Enhanced pricing calculation in Rust for performance
+#[pyfunction]
+fn calculate_quote_rust(
+    print_time_minutes: u32,
+    filament_weight_grams: f32,
+    material_type: String,
+    price_per_kg: f64,
+    additional_time_hours: f64,
+    price_multiplier: f64,
+    minimum_price: f64,
+) -> PyResult<CostBreakdown> {
+    panic_boundary::catch(|| {
+        Ok(compute_cost_breakdown(
+            print_time_minutes,
+            filament_weight_grams,
+            material_type,
+            price_per_kg,
+            additional_time_hours,
+            price_multiplier,
+            minimum_price,
+        ))
     })
 }
 
 /// High-performance file cleanup in Rust
 #[pyfunction]
-fn cleanup_old_files_rust(upload_dir: String, max_age_hours: u64) -> PyResult<CleanupStats> {
-    let dir = Path::new(&upload_dir);
+#[pyo3(signature = (upload_dir, max_age_hours, audit_log_path=None))]
+fn cleanup_old_files_rust(
+    py: Python<'_>,
+    upload_dir: String,
+    max_age_hours: u64,
+    audit_log_path: Option<String>,
+) -> PyResult<CleanupStats> {
+    panic_boundary::catch(|| {
+        let stats = py.allow_threads(|| cleanup_dir(&upload_dir, max_age_hours, audit_log_path))?;
+        Ok(stats)
+    })
+}
+
+fn cleanup_dir(
+    upload_dir: &str,
+    max_age_hours: u64,
+    audit_log_path: Option<String>,
+) -> std::io::Result<CleanupStats> {
+    let dir = Path::new(upload_dir);
     let now = SystemTime::now();
     let max_age = Duration::from_secs(max_age_hours * 3600);
     
-    let mut stats = CleanupStats {
-        files_cleaned: 0,
-        bytes_freed: 0,
-    };
+    let mut stats = CleanupStats::default();
     
     if dir.is_dir() {
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_file() {
-                let metadata = entry.metadata()?;
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        stats.record_error(&path, &e);
+                        continue;
+                    }
+                };
                 if let Ok(modified) = metadata.modified() {
                     if now.duration_since(modified).unwrap_or_default() > max_age {
-                        stats.bytes_freed += metadata.len();
-                        fs::remove_file(path)?;
-                        stats.files_cleaned += 1;
+                        // Keep going past individual failures so one locked file
+                        // doesn't stop the whole sweep.
+                        match fs::remove_file(&path) {
+                            Ok(()) => stats.record_removal(&path, &metadata),
+                            Err(e) => stats.record_error(&path, &e),
+                        }
                     }
                 }
             }
         }
     }
+
+    if let Some(log_path) = audit_log_path {
+        stats.audit(&log_path, upload_dir, max_age_hours);
+    }
     
     Ok(stats)
 }
 
+/// Non-blocking file cleanup using tokio fs, for use from asyncio code
+#[pyfunction]
+#[pyo3(signature = (upload_dir, max_age_hours, audit_log_path=None))]
+fn cleanup_old_files_async(
+    py: Python<'_>,
+    upload_dir: String,
+    max_age_hours: u64,
+    audit_log_path: Option<String>,
+) -> PyResult<&PyAny> {
+    panic_boundary::catch(|| {
+        future_into_py(py, panic_boundary::catch_future(async move {
+            let now = SystemTime::now();
+            let max_age = Duration::from_secs(max_age_hours * 3600);
+
+            let mut stats = CleanupStats::default();
+
+            let is_dir = tokio::fs::metadata(&upload_dir)
+                .await
+                .map(|m| m.is_dir())
+                .unwrap_or(false);
+
+            if is_dir {
+                let mut entries = tokio::fs::read_dir(&upload_dir).await?;
+                while let Some(entry) = entries.next_entry().await? {
+                    let path = entry.path();
+                    let metadata = match entry.metadata().await {
+                        Ok(metadata) => metadata,
+                        Err(e) => {
+                            stats.record_error(&path, &e);
+                            continue;
+                        }
+                    };
+                    if !metadata.is_file() {
+                        continue;
+                    }
+                    if let Ok(modified) = metadata.modified() {
+                        if now.duration_since(modified).unwrap_or_default() > max_age {
+                            match tokio::fs::remove_file(&path).await {
+                                Ok(()) => stats.record_removal(&path, &metadata),
+                                Err(e) => stats.record_error(&path, &e),
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(log_path) = audit_log_path {
+                // A single appended line; not worth a trip through spawn_blocking.
+                stats.audit(&log_path, &upload_dir, max_age_hours);
+            }
+
+            Ok(stats)
+        }))
+    })
+}
+
 /// Sanitize a filename to remove characters that are not allowed by the OS.
 #[pyfunction]
 fn secure_filename(filename: String) -> PyResult<String> {
-    Ok(sanitize(filename))
+    panic_boundary::catch(|| {
+        Ok(sanitize(filename))
+    })
 }
 
 /// Python module definition
 #[pymodule]
-fn _rust_core(_py: Python, m: &PyModule) -> PyResult<()> {
+fn _rust_core(py: Python, m: &PyModule) -> PyResult<()> {
+    panic_boundary::install_hook();
+    m.add("InternalError", py.get_type::<InternalError>())?;
+    m.add("SlicerFailedError", py.get_type::<SlicerFailedError>())?;
+
     // Original validation functions
     m.add_function(wrap_pyfunction!(validate_stl, m)?)?;
     m.add_function(wrap_pyfunction!(validate_obj, m)?)?;
+    m.add_function(wrap_pyfunction!(list_obj_objects, m)?)?;
+    m.add_function(wrap_pyfunction!(split_obj_objects, m)?)?;
     m.add_function(wrap_pyfunction!(validate_step, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_3mf, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_amf, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_ply, m)?)?;
+    m.add_function(wrap_pyfunction!(check_mesh_integrity, m)?)?;
+    m.add_function(wrap_pyfunction!(mesh_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(repair_mesh, m)?)?;
+    m.add_function(wrap_pyfunction!(decimate_mesh, m)?)?;
+    m.add_function(wrap_pyfunction!(check_fits_build_plate, m)?)?;
+    m.add_function(wrap_pyfunction!(convert_stl, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_archived_model, m)?)?;
+    m.add_function(wrap_pyfunction!(set_unpack_limit, m)?)?;
+    m.add_function(wrap_pyfunction!(set_mmap_threshold, m)?)?;
     m.add_function(wrap_pyfunction!(validate_3d_model, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_many, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_file_type, m)?)?;
+    m.add_function(wrap_pyfunction!(create_validation_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_stl_async, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_obj_async, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_step_async, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_3mf_async, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_amf_async, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_ply_async, m)?)?;
+    m.add_function(wrap_pyfunction!(create_malware_scanner, m)?)?;
+    m.add_function(wrap_pyfunction!(scan_upload, m)?)?;
+    m.add_function(wrap_pyfunction!(create_rate_limiter, m)?)?;
+    m.add_function(wrap_pyfunction!(create_upload_session_store, m)?)?;
+    m.add_function(wrap_pyfunction!(execute_slicer_async, m)?)?;
+    m.add_function(wrap_pyfunction!(create_cancellation_token, m)?)?;
+    m.add_function(wrap_pyfunction!(create_slicer_pool, m)?)?;
+    m.add_function(wrap_pyfunction!(create_slicer_retry_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_slicer, m)?)?;
+    m.add_function(wrap_pyfunction!(create_slicer_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_3d_model_async, m)?)?;
+    m.add_function(wrap_pyfunction!(create_streaming_validator, m)?)?;
+    m.add_function(wrap_pyfunction!(set_memory_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_validation_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(validation_cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(record_upload, m)?)?;
+    m.add_function(wrap_pyfunction!(find_duplicate, m)?)?;
     m.add_function(wrap_pyfunction!(secure_filename, m)?)?;
     
     // Enhanced performance functions
     m.add_function(wrap_pyfunction!(parse_slicer_output, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_quote_rust, m)?)?;
     m.add_function(wrap_pyfunction!(cleanup_old_files_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(cleanup_old_files_async, m)?)?;
+    m.add_function(wrap_pyfunction!(create_job_workspace, m)?)?;
+
+    // Slicer profiles
+    m.add_function(wrap_pyfunction!(load_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(resolve_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(resolve_filament_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(list_unmapped_materials, m)?)?;
+    m.add_function(wrap_pyfunction!(resolve_profile_paths, m)?)?;
+    m.add_function(wrap_pyfunction!(lint_profiles, m)?)?;
+    m.add_function(wrap_pyfunction!(discover_machines, m)?)?;
+    m.add_function(wrap_pyfunction!(discover_processes, m)?)?;
+    m.add_function(wrap_pyfunction!(create_profile_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_process_override, m)?)?;
+    m.add_function(wrap_pyfunction!(import_profile_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(export_profile_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(sync_vendor_profiles, m)?)?;
+    m.add_function(wrap_pyfunction!(check_compatibility, m)?)?;
+
+    // Materials
+    m.add_function(wrap_pyfunction!(load_material_catalog, m)?)?;
+    m.add_function(wrap_pyfunction!(create_inventory, m)?)?;
+    m.add_function(wrap_pyfunction!(discover_available_materials, m)?)?;
+
+    // Quote pipeline
+    m.add_function(wrap_pyfunction!(load_fleet, m)?)?;
+    m.add_function(wrap_pyfunction!(create_pipeline_config, m)?)?;
+    m.add_function(wrap_pyfunction!(run_quote_pipeline, m)?)?;
+    m.add_function(wrap_pyfunction!(run_order_pipeline, m)?)?;
+    m.add_function(wrap_pyfunction!(quote_materials, m)?)?;
+    m.add_function(wrap_pyfunction!(requote, m)?)?;
+    m.add_function(wrap_pyfunction!(plan_plates, m)?)?;
+    m.add_function(wrap_pyfunction!(set_slicer_concurrency, m)?)?;
+    m.add_function(wrap_pyfunction!(acquire_slicer_slot, m)?)?;
+    m.add_function(wrap_pyfunction!(create_gcode_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(queue_status, m)?)?;
+    m.add_function(wrap_pyfunction!(warm_up, m)?)?;
+    m.add_function(wrap_pyfunction!(health_check, m)?)?;
+
+    // Printer integrations
+    m.add_function(wrap_pyfunction!(create_octoprint_config, m)?)?;
+    m.add_function(wrap_pyfunction!(send_to_octoprint, m)?)?;
+    m.add_function(wrap_pyfunction!(create_moonraker_config, m)?)?;
+    m.add_function(wrap_pyfunction!(send_to_moonraker, m)?)?;
+    m.add_function(wrap_pyfunction!(create_farm_monitor, m)?)?;
+    m.add_function(wrap_pyfunction!(create_business_calendar, m)?)?;
+    m.add_function(wrap_pyfunction!(promised_completion, m)?)?;
+    m.add_function(wrap_pyfunction!(farm_status, m)?)?;
+    m.add_function(wrap_pyfunction!(estimate_lead_time, m)?)?;
+
+    // Time-of-use pricing
+    m.add_function(wrap_pyfunction!(create_time_of_use_pricing, m)?)?;
+    m.add_function(wrap_pyfunction!(quote_off_peak, m)?)?;
+
+    // Estimate calibration
+    m.add_function(wrap_pyfunction!(create_print_history, m)?)?;
+
+    // Payments
+    m.add_function(wrap_pyfunction!(create_stripe_config, m)?)?;
+    m.add_function(wrap_pyfunction!(create_payment_link, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_paynow_qr, m)?)?;
+    m.add_function(wrap_pyfunction!(paynow_payload, m)?)?;
+
+    // Shipping
+    m.add_function(wrap_pyfunction!(create_easypost_shipping, m)?)?;
+    m.add_function(wrap_pyfunction!(create_callback_shipping, m)?)?;
+    m.add_function(wrap_pyfunction!(get_shipping_rates, m)?)?;
+
+    // Bookkeeping
+    m.add_function(wrap_pyfunction!(create_csv_ledger, m)?)?;
+    m.add_function(wrap_pyfunction!(create_sheets_ledger, m)?)?;
+    m.add_function(wrap_pyfunction!(append_quote_to_ledger, m)?)?;
+
+    // Events
+    m.add_function(wrap_pyfunction!(add_webhook_sink, m)?)?;
+    m.add_function(wrap_pyfunction!(add_mqtt_sink, m)?)?;
+    m.add_function(wrap_pyfunction!(add_event_callback, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_event_sinks, m)?)?;
+    m.add_function(wrap_pyfunction!(emit_event, m)?)?;
+    m.add_function(wrap_pyfunction!(flush_events, m)?)?;
+
+    // Webhooks
+    m.add_function(wrap_pyfunction!(parse_stripe_webhook, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_telegram_webhook, m)?)?;
+
+    // Schemas
+    m.add_function(wrap_pyfunction!(export_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(to_dict, m)?)?;
+    m.add_function(wrap_pyfunction!(render_model_preview, m)?)?;
+    m.add_function(wrap_pyfunction!(render_preview, m)?)?;
+
+    // G-code post-processing
+    m.add_function(wrap_pyfunction!(create_postprocess_config, m)?)?;
+    m.add_function(wrap_pyfunction!(postprocess_gcode, m)?)?;
+
+    // Quote store
+    m.add_function(wrap_pyfunction!(create_quote_store, m)?)?;
+    m.add_function(wrap_pyfunction!(export_job_bundle, m)?)?;
+
+    // Customer portal
+    m.add_function(wrap_pyfunction!(sign_quote_token, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_quote_token, m)?)?;
+
+    // Metrics
+    m.add_function(wrap_pyfunction!(enable_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(gather_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(record_quote_metric, m)?)?;
+    m.add_function(wrap_pyfunction!(set_queue_depth, m)?)?;
+    m.add_function(wrap_pyfunction!(serve_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(init_json_logging, m)?)?;
     
     // Data classes
     m.add_class::<ModelInfo>()?;
+    m.add_class::<ValidationLimits>()?;
+    m.add_class::<MalwareScanner>()?;
+    m.add_class::<Stability>()?;
+    m.add_class::<MeshIntegrity>()?;
+    m.add_class::<MeshStats>()?;
+    m.add_class::<ObjMaterials>()?;
+    m.add_class::<ObjObject>()?;
+    m.add_class::<StepSummary>()?;
+    m.add_class::<StreamingValidator>()?;
+    m.add_class::<MeshRepair>()?;
+    m.add_class::<Decimation>()?;
+    m.add_class::<ValidationCacheStats>()?;
     m.add_class::<SlicingResult>()?;
     m.add_class::<CleanupStats>()?;
     m.add_class::<CostBreakdown>()?;
+    m.add_class::<JobWorkspace>()?;
+    m.add_class::<Profile>()?;
+    m.add_class::<FilamentResolution>()?;
+    m.add_class::<ProfilePaths>()?;
+    m.add_class::<ProfileLintReport>()?;
+    m.add_class::<LintIssue>()?;
+    m.add_class::<MachineListing>()?;
+    m.add_class::<ProcessListing>()?;
+    m.add_class::<ProfileCache>()?;
+    m.add_class::<RateLimiter>()?;
+    m.add_class::<UploadSession>()?;
+    m.add_class::<UploadSessionStore>()?;
+    m.add_class::<CancellationToken>()?;
+    m.add_class::<SlicerPool>()?;
+    m.add_class::<SlicerJob>()?;
+    m.add_class::<SlicerPoolStats>()?;
+    m.add_class::<SlicerRetryPolicy>()?;
+    m.add_class::<SlicerAttempt>()?;
+    m.add_class::<SlicerInfo>()?;
+    m.add_class::<SlicerFailure>()?;
+    m.add_class::<SlicerLimits>()?;
+    m.add_class::<BundleImport>()?;
+    m.add_class::<VendorSync>()?;
+    m.add_class::<CompatibilityReport>()?;
+    m.add_class::<Material>()?;
+    m.add_class::<MaterialCatalog>()?;
+    m.add_class::<Inventory>()?;
+    m.add_class::<StockLevel>()?;
+    m.add_class::<MaterialAvailability>()?;
+    m.add_class::<Fleet>()?;
+    m.add_class::<FleetPrinter>()?;
+    m.add_class::<PipelineConfig>()?;
+    m.add_class::<QuoteResult>()?;
+    m.add_class::<OrderQuote>()?;
+    m.add_class::<OrderPart>()?;
+    m.add_class::<PlatePlan>()?;
+    m.add_class::<MaterialComparison>()?;
+    m.add_class::<MaterialQuote>()?;
+    m.add_class::<Requote>()?;
+    m.add_class::<PrintOptions>()?;
+    m.add_class::<SlicerPermit>()?;
+    m.add_class::<GcodeCache>()?;
+    m.add_class::<QueueStatus>()?;
+    m.add_class::<WarmUpReport>()?;
+    m.add_class::<HealthReport>()?;
+    m.add_class::<DependencyStatus>()?;
+    m.add_class::<OctoPrintConfig>()?;
+    m.add_class::<OctoPrintUpload>()?;
+    m.add_class::<MoonrakerConfig>()?;
+    m.add_class::<MoonrakerUpload>()?;
+    m.add_class::<FarmMonitor>()?;
+    m.add_class::<BusinessCalendar>()?;
+    m.add_class::<PostProcessConfig>()?;
+    m.add_class::<QuoteStore>()?;
+    m.add_class::<PriceAdjustment>()?;
+    m.add_class::<PrinterStatus>()?;
+    m.add_class::<LeadTime>()?;
+    m.add_class::<TimeOfUsePricing>()?;
+    m.add_class::<OffPeakPrice>()?;
+    m.add_class::<PrintHistory>()?;
+    m.add_class::<CorrectionFactor>()?;
+    m.add_class::<EstimateCalibration>()?;
+    m.add_class::<StripeConfig>()?;
+    m.add_class::<ShippingConfig>()?;
+    m.add_class::<ShippingRate>()?;
+    m.add_class::<Parcel>()?;
+    m.add_class::<LedgerConfig>()?;
+    m.add_class::<QuoteEvent>()?;
+    m.add_class::<QuoteToken>()?;
+    m.add_class::<WebhookEvent>()?;
     
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,250 @@
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// clamd closes the stream on chunks larger than its StreamMaxLength allows;
+/// small chunks keep well under any setting.
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// An external malware scanner every upload is run through before slicing
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MalwareScanner {
+    /// Program and arguments run with the upload path appended, following
+    /// clamscan's exit codes: 0 clean, 1 infected, anything else a failure.
+    #[pyo3(get)]
+    pub command: Vec<String>,
+    /// clamd socket the upload is streamed to instead: a Unix socket path, or
+    /// `host:port` for TCP.
+    #[pyo3(get)]
+    pub clamd_socket: Option<String>,
+    #[pyo3(get)]
+    pub timeout_secs: f64,
+}
+
+#[pymethods]
+impl MalwareScanner {
+    fn __str__(&self) -> String {
+        match &self.clamd_socket {
+            Some(socket) => format!("MalwareScanner(clamd={})", socket),
+            None => format!("MalwareScanner(command={:?})", self.command),
+        }
+    }
+}
+
+impl MalwareScanner {
+    /// Scan the file at `path`; `MalwareFound` when the scanner flags it,
+    /// `ScanFailed` when it could not say.
+    pub fn scan(&self, path: &Path) -> Result<(), OrcaError> {
+        let timeout = Duration::from_secs_f64(self.timeout_secs);
+        let verdict = match &self.clamd_socket {
+            Some(socket) => clamd_scan(socket, path, timeout)?,
+            None => command_scan(&self.command, path, timeout)?,
+        };
+        match verdict {
+            None => Ok(()),
+            Some(signature) => Err(OrcaError::MalwareFound {
+                file: path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+                signature,
+            }),
+        }
+    }
+}
+
+/// The signature in a clamscan or clamd report line, `<name>: <signature> FOUND`.
+fn found_signature(report: &str) -> Option<String> {
+    report.lines().find_map(|line| {
+        let line = line.trim_end_matches('\0').trim();
+        let (_, verdict) = line.rsplit_once(": ")?;
+        verdict.strip_suffix(" FOUND").map(str::to_string)
+    })
+}
+
+/// Run `command` on `path`; the signature found, if any.
+fn command_scan(
+    command: &[String],
+    path: &Path,
+    timeout: Duration,
+) -> Result<Option<String>, OrcaError> {
+    let Some((program, args)) = command.split_first() else {
+        return Ok(None);
+    };
+    let failed = |message: String| OrcaError::ScanFailed(format!("{}: {}", program, message));
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| failed(format!("could not start: {}", e)))?;
+    // Read while waiting, so a chatty scanner cannot fill the pipe and stall.
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let report = thread::spawn(move || {
+        let mut report = String::new();
+        let _ = stdout.read_to_string(&mut report);
+        report
+    });
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(failed(format!("did not finish within {:?}", timeout)));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+    let report = report.join().unwrap_or_default();
+    match status.code() {
+        Some(0) => Ok(None),
+        Some(1) => Ok(Some(
+            found_signature(&report).unwrap_or_else(|| "unknown".to_string()),
+        )),
+        _ => Err(failed(format!("exited with {}", status))),
+    }
+}
+
+/// Stream `path` to clamd with INSTREAM and return its reply.
+fn instream<S: Read + Write>(mut stream: S, path: &Path) -> io::Result<String> {
+    stream.write_all(b"zINSTREAM\0")?;
+    let mut file = File::open(path)?;
+    let mut chunk = vec![0u8; CHUNK_BYTES];
+    loop {
+        let read = file.read(&mut chunk)?;
+        stream.write_all(&(read as u32).to_be_bytes())?;
+        if read == 0 {
+            break;
+        }
+        stream.write_all(&chunk[..read])?;
+    }
+    stream.flush()?;
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply)?;
+    Ok(String::from_utf8_lossy(&reply).into_owned())
+}
+
+#[cfg(unix)]
+fn unix_instream(socket: &str, path: &Path, timeout: Duration) -> io::Result<String> {
+    let stream = std::os::unix::net::UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    instream(stream, path)
+}
+
+#[cfg(not(unix))]
+fn unix_instream(_socket: &str, _path: &Path, _timeout: Duration) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix sockets are not available on this platform",
+    ))
+}
+
+fn tcp_instream(socket: &str, path: &Path, timeout: Duration) -> io::Result<String> {
+    let address = socket
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "address resolves to nothing"))?;
+    let stream = TcpStream::connect_timeout(&address, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    instream(stream, path)
+}
+
+/// Have clamd at `socket` scan `path`; the signature found, if any.
+fn clamd_scan(socket: &str, path: &Path, timeout: Duration) -> Result<Option<String>, OrcaError> {
+    if !path.exists() {
+        return Err(OrcaError::FileNotFound(path.to_string_lossy().into_owned()));
+    }
+    let reply = if socket.starts_with('/') {
+        unix_instream(socket, path, timeout)
+    } else {
+        tcp_instream(socket, path, timeout)
+    }
+    .map_err(|e| OrcaError::ScanFailed(format!("clamd at {}: {}", socket, e)))?;
+    let reply = reply.trim_end_matches('\0').trim();
+    if reply.ends_with(": OK") {
+        Ok(None)
+    } else if let Some(signature) = found_signature(reply) {
+        Ok(Some(signature))
+    } else {
+        Err(OrcaError::ScanFailed(format!(
+            "clamd at {}: {}",
+            socket, reply
+        )))
+    }
+}
+
+/// Configure the malware scanner uploads are run through before slicing
+///
+/// Give either `command`, a program and arguments run with the upload path
+/// appended (e.g. `["clamdscan", "--no-summary"]`), or `clamd_socket`, a clamd
+/// Unix socket path or `host:port` the upload is streamed to. Either is given
+/// up on after `timeout_secs`.
+#[pyfunction]
+#[pyo3(signature = (command=Vec::new(), clamd_socket=None, timeout_secs=60.0))]
+pub fn create_malware_scanner(
+    command: Vec<String>,
+    clamd_socket: Option<String>,
+    timeout_secs: f64,
+) -> PyResult<MalwareScanner> {
+    panic_boundary::catch(|| {
+        let clamd_socket = clamd_socket.filter(|s| !s.trim().is_empty());
+        if command.is_empty() == clamd_socket.is_none() {
+            return Err(OrcaError::InvalidConfig {
+                path: "command".to_string(),
+                message: "give either a command or a clamd socket".to_string(),
+            }
+            .into());
+        }
+        if command
+            .first()
+            .is_some_and(|program| program.trim().is_empty())
+        {
+            return Err(OrcaError::InvalidConfig {
+                path: "command".to_string(),
+                message: "program is empty".to_string(),
+            }
+            .into());
+        }
+        if !(timeout_secs > 0.0 && timeout_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "timeout_secs".to_string(),
+                message: format!("{} is not a positive number of seconds", timeout_secs),
+            }
+            .into());
+        }
+        Ok(MalwareScanner {
+            command,
+            clamd_socket,
+            timeout_secs,
+        })
+    })
+}
+
+/// Run an upload through the malware scanner
+///
+/// Raises ValueError naming the signature when the scanner flags the file,
+/// and OSError when the scanner fails, times out or cannot be reached.
+#[pyfunction]
+pub fn scan_upload(py: Python<'_>, file_path: String, scanner: MalwareScanner) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        py.allow_threads(|| scanner.scan(Path::new(&file_path)))?;
+        Ok(())
+    })
+}
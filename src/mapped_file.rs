@@ -0,0 +1,131 @@
+use memchr::memchr;
+use memmap2::Mmap;
+use pyo3::prelude::*;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::memory_limits::{self, Budget, Lines};
+use crate::panic_boundary;
+
+const DEFAULT_MMAP_THRESHOLD_BYTES: u64 = 64 * 1024 * 1024;
+
+static MMAP_THRESHOLD_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MMAP_THRESHOLD_BYTES);
+
+/// A model file opened for validation: memory-mapped when it is at least the
+/// threshold in size, read through a buffer otherwise.
+pub(crate) enum ModelFile {
+    Mapped(Mmap),
+    Buffered(File),
+}
+
+impl ModelFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        ModelFile::new(File::open(path)?)
+    }
+
+    /// Take over an open file, to be read from the start.
+    pub fn new(mut file: File) -> io::Result<Self> {
+        if file.metadata()?.len() < MMAP_THRESHOLD_BYTES.load(Ordering::Relaxed) {
+            file.seek(SeekFrom::Start(0))?;
+            return Ok(ModelFile::Buffered(file));
+        }
+        // SAFETY: uploads are written once before they are validated; a file
+        // truncated under the map would fault, but nothing here writes to it.
+        Ok(ModelFile::Mapped(unsafe { Mmap::map(&file)? }))
+    }
+
+    pub fn reader(&self) -> ModelReader<'_> {
+        match self {
+            ModelFile::Mapped(map) => ModelReader::Mapped(&map[..]),
+            ModelFile::Buffered(file) => ModelReader::Buffered(BufReader::new(file)),
+        }
+    }
+
+    /// The file's lines, like `memory_limits::lines`. Mapped lines are
+    /// borrowed from the map rather than buffered, so the budget is only
+    /// enforced on files read through a buffer.
+    pub fn lines(&self, budget: Budget) -> ModelLines<'_> {
+        match self {
+            ModelFile::Mapped(map) => ModelLines::Mapped(&map[..]),
+            ModelFile::Buffered(file) => {
+                ModelLines::Buffered(memory_limits::lines(BufReader::new(file), budget))
+            }
+        }
+    }
+}
+
+/// Bytes of a `ModelFile`, from the map or through a buffer.
+pub(crate) enum ModelReader<'a> {
+    Mapped(&'a [u8]),
+    Buffered(BufReader<&'a File>),
+}
+
+impl Read for ModelReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            ModelReader::Mapped(bytes) => bytes.read(buf),
+            ModelReader::Buffered(reader) => reader.read(buf),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        match self {
+            ModelReader::Mapped(bytes) => bytes.read_exact(buf),
+            ModelReader::Buffered(reader) => reader.read_exact(buf),
+        }
+    }
+}
+
+pub(crate) enum ModelLines<'a> {
+    Mapped(&'a [u8]),
+    Buffered(Lines<BufReader<&'a File>>),
+}
+
+impl<'a> Iterator for ModelLines<'a> {
+    type Item = io::Result<Cow<'a, str>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = match self {
+            ModelLines::Mapped(rest) => rest,
+            ModelLines::Buffered(lines) => return lines.next().map(|line| line.map(Cow::Owned)),
+        };
+        if rest.is_empty() {
+            return None;
+        }
+        let (mut line, next) = match memchr(b'\n', rest) {
+            Some(end) => (&rest[..end], &rest[end + 1..]),
+            None => (&rest[..], &[][..]),
+        };
+        *rest = next;
+        if let Some(stripped) = line.strip_suffix(b"\r") {
+            line = stripped;
+        }
+        Some(std::str::from_utf8(line).map(Cow::Borrowed).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        }))
+    }
+}
+
+/// Size from which model files are validated from a memory map, in bytes
+///
+/// Large files, such as 3D scans, are then read straight from the page cache
+/// instead of being copied through a buffer. 0 restores the default of 64 MB.
+#[pyfunction]
+#[pyo3(signature = (min_bytes=0))]
+pub fn set_mmap_threshold(min_bytes: u64) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        let min_bytes = if min_bytes == 0 {
+            DEFAULT_MMAP_THRESHOLD_BYTES
+        } else {
+            min_bytes
+        };
+        MMAP_THRESHOLD_BYTES.store(min_bytes, Ordering::Relaxed);
+        Ok(())
+    })
+}
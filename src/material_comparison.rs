@@ -0,0 +1,238 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use sanitize_filename::sanitize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use crate::events;
+use crate::metrics;
+use crate::panic_boundary;
+use crate::pipeline::{self, PipelineConfig, QuoteResult, StageTimer};
+use crate::process_override::PrintOptions;
+use crate::slicer::SlicerWatch;
+use crate::workspace::JobWorkspace;
+use crate::OrcaError;
+
+/// One row of a material comparison
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MaterialQuote {
+    #[pyo3(get)]
+    pub material: String,
+    #[pyo3(get)]
+    pub total_cost: f64,
+    #[pyo3(get)]
+    pub print_time_minutes: u32,
+    #[pyo3(get)]
+    pub filament_grams: f32,
+    #[pyo3(get)]
+    pub quote: QuoteResult,
+}
+
+#[pymethods]
+impl MaterialQuote {
+    fn __str__(&self) -> String {
+        format!(
+            "MaterialQuote(material={}, total=S${:.2}, print_time={}min)",
+            self.material, self.total_cost, self.print_time_minutes
+        )
+    }
+}
+
+/// One model quoted in several materials, for the customer to choose from
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MaterialComparison {
+    #[pyo3(get)]
+    pub model_path: String,
+    /// A row per material that could be quoted, in the order asked for.
+    #[pyo3(get)]
+    pub materials: Vec<MaterialQuote>,
+    /// Materials that could not be quoted, e.g. for want of a filament profile,
+    /// with the reason.
+    #[pyo3(get)]
+    pub errors: HashMap<String, String>,
+    #[pyo3(get)]
+    pub cheapest: Option<String>,
+    #[pyo3(get)]
+    pub fastest: Option<String>,
+}
+
+#[pymethods]
+impl MaterialComparison {
+    fn __str__(&self) -> String {
+        format!(
+            "MaterialComparison(materials={}, cheapest={:?}, fastest={:?})",
+            self.materials.len(),
+            self.cheapest,
+            self.fastest
+        )
+    }
+}
+
+impl MaterialComparison {
+    fn new(model_path: String, rows: Vec<Result<MaterialQuote, (String, PyErr)>>) -> Self {
+        let mut materials = Vec::new();
+        let mut errors = HashMap::new();
+        for row in rows {
+            match row {
+                Ok(row) => materials.push(row),
+                Err((material, e)) => {
+                    errors.insert(material, e.to_string());
+                }
+            }
+        }
+        let cheapest = materials
+            .iter()
+            .min_by(|a, b| a.total_cost.total_cmp(&b.total_cost))
+            .map(|row| row.material.clone());
+        let fastest = materials
+            .iter()
+            .min_by_key(|row| row.print_time_minutes)
+            .map(|row| row.material.clone());
+        MaterialComparison {
+            model_path,
+            materials,
+            errors,
+            cheapest,
+            fastest,
+        }
+    }
+}
+
+/// Validate the model once, then slice it for every material in parallel in one
+/// workspace, each into its own output directory.
+fn compare(
+    model_path: &str,
+    materials: &[String],
+    options: &PrintOptions,
+    config: &PipelineConfig,
+) -> PyResult<Vec<Result<MaterialQuote, (String, PyErr)>>> {
+    let mut timer = StageTimer::default();
+    let model = pipeline::check_model(model_path, config, &mut timer)?;
+    let workspace = JobWorkspace::create(config.work_dir.as_deref().map(Path::new), None)?;
+    let (model, timer, workspace_ref) = (&model, &timer, &workspace);
+    let rows = thread::scope(|scope| {
+        let handles: Vec<_> = materials
+            .iter()
+            .map(|material| {
+                scope.spawn(move || {
+                    let output_dir = Path::new(&workspace_ref.output_dir).join(sanitize(material));
+                    let result = fs::create_dir_all(&output_dir)
+                        .map_err(|e| PyErr::from(OrcaError::IoError(e)))
+                        .and_then(|()| {
+                            pipeline::slice_and_price(
+                                model_path,
+                                model,
+                                material.clone(),
+                                options.clone(),
+                                config,
+                                workspace_ref,
+                                &output_dir,
+                                timer.clone(),
+                                &SlicerWatch::default(),
+                            )
+                        });
+                    metrics::record_quote(material, pipeline::outcome(&result));
+                    result
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .zip(materials)
+            .map(|(handle, material)| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| {
+                        Err(OrcaError::SlicerFailed("material quote panicked".to_string()).into())
+                    })
+                    .map(|quote| MaterialQuote {
+                        material: quote.material.clone(),
+                        total_cost: quote.cost.total_cost,
+                        print_time_minutes: quote.slicing.print_time_minutes,
+                        filament_grams: quote.slicing.filament_weight_grams,
+                        quote,
+                    })
+                    .map_err(|e| (material.clone(), e))
+            })
+            .collect()
+    });
+    workspace.release();
+    Ok(rows)
+}
+
+/// Quote one model in several materials, e.g. PLA vs PETG vs ASA from one upload
+///
+/// The model is validated once and sliced once per material, in parallel within
+/// the slicer concurrency limit. A material that cannot be quoted is listed in
+/// `errors` rather than failing the comparison; when none can, the first
+/// material's error is raised.
+#[pyfunction]
+#[pyo3(signature = (model_path, materials, config, options=None, quote_id=None))]
+pub fn quote_materials(
+    py: Python<'_>,
+    model_path: String,
+    materials: Vec<String>,
+    config: PyRef<'_, PipelineConfig>,
+    options: Option<&PyDict>,
+    quote_id: Option<String>,
+) -> PyResult<MaterialComparison> {
+    panic_boundary::catch(|| {
+        let options = options
+            .map(PrintOptions::from_dict)
+            .transpose()?
+            .unwrap_or_default();
+        // "pla" and "PLA+" are one material once canonical; slice it once.
+        let mut canonical: Vec<String> = Vec::new();
+        for material in &materials {
+            let material = config.canonical_material(material);
+            if !canonical.contains(&material) {
+                canonical.push(material);
+            }
+        }
+        if canonical.is_empty() {
+            return Err(OrcaError::InvalidConfig {
+                path: "materials".to_string(),
+                message: "is empty".to_string(),
+            }
+            .into());
+        }
+        let config: &PipelineConfig = &config;
+        let result = py
+            .allow_threads(|| compare(&model_path, &canonical, &options, config))
+            .and_then(|mut rows| {
+                // When nothing could be quoted, the first material's failure says why.
+                if rows.iter().all(Result::is_err) {
+                    if let Err((_, e)) = rows.swap_remove(0) {
+                        return Err(e);
+                    }
+                }
+                Ok(MaterialComparison::new(model_path.clone(), rows))
+            });
+        match &result {
+            Ok(comparison) => events::emit(
+                "quote.created",
+                quote_id.as_deref(),
+                json!({
+                    "materials": comparison
+                        .materials
+                        .iter()
+                        .map(|row| (row.material.clone(), json!(row.total_cost)))
+                        .collect::<serde_json::Map<_, _>>(),
+                    "cheapest": comparison.cheapest,
+                    "fastest": comparison.fastest,
+                }),
+            ),
+            Err(e) => events::emit(
+                "quote.failed",
+                quote_id.as_deref(),
+                json!({ "materials": canonical, "error": e.to_string() }),
+            ),
+        };
+        result
+    })
+}
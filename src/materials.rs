@@ -0,0 +1,198 @@
+use pyo3::prelude::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::panic_boundary;
+use crate::FilamentSpec;
+use crate::OrcaError;
+
+const DEFAULT_DIAMETER_MM: f64 = 1.75;
+
+/// Canonical printing material with its physical properties and default price
+#[derive(Debug, Clone, Deserialize)]
+#[pyclass]
+pub struct Material {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub display_name: String,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// g/cm³
+    #[pyo3(get)]
+    pub density: f64,
+    /// Filament diameter in mm
+    #[pyo3(get)]
+    #[serde(default = "default_diameter")]
+    pub diameter: f64,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub colors: Vec<String>,
+    #[pyo3(get)]
+    pub price_per_kg: f64,
+}
+
+fn default_diameter() -> f64 {
+    DEFAULT_DIAMETER_MM
+}
+
+impl Material {
+    fn new(
+        name: &str,
+        display_name: &str,
+        aliases: &[&str],
+        density: f64,
+        colors: &[&str],
+        price_per_kg: f64,
+    ) -> Self {
+        Material {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            density,
+            diameter: DEFAULT_DIAMETER_MM,
+            colors: colors.iter().map(|c| c.to_string()).collect(),
+            price_per_kg,
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let wanted = name.trim();
+        self.name.eq_ignore_ascii_case(wanted)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(wanted))
+    }
+}
+
+#[pymethods]
+impl Material {
+    /// Weight in grams of a length of filament in mm, as quotes work it out
+    /// when the filament profile gives no density
+    fn grams_from_length(&self, length_mm: f64) -> f64 {
+        FilamentSpec::from_material(self)
+            .map_or(0.0, |filament| filament.grams_for_length(length_mm))
+    }
+
+    /// Weight in grams of a volume of material in cm³
+    fn grams_from_volume(&self, volume_cm3: f64) -> f64 {
+        volume_cm3 * self.density
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "Material(name={}, density={}, price=S${:.2}/kg)",
+            self.name, self.density, self.price_per_kg
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct CatalogFile {
+    #[serde(default, rename = "material")]
+    materials: Vec<Material>,
+}
+
+/// Canonical materials, looked up by name or alias
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MaterialCatalog {
+    #[pyo3(get)]
+    pub materials: Vec<Material>,
+}
+
+impl MaterialCatalog {
+    /// Materials offered when no catalog file is configured.
+    pub fn builtin() -> Self {
+        MaterialCatalog {
+            materials: vec![
+                Material::new(
+                    "PLA",
+                    "PLA",
+                    &["PLA+", "PLA PLUS", "PLA-CF", "PLA MATTE"],
+                    1.24,
+                    &["Black", "White", "Grey", "Red", "Blue"],
+                    25.0,
+                ),
+                Material::new(
+                    "PETG",
+                    "PETG",
+                    &["PET-G", "PETG-HF", "PETG-CF"],
+                    1.27,
+                    &["Black", "White", "Clear"],
+                    30.0,
+                ),
+                Material::new("ASA", "ASA", &["ASA-CF"], 1.07, &["Black", "White"], 35.0),
+            ],
+        }
+    }
+
+    /// Load a TOML catalog made of `[[material]]` tables.
+    pub fn load(path: &Path) -> Result<Self, OrcaError> {
+        if !path.is_file() {
+            return Err(OrcaError::FileNotFound(path.display().to_string()));
+        }
+        let contents = fs::read_to_string(path)?;
+        let file: CatalogFile =
+            toml::from_str(&contents).map_err(|e| OrcaError::InvalidConfig {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            })?;
+
+        let materials = file
+            .materials
+            .into_iter()
+            .map(|mut material| {
+                if material.display_name.is_empty() {
+                    material.display_name = material.name.clone();
+                }
+                material
+            })
+            .collect();
+        Ok(MaterialCatalog { materials })
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Material> {
+        self.materials.iter().find(|m| m.matches(name))
+    }
+}
+
+#[pymethods]
+impl MaterialCatalog {
+    /// Look up a material by canonical name or alias (case-insensitive)
+    fn get(&self, name: &str) -> Option<Material> {
+        self.find(name).cloned()
+    }
+
+    /// Canonical name for a material, or the uppercased input if it is not catalogued
+    fn canonical_name(&self, name: &str) -> String {
+        self.find(name)
+            .map(|m| m.name.clone())
+            .unwrap_or_else(|| name.trim().to_uppercase())
+    }
+
+    /// Canonical names of every catalogued material
+    fn names(&self) -> Vec<String> {
+        self.materials.iter().map(|m| m.name.clone()).collect()
+    }
+
+    /// Default price for a material, or `fallback` if it is not catalogued
+    fn price_per_kg(&self, name: &str, fallback: f64) -> f64 {
+        self.find(name).map_or(fallback, |m| m.price_per_kg)
+    }
+
+    fn __str__(&self) -> String {
+        format!("MaterialCatalog(materials={:?})", self.names())
+    }
+}
+
+/// Load the material catalog from a TOML file, or the built-in defaults when no path is given
+#[pyfunction]
+#[pyo3(signature = (path=None))]
+pub fn load_material_catalog(path: Option<String>) -> PyResult<MaterialCatalog> {
+    panic_boundary::catch(|| match path {
+        Some(path) => Ok(MaterialCatalog::load(Path::new(&path))?),
+        None => Ok(MaterialCatalog::builtin()),
+    })
+}
@@ -0,0 +1,145 @@
+use pyo3::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::panic_boundary;
+
+/// Bytes the G-code parser may buffer at once; 0 means unlimited.
+static GCODE_PARSE_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Bytes mesh validation and analysis may buffer at once; 0 means unlimited.
+static MESH_ANALYSIS_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Which configurable memory budget a reader is held to.
+#[derive(Debug, Clone, Copy)]
+pub enum Budget {
+    GcodeParse,
+    MeshAnalysis,
+}
+
+impl Budget {
+    pub(crate) fn limit(self) -> u64 {
+        match self {
+            Budget::GcodeParse => GCODE_PARSE_BYTES.load(Ordering::Relaxed),
+            Budget::MeshAnalysis => MESH_ANALYSIS_BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Budget::GcodeParse => "G-code parsing",
+            Budget::MeshAnalysis => "mesh analysis",
+        }
+    }
+
+    /// The error a reader returns when a line needs more than `limit` bytes.
+    pub(crate) fn exceeded(self, limit: u64) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            MemoryLimitExceeded {
+                budget: self,
+                limit,
+            },
+        )
+    }
+}
+
+/// Carried inside an `io::Error` of kind `OutOfMemory` when a reader would go
+/// over its budget, so callers can tell it apart from real I/O failures.
+#[derive(Debug)]
+pub struct MemoryLimitExceeded {
+    budget: Budget,
+    limit: u64,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} exceeded its memory budget of {} bytes (a single line is longer than that)",
+            self.budget.describe(),
+            self.limit
+        )
+    }
+}
+
+impl Error for MemoryLimitExceeded {}
+
+/// True for errors raised by a budgeted reader rather than the filesystem.
+pub fn is_limit_error(err: &io::Error) -> bool {
+    err.get_ref()
+        .is_some_and(|inner| inner.is::<MemoryLimitExceeded>())
+}
+
+/// Like `BufRead::lines`, but a line that would need more than the budget fails
+/// instead of being buffered whole. A file that is one huge line is the only way
+/// these streaming scanners can grow without bound.
+pub struct Lines<R> {
+    reader: R,
+    budget: Budget,
+    limit: u64,
+    buffer: Vec<u8>,
+}
+
+pub fn lines<R: BufRead>(reader: R, budget: Budget) -> Lines<R> {
+    Lines {
+        reader,
+        budget,
+        limit: budget.limit(),
+        buffer: Vec::new(),
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.clear();
+        let read = if self.limit == 0 {
+            self.reader.read_until(b'\n', &mut self.buffer)
+        } else {
+            // One byte past the budget leaves room for the newline itself.
+            (&mut self.reader)
+                .take(self.limit + 1)
+                .read_until(b'\n', &mut self.buffer)
+        };
+        match read {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => return Some(Err(e)),
+        }
+        if self.limit > 0 && self.buffer.len() as u64 > self.limit && !self.buffer.ends_with(b"\n")
+        {
+            return Some(Err(self.budget.exceeded(self.limit)));
+        }
+        if self.buffer.ends_with(b"\n") {
+            self.buffer.pop();
+            if self.buffer.ends_with(b"\r") {
+                self.buffer.pop();
+            }
+        }
+        Some(
+            String::from_utf8(std::mem::take(&mut self.buffer)).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                )
+            }),
+        )
+    }
+}
+
+/// Cap the memory G-code parsing and mesh validation/analysis may use, in bytes; 0 removes a cap
+///
+/// Going over a budget raises MemoryError for that file instead of growing the worker.
+#[pyfunction]
+#[pyo3(signature = (gcode_parse_bytes=0, mesh_analysis_bytes=0))]
+pub fn set_memory_limits(gcode_parse_bytes: u64, mesh_analysis_bytes: u64) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        GCODE_PARSE_BYTES.store(gcode_parse_bytes, Ordering::Relaxed);
+        MESH_ANALYSIS_BYTES.store(mesh_analysis_bytes, Ordering::Relaxed);
+        Ok(())
+    })
+}
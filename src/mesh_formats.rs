@@ -0,0 +1,864 @@
+use flate2::read::GzDecoder;
+use pyo3::prelude::*;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use zip::ZipArchive;
+
+use crate::archives::InflateGuard;
+use crate::geometry::{fan, Triangle};
+use crate::memory_limits::{self, Budget};
+use crate::panic_boundary;
+use crate::validation_cache::hashed;
+use crate::validation_limits::ValidationLimits;
+use crate::xml_scan::{self, XmlEvent};
+use crate::{ModelInfo, ValidationError};
+
+/// Package parts other than the model are small; don't inflate a huge one.
+const MAX_PACKAGE_PART_BYTES: u64 = 1024 * 1024;
+/// Where the 3MF spec puts the model part when the relationships don't say otherwise.
+const DEFAULT_3MF_MODEL: &str = "3D/3dmodel.model";
+const MODEL_CONTENT_TYPE: &str = "application/vnd.ms-package.3dmanufacturing-3dmodel+xml";
+/// Scanners write a few comment lines; anything this long is not a PLY header.
+const MAX_PLY_HEADER_BYTES: u64 = 64 * 1024;
+
+fn not_found(file_type: &str) -> ModelInfo {
+    ModelInfo::invalid(file_type, 0, "File not found".to_string())
+}
+
+/// Millimetres per model unit.
+fn unit_scale(unit: &str) -> Option<f32> {
+    match unit {
+        "micron" => Some(0.001),
+        "millimeter" => Some(1.0),
+        "centimeter" => Some(10.0),
+        "inch" => Some(25.4),
+        // 3MF says "foot", AMF "feet".
+        "foot" | "feet" => Some(304.8),
+        "meter" => Some(1000.0),
+        _ => None,
+    }
+}
+
+/// What one `.model` part of a 3MF package holds.
+#[derive(Default)]
+struct ModelPart {
+    /// The first element is `<model>`.
+    model_root: bool,
+    unit: Option<String>,
+    build_items: usize,
+    vertices: u64,
+    triangles: u64,
+    /// The first vertex index a triangle gives that its object does not have.
+    missing_vertex: Option<String>,
+}
+
+/// Scan a 3MF model part, collecting its triangles (in mm) when asked.
+fn scan_model_part<R: Read>(
+    reader: R,
+    mut collect: Option<&mut Vec<Triangle>>,
+) -> io::Result<ModelPart> {
+    let mut part = ModelPart::default();
+    let mut first = true;
+    let mut scale = 1.0;
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let mut object_vertices = 0u64;
+    xml_scan::for_each_event(reader, Budget::MeshAnalysis, |event| {
+        let XmlEvent::Start(tag) = event else {
+            if let XmlEvent::End("object") = event {
+                vertices.clear();
+                object_vertices = 0;
+            }
+            return;
+        };
+        if first {
+            first = false;
+            if tag.name == "model" {
+                part.model_root = true;
+                part.unit = tag.attr("unit").map(str::to_string);
+                scale = unit_scale(tag.attr("unit").unwrap_or("millimeter")).unwrap_or(1.0);
+            }
+        }
+        match tag.name {
+            "vertex" => {
+                part.vertices += 1;
+                object_vertices += 1;
+                if collect.is_some() {
+                    let coordinate =
+                        |axis| tag.attr(axis).and_then(|v| v.trim().parse::<f32>().ok());
+                    let point = [coordinate("x"), coordinate("y"), coordinate("z")];
+                    vertices.push(point.map(|c| c.unwrap_or(0.0) * scale));
+                }
+            }
+            "triangle" => {
+                part.triangles += 1;
+                if part.missing_vertex.is_none() {
+                    part.missing_vertex = ["v1", "v2", "v3"]
+                        .into_iter()
+                        .filter_map(|name| tag.attr(name).map(str::trim))
+                        .find(|index| index.parse::<u64>().map_or(true, |i| i >= object_vertices))
+                        .map(str::to_string);
+                }
+                if let Some(triangles) = collect.as_deref_mut() {
+                    let corner = |name| {
+                        let index: usize = tag.attr(name)?.trim().parse().ok()?;
+                        vertices.get(index).copied()
+                    };
+                    if let (Some(a), Some(b), Some(c)) = (corner("v1"), corner("v2"), corner("v3"))
+                    {
+                        triangles.push([a, b, c]);
+                    }
+                }
+            }
+            "item" => part.build_items += 1,
+            _ => {}
+        }
+    })?;
+    Ok(part)
+}
+
+/// Read a small package part whole; `None` when the archive has no such entry.
+fn read_package_part<R: Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> io::Result<Option<String>> {
+    let entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+    };
+    let mut text = String::new();
+    entry
+        .take(MAX_PACKAGE_PART_BYTES)
+        .read_to_string(&mut text)?;
+    Ok(Some(text))
+}
+
+/// The model part the package relationships point at, without a leading `/`.
+fn model_part_name(relationships: Option<&str>) -> String {
+    let mut target = None;
+    if let Some(relationships) = relationships {
+        let _ = xml_scan::for_each_event(relationships.as_bytes(), Budget::MeshAnalysis, |event| {
+            if let XmlEvent::Start(tag) = event {
+                let is_model = tag.attr("Type").is_some_and(|t| t.ends_with("/3dmodel"));
+                if tag.name == "Relationship" && is_model && target.is_none() {
+                    target = tag
+                        .attr("Target")
+                        .map(|t| t.trim_start_matches('/').to_string());
+                }
+            }
+        });
+    }
+    target.unwrap_or_else(|| DEFAULT_3MF_MODEL.to_string())
+}
+
+/// Why a 3MF package is not a printable model, or its parts when it is.
+fn read_3mf(
+    path: &Path,
+    mut collect: Option<&mut Vec<Triangle>>,
+) -> io::Result<Result<Vec<ModelPart>, String>> {
+    let Ok(mut archive) = ZipArchive::new(File::open(path)?) else {
+        return Ok(Err("not a ZIP archive".to_string()));
+    };
+    let Some(content_types) = read_package_part(&mut archive, "[Content_Types].xml")? else {
+        return Ok(Err("missing [Content_Types].xml".to_string()));
+    };
+    if !content_types
+        .to_ascii_lowercase()
+        .contains(MODEL_CONTENT_TYPE)
+    {
+        return Ok(Err(
+            "content types do not declare a 3D model part".to_string()
+        ));
+    }
+    let main = model_part_name(read_package_part(&mut archive, "_rels/.rels")?.as_deref());
+    if archive.by_name(&main).is_err() {
+        return Ok(Err(format!("missing {}", main)));
+    }
+
+    // The main part first; objects may also live in further parts it references.
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.to_ascii_lowercase().ends_with(".model") && *name != main)
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names.insert(0, main);
+
+    let mut parts = Vec::with_capacity(names.len());
+    for name in &names {
+        let entry = archive
+            .by_name(name)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let compressed = entry.compressed_size();
+        let part = scan_model_part(
+            InflateGuard::new(entry, name, compressed),
+            collect.as_deref_mut(),
+        )?;
+        if let Some(index) = &part.missing_vertex {
+            return Ok(Err(format!("triangle references missing vertex {}", index)));
+        }
+        parts.push(part);
+    }
+    Ok(Ok(parts))
+}
+
+/// Validate a 3MF package: its ZIP container, content types, and model XML.
+pub fn three_mf_info(path: &Path) -> Result<ModelInfo, ValidationError> {
+    if !path.exists() {
+        return Ok(not_found("3mf"));
+    }
+    let file_size = fs::metadata(path)?.len();
+    let fail = |message: String| {
+        Ok(ModelInfo::invalid(
+            "3mf",
+            file_size,
+            format!("Invalid 3MF - {}", message),
+        ))
+    };
+    let parts = match read_3mf(path, None) {
+        Ok(Ok(parts)) => parts,
+        Ok(Err(message)) => return fail(message),
+        Err(e) if memory_limits::is_limit_error(&e) => return Err(e.into()),
+        Err(e) => return fail(e.to_string()),
+    };
+
+    let main = &parts[0];
+    if !main.model_root {
+        return fail("model part has no <model> root".to_string());
+    }
+    if let Some(unit) = main.unit.as_deref().filter(|u| unit_scale(u).is_none()) {
+        return fail(format!("unknown unit '{}'", unit));
+    }
+    if main.build_items == 0 {
+        return fail("no build items".to_string());
+    }
+    let (vertices, triangles) = parts.iter().fold((0, 0), |(v, t), part| {
+        (v + part.vertices, t + part.triangles)
+    });
+    if vertices == 0 || triangles == 0 {
+        return fail("no mesh with triangles".to_string());
+    }
+    Ok(ModelInfo {
+        triangle_count: Some(triangles),
+        ..ModelInfo::valid("3mf", file_size)
+    })
+}
+
+/// Every triangle of a 3MF package's meshes, in millimetres.
+///
+/// Build item transforms are not applied, so objects sit where their meshes
+/// put them; sizes are right unless an item is rotated.
+pub fn three_mf_triangles(path: &Path) -> io::Result<Vec<Triangle>> {
+    let mut triangles = Vec::new();
+    read_3mf(path, Some(&mut triangles))?
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))?;
+    Ok(triangles)
+}
+
+/// What an AMF document holds.
+#[derive(Default)]
+struct AmfModel {
+    /// The first element is `<amf>`.
+    amf_root: bool,
+    unit: Option<String>,
+    objects: usize,
+    /// Objects with at least one vertex and one triangle.
+    objects_with_mesh: usize,
+    triangles: u64,
+}
+
+/// Scan an AMF document, collecting its triangles (in mm) when asked.
+///
+/// Coordinates and vertex indices are element text, e.g.
+/// `<coordinates><x>1.5</x>...` and `<triangle><v1>0</v1>...`.
+fn scan_amf<R: Read>(reader: R, mut collect: Option<&mut Vec<Triangle>>) -> io::Result<AmfModel> {
+    let mut model = AmfModel::default();
+    let mut first = true;
+    let mut scale = 1.0;
+    // Element whose text is being read, and what has been read so far.
+    let mut field = String::new();
+    let mut point = [0.0f32; 3];
+    let mut corners = [usize::MAX; 3];
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let (mut object_vertices, mut object_triangles) = (0u64, 0u64);
+    xml_scan::for_each_event(reader, Budget::MeshAnalysis, |event| match event {
+        XmlEvent::Start(tag) => {
+            if first {
+                first = false;
+                if tag.name == "amf" {
+                    model.amf_root = true;
+                    // The spec says `unit`; some exporters write `units`.
+                    let unit = tag.attr("unit").or_else(|| tag.attr("units"));
+                    model.unit = unit.map(str::to_string);
+                    scale = unit_scale(unit.unwrap_or("millimeter")).unwrap_or(1.0);
+                }
+            }
+            match tag.name {
+                "object" => model.objects += 1,
+                "vertex" => point = [0.0; 3],
+                "triangle" => corners = [usize::MAX; 3],
+                _ => {}
+            }
+            field.clear();
+            field.push_str(tag.name);
+        }
+        XmlEvent::Text(text) => {
+            let axis = match field.as_str() {
+                "x" | "v1" => 0,
+                "y" | "v2" => 1,
+                "z" | "v3" => 2,
+                _ => return,
+            };
+            if field.starts_with('v') {
+                corners[axis] = text.parse().unwrap_or(usize::MAX);
+            } else {
+                point[axis] = text.parse::<f32>().unwrap_or(0.0) * scale;
+            }
+        }
+        XmlEvent::End(name) => {
+            field.clear();
+            match name {
+                "vertex" => {
+                    object_vertices += 1;
+                    if collect.is_some() {
+                        vertices.push(point);
+                    }
+                }
+                "triangle" => {
+                    object_triangles += 1;
+                    model.triangles += 1;
+                    if let Some(triangles) = collect.as_deref_mut() {
+                        let [a, b, c] = corners.map(|index| vertices.get(index).copied());
+                        if let (Some(a), Some(b), Some(c)) = (a, b, c) {
+                            triangles.push([a, b, c]);
+                        }
+                    }
+                }
+                "object" => {
+                    if object_vertices > 0 && object_triangles > 0 {
+                        model.objects_with_mesh += 1;
+                    }
+                    (object_vertices, object_triangles) = (0, 0);
+                    vertices.clear();
+                }
+                _ => {}
+            }
+        }
+    })?;
+    Ok(model)
+}
+
+/// Scan an AMF file, plain XML or compressed with gzip or ZIP, which the spec
+/// allows for. A ZIP archive's first entry is the document.
+fn read_amf(path: &Path, collect: Option<&mut Vec<Triangle>>) -> io::Result<AmfModel> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 4];
+    let read = file.read(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    match &magic[..read] {
+        [0x1f, 0x8b, ..] => {
+            let compressed = file.metadata()?.len();
+            let inflated = GzDecoder::new(BufReader::new(file));
+            scan_amf(InflateGuard::new(inflated, &name, compressed), collect)
+        }
+        [b'P', b'K', 3, 4] => {
+            let mut archive =
+                ZipArchive::new(file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let entry = archive
+                .by_index(0)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let compressed = entry.compressed_size();
+            let name = entry.name().to_string();
+            scan_amf(InflateGuard::new(entry, &name, compressed), collect)
+        }
+        _ => scan_amf(BufReader::new(file), collect),
+    }
+}
+
+/// Validate an AMF document: its `<amf>` root, unit, and object meshes.
+pub fn amf_info(path: &Path) -> Result<ModelInfo, ValidationError> {
+    if !path.exists() {
+        return Ok(not_found("amf"));
+    }
+    let file_size = fs::metadata(path)?.len();
+    let fail = |message: String| {
+        Ok(ModelInfo::invalid(
+            "amf",
+            file_size,
+            format!("Invalid AMF - {}", message),
+        ))
+    };
+    let model = match read_amf(path, None) {
+        Ok(model) => model,
+        Err(e) if memory_limits::is_limit_error(&e) => return Err(e.into()),
+        Err(e) => return fail(e.to_string()),
+    };
+
+    if !model.amf_root {
+        return fail("no <amf> root element".to_string());
+    }
+    if let Some(unit) = model.unit.as_deref().filter(|u| unit_scale(u).is_none()) {
+        return fail(format!("unknown unit '{}'", unit));
+    }
+    if model.objects == 0 {
+        return fail("no objects".to_string());
+    }
+    if model.objects_with_mesh == 0 {
+        return fail("no object with mesh vertices and triangles".to_string());
+    }
+    Ok(ModelInfo {
+        triangle_count: Some(model.triangles),
+        ..ModelInfo::valid("amf", file_size)
+    })
+}
+
+/// Every triangle of an AMF document's objects, in millimetres.
+///
+/// Constellation offsets are not applied, like 3MF build transforms.
+pub fn amf_triangles(path: &Path) -> io::Result<Vec<Triangle>> {
+    let mut triangles = Vec::new();
+    read_amf(path, Some(&mut triangles))?;
+    Ok(triangles)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// A PLY scalar type, by its size in bytes and how to read it.
+#[derive(Clone, Copy)]
+enum PlyScalar {
+    Int(usize),
+    Uint(usize),
+    Float(usize),
+}
+
+impl PlyScalar {
+    fn parse(name: &str) -> Option<PlyScalar> {
+        Some(match name {
+            "char" | "int8" => PlyScalar::Int(1),
+            "uchar" | "uint8" => PlyScalar::Uint(1),
+            "short" | "int16" => PlyScalar::Int(2),
+            "ushort" | "uint16" => PlyScalar::Uint(2),
+            "int" | "int32" => PlyScalar::Int(4),
+            "uint" | "uint32" => PlyScalar::Uint(4),
+            "float" | "float32" => PlyScalar::Float(4),
+            "double" | "float64" => PlyScalar::Float(8),
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyScalar::Int(size) | PlyScalar::Uint(size) | PlyScalar::Float(size) => size,
+        }
+    }
+
+    fn read<R: Read>(self, reader: &mut R, big_endian: bool) -> io::Result<f64> {
+        let mut bytes = [0u8; 8];
+        let bytes = &mut bytes[..self.size()];
+        reader.read_exact(bytes)?;
+        if big_endian {
+            bytes.reverse();
+        }
+        // Little-endian from here on; widen into eight bytes.
+        let mut wide = [0u8; 8];
+        wide[..bytes.len()].copy_from_slice(bytes);
+        let sign_extend = |value: u64, size: usize| {
+            let shift = 64 - size as u32 * 8;
+            ((value << shift) as i64 >> shift) as f64
+        };
+        Ok(match self {
+            PlyScalar::Uint(_) => u64::from_le_bytes(wide) as f64,
+            PlyScalar::Int(size) => sign_extend(u64::from_le_bytes(wide), size),
+            PlyScalar::Float(4) => f32::from_le_bytes([wide[0], wide[1], wide[2], wide[3]]) as f64,
+            PlyScalar::Float(_) => f64::from_le_bytes(wide),
+        })
+    }
+}
+
+enum PlyProperty {
+    Scalar(String, PlyScalar),
+    /// Name, then the types of the item count and of the items.
+    List(String, PlyScalar, PlyScalar),
+}
+
+impl PlyProperty {
+    fn name(&self) -> &str {
+        match self {
+            PlyProperty::Scalar(name, _) | PlyProperty::List(name, _, _) => name,
+        }
+    }
+}
+
+struct PlyElement {
+    name: String,
+    count: u64,
+    properties: Vec<PlyProperty>,
+}
+
+struct PlyHeader {
+    format: PlyFormat,
+    elements: Vec<PlyElement>,
+    /// Bytes up to and including the `end_header` line.
+    length: u64,
+}
+
+impl PlyHeader {
+    fn element(&self, name: &str) -> Option<&PlyElement> {
+        self.elements.iter().find(|element| element.name == name)
+    }
+
+    fn count(&self, name: &str) -> u64 {
+        self.element(name).map_or(0, |element| element.count)
+    }
+}
+
+/// Read a PLY header, leaving `reader` at the start of the body; `Err` with
+/// the reason when it is not one.
+fn parse_ply_header<R: BufRead>(reader: &mut R) -> io::Result<Result<PlyHeader, String>> {
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    let mut length = 0u64;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .take(MAX_PLY_HEADER_BYTES - length)
+            .read_until(b'\n', &mut line)?;
+        if read == 0 || !line.ends_with(b"\n") {
+            return Ok(Err("header has no end_header line".to_string()));
+        }
+        length += read as u64;
+        let text = String::from_utf8_lossy(&line);
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if length == read as u64 {
+            if words != ["ply"] {
+                return Ok(Err("missing 'ply' magic line".to_string()));
+            }
+            continue;
+        }
+        match words.as_slice() {
+            ["end_header"] => break,
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", name, "1.0"] if format.is_none() => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Ok(Err(format!("unknown format '{}'", name))),
+                });
+            }
+            ["element", name, count] if format.is_some() => match count.parse() {
+                Ok(count) => elements.push(PlyElement {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                }),
+                Err(_) => return Ok(Err(format!("bad {} count '{}'", name, count))),
+            },
+            ["property", rest @ ..] if !elements.is_empty() => {
+                let property = match rest {
+                    ["list", count, item, name] => PlyScalar::parse(count)
+                        .zip(PlyScalar::parse(item))
+                        .map(|(count, item)| PlyProperty::List(name.to_string(), count, item)),
+                    [scalar, name] => PlyScalar::parse(scalar)
+                        .map(|scalar| PlyProperty::Scalar(name.to_string(), scalar)),
+                    _ => None,
+                };
+                let Some(property) = property else {
+                    return Ok(Err(format!("bad property line '{}'", text.trim())));
+                };
+                if let Some(element) = elements.last_mut() {
+                    element.properties.push(property);
+                }
+            }
+            _ if format.is_none() => return Ok(Err("missing format line".to_string())),
+            _ => return Ok(Err(format!("unexpected header line '{}'", text.trim()))),
+        }
+    }
+    match format {
+        Some(format) => Ok(Ok(PlyHeader {
+            format,
+            elements,
+            length,
+        })),
+        None => Ok(Err("missing format line".to_string())),
+    }
+}
+
+/// The face element's vertex index list, by either of its usual names.
+fn face_index_list(face: &PlyElement) -> Option<usize> {
+    face.properties.iter().position(|property| {
+        matches!(property, PlyProperty::List(name, _, _)
+            if name == "vertex_indices" || name == "vertex_index")
+    })
+}
+
+/// Why a PLY header does not describe a printable mesh.
+fn ply_header_problem(header: &PlyHeader) -> Option<String> {
+    let Some(vertex) = header.element("vertex").filter(|v| v.count > 0) else {
+        return Some("no vertices".to_string());
+    };
+    let names: Vec<&str> = vertex.properties.iter().map(PlyProperty::name).collect();
+    if !["x", "y", "z"].iter().all(|axis| names.contains(axis)) {
+        return Some("vertex element lacks x, y and z".to_string());
+    }
+    let Some(face) = header.element("face").filter(|f| f.count > 0) else {
+        return Some("no faces; a point cloud cannot be printed".to_string());
+    };
+    if face_index_list(face).is_none() {
+        return Some("face element has no vertex_indices list".to_string());
+    }
+    None
+}
+
+/// The fewest body bytes a binary PLY with this header can have, counting
+/// three items for each face list.
+fn ply_min_body_bytes(header: &PlyHeader) -> u64 {
+    header
+        .elements
+        .iter()
+        .map(|element| {
+            let row: usize = element
+                .properties
+                .iter()
+                .map(|property| match property {
+                    PlyProperty::Scalar(_, scalar) => scalar.size(),
+                    PlyProperty::List(_, count, item) if element.name == "face" => {
+                        count.size() + 3 * item.size()
+                    }
+                    PlyProperty::List(_, count, _) => count.size(),
+                })
+                .sum();
+            element.count.saturating_mul(row as u64)
+        })
+        .fold(0u64, u64::saturating_add)
+}
+
+/// Validate a PLY mesh: its header's format line and vertex and face elements,
+/// and that the body is long enough for them.
+pub fn ply_info(path: &Path) -> Result<ModelInfo, ValidationError> {
+    if !path.exists() {
+        return Ok(not_found("ply"));
+    }
+    let file_size = fs::metadata(path)?.len();
+    let fail = |message: String| {
+        Ok(ModelInfo::invalid(
+            "ply",
+            file_size,
+            format!("Invalid PLY - {}", message),
+        ))
+    };
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = match parse_ply_header(&mut reader)? {
+        Ok(header) => header,
+        Err(message) => return fail(message),
+    };
+    if let Some(message) = ply_header_problem(&header) {
+        return fail(message);
+    }
+
+    let (vertices, faces) = (header.count("vertex"), header.count("face"));
+    let complete = if header.format == PlyFormat::Ascii {
+        // One element per line.
+        let rows: u64 = header.elements.iter().map(|e| e.count).sum();
+        let mut found = 0u64;
+        for line in memory_limits::lines(reader, Budget::MeshAnalysis) {
+            if !line?.trim().is_empty() {
+                found += 1;
+                if found >= rows {
+                    break;
+                }
+            }
+        }
+        found >= rows
+    } else {
+        file_size - header.length >= ply_min_body_bytes(&header)
+    };
+    if !complete {
+        return fail(format!(
+            "truncated; the header declares {} vertices and {} faces",
+            vertices, faces
+        ));
+    }
+    Ok(ModelInfo {
+        triangle_count: Some(faces),
+        ..ModelInfo::valid("ply", file_size)
+    })
+}
+
+/// A PLY body, read one element row at a time.
+enum PlyBody<R> {
+    /// One row per line.
+    Ascii(memory_limits::Lines<R>),
+    Binary {
+        reader: R,
+        big_endian: bool,
+    },
+}
+
+impl<R: BufRead> PlyBody<R> {
+    /// Read a row of `element` into `values`, flattened, with where each
+    /// property's values start in `starts` and the end of the row last.
+    fn read_row(
+        &mut self,
+        element: &PlyElement,
+        values: &mut Vec<f64>,
+        starts: &mut Vec<usize>,
+    ) -> io::Result<()> {
+        values.clear();
+        starts.clear();
+        match self {
+            PlyBody::Ascii(lines) => {
+                let line = loop {
+                    let line = lines.next().ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::UnexpectedEof, "PLY body ends early")
+                    })??;
+                    if !line.trim().is_empty() {
+                        break line;
+                    }
+                };
+                let mut words = line
+                    .split_whitespace()
+                    .map(|word| word.parse::<f64>().unwrap_or(0.0));
+                for property in &element.properties {
+                    starts.push(values.len());
+                    let count = match property {
+                        PlyProperty::Scalar(..) => 1,
+                        PlyProperty::List(..) => words.next().unwrap_or(0.0) as usize,
+                    };
+                    values.extend(words.by_ref().take(count));
+                }
+            }
+            PlyBody::Binary { reader, big_endian } => {
+                for property in &element.properties {
+                    starts.push(values.len());
+                    match property {
+                        PlyProperty::Scalar(_, scalar) => {
+                            values.push(scalar.read(reader, *big_endian)?)
+                        }
+                        PlyProperty::List(_, count, item) => {
+                            let count = count.read(reader, *big_endian)? as usize;
+                            for _ in 0..count {
+                                values.push(item.read(reader, *big_endian)?);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        starts.push(values.len());
+        Ok(())
+    }
+}
+
+/// Every triangle of a PLY mesh, polygons split into fans. Coordinates are
+/// taken as millimetres; PLY has no unit.
+pub fn ply_triangles(path: &Path) -> io::Result<Vec<Triangle>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = parse_ply_header(&mut reader)?
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))?;
+    let mut body = match header.format {
+        PlyFormat::Ascii => PlyBody::Ascii(memory_limits::lines(reader, Budget::MeshAnalysis)),
+        format => PlyBody::Binary {
+            reader,
+            big_endian: format == PlyFormat::BinaryBigEndian,
+        },
+    };
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let mut triangles = Vec::new();
+    let mut values: Vec<f64> = Vec::new();
+    let mut starts: Vec<usize> = Vec::new();
+
+    for element in &header.elements {
+        let axes = ["x", "y", "z"].map(|axis| {
+            element
+                .properties
+                .iter()
+                .position(|property| property.name() == axis)
+        });
+        let indices = face_index_list(element).filter(|_| element.name == "face");
+        for _ in 0..element.count {
+            body.read_row(element, &mut values, &mut starts)?;
+            if element.name == "vertex" {
+                vertices.push(axes.map(|axis| {
+                    axis.and_then(|i| values.get(starts[i]))
+                        .map_or(0.0, |v| *v as f32)
+                }));
+            } else if let Some(list) = indices {
+                let corners: Vec<[f32; 3]> = values[starts[list]..starts[list + 1]]
+                    .iter()
+                    .filter_map(|index| vertices.get(*index as usize).copied())
+                    .collect();
+                fan(&corners, &mut triangles);
+            }
+        }
+    }
+    Ok(triangles)
+}
+
+/// Validation for 3MF packages, OrcaSlicer's native project format
+///
+/// Checks the ZIP container, that `[Content_Types].xml` declares a 3D model
+/// part, and that the model part (`3D/3dmodel.model` unless `_rels/.rels`
+/// names another) has a `<model>` root, a known unit, build items, and mesh
+/// triangles in it or the parts it references.
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_3mf(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<ModelInfo> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            limits.validate(Path::new(&file_path), |path| hashed(path, three_mf_info))
+        })?)
+    })
+}
+
+/// Validation for AMF documents, plain XML or gzip/ZIP compressed
+///
+/// Checks for an `<amf>` root with a known unit (millimetres when absent) and
+/// at least one `<object>` whose mesh has vertices and triangles.
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_amf(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<ModelInfo> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            limits.validate(Path::new(&file_path), |path| hashed(path, amf_info))
+        })?)
+    })
+}
+
+/// Validation for PLY meshes, ASCII or binary, as scanners write them
+///
+/// Parses the header: the `ply` magic, the format line, and the vertex (with
+/// x, y and z) and face elements' counts. Point clouds without faces are
+/// rejected, and so is a body too short for the counts.
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn validate_ply(
+    py: Python<'_>,
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<ModelInfo> {
+    panic_boundary::catch(|| {
+        let limits = limits.unwrap_or_default();
+        Ok(py.allow_threads(|| {
+            limits.validate(Path::new(&file_path), |path| hashed(path, ply_info))
+        })?)
+    })
+}
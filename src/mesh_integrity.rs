@@ -0,0 +1,270 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::geometry::{model_triangles, Triangle};
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// How sound a mesh is as a solid: open edges, non-manifold edges, and faces
+/// that disagree with their neighbours
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct MeshIntegrity {
+    #[pyo3(get)]
+    pub triangles: u64,
+    /// Distinct corners once coincident ones are welded.
+    #[pyo3(get)]
+    pub vertices: u64,
+    /// Edges used by only one face; they border holes.
+    #[pyo3(get)]
+    pub boundary_edges: u64,
+    /// Separate loops of boundary edges.
+    #[pyo3(get)]
+    pub holes: u64,
+    /// Edges shared by more than two faces.
+    #[pyo3(get)]
+    pub non_manifold_edges: u64,
+    /// Faces wound against the rest of their shell, i.e. normals pointing
+    /// the wrong way.
+    #[pyo3(get)]
+    pub flipped_faces: u64,
+    /// Faces with the same three corners as an earlier face.
+    #[pyo3(get)]
+    pub duplicate_faces: u64,
+    /// No holes and no non-manifold edges: the mesh encloses a volume.
+    #[pyo3(get)]
+    pub is_watertight: bool,
+    /// Each problem found, in words, e.g. "2 holes (36 open edges)".
+    #[pyo3(get)]
+    pub problems: Vec<String>,
+}
+
+#[pymethods]
+impl MeshIntegrity {
+    fn __str__(&self) -> String {
+        format!(
+            "MeshIntegrity(watertight={}, triangles={}, problems={:?})",
+            self.is_watertight, self.triangles, self.problems
+        )
+    }
+}
+
+/// Corner key for welding: exact coordinates, with -0.0 and 0.0 as one.
+fn corner_key(corner: [f32; 3]) -> [u32; 3] {
+    corner.map(|v| if v == 0.0 { 0 } else { v.to_bits() })
+}
+
+/// A triangle soup with coincident corners welded into shared vertices.
+pub(crate) struct WeldedMesh {
+    pub vertices: Vec<[f32; 3]>,
+    /// Faces as vertex indices, in the soup's order and winding.
+    pub faces: Vec<[u32; 3]>,
+    /// Faces with two corners in the same place, left out.
+    pub collapsed_faces: u64,
+    /// Faces with the same three corners as an earlier face, left out.
+    pub duplicate_faces: u64,
+}
+
+impl WeldedMesh {
+    pub fn weld(triangles: &[Triangle]) -> WeldedMesh {
+        let mut ids: HashMap<[u32; 3], u32> = HashMap::new();
+        let mut vertices = Vec::new();
+        let mut seen: HashSet<[u32; 3]> = HashSet::with_capacity(triangles.len());
+        let mut faces = Vec::with_capacity(triangles.len());
+        let (mut collapsed_faces, mut duplicate_faces) = (0, 0);
+        for triangle in triangles {
+            let face = triangle.map(|corner| {
+                *ids.entry(corner_key(corner)).or_insert_with(|| {
+                    vertices.push(corner);
+                    vertices.len() as u32 - 1
+                })
+            });
+            // Faces collapsed to a line or point take no part in the surface.
+            if face[0] == face[1] || face[1] == face[2] || face[0] == face[2] {
+                collapsed_faces += 1;
+                continue;
+            }
+            let mut sorted = face;
+            sorted.sort_unstable();
+            if !seen.insert(sorted) {
+                duplicate_faces += 1;
+                continue;
+            }
+            faces.push(face);
+        }
+        WeldedMesh {
+            vertices,
+            faces,
+            collapsed_faces,
+            duplicate_faces,
+        }
+    }
+}
+
+/// The faces on one undirected edge, and the direction each runs it in.
+#[derive(Default)]
+pub(crate) struct EdgeUse {
+    pub count: u32,
+    faces: [u32; 2],
+    forward: [bool; 2],
+}
+
+/// Every edge of `faces`, keyed by its vertices in ascending order.
+pub(crate) fn edge_uses(faces: &[[u32; 3]]) -> HashMap<(u32, u32), EdgeUse> {
+    let mut edges: HashMap<(u32, u32), EdgeUse> = HashMap::with_capacity(faces.len() * 3 / 2);
+    for (index, face) in faces.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (face[k], face[(k + 1) % 3]);
+            let edge = edges.entry((a.min(b), a.max(b))).or_default();
+            if let Some(slot) = [0, 1].get(edge.count as usize) {
+                edge.faces[*slot] = index as u32;
+                edge.forward[*slot] = a < b;
+            }
+            edge.count += 1;
+        }
+    }
+    edges
+}
+
+/// Which faces are wound against the rest of their shell. Each shell, faces
+/// joined across manifold edges, keeps the winding most of its faces have.
+pub(crate) fn flipped_faces(faces: &[[u32; 3]], edges: &HashMap<(u32, u32), EdgeUse>) -> Vec<bool> {
+    // Neighbours across manifold edges, and whether they are wound alike.
+    let mut neighbours: Vec<Vec<(u32, bool)>> = vec![Vec::new(); faces.len()];
+    for edge in edges.values().filter(|edge| edge.count == 2) {
+        // Consistent neighbours run a shared edge in opposite directions.
+        let consistent = edge.forward[0] != edge.forward[1];
+        let [first, second] = edge.faces;
+        neighbours[first as usize].push((second, consistent));
+        neighbours[second as usize].push((first, consistent));
+    }
+
+    let mut flipped: Vec<Option<bool>> = vec![None; faces.len()];
+    for start in 0..faces.len() {
+        if flipped[start].is_some() {
+            continue;
+        }
+        flipped[start] = Some(false);
+        let mut shell = vec![start];
+        let mut stack = vec![start];
+        while let Some(face) = stack.pop() {
+            let face_flipped = flipped[face] == Some(true);
+            for &(other, consistent) in &neighbours[face] {
+                let other = other as usize;
+                if flipped[other].is_none() {
+                    flipped[other] = Some(face_flipped ^ !consistent);
+                    shell.push(other);
+                    stack.push(other);
+                }
+            }
+        }
+        let against = shell.iter().filter(|f| flipped[**f] == Some(true)).count();
+        if against * 2 > shell.len() {
+            for face in shell {
+                flipped[face] = flipped[face].map(|f| !f);
+            }
+        }
+    }
+    flipped.into_iter().map(|f| f == Some(true)).collect()
+}
+
+fn find(parents: &mut HashMap<u32, u32>, vertex: u32) -> u32 {
+    let mut root = vertex;
+    while let Some(&parent) = parents.get(&root).filter(|p| **p != root) {
+        root = parent;
+    }
+    // Point everything on the way straight at the root.
+    let mut at = vertex;
+    while at != root {
+        let next = parents.insert(at, root).unwrap_or(root);
+        at = next;
+    }
+    root
+}
+
+/// Weld a triangle soup and look at how its faces meet.
+pub fn analyze(triangles: &[Triangle]) -> MeshIntegrity {
+    let mesh = WeldedMesh::weld(triangles);
+    let edges = edge_uses(&mesh.faces);
+
+    let (mut boundary_edges, mut non_manifold_edges) = (0u64, 0u64);
+    let mut parents: HashMap<u32, u32> = HashMap::new();
+    for (&(a, b), edge) in &edges {
+        match edge.count {
+            1 => {
+                boundary_edges += 1;
+                let (root_a, root_b) = (find(&mut parents, a), find(&mut parents, b));
+                parents.insert(root_a, root_b);
+            }
+            2 => {}
+            _ => non_manifold_edges += 1,
+        }
+    }
+    let boundary_vertices: Vec<u32> = parents.keys().copied().collect();
+    let holes = boundary_vertices
+        .into_iter()
+        .filter(|vertex| find(&mut parents, *vertex) == *vertex)
+        .count() as u64;
+    let flipped_faces = flipped_faces(&mesh.faces, &edges)
+        .into_iter()
+        .filter(|flipped| *flipped)
+        .count() as u64;
+    let duplicate_faces = mesh.duplicate_faces;
+
+    let mut problems = Vec::new();
+    if holes > 0 {
+        problems.push(format!(
+            "{} hole{} ({} open edges)",
+            holes,
+            if holes == 1 { "" } else { "s" },
+            boundary_edges
+        ));
+    }
+    if non_manifold_edges > 0 {
+        problems.push(format!("{} non-manifold edges", non_manifold_edges));
+    }
+    if flipped_faces > 0 {
+        problems.push(format!("{} faces with flipped normals", flipped_faces));
+    }
+    if duplicate_faces > 0 {
+        problems.push(format!("{} duplicate faces", duplicate_faces));
+    }
+    MeshIntegrity {
+        triangles: triangles.len() as u64,
+        vertices: mesh.vertices.len() as u64,
+        boundary_edges,
+        holes,
+        non_manifold_edges,
+        flipped_faces,
+        duplicate_faces,
+        is_watertight: boundary_edges == 0 && non_manifold_edges == 0 && !mesh.faces.is_empty(),
+        problems,
+    }
+}
+
+/// Analyze a model file's mesh; formats without triangles (STEP) are an error.
+pub fn mesh_integrity(path: &Path) -> Result<MeshIntegrity, OrcaError> {
+    if !path.exists() {
+        return Err(OrcaError::FileNotFound(path.display().to_string()));
+    }
+    match model_triangles(path)? {
+        Some(triangles) => Ok(analyze(&triangles)),
+        None => Err(OrcaError::InvalidModel(format!(
+            "{} has no mesh to check",
+            path.display()
+        ))),
+    }
+}
+
+/// Check that a model's mesh is watertight and consistently wound
+///
+/// Coincident corners are welded, then every edge should be shared by exactly
+/// two faces running it in opposite directions. Reports holes, non-manifold
+/// edges, flipped faces and duplicate faces. Raises ValueError for formats
+/// without a mesh, such as STEP.
+#[pyfunction]
+pub fn check_mesh_integrity(py: Python<'_>, model_path: String) -> PyResult<MeshIntegrity> {
+    panic_boundary::catch(|| Ok(py.allow_threads(|| mesh_integrity(Path::new(&model_path)))?))
+}
@@ -0,0 +1,194 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::geometry::{model_triangles, Triangle};
+use crate::mesh_integrity::{analyze, edge_uses, flipped_faces, MeshIntegrity, WeldedMesh};
+use crate::panic_boundary;
+use crate::stl_convert::write_binary_stl;
+use crate::OrcaError;
+
+/// Holes bordered by more edges than this are left open by default.
+pub const DEFAULT_MAX_HOLE_EDGES: usize = 64;
+
+/// What `repair_mesh` changed, and how sound the mesh is afterwards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct MeshRepair {
+    #[pyo3(get)]
+    pub triangles_before: u64,
+    #[pyo3(get)]
+    pub triangles_after: u64,
+    /// Triangles with two corners in the same place, dropped.
+    #[pyo3(get)]
+    pub degenerate_removed: u64,
+    #[pyo3(get)]
+    pub duplicates_removed: u64,
+    /// Triangles turned round to face the same way as their shell.
+    #[pyo3(get)]
+    pub faces_flipped: u64,
+    #[pyo3(get)]
+    pub holes_filled: u64,
+    /// The repaired mesh, as `check_mesh_integrity` sees it.
+    #[pyo3(get)]
+    pub integrity: MeshIntegrity,
+}
+
+#[pymethods]
+impl MeshRepair {
+    fn __str__(&self) -> String {
+        format!(
+            "MeshRepair(triangles={}->{}, flipped={}, holes_filled={}, watertight={})",
+            self.triangles_before,
+            self.triangles_after,
+            self.faces_flipped,
+            self.holes_filled,
+            self.integrity.is_watertight
+        )
+    }
+}
+
+/// Closed loops of open edges, each in the order that fills it with faces
+/// wound like its neighbours. Open edges meeting at one vertex more than
+/// twice make no single loop there and are left alone.
+fn boundary_loops(faces: &[[u32; 3]]) -> Vec<Vec<u32>> {
+    let edges = edge_uses(faces);
+    // A face runs its open edge a->b; the filling face runs it b->a.
+    let mut next: HashMap<u32, u32> = HashMap::new();
+    let mut pinched: HashSet<u32> = HashSet::new();
+    for face in faces {
+        for k in 0..3 {
+            let (a, b) = (face[k], face[(k + 1) % 3]);
+            if edges[&(a.min(b), a.max(b))].count == 1 && next.insert(b, a).is_some() {
+                pinched.insert(b);
+            }
+        }
+    }
+
+    let mut visited: HashSet<u32> = HashSet::new();
+    let mut loops = Vec::new();
+    let mut starts: Vec<u32> = next.keys().copied().collect();
+    starts.sort_unstable();
+    for start in starts {
+        if visited.contains(&start) {
+            continue;
+        }
+        let mut path = vec![start];
+        visited.insert(start);
+        let mut at = start;
+        let closed = loop {
+            match next.get(&at) {
+                Some(&following) if following == start => break true,
+                Some(&following) if !pinched.contains(&following) && visited.insert(following) => {
+                    path.push(following);
+                    at = following;
+                }
+                _ => break false,
+            }
+        };
+        if closed && path.len() >= 3 && !pinched.contains(&start) {
+            loops.push(path);
+        }
+    }
+    loops
+}
+
+/// Weld, clean, orient and patch `triangles`.
+pub fn repair(triangles: &[Triangle], max_hole_edges: usize) -> (Vec<Triangle>, MeshRepair) {
+    let mut mesh = WeldedMesh::weld(triangles);
+    let flipped = flipped_faces(&mesh.faces, &edge_uses(&mesh.faces));
+    for (face, flipped) in mesh.faces.iter_mut().zip(&flipped) {
+        if *flipped {
+            face.swap(1, 2);
+        }
+    }
+
+    let sorted = |face: &[u32; 3]| {
+        let mut face = *face;
+        face.sort_unstable();
+        face
+    };
+    let mut existing: HashSet<[u32; 3]> = mesh.faces.iter().map(sorted).collect();
+    let mut holes_filled = 0;
+    for hole in boundary_loops(&mesh.faces) {
+        if hole.len() > max_hole_edges {
+            continue;
+        }
+        // A fan from the first corner; small holes are near enough flat.
+        let patch: Vec<[u32; 3]> = hole[1..]
+            .windows(2)
+            .map(|pair| [hole[0], pair[0], pair[1]])
+            .collect();
+        // A lone triangle's "hole" is the triangle itself; a copy closes nothing.
+        if patch.iter().any(|face| existing.contains(&sorted(face))) {
+            continue;
+        }
+        existing.extend(patch.iter().map(sorted));
+        mesh.faces.extend(patch);
+        holes_filled += 1;
+    }
+
+    let repaired: Vec<Triangle> = mesh
+        .faces
+        .iter()
+        .map(|face| face.map(|vertex| mesh.vertices[vertex as usize]))
+        .collect();
+    let report = MeshRepair {
+        triangles_before: triangles.len() as u64,
+        triangles_after: repaired.len() as u64,
+        degenerate_removed: mesh.collapsed_faces,
+        duplicates_removed: mesh.duplicate_faces,
+        faces_flipped: flipped.iter().filter(|f| **f).count() as u64,
+        holes_filled,
+        integrity: analyze(&repaired),
+    };
+    (repaired, report)
+}
+
+/// Repair a model file's mesh into a binary STL at `output`. `None` for
+/// formats without a mesh (STEP), which are left as they are.
+pub fn repair_file(
+    input: &Path,
+    output: &Path,
+    max_hole_edges: usize,
+) -> Result<Option<MeshRepair>, OrcaError> {
+    if !input.exists() {
+        return Err(OrcaError::FileNotFound(input.display().to_string()));
+    }
+    let Some(triangles) = model_triangles(input)? else {
+        return Ok(None);
+    };
+    let (repaired, report) = repair(&triangles, max_hole_edges);
+    write_binary_stl(output, &repaired)?;
+    Ok(Some(report))
+}
+
+/// Repair a model's mesh and write it to `output_path` as a binary STL
+///
+/// Coincident corners are welded; degenerate and duplicate triangles are
+/// dropped; faces wound against the rest of their shell are turned round;
+/// holes bordered by at most `max_hole_edges` edges are filled; and normals
+/// are recomputed from the winding. Raises ValueError for formats without a
+/// mesh, such as STEP.
+#[pyfunction]
+#[pyo3(signature = (input_path, output_path, max_hole_edges=DEFAULT_MAX_HOLE_EDGES))]
+pub fn repair_mesh(
+    py: Python<'_>,
+    input_path: String,
+    output_path: String,
+    max_hole_edges: usize,
+) -> PyResult<MeshRepair> {
+    panic_boundary::catch(|| {
+        let report = py.allow_threads(|| {
+            repair_file(
+                Path::new(&input_path),
+                Path::new(&output_path),
+                max_hole_edges,
+            )
+        })?;
+        report.ok_or_else(|| {
+            OrcaError::InvalidModel(format!("{} has no mesh to repair", input_path)).into()
+        })
+    })
+}
@@ -0,0 +1,126 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::geometry::{model_triangles, Triangle};
+use crate::mesh_integrity::WeldedMesh;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Triangles smaller than this, in mm², have no area to print.
+const DEGENERATE_AREA_MM2: f64 = 1e-6;
+
+/// Counts describing a model's mesh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct MeshStats {
+    #[pyo3(get)]
+    pub triangles: u64,
+    /// Distinct corners once coincident ones are welded.
+    #[pyo3(get)]
+    pub vertices: u64,
+    /// Triangles with no area: two corners in the same place, or all three
+    /// in a line.
+    #[pyo3(get)]
+    pub degenerate_triangles: u64,
+    /// Triangle corners at a position already seen: three per triangle,
+    /// less the distinct vertices. A closed mesh has about five per vertex.
+    #[pyo3(get)]
+    pub duplicate_vertices: u64,
+    /// Separate pieces: sets of triangles joined through shared corners.
+    #[pyo3(get)]
+    pub shells: u64,
+}
+
+#[pymethods]
+impl MeshStats {
+    fn __str__(&self) -> String {
+        format!(
+            "MeshStats(triangles={}, vertices={}, degenerate={}, shells={})",
+            self.triangles, self.vertices, self.degenerate_triangles, self.shells
+        )
+    }
+}
+
+/// Whether a triangle has no area to print.
+pub(crate) fn is_degenerate(triangle: [[f64; 3]; 3]) -> bool {
+    area(triangle) < DEGENERATE_AREA_MM2
+}
+
+fn area([a, b, c]: [[f64; 3]; 3]) -> f64 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt() / 2.0
+}
+
+fn root(parents: &mut [u32], vertex: u32) -> u32 {
+    let mut at = vertex;
+    while parents[at as usize] != at {
+        // Halve the path on the way up.
+        parents[at as usize] = parents[parents[at as usize] as usize];
+        at = parents[at as usize];
+    }
+    at
+}
+
+/// Count a triangle soup's triangles, welded vertices, degenerate triangles,
+/// repeated corners and shells.
+pub fn stats(triangles: &[Triangle]) -> MeshStats {
+    let mesh = WeldedMesh::weld(triangles);
+    let mut parents: Vec<u32> = (0..mesh.vertices.len() as u32).collect();
+    let mut used = vec![false; mesh.vertices.len()];
+    for face in &mesh.faces {
+        for &vertex in face {
+            used[vertex as usize] = true;
+        }
+        let first = root(&mut parents, face[0]);
+        for &vertex in &face[1..] {
+            let other = root(&mut parents, vertex);
+            parents[other as usize] = first;
+        }
+    }
+    let shells = (0..mesh.vertices.len() as u32)
+        .filter(|&vertex| used[vertex as usize] && root(&mut parents, vertex) == vertex)
+        .count() as u64;
+
+    MeshStats {
+        triangles: triangles.len() as u64,
+        vertices: mesh.vertices.len() as u64,
+        degenerate_triangles: triangles
+            .iter()
+            .filter(|triangle| is_degenerate(triangle.map(|corner| corner.map(f64::from))))
+            .count() as u64,
+        duplicate_vertices: (triangles.len() as u64 * 3).saturating_sub(mesh.vertices.len() as u64),
+        shells,
+    }
+}
+
+/// Count a model file's mesh; formats without triangles (STEP) are an error.
+pub fn file_stats(path: &Path) -> Result<MeshStats, OrcaError> {
+    if !path.exists() {
+        return Err(OrcaError::FileNotFound(path.display().to_string()));
+    }
+    match model_triangles(path)? {
+        Some(triangles) => Ok(stats(&triangles)),
+        None => Err(OrcaError::InvalidModel(format!(
+            "{} has no mesh to measure",
+            path.display()
+        ))),
+    }
+}
+
+/// Count a model's triangles, vertices, degenerate triangles, duplicate
+/// vertices and shells
+///
+/// Coincident corners are welded before vertices and shells are counted, so
+/// STL and indexed formats give the same numbers for the same mesh. Raises
+/// ValueError for formats without a mesh, such as STEP.
+#[pyfunction]
+pub fn mesh_stats(py: Python<'_>, file_path: String) -> PyResult<MeshStats> {
+    panic_boundary::catch(|| Ok(py.allow_threads(|| file_stats(Path::new(&file_path)))?))
+}
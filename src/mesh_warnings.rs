@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+
+use crate::geometry::{obj_face_indices, parse_point};
+use crate::mesh_statistics::is_degenerate;
+
+/// Problems that leave a model printable but suggest a careless export,
+/// counted while it is validated.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct MeshWarnings {
+    zero_area_triangles: u64,
+    duplicate_vertices: u64,
+    unreferenced_vertices: u64,
+}
+
+impl MeshWarnings {
+    pub fn include(&mut self, triangle: [[f64; 3]; 3]) {
+        if is_degenerate(triangle) {
+            self.zero_area_triangles += 1;
+        }
+    }
+
+    /// One line per kind of problem found, for `ModelInfo.warnings`.
+    pub fn messages(&self) -> Vec<String> {
+        let count = |n: u64, one: &str, many: &str| match n {
+            0 => None,
+            1 => Some(format!("1 {}", one)),
+            n => Some(format!("{} {}", n, many)),
+        };
+        [
+            count(
+                self.zero_area_triangles,
+                "triangle has no area",
+                "triangles have no area",
+            ),
+            count(
+                self.duplicate_vertices,
+                "vertex repeats another's position",
+                "vertices repeat another's position",
+            ),
+            count(
+                self.unreferenced_vertices,
+                "vertex is not used by any face",
+                "vertices are not used by any face",
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// An OBJ's vertex list, kept while it is read to find repeated and unused
+/// vertices and faces with no area.
+#[derive(Default)]
+pub(crate) struct ObjVertices {
+    points: Vec<[f64; 3]>,
+    /// Bit patterns of the positions seen.
+    seen: HashSet<[u64; 3]>,
+    used: Vec<bool>,
+    duplicates: u64,
+    triangles: u64,
+}
+
+impl ObjVertices {
+    /// A `v` line, from the words after "v".
+    pub fn vertex<'a>(&mut self, parts: impl Iterator<Item = &'a str>) {
+        let Some(point) = parse_point(parts) else {
+            return;
+        };
+        // Adding zero turns -0.0 into 0.0, so the two compare equal.
+        if !self.seen.insert(point.map(|value| (value + 0.0).to_bits())) {
+            self.duplicates += 1;
+        }
+        self.points.push(point);
+        self.used.push(false);
+    }
+
+    /// An `f` line, from the words after "f".
+    pub fn face<'a>(&mut self, parts: impl Iterator<Item = &'a str>, warnings: &mut MeshWarnings) {
+        let corners = obj_face_indices(parts, self.points.len());
+        for &corner in &corners {
+            self.used[corner] = true;
+        }
+        for pair in corners.windows(2).skip(1) {
+            self.triangles += 1;
+            warnings.include([corners[0], pair[0], pair[1]].map(|corner| self.points[corner]));
+        }
+    }
+
+    /// Triangles the faces so far split into.
+    pub fn triangles(&self) -> u64 {
+        self.triangles
+    }
+
+    pub fn finish(self, warnings: &mut MeshWarnings) {
+        warnings.duplicate_vertices = self.duplicates;
+        warnings.unreferenced_vertices = self.used.iter().filter(|used| !**used).count() as u64;
+    }
+}
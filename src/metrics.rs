@@ -0,0 +1,272 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::job_queue::record_slice_seconds;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Slicing usually takes seconds to a few minutes; the top buckets catch runaway jobs.
+const SLICE_SECONDS_BUCKETS: &[f64] = &[1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+/// Upload sizes up to the default 100 MB limit.
+const FILE_SIZE_BUCKETS: &[f64] = &[
+    100_000.0,
+    1_000_000.0,
+    5_000_000.0,
+    10_000_000.0,
+    25_000_000.0,
+    50_000_000.0,
+    100_000_000.0,
+];
+
+struct Histogram {
+    buckets: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(buckets: &'static [f64]) -> Self {
+        Histogram {
+            buckets,
+            counts: vec![0; buckets.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.buckets.iter().zip(self.counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bound, count) in self.buckets.iter().zip(&self.counts) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum);
+        let _ = writeln!(out, "{}_count {}", name, self.count);
+    }
+}
+
+struct Registry {
+    /// Keyed by (material, outcome).
+    quotes: BTreeMap<(String, String), u64>,
+    slice_seconds: Histogram,
+    file_size_bytes: Histogram,
+    queue_depth: f64,
+    validation_cache_hits: u64,
+    validation_cache_misses: u64,
+    slicer_retries: u64,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(|| {
+    Mutex::new(Registry {
+        quotes: BTreeMap::new(),
+        slice_seconds: Histogram::new(SLICE_SECONDS_BUCKETS),
+        file_size_bytes: Histogram::new(FILE_SIZE_BUCKETS),
+        queue_depth: 0.0,
+        validation_cache_hits: 0,
+        validation_cache_misses: 0,
+        slicer_retries: 0,
+    })
+});
+
+/// Run `update` against the registry when metrics are switched on.
+fn with_registry(update: impl FnOnce(&mut Registry)) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    // A panic while holding the lock only loses a sample; keep counting.
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    update(&mut registry);
+}
+
+/// Count a finished quote; `outcome` is "success" or "error".
+pub fn record_quote(material: &str, outcome: &str) {
+    with_registry(|r| {
+        *r.quotes
+            .entry((material.to_string(), outcome.to_string()))
+            .or_default() += 1;
+    });
+}
+
+pub fn observe_slice_seconds(seconds: f64) {
+    with_registry(|r| r.slice_seconds.observe(seconds));
+}
+
+pub fn observe_file_size(bytes: u64) {
+    with_registry(|r| r.file_size_bytes.observe(bytes as f64));
+}
+
+pub fn record_validation_cache(hit: bool) {
+    with_registry(|r| {
+        if hit {
+            r.validation_cache_hits += 1;
+        } else {
+            r.validation_cache_misses += 1;
+        }
+    });
+}
+
+/// Count a slicer run started again after a crash.
+pub fn record_slicer_retry() {
+    with_registry(|r| r.slicer_retries += 1);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Current metrics in the Prometheus text exposition format (version 0.0.4).
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::new();
+
+    out.push_str("# HELP orca_quotes_total Quotes processed, by material and outcome.\n");
+    out.push_str("# TYPE orca_quotes_total counter\n");
+    for ((material, outcome), count) in &registry.quotes {
+        let _ = writeln!(
+            out,
+            "orca_quotes_total{{material=\"{}\",outcome=\"{}\"}} {}",
+            escape_label(material),
+            escape_label(outcome),
+            count
+        );
+    }
+    registry.slice_seconds.render(
+        &mut out,
+        "orca_slice_duration_seconds",
+        "Time spent running the slicer.",
+    );
+    registry.file_size_bytes.render(
+        &mut out,
+        "orca_model_file_size_bytes",
+        "Size of uploaded model files.",
+    );
+    out.push_str("# HELP orca_queue_depth Quote jobs waiting in the task queue.\n");
+    out.push_str("# TYPE orca_queue_depth gauge\n");
+    let _ = writeln!(out, "orca_queue_depth {}", registry.queue_depth);
+    out.push_str(
+        "# HELP orca_validation_cache_requests_total Validation cache lookups, by result.\n",
+    );
+    out.push_str("# TYPE orca_validation_cache_requests_total counter\n");
+    let _ = writeln!(
+        out,
+        "orca_validation_cache_requests_total{{result=\"hit\"}} {}",
+        registry.validation_cache_hits
+    );
+    let _ = writeln!(
+        out,
+        "orca_validation_cache_requests_total{{result=\"miss\"}} {}",
+        registry.validation_cache_misses
+    );
+    out.push_str(
+        "# HELP orca_slicer_retries_total Slicer runs started again after a transient failure.\n",
+    );
+    out.push_str("# TYPE orca_slicer_retries_total counter\n");
+    let _ = writeln!(out, "orca_slicer_retries_total {}", registry.slicer_retries);
+    out
+}
+
+/// Answer every request on `listener` with the current metrics.
+fn serve(listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        let mut reader = BufReader::new(&stream);
+        // Drain the request head; the path is ignored so any URL scrapes.
+        let mut line = String::new();
+        while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+            line.clear();
+        }
+        let body = render();
+        let _ = write!(
+            &stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+    }
+}
+
+/// Switch metric collection on or off; samples recorded while off are dropped
+#[pyfunction]
+#[pyo3(signature = (enabled=true))]
+pub fn enable_metrics(enabled: bool) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        ENABLED.store(enabled, Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Metrics in the Prometheus text exposition format
+#[pyfunction]
+pub fn gather_metrics() -> PyResult<String> {
+    panic_boundary::catch(|| Ok(render()))
+}
+
+/// Record a quote outcome with its slicing time and model size, when known
+#[pyfunction]
+#[pyo3(signature = (material, outcome, slice_seconds=None, file_size_bytes=None))]
+pub fn record_quote_metric(
+    material: String,
+    outcome: String,
+    slice_seconds: Option<f64>,
+    file_size_bytes: Option<u64>,
+) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        record_quote(&material, &outcome);
+        if let Some(seconds) = slice_seconds {
+            observe_slice_seconds(seconds);
+            // Feeds queue_status's wait estimate even when metrics are off.
+            record_slice_seconds(seconds);
+        }
+        if let Some(bytes) = file_size_bytes {
+            observe_file_size(bytes);
+        }
+        Ok(())
+    })
+}
+
+/// Set the number of quote jobs waiting in the queue
+#[pyfunction]
+pub fn set_queue_depth(depth: f64) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        with_registry(|r| r.queue_depth = depth);
+        Ok(())
+    })
+}
+
+/// Serve metrics over HTTP on `address` (e.g. "0.0.0.0:9100") from a background thread
+#[pyfunction]
+pub fn serve_metrics(address: String) -> PyResult<u16> {
+    panic_boundary::catch(|| {
+        let listener = TcpListener::bind(&address).map_err(OrcaError::IoError)?;
+        let port = listener.local_addr().map_err(OrcaError::IoError)?.port();
+        thread::Builder::new()
+            .name("orca-metrics".to_string())
+            .spawn(move || serve(listener))
+            .map_err(OrcaError::IoError)?;
+        Ok(port)
+    })
+}
@@ -0,0 +1,282 @@
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::audit;
+use crate::gcode_scan;
+use crate::http_upload::{post_file, read_json, request_error, upload_name};
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Moonraker analyses an upload in the background; ask this often before giving up.
+const METADATA_ATTEMPTS: u32 = 10;
+const METADATA_RETRY: Duration = Duration::from_millis(300);
+
+/// Moonraker (Klipper) server to send accepted quotes to
+#[derive(Clone)]
+#[pyclass]
+pub struct MoonrakerConfig {
+    /// Base URL, e.g. "http://voron.local:7125".
+    #[pyo3(get)]
+    pub url: String,
+    api_key: Option<String>,
+    /// Folder under the `gcodes` root; None uploads to the root.
+    #[pyo3(get)]
+    pub folder: Option<String>,
+    #[pyo3(get)]
+    pub start_print: bool,
+    /// Connect and read timeout; the upload itself may take longer.
+    #[pyo3(get)]
+    pub timeout_secs: f64,
+    /// How far Klipper's estimate may differ from the quoted one, in percent,
+    /// before the upload is flagged.
+    #[pyo3(get)]
+    pub tolerance_percent: f64,
+}
+
+// Written by hand so the API key never ends up in a log line.
+impl fmt::Debug for MoonrakerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MoonrakerConfig")
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("folder", &self.folder)
+            .field("start_print", &self.start_print)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("tolerance_percent", &self.tolerance_percent)
+            .finish()
+    }
+}
+
+#[pymethods]
+impl MoonrakerConfig {
+    fn __str__(&self) -> String {
+        format!(
+            "MoonrakerConfig(url={}, folder={:?}, start_print={}, tolerance={}%)",
+            self.url, self.folder, self.start_print, self.tolerance_percent
+        )
+    }
+}
+
+/// A G-code file stored on Moonraker, with Klipper's time estimate checked
+/// against the one the quote was priced on
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MoonrakerUpload {
+    /// Path under the `gcodes` root, including the folder.
+    #[pyo3(get)]
+    pub remote_path: String,
+    #[pyo3(get)]
+    pub print_started: bool,
+    /// None when Moonraker had no estimate for the file.
+    #[pyo3(get)]
+    pub klipper_estimate_seconds: Option<f64>,
+    #[pyo3(get)]
+    pub quoted_estimate_seconds: Option<f64>,
+    /// (Klipper - quoted) / quoted; positive when the print will take longer than quoted.
+    #[pyo3(get)]
+    pub discrepancy_percent: Option<f64>,
+    /// The discrepancy exceeds the config's tolerance.
+    #[pyo3(get)]
+    pub flagged: bool,
+}
+
+#[pymethods]
+impl MoonrakerUpload {
+    fn __str__(&self) -> String {
+        format!(
+            "MoonrakerUpload(remote_path={}, klipper={:?}s, quoted={:?}s, flagged={})",
+            self.remote_path,
+            self.klipper_estimate_seconds,
+            self.quoted_estimate_seconds,
+            self.flagged
+        )
+    }
+}
+
+fn agent(config: &MoonrakerConfig) -> ureq::Agent {
+    let timeout = Duration::from_secs_f64(config.timeout_secs.max(0.0));
+    ureq::AgentBuilder::new()
+        .timeout_connect(timeout)
+        .timeout_read(timeout)
+        .build()
+}
+
+fn with_key(request: ureq::Request, config: &MoonrakerConfig) -> ureq::Request {
+    match &config.api_key {
+        Some(key) => request.set("X-Api-Key", key),
+        None => request,
+    }
+}
+
+/// `estimated_time` from the file's metadata, waiting for Moonraker to finish
+/// analysing a fresh upload.
+fn klipper_estimate(
+    agent: &ureq::Agent,
+    config: &MoonrakerConfig,
+    remote_path: &str,
+) -> Result<Option<f64>, OrcaError> {
+    let url = format!("{}/server/files/metadata", config.url.trim_end_matches('/'));
+    for attempt in 1..=METADATA_ATTEMPTS {
+        let request = with_key(agent.get(&url), config).query("filename", remote_path);
+        match request.call() {
+            Ok(response) => {
+                let body = read_json(&url, response)?;
+                return Ok(body
+                    .pointer("/result/estimated_time")
+                    .and_then(Value::as_f64));
+            }
+            Err(ureq::Error::Status(404, _)) if attempt < METADATA_ATTEMPTS => {
+                thread::sleep(METADATA_RETRY)
+            }
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(e) => return Err(request_error(&url, e)),
+        }
+    }
+    Ok(None)
+}
+
+/// Print time the G-code header promises, when the caller did not pass the quoted one.
+fn header_estimate_minutes(gcode_path: &Path) -> Result<Option<u32>, OrcaError> {
+    let metadata = gcode_scan::scan_metadata(fs::File::open(gcode_path)?)?;
+    let minutes = metadata.finish(None).print_time_minutes;
+    Ok((minutes > 0).then_some(minutes))
+}
+
+/// Upload `gcode_path` to `POST /server/files/upload` and compare Klipper's
+/// estimate with the quoted print time.
+pub fn upload(
+    gcode_path: &Path,
+    config: &MoonrakerConfig,
+    quoted_print_minutes: Option<u32>,
+) -> Result<MoonrakerUpload, OrcaError> {
+    let quoted_print_minutes = match quoted_print_minutes {
+        Some(minutes) => Some(minutes),
+        None => header_estimate_minutes(gcode_path)?,
+    };
+
+    let mut fields = vec![("root", "gcodes")];
+    if let Some(folder) = &config.folder {
+        fields.push(("path", folder.as_str()));
+    }
+    if config.start_print {
+        fields.push(("print", "true"));
+    }
+    let agent = agent(config);
+    let url = format!("{}/server/files/upload", config.url.trim_end_matches('/'));
+    let response = post_file(with_key(agent.post(&url), config), &fields, gcode_path)?;
+    let body = read_json(&url, response)?;
+    let remote_path = body
+        .pointer("/item/path")
+        .or_else(|| body.pointer("/result/item/path"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let name = upload_name(gcode_path);
+            match &config.folder {
+                Some(folder) => format!("{}/{}", folder.trim_end_matches('/'), name),
+                None => name,
+            }
+        });
+    let print_started = body
+        .get("print_started")
+        .or_else(|| body.pointer("/result/print_started"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let klipper_estimate_seconds = klipper_estimate(&agent, config, &remote_path)?;
+    let quoted_estimate_seconds = quoted_print_minutes.map(|minutes| minutes as f64 * 60.0);
+    let discrepancy_percent = match (klipper_estimate_seconds, quoted_estimate_seconds) {
+        (Some(klipper), Some(quoted)) if quoted > 0.0 => Some((klipper - quoted) / quoted * 100.0),
+        _ => None,
+    };
+    let flagged = discrepancy_percent.is_some_and(|d| d.abs() > config.tolerance_percent);
+    if flagged {
+        tracing::warn!(
+            file = %remote_path,
+            klipper_estimate_seconds,
+            quoted_estimate_seconds,
+            "Klipper's print time estimate differs from the quote"
+        );
+    }
+    Ok(MoonrakerUpload {
+        remote_path,
+        print_started,
+        klipper_estimate_seconds,
+        quoted_estimate_seconds,
+        discrepancy_percent,
+        flagged,
+    })
+}
+
+/// Create the connection settings for `send_to_moonraker`
+#[pyfunction]
+#[pyo3(signature = (url, api_key=None, folder=None, start_print=false, timeout_secs=30.0, tolerance_percent=15.0))]
+pub fn create_moonraker_config(
+    url: String,
+    api_key: Option<String>,
+    folder: Option<String>,
+    start_print: bool,
+    timeout_secs: f64,
+    tolerance_percent: f64,
+) -> PyResult<MoonrakerConfig> {
+    panic_boundary::catch(|| {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(OrcaError::InvalidConfig {
+                path: url,
+                message: "Moonraker URL must start with http:// or https://".to_string(),
+            }
+            .into());
+        }
+        Ok(MoonrakerConfig {
+            url,
+            api_key: api_key.filter(|k| !k.is_empty()),
+            folder: folder.filter(|f| !f.is_empty()),
+            start_print,
+            timeout_secs,
+            tolerance_percent,
+        })
+    })
+}
+
+/// Upload G-code to Moonraker and cross-check Klipper's time estimate with the quote
+///
+/// `quoted_print_minutes` defaults to the estimate in the G-code header. With an
+/// `audit_log_path`, the comparison is recorded as a `moonraker_estimate` event.
+#[pyfunction]
+#[pyo3(signature = (gcode_path, moonraker_config, quoted_print_minutes=None, quote_id=None, audit_log_path=None))]
+pub fn send_to_moonraker(
+    py: Python<'_>,
+    gcode_path: String,
+    moonraker_config: PyRef<'_, MoonrakerConfig>,
+    quoted_print_minutes: Option<u32>,
+    quote_id: Option<String>,
+    audit_log_path: Option<String>,
+) -> PyResult<MoonrakerUpload> {
+    panic_boundary::catch(|| {
+        let config = moonraker_config.clone();
+        let result = py.allow_threads(|| {
+            let upload = upload(Path::new(&gcode_path), &config, quoted_print_minutes)?;
+            if let Some(log_path) = &audit_log_path {
+                audit::append_event(
+                    Path::new(log_path),
+                    "moonraker_estimate",
+                    json!({
+                        "quote_id": quote_id,
+                        "file": upload.remote_path,
+                        "klipper_estimate_seconds": upload.klipper_estimate_seconds,
+                        "quoted_estimate_seconds": upload.quoted_estimate_seconds,
+                        "discrepancy_percent": upload.discrepancy_percent,
+                        "flagged": upload.flagged,
+                    }),
+                )?;
+            }
+            Ok::<_, OrcaError>(upload)
+        });
+        Ok(result?)
+    })
+}
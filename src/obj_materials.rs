@@ -0,0 +1,102 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+/// The material libraries and materials an OBJ file refers to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ObjMaterials {
+    /// `mtllib` files, as written in the OBJ.
+    #[pyo3(get)]
+    pub libraries: Vec<String>,
+    /// Those not found beside the OBJ.
+    #[pyo3(get)]
+    pub missing_libraries: Vec<String>,
+    /// `usemtl` names, in order of first use.
+    #[pyo3(get)]
+    pub materials: Vec<String>,
+    /// For the customer: the colours and materials are not used in the quote.
+    #[pyo3(get)]
+    pub warning: String,
+}
+
+#[pymethods]
+impl ObjMaterials {
+    fn __str__(&self) -> String {
+        format!(
+            "ObjMaterials(libraries={:?}, missing={:?}, materials={:?})",
+            self.libraries, self.missing_libraries, self.materials
+        )
+    }
+}
+
+/// `mtllib` and `usemtl` statements gathered from an OBJ's lines.
+#[derive(Default)]
+pub(crate) struct MaterialRefs {
+    libraries: Vec<String>,
+    materials: Vec<String>,
+}
+
+impl MaterialRefs {
+    /// Note a line if it names a library or material; the name is the rest
+    /// of the line, so names with spaces are kept whole.
+    pub fn scan(&mut self, line: &str) {
+        let (list, rest) = if let Some(rest) = line.strip_prefix("mtllib") {
+            (&mut self.libraries, rest)
+        } else if let Some(rest) = line.strip_prefix("usemtl") {
+            (&mut self.materials, rest)
+        } else {
+            return;
+        };
+        if !rest.starts_with(char::is_whitespace) {
+            return;
+        }
+        let name = rest.trim();
+        if !name.is_empty() && !list.iter().any(|seen| seen == name) {
+            list.push(name.to_string());
+        }
+    }
+
+    /// What was referenced, checking libraries against the OBJ's directory;
+    /// `None` when the file names no materials.
+    pub fn finish(self, obj_path: &Path) -> Option<ObjMaterials> {
+        if self.libraries.is_empty() && self.materials.is_empty() {
+            return None;
+        }
+        let dir = obj_path.parent().unwrap_or(Path::new(""));
+        // Only paths inside the upload's directory are looked for.
+        let found = |library: &String| {
+            let relative = Path::new(library);
+            relative
+                .components()
+                .all(|part| matches!(part, Component::Normal(_) | Component::CurDir))
+                && dir.join(relative).is_file()
+        };
+        let missing_libraries: Vec<String> = self
+            .libraries
+            .iter()
+            .filter(|library| !found(library))
+            .cloned()
+            .collect();
+        let mut warning = if self.materials.is_empty() {
+            "The model's material library is ignored".to_string()
+        } else {
+            format!(
+                "Colours and materials in the model ({}) are ignored; it is printed in the material you choose",
+                self.materials.join(", ")
+            )
+        };
+        if !missing_libraries.is_empty() {
+            warning.push_str(&format!(
+                ", and {} was not uploaded with it",
+                missing_libraries.join(", ")
+            ));
+        }
+        Some(ObjMaterials {
+            libraries: self.libraries,
+            missing_libraries,
+            materials: self.materials,
+            warning,
+        })
+    }
+}
@@ -0,0 +1,191 @@
+use pyo3::prelude::*;
+use sanitize_filename::sanitize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::geometry::{fan, obj_face, parse_point, BoundingBox, Triangle};
+use crate::memory_limits::{self, Budget};
+use crate::panic_boundary;
+use crate::stl_convert::write_binary_stl;
+use crate::OrcaError;
+
+/// Name for faces that come before any `o` or `g` line.
+const UNNAMED: &str = "default";
+
+/// One object of a multi-object OBJ file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ObjObject {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub triangles: u64,
+    /// Lowest corner of the object's bounding box.
+    #[pyo3(get)]
+    pub min_mm: (f64, f64, f64),
+    /// Highest corner of the object's bounding box.
+    #[pyo3(get)]
+    pub max_mm: (f64, f64, f64),
+    /// Width, depth and height of the bounding box.
+    #[pyo3(get)]
+    pub dimensions_mm: (f64, f64, f64),
+}
+
+#[pymethods]
+impl ObjObject {
+    fn __str__(&self) -> String {
+        format!(
+            "ObjObject(name={}, triangles={}, size={:?})",
+            self.name, self.triangles, self.dimensions_mm
+        )
+    }
+}
+
+/// Names seen on `o` or `g` lines, numbered in order of first use.
+#[derive(Default)]
+struct Names {
+    ids: HashMap<String, u32>,
+    names: Vec<String>,
+}
+
+impl Names {
+    fn id(&mut self, name: &str) -> u32 {
+        let name = if name.is_empty() { UNNAMED } else { name };
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        self.names.push(name.to_string());
+        self.ids
+            .insert(name.to_string(), self.names.len() as u32 - 1);
+        self.names.len() as u32 - 1
+    }
+}
+
+/// An OBJ file's triangles, split by object. Files with `o` lines are split
+/// by them, keeping any `g` groups inside an object together; files with
+/// only `g` lines are split by group. Objects named more than once are
+/// merged, and faces before the first name go in "default".
+pub(crate) fn obj_groups(path: &Path) -> std::io::Result<Vec<(String, Vec<Triangle>)>> {
+    let reader = BufReader::new(File::open(path)?);
+    let (mut objects, mut groups) = (Names::default(), Names::default());
+    let (mut object, mut group) = (objects.id(""), groups.id(""));
+    let mut any_object = false;
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let mut triangles = Vec::new();
+    // (object, group) of each triangle, and the triangles of one face.
+    let mut owners: Vec<(u32, u32)> = Vec::new();
+    let mut face = Vec::new();
+    for line in memory_limits::lines(reader, Budget::MeshAnalysis) {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => vertices.extend(parse_point(parts).map(|p| p.map(|v| v as f32))),
+            Some("f") => {
+                fan(&obj_face(parts, &vertices), &mut face);
+                owners.extend(face.iter().map(|_| (object, group)));
+                triangles.append(&mut face);
+            }
+            Some("o") => {
+                any_object = true;
+                object = objects.id(&parts.collect::<Vec<_>>().join(" "));
+            }
+            Some("g") => group = groups.id(&parts.collect::<Vec<_>>().join(" ")),
+            _ => {}
+        }
+    }
+
+    let names = if any_object {
+        objects.names
+    } else {
+        groups.names
+    };
+    let mut split: Vec<Vec<Triangle>> = vec![Vec::new(); names.len()];
+    for (triangle, (object, group)) in triangles.into_iter().zip(owners) {
+        split[if any_object { object } else { group } as usize].push(triangle);
+    }
+    Ok(names
+        .into_iter()
+        .zip(split)
+        .filter(|(_, triangles)| !triangles.is_empty())
+        .collect())
+}
+
+/// Each object's name, triangle count and bounding box.
+pub fn obj_objects(path: &Path) -> Result<Vec<ObjObject>, OrcaError> {
+    if !path.exists() {
+        return Err(OrcaError::FileNotFound(path.display().to_string()));
+    }
+    let objects = obj_groups(path)?
+        .into_iter()
+        .map(|(name, triangles)| {
+            let mut bounds = BoundingBox::empty();
+            for corner in triangles.iter().flatten() {
+                bounds.include(corner.map(f64::from));
+            }
+            ObjObject {
+                name,
+                triangles: triangles.len() as u64,
+                min_mm: (bounds.min[0], bounds.min[1], bounds.min[2]),
+                max_mm: (bounds.max[0], bounds.max[1], bounds.max[2]),
+                dimensions_mm: bounds.size(),
+            }
+        })
+        .collect();
+    Ok(objects)
+}
+
+/// List the objects (`o`) or groups (`g`) of an OBJ file
+///
+/// Returns each object's name, triangle count and bounding box, in the order
+/// they first appear. Files with `o` lines are split by them; files with
+/// only `g` lines by group. Faces before any name are in "default".
+#[pyfunction]
+pub fn list_obj_objects(py: Python<'_>, file_path: String) -> PyResult<Vec<ObjObject>> {
+    panic_boundary::catch(|| Ok(py.allow_threads(|| obj_objects(Path::new(&file_path)))?))
+}
+
+/// Write each object of an OBJ file to its own binary STL
+///
+/// Objects are split as `list_obj_objects` lists them and written to
+/// `output_dir` as `<stem>.<object>.stl`, so each can be quoted as its own
+/// line item, e.g. with `run_order_pipeline`. Returns the paths in the same
+/// order.
+#[pyfunction]
+pub fn split_obj_objects(
+    py: Python<'_>,
+    file_path: String,
+    output_dir: String,
+) -> PyResult<Vec<String>> {
+    panic_boundary::catch(|| {
+        py.allow_threads(|| {
+            let path = Path::new(&file_path);
+            if !path.exists() {
+                return Err(OrcaError::FileNotFound(file_path.clone()).into());
+            }
+            let stem = path
+                .file_stem()
+                .map_or("model".into(), |stem| stem.to_string_lossy());
+            fs::create_dir_all(&output_dir).map_err(OrcaError::IoError)?;
+            let mut used = HashSet::new();
+            let mut written = Vec::new();
+            for (name, triangles) in obj_groups(path).map_err(OrcaError::IoError)? {
+                // Names that sanitize to the same file get a number.
+                let base = format!("{}.{}", stem, sanitize(&name));
+                let mut file_name = format!("{}.stl", base);
+                for n in 2.. {
+                    if used.insert(file_name.clone()) {
+                        break;
+                    }
+                    file_name = format!("{}-{}.stl", base, n);
+                }
+                let output = Path::new(&output_dir).join(file_name);
+                write_binary_stl(&output, &triangles).map_err(OrcaError::IoError)?;
+                written.push(output.to_string_lossy().into_owned());
+            }
+            Ok(written)
+        })
+    })
+}
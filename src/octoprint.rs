@@ -0,0 +1,166 @@
+use pyo3::prelude::*;
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::http_upload::{post_file, read_json, upload_name};
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// OctoPrint server to send accepted quotes to
+#[derive(Clone)]
+#[pyclass]
+pub struct OctoPrintConfig {
+    /// Base URL, e.g. "http://octopi.local".
+    #[pyo3(get)]
+    pub url: String,
+    api_key: String,
+    /// Folder on the printer's local storage; None uploads to the root.
+    #[pyo3(get)]
+    pub folder: Option<String>,
+    /// Select the file and start printing it once uploaded.
+    #[pyo3(get)]
+    pub start_print: bool,
+    /// Connect and read timeout; the upload itself may take longer.
+    #[pyo3(get)]
+    pub timeout_secs: f64,
+}
+
+// Written by hand so the API key never ends up in a log line.
+impl fmt::Debug for OctoPrintConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OctoPrintConfig")
+            .field("url", &self.url)
+            .field("api_key", &"<redacted>")
+            .field("folder", &self.folder)
+            .field("start_print", &self.start_print)
+            .field("timeout_secs", &self.timeout_secs)
+            .finish()
+    }
+}
+
+#[pymethods]
+impl OctoPrintConfig {
+    fn __str__(&self) -> String {
+        format!(
+            "OctoPrintConfig(url={}, folder={:?}, start_print={})",
+            self.url, self.folder, self.start_print
+        )
+    }
+}
+
+/// A G-code file stored on OctoPrint
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct OctoPrintUpload {
+    /// Path on the printer's local storage, including the folder.
+    #[pyo3(get)]
+    pub remote_path: String,
+    /// False while OctoPrint is still post-processing the file.
+    #[pyo3(get)]
+    pub done: bool,
+    #[pyo3(get)]
+    pub print_started: bool,
+    /// API URL of the stored file.
+    #[pyo3(get)]
+    pub resource: Option<String>,
+}
+
+#[pymethods]
+impl OctoPrintUpload {
+    fn __str__(&self) -> String {
+        format!(
+            "OctoPrintUpload(remote_path={}, done={}, print_started={})",
+            self.remote_path, self.done, self.print_started
+        )
+    }
+}
+
+/// Upload `gcode_path` to `POST /api/files/local`.
+pub fn upload(gcode_path: &Path, config: &OctoPrintConfig) -> Result<OctoPrintUpload, OrcaError> {
+    let mut fields = Vec::new();
+    if let Some(folder) = &config.folder {
+        fields.push(("path", folder.as_str()));
+    }
+    if config.start_print {
+        fields.push(("select", "true"));
+        fields.push(("print", "true"));
+    }
+    let url = format!("{}/api/files/local", config.url.trim_end_matches('/'));
+    let timeout = Duration::from_secs_f64(config.timeout_secs.max(0.0));
+    let request = ureq::AgentBuilder::new()
+        .timeout_connect(timeout)
+        .timeout_read(timeout)
+        .build()
+        .post(&url)
+        .set("X-Api-Key", &config.api_key);
+    let response = post_file(request, &fields, gcode_path)?;
+    let body = read_json(&url, response)?;
+
+    let file_name = upload_name(gcode_path);
+    let remote_path = body
+        .pointer("/files/local/path")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| match &config.folder {
+            Some(folder) => format!("{}/{}", folder.trim_end_matches('/'), file_name),
+            None => file_name.clone(),
+        });
+    let done = body.get("done").and_then(Value::as_bool).unwrap_or(true);
+    // Only OctoPrint 1.8+ reports whether the print actually started.
+    let print_started = body
+        .get("effectivePrint")
+        .and_then(Value::as_bool)
+        .unwrap_or(config.start_print);
+    Ok(OctoPrintUpload {
+        remote_path,
+        done,
+        print_started,
+        resource: body
+            .pointer("/files/local/refs/resource")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+/// Create the connection settings for `send_to_octoprint`
+#[pyfunction]
+#[pyo3(signature = (url, api_key, folder=None, start_print=false, timeout_secs=30.0))]
+pub fn create_octoprint_config(
+    url: String,
+    api_key: String,
+    folder: Option<String>,
+    start_print: bool,
+    timeout_secs: f64,
+) -> PyResult<OctoPrintConfig> {
+    panic_boundary::catch(|| {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(OrcaError::InvalidConfig {
+                path: url,
+                message: "OctoPrint URL must start with http:// or https://".to_string(),
+            }
+            .into());
+        }
+        Ok(OctoPrintConfig {
+            url,
+            api_key,
+            folder: folder.filter(|f| !f.is_empty()),
+            start_print,
+            timeout_secs,
+        })
+    })
+}
+
+/// Upload G-code to OctoPrint, starting the print when the config asks for it
+#[pyfunction]
+pub fn send_to_octoprint(
+    py: Python<'_>,
+    gcode_path: String,
+    octoprint_config: PyRef<'_, OctoPrintConfig>,
+) -> PyResult<OctoPrintUpload> {
+    panic_boundary::catch(|| {
+        let config = octoprint_config.clone();
+        Ok(py.allow_threads(|| upload(Path::new(&gcode_path), &config))?)
+    })
+}
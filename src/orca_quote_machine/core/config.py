@@ -3,6 +3,7 @@
 import os
 from functools import lru_cache
 from pathlib import Path
+from typing import Literal
 
 from pydantic import BaseModel, field_validator, model_validator
 from pydantic_settings import BaseSettings, SettingsConfigDict
@@ -17,15 +18,18 @@ class SlicerProfileSettings(BaseModel):
     machine: str = "RatRig V-Core 3 400 0.5 nozzle.json"
     process: str = "0.2mm RatRig 0.5mm nozzle.json"
 
-    # Per-material filament profiles for official materials
-    # These act as overrides for the default file-based convention
-    filament_pla: str = "ALT TABL MATTE PLA PEI.json"
-    filament_petg: str = "Alt Tab PETG.json"
-    filament_asa: str = "fusrock ASA.json"
+    # Material -> filament profile mapping (TOML or JSON)
+    material_map: Path = Path("config/material_profiles.toml")
+
+    # Extra directories searched for `inherits` parents, e.g. OrcaSlicer's system profiles
+    system_dirs: list[Path] = []
+
+    # Printer fleet (TOML); None slices everything with the default machine above
+    fleet: Path | None = None
 
     @model_validator(mode="after")
     def validate_profiles_exist(self) -> "SlicerProfileSettings":
-        """Validate that all configured profile files exist.
+        """Validate that the configured profile files and mapping file exist.
 
         Skip validation in test environments or when SKIP_PROFILE_VALIDATION is set.
         """
@@ -36,9 +40,6 @@ class SlicerProfileSettings(BaseModel):
         profiles_to_check = [
             ("machine", self.machine),
             ("process", self.process),
-            ("filament", self.filament_pla),
-            ("filament", self.filament_petg),
-            ("filament", self.filament_asa),
         ]
         for profile_type, filename in profiles_to_check:
             profile_path = self.base_dir / profile_type / filename
@@ -46,6 +47,10 @@ class SlicerProfileSettings(BaseModel):
                 raise ValueError(
                     f"{profile_type.capitalize()} profile not found at: {profile_path}"
                 )
+        if not self.material_map.exists():
+            raise ValueError(f"Material mapping file not found at: {self.material_map}")
+        if self.fleet is not None and not self.fleet.exists():
+            raise ValueError(f"Fleet file not found at: {self.fleet}")
         return self
 
 
@@ -63,13 +68,62 @@ class Settings(BaseSettings):
     # File upload settings
     max_file_size: int = 100 * 1024 * 1024  # 100MB
     upload_dir: str = "uploads"
-    allowed_extensions: list[str] = [".stl", ".obj", ".step", ".stp"]
+    allowed_extensions: list[str] = [
+        ".stl", ".obj", ".step", ".stp", ".3mf", ".amf", ".ply", ".zip", ".gz"
+    ]
+    # Resumable uploads (/uploads) keep their chunks here until quoted; ones
+    # not finished within upload_session_max_age_hours are removed
+    upload_session_dir: str = "uploads/sessions"
+    upload_session_max_age_hours: float = 24
+    upload_chunk_max_bytes: int = 8 * 1024 * 1024
+    # Largest model a ZIP or gzip upload may unpack to
+    max_unpacked_mb: int = 512
+    # How many times its compressed size an archived model may unpack to
+    max_unpack_ratio: int = 100
+    # Validate models at least this large (e.g. 3D scans) from a memory map
+    mmap_threshold_mb: int = 64
+    # Refuse models whose mesh has holes or non-manifold edges
+    require_watertight_mesh: bool = False
+    # Repair meshes (normals, degenerate triangles, small holes) before slicing
+    repair_meshes: bool = False
+    # Convert ASCII STL uploads to binary before slicing
+    convert_ascii_stl: bool = False
+    # Simplify meshes with more triangles than this (e.g. 3D scans) before
+    # slicing. None: slice them as uploaded
+    max_triangles: int | None = None
+    # Refuse models with more triangles, or ASCII STL/OBJ/STEP files with more
+    # lines, than these (along with uploads over max_file_size). 0 = no limit
+    max_model_triangles: int = 20_000_000
+    max_model_lines: int = 20_000_000
+    # Malware scanning of uploads before they are sliced: either a command
+    # (program and arguments, e.g. ["clamdscan", "--no-summary"]) run with the
+    # upload path appended, or a clamd socket (Unix socket path or host:port)
+    malware_scan_command: list[str] = []
+    clamd_socket: str | None = None
+    malware_scan_timeout_secs: float = 60.0
+    # Quote requests allowed per mobile number per hour, after a burst of
+    # quote_rate_burst; counted per API process. 0 = no limit
+    quote_rate_per_hour: float = 0
+    quote_rate_burst: int = 5
 
     # OrcaSlicer settings
     orcaslicer_cli_path: str = (
         "/var/lib/flatpak/exports/bin/io.github.softfever.OrcaSlicer"
     )
     slicer_timeout: int = 300  # 5 minutes
+    # A slicer that crashes (GPU/X errors, killed by a signal) is run again, up to
+    # this many runs in all, waiting slicer_retry_backoff seconds before the second
+    # and twice as long before each one after. 1 = never retried
+    slicer_max_attempts: int = 2
+    slicer_retry_backoff: float = 2.0
+    # Limits on each slicer process, so a pathological model cannot take the host
+    # down: CPU seconds and address space in MB (0 = no limit), niceness (0 =
+    # unchanged; 10 runs it behind the web tier), and a cgroup v2 directory every
+    # slicer joins, whose memory.max and cpu.max then bound them all together
+    slicer_cpu_limit_secs: int = 0
+    slicer_memory_limit_mb: int = 0
+    slicer_nice: int = 0
+    slicer_cgroup: str | None = None
     slicer_profiles: SlicerProfileSettings | None = None
 
     # Pricing settings
@@ -78,7 +132,58 @@ class Settings(BaseSettings):
     minimum_price: float = 5.0  # S$5 minimum
     additional_time_hours: float = 0.5  # Add 30 minutes to print time
 
-    # Material pricing (per kg)
+    # Material stock: a TOML table of [[stock]] entries (material, optional color,
+    # remaining_grams) and/or a Spoolman server. Out-of-stock materials are left
+    # out of the form and refused before slicing; materials not listed are never
+    # refused. Spoolman is asked at most every inventory_max_age_secs
+    inventory_table_path: str | None = None
+    spoolman_url: str | None = None
+    low_stock_grams: float = 200.0
+    inventory_max_age_secs: float = 60.0
+
+    # Time-of-use pricing: print time inside an off-peak window, [start_hour,
+    # end_hour, rate_multiplier] in shop time (business_utc_offset_hours) on
+    # off_peak_days (0 = Monday), is charged at the reduced hourly rate. Quotes
+    # then carry an off-peak price next to the start-anytime one. Empty: off
+    off_peak_windows: list[tuple[int, int, float]] = []
+    off_peak_days: list[int] = [0, 1, 2, 3, 4, 5, 6]
+
+    # Estimate calibration: finished prints' actual time and filament are
+    # recorded next to the slicer's estimates in print_history_path. With
+    # apply_estimate_calibration, each material's estimates are scaled by its
+    # actual/estimated ratio over the last calibration_window prints, once it
+    # has calibration_min_samples of them
+    print_history_path: str | None = None
+    apply_estimate_calibration: bool = False
+    calibration_window: int = 50
+    calibration_min_samples: int = 5
+
+    # Order plating: copies of a part are laid out on as few plates as the bed
+    # allows, plate_spacing_mm apart; plate_overhead_minutes of each sliced copy
+    # (heat-up, homing, purge) is counted once per plate rather than per copy
+    plate_spacing_mm: float = 6.0
+    plate_overhead_minutes: float = 10.0
+
+    # Side in pixels of the model preview sent with each quote: the slicer's
+    # G-code thumbnail when it wrote one, else a render of the mesh. None: no preview
+    preview_size: int | None = None
+
+    # Layer heights (mm) customers may pick from; their infill % and supports
+    # choice are free. All three are applied to a copy of the process profile
+    layer_height_choices: list[float] = [0.12, 0.2, 0.28]
+
+    # How summaries and notifications write numbers: display_locale sets digit
+    # grouping and decimal mark ("en_SG" 1,234.50, "de_DE" 1.234,50),
+    # currency_format places the amount ("{amount} €") and display_units
+    # "imperial" shows filament in oz/lb. Prices are still configured per kg
+    display_locale: str = "en_SG"
+    currency_format: str = "S${amount}"
+    display_units: Literal["metric", "imperial"] = "metric"
+
+    # Material catalog (TOML); None uses the built-in PLA/PETG/ASA catalog
+    material_catalog_path: str | None = None
+
+    # Material pricing (per kg); overrides catalog prices
     material_prices: dict = {
         "PLA": 25.0,
         "PETG": 30.0,
@@ -93,10 +198,157 @@ class Settings(BaseSettings):
     # Telegram bot settings
     telegram_bot_token: str | None = None
     telegram_admin_chat_id: str | None = None
+    # Secret token given to Telegram's setWebhook; enables POST /webhooks/telegram
+    telegram_webhook_secret: str | None = None
+
+    # OctoPrint server accepted quotes are sent to; both must be set to enable it
+    octoprint_url: str | None = None
+    octoprint_api_key: str | None = None
+    octoprint_folder: str | None = None
+    octoprint_start_print: bool = False
+
+    # Moonraker (Klipper) server accepted quotes are sent to; the API key is only
+    # needed when Moonraker requires one. Klipper's print time estimate is compared
+    # with the quoted one and recorded in the audit log, flagged past the tolerance
+    moonraker_url: str | None = None
+    moonraker_api_key: str | None = None
+    moonraker_folder: str | None = None
+    moonraker_start_print: bool = False
+    moonraker_estimate_tolerance_percent: float = 15.0
+
+    # Lead time quoted from the printer farm's current load: fleet printers with a
+    # moonraker_url or octoprint_url are polled, reusing a status for max_age
+    # seconds. Queued jobs count as farm_queued_job_hours each, and
+    # farm_handling_hours is added for post-processing and packing
+    lead_time_enabled: bool = False
+    farm_status_max_age_secs: float = 30.0
+    farm_status_timeout_secs: float = 5.0
+    farm_queued_job_hours: float = 2.0
+    farm_handling_hours: float = 0.0
+
+    # Business calendar for promised completion dates: printers run around the
+    # clock, but handling only happens between business_open_hour and
+    # business_close_hour (shop time, business_utc_offset_hours) on business_days
+    # (0 = Monday) that are not business_holidays ("YYYY-MM-DD")
+    business_calendar_enabled: bool = False
+    business_utc_offset_hours: float = 8.0
+    business_open_hour: int = 9
+    business_close_hour: int = 18
+    business_days: list[int] = [0, 1, 2, 3, 4]
+    business_holidays: list[str] = []
+
+    # Stripe Checkout link for each quote, included in the notification; needs both
+    # the secret key and the success URL. The cancel URL defaults to the success URL
+    stripe_api_key: str | None = None
+    stripe_currency: str = "sgd"
+    stripe_success_url: str | None = None
+    stripe_cancel_url: str | None = None
+    # Signing secret (whsec_...) of the Stripe endpoint; enables POST /webhooks/stripe
+    stripe_webhook_secret: str | None = None
+
+    # Carrier shipping rates for quotes sent with a postal code (in shipping_country),
+    # from EasyPost or any API compatible with its /shipments endpoint. The parcel
+    # is the model's bounding box plus padding on every side, weighing the filament
+    # plus packaging. from_address uses EasyPost's fields, e.g. {"street1": ...,
+    # "zip": ..., "country": "SG"}; carriers limits the rates to those carriers
+    shipping_api_key: str | None = None
+    shipping_api_base: str = "https://api.easypost.com/v2"
+    shipping_from_address: dict[str, str] = {}
+    shipping_country: str = "SG"
+    shipping_carriers: list[str] = []
+    shipping_padding_mm: float = 20.0
+    shipping_packaging_grams: float = 150.0
+
+    # PayNow QR code sent with each quote notification; set one of the UEN or the
+    # mobile number that receives the transfer. The quote ID is the bill reference
+    paynow_uen: str | None = None
+    paynow_mobile: str | None = None
+    paynow_merchant_name: str = "NA"
+
+    # Bookkeeping ledger: each completed quote is appended as a row to CSV files
+    # in this directory (rotated "monthly", "yearly" or "none") and/or to a Google
+    # Sheet shared with the service account
+    ledger_csv_dir: str | None = None
+    ledger_csv_rotation: str = "monthly"
+    google_sheets_spreadsheet_id: str | None = None
+    google_service_account_path: str | None = None
+    google_sheets_sheet: str = "Quotes"
+
+    # Pipeline events (quote.created, quote.failed, quote.requoted, printer.assigned,
+    # notification.sent, job.sent, job.failed, and payment.succeeded,
+    # payment.failed, quote.approved, quote.rejected from webhooks) for other systems: POSTed to a
+    # webhook, signed with the secret when set, and/or published to
+    # <topic prefix>/<event type> on an MQTT broker. Topics maps event types to
+    # templates using {prefix}, {type}, {quote_id} and {printer}; "" drops a type
+    event_webhook_url: str | None = None
+    event_webhook_secret: str | None = None
+    event_mqtt_host: str | None = None
+    event_mqtt_port: int = 1883
+    event_mqtt_topic_prefix: str = "orca-quote"
+    event_mqtt_topics: dict = {}
+    event_mqtt_retain: bool = False
+    event_mqtt_username: str | None = None
+    event_mqtt_password: str | None = None
+
+    # Audit log (JSON lines); None disables audit recording
+    audit_log_path: str | None = None
+
+    # Quotes from the Rust pipeline kept by quote ID (one JSON file each) so an
+    # operator can adjust the price afterwards; None keeps nothing
+    quote_store_dir: str | None = None
+
+    # Content hashes of quoted uploads (JSON lines) for spotting the same model
+    # uploaded again; None keeps no index
+    upload_index_path: str | None = None
+
+    # Validation results cached by file content (number of entries); 0 disables the cache
+    validation_cache_size: int = 0
+
+    # Memory budgets (MB) for G-code parsing and mesh validation/analysis; going
+    # over one fails that file with MemoryError instead of growing the worker. 0 = off
+    gcode_parse_memory_mb: int = 0
+    mesh_analysis_memory_mb: int = 0
+
+    # Slicer processes allowed to run at once in each worker process. The service's
+    # slicer pool queues the rest; 0 sizes it by CPU count and leaves the quote
+    # pipeline unlimited
+    max_concurrent_slicers: int = 0
+
+    # Sliced G-code kept by model and profile hash for printing accepted quotes;
+    # None discards it. The size budget is in megabytes; 0 means unbounded.
+    gcode_cache_dir: str | None = None
+    gcode_cache_max_mb: int = 1024
+
+    # Post-processing of sliced G-code before it is cached or sent to a printer:
+    # thumbnails stripped, start/end snippets inserted around the commands, then
+    # a script (program and arguments) run with the file path appended. Each
+    # processed file is recorded in the audit log
+    gcode_strip_thumbnails: bool = False
+    gcode_start_snippet: str | None = None
+    gcode_end_snippet: str | None = None
+    gcode_postprocess_script: list[str] = []
+    gcode_postprocess_timeout_secs: float = 60.0
+
+    # Compile regexes and parse every slicer profile when a worker process starts;
+    # the calibration slice also quotes a small cube so the slicer's files are warm
+    warm_up_on_start: bool = True
+    warm_up_calibration: bool = False
+
+    # Prometheus metrics: /metrics on the web app, metrics_port (+ pool index) on workers
+    metrics_enabled: bool = False
+    metrics_port: int | None = None
+
+    # JSON-lines log of Rust-side events ("stderr", "stdout" or a file path); None disables it
+    rust_log_sink: str | None = None
+    rust_log_level: str = "info"
 
     # Security
     secret_key: str  # Must be set via environment variable
 
+    # Customer portal links (/portal/<token>) signed with secret_key, which must be
+    # at least 16 bytes for links to be issued; they stop working after this long
+    portal_token_ttl_hours: float = 168.0
+
     model_config = SettingsConfigDict(
         env_file=".env",
         case_sensitive=False,
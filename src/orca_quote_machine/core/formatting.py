@@ -0,0 +1,89 @@
+"""Number, currency and unit formatting for summaries and notifications."""
+
+from typing import TYPE_CHECKING, Literal
+
+from pydantic import BaseModel, ConfigDict
+
+if TYPE_CHECKING:
+    from orca_quote_machine.core.config import Settings
+
+GRAMS_PER_OUNCE = 28.349523125
+GRAMS_PER_POUND = 453.59237
+
+# (thousands separator, decimal separator) by locale, then by language
+_SEPARATORS: dict[str, tuple[str, str]] = {
+    "en": (",", "."),
+    "zh": (",", "."),
+    "ja": (",", "."),
+    "ms": (",", "."),
+    "de": (".", ","),
+    "de_CH": ("'", "."),
+    "es": (".", ","),
+    "id": (".", ","),
+    "it": (".", ","),
+    "nl": (".", ","),
+    "pt": (".", ","),
+    "fr": ("\u00a0", ","),
+    "ru": ("\u00a0", ","),
+    "sv": ("\u00a0", ","),
+}
+
+
+class DisplayFormat(BaseModel):
+    """How amounts, weights and times are written for people."""
+
+    model_config = ConfigDict(frozen=True)
+
+    # e.g. "en_SG" (1,234.50), "de_DE" (1.234,50) or "fr_FR" (1 234,50)
+    locale: str = "en_SG"
+    # Where the amount goes, e.g. "S${amount}" or "{amount} €"
+    currency_format: str = "S${amount}"
+    units: Literal["metric", "imperial"] = "metric"
+
+    @classmethod
+    def from_settings(cls: type["DisplayFormat"], settings: "Settings") -> "DisplayFormat":
+        """Display format configured for this deployment."""
+        return cls(
+            locale=settings.display_locale,
+            currency_format=settings.currency_format,
+            units=settings.display_units,
+        )
+
+    def number(self: "DisplayFormat", value: float, decimals: int = 2) -> str:
+        """A number with the locale's digit grouping and decimal mark."""
+        locale = self.locale.replace("-", "_")
+        thousands, decimal = _SEPARATORS.get(
+            locale, _SEPARATORS.get(locale.split("_")[0], _SEPARATORS["en"])
+        )
+        grouped = f"{value:,.{decimals}f}"
+        return grouped.replace(",", "\0").replace(".", decimal).replace("\0", thousands)
+
+    def money(self: "DisplayFormat", amount: float) -> str:
+        """An amount in the shop's currency, e.g. "S$12.50"."""
+        return self.currency_format.format(amount=self.number(amount))
+
+    def weight(self: "DisplayFormat", grams: float) -> str:
+        """A part or filament weight, e.g. "25.5g" or "0.90oz"."""
+        if self.units == "imperial":
+            return f"{self.number(grams / GRAMS_PER_OUNCE)}oz"
+        return f"{self.number(grams, 1)}g"
+
+    def bulk_weight(self: "DisplayFormat", grams: float) -> str:
+        """A weight in the unit filament is sold by, e.g. "0.026kg" or "0.056lb"."""
+        if self.units == "imperial":
+            return f"{self.number(grams / GRAMS_PER_POUND, 3)}lb"
+        return f"{self.number(grams / 1000, 3)}kg"
+
+    def price_per_bulk_unit(self: "DisplayFormat", price_per_kg: float) -> str:
+        """A filament price per kg or per lb, e.g. "S$25.00/kg"."""
+        if self.units == "imperial":
+            return f"{self.money(price_per_kg * GRAMS_PER_POUND / 1000)}/lb"
+        return f"{self.money(price_per_kg)}/kg"
+
+    def hours(self: "DisplayFormat", hours: float) -> str:
+        """A duration in decimal hours, e.g. "2.5h"."""
+        return f"{self.number(hours, 1)}h"
+
+    def duration(self: "DisplayFormat", minutes: int) -> str:
+        """A print time in hours and minutes, e.g. "2h 30m"."""
+        return f"{minutes // 60}h {minutes % 60}m"
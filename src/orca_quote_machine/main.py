@@ -1,6 +1,7 @@
 """FastAPI application for OrcaSlicer quotation machine."""
 
 import contextlib
+import math
 import os
 import uuid
 from pathlib import Path
@@ -18,17 +19,41 @@ from fastapi import (
     UploadFile,
     status,
 )
-from fastapi.responses import HTMLResponse, JSONResponse
+from fastapi.responses import HTMLResponse, JSONResponse, PlainTextResponse
 from fastapi.staticfiles import StaticFiles
 from fastapi.templating import Jinja2Templates
+from starlette.concurrency import run_in_threadpool
 from starlette.responses import Response
 
-from orca_quote_machine._rust_core import secure_filename
+from orca_quote_machine._rust_core import (
+    InternalError,
+    UploadSession,
+    WebhookEvent,
+    create_rate_limiter,
+    create_streaming_validator,
+    create_upload_session_store,
+    emit_event,
+    enable_metrics,
+    export_schemas,
+    gather_metrics,
+    health_check,
+    parse_stripe_webhook,
+    parse_telegram_webhook,
+    secure_filename,
+    set_queue_depth,
+    sign_quote_token,
+    verify_quote_token,
+)
 from orca_quote_machine.core.config import get_settings
 from orca_quote_machine.dependencies import get_slicer_service
 from orca_quote_machine.models.quote import MaterialType, QuoteRequest
-from orca_quote_machine.services.slicer import OrcaSlicerService
-from orca_quote_machine.tasks import celery_app, process_quote_request
+from orca_quote_machine.services.slicer import OrcaSlicerService, SlicerError
+from orca_quote_machine.tasks import (
+    celery_app,
+    current_queue_status,
+    get_queue_depth,
+    process_quote_request,
+)
 
 settings = get_settings()
 
@@ -49,6 +74,34 @@ templates = Jinja2Templates(directory="templates")
 # Ensure upload directory exists
 Path(settings.upload_dir).mkdir(exist_ok=True)
 
+if settings.metrics_enabled:
+    enable_metrics()
+
+# Resumable uploads, for large models over unreliable connections
+upload_sessions = create_upload_session_store(
+    settings.upload_session_dir,
+    OrcaSlicerService(settings=settings).validation_limits(),
+    settings.upload_session_max_age_hours * 3600,
+)
+
+# Quote requests per mobile number, kept in this process; None leaves them unthrottled
+quote_rate_limiter = (
+    create_rate_limiter(settings.quote_rate_burst, settings.quote_rate_per_hour / 3600)
+    if settings.quote_rate_per_hour
+    else None
+)
+
+
+@app.exception_handler(InternalError)
+async def internal_error_handler(request: Request, exc: InternalError) -> JSONResponse:
+    """Answer a Rust panic with its backtrace ID; the details stay in the server log."""
+    return JSONResponse(
+        status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+        content={
+            "detail": "Internal error",
+            "backtrace_id": getattr(exc, "backtrace_id", None),
+        },
+    )
 
 
 @app.get("/", response_class=HTMLResponse)
@@ -71,6 +124,7 @@ async def home(
             "materials": available_materials,
             "max_file_size_mb": settings.max_file_size // (1024 * 1024),
             "allowed_extensions": ", ".join(settings.allowed_extensions),
+            "layer_heights": settings.layer_height_choices,
         },
     )
 
@@ -82,22 +136,43 @@ async def create_quote(
     mobile: str = Form(..., min_length=8, max_length=20),
     material: str | None = Form(None),
     color: str | None = Form(None, max_length=50),
-    model_file: UploadFile = File(...),
+    postal_code: str | None = Form(None, max_length=12),
+    layer_height: float | None = Form(None),
+    infill_percent: int | None = Form(None),
+    supports: bool | None = Form(None),
+    model_file: UploadFile | None = File(None),
+    upload_session_id: str | None = Form(None, max_length=64),
 ) -> JSONResponse:
     """
     Create a new quote request.
 
-    Accepts form data and uploads the 3D model file.
+    Accepts form data and uploads the 3D model file, or names a finished
+    resumable upload (see /uploads) in its place.
     Starts background processing and returns immediately.
     """
 
     # Validate file
-    if not model_file.filename:
+    if upload_session_id:
+        try:
+            upload_session = upload_sessions.status(upload_session_id)
+        except FileNotFoundError as e:
+            raise HTTPException(
+                status_code=status.HTTP_404_NOT_FOUND, detail="Upload session not found"
+            ) from e
+        original_filename = upload_session.file_name
+    elif model_file is not None:
+        original_filename = model_file.filename
+    else:
+        raise HTTPException(
+            status_code=status.HTTP_422_UNPROCESSABLE_ENTITY,
+            detail="Provide model_file or upload_session_id",
+        )
+    if not original_filename:
         raise HTTPException(
             status_code=status.HTTP_400_BAD_REQUEST, detail="No file provided"
         )
 
-    file_ext = Path(model_file.filename).suffix.lower()
+    file_ext = Path(original_filename).suffix.lower()
     if file_ext not in settings.allowed_extensions:
         raise HTTPException(
             status_code=status.HTTP_400_BAD_REQUEST,
@@ -107,7 +182,9 @@ async def create_quote(
     # Validate material against available materials (including custom ones)
     if material:
         try:
-            available_materials = slicer_service.get_available_materials()
+            available_materials = slicer_service.get_available_materials(
+                include_out_of_stock=True
+            )
             if material.upper() not in available_materials:
                 raise HTTPException(
                     status_code=status.HTTP_400_BAD_REQUEST,
@@ -121,8 +198,23 @@ async def create_quote(
                     detail=f"Invalid material. Supported: {', '.join([m.value for m in MaterialType])}",
                 ) from None
 
+    # Refuse a material the shop has run out of before queueing the slice
+    try:
+        # Spoolman is polled over HTTP; keep it off the event loop
+        await run_in_threadpool(slicer_service.check_stock, material or MaterialType.PLA.value, color)
+    except ValueError as e:
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e)) from e
+
+    if layer_height is not None and not any(
+        math.isclose(layer_height, choice) for choice in settings.layer_height_choices
+    ):
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail=f"Invalid layer height. Supported: {', '.join(f'{h:g}' for h in settings.layer_height_choices)} mm",
+        )
+
     # Sanitize filename to prevent path traversal
-    safe_filename = secure_filename(model_file.filename)
+    safe_filename = secure_filename(original_filename)
     if not safe_filename:
         raise HTTPException(
             status_code=status.HTTP_400_BAD_REQUEST, detail="Invalid filename"
@@ -136,60 +228,116 @@ async def create_quote(
             material=MaterialType(material.upper()) if material else None,
             color=color,
             filename=safe_filename,
+            postal_code=postal_code or None,
+            layer_height=layer_height,
+            infill_percent=infill_percent,
+            supports=supports,
         )
     except ValueError as e:
         raise HTTPException(
             status_code=status.HTTP_400_BAD_REQUEST, detail=str(e)
         ) from e
 
+    # Throttle by the normalized number, so reformatting it does not reset the count
+    if quote_rate_limiter and not quote_rate_limiter.acquire(quote_request.mobile):
+        retry_after = math.ceil(quote_rate_limiter.retry_after(quote_request.mobile))
+        raise HTTPException(
+            status_code=status.HTTP_429_TOO_MANY_REQUESTS,
+            detail="Too many quote requests for this mobile number. Please try again later.",
+            headers={"Retry-After": str(retry_after)},
+        )
+
     # Save uploaded file with size validation during write
     file_id = str(uuid.uuid4())
     file_path = Path(settings.upload_dir) / f"{file_id}_{safe_filename}"
 
-    written_bytes = 0
-    try:
-        async with aiofiles.open(file_path, "wb") as f:
-            while chunk := await model_file.read(8192):  # Read in 8KB chunks
-                written_bytes += len(chunk)
-                if written_bytes > settings.max_file_size:
-                    # Clean up partial file
-                    await f.close()
-                    if file_path.exists():
-                        await aiofiles.os.remove(file_path)
-                    raise HTTPException(
-                        status_code=status.HTTP_413_REQUEST_ENTITY_TOO_LARGE,
-                        detail=f"File too large. Maximum size: {settings.max_file_size // (1024 * 1024)}MB",
-                    )
-                await f.write(chunk)
-    except HTTPException:
-        raise  # Re-raise HTTP exceptions
-    except OSError as e:
-        # Clean up file if it exists
-        if file_path.exists():
+    if upload_session_id:
+        # Already hashed as it arrived; finishing moves it into place and validates it
+        try:
+            model_info = await run_in_threadpool(
+                upload_sessions.finish, upload_session_id, str(file_path)
+            )
+        except FileNotFoundError as e:
+            raise HTTPException(
+                status_code=status.HTTP_404_NOT_FOUND, detail="Upload session not found"
+            ) from e
+        except ValueError as e:
+            raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e)) from e
+        if not model_info.is_valid:
             with contextlib.suppress(OSError):
                 await aiofiles.os.remove(file_path)
-
-        # Provide specific error messages for common I/O errors
-        if e.errno == 28:  # ENOSPC - No space left on device
-            detail = "No disk space available to save the file"
-        elif e.errno == 13:  # EACCES - Permission denied
-            detail = "Permission denied when saving the file"
-        else:
-            detail = f"Failed to save file: {str(e)}"
-
-        raise HTTPException(
-            status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
-            detail=detail,
-        ) from e
-    except Exception as e:
-        # Handle any other unexpected errors
-        if file_path.exists():
-            with contextlib.suppress(OSError):
+            raise HTTPException(
+                status_code=status.HTTP_400_BAD_REQUEST,
+                detail=f"Invalid 3D model: {model_info.error_message}",
+            )
+    else:
+        assert model_file is not None  # one of the two was checked for above
+        written_bytes = 0
+        # Validated as it arrives, so a broken model is refused without waiting for the rest
+        validator = create_streaming_validator(
+            str(file_path), slicer_service.validation_limits()
+        )
+        try:
+            async with aiofiles.open(file_path, "wb") as f:
+                while chunk := await model_file.read(8192):  # Read in 8KB chunks
+                    written_bytes += len(chunk)
+                    if written_bytes > settings.max_file_size:
+                        # Clean up partial file
+                        await f.close()
+                        if file_path.exists():
+                            await aiofiles.os.remove(file_path)
+                        raise HTTPException(
+                            status_code=status.HTTP_413_REQUEST_ENTITY_TOO_LARGE,
+                            detail=f"File too large. Maximum size: {settings.max_file_size // (1024 * 1024)}MB",
+                        )
+                    try:
+                        validator.feed(chunk)
+                    except (ValueError, MemoryError, OSError) as e:
+                        # MemoryError and OSError: text models with endless lines or bytes that aren't UTF-8
+                        await f.close()
+                        if file_path.exists():
+                            await aiofiles.os.remove(file_path)
+                        raise HTTPException(
+                            status_code=status.HTTP_400_BAD_REQUEST,
+                            detail=f"Invalid 3D model: {e}",
+                        ) from e
+                    await f.write(chunk)
+            model_info = await run_in_threadpool(validator.finish)
+            if not model_info.is_valid:
                 await aiofiles.os.remove(file_path)
-        raise HTTPException(
-            status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
-            detail=f"Unexpected error while saving file: {type(e).__name__}",
-        ) from e
+                raise HTTPException(
+                    status_code=status.HTTP_400_BAD_REQUEST,
+                    detail=f"Invalid 3D model: {model_info.error_message}",
+                )
+        except HTTPException:
+            raise  # Re-raise HTTP exceptions
+        except OSError as e:
+            # Clean up file if it exists
+            if file_path.exists():
+                with contextlib.suppress(OSError):
+                    await aiofiles.os.remove(file_path)
+
+            # Provide specific error messages for common I/O errors
+            if e.errno == 28:  # ENOSPC - No space left on device
+                detail = "No disk space available to save the file"
+            elif e.errno == 13:  # EACCES - Permission denied
+                detail = "Permission denied when saving the file"
+            else:
+                detail = f"Failed to save file: {str(e)}"
+
+            raise HTTPException(
+                status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+                detail=detail,
+            ) from e
+        except Exception as e:
+            # Handle any other unexpected errors
+            if file_path.exists():
+                with contextlib.suppress(OSError):
+                    await aiofiles.os.remove(file_path)
+            raise HTTPException(
+                status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+                detail=f"Unexpected error while saving file: {type(e).__name__}",
+            ) from e
 
     # Start background processing
     try:
@@ -199,6 +347,14 @@ async def create_quote(
             material=material,
         )
 
+        # A SECRET_KEY too short to sign with leaves the quote without a portal link
+        portal_url = None
+        with contextlib.suppress(ValueError):
+            token = sign_quote_token(
+                task.id, settings.portal_token_ttl_hours * 3600, settings.secret_key
+            )
+            portal_url = f"/portal/{token}"
+
         return JSONResponse(
             status_code=status.HTTP_202_ACCEPTED,
             content={
@@ -208,6 +364,7 @@ async def create_quote(
                 "filename": quote_request.filename,
                 "material": material or "PLA (default)",
                 "estimated_processing_time": "2-5 minutes",
+                "portal_url": portal_url,
             },
         )
 
@@ -231,12 +388,154 @@ async def create_quote(
         ) from e
 
 
+def upload_session_json(session: UploadSession) -> dict[str, Any]:
+    """A resumable upload's progress; clients resume from received_bytes."""
+    return {
+        "session_id": session.session_id,
+        "file_name": session.file_name,
+        "received_bytes": session.received_bytes,
+        "total_bytes": session.total_bytes,
+        "sha256": session.sha256,
+    }
+
+
+@app.post("/uploads", status_code=status.HTTP_201_CREATED)
+async def start_upload(
+    file_name: str = Form(..., min_length=1, max_length=255),
+    total_bytes: int | None = Form(None, ge=0),
+) -> dict[str, Any]:
+    """Start a resumable upload; send chunks with PUT and quote it with upload_session_id."""
+    file_ext = Path(file_name).suffix.lower()
+    if file_ext not in settings.allowed_extensions:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail=f"File type {file_ext} not allowed. Supported: {', '.join(settings.allowed_extensions)}",
+        )
+    try:
+        session = await run_in_threadpool(upload_sessions.start, file_name, total_bytes)
+    except ValueError as e:
+        raise HTTPException(
+            status_code=status.HTTP_413_REQUEST_ENTITY_TOO_LARGE, detail=str(e)
+        ) from e
+    return upload_session_json(session)
+
+
+@app.put("/uploads/{session_id}")
+async def append_upload(session_id: str, request: Request, offset: int = 0) -> dict[str, Any]:
+    """Store the request body as the chunk starting at byte `offset`."""
+    chunk = bytearray()
+    async for part in request.stream():
+        chunk += part
+        if len(chunk) > settings.upload_chunk_max_bytes:
+            raise HTTPException(
+                status_code=status.HTTP_413_REQUEST_ENTITY_TOO_LARGE,
+                detail=f"Chunks may be at most {settings.upload_chunk_max_bytes} bytes",
+            )
+    try:
+        session = await run_in_threadpool(
+            upload_sessions.append, session_id, offset, bytes(chunk)
+        )
+    except FileNotFoundError as e:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND, detail="Upload session not found"
+        ) from e
+    except ValueError as e:
+        # A gap or an oversized upload; GET the session for where to resume
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e)) from e
+    return upload_session_json(session)
+
+
+@app.get("/uploads/{session_id}")
+async def upload_status(session_id: str) -> dict[str, Any]:
+    """How much of a resumable upload has arrived."""
+    try:
+        session = await run_in_threadpool(upload_sessions.status, session_id)
+    except FileNotFoundError as e:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND, detail="Upload session not found"
+        ) from e
+    return upload_session_json(session)
+
+
+@app.delete("/uploads/{session_id}", status_code=status.HTTP_204_NO_CONTENT)
+async def abort_upload(session_id: str) -> Response:
+    """Drop a resumable upload and what it received."""
+    try:
+        await run_in_threadpool(upload_sessions.abort, session_id)
+    except FileNotFoundError as e:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND, detail="Upload session not found"
+        ) from e
+    return Response(status_code=status.HTTP_204_NO_CONTENT)
+
+
 @app.get("/health")
 async def health_check() -> dict[str, str]:
     """Health check endpoint."""
     return {"status": "healthy", "app_name": settings.app_name, "version": "0.1.0"}
 
 
+@app.get("/health/ready")
+async def readiness_check(
+    slicer_service: Annotated[OrcaSlicerService, Depends(get_slicer_service)]
+) -> JSONResponse:
+    """Readiness check covering the slicer, profiles, upload storage and Telegram bot."""
+    try:
+        config = slicer_service.pipeline_config()
+    except (OSError, ValueError, SlicerError) as e:
+        checks = {"config": {"status": "error", "detail": str(e)}}
+        return JSONResponse(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            content={"status": "unhealthy", "checks": checks},
+        )
+
+    report = await health_check(config, settings.upload_dir, settings.telegram_bot_token)
+    checks = {
+        check.name: {
+            "status": check.status,
+            "detail": check.detail,
+            "duration_ms": round(check.duration_ms, 1),
+        }
+        for check in report.checks
+    }
+    return JSONResponse(
+        status_code=status.HTTP_200_OK if report.healthy else status.HTTP_503_SERVICE_UNAVAILABLE,
+        content={"status": "healthy" if report.healthy else "unhealthy", "checks": checks},
+    )
+
+
+@app.get("/metrics")
+async def metrics() -> Response:
+    """Prometheus scrape endpoint, available when metrics are enabled."""
+    if not settings.metrics_enabled:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="Not Found")
+
+    # An unreachable broker should not break scraping; the gauge keeps its last value.
+    with contextlib.suppress(Exception):
+        set_queue_depth(await run_in_threadpool(get_queue_depth))
+    return PlainTextResponse(gather_metrics(), media_type="text/plain; version=0.0.4")
+
+
+@app.get("/queue/status")
+async def queue_status() -> dict[str, Any]:
+    """Jobs ahead of a new quote and the estimated wait, for "ready in ~N minutes"."""
+    queue = await run_in_threadpool(current_queue_status)
+    wait = queue.estimated_wait_seconds
+    return {
+        "queued": queue.queued,
+        "running": queue.running,
+        "avg_slice_seconds": queue.avg_slice_seconds,
+        "estimated_wait_seconds": wait,
+        "estimated_wait_minutes": math.ceil(wait / 60) if wait is not None else None,
+    }
+
+
+@app.get("/schemas")
+async def schemas() -> dict[str, Any]:
+    """JSON Schema documents for the quote result and config types, keyed by type name."""
+    return export_schemas()
+
+
 @app.get("/status/{task_id}")
 async def get_task_status(task_id: str) -> dict[str, Any]:
     """Get the status of a background task."""
@@ -261,6 +560,67 @@ async def get_task_status(task_id: str) -> dict[str, Any]:
         }
 
 
+@app.get("/portal/{token}")
+async def portal_quote(token: str) -> dict[str, Any]:
+    """Quote status for a customer portal link, without exposing other customers' quotes."""
+    try:
+        access = verify_quote_token(token, settings.secret_key)
+    except ValueError as e:
+        raise HTTPException(
+            status_code=status.HTTP_403_FORBIDDEN, detail="Invalid or expired link"
+        ) from e
+    return await get_task_status(access.quote_id)
+
+
+def publish_webhook_event(event: WebhookEvent) -> dict[str, Any]:
+    """Pass a verified payment or approval on as a pipeline event."""
+    if event.action != "ignored":
+        emit_event(
+            event.action,
+            event.quote_id,
+            {
+                "source": event.source,
+                "event_id": event.event_id,
+                "amount": event.amount,
+                "currency": event.currency,
+                "user": event.user,
+            },
+        )
+    return {"received": True, "action": event.action, "quote_id": event.quote_id}
+
+
+@app.post("/webhooks/stripe")
+async def stripe_webhook(request: Request) -> dict[str, Any]:
+    """Stripe Checkout payment results, verified with the endpoint's signing secret."""
+    if not settings.stripe_webhook_secret:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND)
+    try:
+        event = parse_stripe_webhook(
+            await request.body(),
+            request.headers.get("Stripe-Signature", ""),
+            settings.stripe_webhook_secret,
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e)) from e
+    return publish_webhook_event(event)
+
+
+@app.post("/webhooks/telegram")
+async def telegram_webhook(request: Request) -> dict[str, Any]:
+    """Approve/reject button presses from Telegram, verified with the webhook secret token."""
+    if not settings.telegram_webhook_secret:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND)
+    try:
+        event = parse_telegram_webhook(
+            await request.body(),
+            request.headers.get("X-Telegram-Bot-Api-Secret-Token", ""),
+            settings.telegram_webhook_secret,
+        )
+    except ValueError as e:
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e)) from e
+    return publish_webhook_event(event)
+
+
 if __name__ == "__main__":
     import uvicorn
 
@@ -1,11 +1,14 @@
 """Quote-related data models."""
 
+import math
 import re
 from datetime import datetime
 from enum import Enum
 
 from pydantic import BaseModel, Field, computed_field, field_validator
 
+from orca_quote_machine.core.formatting import DisplayFormat
+
 
 class MaterialType(str, Enum):
     """Available material types."""
@@ -32,6 +35,11 @@ class QuoteRequest(BaseModel):
     material: MaterialType | None = None
     color: str | None = Field(None, max_length=50)
     filename: str = Field(..., min_length=1)
+    postal_code: str | None = Field(None, max_length=12)
+    # Print options; None keeps the process profile's setting
+    layer_height: float | None = Field(None, gt=0, le=1)
+    infill_percent: int | None = Field(None, ge=0, le=100)
+    supports: bool | None = None
 
     @field_validator("mobile")
     @classmethod
@@ -114,21 +122,57 @@ class TelegramMessage(BaseModel):
     print_time: str
     filament_weight: str
     total_cost: float
+    # Total when the print starts off-peak, and the earliest off-peak start in
+    # shop time, e.g. "2025-03-04 22:00"
+    off_peak_total: float | None = None
+    off_peak_start: str | None = None
+    payment_url: str | None = None
+    paynow_qr: bytes | None = None
+    # PNG of the model, sent as a photo after the message
+    preview_png: bytes | None = None
+    lead_time_hours: float | None = None
+    # Completion date promised by the business calendar, e.g. "2025-03-04"
+    promised_date: str | None = None
+    shipping: str | None = None
+    # Print options the model was sliced with, e.g. "0.28mm 10% supports"
+    print_options: str | None = None
+    # Things to know before printing, e.g. that the model needs a raft to stay on the bed
+    warnings: list[str] = []
+    # Full quote ID sent back by Approve/Reject buttons; None leaves the buttons out
+    approval_quote_id: str | None = None
+    # Locale and currency the total is written in
+    display: DisplayFormat = DisplayFormat()
 
     def format_message(self: "TelegramMessage") -> str:
         """Format message for Telegram."""
         material_display = self.material or "PLA (default)"
         color_info = f" - {self.color}" if self.color else ""
+        payment_info = f"\nPayment link: {self.payment_url}" if self.payment_url else ""
+        lead_info = ""
+        if self.lead_time_hours is not None:
+            hours = math.ceil(self.lead_time_hours)
+            ready = f"{hours // 24}d {hours % 24}h" if hours >= 24 else f"{hours}h"
+            lead_info = f"\nReady in: ~{ready}"
+        if self.promised_date:
+            lead_info += f"\nPromised by: {self.promised_date}"
+        shipping_info = f"\nShipping: {self.shipping}" if self.shipping else ""
+        off_peak_info = ""
+        if self.off_peak_total is not None:
+            off_peak_info = f"\nOff-peak: {self.display.money(self.off_peak_total)}"
+            if self.off_peak_start:
+                off_peak_info += f" (start from {self.off_peak_start})"
+        options_info = f"\nOptions: {self.print_options}" if self.print_options else ""
+        warning_info = "".join(f"\nWarning: {warning}" for warning in self.warnings)
 
         return f"""New Quote Request #{self.quote_id}
 
 Customer: {self.customer_name}
 WhatsApp: {self.customer_mobile}
 File: {self.filename}
-Material: {material_display}{color_info}
+Material: {material_display}{color_info}{options_info}{warning_info}
 
 Print Time: {self.print_time}
-Filament: {self.filament_weight}
-Total Cost: S${self.total_cost:.2f}
+Filament: {self.filament_weight}{lead_info}
+Total Cost: {self.display.money(self.total_cost)}{off_peak_info}{shipping_info}{payment_info}
 
 Reply to this message to contact the customer directly."""
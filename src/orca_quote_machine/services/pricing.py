@@ -2,10 +2,21 @@
 
 from orca_quote_machine._rust_core import (
     CostBreakdown,
+    PrintHistory,
+    Profile,
+    ShippingConfig,
     SlicingResult,
+    StripeConfig,
+    TimeOfUsePricing,
     calculate_quote_rust,
+    create_easypost_shipping,
+    create_print_history,
+    create_stripe_config,
+    create_time_of_use_pricing,
+    load_material_catalog,
 )
 from orca_quote_machine.core.config import Settings, get_settings
+from orca_quote_machine.core.formatting import DisplayFormat
 from orca_quote_machine.models.quote import MaterialType
 
 
@@ -14,11 +25,13 @@ class PricingService:
 
     def __init__(self: "PricingService", settings: Settings | None = None) -> None:
         self.settings = settings or get_settings()
+        self.catalog = load_material_catalog(self.settings.material_catalog_path)
 
     def calculate_quote(
         self: "PricingService",
         slicing_result: SlicingResult,
-        material: MaterialType | None = None,
+        material: MaterialType | str | None = None,
+        filament_profile: Profile | None = None,
     ) -> CostBreakdown:
         """
         Calculate pricing for a 3D print job using high-performance Rust implementation.
@@ -28,47 +41,117 @@ class PricingService:
 
         Args:
             slicing_result: Results from slicing operation
-            material: Material type used
+            material: Material type or name (aliases such as "PLA+" are accepted)
+            filament_profile: Filament profile used for slicing; its filament_cost
+                is the price per kg unless MATERIAL_PRICES sets one
 
         Returns:
             CostBreakdown object with pricing details
         """
-        material = material or MaterialType.PLA
-
-        # Get material price per kg
-        price_per_kg = self.settings.material_prices.get(
-            material.value, self.settings.default_price_per_kg
+        material_name = self.catalog.canonical_name(
+            getattr(material, "value", material) or MaterialType.PLA.value
         )
 
+        # Prices set via MATERIAL_PRICES win over the profile's filament_cost,
+        # which wins over catalog defaults. The built-in MATERIAL_PRICES
+        # defaults match the built-in catalog, so they only count when set.
+        profile_cost = filament_profile.filament_cost if filament_profile else None
+        if "material_prices" in self.settings.model_fields_set:
+            configured = self.settings.material_prices.get(material_name)
+        else:
+            configured = None
+        if configured is not None:
+            price_per_kg = configured
+        elif profile_cost and profile_cost > 0:
+            price_per_kg = profile_cost
+        else:
+            price_per_kg = self.settings.material_prices.get(
+                material_name,
+                self.catalog.price_per_kg(
+                    material_name, self.settings.default_price_per_kg
+                ),
+            )
+
         # Use Rust implementation for enhanced performance
         return calculate_quote_rust(
             slicing_result.print_time_minutes,
             slicing_result.filament_weight_grams,
-            material.value,
+            material_name,
             price_per_kg,
             self.settings.additional_time_hours,
             self.settings.price_multiplier,
             self.settings.minimum_price,
         )
 
+    def payment_config(self: "PricingService") -> StripeConfig | None:
+        """Stripe settings for payment links, or None when payments are not configured."""
+        if not (self.settings.stripe_api_key and self.settings.stripe_success_url):
+            return None
+        return create_stripe_config(
+            self.settings.stripe_api_key,
+            self.settings.stripe_success_url,
+            cancel_url=self.settings.stripe_cancel_url,
+            currency=self.settings.stripe_currency,
+        )
+
+    def shipping_config(self: "PricingService") -> ShippingConfig | None:
+        """Carrier rate settings for shipping costs, or None when shipping is not configured."""
+        if not (self.settings.shipping_api_key and self.settings.shipping_from_address):
+            return None
+        return create_easypost_shipping(
+            self.settings.shipping_api_key,
+            self.settings.shipping_from_address,
+            api_base=self.settings.shipping_api_base,
+            carriers=self.settings.shipping_carriers,
+            padding_mm=self.settings.shipping_padding_mm,
+            packaging_grams=self.settings.shipping_packaging_grams,
+        )
+
+    def time_of_use_pricing(self: "PricingService") -> TimeOfUsePricing | None:
+        """Off-peak machine-hour rates, or None when no off-peak windows are configured."""
+        if not self.settings.off_peak_windows:
+            return None
+        return create_time_of_use_pricing(
+            self.settings.off_peak_windows,
+            days=self.settings.off_peak_days,
+            utc_offset_hours=self.settings.business_utc_offset_hours,
+        )
+
+    def print_history(self: "PricingService") -> PrintHistory | None:
+        """Finished prints against their estimates, or None when no history file is set."""
+        if not self.settings.print_history_path:
+            return None
+        return create_print_history(
+            self.settings.print_history_path,
+            window=self.settings.calibration_window,
+            min_samples=self.settings.calibration_min_samples,
+        )
+
+    def estimate_calibration(self: "PricingService") -> PrintHistory | None:
+        """The history slicing estimates are corrected by, or None when correction is off."""
+        if not self.settings.apply_estimate_calibration:
+            return None
+        return self.print_history()
+
     def format_cost_summary(
         self: "PricingService", cost_breakdown: CostBreakdown
     ) -> str:
-        """Format cost breakdown for display."""
+        """Format cost breakdown for display, in the configured locale and units."""
+        fmt = DisplayFormat.from_settings(self.settings)
         material_line = (
-            f"Material: {cost_breakdown.filament_grams:.1f}g "
-            f"({cost_breakdown.filament_kg:.3f}kg) × "
-            f"S${cost_breakdown.price_per_kg:.2f}/kg = "
-            f"S${cost_breakdown.material_cost:.2f}"
+            f"Material: {fmt.weight(cost_breakdown.filament_grams)} "
+            f"({fmt.bulk_weight(cost_breakdown.filament_grams)}) × "
+            f"{fmt.price_per_bulk_unit(cost_breakdown.price_per_kg)} = "
+            f"{fmt.money(cost_breakdown.material_cost)}"
         )
         time_line = (
-            f"Time: {cost_breakdown.print_time_hours:.1f}h × "
-            f"S${cost_breakdown.price_per_kg:.2f}/h = "
-            f"S${cost_breakdown.time_cost:.2f}"
+            f"Time: {fmt.hours(cost_breakdown.print_time_hours)} × "
+            f"{fmt.money(cost_breakdown.price_per_kg)}/h = "
+            f"{fmt.money(cost_breakdown.time_cost)}"
         )
         return f"""Cost Breakdown:
 {material_line}
 {time_line}
-Subtotal: S${cost_breakdown.subtotal:.2f} (includes {cost_breakdown.markup_percentage:.0f}% markup)
-Total: S${cost_breakdown.total_cost:.2f}{"*" if cost_breakdown.minimum_applied else ""}
+Subtotal: {fmt.money(cost_breakdown.subtotal)} (includes {fmt.number(cost_breakdown.markup_percentage, 0)}% markup)
+Total: {fmt.money(cost_breakdown.total_cost)}{"*" if cost_breakdown.minimum_applied else ""}
 {"* Minimum price applied" if cost_breakdown.minimum_applied else ""}"""
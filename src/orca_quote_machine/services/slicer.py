@@ -1,20 +1,119 @@
 """OrcaSlicer integration service."""
 
 import asyncio
+import logging
 import os
-import tempfile
+from collections.abc import Callable
+from functools import lru_cache
 from pathlib import Path
 
 # Import enhanced Rust functions
-from orca_quote_machine._rust_core import SlicingResult, parse_slicer_output
+from orca_quote_machine._rust_core import (
+    BusinessCalendar,
+    CancellationToken,
+    FarmMonitor,
+    FleetPrinter,
+    GcodeCache,
+    Inventory,
+    MalwareScanner,
+    MachineListing,
+    MaterialAvailability,
+    PipelineConfig,
+    PostProcessConfig,
+    QuoteStore,
+    ProcessListing,
+    Profile,
+    ProfileCache,
+    SlicerFailure,
+    SlicerLimits,
+    SlicerPool,
+    SlicerRetryPolicy,
+    SlicingResult,
+    ValidationLimits,
+    check_compatibility,
+    check_fits_build_plate,
+    create_business_calendar,
+    create_gcode_cache,
+    create_farm_monitor,
+    create_inventory,
+    create_job_workspace,
+    create_malware_scanner,
+    create_pipeline_config,
+    create_postprocess_config,
+    create_quote_store,
+    create_slicer_limits,
+    create_slicer_pool,
+    create_slicer_retry_policy,
+    create_validation_limits,
+    create_profile_cache,
+    discover_available_materials,
+    emit_event,
+    generate_process_override,
+    load_fleet,
+    load_material_catalog,
+    parse_slicer_output,
+    postprocess_gcode,
+)
 from orca_quote_machine.core.config import Settings, get_settings
 from orca_quote_machine.models.quote import MaterialType
+from orca_quote_machine.services.pricing import PricingService
+
+logger = logging.getLogger(__name__)
 
 
 class SlicerError(Exception):
-    """Custom exception for slicer-related errors."""
+    """Custom exception for slicer-related errors.
+
+    ``failure`` is the SlicerFailure saying why, when the slicer itself exited
+    with an error; its ``message`` is safe to show the customer.
+    """
+
+    def __init__(self, message: str, failure: SlicerFailure | None = None) -> None:
+        super().__init__(message)
+        self.failure = failure
+
+
+@lru_cache
+def get_profile_cache(profiles_dir: Path) -> ProfileCache:
+    """
+    Process-wide cache of parsed profiles for a profile directory.
+
+    A filesystem watcher clears it whenever a profile changes. A directory
+    that does not exist yet has nothing to watch and is cached as empty.
+    """
+    return create_profile_cache(str(profiles_dir), watch=profiles_dir.is_dir())
+
+
+@lru_cache
+def get_slicer_limits(cpu_secs: int, memory_mb: int, nice: int, cgroup: str | None) -> SlicerLimits | None:
+    """
+    Limits for every slicer process, or None when nothing is limited.
+
+    Cached so the same settings give the same object, which get_slicer_pool
+    is keyed on. 0 means no limit, or for nice, unchanged.
+    """
+    if not (cpu_secs or memory_mb or nice or cgroup):
+        return None
+    return create_slicer_limits(
+        cpu_secs=cpu_secs or None, memory_mb=memory_mb or None, nice=nice or None, cgroup=cgroup
+    )
 
-    pass
+
+@lru_cache
+def get_slicer_pool(
+    max_concurrent: int,
+    timeout_secs: float,
+    max_attempts: int = 1,
+    retry_backoff: float = 0.0,
+    limits: SlicerLimits | None = None,
+) -> SlicerPool:
+    """Process-wide pool every slice_model call queues its slicer run on."""
+    return create_slicer_pool(
+        max_concurrent,
+        timeout_secs=timeout_secs,
+        retry=create_slicer_retry_policy(max_attempts=max_attempts, backoff_secs=retry_backoff),
+        limits=limits,
+    )
 
 
 class OrcaSlicerService:
@@ -24,50 +123,266 @@ class OrcaSlicerService:
         self.settings = settings or get_settings()
         self.cli_path = self.settings.orcaslicer_cli_path
         self.profiles_dir = self.settings.slicer_profiles.base_dir  # type: ignore[union-attr]
-        self.filament_profiles_dir = self.profiles_dir / "filament"
+        self.profile_cache = get_profile_cache(self.profiles_dir)
+        self.catalog = load_material_catalog(self.settings.material_catalog_path)
+        fleet_path = self.settings.slicer_profiles.fleet  # type: ignore[union-attr]
+        self.fleet = (
+            load_fleet(str(fleet_path), str(self.profiles_dir)) if fleet_path else None
+        )
+        self.gcode_cache: GcodeCache | None = (
+            create_gcode_cache(
+                self.settings.gcode_cache_dir,
+                self.settings.gcode_cache_max_mb * 1024 * 1024,
+            )
+            if self.settings.gcode_cache_dir
+            else None
+        )
 
-    def _get_filament_profile_path(self, material_name: str) -> Path:
+    def pipeline_config(self) -> PipelineConfig:
+        """Settings for the Rust quote pipeline and health check."""
+        profiles = self.settings.slicer_profiles
+        if profiles is None:
+            raise SlicerError("Slicer profiles are not configured")
+        explicit_prices = "material_prices" in self.settings.model_fields_set
+        return create_pipeline_config(
+            self.cli_path,
+            str(self.profiles_dir),
+            profiles.machine,
+            profiles.process,
+            material_map=str(profiles.material_map) if profiles.material_map.exists() else None,
+            fleet_path=str(profiles.fleet) if profiles.fleet else None,
+            material_catalog_path=self.settings.material_catalog_path,
+            material_prices=self.settings.material_prices if explicit_prices else {},
+            default_price_per_kg=self.settings.default_price_per_kg,
+            additional_time_hours=self.settings.additional_time_hours,
+            price_multiplier=self.settings.price_multiplier,
+            minimum_price=self.settings.minimum_price,
+            gcode_cache_dir=self.settings.gcode_cache_dir,
+            gcode_cache_max_bytes=self.settings.gcode_cache_max_mb * 1024 * 1024,
+            stripe_config=PricingService(self.settings).payment_config(),
+            farm_monitor=self.farm_monitor(),
+            shipping_config=PricingService(self.settings).shipping_config(),
+            plate_spacing_mm=self.settings.plate_spacing_mm,
+            plate_overhead_minutes=self.settings.plate_overhead_minutes,
+            preview_size=self.settings.preview_size,
+            postprocess=self.postprocess_config(),
+            quote_store=self.quote_store(),
+            time_of_use=PricingService(self.settings).time_of_use_pricing(),
+            inventory=self.inventory(),
+            print_history=PricingService(self.settings).estimate_calibration(),
+            require_watertight=self.settings.require_watertight_mesh,
+            convert_ascii_stl=self.settings.convert_ascii_stl,
+            max_triangles=self.settings.max_triangles,
+            validation_limits=self.validation_limits(),
+            malware_scanner=self.malware_scanner(),
+            slicer_timeout_secs=self.settings.slicer_timeout,
+            slicer_retry=self.slicer_retry_policy(),
+            slicer_limits=self.slicer_limits(),
+        )
+
+    def slicer_retry_policy(self) -> SlicerRetryPolicy:
+        """How often a crashed slicer is run again in the quote pipeline."""
+        return create_slicer_retry_policy(
+            max_attempts=self.settings.slicer_max_attempts,
+            backoff_secs=self.settings.slicer_retry_backoff,
+        )
+
+    def slicer_limits(self) -> SlicerLimits | None:
+        """CPU, memory and priority limits each slicer runs within, or None for none."""
+        return get_slicer_limits(
+            self.settings.slicer_cpu_limit_secs,
+            self.settings.slicer_memory_limit_mb,
+            self.settings.slicer_nice,
+            self.settings.slicer_cgroup,
+        )
+
+    def inventory(self) -> Inventory | None:
+        """Stock levels from the inventory table and/or Spoolman, or None when neither is set."""
+        if not (self.settings.inventory_table_path or self.settings.spoolman_url):
+            return None
+        return create_inventory(
+            table_path=self.settings.inventory_table_path,
+            spoolman_url=self.settings.spoolman_url,
+            low_stock_grams=self.settings.low_stock_grams,
+            max_age_secs=self.settings.inventory_max_age_secs,
+        )
+
+    def check_stock(
+        self, material: str, color: str | None = None, grams: float = 0.0
+    ) -> None:
         """
-        Gets the path to a filament profile using a hybrid strategy.
-        1. Checks for an explicit override in settings (e.g., `filament_pla`).
-        2. Falls back to a file-based convention (`material_name.ini`).
-        3. Raises a clear error if no profile is found.
+        Raise ValueError, e.g. "PETG out of stock", unless `grams` of the
+        material are on hand. An inventory that cannot be read stops nothing.
         """
-        profile_config = self.settings.slicer_profiles
-        material_lower = material_name.lower()
-
-        # 1. Check for an explicit override in settings (for official materials)
-        config_key = f"filament_{material_lower}"
-        if hasattr(profile_config, config_key):
-            profile_filename = getattr(profile_config, config_key)
-            profile_path = self.filament_profiles_dir / profile_filename
-            # The Pydantic validator already checked this at startup, so we can trust it exists.
-            return profile_path  # type: ignore[no-any-return]
-
-        # 2. Fallback to file-based convention for custom materials.
-        # Convention: material name 'TPU' maps to `tpu.json`.
-        conventional_filename = f"{material_lower}.json"
-        profile_path = self.filament_profiles_dir / conventional_filename
-        if profile_path.exists():
-            return profile_path
-
-        # 3. If no profile is found by any method, fail clearly.
-        raise SlicerError(
-            f"No profile found for material '{material_name}'. "
-            f"Looked for config override '{config_key}' and conventional file '{conventional_filename}'."
+        try:
+            inventory = self.inventory()
+        except OSError as e:
+            logger.warning(f"Could not read material stock: {e}")
+            return
+        if inventory is not None:
+            inventory.check(self.catalog, material, color=color, grams=grams)
+
+    def validation_limits(self) -> ValidationLimits:
+        """Size, triangle and line limits uploads are validated against."""
+        return create_validation_limits(
+            max_file_bytes=self.settings.max_file_size,
+            max_triangles=self.settings.max_model_triangles,
+            max_lines=self.settings.max_model_lines,
+        )
+
+    def malware_scanner(self) -> MalwareScanner | None:
+        """The scanner uploads are run through, or None when none is configured."""
+        if not (self.settings.malware_scan_command or self.settings.clamd_socket):
+            return None
+        return create_malware_scanner(
+            command=self.settings.malware_scan_command,
+            clamd_socket=self.settings.clamd_socket,
+            timeout_secs=self.settings.malware_scan_timeout_secs,
+        )
+
+    def quote_store(self) -> QuoteStore | None:
+        """Store of quotes for operator adjustments, or None when not configured."""
+        if not self.settings.quote_store_dir:
+            return None
+        return create_quote_store(
+            self.settings.quote_store_dir, audit_log_path=self.settings.audit_log_path
         )
 
+    def postprocess_config(self) -> PostProcessConfig | None:
+        """G-code post-processing before caching and delivery, or None when nothing is configured."""
+        settings = self.settings
+        if not (
+            settings.gcode_strip_thumbnails
+            or settings.gcode_start_snippet
+            or settings.gcode_end_snippet
+            or settings.gcode_postprocess_script
+        ):
+            return None
+        return create_postprocess_config(
+            start_gcode=settings.gcode_start_snippet,
+            end_gcode=settings.gcode_end_snippet,
+            strip_thumbnails=settings.gcode_strip_thumbnails,
+            script=settings.gcode_postprocess_script,
+            script_timeout_secs=settings.gcode_postprocess_timeout_secs,
+            audit_log_path=settings.audit_log_path,
+        )
+
+    def farm_monitor(self) -> FarmMonitor | None:
+        """Printer-farm monitor for lead times, or None when disabled or without a fleet."""
+        if not self.settings.lead_time_enabled or self.fleet is None:
+            return None
+        return create_farm_monitor(
+            self.fleet,
+            max_age_secs=self.settings.farm_status_max_age_secs,
+            timeout_secs=self.settings.farm_status_timeout_secs,
+            queued_job_hours=self.settings.farm_queued_job_hours,
+            handling_hours=self.settings.farm_handling_hours,
+            calendar=self.business_calendar(),
+        )
+
+    def business_calendar(self) -> BusinessCalendar | None:
+        """Working hours and holidays for promised dates, or None when disabled."""
+        if not self.settings.business_calendar_enabled:
+            return None
+        return create_business_calendar(
+            utc_offset_hours=self.settings.business_utc_offset_hours,
+            open_hour=self.settings.business_open_hour,
+            close_hour=self.settings.business_close_hour,
+            working_days=self.settings.business_days,
+            holidays=self.settings.business_holidays,
+        )
+
+    def _get_filament_profile_path(self, material_name: str) -> Path:
+        """
+        Gets the path to a filament profile.
+
+        Resolution is driven by the material mapping file, falling back to the
+        `<material>.json` convention and then to profiles whose `filament_type`
+        matches. Raises a clear error if no profile is found.
+        """
+        mapping_path = self.settings.slicer_profiles.material_map  # type: ignore[union-attr]
+        try:
+            resolution = self.profile_cache.resolve_filament(
+                material_name,
+                str(mapping_path) if mapping_path.exists() else None,
+            )
+        except (FileNotFoundError, ValueError) as e:
+            raise SlicerError(
+                f"No profile found for material '{material_name}': {e}"
+            ) from e
+        return Path(resolution.path)
+
+    def get_filament_profile(
+        self, material: MaterialType | str | None = None, path: str | None = None
+    ) -> Profile | None:
+        """
+        Loads the filament profile for a material (or an already-resolved path),
+        merged with the profiles it inherits from. Its filament_cost and
+        filament_density feed pricing and weight estimates. Returns None if the
+        profile cannot be resolved.
+        """
+        try:
+            if path is None:
+                material_name = getattr(material, "value", material) or MaterialType.PLA.value
+                path = str(self._get_filament_profile_path(material_name))
+            return self.profile_cache.profile(path)
+        except (SlicerError, FileNotFoundError, ValueError):
+            return None
+
+    def select_printer(
+        self, material: MaterialType | str | None, model_path: str | None = None
+    ) -> FleetPrinter | None:
+        """
+        Picks the fleet printer for a material and model size.
+
+        Returns None when no fleet is configured. Raises SlicerError if no
+        printer supports the material or fits the model.
+        """
+        if self.fleet is None:
+            return None
+        material_name = getattr(material, "value", material) or MaterialType.PLA.value
+        try:
+            return self.fleet.select_printer(
+                self.catalog.canonical_name(material_name), model_path
+            )
+        except ValueError as e:
+            raise SlicerError(str(e)) from e
+
     def get_profile_paths(
-        self, material: MaterialType | str | None = None
+        self,
+        material: MaterialType | str | None = None,
+        printer: FleetPrinter | None = None,
+        nozzle: float | None = None,
     ) -> dict[str, str]:
         """
         Resolves full paths for machine, process, and the correct filament profile.
-        Accepts an enum member or a raw string for the material.
+        Accepts an enum member or a raw string for the material. A fleet printer
+        replaces the default machine and, if it names one, the default process;
+        a process mapped to the material in the mapping file replaces both.
+        With a nozzle diameter, the defaults are swapped for profiles made for that
+        nozzle; SlicerError lists the available nozzles if there are none.
         """
         # Default to PLA if no material is provided.
         material_name = getattr(material, "value", material) or MaterialType.PLA.value
 
         profile_config = self.settings.slicer_profiles
+        if nozzle is not None and printer is None:
+            mapping_path = profile_config.material_map  # type: ignore[union-attr]
+            try:
+                paths = self.profile_cache.resolve_profile_paths(
+                    material_name,
+                    nozzle,
+                    profile_config.machine,  # type: ignore[union-attr]
+                    profile_config.process,  # type: ignore[union-attr]
+                    str(mapping_path) if mapping_path.exists() else None,
+                )
+            except (FileNotFoundError, ValueError) as e:
+                raise SlicerError(str(e)) from e
+            return {
+                "machine": str(Path(paths.machine).resolve()),
+                "filament": str(Path(paths.filament).resolve()),
+                "process": str(Path(paths.process).resolve()),
+            }
         filament_profile_path = self._get_filament_profile_path(material_name)
 
         profiles = {
@@ -75,32 +390,116 @@ class OrcaSlicerService:
             "filament": filament_profile_path,
             "process": self.profiles_dir / "process" / profile_config.process,  # type: ignore[union-attr]
         }
+        if printer is not None:
+            profiles["machine"] = Path(printer.machine_profile)
+            if printer.process_profile:
+                profiles["process"] = Path(printer.process_profile)
+
+        # A process mapped to the material (e.g. slow settings for TPU) wins
+        mapping_path = profile_config.material_map  # type: ignore[union-attr]
+        mapped_process = self.profile_cache.mapped_process(
+            material_name, str(mapping_path) if mapping_path.exists() else None
+        )
+        if mapped_process:
+            profiles["process"] = Path(mapped_process)
 
         return {k: str(v.resolve()) for k, v in profiles.items()}
 
-    def get_available_materials(self) -> list[str]:
+    def check_profile_compatibility(self, profiles: dict[str, str]) -> None:
+        """
+        Verifies the machine, filament, and process profiles suit each other
+        (printer lists, nozzle, layer height, temperature ranges) so mismatches
+        are reported before the slicer runs. Raises SlicerError listing them.
+        """
+        system_dirs = self.settings.slicer_profiles.system_dirs  # type: ignore[union-attr]
+        try:
+            report = check_compatibility(
+                profiles["machine"],
+                profiles["filament"],
+                profiles["process"],
+                [str(d) for d in system_dirs],
+            )
+        except (FileNotFoundError, ValueError) as e:
+            raise SlicerError(f"Cannot check profile compatibility: {e}") from e
+        if not report.is_compatible:
+            raise SlicerError(f"Incompatible slicer profiles:\n{report.summary()}")
+
+    def check_build_plate(self, model_path: str, machine_profile: str) -> None:
+        """
+        Compares the model's bounding box with the machine profile's printable
+        area and height, so an oversized model is refused before the slicer
+        runs. Raises SlicerError with both sizes.
+        """
+        try:
+            check_fits_build_plate(model_path, machine_profile)
+        except ValueError as e:
+            raise SlicerError(str(e)) from e
+
+    def get_available_materials(self, include_out_of_stock: bool = False) -> list[str]:
         """
         Discovers all available materials for populating UI elements.
-        Combines official materials from the enum with custom materials
-        found as .json files in the filament profile directory.
+        Combines catalogued materials with custom materials found as .json
+        files in the filament profile directory. Profile names that are
+        aliases of a catalogued material (e.g. 'PLA+') collapse into it.
+        Materials the inventory has run out of are left out unless
+        `include_out_of_stock` is set.
         """
-        # 1. Start with official materials from the enum
-        official_materials = {m.value for m in MaterialType}
+        return [
+            material.name
+            for material in self.discover_materials()
+            if include_out_of_stock or material.in_stock
+        ]
 
-        # 2. Scan the filesystem for all .json files
-        discovered_materials = set()
-        if self.filament_profiles_dir.is_dir():
-            for f in self.filament_profiles_dir.glob("*.json"):
-                # Convert 'generic_tpu.json' -> 'generic_tpu'
-                material_name = f.stem.upper()  # Convert to uppercase for consistency
-                discovered_materials.add(material_name)
+    def discover_materials(self) -> list[MaterialAvailability]:
+        """Every available material with its stock, for flagging low or out-of-stock ones."""
+        return discover_available_materials(
+            self.catalog,
+            # 'generic_tpu' -> 'GENERIC_TPU', from the cached directory listing
+            self.profile_cache.filament_names(),
+            inventory=self.inventory(),
+        )
+
+    def get_available_machines(self) -> list[MachineListing]:
+        """List machine profiles (name, nozzle, bed size, layer height limits)."""
+        return self.profile_cache.machines()
 
-        # 3. Combine, ensuring original casing is preferred, and sort.
-        all_materials = sorted(official_materials.union(discovered_materials))
-        return all_materials
+    def get_available_processes(
+        self, nozzle: float | None = None
+    ) -> list[ProcessListing]:
+        """List process profiles, optionally only those suited to a nozzle size."""
+        return self.profile_cache.processes(nozzle)
+
+    def _cache_gcode(
+        self, model_path: str, profiles: dict[str, str], output_dir: str
+    ) -> str | None:
+        """
+        Keep the sliced G-code so an accepted quote can be printed without re-slicing.
+
+        Returns the cache key, or None when caching is off or fails; a cache
+        problem never fails the quote.
+        """
+        if self.gcode_cache is None:
+            return None
+        try:
+            key = self.gcode_cache.key(
+                model_path, profiles["machine"], profiles["process"], profiles["filament"]
+            )
+            self.gcode_cache.store(key, output_dir)
+        except (OSError, ValueError) as e:
+            logger.warning(f"Could not cache G-code for {model_path}: {e}")
+            return None
+        return key
 
     async def slice_model(
-        self, model_path: str, material: MaterialType | None = None
+        self,
+        model_path: str,
+        material: MaterialType | None = None,
+        print_options: dict | None = None,
+        nozzle: float | None = None,
+        timings: dict[str, float] | None = None,
+        quote_id: str | None = None,
+        on_output: Callable[[str], None] | None = None,
+        cancel: CancellationToken | None = None,
     ) -> SlicingResult:
         """
         Slice a 3D model and extract print information.
@@ -108,6 +507,16 @@ class OrcaSlicerService:
         Args:
             model_path: Path to the 3D model file
             material: Material type to use for slicing
+            print_options: Process overrides such as layer_height, infill_percent
+                and supports, applied to a temporary copy of the process profile
+            nozzle: Nozzle diameter in mm; picks machine and process profiles for it
+            timings: If given, the wait for a free slicer slot is stored under
+                "slicer_queue" (milliseconds)
+            quote_id: Quote the printer.assigned event refers to
+            on_output: Called with each line the slicer writes as it runs, from
+                a worker thread
+            cancel: Cancelling it kills the slicer and raises
+                asyncio.CancelledError; the workspace is removed either way
 
         Returns:
             SlicingResult with print time and filament usage
@@ -118,51 +527,86 @@ class OrcaSlicerService:
         if not os.path.exists(model_path):
             raise SlicerError(f"Model file not found: {model_path}")
 
-        profiles = self.get_profile_paths(material)
-
-        with tempfile.TemporaryDirectory() as temp_dir:
-            output_dir = Path(temp_dir) / "output"
-            output_dir.mkdir(exist_ok=True)
-
-            # Build command
-            command = [
-                self.cli_path,
-                model_path,
-                "--slice",
-                "0",  # Slice all plates
-                "--load-settings",
-                f"{profiles['machine']};{profiles['process']}",
-                "--load-filaments",
-                profiles["filament"],
-                "--export-slicedata",
-                str(output_dir),
-                "--outputdir",
-                str(output_dir),
-                "--debug",
-                "1",  # Minimal logging
-            ]
+        printer = self.select_printer(material, model_path)
+        profiles = self.get_profile_paths(material, printer, nozzle)
+        self.check_profile_compatibility(profiles)
+        # A fleet printer was already chosen to fit the model
+        if printer is None:
+            self.check_build_plate(model_path, profiles["machine"])
 
-            try:
-                # Run slicer process
-                process = await asyncio.create_subprocess_exec(
-                    *command,
-                    stdout=asyncio.subprocess.PIPE,
-                    stderr=asyncio.subprocess.PIPE,
-                    cwd=temp_dir,
-                )
+        # The workspace removes the output directory on success, failure and
+        # cancellation alike, including any G-code the slicer left behind.
+        with create_job_workspace() as workspace:
+            output_dir = workspace.output_dir
 
-                stdout, stderr = await asyncio.wait_for(
-                    process.communicate(), timeout=self.settings.slicer_timeout
-                )
+            if print_options:
+                try:
+                    profiles["process"] = generate_process_override(
+                        profiles["process"], print_options, workspace.model_dir
+                    )
+                except (OSError, ValueError) as e:
+                    raise SlicerError(f"Invalid print options: {e}") from e
 
-                if process.returncode != 0:
-                    error_msg = stderr.decode() if stderr else "Unknown slicer error"
-                    raise SlicerError(f"Slicer failed: {error_msg}")
+            try:
+                # Queued on the worker's slicer pool (max_concurrent_slicers); the
+                # slicer is killed, with anything it started, once slicer_timeout
+                # passes, and run again after a crash up to slicer_max_attempts
+                job = get_slicer_pool(
+                    self.settings.max_concurrent_slicers or os.cpu_count() or 1,
+                    self.settings.slicer_timeout,
+                    self.settings.slicer_max_attempts,
+                    self.settings.slicer_retry_backoff,
+                    self.slicer_limits(),
+                ).submit(
+                    self.cli_path,
+                    model_path,
+                    str(profiles["machine"]),
+                    str(profiles["process"]),
+                    str(profiles["filament"]),
+                    output_dir,
+                    working_dir=workspace.root,
+                    on_output=on_output,
+                    cancel=cancel,
+                )
+                try:
+                    attempts = await job.wait()
+                except asyncio.CancelledError:
+                    # The caller gave up; don't leave the slicer running for nobody
+                    job.cancel()
+                    raise
+                if timings is not None:
+                    timings["slicer_queue"] = round(job.wait_ms or 0.0, 1)
 
-                # Parse results using Rust implementation
-                return await parse_slicer_output(str(output_dir))
+                # Parse results using Rust implementation; the filament density
+                # turns a reported filament length into a weight, the catalog's
+                # when the profile has none
+                filament = self.get_filament_profile(path=profiles["filament"])
+                density = filament.filament_density if filament else None
+                diameter = filament.filament_diameter if filament else None
+                catalogued = self.catalog.get(getattr(material, "value", material)) if material else None
+                if not density and catalogued is not None:
+                    density, diameter = catalogued.density, catalogued.diameter
+                result = await parse_slicer_output(output_dir, density, diameter)
+                # What is cached, and later printed, is the processed G-code
+                postprocess = self.postprocess_config()
+                if postprocess is not None:
+                    postprocess_gcode(output_dir, postprocess, quote_id=quote_id)
+                result.gcode_cache_key = self._cache_gcode(model_path, profiles, output_dir)
+                result.slicer_attempts = attempts
+                if printer is not None:
+                    emit_event(
+                        "printer.assigned",
+                        quote_id,
+                        {
+                            "printer": printer.name,
+                            "machine_profile": str(profiles["machine"]),
+                            "material": getattr(material, "value", material),
+                            "print_time_minutes": result.print_time_minutes,
+                        },
+                    )
+                return result
 
             except TimeoutError as e:
                 raise SlicerError("Slicing operation timed out") from e
             except Exception as e:
-                raise SlicerError(f"Slicing failed: {str(e)}") from e
+                raise SlicerError(f"Slicing failed: {str(e)}", failure=getattr(e, "failure", None)) from e
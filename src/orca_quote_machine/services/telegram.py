@@ -1,7 +1,7 @@
 """Telegram bot service for admin notifications."""
 
 import httpx
-from telegram import Bot
+from telegram import Bot, InlineKeyboardButton, InlineKeyboardMarkup
 from telegram.error import TelegramError
 
 from orca_quote_machine.core.config import Settings, get_settings
@@ -34,12 +34,40 @@ class TelegramService:
 
         try:
             formatted_message = message.format_message()
+            # Presses come back through POST /webhooks/telegram as quote.approved/rejected
+            buttons = None
+            if message.approval_quote_id:
+                buttons = InlineKeyboardMarkup(
+                    [
+                        [
+                            InlineKeyboardButton(
+                                "Approve", callback_data=f"approve:{message.approval_quote_id}"
+                            ),
+                            InlineKeyboardButton(
+                                "Reject", callback_data=f"reject:{message.approval_quote_id}"
+                            ),
+                        ]
+                    ]
+                )
 
             await self.bot.send_message(
                 chat_id=self.settings.telegram_admin_chat_id,
                 text=formatted_message,
                 parse_mode="HTML",
+                reply_markup=buttons,
             )
+            if message.preview_png:
+                await self.bot.send_photo(
+                    chat_id=self.settings.telegram_admin_chat_id,
+                    photo=message.preview_png,
+                    caption=f"{message.filename} - quote {message.quote_id}",
+                )
+            if message.paynow_qr:
+                await self.bot.send_photo(
+                    chat_id=self.settings.telegram_admin_chat_id,
+                    photo=message.paynow_qr,
+                    caption=f"PayNow S${message.total_cost:.2f} - ref {message.quote_id}",
+                )
 
             print(f"Quote notification sent for {message.quote_id}")
             return True
@@ -57,6 +85,34 @@ class TelegramService:
             print(f"Unexpected error sending Telegram notification: {type(e).__name__}: {e}")
             return False
 
+    async def send_adjustment_notification(
+        self, quote_id: str, total_before: float, total_after: float, reason: str
+    ) -> bool:
+        """Tell the admin chat a quote's price was changed by hand."""
+        if not self.bot or not self.settings.telegram_admin_chat_id:
+            return False
+
+        try:
+            message = (
+                f"Quote #{quote_id} revised\n\n"
+                f"S${total_before:.2f} -> S${total_after:.2f}\n"
+                f"Reason: {reason}"
+            )
+            await self.bot.send_message(
+                chat_id=self.settings.telegram_admin_chat_id, text=message
+            )
+            return True
+
+        except httpx.HTTPError as e:
+            print(f"HTTP error while sending adjustment notification: {e}")
+            return False
+        except (ConnectionError, TimeoutError) as e:
+            print(f"Network error while sending adjustment notification: {e}")
+            return False
+        except Exception as e:
+            print(f"Failed to send adjustment notification: {type(e).__name__}: {e}")
+            return False
+
     async def send_error_notification(self, error_message: str, quote_id: str) -> bool:
         """Send error notification to admin."""
         if not self.bot or not self.settings.telegram_admin_chat_id:
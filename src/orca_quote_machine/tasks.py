@@ -3,19 +3,60 @@
 import asyncio
 import contextlib
 import os
+import time
 import uuid
+from collections.abc import Iterator
 from datetime import datetime
+from functools import lru_cache
 from typing import Any
 
+import redis
 from celery import Celery, Task
+from celery.signals import worker_process_init
 from celery.utils.log import get_task_logger
 
 # Import Rust functions
-from orca_quote_machine._rust_core import cleanup_old_files_rust, validate_3d_model
-from orca_quote_machine.core.config import get_settings
+from orca_quote_machine._rust_core import (
+    CleanupStats,
+    LedgerConfig,
+    QueueStatus,
+    add_mqtt_sink,
+    add_webhook_sink,
+    append_quote_to_ledger,
+    cleanup_old_files_async,
+    configure_validation_cache,
+    create_gcode_cache,
+    create_moonraker_config,
+    create_csv_ledger,
+    create_octoprint_config,
+    create_payment_link,
+    create_sheets_ledger,
+    detect_slicer,
+    emit_event,
+    enable_metrics,
+    export_job_bundle,
+    find_duplicate,
+    generate_paynow_qr,
+    init_json_logging,
+    queue_status,
+    record_upload,
+    requote,
+    run_quote_pipeline,
+    send_to_moonraker,
+    send_to_octoprint,
+    serve_metrics,
+    set_memory_limits,
+    set_mmap_threshold,
+    set_slicer_concurrency,
+    set_unpack_limit,
+    to_dict,
+    warm_up,
+)
+from orca_quote_machine.core.config import Settings, get_settings
+from orca_quote_machine.core.formatting import DisplayFormat
 from orca_quote_machine.models.quote import MaterialType, TelegramMessage
 from orca_quote_machine.services.pricing import PricingService
-from orca_quote_machine.services.slicer import OrcaSlicerService
+from orca_quote_machine.services.slicer import OrcaSlicerService, SlicerError
 from orca_quote_machine.services.telegram import TelegramService
 
 settings = get_settings()
@@ -57,6 +98,144 @@ if os.getenv("PYTEST_CURRENT_TEST") or os.getenv("CELERY_TASK_ALWAYS_EAGER"):
 
 celery_app.conf.update(**celery_config)
 
+if settings.metrics_enabled:
+    enable_metrics()
+
+if settings.validation_cache_size:
+    configure_validation_cache(settings.validation_cache_size)
+
+if settings.gcode_parse_memory_mb or settings.mesh_analysis_memory_mb:
+    set_memory_limits(
+        settings.gcode_parse_memory_mb * 1024 * 1024,
+        settings.mesh_analysis_memory_mb * 1024 * 1024,
+    )
+
+if settings.max_unpacked_mb or settings.max_unpack_ratio:
+    set_unpack_limit(settings.max_unpacked_mb * 1024 * 1024, settings.max_unpack_ratio)
+
+if settings.mmap_threshold_mb:
+    set_mmap_threshold(settings.mmap_threshold_mb * 1024 * 1024)
+
+if settings.max_concurrent_slicers:
+    set_slicer_concurrency(settings.max_concurrent_slicers)
+
+if settings.rust_log_sink:
+    init_json_logging(settings.rust_log_sink, settings.rust_log_level)
+
+if settings.event_webhook_url:
+    add_webhook_sink(settings.event_webhook_url, secret=settings.event_webhook_secret)
+
+if settings.event_mqtt_host:
+    add_mqtt_sink(
+        settings.event_mqtt_host,
+        settings.event_mqtt_port,
+        topic_prefix=settings.event_mqtt_topic_prefix,
+        topics=settings.event_mqtt_topics,
+        retain=settings.event_mqtt_retain,
+        username=settings.event_mqtt_username,
+        password=settings.event_mqtt_password,
+    )
+
+
+@worker_process_init.connect
+def start_metrics_server(**kwargs: Any) -> None:
+    """Expose each pool process's metrics on metrics_port plus its pool index."""
+    if not settings.metrics_enabled or settings.metrics_port is None:
+        return
+    from billiard.process import current_process
+
+    index = getattr(current_process(), "index", 0) or 0
+    try:
+        port = serve_metrics(f"0.0.0.0:{settings.metrics_port + index}")
+        logger.info(f"Serving metrics on port {port}")
+    except OSError as e:
+        logger.warning(f"Metrics server not started: {e}")
+
+
+@worker_process_init.connect
+def warm_up_worker(**kwargs: Any) -> None:
+    """Load regexes and profiles (and optionally slice a cube) before the first quote."""
+    try:
+        # Also leaves out slicer arguments this OrcaSlicer release doesn't take
+        logger.info(f"Using {detect_slicer(settings.orcaslicer_cli_path)}")
+    except (OSError, RuntimeError, TimeoutError) as e:
+        logger.warning(f"Could not detect the slicer version: {e}")
+    if not settings.warm_up_on_start:
+        return
+    try:
+        service = OrcaSlicerService()
+        report = warm_up(
+            service.pipeline_config(),
+            service.profile_cache,
+            calibration=settings.warm_up_calibration,
+        )
+        logger.info(f"Worker warmed up: {report}")
+    except Exception as e:
+        # A cold worker still quotes correctly, just slower the first time.
+        logger.warning(f"Warm-up failed: {e}")
+
+
+def get_queue_depth() -> int:
+    """Number of quote jobs waiting in the default Celery queue."""
+    with celery_app.connection_for_read() as connection:
+        queue = celery_app.conf.task_default_queue
+        return connection.default_channel.queue_declare(
+            queue=queue, passive=True
+        ).message_count
+
+
+# Workers push each slice duration here so the web app can estimate waits.
+RECENT_SLICES_KEY = "orca:recent_slice_seconds"
+RECENT_SLICES = 20
+
+
+@lru_cache
+def get_redis() -> redis.Redis:
+    """Shared Redis client for the small bits of state workers publish."""
+    return redis.Redis.from_url(settings.redis_url)
+
+
+def publish_slice_seconds(seconds: float) -> None:
+    """Add a slice duration to the recent list read by current_queue_status."""
+    pipe = get_redis().pipeline()
+    pipe.lpush(RECENT_SLICES_KEY, seconds)
+    pipe.ltrim(RECENT_SLICES_KEY, 0, RECENT_SLICES - 1)
+    pipe.execute()
+
+
+def current_queue_status() -> QueueStatus:
+    """
+    Queued and running quote jobs across all workers, with an estimated wait.
+
+    Queued jobs come from the broker, running jobs and pool sizes from the
+    workers (1 s timeout), and the average from durations workers published.
+    Any source that cannot be reached is left to queue_status's defaults.
+    """
+    queued = running = parallelism = recent = None
+    with contextlib.suppress(Exception):
+        queued = get_queue_depth()
+    with contextlib.suppress(Exception):
+        inspect = celery_app.control.inspect(timeout=1.0)
+        active = inspect.active() or {}
+        stats = inspect.stats() or {}
+        running = sum(len(jobs) for jobs in active.values())
+        parallelism = sum(
+            worker.get("pool", {}).get("max-concurrency", 1) for worker in stats.values()
+        )
+    with contextlib.suppress(Exception):
+        recent = [float(s) for s in get_redis().lrange(RECENT_SLICES_KEY, 0, -1)]
+    return queue_status(queued, running, parallelism or None, recent or None)
+
+
+@contextlib.contextmanager
+def timed_stage(timings: dict[str, float], stage: str) -> Iterator[None]:
+    """Record how long a pipeline stage took, in milliseconds, under `stage`."""
+    started = time.perf_counter()
+    try:
+        yield
+    finally:
+        timings[stage] = round((time.perf_counter() - started) * 1000, 1)
+
 
 @celery_app.task(bind=True)
 def process_quote_request(
@@ -77,14 +256,9 @@ def process_quote_request(
     short_quote_id = quote_id[:8]
 
     logger.info(f"Processing quote {short_quote_id} for file {file_path}")
+    stage_timings: dict[str, float] = {}
 
     try:
-        # Validate file using Rust
-        validation_result = validate_3d_model(file_path)
-        if not validation_result.is_valid:
-            raise Exception(f"Invalid 3D model: {validation_result.error_message}")
-        logger.info(f"File validation passed: {validation_result.file_type}")
-
         # Parse material
         material_enum = None
         if material:
@@ -97,13 +271,22 @@ def process_quote_request(
         # Run async processing pipeline
         result = asyncio.run(
             run_processing_pipeline(
-                file_path, quote_data, material_enum, quote_id, short_quote_id
+                file_path,
+                quote_data,
+                material_enum,
+                quote_id,
+                short_quote_id,
+                stage_timings,
             )
         )
+        # Wait estimates are best effort; a Redis outage must not fail the quote.
+        with contextlib.suppress(Exception):
+            publish_slice_seconds(stage_timings["slicing"] / 1000)
         return result
 
     except Exception as e:
         error_msg = str(e)
+        failure = getattr(e, "failure", None)
         logger.error(f"Quote processing failed for {short_quote_id}: {error_msg}")
 
         # Send error notification
@@ -114,6 +297,8 @@ def process_quote_request(
             "success": False,
             "quote_id": quote_id,
             "error": error_msg,
+            # Why the slicer failed and what the customer can do, when it ran
+            "failure": to_dict(failure) if failure else None,
             "processed_at": datetime.utcnow().isoformat(),
         }
 
@@ -127,33 +312,124 @@ def process_quote_request(
             logger.warning(f"Failed to cleanup file {file_path}: {e}")
 
 
+def ledger_configs(settings: Settings) -> list[LedgerConfig]:
+    """Ledgers completed quotes are booked to, from the settings."""
+    ledgers = []
+    if settings.ledger_csv_dir:
+        ledgers.append(
+            create_csv_ledger(settings.ledger_csv_dir, rotation=settings.ledger_csv_rotation)
+        )
+    if settings.google_sheets_spreadsheet_id and settings.google_service_account_path:
+        ledgers.append(
+            create_sheets_ledger(
+                settings.google_sheets_spreadsheet_id,
+                settings.google_service_account_path,
+                sheet=settings.google_sheets_sheet,
+            )
+        )
+    return ledgers
+
+
+def describe_print_options(options: dict) -> str | None:
+    """Short summary of print options for the notification, e.g. "0.28mm, 10% infill, supports"."""
+    parts = []
+    if "layer_height" in options:
+        parts.append(f"{options['layer_height']:g}mm")
+    if "infill_percent" in options:
+        parts.append(f"{options['infill_percent']}% infill")
+    if "supports" in options:
+        parts.append("supports" if options["supports"] else "no supports")
+    return ", ".join(parts) or None
+
+
 async def run_processing_pipeline(
     file_path: str,
     quote_data: dict,
     material_enum: MaterialType | None,
     quote_id: str,
     short_quote_id: str,
+    stage_timings: dict[str, float] | None = None,
 ) -> dict[str, Any]:
     """
-    Helper async function to orchestrate async calls in the processing pipeline.
+    Quote a model with the Rust quote pipeline, then notify the admin and book it.
+
+    run_quote_pipeline scans, validates, repairs and slices the model and prices
+    it, adding the payment link, lead time, off-peak price, shipping rate and
+    preview the settings ask for, and keeps the quote in QUOTE_STORE_DIR when
+    that is set. Each stage's duration is added to `stage_timings`
+    (milliseconds) and returned.
     """
     # Get fresh settings for services
     settings = get_settings()
+    timings = stage_timings if stage_timings is not None else {}
 
-    # Run slicing
-    slicer_service = OrcaSlicerService(settings=settings)
-    slicing_result = await slicer_service.slice_model(file_path, material_enum)
+    # Print options the customer left unset keep the process profile's values
+    print_options = {
+        key: quote_data[key]
+        for key in ("layer_height", "infill_percent", "supports")
+        if quote_data.get(key) is not None
+    }
+    options = dict(print_options)
+    if quote_data.get("color"):
+        options["color"] = quote_data["color"]
+    ship_to = (
+        {"zip": quote_data["postal_code"], "country": settings.shipping_country}
+        if quote_data.get("postal_code")
+        else None
+    )
+    material_name = material_enum.value if material_enum else MaterialType.PLA.value
+
+    # Slicing takes minutes; the pipeline releases the GIL while it runs
+    quote = await asyncio.to_thread(
+        run_quote_pipeline,
+        file_path,
+        material_name,
+        OrcaSlicerService(settings=settings).pipeline_config(),
+        quote_id=quote_id,
+        ship_to=ship_to,
+        options=options,
+        repair=settings.repair_meshes,
+        on_slicer_output=lambda line: logger.debug(f"Slicer [{quote_id}]: {line}"),
+    )
+    timings.update({stage: round(ms, 1) for stage, ms in quote.stage_timings_ms.items()})
+    slicing_result = quote.slicing
+    cost_breakdown = quote.cost
+    payment_url = quote.payment_url
+    lead_time = quote.lead_time
+    off_peak = quote.off_peak
+    shipping = quote.shipping
     logger.info(
-        f"Slicing completed: {slicing_result.print_time_minutes}min, {slicing_result.filament_weight_grams}g"
+        f"Quote {short_quote_id} on {quote.printer or quote.machine_profile}: "
+        f"{slicing_result.print_time_minutes}min, {slicing_result.filament_weight_grams}g, "
+        f"S${cost_breakdown.total_cost:.2f}"
     )
-
-    # Calculate pricing
-    pricing_service = PricingService(settings=settings)
-    cost_breakdown = pricing_service.calculate_quote(slicing_result, material_enum)
-    logger.info(f"Pricing calculated: S${cost_breakdown.total_cost:.2f}")
+    if quote.calibration is not None:
+        logger.info(f"Estimates calibrated for {short_quote_id}: {quote.calibration}")
+
+    # The same bytes quoted before point the operator at that quote
+    upload_hash = quote.model.sha256
+    if settings.upload_index_path and upload_hash:
+        previous = find_duplicate(upload_hash, settings.upload_index_path)
+        if previous:
+            logger.info(f"Quote {short_quote_id} is a repeat upload of quote {previous[:8]}")
+        record_upload(upload_hash, quote_id, settings.upload_index_path)
+
+    paynow_qr = None
+    if settings.paynow_uen or settings.paynow_mobile:
+        try:
+            paynow_qr = generate_paynow_qr(
+                cost_breakdown.total_cost,
+                short_quote_id,
+                uen=settings.paynow_uen,
+                mobile=None if settings.paynow_uen else settings.paynow_mobile,
+                merchant_name=settings.paynow_merchant_name,
+            )
+        except ValueError as e:
+            logger.warning(f"Could not create PayNow QR for {short_quote_id}: {e}")
 
     # Send Telegram notification
     telegram_service = TelegramService(settings=settings)
+    display = DisplayFormat.from_settings(settings)
     telegram_message = TelegramMessage(
         quote_id=short_quote_id,
         customer_name=quote_data["name"],
@@ -161,28 +437,62 @@ async def run_processing_pipeline(
         material=material_enum.value if material_enum else None,
         color=quote_data.get("color"),
         filename=quote_data["filename"],
-        print_time=f"{slicing_result.print_time_minutes // 60}h {slicing_result.print_time_minutes % 60}m",
-        filament_weight=f"{slicing_result.filament_weight_grams:.1f}g",
+        print_time=display.duration(slicing_result.print_time_minutes),
+        filament_weight=display.weight(slicing_result.filament_weight_grams),
         total_cost=cost_breakdown.total_cost,
+        off_peak_total=off_peak.total_cost if off_peak else None,
+        off_peak_start=off_peak.start_time if off_peak else None,
+        payment_url=payment_url,
+        paynow_qr=paynow_qr,
+        preview_png=quote.preview_png,
+        lead_time_hours=lead_time.lead_time_seconds / 3600 if lead_time else None,
+        promised_date=lead_time.promised_date if lead_time else None,
+        shipping=f"{shipping.currency} {display.number(shipping.amount)} via {shipping.carrier} {shipping.service}".strip()
+        if shipping
+        else None,
+        print_options=describe_print_options(print_options),
+        warnings=quote.warnings,
+        approval_quote_id=quote_id if settings.telegram_webhook_secret else None,
+        display=display,
     )
 
-    notification_sent = await telegram_service.send_quote_notification(telegram_message)
+    with timed_stage(timings, "notification"):
+        notification_sent = await telegram_service.send_quote_notification(telegram_message)
+    if notification_sent:
+        emit_event("notification.sent", quote_id, {"channel": "telegram"})
+
+    with timed_stage(timings, "ledger"):
+        for ledger in ledger_configs(settings):
+            try:
+                append_quote_to_ledger(
+                    ledger,
+                    quote_id,
+                    cost_breakdown,
+                    customer_name=quote_data["name"],
+                    filename=quote_data["filename"],
+                    payment_url=payment_url,
+                )
+            except (OSError, ValueError) as e:
+                # Bookkeeping can be caught up by hand; never fail the quote for it
+                logger.warning(f"Could not book quote {short_quote_id} in {ledger}: {e}")
+    logger.info(f"Quote {short_quote_id} stage timings (ms): {timings}")
 
     return {
         "success": True,
         "quote_id": quote_id,
-        "slicing_result": {
-            "print_time_minutes": slicing_result.print_time_minutes,
-            "filament_weight_grams": slicing_result.filament_weight_grams,
-        },
-        "cost_breakdown": {
-            "material_type": cost_breakdown.material_type,
-            "total_cost": cost_breakdown.total_cost,
-            "filament_kg": cost_breakdown.filament_kg,
-            "print_time_hours": cost_breakdown.print_time_hours,
-            "minimum_applied": cost_breakdown.minimum_applied,
-        },
+        # Rust results in full, keyed as in their JSON Schemas (export_schemas)
+        "slicing_result": to_dict(slicing_result),
+        "calibration": to_dict(quote.calibration) if quote.calibration else None,
+        "cost_breakdown": to_dict(cost_breakdown),
+        "print_options": {**print_options, "color": quote_data.get("color")},
+        "payment_url": payment_url,
+        "lead_time": to_dict(lead_time) if lead_time else None,
+        "off_peak": to_dict(off_peak) if off_peak else None,
+        "shipping": to_dict(shipping) if shipping else None,
+        "printer": quote.printer,
+        "warnings": quote.warnings,
         "notification_sent": notification_sent,
+        "stage_timings_ms": timings,
         "processed_at": datetime.utcnow().isoformat(),
     }
 
@@ -194,6 +504,13 @@ async def send_failure_notification(error_msg: str, quote_id: str) -> None:
     await telegram_service.send_error_notification(error_msg, quote_id)
 
 
+async def sweep_upload_dir(max_age_hours: int) -> CleanupStats:
+    """Remove uploads older than `max_age_hours` with the non-blocking Rust sweep."""
+    return await cleanup_old_files_async(
+        settings.upload_dir, max_age_hours, settings.audit_log_path
+    )
+
+
 @celery_app.task
 def cleanup_old_files(max_age_hours: int = 24) -> dict[str, Any]:
     """
@@ -206,15 +523,18 @@ def cleanup_old_files(max_age_hours: int = 24) -> dict[str, Any]:
         Cleanup statistics
     """
     try:
-        stats = cleanup_old_files_rust(settings.upload_dir, max_age_hours)
+        stats = asyncio.run(sweep_upload_dir(max_age_hours))
         logger.info(
             f"Cleaned up {stats.files_cleaned} old files, freeing {stats.bytes_freed} bytes."
         )
+        for error in stats.errors:
+            logger.warning(f"Cleanup error: {error}")
 
         return {
             "success": True,
             "files_cleaned": stats.files_cleaned,
             "bytes_freed": stats.bytes_freed,
+            "errors": list(stats.errors),
         }
 
     except Exception as e:
@@ -223,3 +543,318 @@ def cleanup_old_files(max_age_hours: int = 24) -> dict[str, Any]:
             "success": False,
             "error": str(e),
         }
+
+
+@celery_app.task
+def requote_quote(
+    gcode_cache_key: str, material: str = "PLA", quote_id: str | None = None
+) -> dict[str, Any]:
+    """
+    Price a quote again from its cached G-code with the current pricing settings.
+
+    Args:
+        gcode_cache_key: Key returned with the quote (slicing_result.gcode_cache_key)
+        material: Material the quote was sliced for
+        quote_id: Quote the quote.requoted event refers to
+
+    Returns:
+        The new cost breakdown, or an error when the G-code is no longer cached
+    """
+    try:
+        config = OrcaSlicerService(settings=get_settings()).pipeline_config()
+        result = requote(gcode_cache_key, material, config, quote_id=quote_id)
+    except (SlicerError, OSError, ValueError) as e:
+        logger.error(f"Re-quote failed for {gcode_cache_key}: {e}")
+        return {"success": False, "error": str(e)}
+    cost = result.cost
+    logger.info(f"Re-quoted {gcode_cache_key} at S${cost.total_cost:.2f}")
+    return {
+        "success": True,
+        "quote_id": quote_id,
+        "material": result.material,
+        "gcode_cache_key": result.gcode_cache_key,
+        # Same shape as process_quote_request's result
+        "slicing_result": to_dict(result.slicing),
+        "cost_breakdown": to_dict(cost),
+    }
+
+
+@celery_app.task
+def adjust_quote_price(
+    quote_id: str,
+    reason: str,
+    delta: float | None = None,
+    new_total: float | None = None,
+    operator: str | None = None,
+) -> dict[str, Any]:
+    """
+    Change a stored quote's price by hand and tell the admin chat.
+
+    Args:
+        quote_id: Quote that process_quote_request kept in QUOTE_STORE_DIR
+        reason: Why the price changed, kept with the adjustment
+        delta: Amount added to the total (negative for a discount)
+        new_total: Total to charge instead; give this or delta
+        operator: Who made the change
+
+    Returns:
+        The revised cost breakdown, every adjustment so far, and a payment
+        link for the new total when Stripe is configured
+    """
+    store = OrcaSlicerService(settings=settings).quote_store()
+    if store is None:
+        return {"success": False, "error": "Quote store is not configured"}
+    try:
+        quote = store.apply_manual_adjustment(
+            quote_id, reason, delta=delta, new_total=new_total, operator=operator
+        )
+    except (OSError, ValueError) as e:
+        logger.error(f"Adjustment of {quote_id} failed: {e}")
+        return {"success": False, "error": str(e)}
+    stripe = PricingService(settings=settings).payment_config()
+    if stripe is not None:
+        try:
+            quote.payment_url = create_payment_link(quote.cost, stripe, quote_id=quote_id)
+            store.save(quote_id, quote)
+        except (OSError, ValueError) as e:
+            # The adjustment stands; the customer can be sent a link by hand
+            logger.warning(f"Could not create payment link for adjusted {quote_id}: {e}")
+    adjustment = quote.adjustments[-1]
+    logger.info(
+        f"Adjusted {quote_id} from S${adjustment.total_before:.2f} to S${adjustment.total_after:.2f}"
+    )
+    notification_sent = asyncio.run(
+        TelegramService(settings=settings).send_adjustment_notification(
+            quote_id[:8], adjustment.total_before, adjustment.total_after, adjustment.reason
+        )
+    )
+    return {
+        "success": True,
+        "quote_id": quote_id,
+        "cost_breakdown": to_dict(quote.cost),
+        "adjustments": [to_dict(a) for a in quote.adjustments],
+        "payment_url": quote.payment_url,
+        "notification_sent": notification_sent,
+    }
+
+
+@celery_app.task
+def export_quote_job_bundle(quote_id: str, output_path: str | None = None) -> dict[str, Any]:
+    """
+    Zip an accepted quote's G-code, job sheet, preview and manifest for the print floor.
+
+    Args:
+        quote_id: Quote that process_quote_request kept in QUOTE_STORE_DIR, with its G-code in GCODE_CACHE_DIR
+        output_path: Where to write the zip; defaults to <QUOTE_STORE_DIR>/<quote_id>.zip
+
+    Returns:
+        The bundle's path
+    """
+    try:
+        config = OrcaSlicerService(settings=settings).pipeline_config()
+        path = export_job_bundle(quote_id, config, output_path=output_path)
+    except (SlicerError, OSError, ValueError) as e:
+        logger.error(f"Job bundle for {quote_id} failed: {e}")
+        return {"success": False, "error": str(e)}
+    logger.info(f"Job bundle for {quote_id} written to {path}")
+    return {"success": True, "quote_id": quote_id, "bundle_path": path}
+
+
+@celery_app.task
+def record_print_actuals(
+    material: str,
+    estimated_minutes: float,
+    estimated_grams: float,
+    actual_minutes: float,
+    actual_grams: float,
+    quote_id: str | None = None,
+) -> dict[str, Any]:
+    """
+    Record how long a finished print took and how much filament it used.
+
+    Args:
+        material: Material the job was printed in
+        estimated_minutes: The slicer's print time estimate; for a calibrated
+            quote, calibration.slicer_print_time_minutes rather than the corrected one
+        estimated_grams: The slicer's filament estimate, likewise uncorrected
+        actual_minutes: Time the printer actually took
+        actual_grams: Filament actually used, e.g. from the spool's weight
+        quote_id: Quote the job was printed for
+
+    Returns:
+        The material's correction factors with this print counted
+    """
+    pricing_service = PricingService(settings=settings)
+    history = pricing_service.print_history()
+    if history is None:
+        return {"success": False, "error": "PRINT_HISTORY_PATH is not configured"}
+    material_name = pricing_service.catalog.canonical_name(material)
+    try:
+        history.record(
+            material_name,
+            estimated_minutes,
+            estimated_grams,
+            actual_minutes,
+            actual_grams,
+            quote_id=quote_id,
+        )
+        factor = history.factor(material_name)
+    except (OSError, ValueError) as e:
+        logger.error(f"Recording print actuals for {quote_id or material_name} failed: {e}")
+        return {"success": False, "error": str(e)}
+    return {
+        "success": True,
+        "material": material_name,
+        "correction": to_dict(factor) if factor else None,
+    }
+
+
+def cached_gcode_files(gcode_cache_key: str) -> list[str]:
+    """G-code files kept for a quote; raises LookupError when there are none."""
+    if not settings.gcode_cache_dir:
+        raise LookupError("G-code cache is not configured")
+    gcode_files = create_gcode_cache(settings.gcode_cache_dir).get(gcode_cache_key)
+    if not gcode_files:
+        raise LookupError(f"No cached G-code for {gcode_cache_key}")
+    return gcode_files
+
+
+@celery_app.task
+def send_quote_to_octoprint(
+    gcode_cache_key: str, start_print: bool | None = None, quote_id: str | None = None
+) -> dict[str, Any]:
+    """
+    Upload an accepted quote's cached G-code to OctoPrint.
+
+    Args:
+        gcode_cache_key: Key returned with the quote (slicing_result.gcode_cache_key)
+        start_print: Override OCTOPRINT_START_PRINT; only the first plate is started
+        quote_id: Quote the job.sent / job.failed events refer to
+
+    Returns:
+        Remote paths of the uploaded plates and whether printing started
+    """
+    if not (settings.octoprint_url and settings.octoprint_api_key):
+        return {"success": False, "error": "OctoPrint is not configured"}
+    try:
+        gcode_files = cached_gcode_files(gcode_cache_key)
+    except LookupError as e:
+        return {"success": False, "error": str(e)}
+
+    if start_print is None:
+        start_print = settings.octoprint_start_print
+    try:
+        uploads = []
+        for plate, gcode_path in enumerate(gcode_files):
+            config = create_octoprint_config(
+                settings.octoprint_url,
+                settings.octoprint_api_key,
+                folder=settings.octoprint_folder,
+                start_print=start_print and plate == 0,
+            )
+            uploads.append(send_to_octoprint(gcode_path, config))
+        logger.info(f"Sent {len(uploads)} G-code file(s) for {gcode_cache_key} to OctoPrint")
+        result = {
+            "success": True,
+            "files": [upload.remote_path for upload in uploads],
+            "print_started": any(upload.print_started for upload in uploads),
+        }
+        emit_event(
+            "job.sent",
+            quote_id,
+            {"host": "octoprint", "gcode_cache_key": gcode_cache_key, **result},
+        )
+        return result
+    except (OSError, ValueError) as e:
+        logger.error(f"OctoPrint upload failed for {gcode_cache_key}: {e}")
+        emit_event(
+            "job.failed",
+            quote_id,
+            {"host": "octoprint", "gcode_cache_key": gcode_cache_key, "error": str(e)},
+        )
+        return {"success": False, "error": str(e)}
+
+
+@celery_app.task
+def send_quote_to_moonraker(
+    gcode_cache_key: str,
+    quote_id: str | None = None,
+    quoted_print_minutes: int | None = None,
+    start_print: bool | None = None,
+) -> dict[str, Any]:
+    """
+    Upload an accepted quote's cached G-code to Moonraker and check Klipper's estimate.
+
+    Args:
+        gcode_cache_key: Key returned with the quote (slicing_result.gcode_cache_key)
+        quote_id: Quote the audit record and job.sent / job.failed events refer to
+        quoted_print_minutes: Print time the quote was priced on; defaults to the
+            G-code header's estimate
+        start_print: Override MOONRAKER_START_PRINT; only the first plate is started
+
+    Returns:
+        Per-plate remote paths with Klipper's and the quoted estimate
+    """
+    if not settings.moonraker_url:
+        return {"success": False, "error": "Moonraker is not configured"}
+    try:
+        gcode_files = cached_gcode_files(gcode_cache_key)
+    except LookupError as e:
+        return {"success": False, "error": str(e)}
+
+    if start_print is None:
+        start_print = settings.moonraker_start_print
+    # The quoted time covers all plates; compare each plate with its own header.
+    if len(gcode_files) > 1:
+        quoted_print_minutes = None
+    try:
+        uploads = []
+        for plate, gcode_path in enumerate(gcode_files):
+            config = create_moonraker_config(
+                settings.moonraker_url,
+                settings.moonraker_api_key,
+                folder=settings.moonraker_folder,
+                start_print=start_print and plate == 0,
+                tolerance_percent=settings.moonraker_estimate_tolerance_percent,
+            )
+            upload = send_to_moonraker(
+                gcode_path,
+                config,
+                quoted_print_minutes=quoted_print_minutes,
+                quote_id=quote_id,
+                audit_log_path=settings.audit_log_path,
+            )
+            if upload.flagged:
+                logger.warning(
+                    f"Klipper estimates {upload.klipper_estimate_seconds:.0f}s for "
+                    f"{upload.remote_path}, quoted {upload.quoted_estimate_seconds:.0f}s"
+                )
+            uploads.append(upload)
+        result = {
+            "success": True,
+            "files": [
+                {
+                    "path": upload.remote_path,
+                    "klipper_estimate_seconds": upload.klipper_estimate_seconds,
+                    "quoted_estimate_seconds": upload.quoted_estimate_seconds,
+                    "discrepancy_percent": upload.discrepancy_percent,
+                    "flagged": upload.flagged,
+                }
+                for upload in uploads
+            ],
+            "print_started": any(upload.print_started for upload in uploads),
+        }
+        emit_event(
+            "job.sent",
+            quote_id,
+            {"host": "moonraker", "gcode_cache_key": gcode_cache_key, **result},
+        )
+        return result
+    except (OSError, ValueError) as e:
+        logger.error(f"Moonraker upload failed for {gcode_cache_key}: {e}")
+        emit_event(
+            "job.failed",
+            quote_id,
+            {"host": "moonraker", "gcode_cache_key": gcode_cache_key, "error": str(e)},
+        )
+        return {"success": False, "error": str(e)}
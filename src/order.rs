@@ -0,0 +1,266 @@
+use pyo3::prelude::*;
+use serde_json::json;
+use std::thread;
+
+use crate::events;
+use crate::metrics;
+use crate::panic_boundary;
+use crate::pipeline::{self, PipelineConfig, QuoteResult};
+use crate::plating::{self, PlatePlan};
+use crate::process_override::PrintOptions;
+use crate::slicer::SlicerWatch;
+use crate::{compute_cost_breakdown, OrcaError};
+
+/// One model of an order: its single-unit quote and what `quantity` copies cost
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct OrderPart {
+    #[pyo3(get)]
+    pub model_path: String,
+    #[pyo3(get)]
+    pub quantity: u32,
+    /// The part quoted on its own, setup time and minimum price included.
+    #[pyo3(get)]
+    pub quote: QuoteResult,
+    /// One copy without setup time or minimum price, which the order charges once.
+    #[pyo3(get)]
+    pub unit_cost: f64,
+    /// Every copy, batched onto plates when the bed and bounding box are known.
+    #[pyo3(get)]
+    pub print_time_minutes: u64,
+    /// `None` when the bed or bounding box is unknown and copies are timed one by one.
+    #[pyo3(get)]
+    pub plating: Option<PlatePlan>,
+    /// All copies at the batched print time, without setup time or minimum price.
+    #[pyo3(get)]
+    pub line_total: f64,
+}
+
+#[pymethods]
+impl OrderPart {
+    fn __str__(&self) -> String {
+        format!(
+            "OrderPart(model={}, quantity={}, line_total=S${:.2})",
+            self.model_path, self.quantity, self.line_total
+        )
+    }
+}
+
+/// Several parts quoted as one order, with setup and the minimum price counted once
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct OrderQuote {
+    /// In the order they were given.
+    #[pyo3(get)]
+    pub parts: Vec<OrderPart>,
+    #[pyo3(get)]
+    pub total_quantity: u32,
+    /// Print time of every copy of every part, with copies batched onto plates.
+    #[pyo3(get)]
+    pub print_time_minutes: u64,
+    #[pyo3(get)]
+    pub filament_grams: f64,
+    /// Sum of the parts' line totals.
+    #[pyo3(get)]
+    pub parts_cost: f64,
+    /// `additional_time_hours` once, at the dearest part's rate, with markup.
+    #[pyo3(get)]
+    pub setup_cost: f64,
+    #[pyo3(get)]
+    pub subtotal: f64,
+    #[pyo3(get)]
+    pub total_cost: f64,
+    #[pyo3(get)]
+    pub minimum_applied: bool,
+}
+
+#[pymethods]
+impl OrderQuote {
+    fn __str__(&self) -> String {
+        format!(
+            "OrderQuote(parts={}, quantity={}, total=S${:.2})",
+            self.parts.len(),
+            self.total_quantity,
+            self.total_cost
+        )
+    }
+}
+
+/// A part as given: model path, quantity and material.
+type PartSpec = (String, u32, String);
+
+fn order_part(
+    (model_path, quantity, material): PartSpec,
+    quote: QuoteResult,
+    config: &PipelineConfig,
+) -> OrderPart {
+    let unit_minutes = quote.slicing.print_time_minutes;
+    let plating = quote
+        .dimensions
+        .zip(quote.bed_size)
+        .map(|((x, y, _), bed)| {
+            plating::plan(
+                quantity,
+                (x, y),
+                bed,
+                unit_minutes,
+                config.plate_spacing_mm,
+                config.plate_overhead_minutes,
+            )
+        });
+    let print_time_minutes = plating
+        .as_ref()
+        .map_or(unit_minutes as u64 * quantity as u64, |plan| {
+            plan.print_time_minutes
+        });
+    let cost = |minutes: u64, copies: u32| {
+        compute_cost_breakdown(
+            minutes.min(u32::MAX as u64) as u32,
+            quote.slicing.filament_weight_grams * copies as f32,
+            material.clone(),
+            quote.cost.price_per_kg,
+            0.0,
+            config.price_multiplier,
+            0.0,
+        )
+        .total_cost
+    };
+    OrderPart {
+        model_path,
+        quantity,
+        unit_cost: cost(unit_minutes as u64, 1),
+        print_time_minutes,
+        line_total: cost(print_time_minutes, quantity),
+        plating,
+        quote,
+    }
+}
+
+/// Aggregate priced parts; setup time and the minimum price apply to the order.
+fn order_quote(parts: Vec<OrderPart>, config: &PipelineConfig) -> OrderQuote {
+    let parts_cost: f64 = parts.iter().map(|p| p.line_total).sum();
+    let setup_rate = parts
+        .iter()
+        .map(|p| p.quote.cost.price_per_kg)
+        .fold(0.0, f64::max);
+    let setup_cost = config.additional_time_hours * setup_rate * config.price_multiplier;
+    let subtotal = parts_cost + setup_cost;
+    let total_cost = subtotal.max(config.minimum_price);
+    OrderQuote {
+        total_quantity: parts.iter().map(|p| p.quantity).sum(),
+        print_time_minutes: parts.iter().map(|p| p.print_time_minutes).sum(),
+        filament_grams: parts
+            .iter()
+            .map(|p| p.quote.slicing.filament_weight_grams as f64 * p.quantity as f64)
+            .sum(),
+        parts_cost,
+        setup_cost,
+        subtotal,
+        total_cost,
+        minimum_applied: subtotal < config.minimum_price,
+        parts,
+    }
+}
+
+/// Quote every part, in parallel; the slicer concurrency limit still applies.
+fn quote_parts(parts: Vec<PartSpec>, config: &PipelineConfig) -> PyResult<Vec<OrderPart>> {
+    let quotes: Vec<PyResult<QuoteResult>> = thread::scope(|scope| {
+        let handles: Vec<_> = parts
+            .iter()
+            .map(|(model_path, _, material)| {
+                scope.spawn(move || {
+                    let result = pipeline::quote(
+                        model_path,
+                        material.clone(),
+                        PrintOptions::default(),
+                        config,
+                        false,
+                        &SlicerWatch::default(),
+                    );
+                    metrics::record_quote(material, pipeline::outcome(&result));
+                    result
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle.join().unwrap_or_else(|_| {
+                    Err(OrcaError::SlicerFailed("part quote panicked".to_string()).into())
+                })
+            })
+            .collect()
+    });
+    parts
+        .into_iter()
+        .zip(quotes)
+        .enumerate()
+        .map(|(i, (part, quote))| match quote {
+            Ok(quote) => Ok(order_part(part, quote, config)),
+            // Keep the exception type, but say which part it was.
+            Err(e) => Err(Python::with_gil(|py| {
+                PyErr::from_type(
+                    e.get_type(py),
+                    format!("part {} ({}): {}", i + 1, part.0, e.value(py)),
+                )
+            })),
+        })
+        .collect()
+}
+
+/// Quote an order of several parts, each `(model_path, quantity, material)`
+///
+/// Each part is validated, sliced and priced once. Copies are laid out on as few
+/// plates as the bed allows, so each plate's heat-up and homing is paid once
+/// rather than per copy; setup time and the minimum price are charged once per
+/// order. `material` may be None for `default_material`.
+#[pyfunction]
+#[pyo3(signature = (parts, config, quote_id=None, default_material="PLA".to_string()))]
+pub fn run_order_pipeline(
+    py: Python<'_>,
+    parts: Vec<(String, u32, Option<String>)>,
+    config: PyRef<'_, PipelineConfig>,
+    quote_id: Option<String>,
+    default_material: String,
+) -> PyResult<OrderQuote> {
+    panic_boundary::catch(|| {
+        if parts.is_empty() {
+            return Err(OrcaError::InvalidModel("order has no parts".to_string()).into());
+        }
+        if let Some((path, ..)) = parts.iter().find(|(_, quantity, _)| *quantity == 0) {
+            return Err(
+                OrcaError::InvalidModel(format!("{}: quantity must be at least 1", path)).into(),
+            );
+        }
+        let part_count = parts.len();
+        let parts: Vec<PartSpec> = parts
+            .into_iter()
+            .map(|(path, quantity, material)| {
+                let material = material.unwrap_or_else(|| default_material.clone());
+                (path, quantity, config.canonical_material(&material))
+            })
+            .collect();
+        let config: &PipelineConfig = &config;
+        let order =
+            py.allow_threads(|| quote_parts(parts, config).map(|parts| order_quote(parts, config)));
+        match &order {
+            Ok(order) => events::emit(
+                "quote.created",
+                quote_id.as_deref(),
+                json!({
+                    "parts": order.parts.len(),
+                    "total_quantity": order.total_quantity,
+                    "total_cost": order.total_cost,
+                    "print_time_minutes": order.print_time_minutes,
+                    "filament_grams": order.filament_grams,
+                }),
+            ),
+            Err(e) => events::emit(
+                "quote.failed",
+                quote_id.as_deref(),
+                json!({ "parts": part_count, "error": e.to_string() }),
+            ),
+        };
+        order
+    })
+}
@@ -0,0 +1,95 @@
+/// Faces whose normal is within this of straight down overhang: past 45°
+/// from vertical a layer has too little of the one below to stand on.
+const OVERHANG_COS: f64 = std::f64::consts::FRAC_1_SQRT_2;
+/// Corners this close in height count as level, e.g. with the bed.
+const LEVEL_TOLERANCE_MM: f64 = 0.01;
+/// Less overhanging area than this (a chamfer's edge, a stray facet) prints
+/// without supports.
+pub const SUPPORT_AREA_MM2: f64 = 10.0;
+
+/// Downward-facing surface of a mesh, gathered one triangle at a time.
+/// Faces resting on the bed are told apart once the lowest point is known.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Overhangs {
+    /// Every overhanging face's area.
+    down_area: f64,
+    /// Area of the level overhanging faces at `lowest_z`.
+    lowest_area: f64,
+    lowest_z: f64,
+    /// X and Y bounds of those faces: min x, min y, max x, max y.
+    lowest_extent: [f64; 4],
+}
+
+impl Overhangs {
+    pub fn new() -> Self {
+        Overhangs {
+            down_area: 0.0,
+            lowest_area: 0.0,
+            lowest_z: f64::INFINITY,
+            lowest_extent: [
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ],
+        }
+    }
+
+    /// Add a triangle, its normal taken from its winding.
+    pub fn include(&mut self, [a, b, c]: [[f64; 3]; 3]) {
+        let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let n = [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ];
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if length == 0.0 || n[2] / length > -OVERHANG_COS {
+            return;
+        }
+        let area = length / 2.0;
+        self.down_area += area;
+
+        let top = a[2].max(b[2]).max(c[2]);
+        if top - a[2].min(b[2]).min(c[2]) > LEVEL_TOLERANCE_MM {
+            return;
+        }
+        if top < self.lowest_z - LEVEL_TOLERANCE_MM {
+            self.lowest_z = top;
+            self.lowest_area = 0.0;
+            self.lowest_extent = Overhangs::new().lowest_extent;
+        } else if top > self.lowest_z + LEVEL_TOLERANCE_MM {
+            return;
+        }
+        self.lowest_area += area;
+        for corner in [a, b, c] {
+            let extent = &mut self.lowest_extent;
+            extent[0] = extent[0].min(corner[0]);
+            extent[1] = extent[1].min(corner[1]);
+            extent[2] = extent[2].max(corner[0]);
+            extent[3] = extent[3].max(corner[1]);
+        }
+    }
+
+    fn on_bed(&self, floor_z: f64) -> bool {
+        (self.lowest_z - floor_z).abs() <= LEVEL_TOLERANCE_MM
+    }
+
+    /// Overhanging area in mm², leaving out faces on the bed at `floor_z`,
+    /// the mesh's lowest point.
+    pub fn area(&self, floor_z: f64) -> f64 {
+        if self.on_bed(floor_z) {
+            self.down_area - self.lowest_area
+        } else {
+            self.down_area
+        }
+    }
+
+    /// Area of the faces on the bed at `floor_z`, in mm², and their X and Y
+    /// bounds; `None` when the model touches the bed only at points or edges.
+    pub fn footprint(&self, floor_z: f64) -> Option<(f64, [f64; 4])> {
+        (self.on_bed(floor_z) && self.lowest_area > 0.0)
+            .then_some((self.lowest_area, self.lowest_extent))
+    }
+}
@@ -0,0 +1,172 @@
+use once_cell::sync::Lazy;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, Once};
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::OrcaError;
+
+create_exception!(
+    _rust_core,
+    InternalError,
+    PyException,
+    "A bug in the Rust core. `backtrace_id` names the server log entry with the backtrace."
+);
+
+static HOOK: Once = Once::new();
+static PANICS: AtomicU64 = AtomicU64::new(0);
+
+/// Panics logged by the hook but not yet reported to Python, as (ID, message).
+/// Shared between threads because rayon and Tokio re-raise a panic on the thread
+/// waiting for the work, not the one it happened on.
+static LOGGED: Lazy<Mutex<VecDeque<(String, String)>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Panics caught by other code (a failed health check, say) are never claimed,
+/// so only the most recent ones are kept.
+const MAX_LOGGED: usize = 16;
+
+/// Short, unique enough to grep for: milliseconds since the epoch and a counter.
+fn new_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let n = PANICS.fetch_add(1, Ordering::Relaxed);
+    format!("{:011x}-{:04x}", millis, n & 0xffff)
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "panic with a non-string payload"
+    }
+}
+
+/// Log every panic with an ID and a backtrace, instead of Rust's default stderr
+/// message, and remember the ID for the `catch` that will report it.
+fn log_panic(info: &PanicHookInfo<'_>) {
+    let id = new_id();
+    let message = payload_message(info.payload());
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}", l.file(), l.line()))
+        .unwrap_or_default();
+    let backtrace = Backtrace::force_capture();
+    tracing::error!(
+        backtrace_id = %id,
+        location = %location,
+        backtrace = %backtrace,
+        "panic: {}",
+        message
+    );
+    // The JSON log is optional; stderr always ends up in the worker's log.
+    eprintln!("panic {} at {}: {}\n{}", id, location, message, backtrace);
+    let mut logged = LOGGED.lock().unwrap_or_else(|e| e.into_inner());
+    if logged.len() == MAX_LOGGED {
+        logged.pop_front();
+    }
+    logged.push_back((id, message.to_string()));
+}
+
+/// Install the panic hook once per process. Called from module init.
+pub fn install_hook() {
+    HOOK.call_once(|| panic::set_hook(Box::new(log_panic)));
+}
+
+/// A panic caught at the boundary, not yet turned into a Python exception.
+#[derive(Debug)]
+pub struct Panic {
+    pub backtrace_id: String,
+    pub message: String,
+}
+
+impl From<Panic> for PyErr {
+    fn from(panic: Panic) -> PyErr {
+        Python::with_gil(|py| {
+            let err = InternalError::new_err(format!(
+                "internal error (backtrace {}): {}",
+                panic.backtrace_id, panic.message
+            ));
+            // Exception instances take attributes; failing to set one only loses
+            // the structured copy of what is already in the message.
+            let _ = err.value(py).setattr("backtrace_id", &panic.backtrace_id);
+            err
+        })
+    }
+}
+
+/// Run `f`, turning a panic into a `Panic` without needing the GIL, so it can
+/// be used on Tokio and rayon threads as well as in pyfunctions.
+pub fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, Panic> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = payload_message(&*payload).to_string();
+        let mut logged = LOGGED.lock().unwrap_or_else(|e| e.into_inner());
+        let backtrace_id = logged
+            .iter()
+            .rposition(|(_, logged_message)| *logged_message == message)
+            .and_then(|index| logged.remove(index))
+            .map(|(id, _)| id)
+            .unwrap_or_else(|| {
+                // Raised before the hook was installed: nothing logged yet.
+                let id = new_id();
+                tracing::error!(backtrace_id = %id, "panic: {}", message);
+                eprintln!("panic {}: {}", id, message);
+                id
+            });
+        Panic {
+            backtrace_id,
+            message,
+        }
+    })
+}
+
+/// The body of every exported pyfunction: a panic raises `InternalError`
+/// rather than pyo3's `PanicException`.
+pub fn catch<T>(f: impl FnOnce() -> PyResult<T>) -> PyResult<T> {
+    catch_panic(f)?
+}
+
+/// Polls the inner future inside `catch_panic`.
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Panic>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        match catch_panic(|| inner.poll(cx)) {
+            Ok(Poll::Ready(value)) => Poll::Ready(Ok(value)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
+
+/// `catch` for the future an async pyfunction hands to `future_into_py`.
+pub async fn catch_future<T>(future: impl Future<Output = PyResult<T>>) -> PyResult<T> {
+    CatchUnwind(Box::pin(future)).await?
+}
+
+/// `tokio::task::spawn_blocking`, with a panic in `f` caught on its own thread
+/// so it reaches Python as `InternalError` under the ID the hook logged.
+pub async fn spawn_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> PyResult<T> {
+    match tokio::task::spawn_blocking(move || catch_panic(f)).await {
+        Ok(result) => Ok(result?),
+        Err(e) => Err(OrcaError::IoError(io::Error::other(e.to_string())).into()),
+    }
+}
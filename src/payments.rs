@@ -0,0 +1,200 @@
+use pyo3::prelude::*;
+use serde_json::Value;
+use std::fmt;
+use std::time::Duration;
+
+use crate::http_upload::{read_json, request_error};
+use crate::panic_boundary;
+use crate::{CostBreakdown, OrcaError};
+
+/// Stripe account used to take payment for quotes
+#[derive(Clone)]
+#[pyclass]
+pub struct StripeConfig {
+    api_key: String,
+    /// Three-letter ISO currency code, lowercase, e.g. "sgd".
+    #[pyo3(get)]
+    pub currency: String,
+    /// Where Checkout sends the customer after paying.
+    #[pyo3(get)]
+    pub success_url: String,
+    /// Where Checkout sends the customer when they back out; defaults to `success_url`.
+    #[pyo3(get)]
+    pub cancel_url: Option<String>,
+    #[pyo3(get)]
+    pub api_base: String,
+    #[pyo3(get)]
+    pub timeout_secs: f64,
+}
+
+// Written by hand so the secret key never ends up in a log line.
+impl fmt::Debug for StripeConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StripeConfig")
+            .field("api_key", &"<redacted>")
+            .field("currency", &self.currency)
+            .field("success_url", &self.success_url)
+            .field("cancel_url", &self.cancel_url)
+            .field("api_base", &self.api_base)
+            .field("timeout_secs", &self.timeout_secs)
+            .finish()
+    }
+}
+
+#[pymethods]
+impl StripeConfig {
+    fn __str__(&self) -> String {
+        format!(
+            "StripeConfig(currency={}, api_base={})",
+            self.currency, self.api_base
+        )
+    }
+}
+
+fn cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+/// Checkout line items, in cents, adding up to exactly `cost.total_cost`. The
+/// markup is spread over material and print time; any minimum-price top-up is
+/// its own item, and rounding lands on the last one.
+pub fn line_items(cost: &CostBreakdown) -> Vec<(String, i64)> {
+    let multiplier = 1.0 + cost.markup_percentage / 100.0;
+    let mut items = vec![
+        (
+            format!(
+                "{} filament ({:.0} g)",
+                cost.material_type, cost.filament_grams
+            ),
+            cents(cost.material_cost * multiplier),
+        ),
+        (
+            format!("Print time ({:.1} h)", cost.print_time_hours),
+            cents(cost.time_cost * multiplier),
+        ),
+    ];
+    let remainder = cents(cost.total_cost) - items.iter().map(|(_, amount)| amount).sum::<i64>();
+    if cost.minimum_applied && remainder > 0 {
+        items.push(("Minimum order adjustment".to_string(), remainder));
+    } else if let Some((_, amount)) = items.last_mut() {
+        *amount += remainder;
+    }
+    // Stripe rejects empty line items, e.g. the material of a zero-gram job.
+    items.retain(|(_, amount)| *amount > 0);
+    items
+}
+
+/// Create a Checkout Session via `POST /v1/checkout/sessions` and return its URL.
+pub fn checkout_session(
+    cost: &CostBreakdown,
+    config: &StripeConfig,
+    quote_id: Option<&str>,
+) -> Result<String, OrcaError> {
+    let mut form: Vec<(String, String)> = vec![
+        ("mode".to_string(), "payment".to_string()),
+        ("success_url".to_string(), config.success_url.clone()),
+        (
+            "cancel_url".to_string(),
+            config
+                .cancel_url
+                .clone()
+                .unwrap_or_else(|| config.success_url.clone()),
+        ),
+    ];
+    if let Some(quote_id) = quote_id {
+        form.push(("client_reference_id".to_string(), quote_id.to_string()));
+        form.push(("metadata[quote_id]".to_string(), quote_id.to_string()));
+    }
+    for (i, (name, amount)) in line_items(cost).into_iter().enumerate() {
+        let item = format!("line_items[{}]", i);
+        form.push((format!("{}[quantity]", item), "1".to_string()));
+        form.push((
+            format!("{}[price_data][currency]", item),
+            config.currency.clone(),
+        ));
+        form.push((
+            format!("{}[price_data][unit_amount]", item),
+            amount.to_string(),
+        ));
+        form.push((format!("{}[price_data][product_data][name]", item), name));
+    }
+    let form: Vec<(&str, &str)> = form.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+
+    let url = format!(
+        "{}/v1/checkout/sessions",
+        config.api_base.trim_end_matches('/')
+    );
+    let timeout = Duration::from_secs_f64(config.timeout_secs.max(0.0));
+    let response = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .build()
+        .post(&url)
+        .set("Authorization", &format!("Bearer {}", config.api_key))
+        .send_form(&form)
+        .map_err(|e| payment_error(request_error(&url, e)))?;
+    let body = read_json(&url, response).map_err(payment_error)?;
+    body.get("url")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| OrcaError::PaymentFailed(format!("{}: response has no url", url)))
+}
+
+fn payment_error(error: OrcaError) -> OrcaError {
+    match error {
+        OrcaError::UploadFailed(message) => OrcaError::PaymentFailed(message),
+        other => other,
+    }
+}
+
+/// Create the account settings for `create_payment_link`
+#[pyfunction]
+#[pyo3(signature = (api_key, success_url, cancel_url=None, currency="sgd".to_string(), api_base="https://api.stripe.com".to_string(), timeout_secs=30.0))]
+pub fn create_stripe_config(
+    api_key: String,
+    success_url: String,
+    cancel_url: Option<String>,
+    currency: String,
+    api_base: String,
+    timeout_secs: f64,
+) -> PyResult<StripeConfig> {
+    panic_boundary::catch(|| {
+        if api_key.trim().is_empty() {
+            return Err(OrcaError::InvalidConfig {
+                path: "stripe".to_string(),
+                message: "API key is empty".to_string(),
+            }
+            .into());
+        }
+        if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "stripe".to_string(),
+                message: format!("{:?} is not a three-letter currency code", currency),
+            }
+            .into());
+        }
+        Ok(StripeConfig {
+            api_key,
+            currency: currency.to_lowercase(),
+            success_url,
+            cancel_url: cancel_url.filter(|u| !u.is_empty()),
+            api_base,
+            timeout_secs,
+        })
+    })
+}
+
+/// Create a Stripe Checkout link for a quote, itemised from its cost breakdown
+#[pyfunction]
+#[pyo3(signature = (cost_breakdown, stripe_config, quote_id=None))]
+pub fn create_payment_link(
+    py: Python<'_>,
+    cost_breakdown: PyRef<'_, CostBreakdown>,
+    stripe_config: PyRef<'_, StripeConfig>,
+    quote_id: Option<String>,
+) -> PyResult<String> {
+    panic_boundary::catch(|| {
+        let cost = cost_breakdown.clone();
+        let config = stripe_config.clone();
+        Ok(py.allow_threads(|| checkout_session(&cost, &config, quote_id.as_deref()))?)
+    })
+}
@@ -0,0 +1,196 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::panic_boundary;
+use crate::qr::QrCode;
+use crate::OrcaError;
+
+/// PayNow caps the bill reference and merchant name at 25 characters.
+const MAX_TEXT_LEN: usize = 25;
+const SGD_NUMERIC: &str = "702";
+
+/// Who receives a PayNow transfer.
+enum Proxy {
+    Mobile(String),
+    Uen(String),
+}
+
+fn invalid(message: impl Into<String>) -> OrcaError {
+    OrcaError::InvalidPaymentRequest(message.into())
+}
+
+fn proxy(uen: Option<&str>, mobile: Option<&str>) -> Result<Proxy, OrcaError> {
+    match (uen, mobile) {
+        (Some(uen), None) => {
+            let uen = uen.trim().to_ascii_uppercase();
+            if !(9..=13).contains(&uen.len()) || !uen.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(invalid(format!("{:?} is not a UEN", uen)));
+            }
+            Ok(Proxy::Uen(uen))
+        }
+        (None, Some(mobile)) => {
+            let digits: String = mobile
+                .chars()
+                .filter(|c| !c.is_whitespace() && *c != '-')
+                .collect();
+            let local = digits.strip_prefix("+65").unwrap_or(&digits);
+            if local.len() != 8
+                || !local.starts_with(['8', '9'])
+                || !local.chars().all(|c| c.is_ascii_digit())
+            {
+                return Err(invalid(format!(
+                    "{:?} is not a Singapore mobile number",
+                    mobile
+                )));
+            }
+            Ok(Proxy::Mobile(format!("+65{}", local)))
+        }
+        _ => Err(invalid("give exactly one of uen or mobile")),
+    }
+}
+
+fn check_text(field: &str, value: &str) -> Result<(), OrcaError> {
+    if value.is_empty()
+        || value.len() > MAX_TEXT_LEN
+        || !value.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+    {
+        return Err(invalid(format!(
+            "{} must be 1-{} printable ASCII characters",
+            field, MAX_TEXT_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// EMV data object: two-digit ID, two-digit length, value.
+fn tlv(id: u8, value: &str) -> String {
+    format!("{:02}{:02}{}", id, value.len(), value)
+}
+
+/// CRC-16/CCITT-FALSE, as EMVCo requires for the checksum object.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// The EMVCo merchant-presented payload a banking app reads from a PayNow QR.
+pub fn payload(
+    amount: f64,
+    reference: &str,
+    uen: Option<&str>,
+    mobile: Option<&str>,
+    merchant_name: &str,
+    editable: bool,
+    expiry: Option<&str>,
+) -> Result<String, OrcaError> {
+    if !amount.is_finite() || amount <= 0.0 || amount >= 1e8 {
+        return Err(invalid(format!("{} is not a payable amount", amount)));
+    }
+    check_text("reference", reference)?;
+    check_text("merchant_name", merchant_name)?;
+    let (proxy_type, proxy_value) = match proxy(uen, mobile)? {
+        Proxy::Mobile(number) => ("0", number),
+        Proxy::Uen(uen) => ("2", uen),
+    };
+
+    let mut account = tlv(0, "SG.PAYNOW") + &tlv(1, proxy_type) + &tlv(2, &proxy_value);
+    account += &tlv(3, if editable { "1" } else { "0" });
+    if let Some(expiry) = expiry {
+        if expiry.len() != 8 || !expiry.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid(format!("expiry {:?} is not YYYYMMDD", expiry)));
+        }
+        account += &tlv(4, expiry);
+    }
+
+    let mut payload = tlv(0, "01")
+        // 12: dynamic QR, generated for one payment
+        + &tlv(1, "12")
+        + &tlv(26, &account)
+        + &tlv(52, "0000")
+        + &tlv(53, SGD_NUMERIC)
+        + &tlv(54, &format!("{:.2}", amount))
+        + &tlv(58, "SG")
+        + &tlv(59, merchant_name)
+        + &tlv(60, "Singapore")
+        + &tlv(62, &tlv(1, reference));
+    payload.push_str("6304");
+    let checksum = crc16(payload.as_bytes());
+    payload.push_str(&format!("{:04X}", checksum));
+    Ok(payload)
+}
+
+/// Build the PayNow payload string for a quote, e.g. for a custom QR renderer
+#[pyfunction]
+#[pyo3(signature = (amount, reference, uen=None, mobile=None, merchant_name="NA".to_string(), editable=false, expiry=None))]
+pub fn paynow_payload(
+    amount: f64,
+    reference: String,
+    uen: Option<String>,
+    mobile: Option<String>,
+    merchant_name: String,
+    editable: bool,
+    expiry: Option<String>,
+) -> PyResult<String> {
+    panic_boundary::catch(|| {
+        Ok(payload(
+            amount,
+            &reference,
+            uen.as_deref(),
+            mobile.as_deref(),
+            &merchant_name,
+            editable,
+            expiry.as_deref(),
+        )?)
+    })
+}
+
+/// Render a PayNow QR code for a quote as PNG or SVG bytes
+///
+/// Pay to a UEN or a Singapore mobile number; `reference` (e.g. the quote ID)
+/// shows up on the transfer. The amount is fixed unless `editable` is set, and
+/// `expiry` (YYYYMMDD) stops banking apps accepting the code after that date.
+#[pyfunction]
+#[pyo3(signature = (amount, reference, uen=None, mobile=None, image_format="png".to_string(), merchant_name="NA".to_string(), editable=false, expiry=None, scale=8))]
+#[allow(clippy::too_many_arguments)]
+pub fn generate_paynow_qr<'py>(
+    py: Python<'py>,
+    amount: f64,
+    reference: String,
+    uen: Option<String>,
+    mobile: Option<String>,
+    image_format: String,
+    merchant_name: String,
+    editable: bool,
+    expiry: Option<String>,
+    scale: usize,
+) -> PyResult<&'py PyBytes> {
+    panic_boundary::catch(|| {
+        let payload = payload(
+            amount,
+            &reference,
+            uen.as_deref(),
+            mobile.as_deref(),
+            &merchant_name,
+            editable,
+            expiry.as_deref(),
+        )?;
+        let qr = QrCode::encode(payload.as_bytes())
+            .ok_or_else(|| invalid("PayNow payload is too long for a QR code"))?;
+        let image = match image_format.to_ascii_lowercase().as_str() {
+            "png" => qr.to_png(scale),
+            "svg" => qr.to_svg().into_bytes(),
+            other => return Err(invalid(format!("unknown image format {:?}", other)).into()),
+        };
+        Ok(PyBytes::new(py, &image))
+    })
+}
@@ -0,0 +1,1182 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::archives;
+use crate::audit::unix_timestamp;
+use crate::build_plate::check_fits;
+use crate::cancellation::CancellationToken;
+use crate::decimation::{self, MIN_TARGET};
+use crate::events;
+use crate::farm_load::{self, FarmMonitor, LeadTime};
+use crate::fleet::Fleet;
+use crate::gcode_cache::GcodeCache;
+use crate::geometry::{model_dimensions, model_triangles};
+use crate::inventory::Inventory;
+use crate::job_queue;
+use crate::malware_scan::MalwareScanner;
+use crate::materials::MaterialCatalog;
+use crate::mesh_integrity::mesh_integrity;
+use crate::mesh_repair::{self, DEFAULT_MAX_HOLE_EDGES};
+use crate::metrics;
+use crate::panic_boundary;
+use crate::payments::{self, StripeConfig};
+use crate::postprocess::{self, PostProcessConfig};
+use crate::preview;
+use crate::print_history::{EstimateCalibration, PrintHistory};
+use crate::process_override::{write_process_override, PrintOptions};
+use crate::profile_compat::{check_profiles, load_resolved};
+use crate::profile_mapping::{resolve_filament, ProfileMapping};
+use crate::profiles::Profile;
+use crate::quote_store::{PriceAdjustment, QuoteStore};
+use crate::shipping::{ShippingConfig, ShippingRate};
+use crate::slicer::{run_slicer, SlicerProfiles, SlicerSlot, SlicerWatch};
+use crate::slicer_limits::SlicerLimits;
+use crate::slicer_retry::SlicerRetryPolicy;
+use crate::stability::{model_stability, Stability};
+use crate::stl_convert::{self, write_binary_stl};
+use crate::time_of_use::{self, OffPeakPrice, TimeOfUsePricing};
+use crate::validation_cache::cached_model_info;
+use crate::validation_limits::ValidationLimits;
+use crate::workspace::JobWorkspace;
+use crate::{
+    compute_cost_breakdown, parse_slicer_output_dir, CostBreakdown, FilamentSpec, ModelInfo,
+    OrcaError, SlicingResult,
+};
+
+/// Everything the quote pipeline needs, loaded once and reused across jobs
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct PipelineConfig {
+    #[pyo3(get)]
+    pub slicer_path: String,
+    #[pyo3(get)]
+    pub profiles_dir: String,
+    /// Default machine profile file name under `<profiles_dir>/machine`.
+    #[pyo3(get)]
+    pub machine_profile: String,
+    /// Default process profile file name under `<profiles_dir>/process`.
+    #[pyo3(get)]
+    pub process_profile: String,
+    #[pyo3(get)]
+    pub fleet: Option<Fleet>,
+    #[pyo3(get)]
+    pub catalog: MaterialCatalog,
+    #[pyo3(get)]
+    pub material_prices: HashMap<String, f64>,
+    #[pyo3(get)]
+    pub default_price_per_kg: f64,
+    #[pyo3(get)]
+    pub additional_time_hours: f64,
+    #[pyo3(get)]
+    pub price_multiplier: f64,
+    #[pyo3(get)]
+    pub minimum_price: f64,
+    #[pyo3(get)]
+    pub work_dir: Option<String>,
+    /// Where sliced G-code is kept for sending to a printer; `None` discards it.
+    #[pyo3(get)]
+    pub gcode_cache: Option<GcodeCache>,
+    /// Stripe account to create a payment link with; `None` leaves quotes unpaid.
+    #[pyo3(get)]
+    pub stripe: Option<StripeConfig>,
+    /// Printer-farm load used for the lead time; `None` quotes print time only.
+    #[pyo3(get)]
+    pub farm: Option<FarmMonitor>,
+    /// Carrier rates for quotes given a destination; `None` leaves shipping out.
+    #[pyo3(get)]
+    pub shipping: Option<ShippingConfig>,
+    /// Gap left between copies of a part laid out on one plate, in mm.
+    #[pyo3(get)]
+    pub plate_spacing_mm: f64,
+    /// Heat-up, homing and purge each plate repeats, shared by its copies.
+    #[pyo3(get)]
+    pub plate_overhead_minutes: f64,
+    /// Side of the rendered model preview in pixels; `None` renders no preview.
+    #[pyo3(get)]
+    pub preview_size: Option<u32>,
+    /// Start/end snippets, thumbnail stripping and scripts applied to the G-code
+    /// before it is cached; `None` keeps the slicer's output as it is.
+    #[pyo3(get)]
+    pub postprocess: Option<PostProcessConfig>,
+    /// Where quotes run with a `quote_id` are kept for later adjustment.
+    #[pyo3(get)]
+    pub quote_store: Option<QuoteStore>,
+    /// Off-peak machine-hour rates; quotes then also carry an off-peak price.
+    #[pyo3(get)]
+    pub time_of_use: Option<TimeOfUsePricing>,
+    /// Stock levels; out-of-stock materials and colors are refused before slicing.
+    #[pyo3(get)]
+    pub inventory: Option<Inventory>,
+    /// Finished prints against their estimates; each material's slicing
+    /// estimates are corrected by them before pricing.
+    #[pyo3(get)]
+    pub print_history: Option<PrintHistory>,
+    /// Refuse meshes with holes or non-manifold edges before slicing.
+    #[pyo3(get)]
+    pub require_watertight: bool,
+    /// Convert ASCII STL models to binary before slicing.
+    #[pyo3(get)]
+    pub convert_ascii_stl: bool,
+    /// Meshes with more triangles than this are simplified before slicing.
+    #[pyo3(get)]
+    pub max_triangles: Option<u64>,
+    /// Size, triangle and line limits an upload must keep to.
+    #[pyo3(get)]
+    pub validation_limits: ValidationLimits,
+    /// Scanner every upload is run through first; `None` scans nothing.
+    #[pyo3(get)]
+    pub malware_scanner: Option<MalwareScanner>,
+    /// The slicer, and anything it started, is killed after this long.
+    #[pyo3(get)]
+    pub slicer_timeout_secs: f64,
+    /// Runs the slicer again when it crashes; `None` fails the quote at once.
+    #[pyo3(get)]
+    pub slicer_retry: Option<SlicerRetryPolicy>,
+    /// CPU, memory and priority limits the slicer runs within; `None` sets none.
+    #[pyo3(get)]
+    pub slicer_limits: Option<SlicerLimits>,
+    mapping: ProfileMapping,
+}
+
+impl PipelineConfig {
+    fn profile_path(&self, profile_type: &str, file: &str) -> String {
+        Path::new(&self.profiles_dir)
+            .join(profile_type)
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Catalog name for a material or alias, e.g. "pla+" -> "PLA".
+    pub(crate) fn canonical_material(&self, material: &str) -> String {
+        self.catalog
+            .find(material)
+            .map(|m| m.name.clone())
+            .unwrap_or_else(|| material.trim().to_uppercase())
+    }
+
+    /// The filament profile a material is sliced with, found as the pipeline does.
+    pub(crate) fn filament_profile(&self, material: &str) -> Result<Profile, OrcaError> {
+        let filament_dir = PathBuf::from(&self.profiles_dir).join("filament");
+        let filament = resolve_filament(&filament_dir, material, &self.mapping)?;
+        load_resolved(Path::new(&filament.path), &[])
+    }
+
+    /// Density and diameter for turning filament length into grams: the filament
+    /// profile's, then the catalog's.
+    pub(crate) fn filament_spec(
+        &self,
+        material: &str,
+        filament: Option<&Profile>,
+    ) -> Option<FilamentSpec> {
+        filament.and_then(FilamentSpec::from_profile).or_else(|| {
+            self.catalog
+                .find(material)
+                .and_then(FilamentSpec::from_material)
+        })
+    }
+
+    /// Configured price first, then the filament profile's `filament_cost`, then the
+    /// catalog default, then the global default.
+    pub(crate) fn price_per_kg(&self, material: &str, filament: Option<&Profile>) -> f64 {
+        self.material_prices
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(material))
+            .map(|(_, price)| *price)
+            .or_else(|| {
+                filament
+                    .and_then(|profile| profile.filament_cost)
+                    .filter(|cost| *cost > 0.0)
+            })
+            .or_else(|| self.catalog.find(material).map(|m| m.price_per_kg))
+            .unwrap_or(self.default_price_per_kg)
+    }
+}
+
+#[pymethods]
+impl PipelineConfig {
+    fn __str__(&self) -> String {
+        format!(
+            "PipelineConfig(profiles_dir={}, machine={}, fleet={})",
+            self.profiles_dir,
+            self.machine_profile,
+            self.fleet.as_ref().map_or(0, |f| f.printers.len())
+        )
+    }
+}
+
+/// Outcome of a full quote: the printer and profiles used, slicing output and price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct QuoteResult {
+    #[pyo3(get)]
+    pub material: String,
+    /// Fleet printer the job was assigned to, or `None` without a fleet.
+    #[pyo3(get)]
+    pub printer: Option<String>,
+    #[pyo3(get)]
+    pub machine_profile: String,
+    /// The process profile selected; `options` were applied to a copy of it.
+    #[pyo3(get)]
+    pub process_profile: String,
+    #[pyo3(get)]
+    pub filament_profile: String,
+    /// Print options the model was sliced with.
+    #[pyo3(get)]
+    pub options: PrintOptions,
+    #[pyo3(get)]
+    pub model: ModelInfo,
+    #[pyo3(get)]
+    pub dimensions: Option<(f64, f64, f64)>,
+    /// Printable width and depth of the printer or machine profile, when known.
+    #[pyo3(get)]
+    pub bed_size: Option<(f64, f64)>,
+    #[pyo3(get)]
+    pub slicing: SlicingResult,
+    #[pyo3(get)]
+    pub cost: CostBreakdown,
+    /// Milliseconds spent in each stage: validation, profile_selection, slicing,
+    /// parsing, pricing and, when configured, postprocess, preview, payment,
+    /// lead_time and shipping.
+    #[pyo3(get)]
+    pub stage_timings_ms: HashMap<String, f64>,
+    /// Stripe Checkout URL for the quoted amount, when payments are configured
+    /// and the link could be created.
+    #[pyo3(get, set)]
+    pub payment_url: Option<String>,
+    /// When the job would be ready given the farm's current load, when a farm
+    /// monitor is configured and a capable printer can take work.
+    #[pyo3(get)]
+    pub lead_time: Option<LeadTime>,
+    /// Cheapest carrier rate to the destination, when shipping is configured and
+    /// the quote was given one. Not part of `cost`.
+    #[pyo3(get)]
+    pub shipping: Option<ShippingRate>,
+    /// PNG of the model for notifications, when `preview_size` is configured.
+    #[serde(skip)]
+    pub preview_png: Option<Vec<u8>>,
+    /// "gcode" when `preview_png` is the slicer's thumbnail, "mesh" when the
+    /// model was rendered.
+    #[pyo3(get)]
+    pub preview_source: Option<String>,
+    /// Post-processing applied to the G-code, in order; empty without `postprocess`.
+    #[pyo3(get)]
+    pub gcode_transforms: Vec<String>,
+    /// Operator changes to the price since it was quoted, oldest first; already
+    /// counted in `cost.total_cost`.
+    #[pyo3(get)]
+    pub adjustments: Vec<PriceAdjustment>,
+    /// What the quote costs when started in off-peak hours, next to the
+    /// start-anytime `cost.total_cost`; `None` without `time_of_use` or savings.
+    #[pyo3(get)]
+    pub off_peak: Option<OffPeakPrice>,
+    /// The correction applied to `slicing` from finished prints of the material,
+    /// with the slicer's own estimates; `None` when they were priced as sliced.
+    #[pyo3(get)]
+    pub calibration: Option<EstimateCalibration>,
+    /// Centre of mass, footprint and tipping risk, with a brim or raft
+    /// recommendation; `None` for formats without a mesh (STEP).
+    #[pyo3(get)]
+    pub stability: Option<Stability>,
+    /// Things the operator should know before printing, e.g. that the model
+    /// needs a raft to stay on the bed. Missing from quotes stored before it.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[pymethods]
+impl QuoteResult {
+    fn __str__(&self) -> String {
+        format!(
+            "QuoteResult(material={}, printer={:?}, total=S${:.2})",
+            self.material, self.printer, self.cost.total_cost
+        )
+    }
+
+    #[getter]
+    fn preview_png<'py>(&self, py: Python<'py>) -> Option<&'py PyBytes> {
+        self.preview_png.as_deref().map(|png| PyBytes::new(py, png))
+    }
+}
+
+/// Build a pipeline configuration, loading the fleet, catalog and mapping files up front
+#[pyfunction]
+#[pyo3(signature = (
+    slicer_path,
+    profiles_dir,
+    machine_profile,
+    process_profile,
+    material_map=None,
+    fleet_path=None,
+    material_catalog_path=None,
+    material_prices=HashMap::new(),
+    default_price_per_kg=25.0,
+    additional_time_hours=0.5,
+    price_multiplier=1.1,
+    minimum_price=5.0,
+    work_dir=None,
+    gcode_cache_dir=None,
+    gcode_cache_max_bytes=0,
+    stripe_config=None,
+    farm_monitor=None,
+    shipping_config=None,
+    plate_spacing_mm=6.0,
+    plate_overhead_minutes=10.0,
+    preview_size=None,
+    postprocess=None,
+    quote_store=None,
+    time_of_use=None,
+    inventory=None,
+    print_history=None,
+    require_watertight=false,
+    convert_ascii_stl=false,
+    max_triangles=None,
+    validation_limits=None,
+    malware_scanner=None,
+    slicer_timeout_secs=300.0,
+    slicer_retry=None,
+    slicer_limits=None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn create_pipeline_config(
+    slicer_path: String,
+    profiles_dir: String,
+    machine_profile: String,
+    process_profile: String,
+    material_map: Option<String>,
+    fleet_path: Option<String>,
+    material_catalog_path: Option<String>,
+    material_prices: HashMap<String, f64>,
+    default_price_per_kg: f64,
+    additional_time_hours: f64,
+    price_multiplier: f64,
+    minimum_price: f64,
+    work_dir: Option<String>,
+    gcode_cache_dir: Option<String>,
+    gcode_cache_max_bytes: u64,
+    stripe_config: Option<StripeConfig>,
+    farm_monitor: Option<FarmMonitor>,
+    shipping_config: Option<ShippingConfig>,
+    plate_spacing_mm: f64,
+    plate_overhead_minutes: f64,
+    preview_size: Option<u32>,
+    postprocess: Option<PostProcessConfig>,
+    quote_store: Option<QuoteStore>,
+    time_of_use: Option<TimeOfUsePricing>,
+    inventory: Option<Inventory>,
+    print_history: Option<PrintHistory>,
+    require_watertight: bool,
+    convert_ascii_stl: bool,
+    max_triangles: Option<u64>,
+    validation_limits: Option<ValidationLimits>,
+    malware_scanner: Option<MalwareScanner>,
+    slicer_timeout_secs: f64,
+    slicer_retry: Option<SlicerRetryPolicy>,
+    slicer_limits: Option<SlicerLimits>,
+) -> PyResult<PipelineConfig> {
+    panic_boundary::catch(|| {
+        if let Some(max) = max_triangles.filter(|max| *max < MIN_TARGET) {
+            return Err(OrcaError::InvalidConfig {
+                path: "max_triangles".to_string(),
+                message: format!("{} is fewer than the {} a solid needs", max, MIN_TARGET),
+            }
+            .into());
+        }
+        if !(slicer_timeout_secs > 0.0 && slicer_timeout_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "slicer_timeout_secs".to_string(),
+                message: format!(
+                    "{} is not a positive number of seconds",
+                    slicer_timeout_secs
+                ),
+            }
+            .into());
+        }
+        let fleet = fleet_path
+            .map(|path| Fleet::load(Path::new(&path), Path::new(&profiles_dir)))
+            .transpose()?;
+        let catalog = match material_catalog_path {
+            Some(path) => MaterialCatalog::load(Path::new(&path))?,
+            None => MaterialCatalog::builtin(),
+        };
+        let mapping = ProfileMapping::load_optional(material_map.as_deref().map(Path::new))?;
+
+        Ok(PipelineConfig {
+            slicer_path,
+            profiles_dir,
+            machine_profile,
+            process_profile,
+            fleet,
+            catalog,
+            material_prices,
+            default_price_per_kg,
+            additional_time_hours,
+            price_multiplier,
+            minimum_price,
+            work_dir,
+            gcode_cache: gcode_cache_dir.map(|dir| GcodeCache {
+                dir,
+                max_bytes: gcode_cache_max_bytes,
+            }),
+            stripe: stripe_config,
+            farm: farm_monitor,
+            shipping: shipping_config,
+            plate_spacing_mm,
+            plate_overhead_minutes,
+            preview_size,
+            postprocess,
+            quote_store,
+            time_of_use,
+            inventory,
+            print_history,
+            require_watertight,
+            convert_ascii_stl,
+            max_triangles,
+            validation_limits: validation_limits.unwrap_or_default(),
+            malware_scanner,
+            slicer_timeout_secs,
+            slicer_retry,
+            slicer_limits,
+            mapping,
+        })
+    })
+}
+
+pub(crate) fn outcome<T, E>(result: &Result<T, E>) -> &'static str {
+    if result.is_ok() {
+        "success"
+    } else {
+        "error"
+    }
+}
+
+/// Wall-clock time of each pipeline stage, each run inside a tracing span.
+#[derive(Default, Clone)]
+pub(crate) struct StageTimer {
+    timings_ms: HashMap<String, f64>,
+}
+
+impl StageTimer {
+    fn stage<T, E>(
+        &mut self,
+        name: &'static str,
+        run: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let span = tracing::info_span!("quote_stage", stage = name);
+        let _entered = span.enter();
+        let started = Instant::now();
+        let result = run();
+        let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+        tracing::info!(duration_ms, outcome = outcome(&result), "stage finished");
+        self.timings_ms.insert(name.to_string(), duration_ms);
+        result
+    }
+}
+
+/// Validate, slice and price a model on the best-suited printer
+///
+/// With `ship_to` (an address dict, e.g. `{"zip": ..., "country": ...}`) and a
+/// shipping config, the quote also carries the cheapest carrier rate. `options`
+/// may set `layer_height`, `infill_percent`, `supports` and `color`; the first
+/// three are applied to the process profile before slicing. With `repair`,
+/// the mesh is repaired as `repair_mesh` does before it is sliced, so a
+/// model with flipped normals or small holes is still quoted.
+/// `on_slicer_output` is called with each line the slicer writes, as it
+/// writes it, so a long slice can show progress. Cancelling `cancel` stops the
+/// quote, killing the slicer if it is running, removes the job's workspace and
+/// raises `asyncio.CancelledError`.
+#[pyfunction]
+#[pyo3(signature = (model_path, material, config, quote_id=None, ship_to=None, options=None, repair=false, on_slicer_output=None, cancel=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn run_quote_pipeline(
+    py: Python<'_>,
+    model_path: String,
+    material: String,
+    config: PyRef<'_, PipelineConfig>,
+    quote_id: Option<String>,
+    ship_to: Option<HashMap<String, String>>,
+    options: Option<&PyDict>,
+    repair: bool,
+    on_slicer_output: Option<PyObject>,
+    cancel: Option<CancellationToken>,
+) -> PyResult<QuoteResult> {
+    panic_boundary::catch(|| {
+        let material = config.canonical_material(&material);
+        let options = options
+            .map(PrintOptions::from_dict)
+            .transpose()?
+            .unwrap_or_default();
+        let span = tracing::info_span!(
+            "quote_pipeline",
+            quote_id = tracing::field::Empty,
+            model = %model_path,
+            material = %material
+        );
+        if let Some(quote_id) = &quote_id {
+            span.record("quote_id", quote_id.as_str());
+        }
+        let _entered = span.enter();
+
+        let started = Instant::now();
+        // Slicing takes seconds to minutes; let other Python threads run meanwhile.
+        let config: &PipelineConfig = &config;
+        let result = py.allow_threads(|| {
+            let watch = SlicerWatch {
+                on_output: on_slicer_output,
+                cancel,
+            };
+            let mut result = quote(
+                &model_path,
+                material.clone(),
+                options,
+                config,
+                repair,
+                &watch,
+            )?;
+            if let Some(stripe) = &config.stripe {
+                add_payment_link(&mut result, stripe, quote_id.as_deref());
+            }
+            if let Some(farm) = &config.farm {
+                add_lead_time(&mut result, farm);
+            }
+            if let Some(pricing) = &config.time_of_use {
+                add_off_peak(&mut result, pricing, config);
+            }
+            if let (Some(shipping), Some(ship_to)) = (&config.shipping, &ship_to) {
+                add_shipping(&mut result, shipping, ship_to);
+            }
+            if let (Some(store), Some(quote_id)) = (&config.quote_store, &quote_id) {
+                // The quote is still good without a stored copy to adjust later.
+                if let Err(e) = store.put(quote_id, &result) {
+                    tracing::warn!(error = %e, "could not store quote");
+                }
+            }
+            Ok::<_, PyErr>(result)
+        });
+        let outcome = outcome(&result);
+        tracing::info!(
+            duration_ms = started.elapsed().as_secs_f64() * 1000.0,
+            outcome,
+            "quote finished"
+        );
+        metrics::record_quote(&material, outcome);
+        if let Some((quote, printer)) = result
+            .as_ref()
+            .ok()
+            .and_then(|quote| Some((quote, quote.printer.as_ref()?)))
+        {
+            events::emit(
+                "printer.assigned",
+                quote_id.as_deref(),
+                json!({
+                    "printer": printer,
+                    "machine_profile": quote.machine_profile,
+                    "material": quote.material,
+                    "print_time_minutes": quote.slicing.print_time_minutes,
+                }),
+            );
+        }
+        match &result {
+            Ok(quote) => events::emit(
+                "quote.created",
+                quote_id.as_deref(),
+                json!({
+                    "material": quote.material,
+                    "printer": quote.printer,
+                    "total_cost": quote.cost.total_cost,
+                    "print_time_minutes": quote.slicing.print_time_minutes,
+                    "filament_grams": quote.slicing.filament_weight_grams,
+                    "payment_url": quote.payment_url,
+                    "lead_time_seconds": quote.lead_time.as_ref().map(|l| l.lead_time_seconds),
+                    "shipping_cost": quote.shipping.as_ref().map(|s| s.amount),
+                    "off_peak_cost": quote.off_peak.as_ref().map(|o| o.total_cost),
+                }),
+            ),
+            Err(e) => events::emit(
+                "quote.failed",
+                quote_id.as_deref(),
+                json!({ "material": material, "error": e.to_string() }),
+            ),
+        };
+        result
+    })
+}
+
+/// Keep the sliced G-code for later; a cache failure never fails the quote.
+fn cache_gcode(
+    cache: &GcodeCache,
+    model_path: &str,
+    profiles: &[&str],
+    output_dir: &str,
+) -> Option<String> {
+    let profiles: Vec<&Path> = profiles.iter().map(Path::new).collect();
+    let stored = cache
+        .key_for(Path::new(model_path), &profiles)
+        .and_then(|key| cache.insert(&key, Path::new(output_dir)).map(|_| key));
+    match stored {
+        Ok(key) => Some(key),
+        Err(e) => {
+            tracing::warn!(error = %e, "could not cache G-code");
+            None
+        }
+    }
+}
+
+/// Attach a Stripe Checkout link; like the G-code cache, a failure here only
+/// costs the customer the link, never the quote.
+fn add_payment_link(result: &mut QuoteResult, stripe: &StripeConfig, quote_id: Option<&str>) {
+    let mut timer = StageTimer::default();
+    let link = timer.stage("payment", || {
+        payments::checkout_session(&result.cost, stripe, quote_id)
+    });
+    result.stage_timings_ms.extend(timer.timings_ms);
+    match link {
+        Ok(url) => result.payment_url = Some(url),
+        Err(e) => tracing::warn!(error = %e, "could not create payment link"),
+    }
+}
+
+fn add_lead_time(result: &mut QuoteResult, farm: &FarmMonitor) {
+    let mut timer = StageTimer::default();
+    let lead_time = timer.stage("lead_time", || {
+        Ok::<_, OrcaError>(farm_load::lead_time(
+            farm,
+            result.slicing.print_time_minutes as f64 * 60.0,
+            Some(&result.material),
+            result.dimensions,
+        ))
+    });
+    result.stage_timings_ms.extend(timer.timings_ms);
+    result.lead_time = lead_time.ok().flatten();
+}
+
+/// Price an off-peak start, from when the farm can first take the job.
+fn add_off_peak(result: &mut QuoteResult, pricing: &TimeOfUsePricing, config: &PipelineConfig) {
+    let lead_time = result.lead_time.as_ref();
+    let earliest_start =
+        unix_timestamp(SystemTime::now()) + lead_time.map_or(0.0, |l| l.wait_seconds);
+    result.off_peak = time_of_use::off_peak_price(
+        pricing,
+        &result.cost,
+        config.minimum_price,
+        earliest_start,
+        config.farm.as_ref().and_then(|farm| farm.calendar.as_ref()),
+        lead_time.map_or(0.0, |l| l.handling_seconds),
+    );
+}
+
+/// Attach the cheapest carrier rate; without a bounding box or a reachable
+/// provider the quote simply goes out without shipping.
+fn add_shipping(
+    result: &mut QuoteResult,
+    shipping: &ShippingConfig,
+    ship_to: &HashMap<String, String>,
+) {
+    let mut timer = StageTimer::default();
+    let rates = timer.stage("shipping", || {
+        let dimensions = result
+            .dimensions
+            .ok_or_else(|| OrcaError::InvalidModel("bounding box is unknown".to_string()))?;
+        let parcel = shipping.parcel(result.slicing.filament_weight_grams as f64, dimensions);
+        shipping.rates(&parcel, ship_to)
+    });
+    result.stage_timings_ms.extend(timer.timings_ms);
+    match rates {
+        Ok(rates) => result.shipping = rates.into_iter().next(),
+        Err(e) => tracing::warn!(error = %e, "could not get shipping rates"),
+    }
+}
+
+/// A color the catalog does not stock for the material is refused; one it does
+/// is given the catalog's spelling.
+fn check_color(
+    options: &mut PrintOptions,
+    catalog: &MaterialCatalog,
+    material: &str,
+) -> Result<(), OrcaError> {
+    let (Some(color), Some(stocked)) = (&options.color, catalog.find(material)) else {
+        return Ok(());
+    };
+    if stocked.colors.is_empty() {
+        return Ok(());
+    }
+    match stocked
+        .colors
+        .iter()
+        .find(|c| c.eq_ignore_ascii_case(color))
+    {
+        Some(canonical) => {
+            options.color = Some(canonical.clone());
+            Ok(())
+        }
+        None => Err(OrcaError::InvalidOverride(format!(
+            "color '{}' is not stocked in {}; choose one of {}",
+            color,
+            stocked.name,
+            stocked.colors.join(", ")
+        ))),
+    }
+}
+
+/// Refuse a material, or its color, with less than `grams` in stock.
+fn check_stock(
+    config: &PipelineConfig,
+    material: &str,
+    options: &PrintOptions,
+    grams: f64,
+) -> Result<(), OrcaError> {
+    match &config.inventory {
+        Some(inventory) => {
+            inventory.check(&config.catalog, material, options.color.as_deref(), grams)
+        }
+        None => Ok(()),
+    }
+}
+
+/// A model that passed validation, with its bounding box when it could be read.
+pub(crate) struct CheckedModel {
+    info: ModelInfo,
+    dimensions: Option<(f64, f64, f64)>,
+    stability: Option<Stability>,
+}
+
+fn validate_model(
+    model_path: &str,
+    limits: &ValidationLimits,
+    timer: &mut StageTimer,
+) -> PyResult<CheckedModel> {
+    timer.stage("validation", || -> PyResult<_> {
+        let model = limits.validate(Path::new(model_path), cached_model_info)?;
+        metrics::observe_file_size(model.file_size);
+        if !model.is_valid {
+            return Err(OrcaError::InvalidModel(
+                model
+                    .error_message
+                    .clone()
+                    .unwrap_or_else(|| "unknown error".to_string()),
+            )
+            .into());
+        }
+        // STL validation measures the model already; other formats are read again.
+        let dimensions = match model.dimensions_mm {
+            Some(dimensions) => Some(dimensions),
+            None => model_dimensions(Path::new(model_path)).map_err(OrcaError::IoError)?,
+        };
+        let stability = match &model.stability {
+            Some(stability) => Some(stability.clone()),
+            None => model_stability(Path::new(model_path)).map_err(OrcaError::IoError)?,
+        };
+        Ok(CheckedModel {
+            info: model,
+            dimensions,
+            stability,
+        })
+    })
+}
+
+/// Refuse a mesh with holes or non-manifold edges when the config says to.
+fn check_integrity(
+    model_path: &str,
+    config: &PipelineConfig,
+    timer: &mut StageTimer,
+) -> PyResult<()> {
+    if config.require_watertight {
+        timer.stage("integrity", || -> PyResult<_> {
+            // Only holes and non-manifold edges stop a slicer; flipped normals
+            // and duplicate faces it repairs on its own.
+            let integrity = mesh_integrity(Path::new(model_path))?;
+            if !integrity.is_watertight {
+                return Err(OrcaError::InvalidModel(format!(
+                    "Mesh is not watertight: {}",
+                    integrity.problems.join(", ")
+                ))
+                .into());
+            }
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+pub(crate) fn check_model(
+    model_path: &str,
+    config: &PipelineConfig,
+    timer: &mut StageTimer,
+) -> PyResult<CheckedModel> {
+    let checked = validate_model(model_path, &config.validation_limits, timer)?;
+    check_integrity(model_path, config, timer)?;
+    Ok(checked)
+}
+
+/// Where a rewritten copy of the model goes: `<stem>.<label>.stl` in the
+/// workspace's model directory.
+fn workspace_copy(model_path: &str, workspace: &JobWorkspace, label: &str) -> PathBuf {
+    let stem = Path::new(model_path)
+        .file_stem()
+        .map_or("model".into(), |stem| stem.to_string_lossy());
+    Path::new(&workspace.model_dir).join(format!("{}.{}.stl", stem, label))
+}
+
+/// Repair the model's mesh into the workspace, returning the repaired file;
+/// formats without a mesh (STEP) are sliced as they are.
+fn repair_model(
+    model_path: &str,
+    workspace: &JobWorkspace,
+    timer: &mut StageTimer,
+) -> PyResult<String> {
+    timer.stage("repair", || -> PyResult<_> {
+        let output = workspace_copy(model_path, workspace, "repaired");
+        match mesh_repair::repair_file(Path::new(model_path), &output, DEFAULT_MAX_HOLE_EDGES)? {
+            Some(report) => {
+                tracing::info!(
+                    faces_flipped = report.faces_flipped,
+                    holes_filled = report.holes_filled,
+                    degenerate_removed = report.degenerate_removed,
+                    duplicates_removed = report.duplicates_removed,
+                    watertight = report.integrity.is_watertight,
+                    "mesh repaired"
+                );
+                Ok(output.to_string_lossy().into_owned())
+            }
+            None => Ok(model_path.to_string()),
+        }
+    })
+}
+
+/// Simplify the model's mesh into the workspace when it has more than
+/// `max_triangles`, returning the file to slice; smaller meshes and formats
+/// without one (STEP) are sliced as they are.
+fn decimate_model(
+    model_path: &str,
+    max_triangles: u64,
+    workspace: &JobWorkspace,
+    timer: &mut StageTimer,
+) -> PyResult<String> {
+    timer.stage("decimation", || -> PyResult<_> {
+        let triangles = match model_triangles(Path::new(model_path))? {
+            Some(triangles) if triangles.len() as u64 > max_triangles => triangles,
+            _ => return Ok(model_path.to_string()),
+        };
+        let (decimated, cell_size_mm) = decimation::decimate(&triangles, max_triangles);
+        let output = workspace_copy(model_path, workspace, "decimated");
+        write_binary_stl(&output, &decimated)?;
+        tracing::info!(
+            triangles_before = triangles.len(),
+            triangles_after = decimated.len(),
+            cell_size_mm,
+            "mesh decimated"
+        );
+        Ok(output.to_string_lossy().into_owned())
+    })
+}
+
+/// Validate an upload, first running it through the malware scanner and
+/// taking the model out of it into the workspace when it is an archive, and
+/// repairing, simplifying or converting its mesh
+/// there when asked. Returns the model file to slice.
+fn check_upload(
+    model_path: &str,
+    config: &PipelineConfig,
+    repair: bool,
+    workspace: &JobWorkspace,
+    timer: &mut StageTimer,
+) -> PyResult<(String, CheckedModel)> {
+    if let Some(scanner) = &config.malware_scanner {
+        timer.stage("scanning", || scanner.scan(Path::new(model_path)))?;
+    }
+    let (path, entry_name) = if archives::is_archive(Path::new(model_path)) {
+        let (entry_name, extracted) = timer.stage("unpacking", || -> PyResult<_> {
+            Ok(archives::unpack(
+                Path::new(model_path),
+                Path::new(&workspace.model_dir),
+            )?)
+        })?;
+        (extracted.to_string_lossy().into_owned(), Some(entry_name))
+    } else {
+        (model_path.to_string(), None)
+    };
+    let mut model = validate_model(&path, &config.validation_limits, timer)?;
+    model.info.archive_entry = entry_name;
+    let mut path = if repair {
+        repair_model(&path, workspace, timer)?
+    } else {
+        path
+    };
+    if let Some(max_triangles) = config.max_triangles {
+        path = decimate_model(&path, max_triangles, workspace, timer)?;
+    }
+    // Repaired and simplified copies are binary already.
+    let path = if config.convert_ascii_stl && stl_convert::is_ascii_stl(Path::new(&path))? {
+        timer.stage("conversion", || -> PyResult<_> {
+            let output = workspace_copy(&path, workspace, "binary");
+            stl_convert::convert(Path::new(&path), &output, true)?;
+            Ok(output.to_string_lossy().into_owned())
+        })?
+    } else {
+        path
+    };
+    check_integrity(&path, config, timer)?;
+    Ok((path, model))
+}
+
+pub(crate) fn quote(
+    model_path: &str,
+    material: String,
+    options: PrintOptions,
+    config: &PipelineConfig,
+    repair: bool,
+    watch: &SlicerWatch,
+) -> PyResult<QuoteResult> {
+    let mut timer = StageTimer::default();
+    let workspace = JobWorkspace::create(config.work_dir.as_deref().map(Path::new), None)?;
+    let result = check_upload(model_path, config, repair, &workspace, &mut timer).and_then(
+        |(path, model)| {
+            slice_and_price(
+                &path,
+                &model,
+                material,
+                options,
+                config,
+                &workspace,
+                Path::new(&workspace.output_dir),
+                timer,
+                watch,
+            )
+        },
+    );
+    workspace.release();
+    result
+}
+
+/// Select profiles for one material, then slice into `output_dir` and price the
+/// result. Several materials can share a workspace, each with its own output.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn slice_and_price(
+    model_path: &str,
+    model: &CheckedModel,
+    material: String,
+    mut options: PrintOptions,
+    config: &PipelineConfig,
+    workspace: &JobWorkspace,
+    output_dir: &Path,
+    mut timer: StageTimer,
+    watch: &SlicerWatch,
+) -> PyResult<QuoteResult> {
+    watch.check("quote")?;
+    check_color(&mut options, &config.catalog, &material)?;
+    check_stock(config, &material, &options, 0.0)?;
+    let dimensions = model.dimensions;
+
+    let (printer, machine_profile, process_profile, filament, filament_profile, bed_size) =
+        timer.stage("profile_selection", || -> PyResult<_> {
+            let printer = match &config.fleet {
+                Some(fleet) => Some(fleet.select(&material, dimensions)?),
+                None => None,
+            };
+            let machine_profile = printer
+                .map(|p| p.machine_profile.clone())
+                .unwrap_or_else(|| config.profile_path("machine", &config.machine_profile));
+            // A process mapped to the material (e.g. a slow profile for TPU) beats the
+            // printer's own process, which beats the default.
+            let process_profile = config
+                .mapping
+                .process_for(&material)
+                .map(|file| config.profile_path("process", file))
+                .or_else(|| printer.and_then(|p| p.process_profile.clone()))
+                .unwrap_or_else(|| config.profile_path("process", &config.process_profile));
+            let filament_dir = PathBuf::from(&config.profiles_dir).join("filament");
+            let filament = resolve_filament(&filament_dir, &material, &config.mapping)?;
+            let filament_profile = load_resolved(Path::new(&filament.path), &[])?;
+            let machine = load_resolved(Path::new(&machine_profile), &[])?;
+            let bed_size = printer.and_then(|p| p.bed_size).or(machine.bed_size);
+            // Refuse an oversized model here rather than waiting for the slicer to.
+            if let Some(dimensions) = dimensions {
+                let max_height = printer
+                    .and_then(|p| p.max_height)
+                    .or(machine.printable_height);
+                let name = printer.map_or(machine.name.as_str(), |p| p.name.as_str());
+                check_fits(dimensions, name, bed_size, max_height)?;
+            }
+            let compatibility = check_profiles(
+                &machine,
+                &filament_profile,
+                &load_resolved(Path::new(&process_profile), &[])?,
+            );
+            if !compatibility.is_compatible() {
+                return Err(OrcaError::IncompatibleProfiles(compatibility.summary()).into());
+            }
+            Ok((
+                printer,
+                machine_profile,
+                process_profile,
+                filament,
+                filament_profile,
+                bed_size,
+            ))
+        })?;
+
+    // Options are written into a copy of the process profile that goes with the job.
+    let overrides = options.overrides();
+    let sliced_process = if overrides.is_empty() {
+        Ok(process_profile.clone())
+    } else {
+        write_process_override(
+            Path::new(&process_profile),
+            &overrides,
+            Path::new(&workspace.model_dir),
+        )
+        .map(|path| path.to_string_lossy().into_owned())
+    };
+    let slot = timer.stage("slicer_queue", || {
+        SlicerSlot::acquire_unless(watch.cancel.as_ref())
+            .ok_or_else(|| OrcaError::Cancelled("quote waiting for a slicer".to_string()))
+    })?;
+    let retry = config.slicer_retry.clone().unwrap_or_default();
+    let sliced = sliced_process
+        .and_then(|process| {
+            timer
+                .stage("slicing", || {
+                    let profiles = SlicerProfiles {
+                        machine: &machine_profile,
+                        process: &process,
+                        filament: &filament.path,
+                    };
+                    retry.run(watch, || {
+                        run_slicer(
+                            &config.slicer_path,
+                            Path::new(model_path),
+                            &profiles,
+                            output_dir,
+                            Path::new(&workspace.root),
+                            Duration::from_secs_f64(config.slicer_timeout_secs),
+                            config.slicer_limits.as_ref(),
+                            watch,
+                        )
+                    })
+                })
+                .map(|attempts| (process, attempts))
+        })
+        .and_then(|(process, attempts)| {
+            timer
+                .stage("parsing", || {
+                    parse_slicer_output_dir(
+                        output_dir,
+                        config.filament_spec(&material, Some(&filament_profile)),
+                    )
+                    .map_err(OrcaError::IoError)
+                })
+                .map(|mut slicing| {
+                    slicing.slicer_attempts = attempts;
+                    (process, slicing)
+                })
+        })
+        .and_then(|(process, slicing)| match &config.postprocess {
+            // Before caching, so what is stored and sent is the processed file.
+            Some(post) => timer
+                .stage("postprocess", || {
+                    postprocess::process(post, output_dir, None)
+                })
+                .map(|transforms| (process, slicing, transforms)),
+            None => Ok((process, slicing, Vec::new())),
+        })
+        .map(|(process, mut slicing, transforms)| {
+            if let Some(cache) = &config.gcode_cache {
+                slicing.gcode_cache_key = cache_gcode(
+                    cache,
+                    model_path,
+                    &[&machine_profile, &process, &filament.path],
+                    &output_dir.to_string_lossy(),
+                );
+            }
+            (slicing, transforms)
+        });
+    drop(slot);
+    let (mut slicing, gcode_transforms) = sliced?;
+    if let Some(slicing_ms) = timer.timings_ms.get("slicing") {
+        metrics::observe_slice_seconds(slicing_ms / 1000.0);
+        job_queue::record_slice_seconds(slicing_ms / 1000.0);
+    }
+
+    let calibration = config
+        .print_history
+        .as_ref()
+        .and_then(|history| history.calibrate(&material, &mut slicing));
+
+    // Now that the filament used is known, there has to be enough of it.
+    check_stock(
+        config,
+        &material,
+        &options,
+        slicing.filament_weight_grams as f64,
+    )?;
+    let cost = timer.stage("pricing", || {
+        Ok::<_, PyErr>(compute_cost_breakdown(
+            slicing.print_time_minutes,
+            slicing.filament_weight_grams,
+            material.clone(),
+            config.price_per_kg(&material, Some(&filament_profile)),
+            config.additional_time_hours,
+            config.price_multiplier,
+            config.minimum_price,
+        ))
+    })?;
+    // Rendered while the G-code is still in the workspace, for its thumbnail.
+    let preview = config.preview_size.and_then(|size| {
+        timer
+            .stage("preview", || {
+                preview::preview(Path::new(model_path), Some(output_dir), size)
+            })
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "could not render preview");
+                None
+            })
+    });
+    tracing::info!(
+        total = cost.total_cost,
+        slicing_ms = timer.timings_ms.get("slicing").copied().unwrap_or_default(),
+        "quote ready"
+    );
+
+    Ok(QuoteResult {
+        material,
+        printer: printer.map(|p| p.name.clone()),
+        machine_profile,
+        process_profile,
+        filament_profile: filament.path,
+        options,
+        model: model.info.clone(),
+        dimensions,
+        bed_size,
+        slicing,
+        cost,
+        stage_timings_ms: timer.timings_ms,
+        payment_url: None,
+        lead_time: None,
+        shipping: None,
+        preview_source: preview.as_ref().map(|p| p.source.to_string()),
+        preview_png: preview.map(|p| p.png),
+        gcode_transforms,
+        adjustments: Vec::new(),
+        off_peak: None,
+        calibration,
+        warnings: model
+            .stability
+            .iter()
+            .filter_map(|stability| stability.warning.clone())
+            .chain(
+                model
+                    .info
+                    .obj_materials
+                    .iter()
+                    .map(|materials| materials.warning.clone()),
+            )
+            .collect(),
+        stability: model.stability.clone(),
+    })
+}
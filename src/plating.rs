@@ -0,0 +1,109 @@
+use pyo3::prelude::*;
+
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// How copies of one model are spread over build plates
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct PlatePlan {
+    #[pyo3(get)]
+    pub quantity: u32,
+    #[pyo3(get)]
+    pub copies_per_plate: u32,
+    #[pyo3(get)]
+    pub plates: u32,
+    /// Print time of every plate: the per-plate overhead once per plate, the
+    /// rest of the single-copy time once per copy.
+    #[pyo3(get)]
+    pub print_time_minutes: u64,
+}
+
+#[pymethods]
+impl PlatePlan {
+    fn __str__(&self) -> String {
+        format!(
+            "PlatePlan(quantity={}, copies_per_plate={}, plates={}, print_time={}min)",
+            self.quantity, self.copies_per_plate, self.plates, self.print_time_minutes
+        )
+    }
+}
+
+/// Copies of a `width` x `depth` footprint that fit a bed in a grid with `spacing`
+/// between them, in whichever of the two 90° turns fits more. A model larger
+/// than the bed still gets a plate to itself.
+pub fn copies_per_plate(
+    (width, depth): (f64, f64),
+    (bed_width, bed_depth): (f64, f64),
+    spacing: f64,
+) -> u32 {
+    let fit = |size: f64, bed: f64| ((bed + spacing) / (size + spacing)).floor().max(0.0) as u32;
+    let upright = fit(width, bed_width) * fit(depth, bed_depth);
+    let turned = fit(depth, bed_width) * fit(width, bed_depth);
+    upright.max(turned).max(1)
+}
+
+/// Lay `quantity` copies out over plates; `unit_minutes` is the sliced time of
+/// one copy, which includes the heat-up, homing and purge each plate repeats.
+pub fn plan(
+    quantity: u32,
+    footprint: (f64, f64),
+    bed_size: (f64, f64),
+    unit_minutes: u32,
+    spacing_mm: f64,
+    plate_overhead_minutes: f64,
+) -> PlatePlan {
+    let copies_per_plate = copies_per_plate(footprint, bed_size, spacing_mm).min(quantity.max(1));
+    let plates = quantity.div_ceil(copies_per_plate);
+    let overhead = plate_overhead_minutes.clamp(0.0, unit_minutes as f64);
+    let minutes = plates as f64 * overhead + quantity as f64 * (unit_minutes as f64 - overhead);
+    PlatePlan {
+        quantity,
+        copies_per_plate,
+        plates,
+        print_time_minutes: minutes.round() as u64,
+    }
+}
+
+/// Plan how `quantity` copies of a model are batched onto build plates
+///
+/// `dimensions` is the model's bounding box (only width and depth are used) and
+/// `bed_size` the printable width and depth, both in mm.
+#[pyfunction]
+#[pyo3(signature = (
+    quantity,
+    dimensions,
+    bed_size,
+    unit_print_minutes,
+    spacing_mm=6.0,
+    plate_overhead_minutes=10.0,
+))]
+pub fn plan_plates(
+    quantity: u32,
+    dimensions: (f64, f64, f64),
+    bed_size: (f64, f64),
+    unit_print_minutes: u32,
+    spacing_mm: f64,
+    plate_overhead_minutes: f64,
+) -> PyResult<PlatePlan> {
+    panic_boundary::catch(|| {
+        if quantity == 0 {
+            return Err(OrcaError::InvalidModel("quantity must be at least 1".to_string()).into());
+        }
+        if bed_size.0 <= 0.0 || bed_size.1 <= 0.0 {
+            return Err(OrcaError::InvalidConfig {
+                path: "bed_size".to_string(),
+                message: "must be positive".to_string(),
+            }
+            .into());
+        }
+        Ok(plan(
+            quantity,
+            (dimensions.0, dimensions.1),
+            bed_size,
+            unit_print_minutes,
+            spacing_mm.max(0.0),
+            plate_overhead_minutes,
+        ))
+    })
+}
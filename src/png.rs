@@ -0,0 +1,40 @@
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+/// A PNG file of 8-bit pixels, row by row: greyscale with one channel, RGB
+/// with three. Enough for QR codes and previews without an imaging dependency.
+pub fn encode(width: usize, height: usize, channels: usize, pixels: &[u8]) -> Vec<u8> {
+    let stride = width * channels;
+    let mut raw = Vec::with_capacity((stride + 1) * height);
+    for row in pixels.chunks(stride.max(1)).take(height) {
+        raw.push(0); // filter: none
+        raw.extend_from_slice(row);
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    // Writing to a Vec cannot fail.
+    let _ = encoder.write_all(&raw);
+    let compressed = encoder.finish().unwrap_or_default();
+
+    let color_type = if channels == 3 { 2 } else { 0 };
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, color_type, 0, 0, 0]); // 8-bit, no interlace
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [
+        (b"IHDR", &header),
+        (b"IDAT", &compressed),
+        (b"IEND", &Vec::new()),
+    ] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(kind);
+        hasher.update(data);
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        png.extend_from_slice(&hasher.finalize().to_be_bytes());
+    }
+    png
+}
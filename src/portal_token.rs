@@ -0,0 +1,133 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use pyo3::prelude::*;
+use ring::hmac;
+use serde_json::{json, Value};
+use std::time::SystemTime;
+
+use crate::audit::unix_timestamp;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Tokens carry their format version so the scheme can change without
+/// silently accepting old links.
+const VERSION: &str = "v1";
+/// Shorter secrets make the HMAC easy to brute-force.
+const MIN_SECRET_LEN: usize = 16;
+
+/// A verified customer portal token: the quote it grants access to and until when
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct QuoteToken {
+    #[pyo3(get)]
+    pub quote_id: String,
+    /// Seconds since the Unix epoch.
+    #[pyo3(get)]
+    pub expires_at: f64,
+}
+
+#[pymethods]
+impl QuoteToken {
+    fn __str__(&self) -> String {
+        format!(
+            "QuoteToken(quote_id={}, expires_at={:.0})",
+            self.quote_id, self.expires_at
+        )
+    }
+}
+
+fn key(secret: &str) -> Result<hmac::Key, OrcaError> {
+    if secret.len() < MIN_SECRET_LEN {
+        return Err(OrcaError::InvalidConfig {
+            path: "quote token secret".to_string(),
+            message: format!("must be at least {} bytes", MIN_SECRET_LEN),
+        });
+    }
+    Ok(hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()))
+}
+
+/// `v1.<base64url payload>.<base64url HMAC-SHA256 of "v1.<payload>">`
+fn sign(quote_id: &str, expires_at: f64, secret: &str) -> Result<String, OrcaError> {
+    let payload = json!({ "qid": quote_id, "exp": expires_at.floor() as i64 });
+    let signed = format!(
+        "{}.{}",
+        VERSION,
+        URL_SAFE_NO_PAD.encode(payload.to_string())
+    );
+    let tag = hmac::sign(&key(secret)?, signed.as_bytes());
+    Ok(format!(
+        "{}.{}",
+        signed,
+        URL_SAFE_NO_PAD.encode(tag.as_ref())
+    ))
+}
+
+fn verify(token: &str, secret: &str, now: f64) -> Result<QuoteToken, OrcaError> {
+    let invalid = |reason: &str| OrcaError::InvalidToken(reason.to_string());
+    let key = key(secret)?;
+    let (signed, tag) = token.rsplit_once('.').ok_or_else(|| invalid("malformed"))?;
+    let (version, payload) = signed.split_once('.').ok_or_else(|| invalid("malformed"))?;
+    if version != VERSION {
+        return Err(invalid("unsupported version"));
+    }
+    let tag = URL_SAFE_NO_PAD
+        .decode(tag)
+        .map_err(|_| invalid("malformed"))?;
+    // Constant-time comparison, so the tag cannot be guessed byte by byte.
+    hmac::verify(&key, signed.as_bytes(), &tag).map_err(|_| invalid("bad signature"))?;
+
+    let payload: Value = URL_SAFE_NO_PAD
+        .decode(payload)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| invalid("malformed"))?;
+    let quote_id = payload.get("qid").and_then(Value::as_str);
+    let expires_at = payload.get("exp").and_then(Value::as_f64);
+    let (Some(quote_id), Some(expires_at)) = (quote_id, expires_at) else {
+        return Err(invalid("malformed"));
+    };
+    if now >= expires_at {
+        return Err(invalid("expired"));
+    }
+    Ok(QuoteToken {
+        quote_id: quote_id.to_string(),
+        expires_at,
+    })
+}
+
+/// Sign a tamper-proof token giving access to one quote for `expires_in_secs`
+///
+/// Customers can be sent a link containing it instead of needing an account.
+#[pyfunction]
+pub fn sign_quote_token(
+    quote_id: String,
+    expires_in_secs: f64,
+    secret: String,
+) -> PyResult<String> {
+    panic_boundary::catch(|| {
+        if !expires_in_secs.is_finite() || expires_in_secs <= 0.0 {
+            return Err(OrcaError::InvalidConfig {
+                path: "quote token".to_string(),
+                message: format!("expiry must be positive, got {}", expires_in_secs),
+            }
+            .into());
+        }
+        let expires_at = unix_timestamp(SystemTime::now()) + expires_in_secs;
+        Ok(sign(&quote_id, expires_at, &secret)?)
+    })
+}
+
+/// Check a token's signature and expiry; returns the quote it grants access to
+///
+/// Raises ValueError when the token was tampered with, signed with another
+/// secret, or has expired.
+#[pyfunction]
+pub fn verify_quote_token(token: String, secret: String) -> PyResult<QuoteToken> {
+    panic_boundary::catch(|| {
+        Ok(verify(
+            token.trim(),
+            &secret,
+            unix_timestamp(SystemTime::now()),
+        )?)
+    })
+}
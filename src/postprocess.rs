@@ -0,0 +1,340 @@
+use pyo3::prelude::*;
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::audit;
+use crate::gcode_cache::gcode_files;
+use crate::gcode_scan::for_each_line;
+use crate::memory_limits::Budget;
+use crate::panic_boundary;
+use crate::validation_cache::file_digest;
+use crate::OrcaError;
+
+/// Changes made to sliced G-code before it is cached or sent to a printer
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct PostProcessConfig {
+    /// Inserted before the first command, after the slicer's header comments.
+    #[pyo3(get)]
+    pub start_gcode: Option<String>,
+    /// Inserted after the last command, before the slicer's trailing settings block.
+    #[pyo3(get)]
+    pub end_gcode: Option<String>,
+    /// Drop the embedded preview images, which printers without a screen never use.
+    #[pyo3(get)]
+    pub strip_thumbnails: bool,
+    /// Program and arguments run with the G-code path appended, editing the file
+    /// in place as OrcaSlicer's post-processing scripts do; empty runs nothing.
+    #[pyo3(get)]
+    pub script: Vec<String>,
+    #[pyo3(get)]
+    pub script_timeout_secs: f64,
+    /// Where each processed file is recorded as a `gcode_postprocessed` event.
+    #[pyo3(get)]
+    pub audit_log_path: Option<String>,
+}
+
+#[pymethods]
+impl PostProcessConfig {
+    fn __str__(&self) -> String {
+        format!(
+            "PostProcessConfig(transforms={:?}, audit_log={:?})",
+            self.transforms(),
+            self.audit_log_path
+        )
+    }
+}
+
+impl PostProcessConfig {
+    /// Names of the transforms this config applies, in the order they run.
+    pub fn transforms(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        if self.strip_thumbnails {
+            names.push("strip_thumbnails".to_string());
+        }
+        if self.start_gcode.is_some() {
+            names.push("start_gcode".to_string());
+        }
+        if self.end_gcode.is_some() {
+            names.push("end_gcode".to_string());
+        }
+        if let Some(program) = self.script.first() {
+            names.push(format!("script:{}", program));
+        }
+        names
+    }
+
+    fn rewrites(&self) -> bool {
+        self.strip_thumbnails || self.start_gcode.is_some() || self.end_gcode.is_some()
+    }
+}
+
+fn is_comment_or_blank(line: &[u8]) -> bool {
+    let line = line.trim_ascii_start();
+    line.is_empty() || line.starts_with(b";")
+}
+
+/// `; thumbnail begin`, `; thumbnail_PNG begin`, `; thumbnail_JPG end` and the like.
+fn thumbnail_marker(line: &[u8]) -> Option<bool> {
+    let text = std::str::from_utf8(line).ok()?.trim();
+    let rest = text.strip_prefix(';')?.trim_start();
+    let (name, edge) = rest.split_once(' ')?;
+    if !name.starts_with("thumbnail") {
+        return None;
+    }
+    match edge.split_whitespace().next()? {
+        "begin" => Some(true),
+        "end" => Some(false),
+        _ => None,
+    }
+}
+
+fn push_snippet(out: &mut Vec<u8>, snippet: &str) {
+    out.extend_from_slice(snippet.trim_end_matches(['\r', '\n']).as_bytes());
+    out.push(b'\n');
+}
+
+/// Rewrite `path` with thumbnails stripped and the snippets inserted, through a
+/// temporary file so a failure leaves the slicer's output as it was. Line endings
+/// are written as `\n`.
+fn rewrite(path: &Path, config: &PostProcessConfig) -> io::Result<()> {
+    let temp = path.with_extension("gcode.tmp");
+    let mut writer = BufWriter::new(File::create(&temp)?);
+    let mut failed: Option<io::Error> = None;
+    let mut in_thumbnail = false;
+    let mut started = false;
+    // Comments since the last command; the end snippet goes in front of them.
+    let mut trailing: Vec<u8> = Vec::new();
+    let mut write = |bytes: &[u8], writer: &mut BufWriter<File>| {
+        if failed.is_none() {
+            if let Err(e) = writer.write_all(bytes) {
+                failed = Some(e);
+            }
+        }
+    };
+
+    let scanned = for_each_line(File::open(path)?, Budget::GcodeParse, |line| {
+        if config.strip_thumbnails {
+            match thumbnail_marker(line) {
+                Some(true) => in_thumbnail = true,
+                Some(false) => {
+                    in_thumbnail = false;
+                    return;
+                }
+                None => {}
+            }
+            if in_thumbnail {
+                return;
+            }
+        }
+        if is_comment_or_blank(line) {
+            trailing.extend_from_slice(line);
+            trailing.push(b'\n');
+            return;
+        }
+        if !started {
+            started = true;
+            // The header comments stay on top, where printers and slicers look for them.
+            write(&trailing, &mut writer);
+            trailing.clear();
+            if let Some(snippet) = &config.start_gcode {
+                push_snippet(&mut trailing, snippet);
+            }
+        }
+        write(&trailing, &mut writer);
+        trailing.clear();
+        write(line, &mut writer);
+        write(b"\n", &mut writer);
+    });
+
+    let mut tail = Vec::new();
+    if !started {
+        // Only comments: keep them first and add both snippets after.
+        tail.append(&mut trailing);
+        if let Some(snippet) = &config.start_gcode {
+            push_snippet(&mut tail, snippet);
+        }
+    }
+    if let Some(snippet) = &config.end_gcode {
+        push_snippet(&mut tail, snippet);
+    }
+    tail.extend_from_slice(&trailing);
+    write(&tail, &mut writer);
+
+    let finished = scanned
+        .and_then(|()| failed.map_or(Ok(()), Err))
+        .and_then(|()| writer.flush())
+        .and_then(|()| fs::rename(&temp, path));
+    if finished.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    finished
+}
+
+/// Run the user's script on `path`, killing it after `timeout`.
+fn run_script(script: &[String], path: &Path, timeout: Duration) -> Result<(), OrcaError> {
+    let Some((program, args)) = script.split_first() else {
+        return Ok(());
+    };
+    let failed = |message: String| {
+        OrcaError::SlicerFailed(format!("post-processing script {}: {}", program, message))
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(path)
+        .current_dir(path.parent().unwrap_or(Path::new(".")))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| failed(format!("could not start: {}", e)))?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(failed(format!("exited with {}", status))),
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(failed(format!("did not finish within {:?}", timeout)));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Apply every configured transform to one G-code file and record it in the
+/// audit log; returns the transforms applied.
+pub fn process_file(
+    config: &PostProcessConfig,
+    path: &Path,
+    quote_id: Option<&str>,
+) -> Result<Vec<String>, OrcaError> {
+    let bytes_before = fs::metadata(path)?.len();
+    if config.rewrites() {
+        rewrite(path, config)?;
+    }
+    run_script(
+        &config.script,
+        path,
+        Duration::from_secs_f64(config.script_timeout_secs),
+    )?;
+    let transforms = config.transforms();
+    if let Some(log_path) = &config.audit_log_path {
+        let sha256: String = file_digest(path)?
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        audit::append_event(
+            Path::new(log_path),
+            "gcode_postprocessed",
+            json!({
+                "quote_id": quote_id,
+                "file": path.to_string_lossy(),
+                "transforms": transforms,
+                "bytes_before": bytes_before,
+                "bytes_after": fs::metadata(path)?.len(),
+                "sha256": sha256,
+            }),
+        )?;
+    }
+    Ok(transforms)
+}
+
+/// Post-process a G-code file, or every G-code file of a directory in plate order.
+pub fn process(
+    config: &PostProcessConfig,
+    path: &Path,
+    quote_id: Option<&str>,
+) -> Result<Vec<String>, OrcaError> {
+    let files = if path.is_dir() {
+        gcode_files(path)?
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut transforms = Vec::new();
+    for file in &files {
+        transforms = process_file(config, file, quote_id)?;
+    }
+    Ok(transforms)
+}
+
+/// Configure the post-processing applied to sliced G-code before delivery
+///
+/// Thumbnails are stripped first, then `start_gcode` and `end_gcode` are
+/// inserted around the commands, then `script` runs with the file path as its
+/// last argument. With `audit_log_path`, every processed file is recorded with
+/// the transforms applied and its final size and SHA-256.
+#[pyfunction]
+#[pyo3(signature = (
+    start_gcode=None,
+    end_gcode=None,
+    strip_thumbnails=false,
+    script=Vec::new(),
+    script_timeout_secs=60.0,
+    audit_log_path=None,
+))]
+pub fn create_postprocess_config(
+    start_gcode: Option<String>,
+    end_gcode: Option<String>,
+    strip_thumbnails: bool,
+    script: Vec<String>,
+    script_timeout_secs: f64,
+    audit_log_path: Option<String>,
+) -> PyResult<PostProcessConfig> {
+    panic_boundary::catch(|| {
+        if !(script_timeout_secs > 0.0 && script_timeout_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "script_timeout_secs".to_string(),
+                message: format!(
+                    "{} is not a positive number of seconds",
+                    script_timeout_secs
+                ),
+            }
+            .into());
+        }
+        if script
+            .first()
+            .is_some_and(|program| program.trim().is_empty())
+        {
+            return Err(OrcaError::InvalidConfig {
+                path: "script".to_string(),
+                message: "program is empty".to_string(),
+            }
+            .into());
+        }
+        Ok(PostProcessConfig {
+            start_gcode: start_gcode.filter(|s| !s.trim().is_empty()),
+            end_gcode: end_gcode.filter(|s| !s.trim().is_empty()),
+            strip_thumbnails,
+            script,
+            script_timeout_secs,
+            audit_log_path,
+        })
+    })
+}
+
+/// Apply post-processing to a G-code file or a directory of them, in place
+///
+/// Returns the names of the transforms applied, e.g. `["strip_thumbnails",
+/// "start_gcode", "script:/opt/pp.py"]`. A failing script raises RuntimeError
+/// and leaves the file as the script left it.
+#[pyfunction]
+#[pyo3(signature = (gcode_path, config, quote_id=None))]
+pub fn postprocess_gcode(
+    py: Python<'_>,
+    gcode_path: String,
+    config: PyRef<'_, PostProcessConfig>,
+    quote_id: Option<String>,
+) -> PyResult<Vec<String>> {
+    panic_boundary::catch(|| {
+        let config = config.clone();
+        Ok(py.allow_threads(|| process(&config, Path::new(&gcode_path), quote_id.as_deref()))?)
+    })
+}
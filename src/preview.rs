@@ -0,0 +1,278 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::gcode_cache;
+use crate::gcode_scan::for_each_line;
+use crate::geometry::{model_triangles, Triangle};
+use crate::memory_limits::Budget;
+use crate::panic_boundary;
+use crate::png;
+use crate::OrcaError;
+
+/// Bytes at the start of a G-code file searched for thumbnails; slicers write
+/// them in the header, ahead of the moves.
+const THUMBNAIL_SCAN_BYTES: u64 = 16 * 1024 * 1024;
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const BACKGROUND: u8 = 0xF4;
+const MODEL_COLOR: [f64; 3] = [64.0, 132.0, 230.0];
+/// Share of the model color kept on faces turned away from the light.
+const AMBIENT: f64 = 0.3;
+
+/// A PNG image of a model and where it came from
+pub struct Preview {
+    pub png: Vec<u8>,
+    /// "gcode" for the slicer's embedded thumbnail, "mesh" for a render of the model.
+    pub source: &'static str,
+}
+
+/// The largest PNG thumbnail embedded in a G-code file, as OrcaSlicer and
+/// PrusaSlicer write them: base64 comment lines between `; thumbnail begin WxH
+/// LEN` and `; thumbnail end` (or the `thumbnail_PNG` variants).
+pub fn gcode_thumbnail(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    let reader = File::open(path)?.take(THUMBNAIL_SCAN_BYTES);
+    let mut best: Option<(u64, String)> = None;
+    let mut current: Option<(u64, String)> = None;
+    for_each_line(reader, Budget::GcodeParse, |line| {
+        // Moves and ";TYPE:"-style feature comments are skipped without decoding.
+        let Some(comment) = line.strip_prefix(b"; ") else {
+            return;
+        };
+        let text = String::from_utf8_lossy(comment);
+        let text = text.trim();
+        let begin = text
+            .strip_prefix("thumbnail begin ")
+            .or_else(|| text.strip_prefix("thumbnail_PNG begin "));
+        if let Some(header) = begin {
+            let pixels = header
+                .split_whitespace()
+                .next()
+                .and_then(|size| size.split_once('x'))
+                .and_then(|(w, h)| Some(w.parse::<u64>().ok()? * h.parse::<u64>().ok()?))
+                .unwrap_or(0);
+            current = Some((pixels, String::new()));
+        } else if text == "thumbnail end" || text == "thumbnail_PNG end" {
+            if let Some(done) = current.take() {
+                if best.as_ref().is_none_or(|(pixels, _)| done.0 > *pixels) {
+                    best = Some(done);
+                }
+            }
+        } else if let Some((_, data)) = &mut current {
+            data.push_str(text);
+        }
+    })?;
+    Ok(best
+        .and_then(|(_, data)| STANDARD.decode(data).ok())
+        .filter(|image| image.starts_with(PNG_SIGNATURE)))
+}
+
+/// A G-code file, or the G-code files of a directory in plate order.
+fn gcode_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if path.is_dir() {
+        gcode_cache::gcode_files(path)
+    } else {
+        Ok(vec![path.to_path_buf()])
+    }
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Twice the signed area of the triangle a, b, p on screen.
+fn edge(a: [f64; 3], b: [f64; 3], p: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// A `width` by `height` pixel PNG of the mesh seen from the front right,
+/// above the bed, with flat shading and hidden faces removed.
+pub fn render_mesh(triangles: &[Triangle], width: usize, height: usize) -> Vec<u8> {
+    let (sin_yaw, cos_yaw) = 45f64.to_radians().sin_cos();
+    let (sin_pitch, cos_pitch) = 30f64.to_radians().sin_cos();
+    // Model space to view space: right, up, and depth away from the viewer.
+    let view = |[x, y, z]: [f32; 3]| {
+        let (x, y, z) = (x as f64, y as f64, z as f64);
+        let right = x * cos_yaw - y * sin_yaw;
+        let forward = x * sin_yaw + y * cos_yaw;
+        [
+            right,
+            forward * sin_pitch + z * cos_pitch,
+            forward * cos_pitch - z * sin_pitch,
+        ]
+    };
+    let viewed: Vec<[[f64; 3]; 3]> = triangles.iter().map(|t| t.map(view)).collect();
+
+    let (mut low, mut high) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+    for corner in viewed.iter().flatten() {
+        for axis in 0..2 {
+            low[axis] = low[axis].min(corner[axis]);
+            high[axis] = high[axis].max(corner[axis]);
+        }
+    }
+    // The model fills 90% of whichever side it meets first.
+    let scale = (width as f64 * 0.9 / (high[0] - low[0]).max(f64::EPSILON))
+        .min(height as f64 * 0.9 / (high[1] - low[1]).max(f64::EPSILON));
+    let center = [(low[0] + high[0]) / 2.0, (low[1] + high[1]) / 2.0];
+    let to_pixels = |p: [f64; 3]| {
+        [
+            width as f64 / 2.0 + (p[0] - center[0]) * scale,
+            height as f64 / 2.0 - (p[1] - center[1]) * scale,
+            p[2],
+        ]
+    };
+    // From the upper left, towards the model.
+    let light = {
+        let l: [f64; 3] = [-0.4, 0.6, -0.7];
+        let len = l.iter().map(|v| v * v).sum::<f64>().sqrt();
+        l.map(|v| v / len)
+    };
+
+    let mut depth = vec![f64::INFINITY; width * height];
+    let mut pixels = vec![BACKGROUND; width * height * 3];
+    for triangle in &viewed {
+        let normal = cross(sub(triangle[1], triangle[0]), sub(triangle[2], triangle[0]));
+        let len = normal.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if len == 0.0 {
+            continue;
+        }
+        // Both sides are lit alike, so meshes with flipped facets still look solid.
+        let facing = (normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2]) / len;
+        let shade = AMBIENT + (1.0 - AMBIENT) * facing.abs();
+        let color = MODEL_COLOR.map(|c| (c * shade).round() as u8);
+
+        let [a, b, c] = triangle.map(to_pixels);
+        let area = edge(a, b, [c[0], c[1]]);
+        if area.abs() < f64::EPSILON {
+            continue;
+        }
+        let span = |axis: usize, pixels: usize| {
+            let lo = a[axis].min(b[axis]).min(c[axis]).floor().max(0.0) as usize;
+            let hi = a[axis]
+                .max(b[axis])
+                .max(c[axis])
+                .ceil()
+                .min(pixels as f64 - 1.0);
+            (lo, hi.max(0.0) as usize)
+        };
+        let ((x0, x1), (y0, y1)) = (span(0, width), span(1, height));
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let p = [px as f64 + 0.5, py as f64 + 0.5];
+                let weights = [
+                    edge(b, c, p) / area,
+                    edge(c, a, p) / area,
+                    edge(a, b, p) / area,
+                ];
+                if weights.iter().any(|w| *w < 0.0) {
+                    continue;
+                }
+                let z = weights[0] * a[2] + weights[1] * b[2] + weights[2] * c[2];
+                let i = py * width + px;
+                if z < depth[i] {
+                    depth[i] = z;
+                    pixels[i * 3..i * 3 + 3].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+    png::encode(width, height, 3, &pixels)
+}
+
+fn clamp_side(pixels: u32) -> usize {
+    pixels.clamp(16, 2048) as usize
+}
+
+/// The slicer's thumbnail from `gcode` (a file or a directory of plates) when
+/// it has one, else a `size` pixel render of the model; `None` for models
+/// without readable vertices (STEP) and no thumbnail.
+pub fn preview(model_path: &Path, gcode: Option<&Path>, size: u32) -> io::Result<Option<Preview>> {
+    if let Some(gcode) = gcode {
+        for file in gcode_files(gcode)? {
+            if let Some(png) = gcode_thumbnail(&file)? {
+                return Ok(Some(Preview {
+                    png,
+                    source: "gcode",
+                }));
+            }
+        }
+    }
+    Ok(model_triangles(model_path)?.map(|triangles| Preview {
+        png: render_mesh(&triangles, clamp_side(size), clamp_side(size)),
+        source: "mesh",
+    }))
+}
+
+/// Render a PNG preview of a model, preferring the slicer's G-code thumbnail
+///
+/// `gcode_path` may be a G-code file or a directory of them, e.g. a cached
+/// quote's files. Without a thumbnail the mesh (STL or OBJ) is drawn, `size`
+/// pixels square. Returns None for a STEP model without a thumbnail.
+#[pyfunction]
+#[pyo3(signature = (model_path, gcode_path=None, size=300))]
+pub fn render_model_preview<'py>(
+    py: Python<'py>,
+    model_path: String,
+    gcode_path: Option<String>,
+    size: u32,
+) -> PyResult<Option<&'py PyBytes>> {
+    panic_boundary::catch(|| {
+        let preview = py
+            .allow_threads(|| {
+                preview(
+                    Path::new(&model_path),
+                    gcode_path.as_deref().map(Path::new),
+                    size,
+                )
+            })
+            .map_err(OrcaError::IoError)?;
+        Ok(preview.map(|preview| PyBytes::new(py, &preview.png)))
+    })
+}
+
+/// Render a model's mesh as a PNG, `width` by `height` pixels
+///
+/// The model is drawn from the front right, above the bed, and scaled to fill
+/// the image; each side is kept within 16..2048 pixels. Unlike
+/// `render_model_preview`, no G-code thumbnail is looked for. Raises
+/// ValueError for formats without a mesh, such as STEP.
+#[pyfunction]
+#[pyo3(signature = (model_path, width=300, height=300))]
+pub fn render_preview<'py>(
+    py: Python<'py>,
+    model_path: String,
+    width: u32,
+    height: u32,
+) -> PyResult<&'py PyBytes> {
+    panic_boundary::catch(|| {
+        let png = py.allow_threads(|| {
+            let path = Path::new(&model_path);
+            if !path.exists() {
+                return Err(OrcaError::FileNotFound(model_path.clone()));
+            }
+            match model_triangles(path)? {
+                Some(triangles) => Ok(render_mesh(
+                    &triangles,
+                    clamp_side(width),
+                    clamp_side(height),
+                )),
+                None => Err(OrcaError::InvalidModel(format!(
+                    "{} has no mesh to render",
+                    model_path
+                ))),
+            }
+        })?;
+        Ok(PyBytes::new(py, &png))
+    })
+}
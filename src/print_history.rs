@@ -0,0 +1,358 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::audit;
+use crate::panic_boundary;
+use crate::pipeline::QuoteResult;
+use crate::{OrcaError, SlicingResult};
+
+/// Event name of each line in the history file.
+const EVENT: &str = "print_actuals";
+/// Bounds on a correction, so a few botched prints can't halve or triple a price.
+const MIN_FACTOR: f64 = 0.5;
+const MAX_FACTOR: f64 = 2.0;
+
+/// How far a material's finished prints strayed from the slicer's estimates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct CorrectionFactor {
+    #[pyo3(get)]
+    pub material: String,
+    /// Finished prints the factors are drawn from.
+    #[pyo3(get)]
+    pub samples: usize,
+    /// Actual over estimated print time, summed over the samples.
+    #[pyo3(get)]
+    pub time_factor: f64,
+    /// Actual over estimated filament weight, summed over the samples.
+    #[pyo3(get)]
+    pub filament_factor: f64,
+}
+
+#[pymethods]
+impl CorrectionFactor {
+    fn __str__(&self) -> String {
+        format!(
+            "CorrectionFactor(material={}, time=x{:.3}, filament=x{:.3}, samples={})",
+            self.material, self.time_factor, self.filament_factor, self.samples
+        )
+    }
+}
+
+/// A correction applied to a quote's slicing estimates, with the slicer's own figures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct EstimateCalibration {
+    #[pyo3(get)]
+    pub factor: CorrectionFactor,
+    #[pyo3(get)]
+    pub slicer_print_time_minutes: u32,
+    #[pyo3(get)]
+    pub slicer_filament_grams: f32,
+}
+
+#[pymethods]
+impl EstimateCalibration {
+    fn __str__(&self) -> String {
+        format!(
+            "EstimateCalibration(slicer={}min/{:.1}g, time=x{:.3}, filament=x{:.3})",
+            self.slicer_print_time_minutes,
+            self.slicer_filament_grams,
+            self.factor.time_factor,
+            self.factor.filament_factor
+        )
+    }
+}
+
+/// Slicer estimates next to what finished prints actually took, per material
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct PrintHistory {
+    /// JSON lines file, one `print_actuals` record per finished print.
+    #[pyo3(get)]
+    pub path: String,
+    /// Most recent prints of a material that its factors are drawn from.
+    #[pyo3(get)]
+    pub window: usize,
+    /// Fewer finished prints than this and the material is not corrected.
+    #[pyo3(get)]
+    pub min_samples: usize,
+}
+
+#[derive(Deserialize)]
+struct Actuals {
+    event: String,
+    material: String,
+    estimated_minutes: f64,
+    estimated_grams: f64,
+    actual_minutes: f64,
+    actual_grams: f64,
+}
+
+/// Sum of actuals over sum of estimates, ignoring prints with no estimate.
+fn ratio(pairs: impl Iterator<Item = (f64, f64)>) -> f64 {
+    let (estimated, actual) = pairs
+        .filter(|(estimated, _)| *estimated > 0.0)
+        .fold((0.0, 0.0), |(e, a), (estimated, actual)| {
+            (e + estimated, a + actual)
+        });
+    if estimated > 0.0 {
+        (actual / estimated).clamp(MIN_FACTOR, MAX_FACTOR)
+    } else {
+        1.0
+    }
+}
+
+impl PrintHistory {
+    fn records(&self) -> Result<Vec<Actuals>, OrcaError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        // A line cut short by a crash or written by hand is left out.
+        Ok(text
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Actuals>(line).ok())
+            .filter(|record| record.event == EVENT)
+            .collect())
+    }
+
+    /// Append a finished print's actual time and filament next to its estimates.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &self,
+        material: &str,
+        estimated_minutes: f64,
+        estimated_grams: f64,
+        actual_minutes: f64,
+        actual_grams: f64,
+        quote_id: Option<&str>,
+    ) -> Result<(), OrcaError> {
+        for (name, value) in [
+            ("estimated_minutes", estimated_minutes),
+            ("estimated_grams", estimated_grams),
+            ("actual_minutes", actual_minutes),
+            ("actual_grams", actual_grams),
+        ] {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(OrcaError::InvalidConfig {
+                    path: name.to_string(),
+                    message: format!("{} is not a non-negative number", value),
+                });
+            }
+        }
+        audit::append_event(
+            Path::new(&self.path),
+            EVENT,
+            json!({
+                "quote_id": quote_id,
+                "material": material.trim().to_uppercase(),
+                "estimated_minutes": estimated_minutes,
+                "estimated_grams": estimated_grams,
+                "actual_minutes": actual_minutes,
+                "actual_grams": actual_grams,
+            }),
+        )?;
+        Ok(())
+    }
+
+    fn factor_from(&self, records: &[Actuals], material: &str) -> Option<CorrectionFactor> {
+        let recent: Vec<&Actuals> = records
+            .iter()
+            .rev()
+            .filter(|record| record.material.eq_ignore_ascii_case(material))
+            .take(self.window)
+            .collect();
+        if recent.is_empty() || recent.len() < self.min_samples {
+            return None;
+        }
+        Some(CorrectionFactor {
+            material: material.to_uppercase(),
+            samples: recent.len(),
+            time_factor: ratio(
+                recent
+                    .iter()
+                    .map(|r| (r.estimated_minutes, r.actual_minutes)),
+            ),
+            filament_factor: ratio(recent.iter().map(|r| (r.estimated_grams, r.actual_grams))),
+        })
+    }
+
+    /// The material's correction, or `None` with too few finished prints.
+    pub fn factor(&self, material: &str) -> Result<Option<CorrectionFactor>, OrcaError> {
+        Ok(self.factor_from(&self.records()?, material))
+    }
+
+    /// Corrections for every material with enough finished prints, by name.
+    pub fn factors(&self) -> Result<Vec<CorrectionFactor>, OrcaError> {
+        let records = self.records()?;
+        let mut materials: Vec<String> = records
+            .iter()
+            .map(|record| record.material.to_uppercase())
+            .collect();
+        materials.sort();
+        materials.dedup();
+        Ok(materials
+            .iter()
+            .filter_map(|material| self.factor_from(&records, material))
+            .collect())
+    }
+
+    /// Scale `slicing`'s time and filament by the material's correction; `None`
+    /// leaves it as the slicer estimated. An unreadable history only warns.
+    pub fn calibrate(
+        &self,
+        material: &str,
+        slicing: &mut SlicingResult,
+    ) -> Option<EstimateCalibration> {
+        let factor = self.factor(material).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "could not read print history");
+            None
+        })?;
+        let calibration = EstimateCalibration {
+            slicer_print_time_minutes: slicing.print_time_minutes,
+            slicer_filament_grams: slicing.filament_weight_grams,
+            factor,
+        };
+        slicing.print_time_minutes =
+            (slicing.print_time_minutes as f64 * calibration.factor.time_factor).round() as u32;
+        slicing.filament_weight_grams =
+            (slicing.filament_weight_grams as f64 * calibration.factor.filament_factor) as f32;
+        Some(calibration)
+    }
+}
+
+#[pymethods]
+impl PrintHistory {
+    /// Record a finished print's actual time and filament next to the estimates
+    #[pyo3(
+        name = "record",
+        signature = (material, estimated_minutes, estimated_grams, actual_minutes, actual_grams, quote_id=None)
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_record(
+        &self,
+        material: &str,
+        estimated_minutes: f64,
+        estimated_grams: f64,
+        actual_minutes: f64,
+        actual_grams: f64,
+        quote_id: Option<&str>,
+    ) -> PyResult<()> {
+        panic_boundary::catch(|| {
+            Ok(self.record(
+                material,
+                estimated_minutes,
+                estimated_grams,
+                actual_minutes,
+                actual_grams,
+                quote_id,
+            )?)
+        })
+    }
+
+    /// Record how a quoted job actually printed
+    ///
+    /// The quote's estimates are the slicer's own, from before any correction,
+    /// so corrections don't compound.
+    #[pyo3(name = "record_quote", signature = (quote, actual_minutes, actual_grams, quote_id=None))]
+    fn py_record_quote(
+        &self,
+        quote: PyRef<'_, QuoteResult>,
+        actual_minutes: f64,
+        actual_grams: f64,
+        quote_id: Option<&str>,
+    ) -> PyResult<()> {
+        panic_boundary::catch(|| {
+            let (minutes, grams) = quote.calibration.as_ref().map_or(
+                (
+                    quote.slicing.print_time_minutes,
+                    quote.slicing.filament_weight_grams,
+                ),
+                |c| (c.slicer_print_time_minutes, c.slicer_filament_grams),
+            );
+            Ok(self.record(
+                &quote.material,
+                minutes as f64,
+                grams as f64,
+                actual_minutes,
+                actual_grams,
+                quote_id,
+            )?)
+        })
+    }
+
+    /// The material's correction, or None with fewer than `min_samples` prints
+    #[pyo3(name = "factor")]
+    fn py_factor(&self, material: &str) -> PyResult<Option<CorrectionFactor>> {
+        Ok(self.factor(material)?)
+    }
+
+    /// Corrections for every material with enough finished prints
+    #[pyo3(name = "factors")]
+    fn py_factors(&self) -> PyResult<Vec<CorrectionFactor>> {
+        Ok(self.factors()?)
+    }
+
+    /// Apply the material's correction to a slicing estimate
+    ///
+    /// Returns the corrected estimate and the calibration applied, or the
+    /// estimate unchanged and None when the material has too few prints.
+    #[pyo3(name = "calibrate")]
+    fn py_calibrate(
+        &self,
+        slicing: SlicingResult,
+        material: &str,
+    ) -> (SlicingResult, Option<EstimateCalibration>) {
+        let mut slicing = slicing;
+        let calibration = self.calibrate(material, &mut slicing);
+        (slicing, calibration)
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "PrintHistory(path={}, window={}, min_samples={})",
+            self.path, self.window, self.min_samples
+        )
+    }
+}
+
+/// Open a history of slicer estimates against finished prints
+///
+/// Each material's correction is actual over estimated time and filament,
+/// summed over its last `window` finished prints, and bounded to x0.5..x2.
+/// Materials with fewer than `min_samples` prints are left uncorrected.
+#[pyfunction]
+#[pyo3(signature = (path, window=50, min_samples=5))]
+pub fn create_print_history(
+    path: String,
+    window: usize,
+    min_samples: usize,
+) -> PyResult<PrintHistory> {
+    panic_boundary::catch(|| {
+        if window == 0 {
+            return Err(OrcaError::InvalidConfig {
+                path: "window".to_string(),
+                message: "must be at least 1".to_string(),
+            }
+            .into());
+        }
+        if min_samples > window {
+            return Err(OrcaError::InvalidConfig {
+                path: "min_samples".to_string(),
+                message: format!("{} is more than the window of {}", min_samples, window),
+            }
+            .into());
+        }
+        Ok(PrintHistory {
+            path,
+            window,
+            min_samples,
+        })
+    })
+}
@@ -0,0 +1,299 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict};
+use sanitize_filename::sanitize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::panic_boundary;
+use crate::profiles::read_settings;
+use crate::workspace::generate_job_id;
+use crate::OrcaError;
+
+/// Customer-facing print options, applied on top of a process profile.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOverrides {
+    /// mm
+    pub layer_height: Option<f64>,
+    /// 0–100
+    pub infill_percent: Option<f64>,
+    pub supports: Option<bool>,
+    /// "normal" or "tree"; Orca's "(auto)" suffix is added when missing.
+    pub support_type: Option<String>,
+    /// Raw Orca settings written as given, e.g. `wall_loops = "3"`.
+    pub settings: BTreeMap<String, String>,
+}
+
+impl ProcessOverrides {
+    /// Read overrides from a Python dict; unrecognised keys are raw Orca settings.
+    pub fn from_dict(overrides: &PyDict) -> PyResult<Self> {
+        let mut parsed = ProcessOverrides::default();
+        for (key, value) in overrides.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "layer_height" => parsed.layer_height = Some(value.extract()?),
+                "infill_percent" => parsed.infill_percent = Some(value.extract()?),
+                "supports" => parsed.supports = Some(value.extract()?),
+                "support_type" => parsed.support_type = Some(value.extract()?),
+                _ => {
+                    let raw = if value.is_instance_of::<PyBool>() {
+                        if value.extract::<bool>()? { "1" } else { "0" }.to_string()
+                    } else {
+                        value.str()?.to_string()
+                    };
+                    parsed.settings.insert(key, raw);
+                }
+            }
+        }
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layer_height.is_none()
+            && self.infill_percent.is_none()
+            && self.supports.is_none()
+            && self.support_type.is_none()
+            && self.settings.is_empty()
+    }
+
+    fn validate(&self) -> Result<(), OrcaError> {
+        if let Some(height) = self.layer_height {
+            if !(height > 0.0 && height <= 1.0) {
+                return Err(OrcaError::InvalidOverride(format!(
+                    "layer_height must be between 0 and 1 mm, got {}",
+                    height
+                )));
+            }
+        }
+        if let Some(infill) = self.infill_percent {
+            if !(0.0..=100.0).contains(&infill) {
+                return Err(OrcaError::InvalidOverride(format!(
+                    "infill_percent must be between 0 and 100, got {}",
+                    infill
+                )));
+            }
+        }
+        if let Some(support_type) = &self.support_type {
+            let style = support_type
+                .trim_end_matches("(auto)")
+                .trim_end_matches("(manual)");
+            if style != "normal" && style != "tree" {
+                return Err(OrcaError::InvalidOverride(format!(
+                    "support_type must be 'normal' or 'tree', got '{}'",
+                    support_type
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Orca key/value pairs for these overrides, in the string form profiles use.
+    fn orca_settings(&self) -> Vec<(String, String)> {
+        let mut settings = Vec::new();
+        if let Some(height) = self.layer_height {
+            settings.push(("layer_height".to_string(), format_number(height)));
+        }
+        if let Some(infill) = self.infill_percent {
+            settings.push((
+                "sparse_infill_density".to_string(),
+                format!("{}%", format_number(infill)),
+            ));
+        }
+        if let Some(supports) = self.supports {
+            let enabled = if supports { "1" } else { "0" };
+            settings.push(("enable_support".to_string(), enabled.to_string()));
+        }
+        if let Some(support_type) = &self.support_type {
+            let value = if support_type.contains('(') {
+                support_type.clone()
+            } else {
+                format!("{}(auto)", support_type)
+            };
+            settings.push(("support_type".to_string(), value));
+        }
+        settings.extend(self.settings.iter().map(|(k, v)| (k.clone(), v.clone())));
+        settings
+    }
+
+    /// Short suffix for the derived profile name, e.g. "0.28mm 20% supports".
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(height) = self.layer_height {
+            parts.push(format!("{}mm", format_number(height)));
+        }
+        if let Some(infill) = self.infill_percent {
+            parts.push(format!("{}%", format_number(infill)));
+        }
+        match self.supports {
+            Some(true) => parts.push("supports".to_string()),
+            Some(false) => parts.push("no supports".to_string()),
+            None => {}
+        }
+        if parts.is_empty() {
+            "custom".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+/// Print options a customer picked, as applied to the slice
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct PrintOptions {
+    /// mm
+    #[pyo3(get)]
+    pub layer_height: Option<f64>,
+    /// 0–100
+    #[pyo3(get)]
+    pub infill_percent: Option<f64>,
+    #[pyo3(get)]
+    pub supports: Option<bool>,
+    /// Filament color; it does not change the slice, but must be one the
+    /// material catalog stocks when the catalog lists colors.
+    #[pyo3(get)]
+    pub color: Option<String>,
+}
+
+impl PrintOptions {
+    /// Read options from a Python dict. Unlike `ProcessOverrides`, raw Orca
+    /// settings are refused: customers choose from these four only.
+    pub fn from_dict(options: &PyDict) -> PyResult<Self> {
+        let mut parsed = PrintOptions::default();
+        for (key, value) in options.iter() {
+            if value.is_none() {
+                continue;
+            }
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "layer_height" => parsed.layer_height = Some(value.extract()?),
+                "infill_percent" => parsed.infill_percent = Some(value.extract()?),
+                "supports" => parsed.supports = Some(value.extract()?),
+                "color" => {
+                    let color: String = value.extract()?;
+                    parsed.color = Some(color.trim().to_string()).filter(|c| !c.is_empty());
+                }
+                _ => {
+                    return Err(OrcaError::InvalidOverride(format!(
+                        "unknown print option '{}'",
+                        key
+                    ))
+                    .into())
+                }
+            }
+        }
+        parsed.overrides().validate()?;
+        Ok(parsed)
+    }
+
+    /// The process settings these options change; empty when only a color was picked.
+    pub fn overrides(&self) -> ProcessOverrides {
+        ProcessOverrides {
+            layer_height: self.layer_height,
+            infill_percent: self.infill_percent,
+            supports: self.supports,
+            ..ProcessOverrides::default()
+        }
+    }
+}
+
+#[pymethods]
+impl PrintOptions {
+    fn __str__(&self) -> String {
+        let overrides = self.overrides();
+        let mut parts: Vec<String> = self.color.iter().cloned().collect();
+        if !overrides.is_empty() {
+            parts.insert(0, overrides.describe());
+        }
+        if parts.is_empty() {
+            parts.push("profile defaults".to_string());
+        }
+        format!("PrintOptions({})", parts.join(", "))
+    }
+}
+
+/// Format without trailing zeros, as Orca writes numbers ("0.2", "15").
+fn format_number(value: f64) -> String {
+    let formatted = format!("{:.4}", value);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Replace a setting, keeping the array shape if the profile stores it per extruder.
+fn set_setting(settings: &mut Map<String, Value>, key: String, value: String) {
+    let new_value = match settings.get(&key) {
+        Some(Value::Array(items)) if !items.is_empty() => {
+            Value::Array(vec![Value::String(value); items.len()])
+        }
+        _ => Value::String(value),
+    };
+    settings.insert(key, new_value);
+}
+
+/// Write a process profile derived from `base` with the overrides applied.
+///
+/// The copy keeps `inherits`, so the slicer resolves it exactly like the base; it is
+/// renamed and marked as a user preset so it never shadows the original.
+pub fn write_process_override(
+    base: &Path,
+    overrides: &ProcessOverrides,
+    output_dir: &Path,
+) -> Result<PathBuf, OrcaError> {
+    let mut settings = read_settings(base)?;
+    match settings.get("type").and_then(Value::as_str) {
+        None | Some("process") => {}
+        Some(other) => {
+            return Err(OrcaError::InvalidProfile {
+                path: base.display().to_string(),
+                message: format!("expected a process profile, found type '{}'", other),
+            })
+        }
+    }
+
+    let base_name = settings
+        .get("name")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| base.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "process".to_string());
+    let name = format!("{} ({})", base_name, overrides.describe());
+
+    for (key, value) in overrides.orca_settings() {
+        set_setting(&mut settings, key, value);
+    }
+    settings.insert("type".to_string(), Value::String("process".to_string()));
+    settings.insert("name".to_string(), Value::String(name));
+    settings.insert("from".to_string(), Value::String("User".to_string()));
+    settings.remove("setting_id");
+
+    fs::create_dir_all(output_dir)?;
+    let file_name = format!("{}-{}.json", sanitize(&base_name), generate_job_id());
+    let path = output_dir.join(file_name);
+    let contents = serde_json::to_string_pretty(&Value::Object(settings))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+/// Write a derived process profile with layer height, infill and support overrides applied
+#[pyfunction]
+#[pyo3(signature = (base_profile, overrides, output_dir=None))]
+pub fn generate_process_override(
+    base_profile: String,
+    overrides: &PyDict,
+    output_dir: Option<String>,
+) -> PyResult<String> {
+    panic_boundary::catch(|| {
+        let overrides = ProcessOverrides::from_dict(overrides)?;
+        let output_dir = output_dir
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let path = write_process_override(Path::new(&base_profile), &overrides, &output_dir)?;
+        Ok(path.to_string_lossy().into_owned())
+    })
+}
@@ -0,0 +1,257 @@
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::panic_boundary;
+use crate::profiles::collect_json_files;
+use crate::OrcaError;
+
+/// Manifest OrcaSlicer writes at the root of every exported bundle.
+const BUNDLE_MANIFEST: &str = "bundle_structure.json";
+
+/// Profiles larger than this are not real presets; refuse them rather than inflate them.
+const MAX_PROFILE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Bundle folder names, paired with the profile type and directory used on disk.
+const BUNDLE_FOLDERS: &[(&str, &str)] = &[
+    ("printer", "machine"),
+    ("process", "process"),
+    ("filament", "filament"),
+];
+
+/// Profiles written to (or skipped in) the profiles directory by a bundle import
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct BundleImport {
+    /// Bundle type from the manifest, e.g. "printer config bundle".
+    #[pyo3(get)]
+    pub bundle_type: Option<String>,
+    #[pyo3(get)]
+    pub imported: Vec<String>,
+    /// Existing profiles left untouched because `overwrite` was not set.
+    #[pyo3(get)]
+    pub skipped: Vec<String>,
+}
+
+#[pymethods]
+impl BundleImport {
+    fn __str__(&self) -> String {
+        format!(
+            "BundleImport(type={:?}, imported={}, skipped={})",
+            self.bundle_type,
+            self.imported.len(),
+            self.skipped.len()
+        )
+    }
+}
+
+fn bundle_error(path: &Path, message: impl Into<String>) -> OrcaError {
+    OrcaError::InvalidBundle {
+        path: path.display().to_string(),
+        message: message.into(),
+    }
+}
+
+/// Profile directory for an entry: the JSON `type` wins, then the bundle folder.
+fn profile_dir_for(entry_path: &Path, settings: &Map<String, Value>) -> Option<&'static str> {
+    match settings.get("type").and_then(Value::as_str) {
+        Some("machine") | Some("printer") => return Some("machine"),
+        Some("process") => return Some("process"),
+        Some("filament") => return Some("filament"),
+        _ => {}
+    }
+    entry_path
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .find_map(|folder| {
+            BUNDLE_FOLDERS
+                .iter()
+                .find(|(name, _)| *name == folder)
+                .map(|(_, dir)| *dir)
+        })
+}
+
+/// Unpack an `.orca_printer` / `.orca_filament` bundle into `profiles_dir`.
+///
+/// Every profile is parsed and placed before anything is written, so a bad bundle
+/// leaves the directory untouched.
+pub fn import_bundle(
+    bundle: &Path,
+    profiles_dir: &Path,
+    overwrite: bool,
+) -> Result<BundleImport, OrcaError> {
+    if !bundle.is_file() {
+        return Err(OrcaError::FileNotFound(bundle.display().to_string()));
+    }
+    let mut archive =
+        ZipArchive::new(File::open(bundle)?).map_err(|e| bundle_error(bundle, e.to_string()))?;
+
+    let mut bundle_type = None;
+    let mut profiles: Vec<(PathBuf, String)> = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| bundle_error(bundle, e.to_string()))?;
+        if entry.is_dir() {
+            continue;
+        }
+        // Reject absolute paths and `..` so nothing lands outside the profiles directory.
+        let entry_path = entry
+            .enclosed_name()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| bundle_error(bundle, format!("unsafe entry path '{}'", entry.name())))?;
+        if entry_path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        if entry.size() > MAX_PROFILE_BYTES {
+            return Err(bundle_error(
+                bundle,
+                format!(
+                    "{} is larger than {} bytes",
+                    entry_path.display(),
+                    MAX_PROFILE_BYTES
+                ),
+            ));
+        }
+
+        let mut contents = String::new();
+        entry
+            .by_ref()
+            .take(MAX_PROFILE_BYTES)
+            .read_to_string(&mut contents)?;
+        let settings = match serde_json::from_str::<Value>(&contents) {
+            Ok(Value::Object(settings)) => settings,
+            _ => {
+                return Err(bundle_error(
+                    bundle,
+                    format!("{} is not a JSON object", entry_path.display()),
+                ))
+            }
+        };
+
+        if entry_path == Path::new(BUNDLE_MANIFEST) {
+            bundle_type = settings
+                .get("bundle_type")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            continue;
+        }
+        let dir = profile_dir_for(&entry_path, &settings).ok_or_else(|| {
+            bundle_error(
+                bundle,
+                format!("cannot tell the profile type of {}", entry_path.display()),
+            )
+        })?;
+        let file_name = entry_path
+            .file_name()
+            .ok_or_else(|| bundle_error(bundle, "entry without a file name"))?;
+        profiles.push((profiles_dir.join(dir).join(file_name), contents));
+    }
+
+    if profiles.is_empty() {
+        return Err(bundle_error(bundle, "bundle contains no profiles"));
+    }
+
+    let mut result = BundleImport {
+        bundle_type,
+        imported: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for (target, contents) in profiles {
+        let display = target.to_string_lossy().into_owned();
+        if target.exists() && !overwrite {
+            result.skipped.push(display);
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, contents)?;
+        result.imported.push(display);
+    }
+    Ok(result)
+}
+
+/// Write every machine, process and filament profile under `profiles_dir` to a bundle
+/// laid out the way OrcaSlicer exports printer presets. Returns the number of profiles.
+pub fn export_bundle(profiles_dir: &Path, output: &Path, name: &str) -> Result<usize, OrcaError> {
+    let mut writer = ZipWriter::new(File::create(output)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let zip_error = |e: zip::result::ZipError| bundle_error(output, e.to_string());
+
+    let mut manifest = Map::new();
+    let mut count = 0;
+    for (folder, dir) in BUNDLE_FOLDERS {
+        let root = profiles_dir.join(dir);
+        let mut files = Vec::new();
+        collect_json_files(&root, &mut files);
+        files.sort();
+
+        let mut entries = Vec::new();
+        for file in files {
+            let relative = file.strip_prefix(&root).unwrap_or(&file);
+            let entry_name = format!(
+                "{}/{}",
+                folder,
+                relative.to_string_lossy().replace('\\', "/")
+            );
+            writer
+                .start_file(entry_name.as_str(), options)
+                .map_err(zip_error)?;
+            writer.write_all(&fs::read(&file)?)?;
+            entries.push(Value::String(entry_name));
+            count += 1;
+        }
+        manifest.insert(format!("{}_config", folder), Value::Array(entries));
+    }
+
+    manifest.insert("bundle_type".to_string(), json!("printer config bundle"));
+    manifest.insert("printer_preset_name".to_string(), json!(name));
+    manifest.insert("version".to_string(), json!(env!("CARGO_PKG_VERSION")));
+    writer
+        .start_file(BUNDLE_MANIFEST, options)
+        .map_err(zip_error)?;
+    let manifest = serde_json::to_vec_pretty(&Value::Object(manifest))
+        .map_err(|e| bundle_error(output, e.to_string()))?;
+    writer.write_all(&manifest)?;
+    writer.finish().map_err(zip_error)?;
+    Ok(count)
+}
+
+/// Import an OrcaSlicer profile bundle, placing each profile under its type's directory
+#[pyfunction]
+#[pyo3(signature = (bundle_path, profiles_dir, overwrite=false))]
+pub fn import_profile_bundle(
+    bundle_path: String,
+    profiles_dir: String,
+    overwrite: bool,
+) -> PyResult<BundleImport> {
+    panic_boundary::catch(|| {
+        Ok(import_bundle(
+            Path::new(&bundle_path),
+            Path::new(&profiles_dir),
+            overwrite,
+        )?)
+    })
+}
+
+/// Export the profile set as an OrcaSlicer bundle; returns the number of profiles written
+#[pyfunction]
+#[pyo3(signature = (profiles_dir, output_path, name="quote-machine"))]
+pub fn export_profile_bundle(
+    profiles_dir: String,
+    output_path: String,
+    name: &str,
+) -> PyResult<usize> {
+    panic_boundary::catch(|| {
+        Ok(export_bundle(
+            Path::new(&profiles_dir),
+            Path::new(&output_path),
+            name,
+        )?)
+    })
+}
@@ -0,0 +1,297 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::panic_boundary;
+use crate::profile_discovery::{
+    discover_machine_listings, process_listings, MachineListing, ProcessListing,
+};
+use crate::profile_mapping::{
+    filament_profiles, resolve_filament_among, FilamentResolution, ProfileMapping,
+};
+use crate::profile_selection::{select_profiles, ProfilePaths, ProfileRequest};
+use crate::profiles::{index_profiles, resolve_profile_file, Profile};
+use crate::OrcaError;
+
+/// Parsed state of the profile tree; each part is filled on first use.
+#[derive(Default)]
+struct Snapshot {
+    index: Option<Arc<HashMap<String, PathBuf>>>,
+    resolved: HashMap<PathBuf, Profile>,
+    machines: Option<Arc<Vec<MachineListing>>>,
+    processes: Option<Arc<Vec<ProcessListing>>>,
+    filaments: Option<Arc<Vec<Profile>>>,
+}
+
+struct CacheState {
+    profiles_dir: PathBuf,
+    snapshot: Mutex<Snapshot>,
+    invalidations: AtomicU64,
+}
+
+impl CacheState {
+    fn lock(&self) -> MutexGuard<'_, Snapshot> {
+        self.snapshot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn invalidate(&self) {
+        *self.lock() = Snapshot::default();
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Return a cached part of the snapshot, computing it without holding the lock.
+    fn get_or_load<T>(
+        &self,
+        slot: impl Fn(&mut Snapshot) -> &mut Option<Arc<T>>,
+        load: impl FnOnce() -> T,
+    ) -> Arc<T> {
+        if let Some(value) = slot(&mut self.lock()) {
+            return value.clone();
+        }
+        let generation = self.invalidations.load(Ordering::Relaxed);
+        let value = Arc::new(load());
+        // A change seen while loading may have been missed by the scan; serve the
+        // result but leave it uncached.
+        if self.invalidations.load(Ordering::Relaxed) == generation {
+            *slot(&mut self.lock()) = Some(value.clone());
+        }
+        value
+    }
+
+    fn filament_dir(&self) -> PathBuf {
+        self.profiles_dir.join("filament")
+    }
+}
+
+/// In-memory cache of a profile tree, cleared whenever a file below it changes
+#[derive(Clone)]
+#[pyclass]
+pub struct ProfileCache {
+    #[pyo3(get)]
+    pub profiles_dir: String,
+    state: Arc<CacheState>,
+    // Kept alive for as long as any handle to the cache exists.
+    watcher: Option<Arc<Mutex<RecommendedWatcher>>>,
+}
+
+impl ProfileCache {
+    /// Create a cache for `profiles_dir`; with `watch`, a filesystem watcher clears it on change.
+    pub fn new(profiles_dir: &Path, watch: bool) -> Result<Self, OrcaError> {
+        let state = Arc::new(CacheState {
+            profiles_dir: profiles_dir.to_path_buf(),
+            snapshot: Mutex::new(Snapshot::default()),
+            invalidations: AtomicU64::new(0),
+        });
+
+        let watcher = if watch {
+            let handler_state = Arc::clone(&state);
+            let mut watcher =
+                notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                    match event {
+                        Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+                        // Errors may mean dropped events, so treat them as a change too.
+                        _ => handler_state.invalidate(),
+                    }
+                })
+                .map_err(watch_error)?;
+            watcher
+                .watch(profiles_dir, RecursiveMode::Recursive)
+                .map_err(watch_error)?;
+            Some(Arc::new(Mutex::new(watcher)))
+        } else {
+            None
+        };
+
+        Ok(ProfileCache {
+            profiles_dir: profiles_dir.to_string_lossy().into_owned(),
+            state,
+            watcher,
+        })
+    }
+
+    fn index(&self) -> Arc<HashMap<String, PathBuf>> {
+        let profiles_dir = &self.state.profiles_dir;
+        self.state.get_or_load(
+            |snapshot| &mut snapshot.index,
+            || index_profiles(std::slice::from_ref(profiles_dir)),
+        )
+    }
+
+    pub fn machine_listings(&self) -> Arc<Vec<MachineListing>> {
+        self.state.get_or_load(
+            |snapshot| &mut snapshot.machines,
+            || discover_machine_listings(&self.state.profiles_dir),
+        )
+    }
+
+    pub fn process_listings(&self) -> Arc<Vec<ProcessListing>> {
+        let machines = self.machine_listings();
+        self.state.get_or_load(
+            |snapshot| &mut snapshot.processes,
+            || process_listings(&self.state.profiles_dir, &machines),
+        )
+    }
+
+    pub fn filament_profiles(&self) -> Arc<Vec<Profile>> {
+        self.state.get_or_load(
+            |snapshot| &mut snapshot.filaments,
+            || filament_profiles(&self.state.filament_dir()),
+        )
+    }
+
+    /// A profile merged with its ancestors, parsed at most once per change to the tree.
+    pub fn resolved_profile(&self, path: &Path) -> Result<Profile, OrcaError> {
+        if let Some(profile) = self.state.lock().resolved.get(path) {
+            return Ok(profile.clone());
+        }
+        let generation = self.state.invalidations.load(Ordering::Relaxed);
+        let profile = resolve_profile_file(path, &self.index())?;
+        if self.state.invalidations.load(Ordering::Relaxed) == generation {
+            self.state
+                .lock()
+                .resolved
+                .insert(path.to_path_buf(), profile.clone());
+        }
+        Ok(profile)
+    }
+}
+
+fn watch_error(err: notify::Error) -> OrcaError {
+    OrcaError::IoError(std::io::Error::other(format!(
+        "cannot watch profiles: {}",
+        err
+    )))
+}
+
+#[pymethods]
+impl ProfileCache {
+    /// Resolved profile for a file, merged with the profiles it inherits from
+    fn profile(&self, path: String) -> PyResult<Profile> {
+        Ok(self.resolved_profile(Path::new(&path))?)
+    }
+
+    /// Cached equivalent of `discover_machines`
+    fn machines(&self) -> Vec<MachineListing> {
+        self.machine_listings().to_vec()
+    }
+
+    /// Cached equivalent of `discover_processes`
+    #[pyo3(signature = (nozzle=None))]
+    fn processes(&self, nozzle: Option<f64>) -> Vec<ProcessListing> {
+        self.process_listings()
+            .iter()
+            .filter(|process| process.suits_nozzle(nozzle))
+            .cloned()
+            .collect()
+    }
+
+    /// File stems of every filament profile, e.g. "generic_tpu" for generic_tpu.json
+    fn filament_names(&self) -> Vec<String> {
+        self.filament_profiles()
+            .iter()
+            .filter_map(|profile| {
+                Path::new(&profile.path)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .collect()
+    }
+
+    /// Cached equivalent of `resolve_filament_profile` for `<profiles_dir>/filament`
+    #[pyo3(signature = (material, mapping_path=None))]
+    fn resolve_filament(
+        &self,
+        material: String,
+        mapping_path: Option<String>,
+    ) -> PyResult<FilamentResolution> {
+        let mapping = ProfileMapping::load_optional(mapping_path.as_deref().map(Path::new))?;
+        Ok(resolve_filament_among(
+            &self.state.filament_dir(),
+            &material,
+            &mapping,
+            Some(&self.filament_profiles()),
+        )?)
+    }
+
+    /// Process profile path mapped to a material in the mapping file, if any
+    #[pyo3(signature = (material, mapping_path=None))]
+    fn mapped_process(
+        &self,
+        material: String,
+        mapping_path: Option<String>,
+    ) -> PyResult<Option<String>> {
+        let mapping = ProfileMapping::load_optional(mapping_path.as_deref().map(Path::new))?;
+        Ok(mapping.process_for(&material).map(|file| {
+            self.state
+                .profiles_dir
+                .join("process")
+                .join(file)
+                .to_string_lossy()
+                .into_owned()
+        }))
+    }
+
+    /// Cached equivalent of `resolve_profile_paths`
+    #[pyo3(signature = (material, nozzle=None, machine=None, process=None, mapping_path=None))]
+    fn resolve_profile_paths(
+        &self,
+        material: String,
+        nozzle: Option<f64>,
+        machine: Option<String>,
+        process: Option<String>,
+        mapping_path: Option<String>,
+    ) -> PyResult<ProfilePaths> {
+        let mapping = ProfileMapping::load_optional(mapping_path.as_deref().map(Path::new))?;
+        Ok(select_profiles(
+            &self.machine_listings(),
+            &self.process_listings(),
+            &self.filament_profiles(),
+            &Path::new(&self.profiles_dir).join("filament"),
+            &ProfileRequest {
+                material: &material,
+                nozzle,
+                machine: machine.as_deref(),
+                process: process.as_deref(),
+                mapping: &mapping,
+            },
+        )?)
+    }
+
+    /// Drop everything cached; the next lookup rereads the profile tree
+    fn invalidate(&self) {
+        self.state.invalidate();
+    }
+
+    /// Whether a filesystem watcher keeps the cache fresh
+    #[getter]
+    fn is_watching(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Number of times the cache has been cleared
+    #[getter]
+    fn invalidations(&self) -> u64 {
+        self.state.invalidations.load(Ordering::Relaxed)
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "ProfileCache(dir={}, watching={}, invalidations={})",
+            self.profiles_dir,
+            self.watcher.is_some(),
+            self.invalidations()
+        )
+    }
+}
+
+/// Create a profile cache, watching the directory for changes unless `watch` is false
+#[pyfunction]
+#[pyo3(signature = (profiles_dir, watch=true))]
+pub fn create_profile_cache(profiles_dir: String, watch: bool) -> PyResult<ProfileCache> {
+    panic_boundary::catch(|| Ok(ProfileCache::new(Path::new(&profiles_dir), watch)?))
+}
@@ -0,0 +1,276 @@
+use pyo3::prelude::*;
+use std::path::{Path, PathBuf};
+
+use crate::panic_boundary;
+use crate::profile_discovery::{nozzle_in_name, same_nozzle};
+use crate::profile_lint::LintIssue;
+use crate::profiles::{index_profiles, load_profile_file, resolve_profile_file, Profile};
+use crate::OrcaError;
+
+/// Layers thicker than this share of the nozzle diameter extrude poorly.
+const MAX_LAYER_TO_NOZZLE: f64 = 0.8;
+
+/// Whether a machine, filament and process profile can be sliced together
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct CompatibilityReport {
+    #[pyo3(get)]
+    pub machine: String,
+    #[pyo3(get)]
+    pub filament: String,
+    #[pyo3(get)]
+    pub process: String,
+    #[pyo3(get)]
+    pub issues: Vec<LintIssue>,
+}
+
+#[pymethods]
+impl CompatibilityReport {
+    /// True when no errors were found; warnings do not block slicing.
+    #[getter]
+    pub fn is_compatible(&self) -> bool {
+        self.issues.iter().all(|issue| issue.severity != "error")
+    }
+
+    /// One line per issue, suitable for an error message
+    pub fn summary(&self) -> String {
+        self.issues
+            .iter()
+            .map(|issue| format!("{} [{}]: {}", issue.severity, issue.code, issue.message))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "CompatibilityReport(machine={}, filament={}, process={}, issues={})",
+            self.machine,
+            self.filament,
+            self.process,
+            self.issues.len()
+        )
+    }
+}
+
+struct Checker<'a> {
+    machine: &'a Profile,
+    issues: Vec<LintIssue>,
+}
+
+impl Checker<'_> {
+    fn push(&mut self, profile: &Profile, severity: &str, code: &str, message: String) {
+        self.issues.push(LintIssue {
+            path: profile.path.clone(),
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message,
+        });
+    }
+
+    /// `compatible_printers` must name the machine when it is set.
+    fn check_printers(&mut self, profile: &Profile, profile_type: &str) {
+        let machine = &self.machine.name;
+        if !profile.compatible_printers.is_empty() && !profile.compatible_printers.contains(machine)
+        {
+            self.push(
+                profile,
+                "error",
+                &format!("{}_printer_mismatch", profile_type),
+                format!(
+                    "{} '{}' is limited to {}; choose one that lists '{}' or add it to compatible_printers",
+                    profile_type,
+                    profile.name,
+                    profile.compatible_printers.join(", "),
+                    machine
+                ),
+            );
+        }
+    }
+
+    fn check_nozzle(&mut self, process: &Profile, filament: &Profile) {
+        let Some(nozzle) = self.machine.nozzle_diameter else {
+            return;
+        };
+        if let Some(named) = nozzle_in_name(&process.name).filter(|n| !same_nozzle(*n, nozzle)) {
+            self.push(
+                process,
+                "error",
+                "nozzle_mismatch",
+                format!(
+                    "process '{}' is made for a {} mm nozzle but '{}' has a {} mm nozzle",
+                    process.name, named, self.machine.name, nozzle
+                ),
+            );
+        }
+
+        // Abrasive filaments set a minimum nozzle hardness (HRC); 0 means any nozzle.
+        let required = filament.number("required_nozzle_HRC").unwrap_or(0.0);
+        let hardness = self.machine.number("nozzle_hrc").unwrap_or(0.0);
+        if required > 0.0 && hardness > 0.0 && hardness < required {
+            self.push(
+                filament,
+                "error",
+                "nozzle_too_soft",
+                format!(
+                    "'{}' needs a nozzle of at least {} HRC; '{}' has {} HRC",
+                    filament.name, required, self.machine.name, hardness
+                ),
+            );
+        }
+    }
+
+    fn check_layer_height(&mut self, process: &Profile) {
+        let Some(layer_height) = process.layer_height else {
+            return;
+        };
+        let min = self.machine.number("min_layer_height");
+        let max = self.machine.number("max_layer_height").or_else(|| {
+            self.machine
+                .nozzle_diameter
+                .map(|nozzle| nozzle * MAX_LAYER_TO_NOZZLE)
+        });
+        let too_thin = min.is_some_and(|min| layer_height < min);
+        let too_thick = max.is_some_and(|max| layer_height > max + f64::EPSILON);
+        if too_thin || too_thick {
+            self.push(
+                process,
+                "error",
+                "layer_height_out_of_range",
+                format!(
+                    "layer height {} mm is outside {}–{} mm for '{}'",
+                    layer_height,
+                    min.map_or("?".to_string(), |v| v.to_string()),
+                    max.map_or("?".to_string(), |v| format!("{:.2}", v)),
+                    self.machine.name
+                ),
+            );
+        }
+    }
+
+    fn check_temperatures(&mut self, filament: &Profile) {
+        let low = filament.number("nozzle_temperature_range_low");
+        let high = filament.number("nozzle_temperature_range_high");
+        if low.is_none() && high.is_none() {
+            return;
+        }
+        for key in ["nozzle_temperature", "nozzle_temperature_initial_layer"] {
+            let Some(temperature) = filament.number(key) else {
+                continue;
+            };
+            if low.is_some_and(|low| temperature < low)
+                || high.is_some_and(|high| temperature > high)
+            {
+                self.push(
+                    filament,
+                    "error",
+                    "temperature_out_of_range",
+                    format!(
+                        "{} {} °C is outside the recommended {}–{} °C for '{}'",
+                        key,
+                        temperature,
+                        low.map_or("?".to_string(), |v| v.to_string()),
+                        high.map_or("?".to_string(), |v| v.to_string()),
+                        filament.name
+                    ),
+                );
+            }
+        }
+
+        let chamber = filament.number("chamber_temperatures").unwrap_or(0.0);
+        let controlled = self
+            .machine
+            .setting("support_chamber_temp_control")
+            .is_some_and(|v| v == "1" || v == "true");
+        if chamber > 0.0 && !controlled {
+            self.push(
+                filament,
+                "warning",
+                "no_chamber_control",
+                format!(
+                    "'{}' expects a {} °C chamber but '{}' cannot heat its chamber",
+                    filament.name, chamber, self.machine.name
+                ),
+            );
+        }
+    }
+}
+
+/// Check that a machine, filament and process profile suit each other.
+///
+/// Covers printer lists (`compatible_printers`, a filament's `compatible_prints`),
+/// nozzle size and hardness, layer height limits and filament temperature ranges.
+/// Settings a profile does not define are not checked.
+pub fn check_profiles(
+    machine: &Profile,
+    filament: &Profile,
+    process: &Profile,
+) -> CompatibilityReport {
+    let mut checker = Checker {
+        machine,
+        issues: Vec::new(),
+    };
+    checker.check_printers(filament, "filament");
+    checker.check_printers(process, "process");
+
+    let prints = filament.list("compatible_prints");
+    if !prints.is_empty() && !prints.contains(&process.name) {
+        checker.push(
+            filament,
+            "error",
+            "filament_process_mismatch",
+            format!(
+                "filament '{}' is limited to processes {}; '{}' is not one of them",
+                filament.name,
+                prints.join(", "),
+                process.name
+            ),
+        );
+    }
+
+    checker.check_nozzle(process, filament);
+    checker.check_layer_height(process);
+    checker.check_temperatures(filament);
+
+    CompatibilityReport {
+        machine: machine.path.clone(),
+        filament: filament.path.clone(),
+        process: process.path.clone(),
+        issues: checker.issues,
+    }
+}
+
+/// Load a profile merged with its parents, falling back to the file alone when a
+/// parent lives outside the search directories.
+pub(crate) fn load_resolved(path: &Path, search_dirs: &[PathBuf]) -> Result<Profile, OrcaError> {
+    // `<profiles_dir>/<type>/<file>.json`: the profile tree holds sibling parents.
+    let mut dirs: Vec<PathBuf> = path
+        .parent()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .into_iter()
+        .collect();
+    dirs.extend(search_dirs.iter().cloned());
+    resolve_profile_file(path, &index_profiles(&dirs)).or_else(|err| match err {
+        OrcaError::ProfileNotFound(_) => load_profile_file(path),
+        other => Err(other),
+    })
+}
+
+/// Check a machine, filament and process profile trio before slicing
+#[pyfunction]
+#[pyo3(signature = (machine, filament, process, search_dirs=Vec::new()))]
+pub fn check_compatibility(
+    machine: String,
+    filament: String,
+    process: String,
+    search_dirs: Vec<String>,
+) -> PyResult<CompatibilityReport> {
+    panic_boundary::catch(|| {
+        let search_dirs: Vec<PathBuf> = search_dirs.iter().map(PathBuf::from).collect();
+        Ok(check_profiles(
+            &load_resolved(Path::new(&machine), &search_dirs)?,
+            &load_resolved(Path::new(&filament), &search_dirs)?,
+            &load_resolved(Path::new(&process), &search_dirs)?,
+        ))
+    })
+}
@@ -0,0 +1,218 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::panic_boundary;
+use crate::profiles::{
+    collect_json_files, index_profiles, load_profile_file, resolve_profile_file, Profile,
+};
+
+static NOZZLE_IN_NAME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(\d+(?:\.\d+)?)\s*(?:mm)?\s*nozzle").unwrap());
+
+pub(crate) fn init_regexes() {
+    Lazy::force(&NOZZLE_IN_NAME_REGEX);
+}
+
+/// Nozzle sizes are compared to 0.01 mm; profiles write both "0.4" and ".40".
+pub(crate) fn same_nozzle(a: f64, b: f64) -> bool {
+    (a - b).abs() < 0.005
+}
+
+/// Nozzle size named in a profile name such as "0.20mm Standard @0.4 nozzle".
+pub(crate) fn nozzle_in_name(name: &str) -> Option<f64> {
+    NOZZLE_IN_NAME_REGEX
+        .captures(name)
+        .and_then(|cap| cap[1].parse::<f64>().ok())
+}
+
+/// Machine profile summary for selection menus
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MachineListing {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub file_name: String,
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub printer_model: Option<String>,
+    #[pyo3(get)]
+    pub nozzle_diameter: Option<f64>,
+    #[pyo3(get)]
+    pub bed_size: Option<(f64, f64)>,
+    #[pyo3(get)]
+    pub printable_height: Option<f64>,
+    #[pyo3(get)]
+    pub min_layer_height: Option<f64>,
+    #[pyo3(get)]
+    pub max_layer_height: Option<f64>,
+}
+
+#[pymethods]
+impl MachineListing {
+    fn __str__(&self) -> String {
+        format!(
+            "MachineListing(name={}, nozzle={:?}, bed={:?})",
+            self.name, self.nozzle_diameter, self.bed_size
+        )
+    }
+}
+
+/// Process profile summary for selection menus
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct ProcessListing {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub file_name: String,
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub layer_height: Option<f64>,
+    /// Nozzles this process is meant for; empty when it is not tied to one.
+    #[pyo3(get)]
+    pub nozzle_diameters: Vec<f64>,
+    #[pyo3(get)]
+    pub compatible_printers: Vec<String>,
+}
+
+impl ProcessListing {
+    /// Processes with no known nozzle size are offered for every nozzle.
+    pub fn suits_nozzle(&self, nozzle: Option<f64>) -> bool {
+        match nozzle {
+            Some(n) => {
+                self.nozzle_diameters.is_empty()
+                    || self.nozzle_diameters.iter().any(|d| same_nozzle(*d, n))
+            }
+            None => true,
+        }
+    }
+}
+
+#[pymethods]
+impl ProcessListing {
+    fn __str__(&self) -> String {
+        format!(
+            "ProcessListing(name={}, layer_height={:?}, nozzles={:?})",
+            self.name, self.layer_height, self.nozzle_diameters
+        )
+    }
+}
+
+/// Load every instantiable profile of one type, with inheritance resolved where possible.
+fn load_profiles(profiles_dir: &Path, profile_type: &str) -> Vec<Profile> {
+    let index = index_profiles(&[profiles_dir.to_path_buf()]);
+    let mut files = Vec::new();
+    collect_json_files(&profiles_dir.join(profile_type), &mut files);
+    files.sort();
+
+    let mut profiles: Vec<Profile> = files
+        .iter()
+        // Parents may live outside the tree (OrcaSlicer system profiles); the leaf alone
+        // is still worth listing.
+        .filter_map(|path| {
+            resolve_profile_file(path, &index)
+                .or_else(|_| load_profile_file(path))
+                .ok()
+        })
+        .filter(|p| p.instantiation)
+        .filter(|p| p.profile_type.is_empty() || p.profile_type == profile_type)
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+pub fn discover_machine_listings(profiles_dir: &Path) -> Vec<MachineListing> {
+    load_profiles(profiles_dir, "machine")
+        .into_iter()
+        .map(|profile| MachineListing {
+            file_name: file_name(&profile.path),
+            min_layer_height: profile.number("min_layer_height"),
+            max_layer_height: profile.number("max_layer_height"),
+            name: profile.name,
+            path: profile.path,
+            printer_model: profile.printer_model,
+            nozzle_diameter: profile.nozzle_diameter,
+            bed_size: profile.bed_size,
+            printable_height: profile.printable_height,
+        })
+        .collect()
+}
+
+/// Every process listing, with nozzle sizes taken from the given machines where possible.
+pub fn process_listings(profiles_dir: &Path, machines: &[MachineListing]) -> Vec<ProcessListing> {
+    let machine_nozzles: HashMap<&str, f64> = machines
+        .iter()
+        .filter_map(|m| m.nozzle_diameter.map(|n| (m.name.as_str(), n)))
+        .collect();
+
+    load_profiles(profiles_dir, "process")
+        .into_iter()
+        .map(|profile| {
+            // Prefer the nozzles of the printers the process declares; fall back to the
+            // "0.5mm nozzle" naming convention used by most vendor and user profiles.
+            let mut nozzle_diameters: Vec<f64> = Vec::new();
+            for n in profile
+                .compatible_printers
+                .iter()
+                .filter_map(|printer| machine_nozzles.get(printer.as_str()))
+            {
+                if !nozzle_diameters.iter().any(|known| same_nozzle(*known, *n)) {
+                    nozzle_diameters.push(*n);
+                }
+            }
+            if nozzle_diameters.is_empty() {
+                nozzle_diameters.extend(nozzle_in_name(&profile.name));
+            }
+            ProcessListing {
+                file_name: file_name(&profile.path),
+                name: profile.name,
+                path: profile.path,
+                layer_height: profile.layer_height,
+                nozzle_diameters,
+                compatible_printers: profile.compatible_printers,
+            }
+        })
+        .collect()
+}
+
+pub fn discover_process_listings(profiles_dir: &Path, nozzle: Option<f64>) -> Vec<ProcessListing> {
+    let machines = discover_machine_listings(profiles_dir);
+    process_listings(profiles_dir, &machines)
+        .into_iter()
+        .filter(|process| process.suits_nozzle(nozzle))
+        .collect()
+}
+
+/// List instantiable machine profiles under `<profiles_dir>/machine`
+#[pyfunction]
+pub fn discover_machines(profiles_dir: String) -> PyResult<Vec<MachineListing>> {
+    panic_boundary::catch(|| Ok(discover_machine_listings(&PathBuf::from(profiles_dir))))
+}
+
+/// List process profiles under `<profiles_dir>/process`, optionally only those usable with a nozzle size
+#[pyfunction]
+#[pyo3(signature = (profiles_dir, nozzle=None))]
+pub fn discover_processes(
+    profiles_dir: String,
+    nozzle: Option<f64>,
+) -> PyResult<Vec<ProcessListing>> {
+    panic_boundary::catch(|| {
+        Ok(discover_process_listings(
+            &PathBuf::from(profiles_dir),
+            nozzle,
+        ))
+    })
+}
@@ -0,0 +1,208 @@
+use pyo3::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::panic_boundary;
+use crate::profiles::{
+    collect_json_files, index_profiles, read_settings, resolve_profile_file, Profile,
+};
+use crate::OrcaError;
+
+/// Keys a usable (instantiable) profile must define after inheritance is resolved.
+fn required_keys(profile_type: &str) -> &'static [&'static str] {
+    match profile_type {
+        "machine" => &["printable_area", "nozzle_diameter"],
+        "filament" => &["filament_type"],
+        "process" => &["layer_height"],
+        _ => &[],
+    }
+}
+
+/// A single problem found while linting a profile tree
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct LintIssue {
+    #[pyo3(get)]
+    pub path: String,
+    /// "error" or "warning"
+    #[pyo3(get)]
+    pub severity: String,
+    /// Stable identifier, e.g. "parse_error" or "broken_inherits"
+    #[pyo3(get)]
+    pub code: String,
+    #[pyo3(get)]
+    pub message: String,
+}
+
+#[pymethods]
+impl LintIssue {
+    fn __str__(&self) -> String {
+        format!(
+            "{} [{}] {}: {}",
+            self.severity, self.code, self.path, self.message
+        )
+    }
+}
+
+/// Result of linting every profile below a directory
+#[derive(Debug, Clone, Default)]
+#[pyclass]
+pub struct ProfileLintReport {
+    #[pyo3(get)]
+    pub files_checked: u32,
+    #[pyo3(get)]
+    pub issues: Vec<LintIssue>,
+}
+
+impl ProfileLintReport {
+    fn push(&mut self, path: &Path, severity: &str, code: &str, message: String) {
+        self.issues.push(LintIssue {
+            path: path.display().to_string(),
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message,
+        });
+    }
+}
+
+#[pymethods]
+impl ProfileLintReport {
+    /// True when no errors were found; warnings do not fail the report.
+    #[getter]
+    fn is_ok(&self) -> bool {
+        self.issues.iter().all(|issue| issue.severity != "error")
+    }
+
+    #[getter]
+    fn error_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == "error").count()
+    }
+
+    #[getter]
+    fn warning_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == "warning")
+            .count()
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "ProfileLintReport(files={}, errors={}, warnings={})",
+            self.files_checked,
+            self.error_count(),
+            self.warning_count()
+        )
+    }
+}
+
+/// Profile type from the `type` key, or from the `machine`/`filament`/`process` directory it lives in.
+fn profile_type_of(profile: &Profile) -> String {
+    if !profile.profile_type.is_empty() {
+        return profile.profile_type.clone();
+    }
+    Path::new(&profile.path)
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| !required_keys(name).is_empty())
+        .unwrap_or_default()
+}
+
+pub fn lint_profile_tree(profiles_dir: &Path, search_dirs: &[PathBuf]) -> ProfileLintReport {
+    let mut report = ProfileLintReport::default();
+    let mut files = Vec::new();
+    collect_json_files(profiles_dir, &mut files);
+    files.sort();
+
+    // Profiles in the tree shadow same-named profiles from the extra search dirs.
+    let mut index_dirs = vec![profiles_dir.to_path_buf()];
+    index_dirs.extend(search_dirs.iter().cloned());
+    let index = index_profiles(&index_dirs);
+
+    let mut resolved = Vec::new();
+    for path in &files {
+        report.files_checked += 1;
+        if let Err(e) = read_settings(path) {
+            report.push(path, "error", "parse_error", e.to_string());
+            continue;
+        }
+        match resolve_profile_file(path, &index) {
+            Ok(profile) => resolved.push(profile),
+            Err(OrcaError::ProfileNotFound(parent)) => report.push(
+                path,
+                "error",
+                "broken_inherits",
+                format!("inherited profile '{}' not found", parent),
+            ),
+            Err(e) => report.push(path, "error", "inheritance_error", e.to_string()),
+        }
+    }
+
+    let machine_names: HashSet<&str> = resolved
+        .iter()
+        .filter(|p| profile_type_of(p) == "machine")
+        .map(|p| p.name.as_str())
+        .collect();
+
+    for profile in &resolved {
+        // Base profiles only exist to be inherited from and may be incomplete.
+        if !profile.instantiation {
+            continue;
+        }
+        let path = Path::new(&profile.path);
+        let profile_type = profile_type_of(profile);
+        if profile_type.is_empty() {
+            report.push(
+                path,
+                "warning",
+                "unknown_type",
+                "profile has no 'type' and is not in a machine/filament/process directory"
+                    .to_string(),
+            );
+            continue;
+        }
+        for key in required_keys(&profile_type) {
+            if !profile.settings.contains_key(*key) {
+                report.push(
+                    path,
+                    "error",
+                    "missing_key",
+                    format!("{} profile is missing '{}'", profile_type, key),
+                );
+            }
+        }
+        if profile_type != "machine"
+            && !profile.compatible_printers.is_empty()
+            && !machine_names.is_empty()
+            && !profile
+                .compatible_printers
+                .iter()
+                .any(|printer| machine_names.contains(printer.as_str()))
+        {
+            report.push(
+                path,
+                "warning",
+                "no_compatible_machine",
+                format!(
+                    "compatible_printers lists none of the configured machines ({})",
+                    profile.compatible_printers.join(", ")
+                ),
+            );
+        }
+    }
+    report
+}
+
+/// Check every JSON profile for parse errors, broken inheritance, missing keys and compatibility
+#[pyfunction]
+#[pyo3(signature = (profiles_dir, search_dirs=Vec::new()))]
+pub fn lint_profiles(
+    profiles_dir: String,
+    search_dirs: Vec<String>,
+) -> PyResult<ProfileLintReport> {
+    panic_boundary::catch(|| {
+        let search_dirs: Vec<PathBuf> = search_dirs.iter().map(PathBuf::from).collect();
+        Ok(lint_profile_tree(Path::new(&profiles_dir), &search_dirs))
+    })
+}
@@ -0,0 +1,231 @@
+use pyo3::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::panic_boundary;
+use crate::profiles::{read_settings, Profile};
+use crate::OrcaError;
+
+/// Material → filament (and optionally process) profile file mapping, loaded from TOML or JSON.
+///
+/// ```toml
+/// [filament]
+/// PLA = "ALT TABL MATTE PLA PEI.json"
+///
+/// [process]
+/// TPU = "0.25mm TPU slow.json"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProfileMapping {
+    #[serde(default)]
+    filament: HashMap<String, String>,
+    #[serde(default)]
+    process: HashMap<String, String>,
+}
+
+impl ProfileMapping {
+    /// Load a mapping file; the format is chosen by extension (`.json`, otherwise TOML).
+    pub fn load(path: &Path) -> Result<Self, OrcaError> {
+        if !path.is_file() {
+            return Err(OrcaError::FileNotFound(path.display().to_string()));
+        }
+        let contents = fs::read_to_string(path)?;
+        let parsed: Result<ProfileMapping, String> =
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                serde_json::from_str(&contents).map_err(|e| e.to_string())
+            } else {
+                toml::from_str(&contents).map_err(|e| e.to_string())
+            };
+        let mapping = parsed.map_err(|message| OrcaError::InvalidConfig {
+            path: path.display().to_string(),
+            message,
+        })?;
+
+        // Material keys are matched case-insensitively.
+        let normalize = |table: HashMap<String, String>| {
+            table
+                .into_iter()
+                .map(|(material, file)| (normalize_material(&material), file))
+                .collect()
+        };
+        Ok(ProfileMapping {
+            filament: normalize(mapping.filament),
+            process: normalize(mapping.process),
+        })
+    }
+
+    pub fn load_optional(path: Option<&Path>) -> Result<Self, OrcaError> {
+        path.map(Self::load)
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    pub fn filament_for(&self, material: &str) -> Option<&str> {
+        self.filament
+            .get(&normalize_material(material))
+            .map(String::as_str)
+    }
+
+    /// Preferred process profile file for a material, e.g. a slow profile for TPU.
+    pub fn process_for(&self, material: &str) -> Option<&str> {
+        self.process
+            .get(&normalize_material(material))
+            .map(String::as_str)
+    }
+}
+
+fn normalize_material(material: &str) -> String {
+    material.trim().to_uppercase()
+}
+
+/// Filament profile chosen for a material and the rule that selected it
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct FilamentResolution {
+    #[pyo3(get)]
+    pub material: String,
+    #[pyo3(get)]
+    pub path: String,
+    /// One of "mapping", "convention" or "filament_type".
+    #[pyo3(get)]
+    pub source: String,
+}
+
+#[pymethods]
+impl FilamentResolution {
+    fn __str__(&self) -> String {
+        format!(
+            "FilamentResolution(material={}, path={}, source={})",
+            self.material, self.path, self.source
+        )
+    }
+}
+
+/// Every filament profile in `filament_dir`, sorted by file name; unreadable files are skipped.
+pub fn filament_profiles(filament_dir: &Path) -> Vec<Profile> {
+    let mut candidates: Vec<PathBuf> = fs::read_dir(filament_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .filter_map(|path| {
+            read_settings(&path)
+                .ok()
+                .map(|settings| Profile::from_settings(&path, settings))
+        })
+        .collect()
+}
+
+/// Resolve a material to a filament profile in `filament_dir`.
+///
+/// Rules, in order:
+/// 1. An explicit entry in the mapping file (trusted as configured).
+/// 2. The `<material>.json` naming convention, lowercased.
+/// 3. The first instantiable profile, by file name, whose `filament_type` equals the material.
+pub fn resolve_filament(
+    filament_dir: &Path,
+    material: &str,
+    mapping: &ProfileMapping,
+) -> Result<FilamentResolution, OrcaError> {
+    resolve_filament_among(filament_dir, material, mapping, None)
+}
+
+/// `resolve_filament` with already-parsed candidates for the `filament_type` rule;
+/// `None` reads them from `filament_dir` only if that rule is reached.
+pub fn resolve_filament_among(
+    filament_dir: &Path,
+    material: &str,
+    mapping: &ProfileMapping,
+    candidates: Option<&[Profile]>,
+) -> Result<FilamentResolution, OrcaError> {
+    let resolution = |path: PathBuf, source: &str| FilamentResolution {
+        material: normalize_material(material),
+        path: path.to_string_lossy().into_owned(),
+        source: source.to_string(),
+    };
+
+    if let Some(file) = mapping.filament_for(material) {
+        return Ok(resolution(filament_dir.join(file), "mapping"));
+    }
+
+    let conventional = filament_dir.join(format!("{}.json", material.trim().to_lowercase()));
+    if conventional.is_file() {
+        return Ok(resolution(conventional, "convention"));
+    }
+
+    let loaded;
+    let candidates = match candidates {
+        Some(candidates) => candidates,
+        None => {
+            loaded = filament_profiles(filament_dir);
+            &loaded
+        }
+    };
+    let wanted = normalize_material(material);
+    if let Some(profile) = candidates.iter().find(|profile| {
+        profile.instantiation
+            && profile
+                .filament_type
+                .as_deref()
+                .is_some_and(|t| normalize_material(t) == wanted)
+    }) {
+        return Ok(resolution(PathBuf::from(&profile.path), "filament_type"));
+    }
+
+    Err(OrcaError::ProfileNotFound(format!(
+        "no filament profile for material '{}' in {}",
+        material,
+        filament_dir.display()
+    )))
+}
+
+/// Resolve the filament profile for a material using the mapping file and fallback rules
+#[pyfunction]
+#[pyo3(signature = (filament_dir, material, mapping_path=None))]
+pub fn resolve_filament_profile(
+    filament_dir: String,
+    material: String,
+    mapping_path: Option<String>,
+) -> PyResult<FilamentResolution> {
+    panic_boundary::catch(|| {
+        let mapping = ProfileMapping::load_optional(mapping_path.as_deref().map(Path::new))?;
+        Ok(resolve_filament(
+            Path::new(&filament_dir),
+            &material,
+            &mapping,
+        )?)
+    })
+}
+
+/// List materials that neither the mapping file nor the fallback rules can resolve
+#[pyfunction]
+#[pyo3(signature = (filament_dir, materials, mapping_path=None))]
+pub fn list_unmapped_materials(
+    filament_dir: String,
+    materials: Vec<String>,
+    mapping_path: Option<String>,
+) -> PyResult<Vec<String>> {
+    panic_boundary::catch(|| {
+        let mapping = ProfileMapping::load_optional(mapping_path.as_deref().map(Path::new))?;
+        let filament_dir = Path::new(&filament_dir);
+        Ok(materials
+            .into_iter()
+            .filter(
+                |material| match resolve_filament(filament_dir, material, &mapping) {
+                    // A mapped file that is missing on disk is as good as unmapped.
+                    Ok(found) => !Path::new(&found.path).is_file(),
+                    Err(_) => true,
+                },
+            )
+            .collect())
+    })
+}
@@ -0,0 +1,221 @@
+use pyo3::prelude::*;
+use std::path::Path;
+
+use crate::panic_boundary;
+use crate::profile_discovery::{
+    discover_machine_listings, process_listings, same_nozzle, MachineListing, ProcessListing,
+};
+use crate::profile_mapping::{filament_profiles, resolve_filament_among, ProfileMapping};
+use crate::profiles::Profile;
+use crate::OrcaError;
+
+/// Machine, process and filament profiles chosen for one job
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct ProfilePaths {
+    #[pyo3(get)]
+    pub machine: String,
+    #[pyo3(get)]
+    pub process: String,
+    #[pyo3(get)]
+    pub filament: String,
+    #[pyo3(get)]
+    pub nozzle_diameter: Option<f64>,
+}
+
+#[pymethods]
+impl ProfilePaths {
+    fn __str__(&self) -> String {
+        format!(
+            "ProfilePaths(machine={}, process={}, filament={}, nozzle={:?})",
+            self.machine, self.process, self.filament, self.nozzle_diameter
+        )
+    }
+}
+
+/// What a job asks for; `machine` and `process` are preferred file names.
+pub struct ProfileRequest<'a> {
+    pub material: &'a str,
+    pub nozzle: Option<f64>,
+    pub machine: Option<&'a str>,
+    pub process: Option<&'a str>,
+    pub mapping: &'a ProfileMapping,
+}
+
+/// Distinct nozzle sizes, sorted, for error messages.
+fn nozzle_list(nozzles: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut known: Vec<f64> = Vec::new();
+    for n in nozzles {
+        if !known.iter().any(|k| same_nozzle(*k, n)) {
+            known.push(n);
+        }
+    }
+    known.sort_by(|a, b| a.total_cmp(b));
+    known
+}
+
+fn no_match(profile_type: &str, nozzle: f64, available: Vec<f64>) -> OrcaError {
+    OrcaError::NozzleUnavailable {
+        profile_type: profile_type.to_string(),
+        requested: nozzle,
+        available,
+    }
+}
+
+fn select_machine<'m>(
+    machines: &'m [MachineListing],
+    request: &ProfileRequest<'_>,
+) -> Result<&'m MachineListing, OrcaError> {
+    let suits = |m: &MachineListing| match request.nozzle {
+        Some(n) => m.nozzle_diameter.is_some_and(|d| same_nozzle(d, n)),
+        None => true,
+    };
+    let preferred = request
+        .machine
+        .and_then(|file| machines.iter().find(|m| m.file_name == file));
+    if let Some(machine) = preferred.filter(|m| suits(m)) {
+        return Ok(machine);
+    }
+    // Without a nozzle constraint the preferred machine must exist.
+    if let (None, Some(file)) = (request.nozzle, request.machine) {
+        return Err(OrcaError::ProfileNotFound(format!("machine/{}", file)));
+    }
+    machines.iter().find(|m| suits(m)).ok_or_else(|| {
+        no_match(
+            "machine",
+            request.nozzle.unwrap_or_default(),
+            nozzle_list(machines.iter().filter_map(|m| m.nozzle_diameter)),
+        )
+    })
+}
+
+fn select_process<'p>(
+    processes: &'p [ProcessListing],
+    machine: &MachineListing,
+    request: &ProfileRequest<'_>,
+) -> Result<&'p ProcessListing, OrcaError> {
+    let compatible = |p: &ProcessListing| {
+        p.compatible_printers.is_empty() || p.compatible_printers.contains(&machine.name)
+    };
+    let usable = |p: &ProcessListing| p.suits_nozzle(request.nozzle) && compatible(p);
+
+    // A process mapped to the material beats the configured default.
+    let requested = request
+        .mapping
+        .process_for(request.material)
+        .or(request.process);
+    let preferred = requested.and_then(|file| processes.iter().find(|p| p.file_name == file));
+    // Without a nozzle the configured process is trusted as-is.
+    if let Some(process) = preferred.filter(|p| request.nozzle.is_none() || usable(p)) {
+        return Ok(process);
+    }
+    if let (None, Some(file)) = (request.nozzle, requested) {
+        return Err(OrcaError::ProfileNotFound(format!("process/{}", file)));
+    }
+
+    // Processes written for this printer beat ones written for the nozzle size,
+    // which beat generic ones.
+    let rank = |p: &ProcessListing| {
+        if p.compatible_printers.contains(&machine.name) {
+            0
+        } else if !p.nozzle_diameters.is_empty() {
+            1
+        } else {
+            2
+        }
+    };
+    processes
+        .iter()
+        .filter(|p| usable(p))
+        .min_by_key(|p| rank(p))
+        .ok_or_else(|| {
+            no_match(
+                "process",
+                request.nozzle.unwrap_or_default(),
+                nozzle_list(processes.iter().flat_map(|p| p.nozzle_diameters.clone())),
+            )
+        })
+}
+
+/// Choose profiles for a job from already-discovered listings.
+///
+/// With a nozzle, the preferred machine and process are kept only if they match it;
+/// otherwise the first matching profile is used. A filament restricted to other
+/// printers is swapped for a compatible profile of the same material when one exists.
+pub fn select_profiles(
+    machines: &[MachineListing],
+    processes: &[ProcessListing],
+    filaments: &[Profile],
+    filament_dir: &Path,
+    request: &ProfileRequest<'_>,
+) -> Result<ProfilePaths, OrcaError> {
+    let machine = select_machine(machines, request)?;
+    let process = select_process(processes, machine, request)?;
+
+    let mut filament = resolve_filament_among(
+        filament_dir,
+        request.material,
+        request.mapping,
+        Some(filaments),
+    )?
+    .path;
+    let restricted = filaments
+        .iter()
+        .find(|f| f.path == filament)
+        .is_some_and(|f| {
+            !f.compatible_printers.is_empty() && !f.compatible_printers.contains(&machine.name)
+        });
+    if restricted {
+        let wanted = request.material.trim();
+        if let Some(alternative) = filaments.iter().find(|f| {
+            f.instantiation
+                && f.compatible_printers.contains(&machine.name)
+                && f.filament_type
+                    .as_deref()
+                    .is_some_and(|t| t.eq_ignore_ascii_case(wanted))
+        }) {
+            filament = alternative.path.clone();
+        }
+    }
+
+    Ok(ProfilePaths {
+        machine: machine.path.clone(),
+        process: process.path.clone(),
+        filament,
+        nozzle_diameter: machine.nozzle_diameter,
+    })
+}
+
+/// Resolve machine, process and filament profiles for a material and optional nozzle size
+#[pyfunction]
+#[pyo3(signature = (profiles_dir, material, nozzle=None, machine=None, process=None, mapping_path=None))]
+pub fn resolve_profile_paths(
+    profiles_dir: String,
+    material: String,
+    nozzle: Option<f64>,
+    machine: Option<String>,
+    process: Option<String>,
+    mapping_path: Option<String>,
+) -> PyResult<ProfilePaths> {
+    panic_boundary::catch(|| {
+        let profiles_dir = Path::new(&profiles_dir);
+        let mapping = ProfileMapping::load_optional(mapping_path.as_deref().map(Path::new))?;
+        let machines = discover_machine_listings(profiles_dir);
+        let processes = process_listings(profiles_dir, &machines);
+        let filament_dir = profiles_dir.join("filament");
+        let filaments = filament_profiles(&filament_dir);
+        Ok(select_profiles(
+            &machines,
+            &processes,
+            &filaments,
+            &filament_dir,
+            &ProfileRequest {
+                material: &material,
+                nozzle,
+                machine: machine.as_deref(),
+                process: process.as_deref(),
+                mapping: &mapping,
+            },
+        )?)
+    })
+}
@@ -0,0 +1,311 @@
+use pyo3::prelude::*;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Typed view of an OrcaSlicer machine, filament, or process profile
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct Profile {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub profile_type: String,
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub inherits: Option<String>,
+    #[pyo3(get)]
+    pub source: Option<String>,
+    #[pyo3(get)]
+    pub instantiation: bool,
+    #[pyo3(get)]
+    pub printer_model: Option<String>,
+    #[pyo3(get)]
+    pub nozzle_diameter: Option<f64>,
+    #[pyo3(get)]
+    pub bed_size: Option<(f64, f64)>,
+    #[pyo3(get)]
+    pub printable_height: Option<f64>,
+    #[pyo3(get)]
+    pub layer_height: Option<f64>,
+    #[pyo3(get)]
+    pub filament_type: Option<String>,
+    #[pyo3(get)]
+    pub filament_density: Option<f64>,
+    #[pyo3(get)]
+    pub filament_diameter: Option<f64>,
+    #[pyo3(get)]
+    pub filament_cost: Option<f64>,
+    #[pyo3(get)]
+    pub compatible_printers: Vec<String>,
+    /// Profile names from the leaf up to the root system profile.
+    #[pyo3(get)]
+    pub inheritance_chain: Vec<String>,
+    pub settings: Map<String, Value>,
+}
+
+impl Profile {
+    /// Build a profile from already-parsed settings.
+    pub fn from_settings(path: &Path, settings: Map<String, Value>) -> Self {
+        let name = string_setting(&settings, "name").unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        let profile_type = string_setting(&settings, "type").unwrap_or_default();
+
+        Profile {
+            name: name.clone(),
+            profile_type,
+            path: path.to_string_lossy().into_owned(),
+            inherits: string_setting(&settings, "inherits").filter(|s| !s.is_empty()),
+            source: string_setting(&settings, "from"),
+            // User profiles omit the key entirely; only system base profiles say "false".
+            instantiation: string_setting(&settings, "instantiation")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            printer_model: string_setting(&settings, "printer_model"),
+            nozzle_diameter: number_setting(&settings, "nozzle_diameter"),
+            bed_size: settings.get("printable_area").and_then(bed_size_from_area),
+            printable_height: number_setting(&settings, "printable_height"),
+            layer_height: number_setting(&settings, "layer_height"),
+            filament_type: string_setting(&settings, "filament_type"),
+            filament_density: number_setting(&settings, "filament_density"),
+            filament_diameter: number_setting(&settings, "filament_diameter"),
+            filament_cost: number_setting(&settings, "filament_cost"),
+            compatible_printers: list_setting(&settings, "compatible_printers"),
+            inheritance_chain: vec![name],
+            settings,
+        }
+    }
+
+    /// Raw string value of a setting; array settings yield their first element.
+    pub fn setting(&self, key: &str) -> Option<String> {
+        string_setting(&self.settings, key)
+    }
+
+    /// Numeric value of a setting, if present and parseable.
+    pub fn number(&self, key: &str) -> Option<f64> {
+        number_setting(&self.settings, key)
+    }
+
+    /// List value of a setting; `;`-separated strings are split.
+    pub fn list(&self, key: &str) -> Vec<String> {
+        list_setting(&self.settings, key)
+    }
+}
+
+#[pymethods]
+impl Profile {
+    /// Look up any raw setting by key
+    fn get(&self, key: &str) -> Option<String> {
+        self.setting(key)
+    }
+
+    /// All setting keys present in the profile
+    fn keys(&self) -> Vec<String> {
+        self.settings.keys().cloned().collect()
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "Profile(name={}, type={}, inherits={:?})",
+            self.name, self.profile_type, self.inherits
+        )
+    }
+}
+
+/// Read a profile JSON file into its top-level settings map.
+pub fn read_settings(path: &Path) -> Result<Map<String, Value>, OrcaError> {
+    if !path.is_file() {
+        return Err(OrcaError::ProfileNotFound(path.display().to_string()));
+    }
+    let contents = fs::read_to_string(path)?;
+    match serde_json::from_str::<Value>(&contents) {
+        Ok(Value::Object(settings)) => Ok(settings),
+        Ok(_) => Err(OrcaError::InvalidProfile {
+            path: path.display().to_string(),
+            message: "top-level JSON value is not an object".to_string(),
+        }),
+        Err(e) => Err(OrcaError::InvalidProfile {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        }),
+    }
+}
+
+pub fn load_profile_file(path: &Path) -> Result<Profile, OrcaError> {
+    Ok(Profile::from_settings(path, read_settings(path)?))
+}
+
+/// Keys that describe a profile file itself and are never inherited from a parent.
+const NON_INHERITED_KEYS: &[&str] = &["name", "inherits", "from", "instantiation", "setting_id"];
+
+/// Map profile names to files for every `*.json` below the given directories.
+/// Earlier directories win, so user profiles can shadow system profiles of the same name.
+pub fn index_profiles(dirs: &[PathBuf]) -> HashMap<String, PathBuf> {
+    let mut index = HashMap::new();
+    for dir in dirs {
+        let mut files = Vec::new();
+        collect_json_files(dir, &mut files);
+        files.sort();
+        for path in files {
+            let name = read_settings(&path)
+                .ok()
+                .and_then(|settings| string_setting(&settings, "name"))
+                .or_else(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()));
+            if let Some(name) = name {
+                index.entry(name).or_insert(path);
+            }
+        }
+    }
+    index
+}
+
+pub fn collect_json_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_json_files(&path, files);
+            } else if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                files.push(path);
+            }
+        }
+    }
+}
+
+/// Merge a profile with every ancestor named through `inherits`, leaf values winning.
+pub fn resolve_profile_file(
+    path: &Path,
+    index: &HashMap<String, PathBuf>,
+) -> Result<Profile, OrcaError> {
+    let leaf = read_settings(path)?;
+    let mut layers = vec![leaf];
+    let mut seen = HashSet::new();
+    let mut chain = Vec::new();
+    // File of the newest layer; its parent is looked for beside it.
+    let mut layer_path = path.to_path_buf();
+    if let Some(name) = string_setting(&layers[0], "name") {
+        seen.insert(name.clone());
+        chain.push(name);
+    }
+
+    while let Some(parent) =
+        string_setting(layers.last().unwrap(), "inherits").filter(|parent| !parent.is_empty())
+    {
+        if !seen.insert(parent.clone()) {
+            return Err(OrcaError::InvalidProfile {
+                path: path.display().to_string(),
+                message: format!("inheritance cycle through '{}'", parent),
+            });
+        }
+        // A sibling file named after the parent is checked first, matching
+        // how vendor directories lay out their base profiles.
+        let sibling = layer_path
+            .parent()
+            .map(|dir| dir.join(format!("{}.json", parent)))
+            .filter(|candidate| candidate.is_file());
+        let parent_path = sibling
+            .or_else(|| index.get(&parent).cloned())
+            .ok_or_else(|| OrcaError::ProfileNotFound(parent.clone()))?;
+        layers.push(read_settings(&parent_path)?);
+        chain.push(parent);
+        layer_path = parent_path;
+    }
+
+    let mut leaf = layers.remove(0);
+    let mut merged = Map::new();
+    for layer in layers.into_iter().rev() {
+        for (key, value) in layer {
+            if !NON_INHERITED_KEYS.contains(&key.as_str()) {
+                merged.insert(key, value);
+            }
+        }
+    }
+    merged.append(&mut leaf);
+
+    let mut profile = Profile::from_settings(path, merged);
+    if !chain.is_empty() {
+        profile.inheritance_chain = chain;
+    }
+    Ok(profile)
+}
+
+/// Orca stores most settings as strings or single-element string arrays.
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Array(items) => items.first().and_then(scalar_to_string),
+        _ => None,
+    }
+}
+
+fn string_setting(settings: &Map<String, Value>, key: &str) -> Option<String> {
+    settings.get(key).and_then(scalar_to_string)
+}
+
+fn number_setting(settings: &Map<String, Value>, key: &str) -> Option<f64> {
+    string_setting(settings, key).and_then(|v| v.trim().trim_end_matches('%').parse().ok())
+}
+
+fn list_setting(settings: &Map<String, Value>, key: &str) -> Vec<String> {
+    match settings.get(key) {
+        Some(Value::Array(items)) => items.iter().filter_map(scalar_to_string).collect(),
+        Some(Value::String(s)) if !s.is_empty() => s.split(';').map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Width and depth of a `printable_area` polygon given as `["0x0", "400x0", ...]`.
+fn bed_size_from_area(value: &Value) -> Option<(f64, f64)> {
+    let points: Vec<(f64, f64)> = match value {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(scalar_to_string)
+            .collect::<Vec<_>>(),
+        Value::String(s) => s.split(',').map(str::to_string).collect(),
+        _ => return None,
+    }
+    .iter()
+    .filter_map(|point| {
+        let (x, y) = point.trim().split_once('x')?;
+        Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+    })
+    .collect();
+
+    if points.is_empty() {
+        return None;
+    }
+    let (min_x, max_x) = points.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p.0), hi.max(p.0))
+    });
+    let (min_y, max_y) = points.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p.1), hi.max(p.1))
+    });
+    Some((max_x - min_x, max_y - min_y))
+}
+
+/// Parse an OrcaSlicer profile JSON file into a typed Profile
+#[pyfunction]
+pub fn load_profile(path: String) -> PyResult<Profile> {
+    panic_boundary::catch(|| Ok(load_profile_file(Path::new(&path))?))
+}
+
+/// Load a profile with its `inherits` chain merged into effective settings
+#[pyfunction]
+#[pyo3(signature = (path, search_dirs=Vec::new()))]
+pub fn resolve_profile(path: String, search_dirs: Vec<String>) -> PyResult<Profile> {
+    panic_boundary::catch(|| {
+        let dirs: Vec<PathBuf> = search_dirs.iter().map(PathBuf::from).collect();
+        let index = index_profiles(&dirs);
+        Ok(resolve_profile_file(Path::new(&path), &index)?)
+    })
+}
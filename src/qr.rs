@@ -0,0 +1,429 @@
+use crate::png;
+
+const MAX_VERSION: usize = 10;
+/// Error correction codewords per block at level M, indexed by version.
+const ECC_PER_BLOCK: [usize; MAX_VERSION + 1] = [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26];
+/// Error correction blocks at level M, indexed by version.
+const NUM_BLOCKS: [usize; MAX_VERSION + 1] = [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5];
+/// Format information bits for level M.
+const ECL_M_BITS: u32 = 0;
+/// Light modules around the symbol, as the standard requires.
+const QUIET_ZONE: usize = 4;
+
+/// A QR Code symbol (ISO/IEC 18004) in byte mode at error correction level M,
+/// versions 1-10: enough for payment payloads without an imaging dependency.
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    function: Vec<bool>,
+}
+
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8 - ECC_PER_BLOCK[version] * NUM_BLOCKS[version]
+}
+
+fn count_bits(version: usize) -> usize {
+    if version <= 9 {
+        8
+    } else {
+        16
+    }
+}
+
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u16 >> i) & 1) * x as u16;
+    }
+    z as u8
+}
+
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_mul(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+    result
+}
+
+/// Reed-Solomon error correction codewords for one block.
+pub(crate) fn rs_remainder(data: &[u8], degree: usize) -> Vec<u8> {
+    let divisor = rs_divisor(degree);
+    let mut result = vec![0u8; degree];
+    for &b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (r, &coef) in result.iter_mut().zip(&divisor) {
+            *r ^= gf_mul(coef, factor);
+        }
+    }
+    result
+}
+
+struct BitBuffer(Vec<bool>);
+
+impl BitBuffer {
+    fn append(&mut self, value: u32, len: usize) {
+        for i in (0..len).rev() {
+            self.0.push((value >> i) & 1 != 0);
+        }
+    }
+}
+
+/// Mode indicator, length, data, terminator and padding, as codewords.
+fn encode_data(data: &[u8], version: usize) -> Vec<u8> {
+    let capacity_bits = data_codewords(version) * 8;
+    let mut bits = BitBuffer(Vec::with_capacity(capacity_bits));
+    bits.append(0b0100, 4);
+    bits.append(data.len() as u32, count_bits(version));
+    for &b in data {
+        bits.append(b as u32, 8);
+    }
+    let terminator = (capacity_bits - bits.0.len()).min(4);
+    bits.append(0, terminator);
+    let to_byte = (8 - bits.0.len() % 8) % 8;
+    bits.append(0, to_byte);
+    for pad in [0xEC, 0x11].iter().cycle() {
+        if bits.0.len() >= capacity_bits {
+            break;
+        }
+        bits.append(*pad, 8);
+    }
+    bits.0
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0u8, |acc, &bit| (acc << 1) | bit as u8))
+        .collect()
+}
+
+/// Split into blocks, add error correction and interleave.
+fn add_ecc_and_interleave(data: &[u8], version: usize) -> Vec<u8> {
+    let num_blocks = NUM_BLOCKS[version];
+    let ecc_len = ECC_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let num_short = num_blocks - raw_codewords % num_blocks;
+    let short_data_len = raw_codewords / num_blocks - ecc_len;
+
+    let mut blocks = Vec::with_capacity(num_blocks);
+    let mut offset = 0;
+    for i in 0..num_blocks {
+        let len = short_data_len + usize::from(i >= num_short);
+        let block = &data[offset..offset + len];
+        offset += len;
+        blocks.push((block, rs_remainder(block, ecc_len)));
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..=short_data_len {
+        for (block, _) in &blocks {
+            if let Some(&b) = block.get(i) {
+                result.push(b);
+            }
+        }
+    }
+    for i in 0..ecc_len {
+        for (_, ecc) in &blocks {
+            result.push(ecc[i]);
+        }
+    }
+    result
+}
+
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let num_align = version / 7 + 2;
+    let size = version * 4 + 17;
+    let step = (version * 8 + num_align * 3 + 5) / (num_align * 4 - 4) * 2;
+    let mut result: Vec<usize> = (0..num_align - 1).map(|i| size - 7 - i * step).collect();
+    result.push(6);
+    result.reverse();
+    result
+}
+
+fn mask_bit(mask: u8, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => (x * y % 2 + x * y % 3) == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    }
+}
+
+impl QrCode {
+    /// Encode `data` in the smallest version that holds it, or `None` if even
+    /// version 10 is too small.
+    pub fn encode(data: &[u8]) -> Option<QrCode> {
+        let version = (1..=MAX_VERSION)
+            .find(|&v| 4 + count_bits(v) + data.len() * 8 <= data_codewords(v) * 8)?;
+        let size = version * 4 + 17;
+        let mut qr = QrCode {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        qr.draw_function_patterns(version);
+        let codewords = add_ecc_and_interleave(&encode_data(data, version), version);
+        qr.draw_codewords(&codewords);
+
+        let mut best: Option<(u32, u8)> = None;
+        for mask in 0..8 {
+            qr.apply_mask(mask);
+            qr.draw_format_bits(mask);
+            let penalty = qr.penalty();
+            if best.is_none_or(|(p, _)| penalty < p) {
+                best = Some((penalty, mask));
+            }
+            qr.apply_mask(mask);
+        }
+        let (_, mask) = best?;
+        qr.apply_mask(mask);
+        qr.draw_format_bits(mask);
+        Some(qr)
+    }
+
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i.is_multiple_of(2));
+            self.set_function(i, 6, i.is_multiple_of(2));
+        }
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let dist = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &cx) in positions.iter().enumerate() {
+            for (j, &cy) in positions.iter().enumerate() {
+                // The finder patterns already occupy three corners.
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let dark = dx.abs().max(dy.abs()) != 1;
+                        self.set_function(
+                            (cx as i32 + dx) as usize,
+                            (cy as i32 + dy) as usize,
+                            dark,
+                        );
+                    }
+                }
+            }
+        }
+        // Reserve the format areas; the real bits are drawn once the mask is known.
+        self.draw_format_bits(0);
+        if version >= 7 {
+            let mut rem = version as u32;
+            for _ in 0..12 {
+                rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | rem;
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 != 0;
+                let a = size - 11 + i % 3;
+                let b = i / 3;
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u8) {
+        let data = ECL_M_BITS << 3 | mask as u32;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let total_bits = codewords.len() * 8;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = right as usize - j;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.function[y * size + x] && i < total_bits {
+                        self.modules[y * size + x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    /// XOR a mask over the data modules; applying it twice undoes it.
+    fn apply_mask(&mut self, mask: u8) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let idx = y * self.size + x;
+                if !self.function[idx] && mask_bit(mask, x, y) {
+                    self.modules[idx] = !self.modules[idx];
+                }
+            }
+        }
+    }
+
+    fn line(&self, index: usize, horizontal: bool) -> Vec<bool> {
+        (0..self.size)
+            .map(|k| {
+                if horizontal {
+                    self.module(k, index)
+                } else {
+                    self.module(index, k)
+                }
+            })
+            .collect()
+    }
+
+    fn penalty(&self) -> u32 {
+        const FINDER_LIKE: [bool; 11] = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        let size = self.size;
+        let mut penalty = 0;
+        for index in 0..size {
+            for horizontal in [true, false] {
+                let line = self.line(index, horizontal);
+                let mut run = 1;
+                for k in 1..=size {
+                    if k < size && line[k] == line[k - 1] {
+                        run += 1;
+                    } else {
+                        if run >= 5 {
+                            penalty += 3 + (run - 5) as u32;
+                        }
+                        run = 1;
+                    }
+                }
+                for window in line.windows(11) {
+                    if window == FINDER_LIKE || window.iter().rev().eq(FINDER_LIKE.iter()) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.module(x, y);
+                if c == self.module(x + 1, y)
+                    && c == self.module(x, y + 1)
+                    && c == self.module(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        let total = (size * size) as i64;
+        let dark = self.modules.iter().filter(|&&m| m).count() as i64;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        penalty + k as u32 * 10
+    }
+
+    /// SVG with a quiet zone, one user unit per module.
+    pub fn to_svg(&self) -> String {
+        let full = self.size + 2 * QUIET_ZONE;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.module(x, y) {
+                    path.push_str(&format!("M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE));
+                }
+            }
+        }
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {0} {0}\" \
+             shape-rendering=\"crispEdges\"><rect width=\"{0}\" height=\"{0}\" fill=\"#fff\"/>\
+             <path d=\"{1}\" fill=\"#000\"/></svg>",
+            full, path
+        )
+    }
+
+    /// Greyscale PNG with a quiet zone, `scale` pixels per module.
+    pub fn to_png(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let width = (self.size + 2 * QUIET_ZONE) * scale;
+        let mut pixels = Vec::with_capacity(width * width);
+        for py in 0..width {
+            for px in 0..width {
+                let (mx, my) = (px / scale, py / scale);
+                let dark = (QUIET_ZONE..QUIET_ZONE + self.size).contains(&mx)
+                    && (QUIET_ZONE..QUIET_ZONE + self.size).contains(&my)
+                    && self.module(mx - QUIET_ZONE, my - QUIET_ZONE);
+                pixels.push(if dark { 0x00 } else { 0xFF });
+            }
+        }
+        png::encode(width, width, 1, &pixels)
+    }
+}
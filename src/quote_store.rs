@@ -0,0 +1,265 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::audit::{self, unix_timestamp};
+use crate::events;
+use crate::panic_boundary;
+use crate::pipeline::QuoteResult;
+use crate::OrcaError;
+
+/// Saves and adjustments, which read, change and write a quote file, run one at a time per process.
+static WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// An operator's change to a quote's price, kept as a line item of its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PriceAdjustment {
+    /// Added to the total; negative for a discount.
+    #[pyo3(get)]
+    pub amount: f64,
+    #[pyo3(get)]
+    pub reason: String,
+    #[pyo3(get)]
+    pub operator: Option<String>,
+    #[pyo3(get)]
+    pub total_before: f64,
+    #[pyo3(get)]
+    pub total_after: f64,
+    /// Seconds since the Unix epoch.
+    #[pyo3(get)]
+    pub adjusted_at: f64,
+}
+
+#[pymethods]
+impl PriceAdjustment {
+    fn __str__(&self) -> String {
+        format!(
+            "PriceAdjustment(amount={:+.2}, total=S${:.2}, reason={:?})",
+            self.amount, self.total_after, self.reason
+        )
+    }
+}
+
+/// Quotes kept by ID as JSON files, for adjusting and handing over after the fact
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct QuoteStore {
+    #[pyo3(get)]
+    pub dir: String,
+    /// Where adjustments are recorded as `quote_adjusted` events.
+    #[pyo3(get)]
+    pub audit_log_path: Option<String>,
+}
+
+/// Quote IDs name files; anything but letters, digits, `-` and `_` could escape the store.
+fn check_id(quote_id: &str) -> Result<(), OrcaError> {
+    let valid = !quote_id.is_empty()
+        && quote_id.len() <= 128
+        && quote_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(OrcaError::InvalidConfig {
+            path: "quote_id".to_string(),
+            message: format!("{:?} is not a quote ID", quote_id),
+        })
+    }
+}
+
+/// Write through a temporary file so readers never see half a quote. The
+/// temporary name keeps the target's extension, so the JSON and the preview of
+/// one quote never share it.
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, bytes)?;
+    fs::rename(&temp, path)
+}
+
+/// How an operator changes a total.
+pub enum Change {
+    Delta(f64),
+    NewTotal(f64),
+}
+
+impl QuoteStore {
+    fn path(&self, quote_id: &str, extension: &str) -> PathBuf {
+        Path::new(&self.dir).join(format!("{}.{}", quote_id, extension))
+    }
+
+    /// Keep a quote under its ID, replacing any earlier version. The preview is
+    /// written next to it as a PNG rather than into the JSON.
+    pub fn put(&self, quote_id: &str, quote: &QuoteResult) -> Result<(), OrcaError> {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.write(quote_id, quote)
+    }
+
+    /// `put` for callers already holding `WRITE_LOCK`.
+    fn write(&self, quote_id: &str, quote: &QuoteResult) -> Result<(), OrcaError> {
+        check_id(quote_id)?;
+        fs::create_dir_all(&self.dir)?;
+        let data = serde_json::to_vec_pretty(quote).map_err(io::Error::from)?;
+        write_atomic(&self.path(quote_id, "json"), &data)?;
+        let preview = self.path(quote_id, "png");
+        match &quote.preview_png {
+            Some(png) => write_atomic(&preview, png)?,
+            None => match fs::remove_file(&preview) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    /// The stored quote, or `None` when there is none under `quote_id`.
+    pub fn load(&self, quote_id: &str) -> Result<Option<QuoteResult>, OrcaError> {
+        check_id(quote_id)?;
+        let data = match fs::read(self.path(quote_id, "json")) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut quote: QuoteResult =
+            serde_json::from_slice(&data).map_err(|e| OrcaError::InvalidConfig {
+                path: self.path(quote_id, "json").to_string_lossy().into_owned(),
+                message: e.to_string(),
+            })?;
+        quote.preview_png = fs::read(self.path(quote_id, "png")).ok();
+        Ok(Some(quote))
+    }
+
+    /// Add an adjustment to a stored quote's total and keep the revised quote.
+    /// Its checkout link and off-peak price were for the old total, so both are
+    /// dropped.
+    pub fn adjust(
+        &self,
+        quote_id: &str,
+        change: Change,
+        reason: &str,
+        operator: Option<String>,
+    ) -> Result<QuoteResult, OrcaError> {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut quote = self
+            .load(quote_id)?
+            .ok_or_else(|| OrcaError::FileNotFound(format!("no stored quote {}", quote_id)))?;
+        let total_before = quote.cost.total_cost;
+        let total_after = match change {
+            Change::Delta(delta) => total_before + delta,
+            Change::NewTotal(total) => total,
+        };
+        if !total_after.is_finite() || total_after < 0.0 {
+            return Err(OrcaError::InvalidConfig {
+                path: "adjustment".to_string(),
+                message: format!("would make the total {:.2}", total_after),
+            });
+        }
+        let adjustment = PriceAdjustment {
+            amount: total_after - total_before,
+            reason: reason.to_string(),
+            operator,
+            total_before,
+            total_after,
+            adjusted_at: unix_timestamp(SystemTime::now()),
+        };
+        quote.cost.total_cost = total_after;
+        quote.payment_url = None;
+        quote.off_peak = None;
+        quote.adjustments.push(adjustment.clone());
+        self.write(quote_id, &quote)?;
+
+        let record = json!({
+            "quote_id": quote_id,
+            "amount": adjustment.amount,
+            "total_before": total_before,
+            "total_after": total_after,
+            "reason": adjustment.reason,
+            "operator": adjustment.operator,
+        });
+        if let Some(log_path) = &self.audit_log_path {
+            audit::append_event(Path::new(log_path), "quote_adjusted", record.clone())?;
+        }
+        events::emit("quote.adjusted", Some(quote_id), record);
+        Ok(quote)
+    }
+}
+
+#[pymethods]
+impl QuoteStore {
+    /// Keep a quote under its ID, replacing any earlier version
+    fn save(&self, py: Python<'_>, quote_id: &str, quote: QuoteResult) -> PyResult<()> {
+        Ok(py.allow_threads(|| self.put(quote_id, &quote))?)
+    }
+
+    /// The stored quote, or None when nothing was stored under `quote_id`
+    fn get(&self, py: Python<'_>, quote_id: &str) -> PyResult<Option<QuoteResult>> {
+        Ok(py.allow_threads(|| self.load(quote_id))?)
+    }
+
+    /// Change a stored quote's price by `delta`, or to `new_total`, for `reason`
+    ///
+    /// The change is kept as a line item in `QuoteResult.adjustments` and the
+    /// revised quote, returned for re-notification, replaces the stored one.
+    /// Its `payment_url` and `off_peak` priced the old total and are cleared.
+    /// Each adjustment is recorded in the audit log and emitted as `quote.adjusted`.
+    #[pyo3(signature = (quote_id, reason, *, delta=None, new_total=None, operator=None))]
+    fn apply_manual_adjustment(
+        &self,
+        py: Python<'_>,
+        quote_id: &str,
+        reason: &str,
+        delta: Option<f64>,
+        new_total: Option<f64>,
+        operator: Option<String>,
+    ) -> PyResult<QuoteResult> {
+        panic_boundary::catch(|| {
+            let change = match (delta, new_total) {
+                (Some(delta), None) => Change::Delta(delta),
+                (None, Some(total)) => Change::NewTotal(total),
+                _ => {
+                    return Err(OrcaError::InvalidConfig {
+                        path: "adjustment".to_string(),
+                        message: "give either delta or new_total".to_string(),
+                    }
+                    .into())
+                }
+            };
+            if reason.trim().is_empty() {
+                return Err(OrcaError::InvalidConfig {
+                    path: "reason".to_string(),
+                    message: "is empty".to_string(),
+                }
+                .into());
+            }
+            Ok(py.allow_threads(|| self.adjust(quote_id, change, reason.trim(), operator))?)
+        })
+    }
+
+    fn __str__(&self) -> String {
+        format!("QuoteStore(dir={})", self.dir)
+    }
+}
+
+/// Open a quote store in `directory`; adjustments are audited to `audit_log_path`
+#[pyfunction]
+#[pyo3(signature = (directory, audit_log_path=None))]
+pub fn create_quote_store(
+    directory: String,
+    audit_log_path: Option<String>,
+) -> PyResult<QuoteStore> {
+    panic_boundary::catch(|| {
+        Ok(QuoteStore {
+            dir: directory,
+            audit_log_path,
+        })
+    })
+}
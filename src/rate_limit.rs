@@ -0,0 +1,155 @@
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// Buckets are swept for full ones, which are the same as absent, once there
+/// are more keys than this.
+const SWEEP_KEYS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Per-key token buckets, shared by every handle to a limiter
+#[derive(Clone)]
+#[pyclass]
+pub struct RateLimiter {
+    /// Most tokens a key can save up, i.e. the largest burst it may send.
+    #[pyo3(get)]
+    pub capacity: f64,
+    /// Tokens each key gets back per second, up to `capacity`.
+    #[pyo3(get)]
+    pub refill_per_sec: f64,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        RateLimiter {
+            capacity,
+            refill_per_sec,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Bucket>> {
+        self.buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Tokens `bucket` holds at `now`, refilled since it was last touched.
+    fn level(&self, bucket: Option<&Bucket>, now: Instant) -> f64 {
+        match bucket {
+            Some(bucket) => {
+                let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+                (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity)
+            }
+            None => self.capacity,
+        }
+    }
+
+    /// Take `cost` tokens from `key`'s bucket if it holds them; otherwise the
+    /// seconds until it will.
+    pub fn take(&self, key: &str, cost: f64) -> Result<(), f64> {
+        let now = Instant::now();
+        let mut buckets = self.lock();
+        let level = self.level(buckets.get(key), now);
+        if level < cost {
+            return Err(self.wait(level, cost));
+        }
+        if buckets.len() >= SWEEP_KEYS && !buckets.contains_key(key) {
+            buckets.retain(|_, bucket| self.level(Some(bucket), now) < self.capacity);
+        }
+        buckets.insert(
+            key.to_string(),
+            Bucket {
+                tokens: level - cost,
+                updated: now,
+            },
+        );
+        Ok(())
+    }
+
+    fn wait(&self, level: f64, cost: f64) -> f64 {
+        if cost > self.capacity {
+            f64::INFINITY
+        } else {
+            (cost - level) / self.refill_per_sec
+        }
+    }
+}
+
+#[pymethods]
+impl RateLimiter {
+    /// Take `cost` tokens for `key`; False, taking none, when it has too few
+    #[pyo3(signature = (key, cost=1.0))]
+    fn acquire(&self, key: &str, cost: f64) -> bool {
+        self.take(key, cost).is_ok()
+    }
+
+    /// Seconds until `key` has `cost` tokens; 0 when it has them now
+    #[pyo3(signature = (key, cost=1.0))]
+    fn retry_after(&self, key: &str, cost: f64) -> f64 {
+        let level = self.level(self.lock().get(key), Instant::now());
+        if level >= cost {
+            0.0
+        } else {
+            self.wait(level, cost)
+        }
+    }
+
+    /// Tokens `key` has now
+    fn remaining(&self, key: &str) -> f64 {
+        self.level(self.lock().get(key), Instant::now())
+    }
+
+    /// Refill `key`'s bucket, or every bucket without a key
+    #[pyo3(signature = (key=None))]
+    fn reset(&self, key: Option<&str>) {
+        match key {
+            Some(key) => {
+                self.lock().remove(key);
+            }
+            None => self.lock().clear(),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "RateLimiter(capacity={}, refill_per_sec={})",
+            self.capacity, self.refill_per_sec
+        )
+    }
+}
+
+/// Create a per-key token-bucket rate limiter
+///
+/// Each key, such as a customer's phone number, starts with `capacity` tokens
+/// and gets `refill_per_sec` back every second up to that. State lives in this
+/// process only; copies of the limiter share it, and it is safe to use from
+/// any thread.
+#[pyfunction]
+pub fn create_rate_limiter(capacity: f64, refill_per_sec: f64) -> PyResult<RateLimiter> {
+    panic_boundary::catch(|| {
+        for (path, value) in [("capacity", capacity), ("refill_per_sec", refill_per_sec)] {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(OrcaError::InvalidConfig {
+                    path: path.to_string(),
+                    message: format!("{} is not a positive number", value),
+                }
+                .into());
+            }
+        }
+        Ok(RateLimiter::new(capacity, refill_per_sec))
+    })
+}
@@ -0,0 +1,115 @@
+use pyo3::prelude::*;
+use serde_json::json;
+use std::path::Path;
+
+use crate::events;
+use crate::panic_boundary;
+use crate::pipeline::PipelineConfig;
+use crate::print_history::EstimateCalibration;
+use crate::{
+    compute_cost_breakdown, parse_slicer_output_dir, CostBreakdown, OrcaError, SlicingResult,
+};
+
+/// A quote priced again from its stored G-code, without slicing
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct Requote {
+    #[pyo3(get)]
+    pub gcode_cache_key: String,
+    #[pyo3(get)]
+    pub material: String,
+    /// Read back from the stored G-code's header.
+    #[pyo3(get)]
+    pub slicing: SlicingResult,
+    #[pyo3(get)]
+    pub cost: CostBreakdown,
+    /// The correction from finished prints applied to `slicing`, as for a new quote.
+    #[pyo3(get)]
+    pub calibration: Option<EstimateCalibration>,
+}
+
+#[pymethods]
+impl Requote {
+    fn __str__(&self) -> String {
+        format!(
+            "Requote(key={}, material={}, total=S${:.2})",
+            self.gcode_cache_key, self.material, self.cost.total_cost
+        )
+    }
+}
+
+fn price_stored_gcode(
+    gcode_cache_key: &str,
+    material: String,
+    config: &PipelineConfig,
+) -> Result<Requote, OrcaError> {
+    let cache = config
+        .gcode_cache
+        .as_ref()
+        .ok_or_else(|| OrcaError::InvalidConfig {
+            path: "gcode_cache_dir".to_string(),
+            message: "is not set; there is no stored G-code to price".to_string(),
+        })?;
+    let files = cache.lookup(gcode_cache_key).ok_or_else(|| {
+        OrcaError::FileNotFound(format!("no cached G-code for {}", gcode_cache_key))
+    })?;
+    let entry_dir = files[0].parent().unwrap_or(Path::new(&cache.dir));
+    // A profile that has gone away only costs the length-to-weight conversion
+    // and its filament_cost; the configured prices still apply.
+    let filament = config.filament_profile(&material).ok();
+    let mut slicing = parse_slicer_output_dir(
+        entry_dir,
+        config.filament_spec(&material, filament.as_ref()),
+    )?;
+    slicing.gcode_cache_key = Some(gcode_cache_key.to_string());
+    let calibration = config
+        .print_history
+        .as_ref()
+        .and_then(|history| history.calibrate(&material, &mut slicing));
+    let cost = compute_cost_breakdown(
+        slicing.print_time_minutes,
+        slicing.filament_weight_grams,
+        material.clone(),
+        config.price_per_kg(&material, filament.as_ref()),
+        config.additional_time_hours,
+        config.price_multiplier,
+        config.minimum_price,
+    );
+    Ok(Requote {
+        gcode_cache_key: gcode_cache_key.to_string(),
+        material,
+        slicing,
+        cost,
+        calibration,
+    })
+}
+
+/// Price a quote again from the G-code stored under `gcode_cache_key`
+///
+/// Only pricing runs, with `config`'s current rates, so a price match or a rate
+/// change can be honoured without slicing again. Needs the config's G-code cache.
+#[pyfunction]
+#[pyo3(signature = (gcode_cache_key, material, config, quote_id=None))]
+pub fn requote(
+    gcode_cache_key: String,
+    material: String,
+    config: PyRef<'_, PipelineConfig>,
+    quote_id: Option<String>,
+) -> PyResult<Requote> {
+    panic_boundary::catch(|| {
+        let material = config.canonical_material(&material);
+        let requote = price_stored_gcode(&gcode_cache_key, material, &config)?;
+        events::emit(
+            "quote.requoted",
+            quote_id.as_deref(),
+            json!({
+                "material": requote.material,
+                "gcode_cache_key": requote.gcode_cache_key,
+                "total_cost": requote.cost.total_cost,
+                "print_time_minutes": requote.slicing.print_time_minutes,
+                "filament_grams": requote.slicing.filament_weight_grams,
+            }),
+        );
+        Ok(requote)
+    })
+}
@@ -0,0 +1,1095 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::events::EVENT_TYPES;
+use crate::{panic_boundary, OrcaError};
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Shape of one attribute as Python sees it.
+#[derive(Clone, Copy)]
+enum Ty {
+    Str,
+    /// Unsigned: every integer attribute is a count, size or duration.
+    Int,
+    Num,
+    Bool,
+    /// Binary data, e.g. a PNG; base64 in JSON.
+    Bytes,
+    /// Arbitrary JSON.
+    Any,
+    Enum(&'static [&'static str]),
+    Ref(&'static str),
+    Opt(&'static Ty),
+    List(&'static Ty),
+    Map(&'static Ty),
+    /// Fixed-length tuple of numbers, e.g. bed size or model dimensions.
+    Tuple(usize),
+}
+
+use Ty::*;
+
+/// A pyclass and the attributes it exposes, in declaration order.
+struct TypeDoc {
+    name: &'static str,
+    description: &'static str,
+    fields: &'static [(&'static str, Ty)],
+}
+
+// Kept in step with the pyclass definitions by tests/unit/test_schemas.py.
+const TYPES: &[TypeDoc] = &[
+    TypeDoc {
+        name: "QuoteResult",
+        description: "Outcome of a full quote: the printer and profiles used, slicing output and price",
+        fields: &[
+            ("material", Str),
+            ("printer", Opt(&Str)),
+            ("machine_profile", Str),
+            ("process_profile", Str),
+            ("filament_profile", Str),
+            ("options", Ref("PrintOptions")),
+            ("model", Ref("ModelInfo")),
+            ("dimensions", Opt(&Tuple(3))),
+            ("bed_size", Opt(&Tuple(2))),
+            ("slicing", Ref("SlicingResult")),
+            ("cost", Ref("CostBreakdown")),
+            ("stage_timings_ms", Map(&Num)),
+            ("payment_url", Opt(&Str)),
+            ("lead_time", Opt(&Ref("LeadTime"))),
+            ("shipping", Opt(&Ref("ShippingRate"))),
+            ("preview_png", Opt(&Bytes)),
+            ("preview_source", Opt(&Enum(&["gcode", "mesh"]))),
+            ("gcode_transforms", List(&Str)),
+            ("adjustments", List(&Ref("PriceAdjustment"))),
+            ("off_peak", Opt(&Ref("OffPeakPrice"))),
+            ("calibration", Opt(&Ref("EstimateCalibration"))),
+            ("stability", Opt(&Ref("Stability"))),
+            ("warnings", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "OrderQuote",
+        description: "Several parts quoted as one order, with setup and the minimum price counted once",
+        fields: &[
+            ("parts", List(&Ref("OrderPart"))),
+            ("total_quantity", Int),
+            ("print_time_minutes", Int),
+            ("filament_grams", Num),
+            ("parts_cost", Num),
+            ("setup_cost", Num),
+            ("subtotal", Num),
+            ("total_cost", Num),
+            ("minimum_applied", Bool),
+        ],
+    },
+    TypeDoc {
+        name: "OrderPart",
+        description: "One model of an order: its single-unit quote and what `quantity` copies cost",
+        fields: &[
+            ("model_path", Str),
+            ("quantity", Int),
+            ("quote", Ref("QuoteResult")),
+            ("unit_cost", Num),
+            ("print_time_minutes", Int),
+            ("plating", Opt(&Ref("PlatePlan"))),
+            ("line_total", Num),
+        ],
+    },
+    TypeDoc {
+        name: "MaterialComparison",
+        description: "One model quoted in several materials, for the customer to choose from",
+        fields: &[
+            ("model_path", Str),
+            ("materials", List(&Ref("MaterialQuote"))),
+            ("errors", Map(&Str)),
+            ("cheapest", Opt(&Str)),
+            ("fastest", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "MaterialQuote",
+        description: "One row of a material comparison",
+        fields: &[
+            ("material", Str),
+            ("total_cost", Num),
+            ("print_time_minutes", Int),
+            ("filament_grams", Num),
+            ("quote", Ref("QuoteResult")),
+        ],
+    },
+    TypeDoc {
+        name: "Requote",
+        description: "A quote priced again from its stored G-code, without slicing",
+        fields: &[
+            ("gcode_cache_key", Str),
+            ("material", Str),
+            ("slicing", Ref("SlicingResult")),
+            ("cost", Ref("CostBreakdown")),
+            ("calibration", Opt(&Ref("EstimateCalibration"))),
+        ],
+    },
+    TypeDoc {
+        name: "PrintOptions",
+        description: "Print options a customer picked, as applied to the slice",
+        fields: &[
+            ("layer_height", Opt(&Num)),
+            ("infill_percent", Opt(&Num)),
+            ("supports", Opt(&Bool)),
+            ("color", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "PlatePlan",
+        description: "How copies of one model are spread over build plates",
+        fields: &[
+            ("quantity", Int),
+            ("copies_per_plate", Int),
+            ("plates", Int),
+            ("print_time_minutes", Int),
+        ],
+    },
+    TypeDoc {
+        name: "CostBreakdown",
+        description: "Cost breakdown of a quote: material and machine time, before and after the minimum price",
+        fields: &[
+            ("material_type", Str),
+            ("filament_kg", Num),
+            ("filament_grams", Num),
+            ("print_time_hours", Num),
+            ("print_time_minutes", Int),
+            ("price_per_kg", Num),
+            ("material_cost", Num),
+            ("time_cost", Num),
+            ("subtotal", Num),
+            ("total_cost", Num),
+            ("minimum_applied", Bool),
+            ("markup_percentage", Num),
+        ],
+    },
+    TypeDoc {
+        name: "SlicingResult",
+        description: "Print time and filament use reported by the slicer",
+        fields: &[
+            ("print_time_minutes", Int),
+            ("filament_weight_grams", Num),
+            ("layer_count", Opt(&Int)),
+            ("filament_length_mm", Opt(&Num)),
+            ("gcode_cache_key", Opt(&Str)),
+            ("slicer_attempts", List(&Ref("SlicerAttempt"))),
+        ],
+    },
+    TypeDoc {
+        name: "SlicerAttempt",
+        description: "One run of the slicer for a job; runs after the first retried a crash",
+        fields: &[
+            ("attempt", Int),
+            ("duration_ms", Num),
+            ("error", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "SlicerFailure",
+        description: "Why the slicer exited with an error, with a message for the customer",
+        fields: &[
+            (
+                "category",
+                Enum(&[
+                    "model_unloadable",
+                    "out_of_build_volume",
+                    "profile_incompatible",
+                    "crash",
+                    "resource_limit",
+                    "unknown",
+                ]),
+            ),
+            ("message", Str),
+            ("detail", Str),
+            ("stderr", Str),
+            ("exit_status", Str),
+            ("attempts", Int),
+        ],
+    },
+    TypeDoc {
+        name: "ModelInfo",
+        description: "Result of validating an uploaded 3D model",
+        fields: &[
+            ("file_type", Str),
+            ("file_size", Int),
+            ("is_valid", Bool),
+            ("error_message", Opt(&Str)),
+            ("archive_entry", Opt(&Str)),
+            ("dimensions_mm", Opt(&Tuple(3))),
+            ("overhang_area_mm2", Opt(&Num)),
+            ("needs_supports", Opt(&Bool)),
+            ("stability", Opt(&Ref("Stability"))),
+            ("obj_materials", Opt(&Ref("ObjMaterials"))),
+            ("step_summary", Opt(&Ref("StepSummary"))),
+            ("warnings", List(&Str)),
+            ("sha256", Opt(&Str)),
+            ("triangle_count", Opt(&Int)),
+            ("line_count", Opt(&Int)),
+        ],
+    },
+    TypeDoc {
+        name: "Stability",
+        description: "How likely a model is to tip over or come off the bed mid-print",
+        fields: &[
+            ("center_of_mass_mm", Tuple(3)),
+            ("footprint_area_mm2", Num),
+            ("risk_score", Num),
+            ("recommendation", Opt(&Str)),
+            ("warning", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "MeshIntegrity",
+        description: "Holes, non-manifold edges and inconsistent faces found in a model's mesh",
+        fields: &[
+            ("triangles", Int),
+            ("vertices", Int),
+            ("boundary_edges", Int),
+            ("holes", Int),
+            ("non_manifold_edges", Int),
+            ("flipped_faces", Int),
+            ("duplicate_faces", Int),
+            ("is_watertight", Bool),
+            ("problems", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "MeshStats",
+        description: "Counts describing a model's mesh",
+        fields: &[
+            ("triangles", Int),
+            ("vertices", Int),
+            ("degenerate_triangles", Int),
+            ("duplicate_vertices", Int),
+            ("shells", Int),
+        ],
+    },
+    TypeDoc {
+        name: "ObjObject",
+        description: "One object of a multi-object OBJ file, with its triangle count and bounding box",
+        fields: &[
+            ("name", Str),
+            ("triangles", Int),
+            ("min_mm", Tuple(3)),
+            ("max_mm", Tuple(3)),
+            ("dimensions_mm", Tuple(3)),
+        ],
+    },
+    TypeDoc {
+        name: "ObjMaterials",
+        description: "Material libraries and materials an OBJ file names, which quoting ignores",
+        fields: &[
+            ("libraries", List(&Str)),
+            ("missing_libraries", List(&Str)),
+            ("materials", List(&Str)),
+            ("warning", Str),
+        ],
+    },
+    TypeDoc {
+        name: "StepSummary",
+        description: "What a STEP file holds: one part, or an assembly of several",
+        fields: &[
+            ("products", Int),
+            ("assembly_usages", Int),
+            ("solids", Int),
+            ("is_assembly", Bool),
+        ],
+    },
+    TypeDoc {
+        name: "MeshRepair",
+        description: "What a mesh repair changed, and the repaired mesh's integrity",
+        fields: &[
+            ("triangles_before", Int),
+            ("triangles_after", Int),
+            ("degenerate_removed", Int),
+            ("duplicates_removed", Int),
+            ("faces_flipped", Int),
+            ("holes_filled", Int),
+            ("integrity", Ref("MeshIntegrity")),
+        ],
+    },
+    TypeDoc {
+        name: "Decimation",
+        description: "How far a mesh was simplified, and where the result was written",
+        fields: &[
+            ("triangles_before", Int),
+            ("triangles_after", Int),
+            ("cell_size_mm", Num),
+            ("output_path", Str),
+        ],
+    },
+    TypeDoc {
+        name: "PipelineConfig",
+        description: "Everything the quote pipeline needs, loaded once and reused across jobs",
+        fields: &[
+            ("slicer_path", Str),
+            ("profiles_dir", Str),
+            ("machine_profile", Str),
+            ("process_profile", Str),
+            ("fleet", Opt(&Ref("Fleet"))),
+            ("catalog", Ref("MaterialCatalog")),
+            ("material_prices", Map(&Num)),
+            ("default_price_per_kg", Num),
+            ("additional_time_hours", Num),
+            ("price_multiplier", Num),
+            ("minimum_price", Num),
+            ("work_dir", Opt(&Str)),
+            ("gcode_cache", Opt(&Ref("GcodeCache"))),
+            ("stripe", Opt(&Ref("StripeConfig"))),
+            ("farm", Opt(&Ref("FarmMonitor"))),
+            ("shipping", Opt(&Ref("ShippingConfig"))),
+            ("plate_spacing_mm", Num),
+            ("plate_overhead_minutes", Num),
+            ("preview_size", Opt(&Int)),
+            ("postprocess", Opt(&Ref("PostProcessConfig"))),
+            ("quote_store", Opt(&Ref("QuoteStore"))),
+            ("time_of_use", Opt(&Ref("TimeOfUsePricing"))),
+            ("inventory", Opt(&Ref("Inventory"))),
+            ("print_history", Opt(&Ref("PrintHistory"))),
+            ("require_watertight", Bool),
+            ("convert_ascii_stl", Bool),
+            ("max_triangles", Opt(&Int)),
+            ("validation_limits", Ref("ValidationLimits")),
+            ("malware_scanner", Opt(&Ref("MalwareScanner"))),
+            ("slicer_timeout_secs", Num),
+            ("slicer_retry", Opt(&Ref("SlicerRetryPolicy"))),
+            ("slicer_limits", Opt(&Ref("SlicerLimits"))),
+        ],
+    },
+    TypeDoc {
+        name: "SlicerRetryPolicy",
+        description: "How often, and how long apart, a slicer run that crashed is run again",
+        fields: &[
+            ("max_attempts", Int),
+            ("backoff_secs", Num),
+            ("backoff_multiplier", Num),
+            ("transient_patterns", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "SlicerLimits",
+        description: "What one slicer process may use of the host",
+        fields: &[
+            ("cpu_secs", Opt(&Int)),
+            ("memory_mb", Opt(&Int)),
+            ("nice", Opt(&Int)),
+            ("cgroup", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "Fleet",
+        description: "Printers available for quoting, in configuration order",
+        fields: &[("printers", List(&Ref("FleetPrinter")))],
+    },
+    TypeDoc {
+        name: "FleetPrinter",
+        description: "A physical printer with the machine profile that drives it and what it can print",
+        fields: &[
+            ("name", Str),
+            ("machine_profile", Str),
+            ("process_profile", Opt(&Str)),
+            ("nozzle_diameter", Opt(&Num)),
+            ("bed_size", Opt(&Tuple(2))),
+            ("max_height", Opt(&Num)),
+            ("materials", List(&Str)),
+            ("status_provider", Opt(&Enum(&["moonraker", "octoprint"]))),
+            ("status_url", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "MaterialCatalog",
+        description: "Canonical materials, looked up by name or alias",
+        fields: &[("materials", List(&Ref("Material")))],
+    },
+    TypeDoc {
+        name: "Material",
+        description: "Canonical printing material with its physical properties and default price",
+        fields: &[
+            ("name", Str),
+            ("display_name", Str),
+            ("aliases", List(&Str)),
+            ("density", Num),
+            ("diameter", Num),
+            ("colors", List(&Str)),
+            ("price_per_kg", Num),
+        ],
+    },
+    TypeDoc {
+        name: "GcodeCache",
+        description: "Content-addressed store of sliced G-code with a size limit",
+        fields: &[("dir", Str), ("max_bytes", Int)],
+    },
+    TypeDoc {
+        name: "ValidationLimits",
+        description: "Upload limits the validators enforce; 0 lifts a limit",
+        fields: &[
+            ("max_file_bytes", Int),
+            ("max_triangles", Int),
+            ("max_lines", Int),
+        ],
+    },
+    TypeDoc {
+        name: "MalwareScanner",
+        description: "An external malware scanner every upload is run through before slicing",
+        fields: &[
+            ("command", List(&Str)),
+            ("clamd_socket", Opt(&Str)),
+            ("timeout_secs", Num),
+        ],
+    },
+    TypeDoc {
+        name: "StripeConfig",
+        description: "Stripe account used to take payment for quotes; the API key is never exposed",
+        fields: &[
+            ("currency", Str),
+            ("success_url", Str),
+            ("cancel_url", Opt(&Str)),
+            ("api_base", Str),
+            ("timeout_secs", Num),
+        ],
+    },
+    TypeDoc {
+        name: "ShippingConfig",
+        description: "Where shipping rates come from and how a quoted job is packed; the API key is never exposed",
+        fields: &[
+            ("provider", Enum(&["easypost", "callback"])),
+            ("padding_mm", Num),
+            ("packaging_grams", Num),
+            ("carriers", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "PostProcessConfig",
+        description: "Changes made to sliced G-code before it is cached or sent to a printer",
+        fields: &[
+            ("start_gcode", Opt(&Str)),
+            ("end_gcode", Opt(&Str)),
+            ("strip_thumbnails", Bool),
+            ("script", List(&Str)),
+            ("script_timeout_secs", Num),
+            ("audit_log_path", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "PriceAdjustment",
+        description: "An operator's change to a quote's price, kept as a line item of its own",
+        fields: &[
+            ("amount", Num),
+            ("reason", Str),
+            ("operator", Opt(&Str)),
+            ("total_before", Num),
+            ("total_after", Num),
+            ("adjusted_at", Num),
+        ],
+    },
+    TypeDoc {
+        name: "QuoteStore",
+        description: "Quotes kept by ID as JSON files, for adjusting and handing over after the fact",
+        fields: &[("dir", Str), ("audit_log_path", Opt(&Str))],
+    },
+    TypeDoc {
+        name: "ShippingRate",
+        description: "A carrier's price for delivering a parcel",
+        fields: &[
+            ("carrier", Str),
+            ("service", Str),
+            ("amount", Num),
+            ("currency", Str),
+            ("delivery_days", Opt(&Int)),
+        ],
+    },
+    TypeDoc {
+        name: "Parcel",
+        description: "The box a printed job ships in: its bounding box plus padding, and its packed weight",
+        fields: &[
+            ("length_mm", Num),
+            ("width_mm", Num),
+            ("height_mm", Num),
+            ("weight_grams", Num),
+        ],
+    },
+    TypeDoc {
+        name: "LedgerConfig",
+        description: "Where completed quotes are booked: a rotating CSV file or a Google Sheet",
+        fields: &[("backend", Enum(&["csv", "google_sheets"]))],
+    },
+    TypeDoc {
+        name: "OctoPrintConfig",
+        description: "OctoPrint server to send accepted quotes to",
+        fields: &[
+            ("url", Str),
+            ("folder", Opt(&Str)),
+            ("start_print", Bool),
+            ("timeout_secs", Num),
+        ],
+    },
+    TypeDoc {
+        name: "OctoPrintUpload",
+        description: "A G-code file stored on OctoPrint",
+        fields: &[
+            ("remote_path", Str),
+            ("done", Bool),
+            ("print_started", Bool),
+            ("resource", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "MoonrakerConfig",
+        description: "Moonraker (Klipper) server to send accepted quotes to",
+        fields: &[
+            ("url", Str),
+            ("folder", Opt(&Str)),
+            ("start_print", Bool),
+            ("timeout_secs", Num),
+            ("tolerance_percent", Num),
+        ],
+    },
+    TypeDoc {
+        name: "MoonrakerUpload",
+        description: "A G-code file stored on Moonraker, with Klipper's time estimate checked against the quoted one",
+        fields: &[
+            ("remote_path", Str),
+            ("print_started", Bool),
+            ("klipper_estimate_seconds", Opt(&Num)),
+            ("quoted_estimate_seconds", Opt(&Num)),
+            ("discrepancy_percent", Opt(&Num)),
+            ("flagged", Bool),
+        ],
+    },
+    TypeDoc {
+        name: "FarmMonitor",
+        description: "Fleet printers polled for their current load, and how lead times are worked out",
+        fields: &[
+            ("fleet", Ref("Fleet")),
+            ("max_age_secs", Num),
+            ("timeout_secs", Num),
+            ("queued_job_hours", Num),
+            ("handling_hours", Num),
+            ("calendar", Opt(&Ref("BusinessCalendar"))),
+        ],
+    },
+    TypeDoc {
+        name: "Inventory",
+        description: "Filament on hand, from a local stock table or a Spoolman server",
+        fields: &[
+            ("table_path", Opt(&Str)),
+            ("spoolman_url", Opt(&Str)),
+            ("low_stock_grams", Num),
+            ("max_age_secs", Num),
+            ("timeout_secs", Num),
+        ],
+    },
+    TypeDoc {
+        name: "StockLevel",
+        description: "Filament on hand of one material, and color when stock is kept per color",
+        fields: &[
+            ("material", Str),
+            ("color", Opt(&Str)),
+            ("remaining_grams", Num),
+        ],
+    },
+    TypeDoc {
+        name: "MaterialAvailability",
+        description: "Whether a material can be quoted, and how much of it is on hand",
+        fields: &[
+            ("name", Str),
+            ("remaining_grams", Opt(&Num)),
+            ("in_stock", Bool),
+            ("low_stock", Bool),
+            ("colors_in_stock", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "TimeOfUsePricing",
+        description: "Hours of the week when machine time is charged at a reduced rate",
+        fields: &[
+            ("windows", List(&Tuple(3))),
+            ("days", List(&Int)),
+            ("utc_offset_hours", Num),
+        ],
+    },
+    TypeDoc {
+        name: "OffPeakPrice",
+        description: "The price of a quote when its print is started in off-peak hours",
+        fields: &[
+            ("start_at", Num),
+            ("start_time", Str),
+            ("rate_factor", Num),
+            ("time_cost", Num),
+            ("total_cost", Num),
+            ("savings", Num),
+            ("promised_date", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "PrintHistory",
+        description: "Slicer estimates next to what finished prints actually took, per material",
+        fields: &[
+            ("path", Str),
+            ("window", Int),
+            ("min_samples", Int),
+        ],
+    },
+    TypeDoc {
+        name: "CorrectionFactor",
+        description: "How far a material's finished prints strayed from the slicer's estimates",
+        fields: &[
+            ("material", Str),
+            ("samples", Int),
+            ("time_factor", Num),
+            ("filament_factor", Num),
+        ],
+    },
+    TypeDoc {
+        name: "EstimateCalibration",
+        description: "A correction applied to a quote's slicing estimates, with the slicer's own figures",
+        fields: &[
+            ("factor", Ref("CorrectionFactor")),
+            ("slicer_print_time_minutes", Int),
+            ("slicer_filament_grams", Num),
+        ],
+    },
+    TypeDoc {
+        name: "BusinessCalendar",
+        description: "When the shop works: the hours handling and hand-over can happen in",
+        fields: &[
+            ("utc_offset_hours", Num),
+            ("open_hour", Int),
+            ("close_hour", Int),
+            ("working_days", List(&Int)),
+            ("holidays", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "PrinterStatus",
+        description: "What a fleet printer is doing right now, as reported by its host",
+        fields: &[
+            ("printer", Str),
+            (
+                "state",
+                Enum(&["idle", "printing", "paused", "error", "offline", "unknown"]),
+            ),
+            ("remaining_seconds", Opt(&Num)),
+            ("queued_jobs", Int),
+            ("available_in_seconds", Opt(&Num)),
+        ],
+    },
+    TypeDoc {
+        name: "LeadTime",
+        description: "When a quoted job is likely to be ready, given what the farm is doing",
+        fields: &[
+            ("printer", Str),
+            ("wait_seconds", Num),
+            ("print_seconds", Num),
+            ("handling_seconds", Num),
+            ("lead_time_seconds", Num),
+            ("ready_at", Num),
+            ("promised_at", Opt(&Num)),
+            ("promised_date", Opt(&Str)),
+            ("printers", List(&Ref("PrinterStatus"))),
+        ],
+    },
+    TypeDoc {
+        name: "QuoteEvent",
+        description: "Something that happened to a quote, as delivered to every sink",
+        fields: &[
+            ("event_type", Enum(&EVENT_TYPES)),
+            ("quote_id", Opt(&Str)),
+            ("timestamp", Num),
+            ("data", Any),
+        ],
+    },
+    TypeDoc {
+        name: "WebhookEvent",
+        description: "What an incoming payment or approval callback means for a quote",
+        fields: &[
+            ("source", Enum(&["stripe", "telegram"])),
+            ("event_type", Str),
+            (
+                "action",
+                Enum(&[
+                    "payment.succeeded",
+                    "payment.failed",
+                    "quote.approved",
+                    "quote.rejected",
+                    "ignored",
+                ]),
+            ),
+            ("quote_id", Opt(&Str)),
+            ("event_id", Str),
+            ("amount", Opt(&Num)),
+            ("currency", Opt(&Str)),
+            ("user", Opt(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "QuoteToken",
+        description: "A verified customer portal token: the quote it grants access to and until when",
+        fields: &[("quote_id", Str), ("expires_at", Num)],
+    },
+    TypeDoc {
+        name: "QueueStatus",
+        description: "Jobs ahead of a new quote and how long it will likely take",
+        fields: &[
+            ("queued", Int),
+            ("running", Int),
+            ("parallelism", Int),
+            ("avg_slice_seconds", Opt(&Num)),
+            ("estimated_wait_seconds", Opt(&Num)),
+        ],
+    },
+    TypeDoc {
+        name: "HealthReport",
+        description: "Status of every dependency the quote service needs",
+        fields: &[
+            ("checks", List(&Ref("DependencyStatus"))),
+            ("healthy", Bool),
+        ],
+    },
+    TypeDoc {
+        name: "DependencyStatus",
+        description: "Result of checking one dependency",
+        fields: &[
+            ("name", Str),
+            ("status", Enum(&["ok", "error", "skipped"])),
+            ("detail", Str),
+            ("duration_ms", Num),
+        ],
+    },
+    TypeDoc {
+        name: "WarmUpReport",
+        description: "What warm_up prepared and how long each step took",
+        fields: &[
+            ("lazies_ms", Num),
+            ("profiles_loaded", Int),
+            ("profiles_ms", Num),
+            ("calibration_ms", Opt(&Num)),
+        ],
+    },
+    TypeDoc {
+        name: "CleanupStats",
+        description: "File cleanup statistics",
+        fields: &[
+            ("files_cleaned", Int),
+            ("bytes_freed", Int),
+            ("files_by_extension", Map(&Int)),
+            ("bytes_by_extension", Map(&Int)),
+            ("oldest_deleted", Opt(&Num)),
+            ("newest_deleted", Opt(&Num)),
+            ("errors", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "ValidationCacheStats",
+        description: "Hit and miss counts of the validation cache",
+        fields: &[
+            ("hits", Int),
+            ("misses", Int),
+            ("entries", Int),
+            ("capacity", Int),
+        ],
+    },
+    TypeDoc {
+        name: "Profile",
+        description: "Typed view of an OrcaSlicer machine, filament, or process profile",
+        fields: &[
+            ("name", Str),
+            ("profile_type", Str),
+            ("path", Str),
+            ("inherits", Opt(&Str)),
+            ("source", Opt(&Str)),
+            ("instantiation", Bool),
+            ("printer_model", Opt(&Str)),
+            ("nozzle_diameter", Opt(&Num)),
+            ("bed_size", Opt(&Tuple(2))),
+            ("printable_height", Opt(&Num)),
+            ("layer_height", Opt(&Num)),
+            ("filament_type", Opt(&Str)),
+            ("filament_density", Opt(&Num)),
+            ("filament_diameter", Opt(&Num)),
+            ("filament_cost", Opt(&Num)),
+            ("compatible_printers", List(&Str)),
+            ("inheritance_chain", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "ProfilePaths",
+        description: "Machine, process and filament profiles chosen for one job",
+        fields: &[
+            ("machine", Str),
+            ("process", Str),
+            ("filament", Str),
+            ("nozzle_diameter", Opt(&Num)),
+        ],
+    },
+    TypeDoc {
+        name: "FilamentResolution",
+        description: "Filament profile chosen for a material and the rule that selected it",
+        fields: &[("material", Str), ("path", Str), ("source", Str)],
+    },
+    TypeDoc {
+        name: "MachineListing",
+        description: "Machine profile summary for selection menus",
+        fields: &[
+            ("name", Str),
+            ("file_name", Str),
+            ("path", Str),
+            ("printer_model", Opt(&Str)),
+            ("nozzle_diameter", Opt(&Num)),
+            ("bed_size", Opt(&Tuple(2))),
+            ("printable_height", Opt(&Num)),
+            ("min_layer_height", Opt(&Num)),
+            ("max_layer_height", Opt(&Num)),
+        ],
+    },
+    TypeDoc {
+        name: "ProcessListing",
+        description: "Process profile summary for selection menus",
+        fields: &[
+            ("name", Str),
+            ("file_name", Str),
+            ("path", Str),
+            ("layer_height", Opt(&Num)),
+            ("nozzle_diameters", List(&Num)),
+            ("compatible_printers", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "ProfileLintReport",
+        description: "Result of linting every profile below a directory",
+        fields: &[
+            ("files_checked", Int),
+            ("issues", List(&Ref("LintIssue"))),
+            ("is_ok", Bool),
+            ("error_count", Int),
+            ("warning_count", Int),
+        ],
+    },
+    TypeDoc {
+        name: "LintIssue",
+        description: "A single problem found while linting a profile tree",
+        fields: &[
+            ("path", Str),
+            ("severity", Enum(&["error", "warning"])),
+            ("code", Str),
+            ("message", Str),
+        ],
+    },
+    TypeDoc {
+        name: "CompatibilityReport",
+        description: "Whether a machine, filament and process profile can be sliced together",
+        fields: &[
+            ("machine", Str),
+            ("filament", Str),
+            ("process", Str),
+            ("issues", List(&Ref("LintIssue"))),
+            ("is_compatible", Bool),
+        ],
+    },
+    TypeDoc {
+        name: "BundleImport",
+        description: "Profiles written to (or skipped in) the profiles directory by a bundle import",
+        fields: &[
+            ("bundle_type", Opt(&Str)),
+            ("imported", List(&Str)),
+            ("skipped", List(&Str)),
+        ],
+    },
+    TypeDoc {
+        name: "VendorSync",
+        description: "Outcome of a vendor profile sync",
+        fields: &[
+            ("vendor", Str),
+            ("git_ref", Str),
+            ("version", Opt(&Str)),
+            ("updated", List(&Str)),
+            ("unchanged", Int),
+            ("removed", List(&Str)),
+        ],
+    },
+];
+
+fn type_doc(name: &str) -> &'static TypeDoc {
+    TYPES
+        .iter()
+        .find(|t| t.name == name)
+        .unwrap_or_else(|| panic!("schema references unknown type {}", name))
+}
+
+fn ty_schema(ty: &Ty, refs: &mut Vec<&'static str>) -> Value {
+    match ty {
+        Str => json!({"type": "string"}),
+        Int => json!({"type": "integer", "minimum": 0}),
+        Num => json!({"type": "number"}),
+        Bool => json!({"type": "boolean"}),
+        Bytes => json!({"type": "string", "contentEncoding": "base64"}),
+        Any => json!({}),
+        Enum(values) => json!({"type": "string", "enum": values}),
+        Ref(name) => {
+            if !refs.contains(name) {
+                refs.push(name);
+            }
+            json!({"$ref": format!("#/$defs/{}", name)})
+        }
+        Opt(inner) => {
+            let mut schema = ty_schema(inner, refs);
+            match schema
+                .get("type")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+            {
+                Some(kind) => {
+                    schema["type"] = json!([kind, "null"]);
+                    if let Some(values) = schema.get_mut("enum").and_then(Value::as_array_mut) {
+                        values.push(Value::Null);
+                    }
+                    schema
+                }
+                None => json!({"anyOf": [schema, {"type": "null"}]}),
+            }
+        }
+        List(inner) => json!({"type": "array", "items": ty_schema(inner, refs)}),
+        Map(inner) => json!({"type": "object", "additionalProperties": ty_schema(inner, refs)}),
+        Tuple(len) => json!({
+            "type": "array",
+            "prefixItems": vec![json!({"type": "number"}); *len],
+            "items": false,
+            "minItems": len,
+            "maxItems": len,
+        }),
+    }
+}
+
+fn object_schema(doc: &TypeDoc, refs: &mut Vec<&'static str>) -> Map<String, Value> {
+    let properties: Map<String, Value> = doc
+        .fields
+        .iter()
+        .map(|(name, ty)| (name.to_string(), ty_schema(ty, refs)))
+        .collect();
+    let mut schema = Map::new();
+    schema.insert("title".into(), json!(doc.name));
+    schema.insert("description".into(), json!(doc.description));
+    schema.insert("type".into(), json!("object"));
+    schema.insert("properties".into(), Value::Object(properties));
+    let required: Vec<&str> = doc.fields.iter().map(|(name, _)| *name).collect();
+    schema.insert("required".into(), json!(required));
+    schema.insert("additionalProperties".into(), json!(false));
+    schema
+}
+
+/// A standalone schema document: the type itself plus every type it refers to under `$defs`.
+fn document(doc: &TypeDoc) -> Value {
+    let mut refs = Vec::new();
+    let mut schema = object_schema(doc, &mut refs);
+    let mut defs = Map::new();
+    let mut next = 0;
+    while next < refs.len() {
+        let name = refs[next];
+        next += 1;
+        if name != doc.name && !defs.contains_key(name) {
+            let def = object_schema(type_doc(name), &mut refs);
+            defs.insert(name.to_string(), Value::Object(def));
+        }
+    }
+
+    schema.insert("$schema".into(), json!(DIALECT));
+    schema.insert("$id".into(), json!(format!("{}.schema.json", doc.name)));
+    if !defs.is_empty() {
+        schema.insert("$defs".into(), Value::Object(defs));
+    }
+    Value::Object(schema)
+}
+
+fn all_schemas() -> Map<String, Value> {
+    TYPES
+        .iter()
+        .map(|doc| (doc.name.to_string(), document(doc)))
+        .collect()
+}
+
+fn write_schemas(dir: &Path, schemas: &Map<String, Value>) -> Result<(), OrcaError> {
+    fs::create_dir_all(dir)?;
+    for (name, schema) in schemas {
+        let text = serde_json::to_string_pretty(schema).expect("schemas are plain JSON");
+        fs::write(dir.join(format!("{}.schema.json", name)), text + "\n")?;
+    }
+    Ok(())
+}
+
+/// JSON Schema (draft 2020-12) documents for every result and config type, keyed by type name
+///
+/// With `directory`, each document is also written there as `<Name>.schema.json`.
+#[pyfunction]
+#[pyo3(signature = (directory=None))]
+pub fn export_schemas(py: Python<'_>, directory: Option<String>) -> PyResult<PyObject> {
+    panic_boundary::catch(|| {
+        let schemas = all_schemas();
+        if let Some(dir) = directory {
+            write_schemas(Path::new(&dir), &schemas)?;
+        }
+        py.import("json")?
+            .call_method1("loads", (Value::Object(schemas).to_string(),))
+            .map(Into::into)
+    })
+}
+
+/// A Python value converted as its schema describes it: objects become dicts,
+/// tuples lists, bytes base64 text, and everything else is kept as it is.
+fn ty_value(py: Python<'_>, value: &PyAny, ty: &Ty) -> PyResult<PyObject> {
+    match ty {
+        Ref(name) => object_dict(py, value, type_doc(name)).map(Into::into),
+        Opt(_) if value.is_none() => Ok(py.None()),
+        Opt(inner) => ty_value(py, value, inner),
+        List(inner) => {
+            let items = value
+                .iter()?
+                .map(|item| ty_value(py, item?, inner))
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(PyList::new(py, items).into())
+        }
+        Map(inner) => {
+            let dict = PyDict::new(py);
+            for (key, item) in value.downcast::<PyDict>()? {
+                dict.set_item(key, ty_value(py, item, inner)?)?;
+            }
+            Ok(dict.into())
+        }
+        Bytes => Ok(STANDARD.encode(value.extract::<&[u8]>()?).into_py(py)),
+        Tuple(_) => Ok(PyList::new(py, value.iter()?.collect::<PyResult<Vec<_>>>()?).into()),
+        Str | Int | Num | Bool | Any | Enum(_) => Ok(value.into()),
+    }
+}
+
+fn object_dict<'py>(py: Python<'py>, obj: &PyAny, doc: &TypeDoc) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    for (name, ty) in doc.fields {
+        dict.set_item(*name, ty_value(py, obj.getattr(*name)?, ty)?)?;
+    }
+    Ok(dict)
+}
+
+/// Every attribute of a result or config object as a plain dict, nested objects included
+///
+/// Keys are the properties of the type's JSON Schema from `export_schemas`, so
+/// the dict is ready for `json.dumps` and has the same shape for a `QuoteResult`
+/// however it was produced.
+#[pyfunction]
+pub fn to_dict<'py>(py: Python<'py>, obj: &PyAny) -> PyResult<&'py PyDict> {
+    panic_boundary::catch(|| {
+        let name = obj.get_type().name()?;
+        let doc = TYPES
+            .iter()
+            .find(|doc| doc.name == name)
+            .ok_or_else(|| PyTypeError::new_err(format!("{} has no schema", name)))?;
+        object_dict(py, obj, doc)
+    })
+}
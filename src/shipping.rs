@@ -0,0 +1,404 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::geometry::model_dimensions;
+use crate::http_upload::{read_json, request_error};
+use crate::panic_boundary;
+use crate::OrcaError;
+
+const MM_PER_INCH: f64 = 25.4;
+const GRAMS_PER_OUNCE: f64 = 28.349_523_125;
+
+/// A carrier's price for delivering a parcel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ShippingRate {
+    #[pyo3(get)]
+    pub carrier: String,
+    #[pyo3(get)]
+    pub service: String,
+    #[pyo3(get)]
+    pub amount: f64,
+    /// Three-letter ISO currency code, as the provider reports it.
+    #[pyo3(get)]
+    pub currency: String,
+    /// Transit time the carrier quotes, when it quotes one.
+    #[pyo3(get)]
+    pub delivery_days: Option<u32>,
+}
+
+#[pymethods]
+impl ShippingRate {
+    fn __str__(&self) -> String {
+        format!(
+            "ShippingRate(carrier={}, service={}, amount={:.2} {})",
+            self.carrier, self.service, self.amount, self.currency
+        )
+    }
+}
+
+/// The box a printed job ships in: its bounding box plus padding, and its packed weight
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct Parcel {
+    /// Longest side first.
+    #[pyo3(get)]
+    pub length_mm: f64,
+    #[pyo3(get)]
+    pub width_mm: f64,
+    #[pyo3(get)]
+    pub height_mm: f64,
+    #[pyo3(get)]
+    pub weight_grams: f64,
+}
+
+#[pymethods]
+impl Parcel {
+    fn __str__(&self) -> String {
+        format!(
+            "Parcel({:.0}x{:.0}x{:.0} mm, {:.0} g)",
+            self.length_mm, self.width_mm, self.height_mm, self.weight_grams
+        )
+    }
+}
+
+#[derive(Clone)]
+enum Backend {
+    EasyPost {
+        api_key: String,
+        api_base: String,
+        from_address: HashMap<String, String>,
+        timeout_secs: f64,
+    },
+    /// Called with `(parcel, destination)`; returns a list of rate dicts.
+    Callback(PyObject),
+}
+
+// Written by hand so the API key never ends up in a log line.
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::EasyPost {
+                api_base,
+                from_address,
+                timeout_secs,
+                ..
+            } => f
+                .debug_struct("EasyPost")
+                .field("api_key", &"<redacted>")
+                .field("api_base", api_base)
+                .field("from_address", from_address)
+                .field("timeout_secs", timeout_secs)
+                .finish(),
+            Backend::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+/// Where shipping rates come from and how a quoted job is packed
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct ShippingConfig {
+    backend: Backend,
+    /// Added to every side of the model's bounding box for packing material.
+    #[pyo3(get)]
+    pub padding_mm: f64,
+    /// Box and packing material, added to the filament weight.
+    #[pyo3(get)]
+    pub packaging_grams: f64,
+    /// Carriers whose rates are kept, e.g. ["UPS", "DHLExpress"]; empty keeps all.
+    #[pyo3(get)]
+    pub carriers: Vec<String>,
+}
+
+#[pymethods]
+impl ShippingConfig {
+    /// "easypost" or "callback".
+    #[getter]
+    fn provider(&self) -> &'static str {
+        match self.backend {
+            Backend::EasyPost { .. } => "easypost",
+            Backend::Callback(_) => "callback",
+        }
+    }
+
+    fn __str__(&self) -> String {
+        match &self.backend {
+            Backend::EasyPost { api_base, .. } => {
+                format!("ShippingConfig(easypost={})", api_base)
+            }
+            Backend::Callback(_) => "ShippingConfig(callback)".to_string(),
+        }
+    }
+}
+
+impl ShippingConfig {
+    /// Box for a job of `weight_grams` with the given bounding box.
+    pub fn parcel(&self, weight_grams: f64, dimensions: (f64, f64, f64)) -> Parcel {
+        let mut sides = [dimensions.0, dimensions.1, dimensions.2];
+        sides.sort_by(|a, b| b.total_cmp(a));
+        let padded = |side: f64| side.max(0.0) + 2.0 * self.padding_mm.max(0.0);
+        Parcel {
+            length_mm: padded(sides[0]),
+            width_mm: padded(sides[1]),
+            height_mm: padded(sides[2]),
+            weight_grams: weight_grams.max(0.0) + self.packaging_grams.max(0.0),
+        }
+    }
+
+    /// Rates for sending `parcel` to `destination`, cheapest first.
+    pub fn rates(
+        &self,
+        parcel: &Parcel,
+        destination: &HashMap<String, String>,
+    ) -> Result<Vec<ShippingRate>, OrcaError> {
+        let mut rates = match &self.backend {
+            Backend::EasyPost {
+                api_key,
+                api_base,
+                from_address,
+                timeout_secs,
+            } => easypost_rates(
+                api_key,
+                api_base,
+                from_address,
+                *timeout_secs,
+                parcel,
+                destination,
+            )?,
+            Backend::Callback(callback) => callback_rates(callback, parcel, destination)?,
+        };
+        if !self.carriers.is_empty() {
+            rates.retain(|rate| {
+                self.carriers
+                    .iter()
+                    .any(|carrier| carrier.eq_ignore_ascii_case(&rate.carrier))
+            });
+        }
+        rates.sort_by(|a, b| a.amount.total_cmp(&b.amount));
+        Ok(rates)
+    }
+}
+
+fn shipping_error(error: OrcaError) -> OrcaError {
+    match error {
+        OrcaError::UploadFailed(message) => OrcaError::ShippingFailed(message),
+        other => other,
+    }
+}
+
+/// EasyPost wants inches and ounces; round up so the carrier never re-rates the
+/// parcel as heavier or bigger than quoted.
+fn round_up(value: f64) -> f64 {
+    (value * 10.0).ceil() / 10.0
+}
+
+/// Rate a parcel via `POST /shipments`, which any EasyPost-compatible API accepts.
+fn easypost_rates(
+    api_key: &str,
+    api_base: &str,
+    from_address: &HashMap<String, String>,
+    timeout_secs: f64,
+    parcel: &Parcel,
+    destination: &HashMap<String, String>,
+) -> Result<Vec<ShippingRate>, OrcaError> {
+    let body = json!({
+        "shipment": {
+            "to_address": destination,
+            "from_address": from_address,
+            "parcel": {
+                "length": round_up(parcel.length_mm / MM_PER_INCH),
+                "width": round_up(parcel.width_mm / MM_PER_INCH),
+                "height": round_up(parcel.height_mm / MM_PER_INCH),
+                "weight": round_up(parcel.weight_grams / GRAMS_PER_OUNCE),
+            },
+        }
+    });
+    let url = format!("{}/shipments", api_base.trim_end_matches('/'));
+    let timeout = Duration::from_secs_f64(timeout_secs.max(0.0));
+    let response = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .build()
+        .post(&url)
+        .set(
+            "Authorization",
+            &format!("Basic {}", STANDARD.encode(format!("{}:", api_key))),
+        )
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+        .map_err(|e| shipping_error(request_error(&url, e)))?;
+    let body = read_json(&url, response).map_err(shipping_error)?;
+    let rates = body
+        .get("rates")
+        .and_then(Value::as_array)
+        .ok_or_else(|| OrcaError::ShippingFailed(format!("{}: response has no rates", url)))?;
+    Ok(rates.iter().filter_map(easypost_rate).collect())
+}
+
+/// One entry of a shipment's `rates`; amounts come back as strings, e.g. "7.58".
+fn easypost_rate(rate: &Value) -> Option<ShippingRate> {
+    let text = |field: &str| rate.get(field).and_then(Value::as_str);
+    let amount = match rate.get("rate")? {
+        Value::String(amount) => amount.parse().ok()?,
+        amount => amount.as_f64()?,
+    };
+    Some(ShippingRate {
+        carrier: text("carrier")?.to_string(),
+        service: text("service").unwrap_or_default().to_string(),
+        amount,
+        currency: text("currency").unwrap_or("USD").to_uppercase(),
+        delivery_days: rate
+            .get("delivery_days")
+            .and_then(Value::as_u64)
+            .map(|days| days as u32),
+    })
+}
+
+fn callback_rates(
+    callback: &PyObject,
+    parcel: &Parcel,
+    destination: &HashMap<String, String>,
+) -> Result<Vec<ShippingRate>, OrcaError> {
+    Python::with_gil(|py| {
+        let answer = callback.call1(py, (parcel.clone(), destination.clone()))?;
+        let mut rates = Vec::new();
+        for item in answer.as_ref(py).iter()? {
+            let item: &PyDict = item?.downcast()?;
+            let field = |name: &str| -> PyResult<&PyAny> {
+                item.get_item(name)?.ok_or_else(|| {
+                    pyo3::exceptions::PyKeyError::new_err(format!("rate has no {:?}", name))
+                })
+            };
+            rates.push(ShippingRate {
+                carrier: field("carrier")?.extract()?,
+                service: match item.get_item("service")? {
+                    Some(service) => service.extract()?,
+                    None => String::new(),
+                },
+                amount: field("amount")?.extract()?,
+                currency: field("currency")?.extract::<String>()?.to_uppercase(),
+                delivery_days: match item.get_item("delivery_days")? {
+                    Some(days) => days.extract()?,
+                    None => None,
+                },
+            });
+        }
+        Ok::<_, PyErr>(rates)
+    })
+    .map_err(|e| OrcaError::ShippingFailed(format!("rate provider: {}", e)))
+}
+
+fn check_packing(padding_mm: f64, packaging_grams: f64) -> Result<(), OrcaError> {
+    let valid = |value: f64| value.is_finite() && value >= 0.0;
+    if !valid(padding_mm) || !valid(packaging_grams) {
+        return Err(OrcaError::InvalidConfig {
+            path: "shipping".to_string(),
+            message: "padding_mm and packaging_grams must be zero or more".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Rate parcels with EasyPost, or any API that speaks its `/shipments` endpoint
+///
+/// `from_address` uses EasyPost's address fields (street1, city, zip, country, ...).
+#[pyfunction]
+#[pyo3(signature = (api_key, from_address, api_base="https://api.easypost.com/v2".to_string(), carriers=Vec::new(), padding_mm=20.0, packaging_grams=150.0, timeout_secs=15.0))]
+pub fn create_easypost_shipping(
+    api_key: String,
+    from_address: HashMap<String, String>,
+    api_base: String,
+    carriers: Vec<String>,
+    padding_mm: f64,
+    packaging_grams: f64,
+    timeout_secs: f64,
+) -> PyResult<ShippingConfig> {
+    panic_boundary::catch(|| {
+        if api_key.trim().is_empty() {
+            return Err(OrcaError::InvalidConfig {
+                path: "shipping".to_string(),
+                message: "API key is empty".to_string(),
+            }
+            .into());
+        }
+        if from_address.is_empty() {
+            return Err(OrcaError::InvalidConfig {
+                path: "shipping".to_string(),
+                message: "from_address is empty".to_string(),
+            }
+            .into());
+        }
+        check_packing(padding_mm, packaging_grams)?;
+        Ok(ShippingConfig {
+            backend: Backend::EasyPost {
+                api_key,
+                api_base,
+                from_address,
+                timeout_secs,
+            },
+            padding_mm,
+            packaging_grams,
+            carriers,
+        })
+    })
+}
+
+/// Rate parcels with a Python callable, for carriers without an EasyPost-style API
+///
+/// `provider(parcel, destination)` returns a list of `{"carrier", "service",
+/// "amount", "currency", "delivery_days"}` dicts; service and delivery_days may
+/// be left out.
+#[pyfunction]
+#[pyo3(signature = (provider, carriers=Vec::new(), padding_mm=20.0, packaging_grams=150.0))]
+pub fn create_callback_shipping(
+    py: Python<'_>,
+    provider: PyObject,
+    carriers: Vec<String>,
+    padding_mm: f64,
+    packaging_grams: f64,
+) -> PyResult<ShippingConfig> {
+    panic_boundary::catch(|| {
+        if !provider.as_ref(py).is_callable() {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "provider is not callable",
+            ));
+        }
+        check_packing(padding_mm, packaging_grams)?;
+        Ok(ShippingConfig {
+            backend: Backend::Callback(provider),
+            padding_mm,
+            packaging_grams,
+            carriers,
+        })
+    })
+}
+
+/// Shipping rates for a printed model, cheapest first
+///
+/// The parcel is the model's bounding box plus padding, weighing `weight_grams`
+/// (usually the sliced filament weight) plus packaging.
+#[pyfunction]
+pub fn get_shipping_rates(
+    py: Python<'_>,
+    config: PyRef<'_, ShippingConfig>,
+    destination: HashMap<String, String>,
+    weight_grams: f64,
+    model_path: String,
+) -> PyResult<Vec<ShippingRate>> {
+    panic_boundary::catch(|| {
+        let config = config.clone();
+        Ok(py.allow_threads(|| {
+            let dimensions = model_dimensions(Path::new(&model_path))?
+                .ok_or_else(|| OrcaError::InvalidModel("bounding box is unknown".to_string()))?;
+            config.rates(&config.parcel(weight_grams, dimensions), &destination)
+        })?)
+    })
+}
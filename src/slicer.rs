@@ -0,0 +1,571 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3_asyncio::tokio::future_into_py;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::cancellation::CancellationToken;
+use crate::panic_boundary;
+use crate::slicer_discovery::supports_flag;
+use crate::slicer_failure::SlicerFailure;
+use crate::slicer_limits::SlicerLimits;
+use crate::slicer_retry::SlicerRetryPolicy;
+use crate::OrcaError;
+
+/// Slicer processes running in this process, callers waiting for a slot and the
+/// configured cap (0 = no cap).
+#[derive(Default)]
+struct SlotCount {
+    in_use: usize,
+    waiting: usize,
+    limit: usize,
+}
+
+static SLOTS: Lazy<(Mutex<SlotCount>, Condvar)> =
+    Lazy::new(|| (Mutex::new(SlotCount::default()), Condvar::new()));
+
+fn slot_count() -> MutexGuard<'static, SlotCount> {
+    SLOTS.0.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Permission to run one slicer process; the slot is freed when this is dropped.
+#[derive(Debug)]
+pub struct SlicerSlot(());
+
+impl SlicerSlot {
+    /// Block until fewer than the configured number of slicers are running.
+    pub fn acquire() -> Self {
+        Self::acquire_unless(None).expect("nothing to cancel the wait")
+    }
+
+    /// `acquire`, giving up with `None` if `cancel` is cancelled while waiting.
+    pub fn acquire_unless(cancel: Option<&CancellationToken>) -> Option<Self> {
+        let mut count = slot_count();
+        count.waiting += 1;
+        while count.limit > 0 && count.in_use >= count.limit {
+            if cancel.is_some_and(CancellationToken::is_cancelled) {
+                count.waiting -= 1;
+                return None;
+            }
+            count = match cancel {
+                // Woken only by a freed slot, so check the token now and then.
+                Some(_) => {
+                    SLOTS
+                        .1
+                        .wait_timeout(count, Duration::from_millis(50))
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+                None => SLOTS.1.wait(count).unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+        count.waiting -= 1;
+        count.in_use += 1;
+        Some(SlicerSlot(()))
+    }
+}
+
+/// Running slicers, callers waiting for a slot, and the cap (0 = none) in this process.
+pub(crate) fn slot_usage() -> (usize, usize, usize) {
+    let count = slot_count();
+    (count.in_use, count.waiting, count.limit)
+}
+
+impl Drop for SlicerSlot {
+    fn drop(&mut self) {
+        slot_count().in_use -= 1;
+        SLOTS.1.notify_one();
+    }
+}
+
+/// Profiles handed to the slicer for one job.
+#[derive(Debug, Clone)]
+pub struct SlicerProfiles<'a> {
+    pub machine: &'a str,
+    pub process: &'a str,
+    pub filament: &'a str,
+}
+
+/// Keep only this much of the slicer's stderr, the end being what explains a failure.
+const STDERR_TAIL_BYTES: usize = 64 * 1024;
+
+/// The OrcaSlicer command line for one job, writing G-code and slice data into
+/// `output_dir`. Optional arguments a binary checked by `detect_slicer` does not
+/// list are left out. The slicer gets its own process group, so helpers it
+/// starts are killed along with it.
+fn slicer_command(
+    cli_path: &str,
+    model_path: &Path,
+    profiles: &SlicerProfiles<'_>,
+    output_dir: &Path,
+    working_dir: &Path,
+    limits: Option<&SlicerLimits>,
+) -> Command {
+    let mut command = Command::new(cli_path);
+    command
+        .arg(model_path)
+        .args(["--slice", "0"]) // Slice all plates
+        .arg("--load-settings")
+        .arg(format!("{};{}", profiles.machine, profiles.process))
+        .arg("--load-filaments")
+        .arg(profiles.filament);
+    // Only the G-code is read back; slice data is for debugging a quote.
+    if supports_flag(cli_path, "--export-slicedata") {
+        command.arg("--export-slicedata").arg(output_dir);
+    }
+    command.arg("--outputdir").arg(output_dir);
+    if supports_flag(cli_path, "--debug") {
+        command.args(["--debug", "1"]); // Minimal logging
+    }
+    command
+        .current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    if let Some(limits) = limits {
+        limits.apply(&mut command);
+    }
+    command
+}
+
+fn not_started(cli_path: &str, e: io::Error) -> OrcaError {
+    OrcaError::SlicerFailed(format!("could not start {}: {}", cli_path, e))
+}
+
+/// The last `STDERR_TAIL_BYTES` the slicer wrote, shared with the thread or
+/// task reading them so what arrived so far is there even if it never ends.
+#[derive(Clone, Default)]
+struct StderrTail(Arc<Mutex<Vec<u8>>>);
+
+impl StderrTail {
+    fn push(&self, chunk: &[u8]) {
+        let mut tail = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        tail.extend_from_slice(chunk);
+        let excess = tail.len().saturating_sub(STDERR_TAIL_BYTES);
+        tail.drain(..excess);
+    }
+
+    fn text(&self) -> String {
+        let tail = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        String::from_utf8_lossy(&tail).trim().to_string()
+    }
+
+    fn timed_out(&self, timeout: Duration) -> OrcaError {
+        let stderr = self.text();
+        OrcaError::SlicerTimeout {
+            seconds: timeout.as_secs_f64(),
+            stderr: if stderr.is_empty() {
+                "no output".to_string()
+            } else {
+                stderr
+            },
+        }
+    }
+
+    fn finished(&self, status: ExitStatus) -> Result<(), OrcaError> {
+        if status.success() {
+            return Ok(());
+        }
+        Err(OrcaError::SlicerExited(Box::new(SlicerFailure::classify(
+            self.text(),
+            status,
+        ))))
+    }
+}
+
+/// What a caller follows a slicer run with.
+#[derive(Clone, Default)]
+pub struct SlicerWatch {
+    /// Called with each line the slicer writes to stdout or stderr, on the
+    /// thread reading that pipe.
+    pub on_output: Option<PyObject>,
+    /// Kills the slicer, and stops a job before it starts one, when cancelled.
+    pub cancel: Option<CancellationToken>,
+}
+
+impl SlicerWatch {
+    /// `Cancelled` naming `what` once the watcher's token is cancelled.
+    pub fn check(&self, what: &str) -> Result<(), OrcaError> {
+        self.cancel
+            .as_ref()
+            .map_or(Ok(()), |cancel| cancel.check(what))
+    }
+}
+
+/// Splits what one of the slicer's pipes produces into lines for the watcher;
+/// for stderr, also keeps the tail.
+struct OutputPump {
+    tail: Option<StderrTail>,
+    on_output: Option<PyObject>,
+    pending: Vec<u8>,
+}
+
+impl OutputPump {
+    fn new(tail: Option<StderrTail>, watch: &SlicerWatch) -> Self {
+        OutputPump {
+            tail,
+            on_output: watch.on_output.clone(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        if let Some(tail) = &self.tail {
+            tail.push(chunk);
+        }
+        if self.on_output.is_none() {
+            return;
+        }
+        // Progress is often redrawn with a carriage return instead of a newline.
+        let mut rest = chunk;
+        while let Some(end) = rest.iter().position(|b| matches!(b, b'\n' | b'\r')) {
+            self.pending.extend_from_slice(&rest[..end]);
+            self.emit();
+            rest = &rest[end + 1..];
+        }
+        self.pending.extend_from_slice(rest);
+        if self.pending.len() > STDERR_TAIL_BYTES {
+            self.emit();
+        }
+    }
+
+    fn emit(&mut self) {
+        let line = String::from_utf8_lossy(&self.pending)
+            .trim_end()
+            .to_string();
+        self.pending.clear();
+        let Some(callback) = &self.on_output else {
+            return;
+        };
+        if line.is_empty() {
+            return;
+        }
+        if let Some(e) = Python::with_gil(|py| callback.call1(py, (line,)).err()) {
+            tracing::warn!(error = %e, "slicer output callback failed; no more lines sent to it");
+            self.on_output = None;
+        }
+    }
+}
+
+/// Send SIGKILL to the process group led by `pid`.
+pub(crate) fn kill_group(pid: Option<u32>) {
+    #[cfg(unix)]
+    if let Some(group) = pid.and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+        // SAFETY: kill(2) only sends a signal; a negative pid names the group
+        // the child leads, which was set up when it was spawned.
+        unsafe {
+            libc::kill(-group, libc::SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Run the OrcaSlicer CLI on a model, writing G-code and slice data into `output_dir`.
+/// The slicer and any process it started are killed once `timeout` passes, and
+/// it runs within `limits` if given.
+#[allow(clippy::too_many_arguments)]
+pub fn run_slicer(
+    cli_path: &str,
+    model_path: &Path,
+    profiles: &SlicerProfiles<'_>,
+    output_dir: &Path,
+    working_dir: &Path,
+    timeout: Duration,
+    limits: Option<&SlicerLimits>,
+    watch: &SlicerWatch,
+) -> Result<(), OrcaError> {
+    let mut child = slicer_command(
+        cli_path,
+        model_path,
+        profiles,
+        output_dir,
+        working_dir,
+        limits,
+    )
+    .spawn()
+    .map_err(|e| not_started(cli_path, e))?;
+
+    // Read while waiting, so a chatty slicer cannot fill a pipe and stall.
+    let stderr = StderrTail::default();
+    let readers = [
+        pump(child.stdout.take(), OutputPump::new(None, watch)),
+        pump(
+            child.stderr.take(),
+            OutputPump::new(Some(stderr.clone()), watch),
+        ),
+    ];
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if watch.check("slicer run").is_err() => {
+                kill_tree(&mut child);
+                return Err(OrcaError::Cancelled("slicer run".to_string()));
+            }
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                kill_tree(&mut child);
+                // Not joined: a helper that left the group may still hold the pipe.
+                return Err(stderr.timed_out(timeout));
+            }
+            Err(e) => {
+                kill_tree(&mut child);
+                return Err(e.into());
+            }
+        }
+    };
+    for reader in readers {
+        let _ = reader.join();
+    }
+    stderr.finished(status)
+}
+
+/// Feed everything read from `pipe` to `output` on a thread of its own.
+fn pump(pipe: Option<impl Read + Send + 'static>, mut output: OutputPump) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut chunk = [0u8; 8192];
+        while let Ok(read @ 1..) = pipe.read(&mut chunk) {
+            output.feed(&chunk[..read]);
+        }
+        output.emit();
+    })
+}
+
+/// Kill `child` and everything in its process group, then reap it.
+fn kill_tree(child: &mut Child) {
+    kill_group(Some(child.id()));
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// `run_slicer` on the tokio runtime, awaiting the slicer instead of holding a
+/// thread. Dropping the future kills the slicer.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_slicer_async(
+    cli_path: &str,
+    model_path: &Path,
+    profiles: &SlicerProfiles<'_>,
+    output_dir: &Path,
+    working_dir: &Path,
+    timeout: Duration,
+    limits: Option<&SlicerLimits>,
+    watch: &SlicerWatch,
+) -> Result<(), OrcaError> {
+    let mut child = tokio::process::Command::from(slicer_command(
+        cli_path,
+        model_path,
+        profiles,
+        output_dir,
+        working_dir,
+        limits,
+    ))
+    .kill_on_drop(true)
+    .spawn()
+    .map_err(|e| not_started(cli_path, e))?;
+
+    let stderr = StderrTail::default();
+    let readers = [
+        pump_async(child.stdout.take(), OutputPump::new(None, watch)),
+        pump_async(
+            child.stderr.take(),
+            OutputPump::new(Some(stderr.clone()), watch),
+        ),
+    ];
+
+    let cancel = watch.cancel.clone();
+    let cancelled = async move {
+        match cancel {
+            Some(cancel) => cancel.wait_cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    let stopped = tokio::select! {
+        finished = tokio::time::timeout(timeout, child.wait()) => match finished {
+            Ok(status) => {
+                let status = status?;
+                for reader in readers {
+                    let _ = reader.await;
+                }
+                return stderr.finished(status);
+            }
+            Err(_) => stderr.timed_out(timeout),
+        },
+        () = cancelled => OrcaError::Cancelled("slicer run".to_string()),
+    };
+    kill_group(child.id());
+    let _ = child.kill().await;
+    Err(stopped)
+}
+
+/// `pump` as a tokio task.
+fn pump_async(
+    pipe: Option<impl AsyncRead + Unpin + Send + 'static>,
+    mut output: OutputPump,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut chunk = [0u8; 8192];
+        while let Ok(read @ 1..) = pipe.read(&mut chunk).await {
+            output.feed(&chunk[..read]);
+        }
+        output.emit();
+    })
+}
+
+/// A held slicer slot for Python callers; use as a context manager or call `release()`
+#[derive(Debug)]
+#[pyclass]
+pub struct SlicerPermit {
+    slot: Option<SlicerSlot>,
+    /// Time spent waiting for a free slot.
+    #[pyo3(get)]
+    pub wait_ms: f64,
+}
+
+#[pymethods]
+impl SlicerPermit {
+    /// Free the slot; calling it again does nothing.
+    pub fn release(&mut self) {
+        self.slot.take();
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: Option<&PyAny>,
+        _exc_value: Option<&PyAny>,
+        _traceback: Option<&PyAny>,
+    ) -> bool {
+        self.release();
+        false
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerPermit(held={}, wait_ms={:.1})",
+            self.slot.is_some(),
+            self.wait_ms
+        )
+    }
+}
+
+/// Allow at most `limit` slicer processes at once across all entry points; 0 removes the cap
+#[pyfunction]
+pub fn set_slicer_concurrency(limit: usize) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        slot_count().limit = limit;
+        SLOTS.1.notify_all();
+        Ok(())
+    })
+}
+
+/// Wait for a free slicer slot without blocking the event loop
+#[pyfunction]
+pub fn acquire_slicer_slot(py: Python<'_>) -> PyResult<&PyAny> {
+    panic_boundary::catch(|| {
+        future_into_py(
+            py,
+            panic_boundary::catch_future(async move {
+                let started = Instant::now();
+                let slot = tokio::task::spawn_blocking(SlicerSlot::acquire)
+                    .await
+                    .map_err(|e| {
+                        OrcaError::SlicerFailed(format!("waiting for a slicer slot: {}", e))
+                    })?;
+                Ok(SlicerPermit {
+                    slot: Some(slot),
+                    wait_ms: started.elapsed().as_secs_f64() * 1000.0,
+                })
+            }),
+        )
+    })
+}
+
+/// Run the OrcaSlicer CLI on a model without blocking the event loop
+///
+/// Writes G-code and slice data into `output_dir`, running the slicer in
+/// `working_dir` (`output_dir` by default). Raises RuntimeError with the
+/// slicer's stderr when it fails, and TimeoutError once `timeout_secs` pass,
+/// after killing it and anything it started. Hold a slot from
+/// `acquire_slicer_slot` around the call to keep to the configured cap.
+///
+/// `on_output` is called with each line the slicer writes as it is written,
+/// from a worker thread; from asyncio code, hand lines to the event loop with
+/// `loop.call_soon_threadsafe`. Cancelling `cancel` kills the slicer and
+/// raises `asyncio.CancelledError`.
+///
+/// With `retry`, a slicer that crashes is run again as the policy allows; with
+/// `limits`, it runs within them. Returns every run made, as `SlicerAttempt`s.
+#[pyfunction]
+#[pyo3(signature = (cli_path, model_path, machine_profile, process_profile, filament_profile, output_dir, working_dir=None, timeout_secs=300.0, on_output=None, cancel=None, retry=None, limits=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn execute_slicer_async(
+    py: Python<'_>,
+    cli_path: String,
+    model_path: String,
+    machine_profile: String,
+    process_profile: String,
+    filament_profile: String,
+    output_dir: String,
+    working_dir: Option<String>,
+    timeout_secs: f64,
+    on_output: Option<PyObject>,
+    cancel: Option<CancellationToken>,
+    retry: Option<SlicerRetryPolicy>,
+    limits: Option<SlicerLimits>,
+) -> PyResult<&PyAny> {
+    panic_boundary::catch(|| {
+        if !(timeout_secs > 0.0 && timeout_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "timeout_secs".to_string(),
+                message: format!("{} is not a positive number of seconds", timeout_secs),
+            }
+            .into());
+        }
+        future_into_py(
+            py,
+            panic_boundary::catch_future(async move {
+                let working_dir = working_dir.unwrap_or_else(|| output_dir.clone());
+                let profiles = SlicerProfiles {
+                    machine: &machine_profile,
+                    process: &process_profile,
+                    filament: &filament_profile,
+                };
+                let watch = SlicerWatch { on_output, cancel };
+                let attempts = retry
+                    .unwrap_or_default()
+                    .run_async(&watch, || {
+                        run_slicer_async(
+                            &cli_path,
+                            Path::new(&model_path),
+                            &profiles,
+                            Path::new(&output_dir),
+                            Path::new(&working_dir),
+                            Duration::from_secs_f64(timeout_secs),
+                            limits.as_ref(),
+                            &watch,
+                        )
+                    })
+                    .await?;
+                Ok(attempts)
+            }),
+        )
+    })
+}
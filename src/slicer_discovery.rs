@@ -0,0 +1,310 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use regex::Regex;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::panic_boundary;
+use crate::slicer::kill_group;
+use crate::OrcaError;
+
+/// Names the OrcaSlicer CLI is installed under by packages and release archives.
+const PATH_NAMES: &[&str] = &["orca-slicer", "OrcaSlicer", "orcaslicer"];
+
+/// Where installers put OrcaSlicer outside `PATH`; `~` is the user's home.
+const INSTALL_PATHS: &[&str] = &[
+    "/var/lib/flatpak/exports/bin/io.github.softfever.OrcaSlicer",
+    "~/.local/share/flatpak/exports/bin/io.github.softfever.OrcaSlicer",
+    "/opt/OrcaSlicer/orca-slicer",
+    "/opt/orca-slicer/orca-slicer",
+    "/Applications/OrcaSlicer.app/Contents/MacOS/OrcaSlicer",
+];
+
+/// Where a downloaded AppImage usually ends up.
+const APPIMAGE_DIRS: &[&str] = &["~/Applications", "~/.local/bin", "~/Downloads", "/opt"];
+
+/// The first version in the banner, e.g. "OrcaSlicer-2.1.1:" or "OrcaSlicer 02.00.00.51".
+static VERSION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?(?:[.-][0-9A-Za-z][0-9A-Za-z.-]*)?").unwrap()
+});
+
+/// An option at the start of a `--help` line.
+static FLAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*(--[A-Za-z][\w-]*)").unwrap());
+
+/// Flags listed by each binary `detect_slicer` ran, by the path it was asked
+/// for and the one it found.
+static DETECTED_FLAGS: Lazy<Mutex<HashMap<String, Vec<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn detected_flags() -> MutexGuard<'static, HashMap<String, Vec<String>>> {
+    DETECTED_FLAGS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether the slicer at `cli_path` takes `flag`; true unless `detect_slicer`
+/// ran it and its `--help` did not list the flag.
+pub(crate) fn supports_flag(cli_path: &str, flag: &str) -> bool {
+    detected_flags()
+        .get(cli_path)
+        .is_none_or(|flags| flags.iter().any(|f| f == flag))
+}
+
+/// An OrcaSlicer binary found by `detect_slicer`, with what it reported
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct SlicerInfo {
+    #[pyo3(get)]
+    pub path: String,
+    /// "configured", "path", "install_dir" or "appimage".
+    #[pyo3(get)]
+    pub source: String,
+    /// As printed, e.g. "2.1.1"; `None` when neither `--help` nor `--version` showed one.
+    #[pyo3(get)]
+    pub version: Option<String>,
+    /// (major, minor, patch), for comparing releases.
+    #[pyo3(get)]
+    pub version_info: Option<(u32, u32, u32)>,
+    /// Options listed by `--help`, in the order it lists them.
+    #[pyo3(get)]
+    pub flags: Vec<String>,
+}
+
+#[pymethods]
+impl SlicerInfo {
+    /// Whether `--help` listed `flag`, e.g. "--export-slicedata"
+    fn supports(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerInfo(path={}, version={}, source={})",
+            self.path,
+            self.version.as_deref().unwrap_or("unknown"),
+            self.source
+        )
+    }
+}
+
+fn expand_home(path: &str) -> Option<PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(rest) => env::var_os("HOME").map(|home| Path::new(&home).join(rest)),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    metadata.is_file()
+}
+
+fn on_path(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+}
+
+/// The most recently modified OrcaSlicer AppImage in the usual download places.
+fn find_appimage() -> Option<PathBuf> {
+    APPIMAGE_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(expand_home(dir)?).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            name.ends_with(".appimage")
+                && ["orcaslicer", "orca-slicer", "orca_slicer"]
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+        })
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+}
+
+/// The binary at or named by `requested`, or the first one found on `PATH`, in
+/// an install directory or as an AppImage; with where it was found.
+fn locate(requested: Option<&str>) -> Result<(PathBuf, &'static str), OrcaError> {
+    if let Some(requested) = requested {
+        if requested.contains(std::path::MAIN_SEPARATOR) || requested.starts_with('~') {
+            let path = expand_home(requested).unwrap_or_else(|| PathBuf::from(requested));
+            return if is_executable(&path) {
+                Ok((path, "configured"))
+            } else {
+                Err(OrcaError::FileNotFound(format!(
+                    "{} is not an executable file",
+                    requested
+                )))
+            };
+        }
+        return on_path(requested)
+            .map(|path| (path, "path"))
+            .ok_or_else(|| OrcaError::FileNotFound(format!("{} is not on PATH", requested)));
+    }
+    PATH_NAMES
+        .iter()
+        .find_map(|name| on_path(name))
+        .map(|path| (path, "path"))
+        .or_else(|| {
+            INSTALL_PATHS
+                .iter()
+                .filter_map(|path| expand_home(path))
+                .find(|path| is_executable(path))
+                .map(|path| (path, "install_dir"))
+        })
+        .or_else(|| find_appimage().map(|path| (path, "appimage")))
+        .ok_or_else(|| {
+            OrcaError::FileNotFound(
+                "no OrcaSlicer binary on PATH, in the usual install directories or as an AppImage"
+                    .to_string(),
+            )
+        })
+}
+
+/// Everything `path` writes to stdout and stderr when run with `arg`, killed
+/// if it has not exited within `timeout`.
+fn output_of(path: &Path, arg: &str, timeout: Duration) -> Result<String, OrcaError> {
+    let mut command = Command::new(path);
+    command
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn().map_err(|e| {
+        OrcaError::SlicerFailed(format!("could not start {}: {}", path.display(), e))
+    })?;
+    let readers = [
+        child.stdout.take().map(read_all),
+        child.stderr.take().map(read_all),
+    ];
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait()? {
+            Some(_) => break,
+            None if started.elapsed() < timeout => thread::sleep(Duration::from_millis(20)),
+            None => {
+                kill_group(Some(child.id()));
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(OrcaError::SlicerTimeout {
+                    seconds: timeout.as_secs_f64(),
+                    stderr: format!("{} {} did not exit", path.display(), arg),
+                });
+            }
+        }
+    }
+    let mut output = String::new();
+    for reader in readers.into_iter().flatten() {
+        output.push_str(&String::from_utf8_lossy(&reader.join().unwrap_or_default()));
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+fn read_all(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// The first version in `output`; with `banner`, only one on a line naming the
+/// slicer, since `--help` also shows example values such as "0.2".
+fn parse_version(output: &str, banner: bool) -> Option<(String, (u32, u32, u32))> {
+    let captures = output
+        .lines()
+        .filter(|line| !banner || line.to_lowercase().contains("slicer"))
+        .find_map(|line| VERSION.captures(line))?;
+    let part = |i| captures.get(i).map_or(Some(0), |m| m.as_str().parse().ok());
+    Some((captures[0].to_string(), (part(1)?, part(2)?, part(3)?)))
+}
+
+fn parse_flags(output: &str) -> Vec<String> {
+    let mut flags: Vec<String> = Vec::new();
+    for captures in FLAG.captures_iter(output) {
+        let flag = &captures[1];
+        if !flags.iter().any(|f| f == flag) {
+            flags.push(flag.to_string());
+        }
+    }
+    flags
+}
+
+fn detect(requested: Option<&str>, timeout: Duration) -> Result<SlicerInfo, OrcaError> {
+    let (path, source) = locate(requested)?;
+    let help = output_of(&path, "--help", timeout)?;
+    let version = match parse_version(&help, true) {
+        Some(version) => Some(version),
+        // Releases that print no banner with --help answer --version.
+        None => parse_version(&output_of(&path, "--version", timeout)?, false),
+    };
+    let flags = parse_flags(&help);
+
+    let path = path.to_string_lossy().into_owned();
+    if !flags.is_empty() {
+        let mut detected = detected_flags();
+        detected.insert(path.clone(), flags.clone());
+        if let Some(requested) = requested {
+            detected.insert(requested.to_string(), flags.clone());
+        }
+    }
+    let (version, version_info) = version.unzip();
+    Ok(SlicerInfo {
+        path,
+        source: source.to_string(),
+        version,
+        version_info,
+        flags,
+    })
+}
+
+/// Find an OrcaSlicer binary and report its version and command-line options
+///
+/// `path` may be a file path or a command name looked up on `PATH`; without
+/// one, `PATH`, the usual install directories (Flatpak, /opt, macOS) and
+/// AppImages in ~/Applications, ~/.local/bin, ~/Downloads and /opt are searched
+/// in that order. The binary is run with `--help`, and with `--version` when
+/// that shows no version. Slicer runs afterwards leave out optional arguments
+/// (`--export-slicedata`, `--debug`) the binary did not list, so releases
+/// that lack them still slice. Raises FileNotFoundError when nothing is found.
+#[pyfunction]
+#[pyo3(signature = (path=None, timeout_secs=10.0))]
+pub fn detect_slicer(
+    py: Python<'_>,
+    path: Option<String>,
+    timeout_secs: f64,
+) -> PyResult<SlicerInfo> {
+    panic_boundary::catch(|| {
+        if !(timeout_secs > 0.0 && timeout_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "timeout_secs".to_string(),
+                message: format!("{} is not a positive number of seconds", timeout_secs),
+            }
+            .into());
+        }
+        py.allow_threads(|| {
+            Ok(detect(
+                path.as_deref(),
+                Duration::from_secs_f64(timeout_secs),
+            )?)
+        })
+    })
+}
@@ -0,0 +1,210 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::ExitStatus;
+
+create_exception!(
+    _rust_core,
+    SlicerFailedError,
+    PyRuntimeError,
+    "The slicer exited with an error. `failure` is the SlicerFailure saying why."
+);
+
+/// Lowercase pieces of OrcaSlicer's output, by category, checked in this order
+/// since a model off the plate is also reported as there being nothing to slice.
+const PATTERNS: &[(&str, &[&str])] = &[
+    (
+        "out_of_build_volume",
+        &[
+            "nothing to be sliced",
+            "outside of the print volume",
+            "outside the print volume",
+            "outside of the plate",
+            "exceeds the build volume",
+            "exceed the printable",
+            "does not fit",
+            "out of bed",
+        ],
+    ),
+    (
+        "profile_incompatible",
+        &[
+            "not compatible",
+            "incompatible",
+            "failed to load settings",
+            "load settings failed",
+            "load filament failed",
+            "config file error",
+            "invalid config",
+            "invalid value",
+            "unknown option",
+            "printer_technology",
+        ],
+    ),
+    (
+        "model_unloadable",
+        &[
+            "loading of a model file failed",
+            "failed loading",
+            "failed to load",
+            "load model failed",
+            "no object",
+            "invalid file",
+            "unsupported file",
+            "could not read",
+            "parse error",
+        ],
+    ),
+    (
+        "resource_limit",
+        &["std::bad_alloc", "out of memory", "cannot allocate memory"],
+    ),
+    (
+        "crash",
+        &[
+            "segmentation fault",
+            "core dumped",
+            "terminate called",
+            "x error",
+            "glx",
+            "opengl",
+            "cannot open display",
+            "gpu",
+        ],
+    ),
+];
+
+/// OrcaSlicer's CLI return codes (negative, so 256 - n as an exit status) for
+/// when its output says nothing recognisable.
+const EXIT_CODES: &[(i32, &str)] = &[
+    (256 - 3, "model_unloadable"),      // CLI_FILE_NOTFOUND
+    (256 - 5, "profile_incompatible"),  // CLI_CONFIG_FILE_ERROR
+    (256 - 6, "model_unloadable"),      // CLI_DATA_FILE_ERROR
+    (256 - 17, "profile_incompatible"), // CLI_PROCESS_NOT_COMPATIBLE
+];
+
+/// What to tell the customer for each category.
+fn customer_message(category: &str) -> &'static str {
+    match category {
+        "model_unloadable" => {
+            "We could not read this model. Please export it again as STL or 3MF and check it is a closed solid."
+        }
+        "out_of_build_volume" => {
+            "This model does not fit on our printer's build plate. Please scale it down or split it into parts."
+        }
+        "profile_incompatible" => {
+            "This material and these print settings do not work together. Please try another material or the default settings."
+        }
+        "crash" => "The slicer stopped unexpectedly. Please try again in a few minutes.",
+        "resource_limit" => {
+            "This model needs more time or memory to slice than we allow. Please simplify the mesh or contact us for a manual quote."
+        }
+        _ => "We could not slice this model. We will look into it and get back to you.",
+    }
+}
+
+/// Why the slicer exited with an error, sorted into a category the web tier can
+/// explain to the customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SlicerFailure {
+    /// "model_unloadable", "out_of_build_volume", "profile_incompatible",
+    /// "crash", "resource_limit" or "unknown".
+    #[pyo3(get)]
+    pub category: String,
+    /// What the customer can do about it, safe to show them.
+    #[pyo3(get)]
+    pub message: String,
+    /// The line of output that gave the category away, or the exit status.
+    #[pyo3(get)]
+    pub detail: String,
+    /// The end of the slicer's stderr, for the operator.
+    #[pyo3(get)]
+    pub stderr: String,
+    /// E.g. "exit status: 1" or "signal: 11 (SIGSEGV)".
+    #[pyo3(get)]
+    pub exit_status: String,
+    /// Runs that failed, more than one when a crash was retried.
+    #[pyo3(get)]
+    pub attempts: u32,
+}
+
+#[pymethods]
+impl SlicerFailure {
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerFailure(category={}, detail={})",
+            self.category, self.detail
+        )
+    }
+}
+
+/// The slicer's own words, as raised before failures were classified.
+impl fmt::Display for SlicerFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.stderr.is_empty() {
+            write!(f, "exited with {}", self.exit_status)?;
+        } else if self.exit_status.starts_with("signal") {
+            // Say which signal, since a crash is worth retrying.
+            write!(f, "{}\n(exited with {})", self.stderr, self.exit_status)?;
+        } else {
+            f.write_str(&self.stderr)?;
+        }
+        if self.attempts > 1 {
+            write!(f, " (after {} attempts)", self.attempts)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn signal(status: ExitStatus) -> Option<i32> {
+    std::os::unix::process::ExitStatusExt::signal(&status)
+}
+
+#[cfg(not(unix))]
+fn signal(_status: ExitStatus) -> Option<i32> {
+    None
+}
+
+impl SlicerFailure {
+    /// Sort a failed run by its stderr, then by how it exited.
+    pub fn classify(stderr: String, status: ExitStatus) -> Self {
+        let lines: Vec<String> = stderr.lines().map(str::to_lowercase).collect();
+        // The last matching line is the one nearest the failure.
+        let matched = PATTERNS.iter().find_map(|(category, patterns)| {
+            lines
+                .iter()
+                .rposition(|line| patterns.iter().any(|p| line.contains(p)))
+                .map(|index| (*category, stderr.lines().nth(index).unwrap_or_default()))
+        });
+        let (category, detail) = match (matched, status.code()) {
+            // SIGXCPU is a CPU limit; SIGKILL from anyone but us, the OOM killer.
+            (_, None) if matches!(signal(status), Some(libc::SIGXCPU | libc::SIGKILL)) => {
+                ("resource_limit", status.to_string())
+            }
+            // std::bad_alloc aborts, so running out of memory also ends in a signal.
+            (Some(("resource_limit", line)), _) => ("resource_limit", line.trim().to_string()),
+            // Otherwise a signal trumps whatever the slicer was saying when it got it.
+            (_, None) => ("crash", status.to_string()),
+            (Some((category, line)), _) => (category, line.trim().to_string()),
+            (None, Some(code)) => (
+                EXIT_CODES
+                    .iter()
+                    .find(|(exit, _)| *exit == code)
+                    .map_or("unknown", |(_, category)| *category),
+                status.to_string(),
+            ),
+        };
+        SlicerFailure {
+            category: category.to_string(),
+            message: customer_message(category).to_string(),
+            detail,
+            stderr,
+            exit_status: status.to_string(),
+            attempts: 1,
+        }
+    }
+}
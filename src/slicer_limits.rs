@@ -0,0 +1,176 @@
+use pyo3::prelude::*;
+#[cfg(unix)]
+use std::ffi::CString;
+use std::process::Command;
+
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// CPU seconds a slicer gets past its limit, after SIGXCPU, before SIGKILL.
+const CPU_GRACE_SECS: u64 = 5;
+
+/// What one slicer process may use of the host
+#[derive(Debug, Clone, Default)]
+#[pyclass]
+pub struct SlicerLimits {
+    /// CPU time, all threads together; past it the slicer gets SIGXCPU.
+    #[pyo3(get)]
+    pub cpu_secs: Option<u64>,
+    /// Address space (RLIMIT_AS); allocations beyond it fail.
+    #[pyo3(get)]
+    pub memory_mb: Option<u64>,
+    /// Niceness the slicer runs at, -20 (first) to 19 (last).
+    #[pyo3(get)]
+    pub nice: Option<i32>,
+    /// A cgroup v2 directory each slicer joins, so the limits set on it
+    /// (memory.max, cpu.max, pids.max) cover all of them together.
+    #[pyo3(get)]
+    pub cgroup: Option<String>,
+}
+
+#[pymethods]
+impl SlicerLimits {
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerLimits(cpu_secs={:?}, memory_mb={:?}, nice={:?}, cgroup={:?})",
+            self.cpu_secs, self.memory_mb, self.nice, self.cgroup
+        )
+    }
+}
+
+impl SlicerLimits {
+    /// Have `command`'s child apply the limits between fork and exec; one that
+    /// cannot be applied fails the spawn. Not applied off Unix.
+    pub fn apply(&self, command: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let (cpu_secs, memory_mb, nice) = (self.cpu_secs, self.memory_mb, self.nice);
+            // Built here: the child may not allocate before exec.
+            let procs = self
+                .cgroup
+                .as_ref()
+                .and_then(|dir| CString::new(format!("{}/cgroup.procs", dir)).ok());
+            // SAFETY: the closure runs in the forked child and only makes
+            // async-signal-safe calls (open, write, close, getrlimit,
+            // setrlimit, setpriority) on values prepared before the fork.
+            unsafe {
+                command.pre_exec(move || {
+                    if let Some(procs) = &procs {
+                        join_cgroup(procs)?;
+                    }
+                    if let Some(secs) = cpu_secs {
+                        set_limit(libc::RLIMIT_CPU, secs, secs + CPU_GRACE_SECS)?;
+                    }
+                    if let Some(mb) = memory_mb {
+                        let bytes = mb.saturating_mul(1024 * 1024);
+                        set_limit(libc::RLIMIT_AS, bytes, bytes)?;
+                    }
+                    if let Some(nice) = nice {
+                        if libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+}
+
+/// Lower a resource limit of the calling process to `soft` and `hard`, never
+/// above the hard limit it already has.
+#[cfg(unix)]
+unsafe fn set_limit(resource: Resource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if libc::getrlimit(resource, &mut limit) != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    limit.rlim_max = limit.rlim_max.min(hard as libc::rlim_t);
+    limit.rlim_cur = limit.rlim_max.min(soft as libc::rlim_t);
+    if libc::setrlimit(resource, &limit) != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// The type glibc gives resource numbers, and everything else an int.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type Resource = libc::c_int;
+
+/// Move the calling process into the cgroup whose `cgroup.procs` is `procs`.
+#[cfg(unix)]
+unsafe fn join_cgroup(procs: &CString) -> std::io::Result<()> {
+    let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // "0" names the writing process, so no pid has to be formatted.
+    let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+    let error = std::io::Error::last_os_error();
+    libc::close(fd);
+    if written != 1 {
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Create limits on the CPU, memory and priority of each slicer process
+///
+/// A malformed or pathological model can keep OrcaSlicer busy for hours or
+/// grow it until the host swaps. `cpu_secs` caps its CPU time (SIGXCPU, then
+/// SIGKILL a few seconds later), `memory_mb` its address space, and `nice`
+/// runs it behind the web tier. With `cgroup`, a cgroup v2 directory the
+/// worker may write to, every slicer joins it, so its memory.max and cpu.max
+/// bound the slicers together. A slicer stopped by a limit fails with the
+/// "resource_limit" category and is not retried. Only applied on Unix.
+#[pyfunction]
+#[pyo3(signature = (cpu_secs=None, memory_mb=None, nice=None, cgroup=None))]
+pub fn create_slicer_limits(
+    cpu_secs: Option<u64>,
+    memory_mb: Option<u64>,
+    nice: Option<i32>,
+    cgroup: Option<String>,
+) -> PyResult<SlicerLimits> {
+    panic_boundary::catch(|| {
+        for (path, value) in [("cpu_secs", cpu_secs), ("memory_mb", memory_mb)] {
+            if value == Some(0) {
+                return Err(OrcaError::InvalidConfig {
+                    path: path.to_string(),
+                    message: "a limit of 0 would stop the slicer before it starts".to_string(),
+                }
+                .into());
+            }
+        }
+        if let Some(nice) = nice.filter(|nice| !(-20..=19).contains(nice)) {
+            return Err(OrcaError::InvalidConfig {
+                path: "nice".to_string(),
+                message: format!("{} is not a niceness from -20 to 19", nice),
+            }
+            .into());
+        }
+        if let Some(dir) = cgroup.as_deref() {
+            if !std::path::Path::new(dir).join("cgroup.procs").is_file() {
+                return Err(OrcaError::InvalidConfig {
+                    path: "cgroup".to_string(),
+                    message: format!("{} is not a cgroup v2 directory", dir),
+                }
+                .into());
+            }
+        }
+        Ok(SlicerLimits {
+            cpu_secs,
+            memory_mb,
+            nice,
+            cgroup,
+        })
+    })
+}
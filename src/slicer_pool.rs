@@ -0,0 +1,395 @@
+use pyo3::prelude::*;
+use pyo3_asyncio::tokio::{future_into_py, get_runtime};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+use crate::cancellation::CancellationToken;
+use crate::job_queue::record_slice_seconds;
+use crate::panic_boundary;
+use crate::slicer::{run_slicer_async, SlicerProfiles, SlicerSlot, SlicerWatch};
+use crate::slicer_limits::SlicerLimits;
+use crate::slicer_retry::{SlicerAttempt, SlicerRetryPolicy};
+use crate::OrcaError;
+
+fn locked<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Default)]
+struct PoolCounts {
+    queued: u64,
+    running: u64,
+    completed: u64,
+    failed: u64,
+    cancelled: u64,
+}
+
+impl PoolCounts {
+    /// Count a job that ended, failing with `error` if it has one.
+    fn tally(&mut self, error: Option<&OrcaError>) {
+        match error {
+            None => self.completed += 1,
+            Some(OrcaError::Cancelled(_)) => self.cancelled += 1,
+            Some(_) => self.failed += 1,
+        }
+    }
+}
+
+/// Jobs in a slicer pool by state, since it was created
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct SlicerPoolStats {
+    #[pyo3(get)]
+    pub max_concurrent: usize,
+    #[pyo3(get)]
+    pub queued: u64,
+    #[pyo3(get)]
+    pub running: u64,
+    #[pyo3(get)]
+    pub completed: u64,
+    #[pyo3(get)]
+    pub failed: u64,
+    #[pyo3(get)]
+    pub cancelled: u64,
+}
+
+#[pymethods]
+impl SlicerPoolStats {
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerPoolStats(max_concurrent={}, queued={}, running={}, completed={}, failed={}, cancelled={})",
+            self.max_concurrent,
+            self.queued,
+            self.running,
+            self.completed,
+            self.failed,
+            self.cancelled
+        )
+    }
+}
+
+/// Where a job is, and how it ended once it has.
+struct JobProgress {
+    status: &'static str,
+    wait_ms: Option<f64>,
+    run_ms: Option<f64>,
+    outcome: Option<Result<Vec<SlicerAttempt>, PyErr>>,
+}
+
+struct JobState {
+    progress: Mutex<JobProgress>,
+    finished: Notify,
+}
+
+impl JobState {
+    fn finish(&self, outcome: Result<Vec<SlicerAttempt>, OrcaError>, run_ms: Option<f64>) {
+        let mut progress = locked(&self.progress);
+        progress.status = match &outcome {
+            Ok(_) => "completed",
+            Err(OrcaError::Cancelled(_)) => "cancelled",
+            Err(_) => "failed",
+        };
+        progress.run_ms = run_ms;
+        progress.outcome = Some(outcome.map_err(PyErr::from));
+        drop(progress);
+        self.finished.notify_waiters();
+    }
+
+    fn outcome(&self) -> Option<Result<Vec<SlicerAttempt>, PyErr>> {
+        let progress = locked(&self.progress);
+        progress.outcome.as_ref().map(|outcome| match outcome {
+            Ok(attempts) => Ok(attempts.clone()),
+            Err(e) => Err(Python::with_gil(|py| e.clone_ref(py))),
+        })
+    }
+}
+
+/// One slicer run submitted to a `SlicerPool`
+#[derive(Clone)]
+#[pyclass]
+pub struct SlicerJob {
+    state: Arc<JobState>,
+    cancel: CancellationToken,
+}
+
+#[pymethods]
+impl SlicerJob {
+    /// Wait for the run to end; returns and raises as `execute_slicer_async` does
+    fn wait<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let state = Arc::clone(&self.state);
+        future_into_py(
+            py,
+            panic_boundary::catch_future(async move {
+                loop {
+                    // Registered before the check, so finishing in between still wakes it.
+                    let finished = state.finished.notified();
+                    if let Some(outcome) = state.outcome() {
+                        return outcome;
+                    }
+                    finished.await;
+                }
+            }),
+        )
+    }
+
+    /// Take the job out of the queue, or kill its slicer if it is running.
+    fn cancel(&self) {
+        self.cancel.trigger();
+    }
+
+    /// "queued", "running", "completed", "failed" or "cancelled".
+    #[getter]
+    fn status(&self) -> &'static str {
+        locked(&self.state.progress).status
+    }
+
+    #[getter]
+    fn done(&self) -> bool {
+        locked(&self.state.progress).outcome.is_some()
+    }
+
+    /// Time spent queued for a free slicer, once the job has left the queue.
+    #[getter]
+    fn wait_ms(&self) -> Option<f64> {
+        locked(&self.state.progress).wait_ms
+    }
+
+    /// Time the slicer ran, once it has finished.
+    #[getter]
+    fn run_ms(&self) -> Option<f64> {
+        locked(&self.state.progress).run_ms
+    }
+
+    fn __str__(&self) -> String {
+        format!("SlicerJob(status={})", self.status())
+    }
+}
+
+/// A queue of slicer runs, at most `max_concurrent` of them at once
+#[derive(Clone)]
+#[pyclass]
+pub struct SlicerPool {
+    #[pyo3(get)]
+    pub max_concurrent: usize,
+    #[pyo3(get)]
+    pub timeout_secs: f64,
+    /// Runs again a slicer that crashed; `None` runs each job once.
+    #[pyo3(get)]
+    pub retry: Option<SlicerRetryPolicy>,
+    /// CPU, memory and priority limits each slicer runs within.
+    #[pyo3(get)]
+    pub limits: Option<SlicerLimits>,
+    permits: Arc<Semaphore>,
+    counts: Arc<Mutex<PoolCounts>>,
+}
+
+/// A job's owned arguments, borrowed as the slicer's when it runs.
+struct JobSpec {
+    cli_path: String,
+    model_path: String,
+    machine_profile: String,
+    process_profile: String,
+    filament_profile: String,
+    output_dir: String,
+    working_dir: String,
+}
+
+impl SlicerPool {
+    /// Queue for a pool permit and then a process-wide slicer slot, so
+    /// `set_slicer_concurrency` still caps pools and direct runs together.
+    async fn slot(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<(OwnedSemaphorePermit, SlicerSlot), OrcaError> {
+        let queued = || OrcaError::Cancelled("slicer job while queued".to_string());
+        let permit = tokio::select! {
+            permit = Arc::clone(&self.permits).acquire_owned() => permit.map_err(|_| queued())?,
+            () = cancel.wait_cancelled() => return Err(queued()),
+        };
+        let waiting = cancel.clone();
+        let slot = tokio::task::spawn_blocking(move || SlicerSlot::acquire_unless(Some(&waiting)))
+            .await
+            .map_err(|e| OrcaError::SlicerFailed(format!("waiting for a slicer slot: {}", e)))?
+            .ok_or_else(queued)?;
+        Ok((permit, slot))
+    }
+
+    async fn run(&self, spec: JobSpec, watch: SlicerWatch, state: Arc<JobState>) {
+        let cancel = watch.cancel.clone().unwrap_or_default();
+        let queued_at = Instant::now();
+        let held = match self.slot(&cancel).await {
+            Ok(held) => held,
+            Err(e) => {
+                self.record(|counts| {
+                    counts.queued -= 1;
+                    counts.tally(Some(&e));
+                });
+                state.finish(Err(e), None);
+                return;
+            }
+        };
+        self.record(|counts| {
+            counts.queued -= 1;
+            counts.running += 1;
+        });
+        {
+            let mut progress = locked(&state.progress);
+            progress.status = "running";
+            progress.wait_ms = Some(queued_at.elapsed().as_secs_f64() * 1000.0);
+        }
+
+        let started = Instant::now();
+        let profiles = SlicerProfiles {
+            machine: &spec.machine_profile,
+            process: &spec.process_profile,
+            filament: &spec.filament_profile,
+        };
+        let outcome = self
+            .retry
+            .clone()
+            .unwrap_or_default()
+            .run_async(&watch, || {
+                run_slicer_async(
+                    &spec.cli_path,
+                    Path::new(&spec.model_path),
+                    &profiles,
+                    Path::new(&spec.output_dir),
+                    Path::new(&spec.working_dir),
+                    Duration::from_secs_f64(self.timeout_secs),
+                    self.limits.as_ref(),
+                    &watch,
+                )
+            })
+            .await;
+        let run_secs = started.elapsed().as_secs_f64();
+        drop(held);
+
+        self.record(|counts| {
+            counts.running -= 1;
+            counts.tally(outcome.as_ref().err());
+        });
+        if outcome.is_ok() {
+            record_slice_seconds(run_secs);
+        }
+        state.finish(outcome, Some(run_secs * 1000.0));
+    }
+
+    fn record(&self, update: impl FnOnce(&mut PoolCounts)) {
+        update(&mut locked(&self.counts));
+    }
+}
+
+#[pymethods]
+impl SlicerPool {
+    /// Queue a slicer run; returns at once with a job to await
+    ///
+    /// Arguments are those of `execute_slicer_async`. Cancelling `cancel`, or
+    /// calling the job's `cancel()`, takes it out of the queue or kills its
+    /// slicer.
+    #[pyo3(signature = (cli_path, model_path, machine_profile, process_profile, filament_profile, output_dir, working_dir=None, on_output=None, cancel=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn submit(
+        &self,
+        cli_path: String,
+        model_path: String,
+        machine_profile: String,
+        process_profile: String,
+        filament_profile: String,
+        output_dir: String,
+        working_dir: Option<String>,
+        on_output: Option<PyObject>,
+        cancel: Option<CancellationToken>,
+    ) -> SlicerJob {
+        let cancel = cancel.unwrap_or_default();
+        let state = Arc::new(JobState {
+            progress: Mutex::new(JobProgress {
+                status: "queued",
+                wait_ms: None,
+                run_ms: None,
+                outcome: None,
+            }),
+            finished: Notify::new(),
+        });
+        let spec = JobSpec {
+            working_dir: working_dir.unwrap_or_else(|| output_dir.clone()),
+            cli_path,
+            model_path,
+            machine_profile,
+            process_profile,
+            filament_profile,
+            output_dir,
+        };
+        let watch = SlicerWatch {
+            on_output,
+            cancel: Some(cancel.clone()),
+        };
+        self.record(|counts| counts.queued += 1);
+        let pool = self.clone();
+        let job_state = Arc::clone(&state);
+        get_runtime().spawn(async move { pool.run(spec, watch, job_state).await });
+        SlicerJob { state, cancel }
+    }
+
+    /// Queued, running and finished jobs
+    fn stats(&self) -> SlicerPoolStats {
+        let counts = locked(&self.counts);
+        SlicerPoolStats {
+            max_concurrent: self.max_concurrent,
+            queued: counts.queued,
+            running: counts.running,
+            completed: counts.completed,
+            failed: counts.failed,
+            cancelled: counts.cancelled,
+        }
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerPool(max_concurrent={}, timeout_secs={})",
+            self.max_concurrent, self.timeout_secs
+        )
+    }
+}
+
+/// Create a pool running at most `max_concurrent` slicer processes at once
+///
+/// Jobs submitted beyond that wait in order for a free place, so a burst of
+/// quotes queues instead of running every slicer at once and exhausting
+/// memory. Each run is killed after `timeout_secs`, run again after a crash
+/// as `retry` allows, and held to `limits`. The process-wide cap from
+/// `set_slicer_concurrency` applies on top.
+#[pyfunction]
+#[pyo3(signature = (max_concurrent, timeout_secs=300.0, retry=None, limits=None))]
+pub fn create_slicer_pool(
+    max_concurrent: usize,
+    timeout_secs: f64,
+    retry: Option<SlicerRetryPolicy>,
+    limits: Option<SlicerLimits>,
+) -> PyResult<SlicerPool> {
+    panic_boundary::catch(|| {
+        if max_concurrent == 0 {
+            return Err(OrcaError::InvalidConfig {
+                path: "max_concurrent".to_string(),
+                message: "a pool needs room for at least one slicer".to_string(),
+            }
+            .into());
+        }
+        if !(timeout_secs > 0.0 && timeout_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "timeout_secs".to_string(),
+                message: format!("{} is not a positive number of seconds", timeout_secs),
+            }
+            .into());
+        }
+        Ok(SlicerPool {
+            max_concurrent,
+            timeout_secs,
+            retry,
+            limits,
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            counts: Arc::new(Mutex::new(PoolCounts::default())),
+        })
+    })
+}
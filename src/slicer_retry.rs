@@ -0,0 +1,261 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::metrics;
+use crate::panic_boundary;
+use crate::slicer::SlicerWatch;
+use crate::OrcaError;
+
+/// Pieces of a slicer failure, lowercased, that mark it as a crash worth another
+/// run rather than a model or profile it will never slice.
+const TRANSIENT_PATTERNS: &[&str] = &[
+    "signal:", // Killed by a signal, e.g. "signal: 11 (SIGSEGV)"
+    "segmentation fault",
+    "core dumped",
+    "x error",
+    "cannot open display",
+    "glx",
+    "opengl",
+    "gpu",
+];
+
+/// One run of the slicer for a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SlicerAttempt {
+    /// 1 for the first run.
+    #[pyo3(get)]
+    pub attempt: u32,
+    #[pyo3(get)]
+    pub duration_ms: f64,
+    /// Why the run failed; `None` for the run that succeeded.
+    #[pyo3(get)]
+    pub error: Option<String>,
+}
+
+#[pymethods]
+impl SlicerAttempt {
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerAttempt(attempt={}, duration_ms={:.1}, error={:?})",
+            self.attempt, self.duration_ms, self.error
+        )
+    }
+}
+
+/// How often, and how long apart, a slicer run that crashed is run again
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct SlicerRetryPolicy {
+    /// Runs in all, the first included.
+    #[pyo3(get)]
+    pub max_attempts: u32,
+    /// Wait before the second run.
+    #[pyo3(get)]
+    pub backoff_secs: f64,
+    /// Each later wait is this many times the one before.
+    #[pyo3(get)]
+    pub backoff_multiplier: f64,
+    /// Lowercase pieces of the slicer's error that make a failure worth retrying.
+    #[pyo3(get)]
+    pub transient_patterns: Vec<String>,
+}
+
+impl Default for SlicerRetryPolicy {
+    /// One run, nothing retried.
+    fn default() -> Self {
+        SlicerRetryPolicy {
+            max_attempts: 1,
+            backoff_secs: 0.0,
+            backoff_multiplier: 1.0,
+            transient_patterns: TRANSIENT_PATTERNS.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+impl SlicerRetryPolicy {
+    /// Whether a run that failed with `error` may succeed if run again. Timeouts,
+    /// cancellations and slicers stopped by their limits never do: a slice that
+    /// hung or outgrew its limits would do so again.
+    pub fn is_transient(&self, error: &OrcaError) -> bool {
+        let message = match error {
+            OrcaError::SlicerExited(failure) if failure.category == "resource_limit" => {
+                return false
+            }
+            OrcaError::SlicerFailed(message) => message.to_lowercase(),
+            OrcaError::SlicerExited(failure) => failure.to_string().to_lowercase(),
+            _ => return false,
+        };
+        self.transient_patterns
+            .iter()
+            .any(|pattern| message.contains(pattern.as_str()))
+    }
+
+    /// Add the run that began at `started` to `attempts`; the wait before the
+    /// next run when it failed and should be retried.
+    fn record(
+        &self,
+        attempts: &mut Vec<SlicerAttempt>,
+        started: Instant,
+        outcome: &Result<(), OrcaError>,
+    ) -> Option<Duration> {
+        let attempt = attempts.len() as u32 + 1;
+        attempts.push(SlicerAttempt {
+            attempt,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            error: outcome.as_ref().err().map(ToString::to_string),
+        });
+        let error = outcome.as_ref().err()?;
+        if attempt >= self.max_attempts || !self.is_transient(error) {
+            return None;
+        }
+        let delay = Duration::from_secs_f64(
+            self.backoff_secs * self.backoff_multiplier.powi(attempt as i32 - 1),
+        );
+        tracing::warn!(
+            attempt,
+            error = %error,
+            delay_secs = delay.as_secs_f64(),
+            "slicer failed; running it again"
+        );
+        metrics::record_slicer_retry();
+        Some(delay)
+    }
+
+    /// Call `run` until it succeeds, fails for good or uses up the attempts,
+    /// waiting between runs; every run made when it succeeds.
+    pub fn run(
+        &self,
+        watch: &SlicerWatch,
+        mut run: impl FnMut() -> Result<(), OrcaError>,
+    ) -> Result<Vec<SlicerAttempt>, OrcaError> {
+        let mut attempts = Vec::new();
+        loop {
+            let started = Instant::now();
+            let outcome = run();
+            let Some(delay) = self.record(&mut attempts, started, &outcome) else {
+                return finish(outcome, attempts);
+            };
+            let until = Instant::now() + delay;
+            loop {
+                watch.check("slicer retry")?;
+                let left = until.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    break;
+                }
+                thread::sleep(left.min(Duration::from_millis(20)));
+            }
+        }
+    }
+
+    /// `run` for async slicer runs.
+    pub async fn run_async<F, Fut>(
+        &self,
+        watch: &SlicerWatch,
+        mut run: F,
+    ) -> Result<Vec<SlicerAttempt>, OrcaError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<(), OrcaError>>,
+    {
+        let mut attempts = Vec::new();
+        loop {
+            let started = Instant::now();
+            let outcome = run().await;
+            let Some(delay) = self.record(&mut attempts, started, &outcome) else {
+                return finish(outcome, attempts);
+            };
+            let cancelled = async {
+                match &watch.cancel {
+                    Some(cancel) => cancel.wait_cancelled().await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                () = tokio::time::sleep(delay) => {}
+                () = cancelled => return Err(OrcaError::Cancelled("slicer retry".to_string())),
+            }
+        }
+    }
+}
+
+/// The attempts, or the last run's error noting how many runs failed.
+fn finish(
+    outcome: Result<(), OrcaError>,
+    attempts: Vec<SlicerAttempt>,
+) -> Result<Vec<SlicerAttempt>, OrcaError> {
+    match outcome {
+        Ok(()) => Ok(attempts),
+        Err(OrcaError::SlicerFailed(message)) if attempts.len() > 1 => Err(
+            OrcaError::SlicerFailed(format!("{} (after {} attempts)", message, attempts.len())),
+        ),
+        Err(OrcaError::SlicerExited(mut failure)) => {
+            failure.attempts = attempts.len() as u32;
+            Err(OrcaError::SlicerExited(failure))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[pymethods]
+impl SlicerRetryPolicy {
+    fn __str__(&self) -> String {
+        format!(
+            "SlicerRetryPolicy(max_attempts={}, backoff_secs={}, backoff_multiplier={})",
+            self.max_attempts, self.backoff_secs, self.backoff_multiplier
+        )
+    }
+}
+
+/// Create a policy for running the slicer again when it crashes
+///
+/// OrcaSlicer now and then dies on a GPU or X error, or a signal, and slices
+/// fine when run again. A failure whose message contains one of
+/// `transient_patterns` (crashes, signals and display errors by default) is
+/// retried up to `max_attempts` runs in all, waiting `backoff_secs` before the
+/// second and `backoff_multiplier` times longer before each one after. Other
+/// failures, timeouts and cancellations are not retried.
+#[pyfunction]
+#[pyo3(signature = (max_attempts=3, backoff_secs=2.0, backoff_multiplier=2.0, transient_patterns=None))]
+pub fn create_slicer_retry_policy(
+    max_attempts: u32,
+    backoff_secs: f64,
+    backoff_multiplier: f64,
+    transient_patterns: Option<Vec<String>>,
+) -> PyResult<SlicerRetryPolicy> {
+    panic_boundary::catch(|| {
+        if max_attempts == 0 {
+            return Err(OrcaError::InvalidConfig {
+                path: "max_attempts".to_string(),
+                message: "the slicer has to run at least once".to_string(),
+            }
+            .into());
+        }
+        if !(backoff_secs >= 0.0 && backoff_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "backoff_secs".to_string(),
+                message: format!("{} is not a number of seconds", backoff_secs),
+            }
+            .into());
+        }
+        if !(backoff_multiplier >= 1.0 && backoff_multiplier.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "backoff_multiplier".to_string(),
+                message: format!("{} would shorten the wait between runs", backoff_multiplier),
+            }
+            .into());
+        }
+        let defaults = SlicerRetryPolicy::default();
+        Ok(SlicerRetryPolicy {
+            max_attempts,
+            backoff_secs,
+            backoff_multiplier,
+            transient_patterns: transient_patterns
+                .map(|patterns| patterns.iter().map(|p| p.to_lowercase()).collect())
+                .unwrap_or(defaults.transient_patterns),
+        })
+    })
+}
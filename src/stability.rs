@@ -0,0 +1,174 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::geometry::{model_triangles, BoundingBox};
+use crate::overhangs::Overhangs;
+
+/// Centre of mass this many footprint widths up and the model is certain to
+/// tip or pull off the bed; half a width (a cube) is no risk at all.
+const TIPPING_RATIO: f64 = 3.0;
+const SQUAT_RATIO: f64 = 0.5;
+/// Risk from which a brim, and then a raft, is recommended.
+const BRIM_RISK: f64 = 0.4;
+const RAFT_RISK: f64 = 0.75;
+
+/// How likely a model is to tip over or come off the bed mid-print
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct Stability {
+    /// Centre of mass of the solid, in the model's coordinates (mm).
+    #[pyo3(get)]
+    pub center_of_mass_mm: (f64, f64, f64),
+    /// Area of the faces resting on the bed; 0 when it stands on points or
+    /// edges.
+    #[pyo3(get)]
+    pub footprint_area_mm2: f64,
+    /// 0 for a squat model on a broad base, up to 1 for one tall and narrow
+    /// for its footprint, or with its centre of mass beyond it.
+    #[pyo3(get)]
+    pub risk_score: f64,
+    /// "brim" or "raft" when the risk calls for one.
+    #[pyo3(get)]
+    pub recommendation: Option<String>,
+    /// For the operator, when there is a recommendation.
+    #[pyo3(get)]
+    pub warning: Option<String>,
+}
+
+#[pymethods]
+impl Stability {
+    fn __str__(&self) -> String {
+        format!(
+            "Stability(risk={:.2}, footprint={:.1}mm², recommendation={:?})",
+            self.risk_score, self.footprint_area_mm2, self.recommendation
+        )
+    }
+}
+
+/// Volume and centre of a closed mesh, gathered one triangle at a time from
+/// the tetrahedra each face makes with a fixed point. Open meshes, which
+/// enclose no volume, fall back to the centre of their surface.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Balance {
+    origin: Option<[f64; 3]>,
+    /// Six times the signed volume.
+    volume: f64,
+    moment: [f64; 3],
+    area: f64,
+    area_moment: [f64; 3],
+}
+
+impl Balance {
+    pub fn new() -> Self {
+        Balance {
+            origin: None,
+            volume: 0.0,
+            moment: [0.0; 3],
+            area: 0.0,
+            area_moment: [0.0; 3],
+        }
+    }
+
+    pub fn include(&mut self, triangle: [[f64; 3]; 3]) {
+        // Measured from the first corner seen, to keep far-off models precise.
+        let origin = *self.origin.get_or_insert(triangle[0]);
+        let [a, b, c] = triangle.map(|corner| [0, 1, 2].map(|axis| corner[axis] - origin[axis]));
+        let cross = |u: [f64; 3], v: [f64; 3]| {
+            [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ]
+        };
+        let bc = cross(b, c);
+        let volume = a[0] * bc[0] + a[1] * bc[1] + a[2] * bc[2];
+        let normal = cross(
+            [b[0] - a[0], b[1] - a[1], b[2] - a[2]],
+            [c[0] - a[0], c[1] - a[1], c[2] - a[2]],
+        );
+        let area = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+        self.volume += volume;
+        self.area += area;
+        for axis in 0..3 {
+            let sum = a[axis] + b[axis] + c[axis];
+            self.moment[axis] += volume * sum;
+            self.area_moment[axis] += area * sum;
+        }
+    }
+
+    /// Centre of mass, or of the surface for a mesh with no volume.
+    pub fn center(&self) -> Option<[f64; 3]> {
+        let origin = self.origin?;
+        let center = if self.volume.abs() > 1e-9 {
+            self.moment.map(|moment| moment / (4.0 * self.volume))
+        } else if self.area > 0.0 {
+            self.area_moment.map(|moment| moment / (3.0 * self.area))
+        } else {
+            return None;
+        };
+        Some([0, 1, 2].map(|axis| center[axis] + origin[axis]))
+    }
+}
+
+/// Score a model from its bounds, its faces on the bed and its balance.
+pub(crate) fn assess(
+    bounds: &BoundingBox,
+    overhangs: &Overhangs,
+    balance: &Balance,
+) -> Option<Stability> {
+    let center = balance.center()?;
+    let floor = bounds.min[2];
+    let footprint = overhangs.footprint(floor);
+    let risk_score = match footprint {
+        Some((area, [min_x, min_y, max_x, max_y])) => {
+            let beyond_base =
+                center[0] < min_x || center[0] > max_x || center[1] < min_y || center[1] > max_y;
+            if beyond_base {
+                1.0
+            } else {
+                let ratio = (center[2] - floor) / area.sqrt();
+                ((ratio - SQUAT_RATIO) / (TIPPING_RATIO - SQUAT_RATIO)).clamp(0.0, 1.0)
+            }
+        }
+        None => 1.0,
+    };
+    let recommendation = if risk_score >= RAFT_RISK {
+        Some("raft")
+    } else if risk_score >= BRIM_RISK {
+        Some("brim")
+    } else {
+        None
+    };
+    let footprint_area_mm2 = footprint.map_or(0.0, |(area, _)| area);
+    Some(Stability {
+        center_of_mass_mm: (center[0], center[1], center[2]),
+        footprint_area_mm2,
+        risk_score,
+        recommendation: recommendation.map(str::to_string),
+        warning: recommendation.map(|adhesion| {
+            format!(
+                "Tall or top-heavy model (tipping risk {:.2}, {:.0} mm² on the bed): print with a {}",
+                risk_score, footprint_area_mm2, adhesion
+            )
+        }),
+    })
+}
+
+/// Stability of any mesh format, read in full; `None` for STEP.
+pub fn model_stability(path: &Path) -> std::io::Result<Option<Stability>> {
+    let Some(triangles) = model_triangles(path)? else {
+        return Ok(None);
+    };
+    let (mut bounds, mut overhangs, mut balance) =
+        (BoundingBox::empty(), Overhangs::new(), Balance::new());
+    for triangle in &triangles {
+        let triangle = triangle.map(|corner| corner.map(f64::from));
+        for corner in triangle {
+            bounds.include(corner);
+        }
+        overhangs.include(triangle);
+        balance.include(triangle);
+    }
+    Ok(assess(&bounds, &overhangs, &balance))
+}
@@ -0,0 +1,73 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// An instance's entity name, e.g. `PRODUCT` in `#12 = PRODUCT('bracket', ...`.
+static INSTANCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"#\d+\s*=\s*([A-Z][A-Z0-9_]*)\s*\(").unwrap());
+
+/// Entities that are one solid body each.
+const SOLIDS: &[&str] = &["MANIFOLD_SOLID_BREP", "BREP_WITH_VOIDS", "FACETED_BREP"];
+
+/// What a STEP file holds: one part, or an assembly of several
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct StepSummary {
+    /// `PRODUCT` entities: each part, and each assembly of parts.
+    #[pyo3(get)]
+    pub products: u64,
+    /// `NEXT_ASSEMBLY_USAGE_OCCURRENCE` entities: each placement of a part
+    /// or sub-assembly in an assembly.
+    #[pyo3(get)]
+    pub assembly_usages: u64,
+    /// Solid bodies (B-reps) across all parts.
+    #[pyo3(get)]
+    pub solids: u64,
+    /// More than one product, or any product placed in another.
+    #[pyo3(get)]
+    pub is_assembly: bool,
+}
+
+#[pymethods]
+impl StepSummary {
+    fn __str__(&self) -> String {
+        format!(
+            "StepSummary(products={}, assembly_usages={}, solids={}, assembly={})",
+            self.products, self.assembly_usages, self.solids, self.is_assembly
+        )
+    }
+}
+
+/// Entity counts gathered from a STEP file's lines.
+#[derive(Default)]
+pub(crate) struct StepEntities {
+    products: u64,
+    assembly_usages: u64,
+    solids: u64,
+}
+
+impl StepEntities {
+    pub fn scan(&mut self, line: &str) {
+        if !line.contains('=') {
+            return;
+        }
+        for instance in INSTANCE.captures_iter(line) {
+            match &instance[1] {
+                "PRODUCT" => self.products += 1,
+                "NEXT_ASSEMBLY_USAGE_OCCURRENCE" => self.assembly_usages += 1,
+                name if SOLIDS.contains(&name) => self.solids += 1,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn finish(self) -> StepSummary {
+        StepSummary {
+            products: self.products,
+            assembly_usages: self.assembly_usages,
+            solids: self.solids,
+            is_assembly: self.products > 1 || self.assembly_usages > 0,
+        }
+    }
+}
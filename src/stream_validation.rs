@@ -0,0 +1,574 @@
+use memchr::memchr;
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::file_sniffing::{check_content, SNIFF_BYTES};
+use crate::geometry::{include_stl_record, parse_point, SurfaceScan};
+use crate::memory_limits::Budget;
+use crate::mesh_warnings::{MeshWarnings, ObjVertices};
+use crate::obj_materials::MaterialRefs;
+use crate::panic_boundary;
+use crate::step_entities::StepEntities;
+use crate::validation_cache::hex_digest;
+use crate::validation_limits::{file_type, ValidationLimits};
+use crate::{model_info, ModelInfo, ValidationError};
+
+const STL_HEADER_BYTES: usize = 84;
+const STL_RECORD_BYTES: usize = 50;
+
+/// ASCII STL validation, a line at a time.
+pub(crate) struct AsciiStlScan {
+    surface: SurfaceScan,
+    corners: Vec<[f64; 3]>,
+    found_endsolid: bool,
+    lines: u64,
+}
+
+impl AsciiStlScan {
+    pub fn new() -> Self {
+        AsciiStlScan {
+            surface: SurfaceScan::new(),
+            corners: Vec::with_capacity(3),
+            found_endsolid: false,
+            lines: 0,
+        }
+    }
+
+    /// Take a trimmed line; true once `endsolid` ends the model.
+    pub fn line(&mut self, trimmed: &str) -> bool {
+        self.lines += 1;
+        // Bounds, overhangs and balance come from the vertex lines on the same pass.
+        if let Some(point) = trimmed.strip_prefix("vertex") {
+            if let Some(point) = parse_point(point.split_whitespace()) {
+                self.surface.bounds.include(point);
+                self.corners.push(point);
+            }
+        } else if trimmed.starts_with("endloop") {
+            let corners = &self.corners;
+            for pair in corners.windows(2).skip(1) {
+                self.surface.include([corners[0], pair[0], pair[1]]);
+            }
+            self.corners.clear();
+        } else if trimmed.starts_with("endsolid") {
+            self.found_endsolid = true;
+        }
+        self.found_endsolid
+    }
+
+    pub fn finish(self, file_size: u64) -> ModelInfo {
+        if !self.found_endsolid {
+            return ModelInfo::invalid(
+                "stl",
+                file_size,
+                "Invalid ASCII STL format - missing endsolid".to_string(),
+            );
+        }
+        valid_stl(file_size, &self.surface, Some(self.lines))
+    }
+}
+
+/// Result for a binary STL of `triangle_count` triangles whose records,
+/// when the size is right, made `surface`.
+pub(crate) fn binary_stl_info(
+    file_size: u64,
+    triangle_count: u32,
+    surface: &SurfaceScan,
+) -> ModelInfo {
+    let expected_size = binary_stl_size(triangle_count);
+    if file_size != expected_size {
+        return ModelInfo::invalid(
+            "stl",
+            file_size,
+            format!(
+                "Binary STL size mismatch. Expected {}, got {}",
+                expected_size, file_size
+            ),
+        );
+    }
+    valid_stl(file_size, surface, None)
+}
+
+pub(crate) fn binary_stl_size(triangle_count: u32) -> u64 {
+    (STL_HEADER_BYTES as u64).saturating_add(triangle_count as u64 * STL_RECORD_BYTES as u64)
+}
+
+fn valid_stl(file_size: u64, surface: &SurfaceScan, line_count: Option<u64>) -> ModelInfo {
+    let (overhang_area_mm2, needs_supports, stability) = surface.fields(true);
+    ModelInfo {
+        dimensions_mm: Some(surface.bounds)
+            .filter(|b| !b.is_empty())
+            .map(|b| b.size()),
+        overhang_area_mm2,
+        needs_supports,
+        stability,
+        warnings: surface.warnings.messages(),
+        triangle_count: Some(surface.triangles),
+        line_count,
+        ..ModelInfo::valid("stl", file_size)
+    }
+}
+
+/// OBJ validation, a line at a time. `usemtl` can come anywhere, so the
+/// whole file is read.
+#[derive(Default)]
+pub(crate) struct ObjScan {
+    lines: u64,
+    has_vertices: bool,
+    has_faces: bool,
+    materials: MaterialRefs,
+    vertices: ObjVertices,
+    warnings: MeshWarnings,
+}
+
+impl ObjScan {
+    pub fn line(&mut self, trimmed: &str) {
+        self.lines += 1;
+        if let Some(point) = trimmed.strip_prefix("v ") {
+            self.has_vertices = true;
+            self.vertices.vertex(point.split_whitespace());
+        } else if let Some(corners) = trimmed.strip_prefix("f ") {
+            self.has_faces = true;
+            self.vertices
+                .face(corners.split_whitespace(), &mut self.warnings);
+        } else {
+            self.materials.scan(trimmed);
+        }
+    }
+
+    /// Material libraries are looked for beside `path`.
+    pub fn finish(mut self, path: &Path, file_size: u64) -> ModelInfo {
+        if !(self.has_vertices && self.has_faces) {
+            return ModelInfo::invalid(
+                "obj",
+                file_size,
+                "Invalid OBJ format - missing vertices or faces".to_string(),
+            );
+        }
+        let triangle_count = self.vertices.triangles();
+        self.vertices.finish(&mut self.warnings);
+        ModelInfo {
+            obj_materials: self.materials.finish(path),
+            warnings: self.warnings.messages(),
+            triangle_count: Some(triangle_count),
+            line_count: Some(self.lines),
+            ..ModelInfo::valid("obj", file_size)
+        }
+    }
+}
+
+/// STEP validation, a line at a time: the ISO header, HEADER and DATA
+/// sections and the closing line, with the entities in between.
+#[derive(Default)]
+pub(crate) struct StepScan {
+    lines: u64,
+    has_iso_header: bool,
+    has_header_section: bool,
+    has_data_section: bool,
+    has_end_iso: bool,
+    entities: StepEntities,
+}
+
+impl StepScan {
+    /// Take a trimmed line; true once `END-ISO-10303` ends the file.
+    pub fn line(&mut self, trimmed: &str) -> bool {
+        self.lines += 1;
+        if self.lines == 1 {
+            self.has_iso_header = trimmed.starts_with("ISO-10303");
+        }
+        if trimmed == "HEADER;" {
+            self.has_header_section = true;
+        } else if trimmed == "DATA;" {
+            self.has_data_section = true;
+        } else if trimmed.starts_with("END-ISO-10303") {
+            self.has_end_iso = true;
+        } else if self.has_data_section {
+            self.entities.scan(trimmed);
+        }
+        self.has_end_iso
+    }
+
+    /// A first line that is not the ISO header rules the file out.
+    pub fn lacks_iso_header(&self) -> bool {
+        self.lines > 0 && !self.has_iso_header
+    }
+
+    pub fn finish(self, file_size: u64) -> ModelInfo {
+        let mut missing_parts = Vec::new();
+        if !self.has_iso_header {
+            missing_parts.push("ISO header");
+        }
+        if !self.has_header_section {
+            missing_parts.push("HEADER section");
+        }
+        if !self.has_data_section {
+            missing_parts.push("DATA section");
+        }
+        if !self.has_end_iso {
+            missing_parts.push("END-ISO section");
+        }
+        if !missing_parts.is_empty() {
+            return ModelInfo::invalid(
+                "step",
+                file_size,
+                format!(
+                    "Invalid STEP format - missing: {}",
+                    missing_parts.join(", ")
+                ),
+            );
+        }
+        ModelInfo {
+            step_summary: Some(self.entities.finish()),
+            line_count: Some(self.lines),
+            ..ModelInfo::valid("step", file_size)
+        }
+    }
+}
+
+enum Stream {
+    /// An STL until "solid" or its 84-byte header says which encoding it is.
+    StlHeader,
+    AsciiStl(AsciiStlScan),
+    BinaryStl {
+        triangle_count: u32,
+        surface: SurfaceScan,
+    },
+    Obj(ObjScan),
+    Step(StepScan),
+    /// Formats read whole (3MF, AMF, PLY, archives), validated from the
+    /// saved file by `finish`.
+    Whole,
+    Finished,
+}
+
+impl Stream {
+    /// Triangles and lines scanned so far; for a binary STL, the triangles
+    /// its header declares.
+    fn counts(&self) -> (u64, u64) {
+        match self {
+            Stream::AsciiStl(scan) => (scan.surface.triangles, scan.lines),
+            Stream::BinaryStl { triangle_count, .. } => (*triangle_count as u64, 0),
+            Stream::Obj(scan) => (scan.vertices.triangles(), scan.lines),
+            Stream::Step(scan) => (0, scan.lines),
+            Stream::StlHeader | Stream::Whole | Stream::Finished => (0, 0),
+        }
+    }
+}
+
+/// Validation of an upload while it is received, a chunk at a time
+#[pyclass]
+pub struct StreamingValidator {
+    path: PathBuf,
+    stream: Stream,
+    /// The part of a line or record not yet complete.
+    pending: Vec<u8>,
+    received: u64,
+    /// The first `SNIFF_BYTES` fed, to check against the extension.
+    head: Vec<u8>,
+    /// SHA-256 of every byte fed, the model's and any after it.
+    digest: Sha256,
+    /// The model has ended; the rest of the upload is not looked at.
+    done: bool,
+    rejected: Option<ModelInfo>,
+    limits: ValidationLimits,
+}
+
+impl StreamingValidator {
+    fn feed_bytes(&mut self, data: &[u8]) -> Result<(), ValidationError> {
+        if let Some(info) = &self.rejected {
+            return Err(rejection(info));
+        }
+        if matches!(self.stream, Stream::Finished) {
+            return Err(finished());
+        }
+        self.received += data.len() as u64;
+        self.digest.update(data);
+        if self.head.len() < SNIFF_BYTES {
+            let needed = (SNIFF_BYTES - self.head.len()).min(data.len());
+            self.head.extend_from_slice(&data[..needed]);
+            if self.head.len() == SNIFF_BYTES {
+                if let Some(info) = check_content(&self.path, &self.head, self.received) {
+                    let error = rejection(&info);
+                    self.rejected = Some(info);
+                    return Err(error);
+                }
+            }
+        }
+        if self.done {
+            return Ok(());
+        }
+        match self.stream {
+            Stream::StlHeader => {
+                self.pending.extend_from_slice(data);
+                let bytes = if self.pending.starts_with(b"solid") {
+                    self.stream = Stream::AsciiStl(AsciiStlScan::new());
+                    std::mem::take(&mut self.pending)
+                } else if self.pending.len() >= STL_HEADER_BYTES {
+                    let count = &self.pending[80..STL_HEADER_BYTES];
+                    self.stream = Stream::BinaryStl {
+                        triangle_count: u32::from_le_bytes([
+                            count[0], count[1], count[2], count[3],
+                        ]),
+                        surface: SurfaceScan::new(),
+                    };
+                    self.pending.drain(..STL_HEADER_BYTES);
+                    std::mem::take(&mut self.pending)
+                } else {
+                    return Ok(());
+                };
+                self.feed_decoded(&bytes)
+            }
+            _ => self.feed_decoded(data),
+        }
+    }
+
+    /// Refuse the upload once it has broken a limit.
+    fn check_limits(&mut self) -> Result<(), ValidationError> {
+        let (triangles, lines) = self.stream.counts();
+        if let Some(message) = self.limits.exceeded(self.received, triangles, lines) {
+            let info = ModelInfo::invalid(&file_type(&self.path), self.received, message);
+            let error = rejection(&info);
+            self.rejected = Some(info);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Pass on bytes once the format is known.
+    fn feed_decoded(&mut self, data: &[u8]) -> Result<(), ValidationError> {
+        match &mut self.stream {
+            Stream::BinaryStl {
+                triangle_count,
+                surface,
+            } => {
+                let expected_size = binary_stl_size(*triangle_count);
+                if self.received > expected_size {
+                    let info = ModelInfo::invalid(
+                        "stl",
+                        self.received,
+                        format!(
+                            "Binary STL size mismatch. Expected {}, got at least {}",
+                            expected_size, self.received
+                        ),
+                    );
+                    let error = rejection(&info);
+                    self.rejected = Some(info);
+                    return Err(error);
+                }
+                let mut data = data;
+                if !self.pending.is_empty() {
+                    let needed = (STL_RECORD_BYTES - self.pending.len()).min(data.len());
+                    self.pending.extend_from_slice(&data[..needed]);
+                    data = &data[needed..];
+                    if self.pending.len() < STL_RECORD_BYTES {
+                        return Ok(());
+                    }
+                    include_stl_record(surface, &self.pending);
+                    self.pending.clear();
+                }
+                let mut records = data.chunks_exact(STL_RECORD_BYTES);
+                for record in &mut records {
+                    include_stl_record(surface, record);
+                }
+                self.pending.extend_from_slice(records.remainder());
+                Ok(())
+            }
+            Stream::Whole => Ok(()),
+            _ => self.feed_lines(data),
+        }
+    }
+
+    fn feed_lines(&mut self, mut data: &[u8]) -> Result<(), ValidationError> {
+        while let Some(end) = memchr(b'\n', data) {
+            let done = if self.pending.is_empty() {
+                scan_line(&mut self.stream, &data[..end])?
+            } else {
+                self.pending.extend_from_slice(&data[..end]);
+                let line = std::mem::take(&mut self.pending);
+                scan_line(&mut self.stream, &line)?
+            };
+            data = &data[end + 1..];
+            if done {
+                self.done = true;
+                self.pending.clear();
+                return Ok(());
+            }
+            if let Stream::Step(scan) = &self.stream {
+                if scan.lacks_iso_header() {
+                    let info = ModelInfo::invalid(
+                        "step",
+                        self.received,
+                        "Invalid STEP format - missing: ISO header".to_string(),
+                    );
+                    let error = rejection(&info);
+                    self.rejected = Some(info);
+                    return Err(error);
+                }
+            }
+        }
+        self.pending.extend_from_slice(data);
+        // Like `memory_limits::lines`, a line may not outgrow the budget.
+        let limit = Budget::MeshAnalysis.limit();
+        if limit > 0 && self.pending.len() as u64 > limit {
+            return Err(Budget::MeshAnalysis.exceeded(limit).into());
+        }
+        Ok(())
+    }
+
+    fn finish_info(&mut self) -> Result<ModelInfo, ValidationError> {
+        let stream = std::mem::replace(&mut self.stream, Stream::Finished);
+        // Like `model_info`, content that is not what the extension says
+        // outranks whatever else is wrong with it.
+        if let Some(info) = check_content(&self.path, &self.head, self.received) {
+            return Ok(info);
+        }
+        if let Some(info) = self.rejected.clone() {
+            return Ok(info);
+        }
+        // The last line need not end in a newline.
+        let mut stream = stream;
+        if !self.done && !self.pending.is_empty() && !matches!(stream, Stream::BinaryStl { .. }) {
+            let line = std::mem::take(&mut self.pending);
+            scan_line(&mut stream, &line)?;
+        }
+        let size = self.received;
+        let info = match stream {
+            Stream::StlHeader if size < 5 => {
+                ModelInfo::invalid("stl", size, "File too small to be valid STL".to_string())
+            }
+            Stream::StlHeader => {
+                ModelInfo::invalid("stl", size, "Binary STL too small".to_string())
+            }
+            Stream::AsciiStl(scan) => scan.finish(size),
+            Stream::BinaryStl {
+                triangle_count,
+                surface,
+            } => binary_stl_info(size, triangle_count, &surface),
+            Stream::Obj(scan) => scan.finish(&self.path, size),
+            Stream::Step(scan) => scan.finish(size),
+            Stream::Whole => model_info(&self.path)?,
+            Stream::Finished => return Err(finished()),
+        };
+        let mut info = self.limits.apply(info);
+        if info.is_valid {
+            info.sha256 = Some(hex_digest(&std::mem::take(&mut self.digest).finalize()));
+        }
+        Ok(info)
+    }
+}
+
+/// Hand one line, without its newline, to the stream's scanner; true once
+/// the model has ended.
+fn scan_line(stream: &mut Stream, line: &[u8]) -> Result<bool, ValidationError> {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let line = std::str::from_utf8(line).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        )
+    })?;
+    let trimmed = line.trim();
+    Ok(match stream {
+        Stream::AsciiStl(scan) => scan.line(trimmed),
+        Stream::Obj(scan) => {
+            scan.line(trimmed);
+            false
+        }
+        Stream::Step(scan) => scan.line(trimmed),
+        _ => false,
+    })
+}
+
+fn rejection(info: &ModelInfo) -> ValidationError {
+    ValidationError::InvalidFormat(info.error_message.clone().unwrap_or_default())
+}
+
+fn finished() -> ValidationError {
+    ValidationError::InvalidFormat("the validator has already finished".to_string())
+}
+
+#[pymethods]
+impl StreamingValidator {
+    /// Check the next chunk of the upload
+    fn feed(&mut self, py: Python<'_>, data: &[u8]) -> PyResult<()> {
+        panic_boundary::catch(|| {
+            Ok(py.allow_threads(|| {
+                self.feed_bytes(data)?;
+                self.check_limits()
+            })?)
+        })
+    }
+
+    /// The upload's validation result, once every chunk has been fed
+    fn finish(&mut self, py: Python<'_>) -> PyResult<ModelInfo> {
+        panic_boundary::catch(|| Ok(py.allow_threads(|| self.finish_info())?))
+    }
+
+    /// Bytes fed so far.
+    #[getter]
+    fn bytes_received(&self) -> u64 {
+        self.received
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "StreamingValidator(path={}, received={}, rejected={})",
+            self.path.display(),
+            self.received,
+            self.rejected.is_some()
+        )
+    }
+}
+
+/// Start validating an upload being saved to `file_path`, chunk by chunk
+///
+/// Feed the validator each chunk as it is written with `feed`, and call
+/// `finish` once the upload is complete for the same `ModelInfo` the file's
+/// validator would give. STL, OBJ and STEP are checked as they stream in,
+/// holding no more than a line or a triangle, and `feed` raises ValueError
+/// as soon as the upload cannot be valid: a binary STL longer than its
+/// triangle count allows, a STEP file without its ISO header, or an
+/// unsupported extension. Other formats, and OBJ material libraries, are
+/// checked from the saved file by `finish`. An upload over `limits` (the
+/// defaults of `create_validation_limits` without them) is refused as soon
+/// as it is over.
+#[pyfunction]
+#[pyo3(signature = (file_path, limits=None))]
+pub fn create_streaming_validator(
+    file_path: String,
+    limits: Option<ValidationLimits>,
+) -> PyResult<StreamingValidator> {
+    panic_boundary::catch(|| {
+        let path = PathBuf::from(file_path);
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_lowercase());
+        let (stream, rejected) = match extension.as_deref() {
+            Some("stl") => (Stream::StlHeader, None),
+            Some("obj") => (Stream::Obj(ObjScan::default()), None),
+            Some("step" | "stp") => (Stream::Step(StepScan::default()), None),
+            Some("3mf" | "amf" | "ply" | "zip" | "gz") => (Stream::Whole, None),
+            _ => (
+                Stream::Whole,
+                Some(ModelInfo::invalid(
+                    "unknown",
+                    0,
+                    "Unsupported file type".to_string(),
+                )),
+            ),
+        };
+        Ok(StreamingValidator {
+            path,
+            stream,
+            pending: Vec::new(),
+            received: 0,
+            head: Vec::with_capacity(SNIFF_BYTES),
+            digest: Sha256::new(),
+            done: false,
+            rejected,
+            limits: limits.unwrap_or_default(),
+        })
+    })
+}
@@ -0,0 +1,293 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+use crate::audit::unix_timestamp;
+use crate::business_calendar::BusinessCalendar;
+use crate::farm_load::LeadTime;
+use crate::ledger::utc_datetime;
+use crate::panic_boundary;
+use crate::{CostBreakdown, OrcaError};
+
+/// How far ahead the next off-peak hour is looked for; windows repeat weekly.
+const SEARCH_HOURS: u32 = 8 * 24;
+
+/// Hours of the week when machine time is charged at a reduced rate
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct TimeOfUsePricing {
+    /// `(start_hour, end_hour, rate_multiplier)` in shop time; a window with
+    /// `end_hour` before `start_hour` runs past midnight, e.g. `(22, 6, 0.7)`.
+    #[pyo3(get)]
+    pub windows: Vec<(u32, u32, f64)>,
+    /// Days the windows' hours fall on, 0 = Monday to 6 = Sunday.
+    #[pyo3(get)]
+    pub days: Vec<u32>,
+    /// Shop time zone, e.g. 8.0 for Singapore.
+    #[pyo3(get)]
+    pub utc_offset_hours: f64,
+}
+
+#[pymethods]
+impl TimeOfUsePricing {
+    fn __str__(&self) -> String {
+        format!(
+            "TimeOfUsePricing(windows={:?}, days={:?}, utc_offset={}h)",
+            self.windows, self.days, self.utc_offset_hours
+        )
+    }
+}
+
+/// The price of a quote when its print is started in off-peak hours
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct OffPeakPrice {
+    /// When the print has to start, in seconds since the Unix epoch.
+    #[pyo3(get)]
+    pub start_at: f64,
+    /// `start_at` as "YYYY-MM-DD HH:MM" in the shop's time zone.
+    #[pyo3(get)]
+    pub start_time: String,
+    /// Average machine-hour rate over the print, as a share of the full rate.
+    #[pyo3(get)]
+    pub rate_factor: f64,
+    #[pyo3(get)]
+    pub time_cost: f64,
+    #[pyo3(get)]
+    pub total_cost: f64,
+    /// Below the start-anytime `cost.total_cost`.
+    #[pyo3(get)]
+    pub savings: f64,
+    /// Promised completion date for the off-peak start, by the business
+    /// calendar; `None` without one.
+    #[pyo3(get)]
+    pub promised_date: Option<String>,
+}
+
+#[pymethods]
+impl OffPeakPrice {
+    fn __str__(&self) -> String {
+        format!(
+            "OffPeakPrice(total=S${:.2}, savings=S${:.2}, start={})",
+            self.total_cost, self.savings, self.start_time
+        )
+    }
+}
+
+impl TimeOfUsePricing {
+    fn seconds_offset(&self) -> f64 {
+        self.utc_offset_hours * 3600.0
+    }
+
+    /// Machine-hour rate multiplier at a moment; the lowest of the windows it
+    /// falls in, 1.0 outside them.
+    pub fn rate_at(&self, at: f64) -> f64 {
+        let local = at + self.seconds_offset();
+        let day = (local / 86_400.0).floor();
+        // The epoch was a Thursday.
+        let weekday = (day as i64 + 3).rem_euclid(7) as u32;
+        if !self.days.contains(&weekday) {
+            return 1.0;
+        }
+        let hour = (local - day * 86_400.0) / 3600.0;
+        self.windows
+            .iter()
+            .filter(|(start, end, _)| {
+                let (start, end) = (*start as f64, *end as f64);
+                match start.partial_cmp(&end) {
+                    Some(std::cmp::Ordering::Less) => start <= hour && hour < end,
+                    Some(std::cmp::Ordering::Greater) => hour >= start || hour < end,
+                    _ => true,
+                }
+            })
+            .map(|(_, _, multiplier)| *multiplier)
+            .fold(1.0, f64::min)
+    }
+
+    /// The next whole hour of shop time after `at`; rates only change there.
+    fn next_hour(&self, at: f64) -> f64 {
+        let local = at + self.seconds_offset();
+        ((local / 3600.0).floor() + 1.0) * 3600.0 - self.seconds_offset()
+    }
+
+    /// Average rate multiplier over `seconds` of machine time from `start`.
+    pub fn rate_factor(&self, start: f64, seconds: f64) -> f64 {
+        if seconds <= 0.0 {
+            return self.rate_at(start);
+        }
+        let end = start + seconds;
+        let mut at = start;
+        let mut weighted = 0.0;
+        while at < end {
+            let next = self.next_hour(at).min(end);
+            weighted += self.rate_at(at) * (next - at);
+            at = next;
+        }
+        weighted / seconds
+    }
+
+    /// The first off-peak moment at or after `from`; `None` when no window
+    /// comes round within a week.
+    pub fn next_off_peak(&self, from: f64) -> Option<f64> {
+        let mut at = from;
+        for _ in 0..=SEARCH_HOURS {
+            if self.rate_at(at) < 1.0 {
+                return Some(at);
+            }
+            at = self.next_hour(at);
+        }
+        None
+    }
+
+    /// "YYYY-MM-DD HH:MM" of a moment, in the shop's time zone.
+    fn local_time(&self, at: f64) -> String {
+        let (year, month, day, hour, minute, _) =
+            utc_datetime((at + self.seconds_offset()).max(0.0) as u64);
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            year, month, day, hour, minute
+        )
+    }
+}
+
+/// The quote's price when the print starts at the first off-peak hour from
+/// `earliest_start`; `None` when that saves nothing, e.g. under the minimum price.
+///
+/// Only print time is discounted: the set-up hours in `cost.time_cost` are
+/// worked at the full rate whenever the print runs.
+pub fn off_peak_price(
+    pricing: &TimeOfUsePricing,
+    cost: &CostBreakdown,
+    minimum_price: f64,
+    earliest_start: f64,
+    calendar: Option<&BusinessCalendar>,
+    handling_seconds: f64,
+) -> Option<OffPeakPrice> {
+    let print_seconds = cost.print_time_minutes as f64 * 60.0;
+    let start_at = pricing.next_off_peak(earliest_start)?;
+    let rate_factor = pricing.rate_factor(start_at, print_seconds);
+    let discount = print_seconds / 3600.0 * cost.price_per_kg * (1.0 - rate_factor);
+    let time_cost = cost.time_cost - discount;
+    let subtotal = (cost.material_cost + time_cost) * (1.0 + cost.markup_percentage / 100.0);
+    let total_cost = if subtotal < minimum_price {
+        minimum_price
+    } else {
+        subtotal
+    };
+    let savings = cost.total_cost - total_cost;
+    if savings < 0.005 {
+        return None;
+    }
+    let promised_date = calendar.and_then(|calendar| {
+        calendar
+            .promise(start_at + print_seconds, handling_seconds)
+            .map(|at| calendar.local_date(at))
+    });
+    Some(OffPeakPrice {
+        start_at,
+        start_time: pricing.local_time(start_at),
+        rate_factor,
+        time_cost,
+        total_cost,
+        savings,
+        promised_date,
+    })
+}
+
+fn invalid(path: &str, message: String) -> OrcaError {
+    OrcaError::InvalidConfig {
+        path: path.to_string(),
+        message,
+    }
+}
+
+/// Off-peak windows with reduced machine-hour rates
+///
+/// Each window is `(start_hour, end_hour, rate_multiplier)` in shop time, e.g.
+/// `(22, 6, 0.7)` charges print time between 22:00 and 06:00 at 70% of the
+/// hourly rate. `days` counts 0 for Monday to 6 for Sunday.
+#[pyfunction]
+#[pyo3(signature = (windows, days=vec![0, 1, 2, 3, 4, 5, 6], utc_offset_hours=8.0))]
+pub fn create_time_of_use_pricing(
+    windows: Vec<(u32, u32, f64)>,
+    days: Vec<u32>,
+    utc_offset_hours: f64,
+) -> PyResult<TimeOfUsePricing> {
+    panic_boundary::catch(|| {
+        if windows.is_empty() {
+            return Err(invalid("windows", "is empty".to_string()).into());
+        }
+        for (start, end, multiplier) in &windows {
+            if *start > 23 || *end > 24 {
+                return Err(invalid(
+                    "windows",
+                    format!("{}-{} is not a range of hours", start, end),
+                )
+                .into());
+            }
+            if !(0.0..1.0).contains(multiplier) {
+                return Err(invalid(
+                    "windows",
+                    format!(
+                        "rate multiplier {} is not below 1 and at least 0",
+                        multiplier
+                    ),
+                )
+                .into());
+            }
+        }
+        if let Some(day) = days.iter().find(|day| **day > 6) {
+            return Err(invalid(
+                "days",
+                format!("{} is not a weekday (0 = Monday to 6 = Sunday)", day),
+            )
+            .into());
+        }
+        if days.is_empty() {
+            return Err(invalid("days", "is empty".to_string()).into());
+        }
+        if !(-14.0..=14.0).contains(&utc_offset_hours) {
+            return Err(invalid(
+                "utc_offset_hours",
+                format!("{} is not a UTC offset", utc_offset_hours),
+            )
+            .into());
+        }
+        Ok(TimeOfUsePricing {
+            windows,
+            days,
+            utc_offset_hours,
+        })
+    })
+}
+
+/// Price a quote for an off-peak start, next to its start-anytime total
+///
+/// The print starts at the first off-peak hour after `start` (default now)
+/// plus the lead time's queue wait; with a `calendar` the off-peak price
+/// carries its own promised date. Returns None when off-peak saves nothing.
+#[pyfunction]
+#[pyo3(signature = (pricing, cost, minimum_price=0.0, lead_time=None, calendar=None, start=None))]
+pub fn quote_off_peak(
+    pricing: PyRef<'_, TimeOfUsePricing>,
+    cost: PyRef<'_, CostBreakdown>,
+    minimum_price: f64,
+    lead_time: Option<PyRef<'_, LeadTime>>,
+    calendar: Option<PyRef<'_, BusinessCalendar>>,
+    start: Option<f64>,
+) -> PyResult<Option<OffPeakPrice>> {
+    panic_boundary::catch(|| {
+        let start = start.unwrap_or_else(|| unix_timestamp(SystemTime::now()));
+        let (wait, handling) = lead_time
+            .as_ref()
+            .map_or((0.0, 0.0), |l| (l.wait_seconds, l.handling_seconds));
+        Ok(off_peak_price(
+            &pricing,
+            &cost,
+            minimum_price,
+            start + wait,
+            calendar.as_deref(),
+            handling,
+        ))
+    })
+}
@@ -0,0 +1,75 @@
+use pyo3::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::audit;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+const EVENT: &str = "upload_indexed";
+
+#[derive(Deserialize)]
+struct Indexed {
+    event: String,
+    sha256: String,
+    quote_id: String,
+}
+
+/// A `ModelInfo.sha256`, lowercased; anything else cannot be in the index.
+fn check_hash(sha256: &str) -> Result<String, OrcaError> {
+    if sha256.len() == 64 && sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(sha256.to_ascii_lowercase())
+    } else {
+        Err(OrcaError::InvalidConfig {
+            path: "sha256".to_string(),
+            message: format!("{:?} is not a hex SHA-256", sha256),
+        })
+    }
+}
+
+/// Record that the upload with content hash `sha256` was quoted as `quote_id`
+///
+/// The index is a JSON lines file of `upload_indexed` records, created if it
+/// does not exist yet.
+#[pyfunction]
+pub fn record_upload(sha256: &str, quote_id: &str, index_path: String) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        let sha256 = check_hash(sha256)?;
+        audit::append_event(
+            Path::new(&index_path),
+            EVENT,
+            json!({ "sha256": sha256, "quote_id": quote_id }),
+        )
+        .map_err(OrcaError::IoError)?;
+        Ok(())
+    })
+}
+
+/// The quote ID last recorded for an upload with content hash `sha256`
+///
+/// `None` when the same bytes were never quoted, or the index does not exist.
+#[pyfunction]
+pub fn find_duplicate(
+    py: Python<'_>,
+    sha256: &str,
+    index_path: String,
+) -> PyResult<Option<String>> {
+    panic_boundary::catch(|| {
+        let sha256 = check_hash(sha256)?;
+        let text = match py.allow_threads(|| fs::read_to_string(&index_path)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(OrcaError::IoError(e).into()),
+        };
+        // A line cut short by a crash or written by hand is left out.
+        Ok(text
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<Indexed>(line).ok())
+            .find(|record| record.event == EVENT && record.sha256 == sha256)
+            .map(|record| record.quote_id))
+    })
+}
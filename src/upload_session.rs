@@ -0,0 +1,474 @@
+use pyo3::prelude::*;
+use ring::rand::{SecureRandom, SystemRandom};
+use sanitize_filename::sanitize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::audit::unix_timestamp;
+use crate::panic_boundary;
+use crate::validation_cache::{cached_model_info_with_digest, hex_digest};
+use crate::validation_limits::ValidationLimits;
+use crate::{ModelInfo, OrcaError};
+
+const PART_FILE: &str = "upload.part";
+const META_FILE: &str = "session.json";
+
+/// What a session was started with, kept beside its part file.
+#[derive(Serialize, Deserialize)]
+struct SessionMeta {
+    file_name: String,
+    total_bytes: Option<u64>,
+    created_at: f64,
+}
+
+/// Progress of a resumable upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct UploadSession {
+    #[pyo3(get)]
+    pub session_id: String,
+    /// The client's file name, made safe to write.
+    #[pyo3(get)]
+    pub file_name: String,
+    /// Bytes stored so far, which is where the next chunk starts.
+    #[pyo3(get)]
+    pub received_bytes: u64,
+    /// Size the client said the file is; `None` when it did not say.
+    #[pyo3(get)]
+    pub total_bytes: Option<u64>,
+    /// SHA-256 of the bytes stored so far, as hex.
+    #[pyo3(get)]
+    pub sha256: String,
+    /// Seconds since the Unix epoch.
+    #[pyo3(get)]
+    pub created_at: f64,
+}
+
+#[pymethods]
+impl UploadSession {
+    fn __str__(&self) -> String {
+        match self.total_bytes {
+            Some(total) => format!(
+                "UploadSession({}, {}/{} bytes)",
+                self.file_name, self.received_bytes, total
+            ),
+            None => format!(
+                "UploadSession({}, {} bytes)",
+                self.file_name, self.received_bytes
+            ),
+        }
+    }
+}
+
+/// Hash state of a session's part file, current while `hashed` matches its length.
+#[derive(Clone)]
+struct Rolling {
+    hasher: Sha256,
+    hashed: u64,
+}
+
+/// Resumable uploads, each a part file appended to chunk by chunk
+#[derive(Clone)]
+#[pyclass]
+pub struct UploadSessionStore {
+    #[pyo3(get)]
+    pub dir: String,
+    /// Limits a finished upload is validated against; larger uploads are
+    /// refused as their chunks arrive.
+    #[pyo3(get)]
+    pub limits: ValidationLimits,
+    /// Sessions not finished within this are removed.
+    #[pyo3(get)]
+    pub max_age_secs: f64,
+    /// Hashes carried from chunk to chunk, so a part file is only read back
+    /// when this process has not seen the session before, e.g. after a restart.
+    hashes: Arc<Mutex<HashMap<String, Rolling>>>,
+}
+
+/// Session IDs name directories; anything but what `start` hands out could
+/// escape the store, and is as good as unknown.
+fn check_id(session_id: &str) -> Result<(), OrcaError> {
+    if session_id.len() == 32
+        && session_id
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        Ok(())
+    } else {
+        Err(not_found(session_id))
+    }
+}
+
+fn not_found(session_id: &str) -> OrcaError {
+    OrcaError::FileNotFound(format!("upload session {}", session_id))
+}
+
+fn new_session_id() -> Result<String, OrcaError> {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| OrcaError::UploadFailed("no randomness for a session ID".to_string()))?;
+    Ok(hex_digest(&bytes))
+}
+
+/// The first `length` bytes of the file at `path`, hashed.
+fn rehash(path: &Path, length: u64) -> io::Result<Rolling> {
+    let mut hasher = Sha256::new();
+    let mut file = File::open(path)?.take(length);
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(Rolling {
+        hasher,
+        hashed: length,
+    })
+}
+
+/// Move `from` to `to`, copying when they are on different filesystems.
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+impl UploadSessionStore {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Rolling>> {
+        self.hashes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn session_dir(&self, session_id: &str) -> Result<PathBuf, OrcaError> {
+        check_id(session_id)?;
+        let dir = Path::new(&self.dir).join(session_id);
+        if dir.join(META_FILE).is_file() {
+            Ok(dir)
+        } else {
+            Err(not_found(session_id))
+        }
+    }
+
+    fn read_meta(dir: &Path) -> Result<SessionMeta, OrcaError> {
+        let text = fs::read_to_string(dir.join(META_FILE))?;
+        serde_json::from_str(&text).map_err(|e| OrcaError::InvalidConfig {
+            path: dir.join(META_FILE).to_string_lossy().into_owned(),
+            message: e.to_string(),
+        })
+    }
+
+    /// The session's hash state, brought up to the part file's length.
+    fn rolling(
+        hashes: &mut HashMap<String, Rolling>,
+        session_id: &str,
+        part: &Path,
+        length: u64,
+    ) -> io::Result<Rolling> {
+        match hashes.get(session_id) {
+            Some(rolling) if rolling.hashed == length => Ok(rolling.clone()),
+            _ => {
+                let rolling = rehash(part, length)?;
+                hashes.insert(session_id.to_string(), rolling.clone());
+                Ok(rolling)
+            }
+        }
+    }
+
+    fn session(session_id: &str, meta: SessionMeta, rolling: Rolling) -> UploadSession {
+        UploadSession {
+            session_id: session_id.to_string(),
+            file_name: meta.file_name,
+            received_bytes: rolling.hashed,
+            total_bytes: meta.total_bytes,
+            sha256: hex_digest(&rolling.hasher.finalize()),
+            created_at: meta.created_at,
+        }
+    }
+
+    /// An error when an upload of `bytes` would be over the size limit.
+    fn check_size(&self, bytes: u64) -> Result<(), OrcaError> {
+        match self.limits.exceeded(bytes, 0, 0) {
+            Some(message) => Err(OrcaError::InvalidUpload(message)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn start_session(
+        &self,
+        file_name: &str,
+        total_bytes: Option<u64>,
+    ) -> Result<UploadSession, OrcaError> {
+        let file_name = sanitize(file_name);
+        if file_name.is_empty() {
+            return Err(OrcaError::InvalidUpload("the file has no name".to_string()));
+        }
+        if let Some(total) = total_bytes {
+            self.check_size(total)?;
+        }
+        self.expire_sessions()?;
+        let session_id = new_session_id()?;
+        let dir = Path::new(&self.dir).join(&session_id);
+        fs::create_dir_all(&dir)?;
+        File::create(dir.join(PART_FILE))?;
+        let meta = SessionMeta {
+            file_name,
+            total_bytes,
+            created_at: unix_timestamp(SystemTime::now()),
+        };
+        let json = serde_json::to_vec(&meta).map_err(io::Error::from)?;
+        fs::write(dir.join(META_FILE), json)?;
+        let rolling = Rolling {
+            hasher: Sha256::new(),
+            hashed: 0,
+        };
+        self.lock().insert(session_id.clone(), rolling.clone());
+        Ok(Self::session(&session_id, meta, rolling))
+    }
+
+    /// Store `chunk`, which starts at byte `offset` of the file. A chunk sent
+    /// again after a dropped connection is taken for what it adds, if anything.
+    pub fn append_chunk(
+        &self,
+        session_id: &str,
+        offset: u64,
+        chunk: &[u8],
+    ) -> Result<UploadSession, OrcaError> {
+        let dir = self.session_dir(session_id)?;
+        let meta = Self::read_meta(&dir)?;
+        let part = dir.join(PART_FILE);
+        // Appends to one store are taken in turn, so each sees the last's length.
+        let mut hashes = self.lock();
+        let received = fs::metadata(&part)?.len();
+        if offset > received {
+            return Err(OrcaError::InvalidUpload(format!(
+                "chunk starts at byte {} but only {} bytes have arrived",
+                offset, received
+            )));
+        }
+        let skip = (received - offset).min(chunk.len() as u64) as usize;
+        let new = &chunk[skip..];
+        let length = received + new.len() as u64;
+        if let Some(total) = meta.total_bytes.filter(|total| length > *total) {
+            return Err(OrcaError::InvalidUpload(format!(
+                "{} bytes sent, more than the {} announced",
+                length, total
+            )));
+        }
+        self.check_size(length)?;
+        let mut rolling = Self::rolling(&mut hashes, session_id, &part, received)?;
+        if !new.is_empty() {
+            // Only a complete write moves the hash on; after a failed one the
+            // next chunk finds the lengths differ and reads the file back.
+            OpenOptions::new()
+                .append(true)
+                .open(&part)?
+                .write_all(new)?;
+            rolling.hasher.update(new);
+            rolling.hashed = length;
+            hashes.insert(session_id.to_string(), rolling.clone());
+        }
+        Ok(Self::session(session_id, meta, rolling))
+    }
+
+    fn progress(&self, session_id: &str) -> Result<(SessionMeta, Rolling), OrcaError> {
+        let dir = self.session_dir(session_id)?;
+        let meta = Self::read_meta(&dir)?;
+        let part = dir.join(PART_FILE);
+        let mut hashes = self.lock();
+        let received = fs::metadata(&part)?.len();
+        let rolling = Self::rolling(&mut hashes, session_id, &part, received)?;
+        Ok((meta, rolling))
+    }
+
+    pub fn session_status(&self, session_id: &str) -> Result<UploadSession, OrcaError> {
+        let (meta, rolling) = self.progress(session_id)?;
+        Ok(Self::session(session_id, meta, rolling))
+    }
+
+    /// Move the finished upload to `dest_path` and validate it there. With
+    /// `sha256`, the bytes received must hash to it.
+    pub fn finish_session(
+        &self,
+        session_id: &str,
+        dest_path: &Path,
+        sha256: Option<&str>,
+    ) -> PyResult<ModelInfo> {
+        let (meta, rolling) = self.progress(session_id)?;
+        let received = rolling.hashed;
+        if received == 0 {
+            return Err(OrcaError::InvalidUpload("no bytes were uploaded".to_string()).into());
+        }
+        if let Some(total) = meta.total_bytes.filter(|total| received < *total) {
+            return Err(OrcaError::InvalidUpload(format!(
+                "upload is incomplete, {} of {} bytes",
+                received, total
+            ))
+            .into());
+        }
+        let digest: [u8; 32] = rolling.hasher.finalize().into();
+        let actual = hex_digest(&digest);
+        if let Some(expected) = sha256.filter(|expected| !expected.eq_ignore_ascii_case(&actual)) {
+            return Err(OrcaError::ChecksumMismatch {
+                path: meta.file_name,
+                expected: expected.to_ascii_lowercase(),
+                actual,
+            }
+            .into());
+        }
+        let dir = self.session_dir(session_id)?;
+        move_file(&dir.join(PART_FILE), dest_path).map_err(OrcaError::IoError)?;
+        self.remove_session(session_id)?;
+        Ok(self.limits.validate(dest_path, |path| {
+            cached_model_info_with_digest(path, digest)
+        })?)
+    }
+
+    pub fn remove_session(&self, session_id: &str) -> Result<(), OrcaError> {
+        check_id(session_id)?;
+        self.lock().remove(session_id);
+        match fs::remove_dir_all(Path::new(&self.dir).join(session_id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove sessions older than `max_age_secs`; returns how many.
+    pub fn expire_sessions(&self) -> Result<usize, OrcaError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let cutoff = unix_timestamp(SystemTime::now()) - self.max_age_secs;
+        let mut removed = 0;
+        for entry in entries {
+            let entry = entry?;
+            let session_id = entry.file_name().to_string_lossy().into_owned();
+            if check_id(&session_id).is_err() {
+                continue;
+            }
+            // A session being started has no metadata yet; it is not stale.
+            let Ok(meta) = Self::read_meta(&entry.path()) else {
+                continue;
+            };
+            if meta.created_at < cutoff {
+                self.remove_session(&session_id)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[pymethods]
+impl UploadSessionStore {
+    /// Start an upload of `file_name`; `total_bytes` is its size when known
+    #[pyo3(signature = (file_name, total_bytes=None))]
+    fn start(
+        &self,
+        py: Python<'_>,
+        file_name: &str,
+        total_bytes: Option<u64>,
+    ) -> PyResult<UploadSession> {
+        panic_boundary::catch(|| {
+            Ok(py.allow_threads(|| self.start_session(file_name, total_bytes))?)
+        })
+    }
+
+    /// Store a chunk starting at byte `offset`; returns the progress after it
+    ///
+    /// Raises ValueError when the chunk leaves a gap, goes past the size
+    /// announced or the size limit, and FileNotFoundError for an unknown
+    /// session.
+    fn append(
+        &self,
+        py: Python<'_>,
+        session_id: &str,
+        offset: u64,
+        chunk: &[u8],
+    ) -> PyResult<UploadSession> {
+        panic_boundary::catch(|| {
+            Ok(py.allow_threads(|| self.append_chunk(session_id, offset, chunk))?)
+        })
+    }
+
+    /// Progress of an upload, to resume it from `received_bytes`
+    fn status(&self, py: Python<'_>, session_id: &str) -> PyResult<UploadSession> {
+        panic_boundary::catch(|| Ok(py.allow_threads(|| self.session_status(session_id))?))
+    }
+
+    /// Move a finished upload to `dest_path` and validate it
+    ///
+    /// Raises ValueError when bytes are missing or they do not hash to
+    /// `sha256`; the session is kept so the client can carry on. Once moved,
+    /// the session is gone and the model is returned whether valid or not.
+    #[pyo3(signature = (session_id, dest_path, sha256=None))]
+    fn finish(
+        &self,
+        py: Python<'_>,
+        session_id: &str,
+        dest_path: String,
+        sha256: Option<&str>,
+    ) -> PyResult<ModelInfo> {
+        panic_boundary::catch(|| {
+            py.allow_threads(|| self.finish_session(session_id, Path::new(&dest_path), sha256))
+        })
+    }
+
+    /// Drop an upload and what it received
+    fn abort(&self, py: Python<'_>, session_id: &str) -> PyResult<()> {
+        panic_boundary::catch(|| Ok(py.allow_threads(|| self.remove_session(session_id))?))
+    }
+
+    /// Remove uploads older than `max_age_secs`; returns how many
+    fn expire(&self, py: Python<'_>) -> PyResult<usize> {
+        panic_boundary::catch(|| Ok(py.allow_threads(|| self.expire_sessions())?))
+    }
+
+    fn __str__(&self) -> String {
+        format!("UploadSessionStore({})", self.dir)
+    }
+}
+
+/// Keep resumable uploads under `dir`, one directory per session
+///
+/// Clients start a session, send the file in chunks at the offsets the
+/// session reports, and finish it into a validated upload. Sessions live on
+/// disk, so an upload can be resumed after a dropped connection or a restart;
+/// ones not finished within `max_age_secs` are removed as new ones start.
+#[pyfunction]
+#[pyo3(signature = (dir, limits=None, max_age_secs=86400.0))]
+pub fn create_upload_session_store(
+    dir: String,
+    limits: Option<ValidationLimits>,
+    max_age_secs: f64,
+) -> PyResult<UploadSessionStore> {
+    panic_boundary::catch(|| {
+        if !(max_age_secs > 0.0 && max_age_secs.is_finite()) {
+            return Err(OrcaError::InvalidConfig {
+                path: "max_age_secs".to_string(),
+                message: format!("{} is not a positive number of seconds", max_age_secs),
+            }
+            .into());
+        }
+        fs::create_dir_all(&dir).map_err(OrcaError::IoError)?;
+        Ok(UploadSessionStore {
+            dir,
+            limits: limits.unwrap_or_default(),
+            max_age_secs,
+            hashes: Arc::new(Mutex::new(HashMap::new())),
+        })
+    })
+}
@@ -0,0 +1,207 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::panic_boundary;
+use crate::{metrics, model_info, ModelInfo, ValidationError};
+
+/// Results are keyed by extension and content, since the extension picks the validator.
+type CacheKey = (String, [u8; 32]);
+
+/// Least-recently-used map of validation results; a capacity of 0 disables it.
+#[derive(Default)]
+struct Lru {
+    capacity: usize,
+    entries: HashMap<CacheKey, ModelInfo>,
+    /// Keys from least to most recently used.
+    order: VecDeque<CacheKey>,
+    hits: u64,
+    misses: u64,
+}
+
+impl Lru {
+    fn get(&mut self, key: &CacheKey) -> Option<ModelInfo> {
+        let info = self.entries.get(key)?.clone();
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key.clone());
+        Some(info)
+    }
+
+    fn insert(&mut self, key: CacheKey, info: ModelInfo) {
+        if self.entries.insert(key.clone(), info).is_none() {
+            self.order.push_back(key);
+        }
+        self.shrink();
+    }
+
+    fn shrink(&mut self) {
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+static CACHE: Lazy<Mutex<Lru>> = Lazy::new(|| Mutex::new(Lru::default()));
+
+fn cache() -> std::sync::MutexGuard<'static, Lru> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// SHA-256 of a file's contents, read in 64 KiB chunks.
+pub(crate) fn file_digest(path: &Path) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    let mut file = fs::File::open(path)?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+pub(crate) fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `info` for the file at `path`, with its content hash when it is valid.
+pub(crate) fn hashed(
+    path: &Path,
+    info: impl FnOnce(&Path) -> Result<ModelInfo, ValidationError>,
+) -> Result<ModelInfo, ValidationError> {
+    let mut info = info(path)?;
+    if info.is_valid {
+        info.sha256 = file_digest(path).ok().map(|digest| hex_digest(&digest));
+    }
+    Ok(info)
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+fn content_key(path: &Path) -> io::Result<CacheKey> {
+    Ok((extension(path), file_digest(path)?))
+}
+
+/// `model_info`, answered from the cache when the same content was validated before.
+///
+/// Files that cannot be read (missing, permissions) bypass the cache.
+pub(crate) fn cached_model_info(path: &Path) -> Result<ModelInfo, ValidationError> {
+    if cache().capacity == 0 {
+        return hashed(path, model_info);
+    }
+    let Ok(key) = content_key(path) else {
+        return model_info(path);
+    };
+    keyed_model_info(path, key)
+}
+
+/// `cached_model_info` for a file whose SHA-256 is already known, such as one
+/// hashed as it was uploaded, so it is not read again just to key the cache.
+pub(crate) fn cached_model_info_with_digest(
+    path: &Path,
+    digest: [u8; 32],
+) -> Result<ModelInfo, ValidationError> {
+    if cache().capacity == 0 {
+        let mut info = model_info(path)?;
+        if info.is_valid {
+            info.sha256 = Some(hex_digest(&digest));
+        }
+        return Ok(info);
+    }
+    keyed_model_info(path, (extension(path), digest))
+}
+
+fn keyed_model_info(path: &Path, key: CacheKey) -> Result<ModelInfo, ValidationError> {
+    let cached = {
+        let mut lru = cache();
+        let cached = lru.get(&key);
+        if cached.is_some() {
+            lru.hits += 1;
+        }
+        cached
+    };
+    if let Some(info) = cached {
+        metrics::record_validation_cache(true);
+        return Ok(info);
+    }
+    // Validate without the lock so parallel batches are not serialized.
+    let mut info = model_info(path)?;
+    if info.is_valid {
+        info.sha256 = Some(hex_digest(&key.1));
+    }
+    {
+        let mut lru = cache();
+        lru.misses += 1;
+        lru.insert(key, info.clone());
+    }
+    metrics::record_validation_cache(false);
+    Ok(info)
+}
+
+/// Hit and miss counts of the validation cache
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct ValidationCacheStats {
+    #[pyo3(get)]
+    pub hits: u64,
+    #[pyo3(get)]
+    pub misses: u64,
+    #[pyo3(get)]
+    pub entries: usize,
+    #[pyo3(get)]
+    pub capacity: usize,
+}
+
+#[pymethods]
+impl ValidationCacheStats {
+    fn __str__(&self) -> String {
+        format!(
+            "ValidationCacheStats(hits={}, misses={}, entries={}/{})",
+            self.hits, self.misses, self.entries, self.capacity
+        )
+    }
+}
+
+/// Keep up to `capacity` validation results keyed by file content; 0 disables the cache
+#[pyfunction]
+pub fn configure_validation_cache(capacity: usize) -> PyResult<()> {
+    panic_boundary::catch(|| {
+        let mut lru = cache();
+        lru.capacity = capacity;
+        lru.shrink();
+        Ok(())
+    })
+}
+
+/// Current validation cache size and hit counts
+#[pyfunction]
+pub fn validation_cache_stats() -> PyResult<ValidationCacheStats> {
+    panic_boundary::catch(|| {
+        let lru = cache();
+        Ok(ValidationCacheStats {
+            hits: lru.hits,
+            misses: lru.misses,
+            entries: lru.entries.len(),
+            capacity: lru.capacity,
+        })
+    })
+}
@@ -0,0 +1,134 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::panic_boundary;
+use crate::{ModelInfo, ValidationError};
+
+const DEFAULT_MAX_FILE_BYTES: u64 = 512 * 1024 * 1024;
+const DEFAULT_MAX_TRIANGLES: u64 = 20_000_000;
+const DEFAULT_MAX_LINES: u64 = 20_000_000;
+
+/// Upload limits the validators enforce; 0 lifts a limit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[pyclass]
+pub struct ValidationLimits {
+    #[pyo3(get)]
+    pub max_file_bytes: u64,
+    /// Triangles in a mesh, as `ModelInfo.triangle_count` counts them.
+    #[pyo3(get)]
+    pub max_triangles: u64,
+    /// Lines of an ASCII STL, OBJ or STEP file.
+    #[pyo3(get)]
+    pub max_lines: u64,
+}
+
+impl Default for ValidationLimits {
+    fn default() -> Self {
+        ValidationLimits {
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_triangles: DEFAULT_MAX_TRIANGLES,
+            max_lines: DEFAULT_MAX_LINES,
+        }
+    }
+}
+
+impl ValidationLimits {
+    /// The first limit that `bytes`, `triangles` and `lines` break, as an
+    /// error message.
+    pub fn exceeded(&self, bytes: u64, triangles: u64, lines: u64) -> Option<String> {
+        [
+            (bytes, self.max_file_bytes, "File is", "bytes"),
+            (triangles, self.max_triangles, "Model has", "triangles"),
+            (lines, self.max_lines, "Model has", "lines"),
+        ]
+        .into_iter()
+        .find(|&(count, max, _, _)| max > 0 && count > max)
+        .map(|(count, max, subject, unit)| {
+            format!("{} {} {}, over the limit of {}", subject, count, unit, max)
+        })
+    }
+
+    /// `info`, made invalid when the model it describes breaks a limit.
+    pub fn apply(&self, info: ModelInfo) -> ModelInfo {
+        if !info.is_valid {
+            return info;
+        }
+        let exceeded = self.exceeded(
+            info.file_size,
+            info.triangle_count.unwrap_or(0),
+            info.line_count.unwrap_or(0),
+        );
+        match exceeded {
+            Some(message) => ModelInfo {
+                archive_entry: info.archive_entry,
+                ..ModelInfo::invalid(&info.file_type, info.file_size, message)
+            },
+            None => info,
+        }
+    }
+
+    /// `validate` for the file at `path` with these limits; a file over
+    /// `max_file_bytes` is refused without being read.
+    pub fn validate(
+        &self,
+        path: &Path,
+        validate: impl FnOnce(&Path) -> Result<ModelInfo, ValidationError>,
+    ) -> Result<ModelInfo, ValidationError> {
+        if let Ok(metadata) = fs::metadata(path) {
+            if let Some(message) = self.exceeded(metadata.len(), 0, 0) {
+                return Ok(ModelInfo::invalid(
+                    &file_type(path),
+                    metadata.len(),
+                    message,
+                ));
+            }
+        }
+        Ok(self.apply(validate(path)?))
+    }
+}
+
+/// `ModelInfo.file_type` for the file at `path`, from its extension.
+pub(crate) fn file_type(path: &Path) -> String {
+    match path.extension().and_then(|s| s.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("stp") => "step".to_string(),
+        Some(ext) => ext.to_lowercase(),
+        None => "unknown".to_string(),
+    }
+}
+
+#[pymethods]
+impl ValidationLimits {
+    fn __str__(&self) -> String {
+        format!(
+            "ValidationLimits(max_file_bytes={}, max_triangles={}, max_lines={})",
+            self.max_file_bytes, self.max_triangles, self.max_lines
+        )
+    }
+}
+
+/// Limits on uploads for the validators and the quote pipeline
+///
+/// A valid model over any of them is reported invalid, saying which. Without
+/// limits, the validators apply the defaults: 512 MB, 20 million triangles
+/// and 20 million lines. 0 lifts a limit.
+#[pyfunction]
+#[pyo3(signature = (
+    max_file_bytes=DEFAULT_MAX_FILE_BYTES,
+    max_triangles=DEFAULT_MAX_TRIANGLES,
+    max_lines=DEFAULT_MAX_LINES,
+))]
+pub fn create_validation_limits(
+    max_file_bytes: u64,
+    max_triangles: u64,
+    max_lines: u64,
+) -> PyResult<ValidationLimits> {
+    panic_boundary::catch(|| {
+        Ok(ValidationLimits {
+            max_file_bytes,
+            max_triangles,
+            max_lines,
+        })
+    })
+}
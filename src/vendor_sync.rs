@@ -0,0 +1,360 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// OrcaSlicer's bundled vendor profiles; `{ref}` is the pinned branch, tag or commit.
+pub const DEFAULT_VENDOR_SOURCE: &str =
+    "https://raw.githubusercontent.com/SoftFever/OrcaSlicer/{ref}/resources/profiles";
+
+const DEFAULT_REF: &str = "main";
+
+/// Lists in a vendor index (`<vendor>.json`) whose entries name profile files.
+const INDEX_LISTS: &[&str] = &[
+    "machine_model_list",
+    "machine_list",
+    "process_list",
+    "filament_list",
+];
+
+/// Same cap as bundle imports; vendor profiles are a few KiB each.
+const MAX_DOWNLOAD_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Pin written next to the synced profiles: the ref and the SHA-256 of every file.
+#[derive(Debug, Serialize, Deserialize)]
+struct VendorLock {
+    vendor: String,
+    git_ref: String,
+    version: Option<String>,
+    files: BTreeMap<String, String>,
+}
+
+/// Outcome of a vendor profile sync
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct VendorSync {
+    #[pyo3(get)]
+    pub vendor: String,
+    #[pyo3(get)]
+    pub git_ref: String,
+    /// Vendor profile version from the index, e.g. "02.00.00.54".
+    #[pyo3(get)]
+    pub version: Option<String>,
+    /// Files written because they were new or changed.
+    #[pyo3(get)]
+    pub updated: Vec<String>,
+    #[pyo3(get)]
+    pub unchanged: usize,
+    /// Files from the previous sync that the vendor no longer ships.
+    #[pyo3(get)]
+    pub removed: Vec<String>,
+}
+
+#[pymethods]
+impl VendorSync {
+    fn __str__(&self) -> String {
+        format!(
+            "VendorSync(vendor={}, ref={}, version={:?}, updated={}, unchanged={}, removed={})",
+            self.vendor,
+            self.git_ref,
+            self.version,
+            self.updated.len(),
+            self.unchanged,
+            self.removed.len()
+        )
+    }
+}
+
+/// Where vendor files come from: an HTTP(S) URL template or a local profiles checkout.
+struct VendorSource {
+    root: String,
+}
+
+impl VendorSource {
+    fn new(source: &str, git_ref: &str) -> Self {
+        VendorSource {
+            root: source
+                .replace("{ref}", git_ref)
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+
+    fn is_remote(&self) -> bool {
+        self.root.starts_with("http://") || self.root.starts_with("https://")
+    }
+
+    fn fetch(&self, relative: &str) -> Result<Vec<u8>, OrcaError> {
+        let mut body = Vec::new();
+        if self.is_remote() {
+            let url = format!("{}/{}", self.root, encode_path(relative));
+            let response = ureq::get(&url)
+                .call()
+                .map_err(|e| OrcaError::DownloadFailed(format!("{}: {}", url, e)))?;
+            response
+                .into_reader()
+                .take(MAX_DOWNLOAD_BYTES)
+                .read_to_end(&mut body)?;
+        } else {
+            let path = Path::new(&self.root).join(relative);
+            if !path.is_file() {
+                return Err(OrcaError::FileNotFound(path.display().to_string()));
+            }
+            fs::File::open(&path)?
+                .take(MAX_DOWNLOAD_BYTES)
+                .read_to_end(&mut body)?;
+        }
+        Ok(body)
+    }
+}
+
+/// Percent-encode the characters that show up in vendor file names ("Bambu Lab X1.json").
+fn encode_path(relative: &str) -> String {
+    let mut encoded = String::with_capacity(relative.len());
+    for c in relative.chars() {
+        match c {
+            ' ' => encoded.push_str("%20"),
+            '#' => encoded.push_str("%23"),
+            '%' => encoded.push_str("%25"),
+            '?' => encoded.push_str("%3F"),
+            '+' => encoded.push_str("%2B"),
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Only plain relative paths are accepted, so a hostile index cannot write elsewhere.
+fn is_safe_relative(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Not `.json`, so profile indexing never mistakes the lock for a profile.
+fn lock_path(dest_dir: &Path, vendor: &str) -> PathBuf {
+    dest_dir.join(format!("{}.lock", vendor))
+}
+
+fn read_lock(path: &Path) -> Result<Option<VendorLock>, OrcaError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| OrcaError::InvalidConfig {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })
+}
+
+/// Destination of a locked file; the index sits beside the vendor directory, as in OrcaSlicer.
+fn local_path(dest_dir: &Path, vendor: &str, relative: &str) -> PathBuf {
+    if relative == format!("{}.json", vendor) {
+        dest_dir.join(relative)
+    } else {
+        dest_dir.join(vendor).join(relative)
+    }
+}
+
+fn matches_lock(dest_dir: &Path, vendor: &str, lock: &VendorLock) -> bool {
+    lock.files.iter().all(|(relative, checksum)| {
+        fs::read(local_path(dest_dir, vendor, relative))
+            .is_ok_and(|bytes| &sha256_hex(&bytes) == checksum)
+    })
+}
+
+fn parse_object(relative: &str, bytes: &[u8]) -> Result<serde_json::Map<String, Value>, OrcaError> {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(Value::Object(settings)) => Ok(settings),
+        _ => Err(OrcaError::InvalidProfile {
+            path: relative.to_string(),
+            message: "expected a JSON object".to_string(),
+        }),
+    }
+}
+
+/// Download a vendor's profiles into `dest_dir/<vendor>/`, pinned by `<vendor>.lock`.
+///
+/// Once a lock exists, syncs reuse its ref and reject any file whose checksum differs;
+/// pass `update` to move to `git_ref` (or the default branch) and re-pin. Nothing is
+/// written until every file has been fetched and verified.
+pub fn sync_vendor(
+    vendor: &str,
+    dest_dir: &Path,
+    git_ref: Option<&str>,
+    source: Option<&str>,
+    update: bool,
+) -> Result<VendorSync, OrcaError> {
+    if vendor.is_empty() || sanitize_filename::sanitize(vendor) != vendor || vendor.starts_with('.')
+    {
+        return Err(OrcaError::InvalidConfig {
+            path: vendor.to_string(),
+            message: "vendor must be a plain name such as 'BBL' or 'Prusa'".to_string(),
+        });
+    }
+
+    let lock_file = lock_path(dest_dir, vendor);
+    let previous = read_lock(&lock_file)?;
+    let pinned = previous.as_ref().filter(|_| !update);
+    let git_ref = match (pinned, git_ref) {
+        (Some(lock), Some(requested)) if lock.git_ref != requested => {
+            return Err(OrcaError::InvalidConfig {
+                path: lock_file.display().to_string(),
+                message: format!(
+                    "{} is pinned to '{}'; sync with update to move to '{}'",
+                    vendor, lock.git_ref, requested
+                ),
+            })
+        }
+        (Some(lock), _) => lock.git_ref.clone(),
+        (None, requested) => requested.unwrap_or(DEFAULT_REF).to_string(),
+    };
+
+    if let Some(lock) = pinned {
+        if matches_lock(dest_dir, vendor, lock) {
+            return Ok(VendorSync {
+                vendor: vendor.to_string(),
+                git_ref,
+                version: lock.version.clone(),
+                updated: Vec::new(),
+                unchanged: lock.files.len(),
+                removed: Vec::new(),
+            });
+        }
+    }
+
+    let source = VendorSource::new(source.unwrap_or(DEFAULT_VENDOR_SOURCE), &git_ref);
+    let verify = |relative: &str, bytes: &[u8]| -> Result<String, OrcaError> {
+        let actual = sha256_hex(bytes);
+        if let Some(lock) = pinned {
+            let expected = lock.files.get(relative).cloned().unwrap_or_default();
+            if expected != actual {
+                return Err(OrcaError::ChecksumMismatch {
+                    path: relative.to_string(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(actual)
+    };
+
+    let index_name = format!("{}.json", vendor);
+    let index_bytes = source.fetch(&index_name)?;
+    let index_checksum = verify(&index_name, &index_bytes)?;
+    let index = parse_object(&index_name, &index_bytes)?;
+    let version = index
+        .get("version")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let mut sub_paths: Vec<String> = INDEX_LISTS
+        .iter()
+        .filter_map(|list| index.get(*list).and_then(Value::as_array))
+        .flatten()
+        .filter_map(|entry| entry.get("sub_path").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    sub_paths.sort();
+    sub_paths.dedup();
+
+    let mut fetched = vec![(index_name.clone(), index_bytes)];
+    let mut files = BTreeMap::from([(index_name, index_checksum)]);
+    for sub_path in sub_paths {
+        if !is_safe_relative(&sub_path) {
+            return Err(OrcaError::InvalidProfile {
+                path: format!("{}.json", vendor),
+                message: format!("unsafe sub_path '{}'", sub_path),
+            });
+        }
+        let bytes = source.fetch(&format!("{}/{}", vendor, sub_path))?;
+        parse_object(&sub_path, &bytes)?;
+        files.insert(sub_path.clone(), verify(&sub_path, &bytes)?);
+        fetched.push((sub_path, bytes));
+    }
+
+    let mut updated = Vec::new();
+    let mut unchanged = 0;
+    for (relative, bytes) in &fetched {
+        let target = local_path(dest_dir, vendor, relative);
+        if fs::read(&target).is_ok_and(|current| &current == bytes) {
+            unchanged += 1;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, bytes)?;
+        updated.push(relative.clone());
+    }
+
+    let mut removed = Vec::new();
+    for relative in previous.iter().flat_map(|lock| lock.files.keys()) {
+        if !files.contains_key(relative) && is_safe_relative(relative) {
+            let stale = local_path(dest_dir, vendor, relative);
+            if stale.is_file() {
+                fs::remove_file(&stale)?;
+                removed.push(relative.clone());
+            }
+        }
+    }
+
+    let lock = VendorLock {
+        vendor: vendor.to_string(),
+        git_ref: git_ref.clone(),
+        version: version.clone(),
+        files,
+    };
+    let contents = serde_json::to_string_pretty(&lock)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    fs::write(&lock_file, contents)?;
+
+    Ok(VendorSync {
+        vendor: vendor.to_string(),
+        git_ref,
+        version,
+        updated,
+        unchanged,
+        removed,
+    })
+}
+
+/// Download or update a vendor's official OrcaSlicer profiles, pinned and checksum-verified
+#[pyfunction]
+#[pyo3(signature = (vendor, dest_dir, git_ref=None, source=None, update=false))]
+pub fn sync_vendor_profiles(
+    py: Python<'_>,
+    vendor: String,
+    dest_dir: String,
+    git_ref: Option<String>,
+    source: Option<String>,
+    update: bool,
+) -> PyResult<VendorSync> {
+    panic_boundary::catch(|| {
+        let result = py.allow_threads(|| {
+            sync_vendor(
+                &vendor,
+                Path::new(&dest_dir),
+                git_ref.as_deref(),
+                source.as_deref(),
+                update,
+            )
+        });
+        Ok(result?)
+    })
+}
@@ -0,0 +1,210 @@
+use pyo3::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::panic_boundary;
+use crate::pipeline::{quote, PipelineConfig};
+use crate::process_override::PrintOptions;
+use crate::profile_cache::ProfileCache;
+use crate::slicer::SlicerWatch;
+use crate::workspace::JobWorkspace;
+use crate::{init_regexes, OrcaError};
+
+/// A 10 mm cube: big enough to produce real G-code, small enough to slice in seconds.
+const CALIBRATION_CUBE: &str = "solid calibration
+facet normal 0 0 -1
+outer loop
+vertex 0 0 0
+vertex 10 10 0
+vertex 10 0 0
+endloop
+endfacet
+facet normal 0 0 -1
+outer loop
+vertex 0 0 0
+vertex 0 10 0
+vertex 10 10 0
+endloop
+endfacet
+facet normal 0 0 1
+outer loop
+vertex 0 0 10
+vertex 10 0 10
+vertex 10 10 10
+endloop
+endfacet
+facet normal 0 0 1
+outer loop
+vertex 0 0 10
+vertex 10 10 10
+vertex 0 10 10
+endloop
+endfacet
+facet normal 0 -1 0
+outer loop
+vertex 0 0 0
+vertex 10 0 0
+vertex 10 0 10
+endloop
+endfacet
+facet normal 0 -1 0
+outer loop
+vertex 0 0 0
+vertex 10 0 10
+vertex 0 0 10
+endloop
+endfacet
+facet normal 0 1 0
+outer loop
+vertex 0 10 0
+vertex 10 10 10
+vertex 10 10 0
+endloop
+endfacet
+facet normal 0 1 0
+outer loop
+vertex 0 10 0
+vertex 0 10 10
+vertex 10 10 10
+endloop
+endfacet
+facet normal -1 0 0
+outer loop
+vertex 0 0 0
+vertex 0 0 10
+vertex 0 10 10
+endloop
+endfacet
+facet normal -1 0 0
+outer loop
+vertex 0 0 0
+vertex 0 10 10
+vertex 0 10 0
+endloop
+endfacet
+facet normal 1 0 0
+outer loop
+vertex 10 0 0
+vertex 10 10 0
+vertex 10 10 10
+endloop
+endfacet
+facet normal 1 0 0
+outer loop
+vertex 10 0 0
+vertex 10 10 10
+vertex 10 0 10
+endloop
+endfacet
+endsolid calibration
+";
+
+/// What `warm_up` prepared and how long each step took
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct WarmUpReport {
+    /// Regexes and the async runtime.
+    #[pyo3(get)]
+    pub lazies_ms: f64,
+    #[pyo3(get)]
+    pub profiles_loaded: usize,
+    #[pyo3(get)]
+    pub profiles_ms: f64,
+    /// Time to quote the calibration cube, when a calibration slice was requested.
+    #[pyo3(get)]
+    pub calibration_ms: Option<f64>,
+}
+
+#[pymethods]
+impl WarmUpReport {
+    fn __str__(&self) -> String {
+        format!(
+            "WarmUpReport(lazies={:.1}ms, profiles={} in {:.1}ms, calibration={:?})",
+            self.lazies_ms, self.profiles_loaded, self.profiles_ms, self.calibration_ms
+        )
+    }
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Parse every machine, process and filament profile into the cache.
+fn load_profiles(cache: &ProfileCache) -> Result<usize, OrcaError> {
+    let machines = cache.machine_listings();
+    let processes = cache.process_listings();
+    for path in machines
+        .iter()
+        .map(|m| &m.path)
+        .chain(processes.iter().map(|p| &p.path))
+    {
+        cache.resolved_profile(Path::new(path))?;
+    }
+    Ok(machines.len() + processes.len() + cache.filament_profiles().len())
+}
+
+/// Quote a small cube in PLA end to end, so the slicer binary and its data files
+/// are in the page cache before the first customer upload.
+fn calibration_slice(config: &PipelineConfig) -> PyResult<f64> {
+    let workspace = JobWorkspace::create(config.work_dir.as_deref().map(Path::new), None)?;
+    let model = Path::new(&workspace.model_dir).join("calibration.stl");
+    let result = fs::write(&model, CALIBRATION_CUBE)
+        .map_err(PyErr::from)
+        .and_then(|()| {
+            let started = Instant::now();
+            quote(
+                &model.to_string_lossy(),
+                "PLA".to_string(),
+                PrintOptions::default(),
+                config,
+                false,
+                &SlicerWatch::default(),
+            )
+            .map(|_| elapsed_ms(started))
+        });
+    workspace.release();
+    result
+}
+
+/// Initialise lazies, parse all profiles and optionally run a calibration slice
+///
+/// Call once per process at startup so the first quote is not paying for them.
+#[pyfunction]
+#[pyo3(signature = (config, profile_cache=None, calibration=false))]
+pub fn warm_up(
+    py: Python<'_>,
+    config: PyRef<'_, PipelineConfig>,
+    profile_cache: Option<PyRef<'_, ProfileCache>>,
+    calibration: bool,
+) -> PyResult<WarmUpReport> {
+    panic_boundary::catch(|| {
+        let config: &PipelineConfig = &config;
+        let profile_cache: Option<&ProfileCache> = profile_cache.as_deref();
+        py.allow_threads(|| {
+            let started = Instant::now();
+            init_regexes();
+            pyo3_asyncio::tokio::get_runtime();
+            let lazies_ms = elapsed_ms(started);
+
+            let started = Instant::now();
+            let profiles_loaded = match profile_cache {
+                Some(cache) => load_profiles(cache)?,
+                None => 0,
+            };
+            let profiles_ms = elapsed_ms(started);
+
+            let calibration_ms = if calibration {
+                Some(calibration_slice(config)?)
+            } else {
+                None
+            };
+            Ok(WarmUpReport {
+                lazies_ms,
+                profiles_loaded,
+                profiles_ms,
+                calibration_ms,
+            })
+        })
+    })
+}
@@ -0,0 +1,265 @@
+use pyo3::prelude::*;
+use ring::hmac;
+use serde_json::Value;
+use std::time::SystemTime;
+
+use crate::audit::unix_timestamp;
+use crate::panic_boundary;
+use crate::OrcaError;
+
+/// What an incoming payment or approval callback means for a quote
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct WebhookEvent {
+    /// "stripe" or "telegram".
+    #[pyo3(get)]
+    pub source: String,
+    /// The provider's own event type, e.g. "checkout.session.completed".
+    #[pyo3(get)]
+    pub event_type: String,
+    /// "payment.succeeded", "payment.failed", "quote.approved", "quote.rejected",
+    /// or "ignored" for events that do not concern a quote.
+    #[pyo3(get)]
+    pub action: String,
+    #[pyo3(get)]
+    pub quote_id: Option<String>,
+    /// The provider's ID for the event, for dropping retried deliveries.
+    #[pyo3(get)]
+    pub event_id: String,
+    /// Amount paid, in major units (dollars, not cents).
+    #[pyo3(get)]
+    pub amount: Option<f64>,
+    #[pyo3(get)]
+    pub currency: Option<String>,
+    /// Telegram user who pressed the button, as @username or numeric ID.
+    #[pyo3(get)]
+    pub user: Option<String>,
+}
+
+#[pymethods]
+impl WebhookEvent {
+    fn __str__(&self) -> String {
+        format!(
+            "WebhookEvent(source={}, action={}, quote_id={:?})",
+            self.source, self.action, self.quote_id
+        )
+    }
+}
+
+fn invalid(reason: &str) -> OrcaError {
+    OrcaError::InvalidSignature(reason.to_string())
+}
+
+/// Anyone could sign with an empty secret.
+fn check_secret(secret: &str) -> Result<(), OrcaError> {
+    if secret.is_empty() {
+        return Err(OrcaError::InvalidConfig {
+            path: "webhook secret".to_string(),
+            message: "is empty".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn hex_bytes(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Check a `Stripe-Signature` header (`t=<unix time>,v1=<hex HMAC>,...`) against
+/// the endpoint's signing secret. Any of several `v1` signatures may match, as
+/// Stripe sends one per active secret while a secret is being rolled.
+pub fn verify_stripe(
+    payload: &[u8],
+    header: &str,
+    secret: &str,
+    tolerance_secs: f64,
+    now: f64,
+) -> Result<(), OrcaError> {
+    check_secret(secret)?;
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.extend(hex_bytes(value)),
+            _ => {}
+        }
+    }
+    let Some(timestamp) = timestamp else {
+        return Err(invalid("no timestamp"));
+    };
+    if signatures.is_empty() {
+        return Err(invalid("no v1 signature"));
+    }
+    // Old deliveries are refused so a captured request cannot be replayed.
+    if tolerance_secs > 0.0 && (now - timestamp as f64).abs() > tolerance_secs {
+        return Err(invalid("timestamp outside the tolerance"));
+    }
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(payload);
+    signatures
+        .iter()
+        .find(|signature| hmac::verify(&key, &signed, signature).is_ok())
+        .map(|_| ())
+        .ok_or_else(|| invalid("signature mismatch"))
+}
+
+/// Compare the `X-Telegram-Bot-Api-Secret-Token` header with the secret given
+/// to `setWebhook`, in constant time: both are MACed and the tags compared.
+pub fn verify_telegram(header: &str, secret: &str) -> Result<(), OrcaError> {
+    check_secret(secret)?;
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let expected = hmac::sign(&key, secret.as_bytes());
+    hmac::verify(&key, header.as_bytes(), expected.as_ref())
+        .map_err(|_| invalid("secret token mismatch"))
+}
+
+fn parse_json(payload: &[u8]) -> Result<Value, OrcaError> {
+    serde_json::from_slice(payload).map_err(|e| OrcaError::InvalidWebhook(e.to_string()))
+}
+
+fn text(value: &Value, pointer: &str) -> Option<String> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Map a Stripe event to the quote it pays for. Checkout sessions created by
+/// `create_payment_link` carry the quote ID as `client_reference_id` and in
+/// `metadata.quote_id`.
+pub fn stripe_event(body: &Value) -> Result<WebhookEvent, OrcaError> {
+    let event_type = text(body, "/type")
+        .ok_or_else(|| OrcaError::InvalidWebhook("Stripe event has no type".to_string()))?;
+    let event_id = text(body, "/id").unwrap_or_default();
+    let object = body.pointer("/data/object").unwrap_or(&Value::Null);
+    let action = match event_type.as_str() {
+        "checkout.session.completed" => {
+            // Bank transfers and the like complete unpaid and settle later.
+            if object.get("payment_status").and_then(Value::as_str) == Some("unpaid") {
+                "ignored"
+            } else {
+                "payment.succeeded"
+            }
+        }
+        "checkout.session.async_payment_succeeded" => "payment.succeeded",
+        "checkout.session.async_payment_failed" | "checkout.session.expired" => "payment.failed",
+        _ => "ignored",
+    };
+    let quote_id =
+        text(object, "/client_reference_id").or_else(|| text(object, "/metadata/quote_id"));
+    // Payments for anything but a quote (e.g. other products) are not ours to act on.
+    let action = if quote_id.is_some() {
+        action
+    } else {
+        "ignored"
+    };
+    let amount = object
+        .get("amount_total")
+        .and_then(Value::as_f64)
+        .map(|cents| cents / 100.0);
+    Ok(WebhookEvent {
+        source: "stripe".to_string(),
+        event_type,
+        action: action.to_string(),
+        quote_id,
+        event_id,
+        amount,
+        currency: text(object, "/currency").map(|c| c.to_uppercase()),
+        user: None,
+    })
+}
+
+/// Map a Telegram update to a quote decision. Inline buttons carry
+/// `approve:<quote_id>` or `reject:<quote_id>` as their callback data.
+pub fn telegram_event(body: &Value) -> Result<WebhookEvent, OrcaError> {
+    let update_id = body
+        .get("update_id")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| OrcaError::InvalidWebhook("Telegram update has no update_id".to_string()))?;
+    let callback = body.get("callback_query");
+    let event_type = match callback {
+        Some(_) => "callback_query",
+        None => "update",
+    };
+    let decision = callback
+        .and_then(|c| c.get("data"))
+        .and_then(Value::as_str)
+        .and_then(|data| data.split_once(':'))
+        .and_then(|(verb, quote_id)| match verb {
+            "approve" => Some(("quote.approved", quote_id)),
+            "reject" => Some(("quote.rejected", quote_id)),
+            _ => None,
+        })
+        .filter(|(_, quote_id)| !quote_id.is_empty());
+    let user = callback.and_then(|c| {
+        text(c, "/from/username")
+            .map(|name| format!("@{}", name))
+            .or_else(|| {
+                c.pointer("/from/id")
+                    .and_then(Value::as_i64)
+                    .map(|id| id.to_string())
+            })
+    });
+    Ok(WebhookEvent {
+        source: "telegram".to_string(),
+        event_type: event_type.to_string(),
+        action: decision.map_or("ignored", |(action, _)| action).to_string(),
+        quote_id: decision.map(|(_, quote_id)| quote_id.to_string()),
+        event_id: callback
+            .and_then(|c| text(c, "/id"))
+            .unwrap_or_else(|| update_id.to_string()),
+        amount: None,
+        currency: None,
+        user,
+    })
+}
+
+/// Verify a Stripe webhook's signature and map it to the quote it concerns
+///
+/// `payload` must be the raw request body; `signature` is the `Stripe-Signature`
+/// header and `secret` the endpoint's `whsec_...` signing secret. Deliveries
+/// signed more than `tolerance_secs` ago are refused; 0 turns the check off.
+#[pyfunction]
+#[pyo3(signature = (payload, signature, secret, tolerance_secs=300.0))]
+pub fn parse_stripe_webhook(
+    payload: &[u8],
+    signature: &str,
+    secret: &str,
+    tolerance_secs: f64,
+) -> PyResult<WebhookEvent> {
+    panic_boundary::catch(|| {
+        verify_stripe(
+            payload,
+            signature,
+            secret,
+            tolerance_secs,
+            unix_timestamp(SystemTime::now()),
+        )?;
+        Ok(stripe_event(&parse_json(payload)?)?)
+    })
+}
+
+/// Verify a Telegram webhook's secret token and map approve/reject buttons to quotes
+///
+/// `secret_token` is the `X-Telegram-Bot-Api-Secret-Token` header and `secret`
+/// the value passed to `setWebhook`.
+#[pyfunction]
+pub fn parse_telegram_webhook(
+    payload: &[u8],
+    secret_token: &str,
+    secret: &str,
+) -> PyResult<WebhookEvent> {
+    panic_boundary::catch(|| {
+        verify_telegram(secret_token, secret)?;
+        Ok(telegram_event(&parse_json(payload)?)?)
+    })
+}
@@ -0,0 +1,257 @@
+use pyo3::prelude::*;
+use sanitize_filename::sanitize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::panic_boundary;
+use crate::CleanupStats;
+
+static WORKSPACE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Shared state behind every clone of a workspace handle.
+/// Dropping the last handle cleans up if `cleanup()` was never called.
+#[derive(Debug)]
+struct WorkspaceState {
+    root: PathBuf,
+    artifacts: Vec<PathBuf>,
+    cleaned: bool,
+}
+
+impl WorkspaceState {
+    fn cleanup(&mut self) -> CleanupStats {
+        let mut stats = CleanupStats::default();
+        if self.cleaned {
+            return stats;
+        }
+        self.cleaned = true;
+
+        // Artifacts may live outside the root (e.g. a staged upload), so remove
+        // those individually before dropping the directory tree.
+        for artifact in self.artifacts.drain(..) {
+            if artifact.starts_with(&self.root) {
+                continue;
+            }
+            if let Ok(metadata) = fs::metadata(&artifact) {
+                if metadata.is_file() {
+                    match fs::remove_file(&artifact) {
+                        Ok(()) => stats.record_removal(&artifact, &metadata),
+                        Err(e) => stats.record_error(&artifact, &e),
+                    }
+                }
+            }
+        }
+
+        let mut contents = Vec::new();
+        collect_files(&self.root, &mut contents);
+        match fs::remove_dir_all(&self.root) {
+            Ok(()) => {
+                for (path, metadata) in &contents {
+                    stats.record_removal(path, metadata);
+                }
+            }
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                stats.record_error(&self.root, &e)
+            }
+            Err(_) => {}
+        }
+        stats
+    }
+}
+
+impl Drop for WorkspaceState {
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+/// Collect every file below a directory, ignoring unreadable entries.
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, fs::Metadata)>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_files(&path, files);
+            } else if let Ok(metadata) = entry.metadata() {
+                files.push((path, metadata));
+            }
+        }
+    }
+}
+
+/// Scratch directories for a single quote job, removed on success, failure, or cancellation
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct JobWorkspace {
+    #[pyo3(get)]
+    pub job_id: String,
+    #[pyo3(get)]
+    pub root: String,
+    #[pyo3(get)]
+    pub model_dir: String,
+    #[pyo3(get)]
+    pub output_dir: String,
+    state: Arc<Mutex<WorkspaceState>>,
+}
+
+impl JobWorkspace {
+    /// Create `<base_dir>/orca-job-<job_id>/{model,output}`; AlreadyExists if
+    /// another job, or an id that sanitizes the same, already has the root.
+    pub fn create(base_dir: Option<&Path>, job_id: Option<&str>) -> std::io::Result<Self> {
+        let job_id = match job_id {
+            Some(id) if !sanitize(id).is_empty() => sanitize(id),
+            _ => generate_job_id(),
+        };
+        let base = base_dir
+            .map(Path::to_path_buf)
+            .unwrap_or_else(std::env::temp_dir);
+        let root = base.join(format!("orca-job-{}", job_id));
+        let model_dir = root.join("model");
+        let output_dir = root.join("output");
+
+        fs::create_dir_all(&base)?;
+        // Not create_dir_all: a shared root would be deleted by whichever job
+        // cleans up first.
+        fs::create_dir(&root).map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => std::io::Error::new(
+                e.kind(),
+                format!("workspace {} is already in use", root.display()),
+            ),
+            _ => e,
+        })?;
+        if let Err(e) = fs::create_dir(&model_dir).and_then(|()| fs::create_dir(&output_dir)) {
+            let _ = fs::remove_dir_all(&root);
+            return Err(e);
+        }
+
+        Ok(JobWorkspace {
+            job_id,
+            root: root.to_string_lossy().into_owned(),
+            model_dir: model_dir.to_string_lossy().into_owned(),
+            output_dir: output_dir.to_string_lossy().into_owned(),
+            state: Arc::new(Mutex::new(WorkspaceState {
+                root,
+                artifacts: Vec::new(),
+                cleaned: false,
+            })),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WorkspaceState> {
+        // A panic while holding the lock must not prevent cleanup.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Record a file so it is removed together with the workspace.
+    pub fn track(&self, path: &Path) {
+        let mut state = self.lock();
+        if !state.artifacts.iter().any(|p| p == path) {
+            state.artifacts.push(path.to_path_buf());
+        }
+    }
+
+    /// Copy a model into `model_dir` under a sanitized name and track it.
+    pub fn stage(&self, source: &Path) -> std::io::Result<PathBuf> {
+        let file_name = source
+            .file_name()
+            .map(|name| sanitize(name.to_string_lossy()))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "model".to_string());
+        let target = Path::new(&self.model_dir).join(file_name);
+        fs::copy(source, &target)?;
+        self.track(&target);
+        Ok(target)
+    }
+
+    pub fn release(&self) -> CleanupStats {
+        self.lock().cleanup()
+    }
+}
+
+pub(crate) fn generate_job_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let count = WORKSPACE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:x}-{:x}", nanos, std::process::id(), count)
+}
+
+#[pymethods]
+impl JobWorkspace {
+    /// Paths of every artifact tracked so far.
+    #[getter]
+    fn artifacts(&self) -> Vec<String> {
+        self.lock()
+            .artifacts
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// False once the workspace has been cleaned up.
+    #[getter]
+    fn is_active(&self) -> bool {
+        !self.lock().cleaned
+    }
+
+    /// Register a produced file for removal during cleanup.
+    fn track_artifact(&self, path: String) {
+        self.track(Path::new(&path));
+    }
+
+    /// Copy an uploaded model into the workspace and return the staged path.
+    fn stage_model(&self, source_path: String) -> PyResult<String> {
+        let staged = self.stage(Path::new(&source_path))?;
+        Ok(staged.to_string_lossy().into_owned())
+    }
+
+    /// Remove the workspace directory and all tracked artifacts. Safe to call twice.
+    fn cleanup(&self) -> CleanupStats {
+        self.release()
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __exit__(
+        &self,
+        _exc_type: Option<&PyAny>,
+        _exc_value: Option<&PyAny>,
+        _traceback: Option<&PyAny>,
+    ) -> bool {
+        self.release();
+        false
+    }
+
+    fn __str__(&self) -> String {
+        let state = self.lock();
+        format!(
+            "JobWorkspace(id={}, root={}, artifacts={}, active={})",
+            self.job_id,
+            self.root,
+            state.artifacts.len(),
+            !state.cleaned
+        )
+    }
+}
+
+/// Create a per-quote workspace with `model` and `output` subdirectories
+#[pyfunction]
+#[pyo3(signature = (base_dir=None, job_id=None))]
+pub fn create_job_workspace(
+    base_dir: Option<String>,
+    job_id: Option<String>,
+) -> PyResult<JobWorkspace> {
+    panic_boundary::catch(|| {
+        Ok(JobWorkspace::create(
+            base_dir.as_deref().map(Path::new),
+            job_id.as_deref(),
+        )?)
+    })
+}
@@ -0,0 +1,151 @@
+use std::io::{self, Read};
+
+use crate::memory_limits::Budget;
+
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// An opening or empty-element tag, e.g. `<vertex x="1" y="2" z="3"/>`.
+pub struct Tag<'a> {
+    /// Element name without its namespace prefix.
+    pub name: &'a str,
+    attributes: &'a str,
+}
+
+impl<'a> Tag<'a> {
+    /// Value of the attribute with this name, namespace prefix ignored.
+    pub fn attr(&self, name: &str) -> Option<&'a str> {
+        let mut rest = self.attributes;
+        loop {
+            rest = rest.trim_start();
+            let (key, after) = rest.split_once('=')?;
+            let after = after.trim_start();
+            let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let (value, tail) = after[1..].split_once(quote)?;
+            if local_name(key.trim()) == name {
+                return Some(value);
+            }
+            rest = tail;
+        }
+    }
+}
+
+/// What the scanner found next in the document.
+pub enum XmlEvent<'a> {
+    Start(Tag<'a>),
+    /// An end tag, by name without its namespace prefix.
+    End(&'a str),
+    /// Text between tags, trimmed; whitespace-only text is not reported.
+    Text(&'a str),
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+fn utf8(bytes: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(bytes)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "XML is not valid UTF-8"))
+}
+
+fn dispatch_text(text: &[u8], on_event: &mut impl FnMut(XmlEvent<'_>)) -> io::Result<()> {
+    let text = utf8(text)?.trim();
+    if !text.is_empty() {
+        on_event(XmlEvent::Text(text));
+    }
+    Ok(())
+}
+
+fn dispatch(markup: &[u8], on_event: &mut impl FnMut(XmlEvent<'_>)) -> io::Result<()> {
+    let markup = utf8(markup)?;
+    if markup.starts_with('?') || markup.starts_with('!') {
+        // Declarations, processing instructions, comments and CDATA carry no markup.
+    } else if let Some(name) = markup.strip_prefix('/') {
+        on_event(XmlEvent::End(local_name(name.trim())));
+    } else {
+        let body = markup.strip_suffix('/').unwrap_or(markup);
+        let (name, attributes) = body
+            .split_once(|c: char| c.is_ascii_whitespace())
+            .unwrap_or((body, ""));
+        on_event(XmlEvent::Start(Tag {
+            name: local_name(name),
+            attributes,
+        }));
+    }
+    Ok(())
+}
+
+/// Markup is complete at a `>` outside quotes, unless a comment or CDATA
+/// section has not reached its own terminator yet.
+fn markup_complete(markup: &[u8]) -> bool {
+    if markup.starts_with(b"!--") {
+        markup.len() >= 5 && markup.ends_with(b"--")
+    } else if markup.starts_with(b"![CDATA[") {
+        markup.ends_with(b"]]")
+    } else {
+        true
+    }
+}
+
+/// Call `on_event` for every start tag, end tag and run of text of an XML
+/// document, streaming.
+///
+/// This is not a validating parser: it finds the elements, attributes and text
+/// that mesh formats are made of, and no more. Entities are not decoded. A
+/// single tag or run of text longer than the budget fails with a memory-limit
+/// error.
+pub fn for_each_event<R: Read>(
+    mut reader: R,
+    budget: Budget,
+    mut on_event: impl FnMut(XmlEvent<'_>),
+) -> io::Result<()> {
+    let limit = budget.limit();
+    let mut chunk = vec![0u8; CHUNK_BYTES];
+    let mut current: Vec<u8> = Vec::new();
+    let mut in_markup = false;
+    let mut quote: Option<u8> = None;
+
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &byte in &chunk[..read] {
+            if !in_markup {
+                if byte == b'<' {
+                    dispatch_text(&current, &mut on_event)?;
+                    current.clear();
+                    in_markup = true;
+                } else {
+                    current.push(byte);
+                }
+            } else if let Some(open) = quote {
+                current.push(byte);
+                if byte == open {
+                    quote = None;
+                }
+            } else if byte == b'>' && markup_complete(&current) {
+                dispatch(&current, &mut on_event)?;
+                current.clear();
+                in_markup = false;
+            } else {
+                // Quotes only matter inside tags, not comments or CDATA.
+                if (byte == b'"' || byte == b'\'') && !current.starts_with(b"!") {
+                    quote = Some(byte);
+                }
+                current.push(byte);
+            }
+            if limit > 0 && current.len() as u64 > limit {
+                return Err(budget.exceeded(limit));
+            }
+        }
+    }
+    if in_markup {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "XML ends inside a tag",
+        ));
+    }
+    Ok(())
+}
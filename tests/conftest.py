@@ -59,14 +59,13 @@ def test_settings() -> Any:
 
 @pytest.fixture
 def mock_orcaslicer_cli(mocker: MockerFixture) -> MagicMock:
-    """Mock only the OrcaSlicer CLI subprocess call."""
-    # Mock at the subprocess level, not the service level
-    mock_subprocess = mocker.patch("asyncio.create_subprocess_exec")
-    mock_process = mocker.AsyncMock()
-    mock_process.returncode = 0
-    mock_process.communicate = mocker.AsyncMock(return_value=(b"Success", b""))
-    mock_subprocess.return_value = mock_process
-    return mock_subprocess
+    """Mock only the OrcaSlicer CLI run."""
+    # Mock at the slicer pool, not the service level
+    job = mocker.MagicMock(wait_ms=0.0)
+    job.wait = mocker.AsyncMock(return_value=[])
+    pool = mocker.patch("orca_quote_machine.services.slicer.get_slicer_pool")
+    pool.return_value.submit.return_value = job
+    return pool.return_value.submit
 
 
 @pytest.fixture
@@ -319,3 +319,23 @@ class TestTelegramMessage:
         assert "Material: PLA (default)" in formatted
         # Should not contain color info when color is None
         assert " - " not in formatted.split("Material:")[1].split("\n")[0]
+
+    def test_format_message_with_warnings(self):
+        """Test each operator warning gets its own line under the material."""
+        message = TelegramMessage(
+            quote_id="test-789",
+            customer_name="Jane Doe",
+            customer_mobile="+6598765432",
+            material="PLA",
+            color=None,
+            filename="pillar.stl",
+            print_time="3h",
+            filament_weight="40g",
+            total_cost=35.0,
+            warnings=["Tall or top-heavy model (tipping risk 1.00, 100 mm² on the bed): print with a raft"],
+        )
+
+        formatted = message.format_message()
+
+        assert "Material: PLA\nWarning: Tall or top-heavy model" in formatted
+        assert formatted.count("Warning:") == 1
@@ -153,13 +153,13 @@ class TestSlicerProfileSettings:
             slicer_settings = SlicerProfileSettings(
                 base_dir=Path("/custom/path"),
                 machine="my_machine.json",
-                filament_pla="my_pla.json",
+                material_map=Path("/custom/materials.toml"),
             )
 
             # Test that our path logic works correctly
             assert slicer_settings.base_dir == Path("/custom/path")
             assert slicer_settings.machine == "my_machine.json"
-            assert slicer_settings.filament_pla == "my_pla.json"
+            assert slicer_settings.material_map == Path("/custom/materials.toml")
 
     def test_default_profile_names(self):
         """Test that default profile filenames are sensible."""
@@ -169,6 +169,4 @@ class TestSlicerProfileSettings:
             # Test our updated default choices that match actual files
             assert slicer_settings.machine == "RatRig V-Core 3 400 0.5 nozzle.json"
             assert slicer_settings.process == "0.2mm RatRig 0.5mm nozzle.json"
-            assert slicer_settings.filament_pla == "ALT TABL MATTE PLA PEI.json"
-            assert slicer_settings.filament_petg == "Alt Tab PETG.json"
-            assert slicer_settings.filament_asa == "fusrock ASA.json"
+            assert slicer_settings.material_map == Path("config/material_profiles.toml")
@@ -156,6 +156,18 @@ class TestQuoteEndpoint:
         assert response.status_code == 413  # Request entity too large
         assert "File too large" in response.json()["detail"]
 
+    def test_create_quote_invalid_model(
+        self, client: TestClient, sample_quote_data: dict
+    ) -> None:
+        """Test quote creation fails when the upload is not a valid model."""
+        html = b"<html><body>502 Bad Gateway</body></html>\n"
+        files = {"model_file": ("part.step", html, "application/octet-stream")}
+
+        response = client.post("/quote", files=files, data=sample_quote_data)
+
+        assert response.status_code == 400
+        assert "missing: ISO header" in response.json()["detail"]
+
 
 class TestTaskStatusEndpoint:
     """Tests for the task status endpoint."""
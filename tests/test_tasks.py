@@ -17,7 +17,6 @@ class TestTasks:
     """Tests for Celery task functions."""
 
     @patch("orca_quote_machine.tasks.asyncio.run")
-    @patch("orca_quote_machine.tasks.validate_3d_model", None)
     @patch("orca_quote_machine.tasks.os.path.exists", return_value=False)
     def test_process_quote_request(
         self, mock_exists: MagicMock, mock_asyncio_run: MagicMock
@@ -38,38 +37,38 @@ class TestTasks:
 
     @pytest.mark.asyncio
     @patch("orca_quote_machine.tasks.OrcaSlicerService")
-    @patch("orca_quote_machine.tasks.PricingService")
     @patch("orca_quote_machine.tasks.TelegramService")
     async def test_run_processing_pipeline(
-        self, mock_telegram: MagicMock, mock_pricing: MagicMock, mock_slicer: MagicMock
+        self, mock_telegram: MagicMock, mock_slicer: MagicMock, tmp_path
     ) -> None:
         """Test run_processing_pipeline function."""
-        import os
-        import tempfile
-
-        from orca_quote_machine._rust_core import SlicingResult, parse_slicer_output
-
-        # Create a real SlicingResult using Rust parser
-        async def create_real_slicing_result() -> SlicingResult:
-            with tempfile.TemporaryDirectory() as temp_dir:
-                gcode_file = os.path.join(temp_dir, 'test.gcode')
-                with open(gcode_file, 'w') as f:  # noqa: ASYNC230  # Test file creation
-                    f.write('; estimated printing time: 2h 0m\n; filament used: 25.5g\n')
-                return await parse_slicer_output(temp_dir)
-
-        real_slicing_result = await create_real_slicing_result()
-
-        # Setup mocks with real objects
-        mock_slicer_instance = mock_slicer.return_value
-        mock_slicer_instance.slice_model = AsyncMock(return_value=real_slicing_result)
-
-        # Use real pricing service to create real CostBreakdown
-        from orca_quote_machine.services.pricing import PricingService
-        real_pricing_service = PricingService()
-        real_cost_breakdown = real_pricing_service.calculate_quote(real_slicing_result, MaterialType.PLA)
-
-        mock_pricing_instance = mock_pricing.return_value
-        mock_pricing_instance.calculate_quote.return_value = real_cost_breakdown
+        import json
+        import stat
+
+        from orca_quote_machine._rust_core import create_pipeline_config
+
+        # Quote against a stub slicer and a one-printer profile tree
+        for profile_type in ("machine", "process", "filament"):
+            (tmp_path / profile_type).mkdir()
+        (tmp_path / "machine" / "printer.json").write_text(json.dumps({"type": "machine"}))
+        (tmp_path / "process" / "standard.json").write_text(json.dumps({"type": "process"}))
+        (tmp_path / "filament" / "pla.json").write_text(json.dumps({"filament_type": ["PLA"]}))
+        slicer = tmp_path / "slicer.sh"
+        slicer.write_text(
+            '#!/bin/sh\nwhile [ $# -gt 0 ]; do [ "$1" = "--outputdir" ] && out="$2"; shift; done\n'
+            "printf '; estimated printing time = 2h 0m\\n; filament used [g] = 25.5\\n'"
+            ' > "$out/plate_1.gcode"\n'
+        )
+        slicer.chmod(slicer.stat().st_mode | stat.S_IEXEC)
+        model = tmp_path / "test.stl"
+        model.write_text(
+            "solid cube\n  facet normal 0 0 1\n    outer loop\n"
+            "      vertex 0 0 0\n      vertex 20 0 0\n      vertex 0 20 10\n"
+            "    endloop\n  endfacet\nendsolid cube\n"
+        )
+        mock_slicer.return_value.pipeline_config.return_value = create_pipeline_config(
+            str(slicer), str(tmp_path), "printer.json", "standard.json"
+        )
 
         mock_telegram_instance = mock_telegram.return_value
         mock_telegram_instance.send_quote_notification = AsyncMock(return_value=True)
@@ -81,11 +80,13 @@ class TestTasks:
         }
 
         result = await run_processing_pipeline(
-            "/path/to/test.stl", quote_data, MaterialType.PLA, "test-uuid", "test-123"
+            str(model), quote_data, MaterialType.PLA, "test-uuid", "test-123"
         )
 
         assert isinstance(result, dict)
-        assert "success" in result
+        assert result["success"] is True
+        assert result["slicing_result"]["print_time_minutes"] == 120
+        assert result["cost_breakdown"]["total_cost"] > 0
 
     @pytest.mark.asyncio
     @patch("orca_quote_machine.tasks.TelegramService")
@@ -99,14 +100,14 @@ class TestTasks:
         # Function returns None
         assert result is None
 
-    @patch("orca_quote_machine.tasks.cleanup_old_files_rust")
-    def test_cleanup_old_files(self, mock_cleanup_rust: MagicMock) -> None:
+    @patch("orca_quote_machine.tasks.cleanup_old_files_async", new_callable=AsyncMock)
+    def test_cleanup_old_files(self, mock_cleanup_async: AsyncMock) -> None:
         """Test cleanup_old_files function."""
-        # Mock the Rust cleanup function to return stats
+        # Mock the Rust cleanup coroutine to return stats
         mock_stats = MagicMock()
         mock_stats.files_cleaned = 5
         mock_stats.bytes_freed = 12345
-        mock_cleanup_rust.return_value = mock_stats
+        mock_cleanup_async.return_value = mock_stats
 
         result = cleanup_old_files(max_age_hours=24)
 
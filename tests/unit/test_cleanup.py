@@ -0,0 +1,81 @@
+"""Unit tests for the Rust file cleanup functions.
+
+Focus: Test that old files are removed and statistics are reported.
+"""
+
+import json
+import os
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    CleanupStats,
+    cleanup_old_files_async,
+    cleanup_old_files_rust,
+)
+
+
+def _make_old_file(path, age_hours: int) -> None:
+    path.write_text("test content")
+    old_time = os.path.getmtime(path) - age_hours * 3600
+    os.utime(path, (old_time, old_time))
+
+
+class TestCleanupOldFilesRust:
+    """Tests for cleanup statistics and audit recording."""
+
+    def test_cleanup_reports_breakdown_and_audit(self, tmp_path):
+        """Test per-extension stats, timestamps, and the audit log record."""
+        uploads = tmp_path / "uploads"
+        uploads.mkdir()
+        _make_old_file(uploads / "a.stl", 48)
+        _make_old_file(uploads / "b.STL", 72)
+        _make_old_file(uploads / "c.obj", 48)
+        audit_log = tmp_path / "audit.jsonl"
+
+        stats = cleanup_old_files_rust(str(uploads), 24, str(audit_log))
+
+        assert stats.files_by_extension == {"stl": 2, "obj": 1}
+        assert stats.oldest_deleted < stats.newest_deleted
+        assert stats.errors == []
+
+        record = json.loads(audit_log.read_text().splitlines()[-1])
+        assert record["event"] == "cleanup"
+        assert record["files_cleaned"] == 3
+
+    def test_audit_failure_still_reports_stats(self, tmp_path):
+        """Test an unwritable audit log lands in errors instead of losing the sweep's stats."""
+        uploads = tmp_path / "uploads"
+        uploads.mkdir()
+        _make_old_file(uploads / "a.stl", 48)
+        (tmp_path / "not-a-dir").write_text("")
+        audit_log = tmp_path / "not-a-dir" / "audit.jsonl"
+
+        stats = cleanup_old_files_rust(str(uploads), 24, str(audit_log))
+
+        assert stats.files_by_extension == {"stl": 1}
+        assert len(stats.errors) == 1 and str(audit_log) in stats.errors[0]
+
+
+class TestCleanupOldFilesAsync:
+    """Tests for the tokio-based cleanup variant."""
+
+    @pytest.mark.asyncio
+    async def test_cleanup_old_files_async(self, tmp_path):
+        """Test that only files older than the cutoff are removed."""
+        _make_old_file(tmp_path / "old.stl", 48)
+        (tmp_path / "fresh.stl").write_text("test content")
+
+        stats = await cleanup_old_files_async(str(tmp_path), 24)
+
+        assert isinstance(stats, CleanupStats)
+        assert stats.files_cleaned == 1
+        assert stats.bytes_freed == len("test content")
+        assert os.listdir(tmp_path) == ["fresh.stl"]
+
+    @pytest.mark.asyncio
+    async def test_cleanup_old_files_async_missing_dir(self, tmp_path):
+        """Test that a missing directory is treated as nothing to clean."""
+        stats = await cleanup_old_files_async(str(tmp_path / "missing"), 24)
+
+        assert stats.files_cleaned == 0
@@ -0,0 +1,108 @@
+"""Unit tests for mesh decimation.
+
+Focus: Test that dense meshes are simplified to within the target triangle
+count while keeping their size, and that small meshes are left as they are.
+"""
+
+import math
+
+import pytest
+
+from orca_quote_machine._rust_core import decimate_mesh, validate_3d_model
+
+
+def _write_sphere(path, segments: int = 40, radius: float = 10.0) -> str:
+    """An ASCII STL sphere of latitude/longitude quads, 2 * segments * (segments - 1) facets."""
+
+    def point(ring: int, step: int) -> tuple[float, float, float]:
+        polar = math.pi * ring / segments
+        azimuth = 2 * math.pi * step / segments
+        return (
+            radius * math.sin(polar) * math.cos(azimuth),
+            radius * math.sin(polar) * math.sin(azimuth),
+            radius * math.cos(polar),
+        )
+
+    lines = ["solid sphere"]
+    for ring in range(segments):
+        for step in range(segments):
+            a, b = point(ring, step), point(ring, step + 1)
+            c, d = point(ring + 1, step), point(ring + 1, step + 1)
+            triangles = []
+            if ring > 0:
+                triangles.append((a, c, b))
+            if ring < segments - 1:
+                triangles.append((b, c, d))
+            for triangle in triangles:
+                lines += ["facet normal 0 0 0", "outer loop"]
+                lines += [f"vertex {x:.6f} {y:.6f} {z:.6f}" for x, y, z in triangle]
+                lines += ["endloop", "endfacet"]
+    lines.append("endsolid sphere")
+    path.write_text("\n".join(lines) + "\n")
+    return str(path)
+
+
+class TestDecimateMesh:
+    """Tests for decimate_mesh."""
+
+    def test_dense_mesh_simplified_within_target(self, tmp_path):
+        """Test a 3120-triangle sphere comes down to at most 300 of about the same size."""
+        output = tmp_path / "sphere.small.stl"
+        decimation = decimate_mesh(_write_sphere(tmp_path / "sphere.stl"), 300, str(output))
+
+        assert decimation.triangles_before == 3120
+        assert 100 < decimation.triangles_after <= 300
+        assert decimation.cell_size_mm > 0
+        info = validate_3d_model(str(output))
+        assert info.is_valid
+        assert all(side > 17.0 for side in info.dimensions_mm)
+
+    def test_small_mesh_written_unchanged(self, tmp_path):
+        """Test a mesh already within target is copied beside the model as it is."""
+        decimation = decimate_mesh(_write_sphere(tmp_path / "ball.stl", segments=8), 1000)
+
+        assert decimation.triangles_after == decimation.triangles_before == 112
+        assert decimation.cell_size_mm == 0
+        assert decimation.output_path == str(tmp_path / "ball.decimated.stl")
+
+    def test_never_collapses_to_nothing(self, tmp_path):
+        """Test a mesh no grid can bring within target without collapsing is kept as it is."""
+        cube = tmp_path / "cube.stl"
+        corners = [(x, y, z) for x in (0, 10) for y in (0, 10) for z in (0, 10)]
+        faces = [
+            (0, 1, 3), (0, 3, 2), (4, 6, 7), (4, 7, 5), (0, 4, 5), (0, 5, 1),
+            (2, 3, 7), (2, 7, 6), (0, 2, 6), (0, 6, 4), (1, 5, 7), (1, 7, 3),
+        ]
+        lines = ["solid cube"]
+        for face in faces:
+            lines += ["facet normal 0 0 0", "outer loop"]
+            lines += ["vertex %d %d %d" % corners[i] for i in face]
+            lines += ["endloop", "endfacet"]
+        cube.write_text("\n".join(lines + ["endsolid cube"]) + "\n")
+
+        kept = decimate_mesh(str(cube), 10)
+        assert kept.triangles_after == kept.triangles_before == 12
+        assert kept.cell_size_mm == 0
+
+        sphere = decimate_mesh(_write_sphere(tmp_path / "sphere.stl"), 10)
+        assert sphere.triangles_after >= 4
+        assert validate_3d_model(sphere.output_path).is_valid
+
+    def test_output_over_input_refused(self, tmp_path):
+        """Test decimating a model onto itself raises and leaves it intact."""
+        sphere = _write_sphere(tmp_path / "sphere.stl")
+        original = (tmp_path / "sphere.stl").read_bytes()
+
+        with pytest.raises(ValueError, match="is the input file"):
+            decimate_mesh(sphere, 4, sphere)
+        assert (tmp_path / "sphere.stl").read_bytes() == original
+
+    def test_unusable_targets_and_step_refused(self, tmp_path):
+        """Test targets too small for a solid, and files without a mesh, raise ValueError."""
+        with pytest.raises(ValueError, match="fewer than the 4"):
+            decimate_mesh(_write_sphere(tmp_path / "sphere.stl"), 3)
+
+        step = tmp_path / "part.step"
+        step.write_text("ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\nENDSEC;\nEND-ISO-10303-21;\n")
+        with pytest.raises(ValueError, match="no mesh to decimate"):
+            decimate_mesh(str(step), 100)
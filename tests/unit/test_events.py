@@ -0,0 +1,161 @@
+"""Unit tests for pipeline events.
+
+Focus: Test events reach callbacks, webhooks and MQTT brokers in the documented format.
+"""
+
+import hashlib
+import hmac
+import json
+import socket
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    add_event_callback,
+    add_mqtt_sink,
+    add_webhook_sink,
+    clear_event_sinks,
+    emit_event,
+    flush_events,
+)
+
+
+def _packet(data):
+    """Split the first MQTT packet off `data`: (type, rest of data, body)."""
+    length, multiplier, pos = 0, 1, 1
+    while True:
+        byte = data[pos]
+        length += (byte & 0x7F) * multiplier
+        multiplier *= 128
+        pos += 1
+        if not byte & 0x80:
+            break
+    return data[0], data[pos + length :], data[pos : pos + length]
+
+
+@pytest.fixture(autouse=True)
+def no_sinks():
+    """Start and end each test without sinks, as they are process-wide."""
+    clear_event_sinks()
+    yield
+    clear_event_sinks()
+
+
+@pytest.fixture
+def webhook():
+    """Fake webhook receiver recording headers and bodies."""
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = self.rfile.read(int(self.headers["Content-Length"]))
+            server.requests.append((dict(self.headers), body))
+            self.send_response(204)
+            self.end_headers()
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    server.requests = []
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield server
+    server.shutdown()
+
+
+@pytest.fixture
+def broker():
+    """Fake MQTT broker that accepts one connection and keeps what it was sent."""
+    listener = socket.socket()
+    listener.bind(("127.0.0.1", 0))
+    listener.listen(1)
+    received = bytearray()
+
+    def serve():
+        conn, _ = listener.accept()
+        with conn:
+            connect = conn.recv(1024)
+            received.extend(connect)
+            conn.sendall(b"\x20\x02\x00\x00")
+            while chunk := conn.recv(4096):
+                received.extend(chunk)
+
+    thread = threading.Thread(target=serve, daemon=True)
+    thread.start()
+    yield listener.getsockname()[1], received, thread
+    listener.close()
+
+
+class TestEmitEvent:
+    """Tests for emit_event and its sinks."""
+
+    def test_callback_receives_typed_event(self):
+        """Test callbacks get the event synchronously and unknown types are rejected."""
+        seen = []
+        add_event_callback(seen.append)
+
+        event = emit_event("quote.created", "q-1", {"total_cost": 12.5})
+
+        assert [e.event_type for e in seen] == ["quote.created"]
+        assert seen[0].quote_id == "q-1" and seen[0].data == {"total_cost": 12.5}
+        assert json.loads(event.to_json())["type"] == "quote.created"
+        with pytest.raises(ValueError, match="unknown event type"):
+            emit_event("quote.deleted", "q-1")
+
+    def test_signed_webhook_delivery(self, webhook):
+        """Test the webhook gets the event JSON with a verifiable HMAC signature."""
+        add_webhook_sink(f"http://127.0.0.1:{webhook.server_port}/hook", secret="s3cret")
+
+        emit_event("quote.failed", "q-2", {"error": "Invalid 3D model"})
+        assert flush_events(5.0)
+
+        headers, body = webhook.requests[0]
+        expected = hmac.new(b"s3cret", body, hashlib.sha256).hexdigest()
+        assert headers["X-Orca-Signature"] == f"sha256={expected}"
+        assert headers["X-Orca-Event"] == "quote.failed"
+        assert json.loads(body)["data"] == {"error": "Invalid 3D model"}
+
+    def test_mqtt_publish(self, broker):
+        """Test events are published to <prefix>/<type> with the event JSON as payload."""
+        port, received, thread = broker
+        add_mqtt_sink("127.0.0.1", port, topic_prefix="shop/quotes", client_id="test")
+
+        emit_event("notification.sent", "q-3", {"channel": "telegram"})
+        assert flush_events(5.0)
+        thread.join(5.0)
+
+        connect_end = 2 + received[1]
+        assert received[0] == 0x10 and b"MQTT" in received[:connect_end]
+        kind, remaining, body = _packet(bytes(received[connect_end:]))
+        topic = b"shop/quotes/notification.sent"
+        assert kind == 0x30 and body[:2] == len(topic).to_bytes(2, "big")
+        assert body[2 : 2 + len(topic)] == topic
+        assert json.loads(body[2 + len(topic) :])["quote_id"] == "q-3"
+        assert remaining == b"\xe0\x00"
+
+    def test_mqtt_topic_templates(self, broker):
+        """Test topic templates, dropped types, the retain flag and bad templates."""
+        port, received, thread = broker
+        add_mqtt_sink(
+            "127.0.0.1",
+            port,
+            topic_prefix="shop",
+            topics={"printer.assigned": "farm/{printer}/{type}", "quote.created": ""},
+            retain=True,
+            client_id="test",
+        )
+
+        emit_event("quote.created", "q-4", {"printer": "Voron"})
+        emit_event("printer.assigned", "q-4", {"printer": "Voron/2.4"})
+        assert flush_events(5.0)
+        thread.join(5.0)
+
+        _, publish, _ = _packet(bytes(received))
+        kind, _, body = _packet(publish)
+        topic = b"farm/Voron_2.4/printer.assigned"
+        assert kind == 0x31 and body[2 : 2 + len(topic)] == topic
+        with pytest.raises(ValueError, match="wildcard"):
+            add_mqtt_sink("127.0.0.1", port, topics={"job.sent": "farm/+/jobs"})
+        with pytest.raises(ValueError, match="unknown event type"):
+            add_mqtt_sink("127.0.0.1", port, topics={"job.queued": "farm/jobs"})
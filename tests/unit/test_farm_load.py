@@ -0,0 +1,187 @@
+"""Unit tests for printer-farm load and lead times.
+
+Focus: Test printer hosts are polled and the first free capable printer sets the lead time.
+"""
+
+import json
+import os
+import socket
+import threading
+from datetime import datetime, timedelta, timezone
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    create_business_calendar,
+    create_farm_monitor,
+    estimate_lead_time,
+    farm_status,
+    load_fleet,
+    promised_completion,
+)
+
+
+def _write_machine(profiles_dir, file_name: str) -> None:
+    machine_dir = profiles_dir / "machine"
+    machine_dir.mkdir(parents=True, exist_ok=True)
+    (machine_dir / file_name).write_text(
+        json.dumps({"type": "machine", "name": file_name.removesuffix(".json")})
+    )
+
+
+def _closed_port() -> int:
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def hosts():
+    """Fake Moonraker and OctoPrint APIs on one server, answering from `responses`."""
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            path = self.path.split("?")[0]
+            server.requests.append((path, self.headers.get("X-Api-Key")))
+            if path not in server.responses:
+                self.send_response(404)
+                self.end_headers()
+                return
+            body = json.dumps(server.responses[path]).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    server.requests = []
+    server.responses = {}
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield server
+    server.shutdown()
+
+
+def _fleet(tmp_path, moonraker_url: str, octoprint_url: str):
+    profiles_dir = tmp_path / "profiles"
+    _write_machine(profiles_dir, "voron.json")
+    _write_machine(profiles_dir, "prusa.json")
+    fleet = tmp_path / "fleet.toml"
+    fleet.write_text(
+        f'[[printer]]\nname = "Voron"\nmachine = "voron.json"\nmoonraker_url = "{moonraker_url}"\n\n'
+        f'[[printer]]\nname = "Prusa"\nmachine = "prusa.json"\noctoprint_url = "{octoprint_url}"\n'
+        'api_key_env = "TEST_FARM_PRUSA_KEY"\n\n'
+        '[[printer]]\nname = "Spare"\nmachine = "prusa.json"\nmaterials = ["ASA"]\n'
+    )
+    return load_fleet(str(fleet), str(profiles_dir))
+
+
+class TestEstimateLeadTime:
+    """Tests for estimate_lead_time and farm_status."""
+
+    def test_first_free_capable_printer_sets_lead_time(self, tmp_path, hosts):
+        """Test running jobs, queued jobs and handling time add up on the right printer."""
+        base = f"http://127.0.0.1:{hosts.server_port}"
+        hosts.responses = {
+            "/printer/objects/query": {
+                "result": {
+                    "status": {
+                        "print_stats": {"state": "printing", "print_duration": 3600.0},
+                        "virtual_sdcard": {"progress": 0.5},
+                    }
+                }
+            },
+            "/server/job_queue/status": {"result": {"queued_jobs": [{"filename": "a.gcode"}]}},
+            "/api/job": {"state": "Printing", "progress": {"printTimeLeft": 1800}},
+        }
+        os.environ["TEST_FARM_PRUSA_KEY"] = "prusa-key"
+        try:
+            fleet = _fleet(tmp_path, base, base)
+            monitor = create_farm_monitor(fleet, max_age_secs=0, handling_hours=1.0)
+            lead = estimate_lead_time(monitor, 60, material="PLA")
+        finally:
+            del os.environ["TEST_FARM_PRUSA_KEY"]
+
+        assert fleet.printers[0].status_provider == "moonraker"
+        voron, prusa = lead.printers
+        assert voron.remaining_seconds == pytest.approx(3600)
+        assert voron.queued_jobs == 1 and voron.available_in_seconds == pytest.approx(3600 + 7200)
+        assert prusa.state == "printing" and prusa.available_in_seconds == 1800
+        assert lead.printer == "Prusa" and lead.wait_seconds == 1800
+        assert lead.lead_time_seconds == pytest.approx(1800 + 3600 + 3600)
+        assert ("/api/job", "prusa-key") in hosts.requests
+
+    def test_provider_and_unreachable_hosts(self, tmp_path, hosts):
+        """Test a custom provider, offline hosts and unmonitored printers."""
+        fleet = _fleet(tmp_path, "http://127.0.0.1:1", f"http://127.0.0.1:{_closed_port()}")
+
+        def provider(printer):
+            if printer.name == "Voron":
+                return {"state": "paused", "remaining_seconds": 600.0, "queued_jobs": 0}
+            return None
+
+        monitor = create_farm_monitor(fleet, provider=provider, max_age_secs=0, timeout_secs=1.0)
+        states = {s.printer: s.state for s in farm_status(monitor)}
+        lead = estimate_lead_time(monitor, 30, material="PETG")
+
+        assert states == {"Voron": "paused", "Prusa": "offline", "Spare": "unknown"}
+        assert lead.printer == "Voron" and lead.wait_seconds == 600
+        assert estimate_lead_time(
+            create_farm_monitor(fleet, max_age_secs=0, timeout_secs=1.0), 30, material="PLA"
+        ) is None
+
+    def test_conflicting_status_urls_rejected(self, tmp_path):
+        """Test a printer cannot name both a Moonraker and an OctoPrint host."""
+        profiles_dir = tmp_path / "profiles"
+        _write_machine(profiles_dir, "voron.json")
+        fleet = tmp_path / "fleet.toml"
+        fleet.write_text(
+            '[[printer]]\nname = "Voron"\nmachine = "voron.json"\n'
+            'moonraker_url = "http://a"\noctoprint_url = "http://b"\n'
+        )
+
+        with pytest.raises(ValueError, match="both moonraker_url and octoprint_url"):
+            load_fleet(str(fleet), str(profiles_dir))
+
+
+class TestBusinessCalendar:
+    """Tests for create_business_calendar and promised_completion."""
+
+    def test_handling_waits_for_working_hours(self):
+        """Test nights, weekends and holidays push the promise to the next working hours."""
+        # Friday 2025-03-07 17:00 in Singapore (UTC+8)
+        friday_5pm = datetime(2025, 3, 7, 9, tzinfo=timezone.utc).timestamp()
+        calendar = create_business_calendar(holidays=["2025-03-10"])
+
+        def promised(busy_hours, handling_hours):
+            at = promised_completion(calendar, busy_hours * 3600, handling_hours * 3600,
+                                     start=friday_5pm)
+            return datetime.fromtimestamp(at, timezone(timedelta(hours=8))).strftime("%a %d %H:%M")
+
+        assert promised(0, 0.5) == "Fri 07 17:30"
+        # One hour on Friday, the rest after the weekend and Monday's holiday
+        assert promised(0, 3) == "Tue 11 11:00"
+        # Printed overnight, handed over when the shop opens
+        assert promised(6, 0) == "Tue 11 09:00"
+
+    def test_lead_time_carries_promised_date(self, tmp_path):
+        """Test a monitor with a calendar promises a date and bad calendars are refused."""
+        fleet = _fleet(tmp_path, "http://127.0.0.1:1", "http://127.0.0.1:1")
+        every_day = create_business_calendar(utc_offset_hours=0, open_hour=0, close_hour=24,
+                                             working_days=list(range(7)))
+        monitor = create_farm_monitor(fleet, provider=lambda printer: {"state": "idle"},
+                                      handling_hours=1.0, calendar=every_day)
+
+        lead = estimate_lead_time(monitor, 60, material="ASA")
+
+        assert lead.promised_at == pytest.approx(lead.ready_at, abs=5)
+        assert lead.promised_date == datetime.fromtimestamp(
+            lead.promised_at, timezone.utc).strftime("%Y-%m-%d")
+        with pytest.raises(ValueError, match="not a YYYY-MM-DD date"):
+            create_business_calendar(holidays=["2025-02-30"])
+        with pytest.raises(ValueError, match="not a range of hours"):
+            create_business_calendar(open_hour=18, close_hour=9)
@@ -0,0 +1,108 @@
+"""Unit tests for printer fleet configuration and build-plate fit.
+
+Focus: Test capability loading from machine profiles, printer selection, and
+checking a model against a machine profile's build volume.
+"""
+
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import check_fits_build_plate, load_fleet
+
+
+def _write_machine(profiles_dir, file_name: str, size: int, height: int) -> None:
+    machine_dir = profiles_dir / "machine"
+    machine_dir.mkdir(parents=True, exist_ok=True)
+    (machine_dir / file_name).write_text(
+        json.dumps(
+            {
+                "type": "machine",
+                "name": file_name.removesuffix(".json"),
+                "nozzle_diameter": ["0.4"],
+                "printable_area": ["0x0", f"{size}x0", f"{size}x{size}", f"0x{size}"],
+                "printable_height": str(height),
+            }
+        )
+    )
+
+
+def _write_cube_obj(path, x: float, y: float, z: float) -> str:
+    vertices = [(vx, vy, vz) for vx in (0, x) for vy in (0, y) for vz in (0, z)]
+    path.write_text(
+        "".join(f"v {vx} {vy} {vz}\n" for vx, vy, vz in vertices) + "f 1 2 3\n"
+    )
+    return str(path)
+
+
+@pytest.fixture
+def fleet_file(tmp_path):
+    """A small printer for PLA/PETG and a large one for everything."""
+    profiles_dir = tmp_path / "profiles"
+    _write_machine(profiles_dir, "mini.json", 180, 180)
+    _write_machine(profiles_dir, "big.json", 400, 400)
+    fleet = tmp_path / "fleet.toml"
+    fleet.write_text(
+        '[[printer]]\nname = "Big"\nmachine = "big.json"\n\n'
+        '[[printer]]\nname = "Mini"\nmachine = "mini.json"\nmaterials = ["pla", "PETG"]\n'
+    )
+    return fleet, profiles_dir
+
+
+class TestFleet:
+    """Tests for load_fleet and Fleet.select_printer."""
+
+    def test_capabilities_come_from_machine_profiles(self, fleet_file):
+        """Test bed size, height, and nozzle are read from each machine profile."""
+        fleet_path, profiles_dir = fleet_file
+
+        fleet = load_fleet(str(fleet_path), str(profiles_dir))
+        mini = fleet.printers[1]
+
+        assert [p.name for p in fleet.printers] == ["Big", "Mini"]
+        assert mini.bed_size == (180.0, 180.0)
+        assert mini.max_height == 180.0
+        assert mini.nozzle_diameter == pytest.approx(0.4)
+        assert mini.materials == ["PLA", "PETG"]
+
+    def test_select_printer_by_material_and_size(self, fleet_file, tmp_path):
+        """Test the smallest capable printer wins and oversized models are rejected."""
+        fleet = load_fleet(str(fleet_file[0]), str(fleet_file[1]))
+        small = _write_cube_obj(tmp_path / "small.obj", 50, 50, 50)
+        # Fits the mini bed only when rotated 90 degrees.
+        rotated = _write_cube_obj(tmp_path / "rotated.obj", 170, 100, 20)
+        large = _write_cube_obj(tmp_path / "large.obj", 300, 50, 50)
+        huge = _write_cube_obj(tmp_path / "huge.obj", 500, 50, 50)
+
+        assert fleet.select_printer("PLA", small).name == "Mini"
+        assert fleet.select_printer("PLA", rotated).name == "Mini"
+        assert fleet.select_printer("PLA", large).name == "Big"
+        assert fleet.select_printer("ASA", small).name == "Big"
+        assert fleet.select_printer("PETG").name == "Mini"
+        with pytest.raises(ValueError):
+            fleet.select_printer("PLA", huge)
+
+
+class TestCheckFitsBuildPlate:
+    """Tests for check_fits_build_plate."""
+
+    def test_fitting_model_returns_dimensions(self, tmp_path):
+        """Test a model that fits, turned 90 degrees if need be, returns its size."""
+        _write_machine(tmp_path, "mini.json", 180, 180)
+        machine = str(tmp_path / "machine" / "mini.json")
+
+        assert check_fits_build_plate(
+            _write_cube_obj(tmp_path / "rotated.obj", 170, 100, 20), machine
+        ) == (170.0, 100.0, 20.0)
+
+    def test_oversized_model_rejected(self, tmp_path):
+        """Test a model too wide or too tall for the resolved profile is a ValueError."""
+        _write_machine(tmp_path, "mini.json", 180, 180)
+        # Bed and height are inherited from the parent profile.
+        child = tmp_path / "machine" / "mini-child.json"
+        child.write_text(json.dumps({"type": "machine", "name": "Mini child", "inherits": "mini"}))
+
+        with pytest.raises(ValueError, match=r"200.0 x 50.0 x 50.0 mm, larger than the Mini child"):
+            check_fits_build_plate(_write_cube_obj(tmp_path / "wide.obj", 200, 50, 50), str(child))
+        with pytest.raises(ValueError, match=r"build volume of 180.0 x 180.0 x 180.0 mm"):
+            check_fits_build_plate(_write_cube_obj(tmp_path / "tall.obj", 50, 50, 181), str(child))
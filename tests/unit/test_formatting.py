@@ -0,0 +1,47 @@
+"""Unit tests for display formatting.
+
+Focus: Test amounts and weights follow the configured locale, currency and units.
+"""
+
+from orca_quote_machine._rust_core import calculate_quote_rust
+from orca_quote_machine.core.config import Settings
+from orca_quote_machine.core.formatting import DisplayFormat
+from orca_quote_machine.models.quote import TelegramMessage
+from orca_quote_machine.services.pricing import PricingService
+
+
+class TestDisplayFormat:
+    """Tests for DisplayFormat and the summaries that use it."""
+
+    def test_locales_currencies_and_units(self):
+        """Test digit grouping, currency placement and imperial weights."""
+        sg = DisplayFormat()
+        de = DisplayFormat(locale="de_DE", currency_format="{amount} €")
+        us = DisplayFormat(locale="en-US", currency_format="${amount}", units="imperial")
+
+        assert sg.money(1234.5) == "S$1,234.50"
+        assert de.money(1234.5) == "1.234,50 €"
+        assert DisplayFormat(locale="fr_FR").number(1234.5) == "1 234,50"
+        assert DisplayFormat(locale="xx").number(0.5, 1) == "0.5"
+        assert (sg.weight(453.59237), sg.bulk_weight(453.59237)) == ("453.6g", "0.454kg")
+        assert (us.weight(453.59237), us.bulk_weight(453.59237)) == ("16.00oz", "1.000lb")
+        assert us.price_per_bulk_unit(25.0) == "$11.34/lb"
+        assert sg.duration(150) == "2h 30m"
+
+    def test_summary_and_notification_use_settings(self):
+        """Test the cost summary and Telegram total follow the configured format."""
+        settings = Settings(
+            display_locale="de_DE", currency_format="{amount} €", display_units="imperial"
+        )
+        cost = calculate_quote_rust(120, 100.0, "PLA", 25.0, 0.5, 1.1, 5.0)
+        summary = PricingService(settings).format_cost_summary(cost)
+        message = TelegramMessage(
+            quote_id="q1", customer_name="A", customer_mobile="+6591234567",
+            material="PLA", color=None, filename="part.stl", print_time="2h 0m",
+            filament_weight="3.53oz", total_cost=1234.5,
+            display=DisplayFormat.from_settings(settings),
+        )
+
+        assert "Material: 3,53oz (0,220lb) × 11,34 €/lb = 2,50 €" in summary
+        assert "Time: 2,5h × 25,00 €/h = 62,50 €" in summary
+        assert "Total Cost: 1.234,50 €" in message.format_message()
@@ -0,0 +1,66 @@
+"""Unit tests for the G-code artifact cache.
+
+Focus: Test keys follow model and effective profile content, and eviction keeps the cache in budget.
+"""
+
+import json
+import os
+import time
+
+from orca_quote_machine._rust_core import create_gcode_cache
+
+
+def _profiles(root, layer_height="0.2"):
+    """Machine, process and filament profiles; returns their paths in that order."""
+    root.mkdir(parents=True, exist_ok=True)
+    machine = root / "machine.json"
+    machine.write_text(json.dumps({"type": "machine", "nozzle_diameter": ["0.4"]}))
+    process = root / "process.json"
+    process.write_text(json.dumps({"type": "process", "layer_height": layer_height}))
+    filament = root / "filament.json"
+    filament.write_text(json.dumps({"type": "filament", "filament_type": ["PLA"]}))
+    return str(machine), str(process), str(filament)
+
+
+def _output_dir(path, size=100):
+    path.mkdir(parents=True)
+    (path / "plate_1.gcode").write_text(";" * size)
+    return str(path)
+
+
+class TestGcodeCache:
+    """Tests for GcodeCache."""
+
+    def test_store_and_get_by_content_key(self, tmp_path):
+        """Test stored G-code is found by key, and a changed profile setting changes the key."""
+        model = tmp_path / "cube.stl"
+        model.write_text("solid cube\nendsolid cube\n")
+        cache = create_gcode_cache(str(tmp_path / "cache"))
+        key = cache.key(str(model), *_profiles(tmp_path / "a"))
+
+        stored = cache.store(key, _output_dir(tmp_path / "out"))
+
+        assert cache.get(key) == stored
+        assert open(stored[0]).read() == ";" * 100
+        assert cache.key(str(model), *_profiles(tmp_path / "b")) == key
+        assert cache.key(str(model), *_profiles(tmp_path / "c", layer_height="0.1")) != key
+        assert cache.get("0" * 64) is None
+        assert cache.get("../escape") is None
+
+    def test_least_recently_used_entry_evicted(self, tmp_path):
+        """Test going over the byte budget removes the entry used longest ago."""
+        cache = create_gcode_cache(str(tmp_path / "cache"), max_bytes=250)
+        first, second, third = ("1" * 64, "2" * 64, "3" * 64)
+        cache.store(first, _output_dir(tmp_path / "one"))
+        cache.store(second, _output_dir(tmp_path / "two"))
+        # Make "first" the most recent use so "second" is the eviction candidate.
+        past = time.time() - 60
+        os.utime(tmp_path / "cache" / second, (past, past))
+
+        assert cache.get(first) is not None
+        cache.store(third, _output_dir(tmp_path / "three"))
+
+        assert cache.get(second) is None
+        assert cache.get(first) is not None
+        assert cache.get(third) is not None
+        assert cache.size_bytes() == 200
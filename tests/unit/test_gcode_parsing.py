@@ -0,0 +1,48 @@
+"""Unit tests for G-code metadata parsing.
+
+Focus: Test the chunked scanner reads the whole file, including footers and lines split across chunks.
+"""
+
+import asyncio
+
+from orca_quote_machine._rust_core import parse_slicer_output
+
+
+def _parse(output_dir):
+    async def parse():
+        return await parse_slicer_output(output_dir)
+
+    return asyncio.run(parse())
+
+
+class TestParseSlicerOutput:
+    """Tests for parse_slicer_output."""
+
+    def test_footer_totals_after_the_moves_are_read(self, tmp_path):
+        """Test the totals OrcaSlicer writes after the last move override the header."""
+        moves = "".join(f";TYPE:Outer wall\nG1 X{i} Y{i} E0.1\n" for i in range(50_000))
+        (tmp_path / "plate_1.gcode").write_text(
+            "; HEADER_BLOCK_START\n; total layer number: 12\n; HEADER_BLOCK_END\n"
+            + moves
+            + "; filament used: 3.68g\n"
+            + "; total layers count = 250\n"
+            + "; estimated printing time (normal mode) = 1h 2m 3s\n"
+        )
+
+        result = _parse(str(tmp_path))
+
+        assert result.print_time_minutes == 62
+        assert abs(result.filament_weight_grams - 3.68) < 0.001
+        assert result.layer_count == 250
+
+    def test_lines_split_across_chunks_and_crlf(self, tmp_path):
+        """Test a comment straddling the 256 KiB read boundary and CRLF endings parse intact."""
+        padding = "G1 X1\r\n" * ((256 * 1024 - 10) // 7)
+        (tmp_path / "plate_1.gcode").write_bytes(
+            (padding + "; filament used: 42.5g\r\n; estimated printing time: 3h 0m\r\n").encode()
+        )
+
+        result = _parse(str(tmp_path))
+
+        assert result.print_time_minutes == 180
+        assert result.filament_weight_grams == 42.5
@@ -0,0 +1,69 @@
+"""Unit tests for the dependency health check.
+
+Focus: Test each dependency is reported ok, skipped, or failed with a readable detail.
+"""
+
+import asyncio
+import json
+import stat
+
+from orca_quote_machine._rust_core import create_pipeline_config, health_check
+
+
+def _profiles(root):
+    """Minimal profile tree: one machine, one process, one filament."""
+    for profile_type in ("machine", "process", "filament"):
+        (root / profile_type).mkdir(parents=True)
+    (root / "machine" / "printer.json").write_text(json.dumps({"type": "machine"}))
+    (root / "process" / "standard.json").write_text(json.dumps({"type": "process"}))
+    (root / "filament" / "pla.json").write_text(json.dumps({"type": "filament"}))
+    return str(root)
+
+
+def _run(config, upload_dir, **kwargs):
+    async def check():
+        return await health_check(config, upload_dir, **kwargs)
+
+    return asyncio.run(check())
+
+
+class TestHealthCheck:
+    """Tests for health_check."""
+
+    def test_all_dependencies_healthy(self, tmp_path):
+        """Test a runnable slicer, parsable profiles and writable uploads are ok; no bot is skipped."""
+        slicer = tmp_path / "slicer.sh"
+        slicer.write_text("#!/bin/sh\necho 'Usage: orca-slicer'\n")
+        slicer.chmod(slicer.stat().st_mode | stat.S_IEXEC)
+        config = create_pipeline_config(
+            str(slicer), _profiles(tmp_path / "profiles"), "printer.json", "standard.json"
+        )
+        uploads = tmp_path / "uploads"
+        uploads.mkdir()
+
+        report = _run(config, str(uploads))
+
+        statuses = {check.name: check.status for check in report.checks}
+        assert statuses == {"slicer": "ok", "profiles": "ok", "storage": "ok", "telegram": "skipped"}
+        assert report.healthy
+        assert list(uploads.iterdir()) == []
+
+    def test_failures_are_reported_without_leaking_the_token(self, tmp_path):
+        """Test a missing slicer, profile, upload dir and unreachable bot API each report an error."""
+        config = create_pipeline_config(
+            str(tmp_path / "no-slicer"), str(tmp_path / "profiles"), "printer.json", "standard.json"
+        )
+
+        report = _run(
+            config,
+            str(tmp_path / "missing"),
+            telegram_bot_token="123:secret-token",
+            telegram_api="http://127.0.0.1:9",
+            timeout_secs=2.0,
+        )
+
+        checks = {check.name: check for check in report.checks}
+        assert not report.healthy
+        assert all(check.status == "error" for check in checks.values())
+        assert "could not start" in checks["slicer"].detail
+        assert "secret-token" not in checks["telegram"].detail
@@ -0,0 +1,72 @@
+"""Unit tests for material stock levels.
+
+Focus: Test out-of-stock materials are flagged and refused with a clear error.
+"""
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    create_inventory,
+    discover_available_materials,
+    load_material_catalog,
+)
+
+STOCK = """
+[[stock]]
+material = "PETG"
+remaining_grams = 0
+
+[[stock]]
+material = "pla+"
+color = "Black"
+remaining_grams = 150
+
+[[stock]]
+material = "PLA"
+color = "White"
+remaining_grams = 900
+"""
+
+
+@pytest.fixture
+def inventory(tmp_path):
+    path = tmp_path / "stock.toml"
+    path.write_text(STOCK)
+    return create_inventory(table_path=str(path), low_stock_grams=200.0)
+
+
+class TestInventory:
+    """Tests for Inventory and discover_available_materials."""
+
+    def test_discover_flags_out_of_stock_and_low_stock(self, inventory):
+        """Test each material carries its stock; untracked ones count as in stock."""
+        catalog = load_material_catalog()
+
+        materials = {
+            m.name: m
+            for m in discover_available_materials(catalog, ["TPU"], inventory=inventory)
+        }
+
+        assert sorted(materials) == ["ASA", "PETG", "PLA", "TPU"]
+        assert not materials["PETG"].in_stock
+        assert materials["PLA"].remaining_grams == 1050.0
+        assert materials["PLA"].colors_in_stock == ["Black", "White"]
+        assert not materials["PLA"].low_stock
+        assert materials["ASA"].in_stock
+        assert materials["ASA"].remaining_grams is None
+
+    def test_check_refuses_out_of_stock_material(self, inventory):
+        """Test the check names the material and how much is missing."""
+        catalog = load_material_catalog()
+
+        with pytest.raises(ValueError, match="PETG out of stock"):
+            inventory.check(catalog, "petg")
+        with pytest.raises(ValueError, match="Not enough PLA Black in stock: 400 g needed, 150 g left"):
+            inventory.check(catalog, "PLA", color="Black", grams=400.0)
+        inventory.check(catalog, "PLA", color="White", grams=400.0)
+        inventory.check(catalog, "ASA", grams=5000.0)
+
+    def test_create_inventory_needs_a_source(self):
+        """Test an inventory without a table or Spoolman is refused."""
+        with pytest.raises(ValueError, match="table_path or a spoolman_url"):
+            create_inventory()
@@ -0,0 +1,137 @@
+"""Unit tests for the quote ledger.
+
+Focus: Test rows booked to the CSV ledger and to Google Sheets.
+"""
+
+import base64
+import csv
+import json
+import shutil
+import subprocess
+import threading
+from datetime import datetime, timezone
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from urllib.parse import parse_qs, unquote, urlparse
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    append_quote_to_ledger,
+    calculate_quote_rust,
+    create_csv_ledger,
+    create_sheets_ledger,
+)
+
+
+def _b64url(segment):
+    return base64.urlsafe_b64decode(segment + "=" * (-len(segment) % 4))
+
+
+@pytest.fixture
+def google():
+    """Fake Google token endpoint and Sheets API over an empty sheet."""
+    class Handler(BaseHTTPRequestHandler):
+        def _answer(self, payload):
+            body = json.dumps(payload).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def do_GET(self):
+            server.requests.append(("GET", self.path, self.headers["Authorization"], None))
+            self._answer({"range": "Quotes!A1"})
+
+        def do_POST(self):
+            body = self.rfile.read(int(self.headers["Content-Length"])).decode()
+            server.requests.append(("POST", self.path, self.headers["Authorization"], body))
+            if self.path == "/token":
+                self._answer({"access_token": "ya29.test", "expires_in": 3600})
+            else:
+                self._answer({"updates": {"updatedRange": "Quotes!A1:L2"}})
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    server.requests = []
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield server
+    server.shutdown()
+
+
+class TestCsvLedger:
+    """Tests for the CSV ledger."""
+
+    def test_rows_appended_to_monthly_file(self, tmp_path):
+        """Test the header is written once and text cells cannot become formulas."""
+        ledger = create_csv_ledger(str(tmp_path / "ledger"))
+        cost = calculate_quote_rust(90, 25.0, "PLA", 25.0, 0.5, 1.1, 5.0)
+
+        first = append_quote_to_ledger(ledger, "q-1", cost, customer_name="=HYPERLINK(1)")
+        second = append_quote_to_ledger(ledger, "q-2", cost, filename="part, v2.stl")
+
+        month = datetime.now(timezone.utc).strftime("%Y-%m")
+        assert first == second and first.endswith(f"quotes-{month}.csv")
+        rows = list(csv.DictReader(open(first, newline="")))
+        assert [row["quote_id"] for row in rows] == ["q-1", "q-2"]
+        assert rows[0]["customer"] == "'=HYPERLINK(1)"
+        assert rows[1]["file"] == "part, v2.stl"
+        assert float(rows[0]["total_cost"]) == round(cost.total_cost, 2)
+
+    def test_unknown_rotation_rejected(self, tmp_path):
+        """Test an unknown rotation raises ValueError."""
+        with pytest.raises(ValueError, match="rotation"):
+            create_csv_ledger(str(tmp_path), rotation="hourly")
+
+
+@pytest.mark.skipif(shutil.which("openssl") is None, reason="needs openssl to make a test key")
+class TestSheetsLedger:
+    """Tests for the Google Sheets ledger."""
+
+    def test_signed_token_and_append(self, tmp_path, google):
+        """Test the JWT is signed with the account key and the row is appended."""
+        key = tmp_path / "key.pem"
+        subprocess.run(
+            ["openssl", "genpkey", "-algorithm", "RSA", "-pkeyopt", "rsa_keygen_bits:2048",
+             "-out", str(key)],
+            check=True, capture_output=True,
+        )
+        base = f"http://127.0.0.1:{google.server_port}"
+        account = tmp_path / "account.json"
+        account.write_text(json.dumps({
+            "client_email": "ledger-test@example.iam.gserviceaccount.com",
+            "private_key": key.read_text(),
+            "token_uri": f"{base}/token",
+        }))
+        ledger = create_sheets_ledger("sheet-123", str(account), api_base=base)
+        cost = calculate_quote_rust(60, 10.0, "PETG", 30.0, 0.5, 1.1, 5.0)
+
+        written = append_quote_to_ledger(ledger, "q-7", cost, customer_name="Ada")
+
+        assert written == "Quotes!A1:L2"
+        method, path, _, form = google.requests[0]
+        assertion = parse_qs(form)["assertion"][0]
+        header, claims, signature = assertion.split(".")
+        assert json.loads(_b64url(header))["alg"] == "RS256"
+        assert json.loads(_b64url(claims))["aud"] == f"{base}/token"
+        public = tmp_path / "public.pem"
+        sig = tmp_path / "sig.bin"
+        sig.write_bytes(_b64url(signature))
+        subprocess.run(["openssl", "pkey", "-in", str(key), "-pubout", "-out", str(public)],
+                       check=True, capture_output=True)
+        verify = subprocess.run(
+            ["openssl", "dgst", "-sha256", "-verify", str(public), "-signature", str(sig)],
+            input=f"{header}.{claims}".encode(), capture_output=True,
+        )
+        assert verify.returncode == 0
+
+        method, path, auth, body = google.requests[-1]
+        url = urlparse(path)
+        assert unquote(url.path) == "/v4/spreadsheets/sheet-123/values/Quotes!A1:append"
+        assert parse_qs(url.query)["valueInputOption"] == ["USER_ENTERED"]
+        assert auth == "Bearer ya29.test"
+        header_row, row = json.loads(body)["values"]
+        assert header_row[1] == "quote_id" and row[1:4] == ["q-7", "Ada", ""]
@@ -8,7 +8,7 @@ from unittest.mock import MagicMock, patch
 import pytest
 from fastapi.testclient import TestClient
 
-from orca_quote_machine._rust_core import secure_filename
+from orca_quote_machine._rust_core import create_rate_limiter, secure_filename
 from orca_quote_machine.main import app
 
 
@@ -125,6 +125,23 @@ class TestQuoteEndpointLogic:
                 assert ".." not in response.json()["filename"]
 
 
+class TestQuoteRateLimit:
+    """Test quote requests are throttled per mobile number."""
+
+    def test_quote_refused_once_limit_used(self):
+        """Test a number with no tokens left gets 429 and a Retry-After header."""
+        limiter = create_rate_limiter(1, 1 / 3600)
+        assert limiter.acquire("+1234567890")
+        files = {"model_file": ("cube.stl", b"solid cube\nendsolid cube\n", "application/octet-stream")}
+        data = {"name": "Test User", "mobile": "+123 456 7890", "material": "PLA"}
+
+        with patch("orca_quote_machine.main.quote_rate_limiter", limiter):
+            response = TestClient(app).post("/quote", files=files, data=data)
+
+        assert response.status_code == 429
+        assert 3500 < int(response.headers["Retry-After"]) <= 3600
+
+
 class TestHomeEndpointLogic:
     """Test the home endpoint template data logic."""
 
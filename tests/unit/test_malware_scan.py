@@ -0,0 +1,110 @@
+"""Unit tests for malware scanning of uploads.
+
+Focus: Test scan commands and clamd's INSTREAM protocol pass clean uploads and raise on infected or unscanned ones.
+"""
+
+import socket
+import struct
+import threading
+
+import pytest
+
+from orca_quote_machine._rust_core import create_malware_scanner, scan_upload
+
+EICAR_REPORT = "{path}: Win.Test.EICAR_HDB-1 FOUND"
+
+
+def fake_clamd(path, reply):
+    """Serve one INSTREAM request on a Unix socket; returns the bytes streamed."""
+    server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+    server.bind(str(path))
+    server.listen(1)
+    received = bytearray()
+
+    def serve():
+        conn, _ = server.accept()
+        with conn, conn.makefile("rb") as stream:
+            assert stream.read(10) == b"zINSTREAM\0"
+            while size := struct.unpack(">I", stream.read(4))[0]:
+                received.extend(stream.read(size))
+            conn.sendall(reply)
+        server.close()
+
+    thread = threading.Thread(target=serve, daemon=True)
+    thread.start()
+    return received, thread
+
+
+class TestScanCommand:
+    """Tests for scanners run as a command."""
+
+    def test_clean_and_infected_uploads(self, tmp_path):
+        """Test exit 0 passes, and exit 1 raises with the signature it reported."""
+        upload = tmp_path / "part.stl"
+        upload.write_text("solid part\nendsolid part\n")
+        infected = create_malware_scanner(
+            command=["sh", "-c", f'echo "{EICAR_REPORT.format(path="$0")}"; exit 1']
+        )
+
+        scan_upload(str(upload), create_malware_scanner(command=["true"]))
+        with pytest.raises(ValueError, match="part.stl is infected: Win.Test.EICAR_HDB-1"):
+            scan_upload(str(upload), infected)
+
+    def test_scanner_failures_raise_os_error(self, tmp_path):
+        """Test a scanner that errors, hangs or is missing leaves the upload unscanned."""
+        upload = tmp_path / "part.stl"
+        upload.write_text("solid part\nendsolid part\n")
+
+        with pytest.raises(OSError, match="Malware scan failed: sh: exited with"):
+            scan_upload(str(upload), create_malware_scanner(command=["sh", "-c", "exit 2"]))
+        with pytest.raises(OSError, match="did not finish"):
+            scan_upload(
+                str(upload),
+                create_malware_scanner(command=["sh", "-c", "sleep 5"], timeout_secs=0.2),
+            )
+        with pytest.raises(OSError, match="could not start"):
+            scan_upload(str(upload), create_malware_scanner(command=["/nonexistent/clamscan"]))
+
+    def test_config_validated(self):
+        """Test exactly one of a command or socket, and a positive timeout, is required."""
+        with pytest.raises(ValueError, match="either a command or a clamd socket"):
+            create_malware_scanner()
+        with pytest.raises(ValueError, match="either a command or a clamd socket"):
+            create_malware_scanner(command=["clamdscan"], clamd_socket="/run/clamd.ctl")
+        with pytest.raises(ValueError, match="timeout_secs"):
+            create_malware_scanner(command=["clamdscan"], timeout_secs=0)
+
+
+class TestClamdSocket:
+    """Tests for scanning through a clamd socket."""
+
+    def test_upload_streamed_and_clean(self, tmp_path):
+        """Test the whole upload is streamed in chunks and an OK reply passes."""
+        upload = tmp_path / "scan.stl"
+        upload.write_bytes(bytes(range(256)) * 1024)
+        sock = tmp_path / "clamd.sock"
+        received, thread = fake_clamd(sock, b"stream: OK\0")
+
+        scan_upload(str(upload), create_malware_scanner(clamd_socket=str(sock)))
+
+        thread.join(timeout=5)
+        assert bytes(received) == upload.read_bytes()
+
+    def test_found_and_error_replies(self, tmp_path):
+        """Test a FOUND reply raises ValueError and an ERROR reply or no clamd OSError."""
+        upload = tmp_path / "part.stl"
+        upload.write_text("solid part\nendsolid part\n")
+        found = tmp_path / "found.sock"
+        fake_clamd(found, b"stream: Eicar-Signature FOUND\0")
+        limited = tmp_path / "limited.sock"
+        fake_clamd(limited, b"INSTREAM size limit exceeded. ERROR\0")
+
+        with pytest.raises(ValueError, match="infected: Eicar-Signature"):
+            scan_upload(str(upload), create_malware_scanner(clamd_socket=str(found)))
+        with pytest.raises(OSError, match="size limit exceeded"):
+            scan_upload(str(upload), create_malware_scanner(clamd_socket=str(limited)))
+        with pytest.raises(OSError, match="Malware scan failed: clamd at"):
+            scan_upload(
+                str(upload),
+                create_malware_scanner(clamd_socket=str(tmp_path / "missing.sock")),
+            )
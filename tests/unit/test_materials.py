@@ -0,0 +1,44 @@
+"""Unit tests for the material catalog.
+
+Focus: Test alias lookup, catalog files, and weight conversions.
+"""
+
+import pytest
+
+from orca_quote_machine._rust_core import MaterialCatalog, load_material_catalog
+
+
+class TestMaterialCatalog:
+    """Tests for MaterialCatalog lookups."""
+
+    def test_builtin_catalog_resolves_aliases(self):
+        """Test that aliases map to their canonical material and price."""
+        catalog = load_material_catalog()
+
+        assert isinstance(catalog, MaterialCatalog)
+        assert catalog.names() == ["PLA", "PETG", "ASA"]
+        assert catalog.canonical_name("pla+") == "PLA"
+        assert catalog.canonical_name("PLA-CF") == "PLA"
+        assert catalog.canonical_name("tpu") == "TPU"
+        assert catalog.price_per_kg("PETG", 99.0) == 30.0
+        assert catalog.price_per_kg("TPU", 99.0) == 99.0
+
+    def test_load_catalog_file_and_weights(self, tmp_path):
+        """Test catalog files and the length/volume to grams conversions."""
+        path = tmp_path / "materials.toml"
+        path.write_text(
+            '[[material]]\n'
+            'name = "TPU"\n'
+            'aliases = ["TPU 95A"]\n'
+            'density = 1.2\n'
+            'price_per_kg = 45.0\n'
+        )
+
+        material = load_material_catalog(str(path)).get("tpu 95a")
+
+        assert material.name == "TPU"
+        assert material.display_name == "TPU"
+        assert material.diameter == 1.75
+        assert material.grams_from_volume(10.0) == pytest.approx(12.0)
+        # 1 m of 1.75 mm filament is ~2.405 cm³
+        assert material.grams_from_length(1000.0) == pytest.approx(2.886, rel=1e-3)
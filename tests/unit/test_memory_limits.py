@@ -0,0 +1,60 @@
+"""Unit tests for memory budgets.
+
+Focus: Test oversized input fails with MemoryError under a budget and is unaffected without one.
+"""
+
+import asyncio
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    parse_slicer_output,
+    set_memory_limits,
+    validate_3d_model,
+)
+
+
+@pytest.fixture
+def budgets():
+    """Reset both budgets after each test; they are process-wide."""
+    yield set_memory_limits
+    set_memory_limits()
+
+
+def _parse(output_dir):
+    async def parse():
+        return await parse_slicer_output(output_dir)
+
+    return asyncio.run(parse())
+
+
+class TestMemoryLimits:
+    """Tests for set_memory_limits."""
+
+    def test_mesh_line_over_budget_raises_memory_error(self, tmp_path, budgets):
+        """Test a single huge OBJ line exceeds the mesh budget while a normal model passes."""
+        huge = tmp_path / "huge.obj"
+        huge.write_text("# " + "x" * 200_000 + "\nv 0 0 0\nf 1 1 1\n")
+        normal = tmp_path / "normal.obj"
+        normal.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+
+        budgets(mesh_analysis_bytes=64 * 1024)
+
+        with pytest.raises(MemoryError, match="mesh analysis exceeded its memory budget"):
+            validate_3d_model(str(huge))
+        assert validate_3d_model(str(normal)).is_valid
+        budgets()
+        assert validate_3d_model(str(huge)).is_valid
+
+    def test_gcode_line_over_budget_raises_memory_error(self, tmp_path, budgets):
+        """Test the G-code parser stops at an oversized header line instead of buffering it."""
+        (tmp_path / "plate_1.gcode").write_text(
+            "; estimated printing time = 1h 30m\n;" + "G1 X1 " * 50_000 + "\n"
+        )
+
+        budgets(gcode_parse_bytes=64 * 1024)
+
+        with pytest.raises(MemoryError, match="G-code parsing"):
+            _parse(str(tmp_path))
+        budgets()
+        assert _parse(str(tmp_path)).print_time_minutes == 90
@@ -0,0 +1,126 @@
+"""Unit tests for mesh integrity checks, repair and statistics.
+
+Focus: Test that holes, non-manifold edges, flipped normals and duplicate
+faces are found once coincident corners are welded, that repair fixes them,
+and that mesh statistics count vertices and shells after welding.
+"""
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    check_mesh_integrity,
+    mesh_stats,
+    repair_mesh,
+    validate_3d_model,
+)
+
+# A closed tetrahedron, every face wound outwards.
+CORNERS = [(0, 0, 0), (10, 0, 0), (0, 10, 0), (0, 0, 10)]
+FACES = [(0, 2, 1), (0, 1, 3), (1, 2, 3), (0, 3, 2)]
+
+
+def _write_stl(path, faces, corners=CORNERS) -> str:
+    lines = ["solid part"]
+    for face in faces:
+        lines += ["  facet normal 0 0 0", "    outer loop"]
+        lines += [f"      vertex {x} {y} {z}" for x, y, z in (corners[i] for i in face)]
+        lines += ["    endloop", "  endfacet"]
+    lines.append("endsolid part")
+    path.write_text("\n".join(lines) + "\n")
+    return str(path)
+
+
+class TestCheckMeshIntegrity:
+    """Tests for check_mesh_integrity."""
+
+    def test_closed_mesh_is_watertight(self, tmp_path):
+        """Test a closed, consistently wound solid has no problems."""
+        integrity = check_mesh_integrity(_write_stl(tmp_path / "tetra.stl", FACES))
+
+        assert integrity.is_watertight
+        assert integrity.triangles == 4
+        assert integrity.vertices == 4
+        assert integrity.problems == []
+
+    def test_missing_face_leaves_a_hole(self, tmp_path):
+        """Test an open mesh reports one hole bordered by three open edges."""
+        integrity = check_mesh_integrity(_write_stl(tmp_path / "open.stl", FACES[:3]))
+
+        assert not integrity.is_watertight
+        assert integrity.holes == 1
+        assert integrity.boundary_edges == 3
+        assert integrity.problems == ["1 hole (3 open edges)"]
+
+    def test_flipped_and_duplicate_faces_reported(self, tmp_path):
+        """Test a reversed face and a repeated face are counted but still watertight."""
+        faces = [FACES[0], FACES[1], FACES[2], FACES[3][::-1], FACES[1]]
+        integrity = check_mesh_integrity(_write_stl(tmp_path / "messy.stl", faces))
+
+        assert integrity.is_watertight
+        assert integrity.flipped_faces == 1
+        assert integrity.duplicate_faces == 1
+
+    def test_step_has_no_mesh(self, tmp_path):
+        """Test a STEP file, which has no triangles, is a ValueError."""
+        step = tmp_path / "part.step"
+        step.write_text("ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\nENDSEC;\nEND-ISO-10303-21;\n")
+
+        with pytest.raises(ValueError, match="no mesh to check"):
+            check_mesh_integrity(str(step))
+
+
+class TestRepairMesh:
+    """Tests for repair_mesh."""
+
+    def test_open_messy_mesh_made_watertight(self, tmp_path):
+        """Test a flipped face, a duplicate, a degenerate face and a hole are all repaired."""
+        faces = [FACES[0], FACES[1][::-1], FACES[2], FACES[2], (0, 0, 1)]
+        output = tmp_path / "repaired.stl"
+
+        repair = repair_mesh(_write_stl(tmp_path / "messy.stl", faces), str(output))
+
+        assert repair.faces_flipped == 1
+        assert repair.duplicates_removed == 1
+        assert repair.degenerate_removed == 1
+        assert repair.holes_filled == 1
+        assert repair.triangles_after == 4
+        assert repair.integrity.is_watertight
+        assert repair.integrity.problems == []
+        assert check_mesh_integrity(str(output)).is_watertight
+        assert validate_3d_model(str(output)).dimensions_mm == (10.0, 10.0, 10.0)
+
+    def test_large_holes_left_open(self, tmp_path):
+        """Test a hole bordered by more than max_hole_edges edges is not filled."""
+        output = tmp_path / "repaired.stl"
+
+        repair = repair_mesh(
+            _write_stl(tmp_path / "open.stl", FACES[:3]), str(output), max_hole_edges=2
+        )
+
+        assert repair.holes_filled == 0
+        assert repair.integrity.holes == 1
+
+
+class TestMeshStats:
+    """Tests for mesh_stats."""
+
+    def test_shells_and_degenerate_triangles_counted(self, tmp_path):
+        """Test two separate tetrahedra and a collapsed face give two shells and one degenerate."""
+        corners = CORNERS + [(x + 20, y, z) for x, y, z in CORNERS]
+        faces = FACES + [tuple(i + 4 for i in face) for face in FACES] + [(0, 0, 1)]
+
+        stats = mesh_stats(_write_stl(tmp_path / "pair.stl", faces, corners))
+
+        assert stats.triangles == 9
+        assert stats.vertices == 8
+        assert stats.degenerate_triangles == 1
+        assert stats.duplicate_vertices == 27 - 8
+        assert stats.shells == 2
+
+    def test_step_has_no_mesh(self, tmp_path):
+        """Test a STEP file raises ValueError rather than reporting an empty mesh."""
+        step = tmp_path / "part.step"
+        step.write_text("ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\nENDSEC;\nEND-ISO-10303-21;\n")
+
+        with pytest.raises(ValueError, match="no mesh to measure"):
+            mesh_stats(str(step))
@@ -0,0 +1,53 @@
+"""Unit tests for the metrics exporter.
+
+Focus: Test that recorded quotes show up in the Prometheus text format only while enabled.
+"""
+
+import urllib.request
+
+from orca_quote_machine._rust_core import (
+    enable_metrics,
+    gather_metrics,
+    record_quote_metric,
+    serve_metrics,
+)
+
+
+def _sample(text: str, prefix: str) -> float:
+    """Value of the first exposition line starting with prefix, or 0 when absent."""
+    for line in text.splitlines():
+        if line.startswith(prefix + " "):
+            return float(line.rsplit(" ", 1)[1])
+    return 0.0
+
+
+class TestMetrics:
+    """Tests for gather_metrics and record_quote_metric."""
+
+    def test_recorded_quote_appears_in_exposition(self):
+        """Test quote counters and histograms are exported once metrics are enabled."""
+        enable_metrics()
+        try:
+            before = gather_metrics()
+            record_quote_metric("TPU", "success", slice_seconds=42.0, file_size_bytes=2_000_000)
+            after = gather_metrics()
+        finally:
+            enable_metrics(False)
+
+        counter = 'orca_quotes_total{material="TPU",outcome="success"}'
+        assert _sample(after, counter) == _sample(before, counter) + 1
+        bucket = 'orca_slice_duration_seconds_bucket{le="60"}'
+        assert _sample(after, bucket) == _sample(before, bucket) + 1
+        assert "# TYPE orca_model_file_size_bytes histogram" in after
+        assert "# TYPE orca_queue_depth gauge" in after
+
+    def test_disabled_metrics_drop_samples_and_serve_over_http(self):
+        """Test samples are ignored while disabled and the scrape server returns the text format."""
+        enable_metrics(False)
+        record_quote_metric("NYLON", "error")
+        assert 'material="NYLON"' not in gather_metrics()
+
+        port = serve_metrics("127.0.0.1:0")
+        with urllib.request.urlopen(f"http://127.0.0.1:{port}/metrics", timeout=5) as response:
+            body = response.read().decode()
+        assert "# TYPE orca_quotes_total counter" in body
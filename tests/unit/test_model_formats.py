@@ -0,0 +1,390 @@
+"""Unit tests for model formats beyond STL, OBJ and STEP.
+
+Focus: Test each format's container and structure checks, and that valid files
+pass through validate_3d_model.
+"""
+
+import gzip
+import struct
+import zipfile
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    extract_archived_model,
+    set_unpack_limit,
+    validate_3d_model,
+    validate_3mf,
+    validate_amf,
+    validate_ply,
+)
+
+CONTENT_TYPES = (
+    '<?xml version="1.0" encoding="UTF-8"?>\n'
+    '<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">'
+    '<Default Extension="rels" '
+    'ContentType="application/vnd.openxmlformats-package.relationships+xml"/>'
+    '<Default Extension="model" '
+    'ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>'
+    "</Types>"
+)
+RELS = (
+    '<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">'
+    '<Relationship Target="/3D/3dmodel.model" Id="rel0" '
+    'Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/>'
+    "</Relationships>"
+)
+AMF = """<?xml version="1.0" encoding="UTF-8"?>
+<amf unit="{unit}">
+  <object id="0">
+    <mesh>
+      <vertices>
+        <vertex><coordinates><x>0</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>20</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>0</x><y>20</y><z>10</z></coordinates></vertex>
+      </vertices>
+      <volume>
+        <triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle>
+      </volume>
+    </mesh>
+  </object>
+</amf>
+"""
+MODEL = """<?xml version="1.0" encoding="UTF-8"?>
+<model unit="{unit}" xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02">
+  <resources>
+    <object id="1" type="model">
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0"/>
+          <vertex x="20" y="0" z="0"/>
+          <vertex x="0" y="20" z="0"/>
+          <vertex x="0" y="0" z="10"/>
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="2" v3="1"/>
+          <triangle v1="0" v2="1" v3="3"/>
+          <triangle v1="0" v2="3" v3="2"/>
+          <triangle v1="1" v2="2" v3="3"/>
+        </triangles>
+      </mesh>
+    </object>
+  </resources>
+  <build>{items}</build>
+</model>
+"""
+
+
+def write_3mf(path, unit="millimeter", items='<item objectid="1"/>', content_types=CONTENT_TYPES):
+    with zipfile.ZipFile(path, "w") as archive:
+        if content_types is not None:
+            archive.writestr("[Content_Types].xml", content_types)
+        archive.writestr("_rels/.rels", RELS)
+        archive.writestr("3D/3dmodel.model", MODEL.format(unit=unit, items=items))
+    return str(path)
+
+
+class TestValidate3mf:
+    """Tests for validate_3mf."""
+
+    def test_valid_package(self, tmp_path):
+        """Test a package with content types, a model part and a build item passes."""
+        path = write_3mf(tmp_path / "part.3mf")
+
+        info = validate_3mf(path)
+
+        assert info.is_valid, info.error_message
+        assert info.file_type == "3mf"
+        assert validate_3d_model(path).is_valid
+
+    def test_broken_packages_rejected(self, tmp_path):
+        """Test each missing piece is named in the error."""
+        not_zip = tmp_path / "plain.3mf"
+        not_zip.write_text("solid cube\nendsolid cube\n")
+
+        cases = {
+            str(not_zip): "not a ZIP archive",
+            write_3mf(tmp_path / "a.3mf", content_types=None): "missing [Content_Types].xml",
+            write_3mf(tmp_path / "b.3mf", items=""): "no build items",
+            write_3mf(tmp_path / "c.3mf", unit="furlong"): "unknown unit 'furlong'",
+        }
+
+        for path, message in cases.items():
+            info = validate_3mf(path)
+            assert not info.is_valid
+            assert info.error_message == f"Invalid 3MF - {message}"
+
+    def test_model_part_found_through_relationships(self, tmp_path):
+        """Test a model part stored elsewhere is found via _rels/.rels."""
+        path = tmp_path / "moved.3mf"
+        with zipfile.ZipFile(path, "w") as archive:
+            archive.writestr("[Content_Types].xml", CONTENT_TYPES)
+            archive.writestr("_rels/.rels", RELS.replace("/3D/3dmodel.model", "/3D/part.model"))
+            archive.writestr(
+                "3D/part.model", MODEL.format(unit="millimeter", items='<item objectid="1"/>')
+            )
+
+        assert validate_3mf(str(path)).is_valid
+
+    def test_triangle_with_missing_vertex_rejected(self, tmp_path):
+        """Test a triangle indexing past its object's vertices fails validation and names the index."""
+        path = tmp_path / "dangling.3mf"
+        model = MODEL.format(unit="millimeter", items='<item objectid="1"/>')
+        with zipfile.ZipFile(path, "w") as archive:
+            archive.writestr("[Content_Types].xml", CONTENT_TYPES)
+            archive.writestr("_rels/.rels", RELS)
+            archive.writestr("3D/3dmodel.model", model.replace('v3="3"/>', 'v3="99"/>', 1))
+
+        info = validate_3mf(str(path))
+
+        assert not info.is_valid
+        assert info.error_message == "Invalid 3MF - triangle references missing vertex 99"
+
+
+class TestValidateAmf:
+    """Tests for validate_amf."""
+
+    def test_plain_and_compressed_documents(self, tmp_path):
+        """Test plain XML, gzip and ZIP compressed AMF all pass."""
+        document = AMF.format(unit="millimeter").encode()
+        plain = tmp_path / "plain.amf"
+        plain.write_bytes(document)
+        gzipped = tmp_path / "gzipped.amf"
+        gzipped.write_bytes(gzip.compress(document))
+        zipped = tmp_path / "zipped.amf"
+        with zipfile.ZipFile(zipped, "w", zipfile.ZIP_DEFLATED) as archive:
+            archive.writestr("zipped.amf", document)
+
+        for path in (plain, gzipped, zipped):
+            info = validate_amf(str(path))
+            assert info.is_valid, info.error_message
+            assert info.file_type == "amf"
+        assert validate_3d_model(str(gzipped)).is_valid
+
+    def test_broken_documents_rejected(self, tmp_path):
+        """Test the root, unit and object mesh checks."""
+        cases = {
+            "<model><object/></model>": "no <amf> root element",
+            AMF.format(unit="furlong"): "unknown unit 'furlong'",
+            '<amf unit="inch"><metadata type="name">empty</metadata></amf>': "no objects",
+            '<amf><object id="0"><mesh><vertices/></mesh></object></amf>': (
+                "no object with mesh vertices and triangles"
+            ),
+        }
+
+        for index, (document, message) in enumerate(cases.items()):
+            path = tmp_path / f"broken{index}.amf"
+            path.write_text(document)
+            info = validate_amf(str(path))
+            assert not info.is_valid
+            assert info.error_message == f"Invalid AMF - {message}"
+
+
+def ply_header(fmt, vertices=3, faces=1):
+    return (
+        f"ply\nformat {fmt} 1.0\ncomment scanned\n"
+        f"element vertex {vertices}\nproperty float x\nproperty float y\nproperty float z\n"
+        f"element face {faces}\nproperty list uchar int vertex_indices\nend_header\n"
+    ).encode()
+
+
+class TestValidatePly:
+    """Tests for validate_ply."""
+
+    def test_ascii_and_binary_meshes(self, tmp_path):
+        """Test ASCII and both binary byte orders pass."""
+        ascii_ply = tmp_path / "ascii.ply"
+        ascii_ply.write_bytes(ply_header("ascii") + b"0 0 0\n20 0 0\n0 20 10\n3 0 1 2\n")
+        paths = [ascii_ply]
+        for fmt, order in (("binary_little_endian", "<"), ("binary_big_endian", ">")):
+            body = struct.pack(f"{order}9f", 0, 0, 0, 20, 0, 0, 0, 20, 10)
+            body += struct.pack(f"{order}B3i", 3, 0, 1, 2)
+            path = tmp_path / f"{fmt}.ply"
+            path.write_bytes(ply_header(fmt) + body)
+            paths.append(path)
+
+        for path in paths:
+            info = validate_ply(str(path))
+            assert info.is_valid, info.error_message
+            assert info.file_type == "ply"
+        assert validate_3d_model(str(paths[1])).is_valid
+
+    def test_broken_meshes_rejected(self, tmp_path):
+        """Test the header checks, point clouds, and bodies short of the counts."""
+        cases = {
+            b"solid cube\n": "missing 'ply' magic line",
+            b"ply\nelement vertex 3\nend_header\n": "missing format line",
+            b"ply\nformat ascii 1.0\n": "header has no end_header line",
+            ply_header("ascii", faces=0) + b"0 0 0\n20 0 0\n0 20 10\n": (
+                "no faces; a point cloud cannot be printed"
+            ),
+            ply_header("binary_little_endian", vertices=1000, faces=500) + bytes(64): (
+                "truncated; the header declares 1000 vertices and 500 faces"
+            ),
+        }
+
+        for index, (data, message) in enumerate(cases.items()):
+            path = tmp_path / f"broken{index}.ply"
+            path.write_bytes(data)
+            info = validate_ply(str(path))
+            assert not info.is_valid
+            assert info.error_message == f"Invalid PLY - {message}"
+
+
+ASCII_STL = (
+    "solid cube\n  facet normal 0 0 1\n    outer loop\n"
+    "      vertex 0 0 0\n      vertex 20 0 0\n      vertex 0 20 10\n"
+    "    endloop\n  endfacet\nendsolid cube\n"
+)
+
+
+class TestValidateArchive:
+    """Tests for ZIP uploads through validate_3d_model."""
+
+    def test_first_model_entry_validated(self, tmp_path):
+        """Test folders, resource forks and other files are passed over."""
+        path = tmp_path / "upload.zip"
+        with zipfile.ZipFile(path, "w", zipfile.ZIP_DEFLATED) as archive:
+            archive.writestr("README.txt", "print in PETG")
+            archive.writestr("__MACOSX/parts/._cube.stl", "resource fork")
+            archive.writestr("parts/", "")
+            archive.writestr("parts/cube.stl", ASCII_STL)
+            archive.writestr("parts/spare.obj", "v 0 0 0\nf 1 1 1\n")
+
+        info = validate_3d_model(str(path))
+
+        assert info.is_valid, info.error_message
+        assert info.file_type == "stl"
+        assert info.archive_entry == "parts/cube.stl"
+
+    def test_archive_without_a_model_rejected(self, tmp_path):
+        """Test an archive holding no model, or one that escapes, is refused."""
+        empty = tmp_path / "notes.zip"
+        with zipfile.ZipFile(empty, "w") as archive:
+            archive.writestr("notes.txt", "nothing to print")
+            archive.writestr("../escape.stl", ASCII_STL)
+        not_zip = tmp_path / "broken.zip"
+        not_zip.write_text(ASCII_STL)
+
+        info = validate_3d_model(str(empty))
+        assert not info.is_valid
+        assert info.file_type == "zip"
+        assert info.error_message == "Invalid ZIP - no STL/OBJ/STEP/STP/3MF/AMF/PLY file inside"
+        assert validate_3d_model(str(not_zip)).error_message == "Invalid ZIP - not a ZIP archive"
+
+    def test_model_extracted_under_its_own_name(self, tmp_path):
+        """Test extraction keeps only the entry's file name, inside the target directory."""
+        path = tmp_path / "upload.zip"
+        with zipfile.ZipFile(path, "w") as archive:
+            archive.writestr("deep/folder/bracket.stl", ASCII_STL)
+        target = tmp_path / "unzipped"
+        target.mkdir()
+
+        entry, model = extract_archived_model(str(path), str(target))
+
+        assert entry == "deep/folder/bracket.stl"
+        assert model == str(target / "bracket.stl")
+        assert (target / "bracket.stl").read_text() == ASCII_STL
+
+
+class TestValidateGzip:
+    """Tests for gzip uploads through validate_3d_model."""
+
+    def test_compressed_meshes_validated_as_inner_file(self, tmp_path):
+        """Test part.stl.gz and part.obj.gz validate as the files they hold."""
+        stl = tmp_path / "part.stl.gz"
+        stl.write_bytes(gzip.compress(ASCII_STL.encode()))
+        obj = tmp_path / "part.obj.gz"
+        obj.write_bytes(gzip.compress(b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n"))
+
+        for path, file_type in ((stl, "stl"), (obj, "obj")):
+            info = validate_3d_model(str(path))
+            assert info.is_valid, info.error_message
+            assert info.file_type == file_type
+            assert info.archive_entry == f"part.{file_type}"
+
+    def test_bad_gzip_uploads_rejected(self, tmp_path):
+        """Test non-gzip data, unnamed contents, corrupt streams and the size cap."""
+        plain = tmp_path / "plain.stl.gz"
+        plain.write_text(ASCII_STL)
+        unnamed = tmp_path / "upload.gz"
+        unnamed.write_bytes(gzip.compress(ASCII_STL.encode()))
+        corrupt = tmp_path / "corrupt.stl.gz"
+        corrupt.write_bytes(gzip.compress(ASCII_STL.encode())[:40])
+        big = tmp_path / "big.stl.gz"
+        big.write_bytes(gzip.compress(ASCII_STL.encode() + b" " * (2 * 1024 * 1024)))
+
+        assert validate_3d_model(str(plain)).error_message == "Invalid gzip - not gzip data"
+        assert validate_3d_model(str(unnamed)).error_message == (
+            "Invalid gzip - the name does not say what is inside, e.g. part.stl.gz"
+        )
+        assert validate_3d_model(str(corrupt)).error_message.startswith(
+            "Invalid gzip - corrupt gzip data"
+        )
+        set_unpack_limit(1024 * 1024)
+        try:
+            info = validate_3d_model(str(big))
+        finally:
+            set_unpack_limit()
+        assert info.error_message == "Invalid gzip - big.stl is larger than 1 MB unpacked"
+
+
+class TestDecompressionBombs:
+    """Tests for archives built to exhaust memory or disk."""
+
+    def test_nested_archive_refused(self, tmp_path):
+        """Test a ZIP holding another archive is refused, even beside a model."""
+        path = tmp_path / "upload.zip"
+        with zipfile.ZipFile(path, "w") as archive:
+            archive.writestr("cube.stl", ASCII_STL)
+            archive.writestr("more/inner.zip", b"PK\x05\x06" + b"\0" * 18)
+        target = tmp_path / "unzipped"
+        target.mkdir()
+
+        info = validate_3d_model(str(path))
+        assert not info.is_valid
+        assert info.error_message == "Invalid ZIP - holds another archive, more/inner.zip"
+        with pytest.raises(ValueError, match="Suspicious archive: ZIP holds another archive"):
+            extract_archived_model(str(path), str(target))
+
+    def test_compression_ratio_limit(self, tmp_path):
+        """Test an entry inflating far past its compressed size is refused."""
+        padding = b" " * (9 * 1024 * 1024)
+        gz = tmp_path / "bomb.stl.gz"
+        gz.write_bytes(gzip.compress(ASCII_STL.encode() + padding))
+        zipped = tmp_path / "bomb.zip"
+        with zipfile.ZipFile(zipped, "w", zipfile.ZIP_DEFLATED) as archive:
+            archive.writestr("bomb.stl", ASCII_STL.encode() + padding)
+        target = tmp_path / "unzipped"
+        target.mkdir()
+
+        assert validate_3d_model(str(gz)).error_message == (
+            "Invalid gzip - bomb.stl inflates to over 100 times its compressed size"
+        )
+        assert validate_3d_model(str(zipped)).error_message == (
+            "Invalid ZIP - bomb.stl inflates to over 100 times its compressed size"
+        )
+        with pytest.raises(ValueError, match="Suspicious archive: gzip bomb.stl inflates"):
+            extract_archived_model(str(gz), str(target))
+        set_unpack_limit(max_ratio=100_000)
+        try:
+            assert validate_3d_model(str(gz)).is_valid
+        finally:
+            set_unpack_limit()
+
+    def test_3mf_part_ratio_limit(self, tmp_path):
+        """Test a 3MF model part is held to the ratio as it is scanned."""
+        path = tmp_path / "bomb.3mf"
+        model = MODEL.format(unit="millimeter", items='<item objectid="1"/>')
+        model = model.replace("<resources>", "<resources>" + " " * (9 * 1024 * 1024))
+        with zipfile.ZipFile(path, "w", zipfile.ZIP_DEFLATED) as archive:
+            archive.writestr("[Content_Types].xml", CONTENT_TYPES)
+            archive.writestr("_rels/.rels", RELS)
+            archive.writestr("3D/3dmodel.model", model)
+
+        info = validate_3mf(str(path))
+
+        assert not info.is_valid
+        assert info.error_message == (
+            "Invalid 3MF - 3D/3dmodel.model inflates to over 100 times its compressed size"
+        )
@@ -0,0 +1,98 @@
+"""Unit tests for the Moonraker integration.
+
+Focus: Test uploads and the cross-check of Klipper's estimate against the quote.
+"""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from urllib.parse import parse_qs, urlparse
+
+import pytest
+
+from orca_quote_machine._rust_core import create_moonraker_config, send_to_moonraker
+
+
+@pytest.fixture
+def moonraker():
+    """Fake Moonraker; metadata is missing on the first request, as while it analyses."""
+    class Handler(BaseHTTPRequestHandler):
+        def _answer(self, status, payload):
+            body = json.dumps(payload).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def do_POST(self):
+            body = self.rfile.read(int(self.headers["Content-Length"]))
+            server.uploads.append(body)
+            item = {"path": "quotes/plate_1.gcode", "root": "gcodes"}
+            self._answer(201, {"item": item, "print_started": False, "action": "create_file"})
+
+        def do_GET(self):
+            url = urlparse(self.path)
+            server.metadata_requests.append(parse_qs(url.query)["filename"][0])
+            if len(server.metadata_requests) == 1:
+                self._answer(404, {"error": {"code": 404, "message": "Metadata not available"}})
+            else:
+                self._answer(200, {"result": {"estimated_time": server.estimated_time}})
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    server.uploads = []
+    server.metadata_requests = []
+    server.estimated_time = 3600.0
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield server
+    server.shutdown()
+
+
+def _gcode(tmp_path, header="; estimated printing time: 1h 0m\n"):
+    path = tmp_path / "plate_1.gcode"
+    path.write_text(header + "G28\n")
+    return str(path)
+
+
+class TestSendToMoonraker:
+    """Tests for send_to_moonraker."""
+
+    def test_discrepancy_flagged_in_audit_log(self, tmp_path, moonraker):
+        """Test an estimate 50% over the quoted time is flagged and recorded."""
+        moonraker.estimated_time = 5400.0
+        config = create_moonraker_config(
+            f"http://127.0.0.1:{moonraker.server_port}", folder="quotes"
+        )
+        audit_log = tmp_path / "audit.jsonl"
+
+        upload = send_to_moonraker(
+            _gcode(tmp_path),
+            config,
+            quoted_print_minutes=60,
+            quote_id="q-1",
+            audit_log_path=str(audit_log),
+        )
+
+        assert b'name="path"\r\n\r\nquotes\r\n' in moonraker.uploads[0]
+        assert moonraker.metadata_requests[-1] == "quotes/plate_1.gcode"
+        assert upload.remote_path == "quotes/plate_1.gcode"
+        assert upload.discrepancy_percent == pytest.approx(50.0)
+        assert upload.flagged
+        record = json.loads(audit_log.read_text())
+        assert record["event"] == "moonraker_estimate"
+        assert record["quote_id"] == "q-1" and record["flagged"] is True
+
+    def test_header_estimate_used_when_quote_time_missing(self, tmp_path, moonraker):
+        """Test the G-code header stands in for the quoted time, within tolerance."""
+        moonraker.estimated_time = 3780.0
+        config = create_moonraker_config(f"http://127.0.0.1:{moonraker.server_port}")
+
+        upload = send_to_moonraker(_gcode(tmp_path), config)
+
+        assert upload.quoted_estimate_seconds == 3600.0
+        assert upload.klipper_estimate_seconds == 3780.0
+        assert not upload.flagged
@@ -0,0 +1,66 @@
+"""Unit tests for multi-object OBJ files.
+
+Focus: Test that OBJ files are split by `o` objects, or by `g` groups when there
+are none, with per-object triangle counts and bounds, and that each object can
+be written out as its own STL.
+"""
+
+import pytest
+
+from orca_quote_machine._rust_core import list_obj_objects, split_obj_objects, validate_3d_model
+
+# Two unit-height squares, each as one quad: one at the origin, one 10 mm along X.
+VERTICES = "v 0 0 0\nv 2 0 0\nv 2 2 1\nv 0 2 1\nv 10 0 0\nv 13 0 0\nv 13 3 2\nv 10 3 2\n"
+
+
+class TestListObjObjects:
+    """Tests for list_obj_objects."""
+
+    def test_objects_keep_their_groups_together(self, tmp_path):
+        """Test `o` objects split the file, `g` groups inside one are merged, and reopened objects merge."""
+        model = tmp_path / "pair.obj"
+        model.write_text(
+            VERTICES
+            + "o Left\ng top\nf 1 2 3\ng bottom\nf 1 3 4\n"
+            + "o Right\nf 5 6 7 8\n"
+            + "o Left\nf 4 3 1\n"
+        )
+
+        left, right = list_obj_objects(str(model))
+
+        assert (left.name, left.triangles) == ("Left", 3)
+        assert left.min_mm == (0.0, 0.0, 0.0) and left.max_mm == (2.0, 2.0, 1.0)
+        assert (right.name, right.triangles) == ("Right", 2)
+        assert right.dimensions_mm == (3.0, 3.0, 2.0)
+
+    def test_groups_split_files_without_objects(self, tmp_path):
+        """Test `g` groups are the objects when there are no `o` lines, after any unnamed faces."""
+        model = tmp_path / "groups.obj"
+        model.write_text(VERTICES + "f 1 2 3\ng wheel\nf 5 6 7\ng\nf 1 3 4\n")
+
+        objects = list_obj_objects(str(model))
+
+        assert [(o.name, o.triangles) for o in objects] == [("default", 2), ("wheel", 1)]
+
+    def test_missing_file_raises(self, tmp_path):
+        """Test a missing file raises FileNotFoundError."""
+        with pytest.raises(FileNotFoundError):
+            list_obj_objects(str(tmp_path / "missing.obj"))
+
+
+class TestSplitObjObjects:
+    """Tests for split_obj_objects."""
+
+    def test_each_object_written_as_stl(self, tmp_path):
+        """Test each object gets its own valid STL, with clashing names numbered."""
+        model = tmp_path / "kit.obj"
+        model.write_text(VERTICES + "o arm/left\nf 1 2 3 4\no armleft\nf 5 6 7 8\n")
+
+        paths = split_obj_objects(str(model), str(tmp_path / "parts"))
+
+        assert paths == [
+            str(tmp_path / "parts" / "kit.armleft.stl"),
+            str(tmp_path / "parts" / "kit.armleft-2.stl"),
+        ]
+        sizes = [validate_3d_model(path).dimensions_mm for path in paths]
+        assert sizes == [(2.0, 2.0, 1.0), (3.0, 3.0, 2.0)]
@@ -0,0 +1,91 @@
+"""Unit tests for the OctoPrint integration.
+
+Focus: Test the upload request OctoPrint receives and how its answers are reported.
+"""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from orca_quote_machine._rust_core import create_octoprint_config, send_to_octoprint
+
+
+@pytest.fixture
+def octoprint():
+    """Fake OctoPrint recording each request; set `status` to change the answer."""
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = self.rfile.read(int(self.headers["Content-Length"]))
+            server.requests.append((self.path, dict(self.headers), body))
+            if server.status == 201:
+                answer = {
+                    "done": True,
+                    "effectivePrint": b'name="print"' in body,
+                    "files": {
+                        "local": {
+                            "path": "quotes/plate_1.gcode",
+                            "refs": {"resource": "/api/files/local/quotes/plate_1.gcode"},
+                        }
+                    },
+                }
+            else:
+                answer = {"error": "Invalid API key"}
+            payload = json.dumps(answer).encode()
+            self.send_response(server.status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    server.requests = []
+    server.status = 201
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield server
+    server.shutdown()
+
+
+class TestSendToOctoPrint:
+    """Tests for send_to_octoprint."""
+
+    def test_upload_and_start_print(self, tmp_path, octoprint):
+        """Test the file, folder and print flag are posted with the API key."""
+        gcode = tmp_path / "plate_1.gcode"
+        gcode.write_text("G28\nG1 X10\n")
+        config = create_octoprint_config(
+            f"http://127.0.0.1:{octoprint.server_port}/",
+            "secret-key",
+            folder="quotes",
+            start_print=True,
+        )
+
+        upload = send_to_octoprint(str(gcode), config)
+
+        path, headers, body = octoprint.requests[0]
+        assert path == "/api/files/local"
+        assert headers["X-Api-Key"] == "secret-key"
+        assert b'filename="plate_1.gcode"' in body and b"G28\nG1 X10\n" in body
+        assert b'name="path"\r\n\r\nquotes\r\n' in body
+        assert upload.remote_path == "quotes/plate_1.gcode"
+        assert upload.print_started and upload.done
+        assert "secret-key" not in str(config)
+
+    def test_rejected_upload_raises(self, tmp_path, octoprint):
+        """Test an HTTP error from OctoPrint raises OSError with its status and message."""
+        gcode = tmp_path / "plate_1.gcode"
+        gcode.write_text("G28\n")
+        octoprint.status = 403
+        config = create_octoprint_config(f"http://127.0.0.1:{octoprint.server_port}", "bad")
+
+        with pytest.raises(OSError, match="HTTP 403.*Invalid API key"):
+            send_to_octoprint(str(gcode), config)
+        with pytest.raises(FileNotFoundError):
+            send_to_octoprint(str(tmp_path / "missing.gcode"), config)
+        with pytest.raises(ValueError):
+            create_octoprint_config("octopi.local", "key")
@@ -0,0 +1,23 @@
+"""Unit tests for the Rust panic boundary.
+
+Focus: Test InternalError is catchable like any error and ordinary failures keep their types.
+"""
+
+import pytest
+
+from orca_quote_machine._rust_core import InternalError, load_profile, secure_filename
+
+
+class TestPanicBoundary:
+    """Tests for the panic boundary around exported functions."""
+
+    def test_internal_error_is_an_exception(self):
+        """Test InternalError is caught by `except Exception`, unlike pyo3's PanicException."""
+        assert issubclass(InternalError, Exception)
+        assert InternalError.__module__ == "_rust_core"
+
+    def test_ordinary_errors_pass_through(self, tmp_path):
+        """Test errors that are not panics keep their exception types and results are unchanged."""
+        with pytest.raises(FileNotFoundError):
+            load_profile(str(tmp_path / "missing.json"))
+        assert secure_filename("../model.stl") == "..model.stl"
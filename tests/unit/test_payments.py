@@ -0,0 +1,101 @@
+"""Unit tests for Stripe payment links.
+
+Focus: Test the Checkout Session Stripe receives and how failures are reported.
+"""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from urllib.parse import parse_qs
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    calculate_quote_rust,
+    create_payment_link,
+    create_stripe_config,
+)
+
+
+@pytest.fixture
+def stripe():
+    """Fake Stripe API recording each form; set `status` to change the answer."""
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = self.rfile.read(int(self.headers["Content-Length"])).decode()
+            server.requests.append((self.path, dict(self.headers), parse_qs(body)))
+            if server.status == 200:
+                answer = {"id": "cs_test_1", "url": "https://checkout.stripe.com/c/pay/cs_test_1"}
+            else:
+                answer = {"error": {"message": "Invalid API Key provided"}}
+            payload = json.dumps(answer).encode()
+            self.send_response(server.status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    server.requests = []
+    server.status = 200
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield server
+    server.shutdown()
+
+
+def _config(stripe, key="sk_test_123"):
+    return create_stripe_config(
+        key,
+        "https://example.com/paid",
+        api_base=f"http://127.0.0.1:{stripe.server_port}",
+    )
+
+
+def _amounts(form):
+    return [
+        int(value[0])
+        for name, value in form.items()
+        if name.endswith("[price_data][unit_amount]")
+    ]
+
+
+class TestCreatePaymentLink:
+    """Tests for create_payment_link."""
+
+    def test_line_items_add_up_to_total(self, stripe):
+        """Test the session is itemised from the breakdown and sums to the quoted total."""
+        cost = calculate_quote_rust(125, 37.3, "PETG", 30.0, 0.5, 1.1, 5.0)
+
+        url = create_payment_link(cost, _config(stripe), quote_id="q-1")
+
+        path, headers, form = stripe.requests[0]
+        assert url == "https://checkout.stripe.com/c/pay/cs_test_1"
+        assert path == "/v1/checkout/sessions"
+        assert headers["Authorization"] == "Bearer sk_test_123"
+        assert form["mode"] == ["payment"] and form["client_reference_id"] == ["q-1"]
+        assert form["line_items[0][price_data][currency]"] == ["sgd"]
+        assert "PETG" in form["line_items[0][price_data][product_data][name]"][0]
+        assert sum(_amounts(form)) == round(cost.total_cost * 100)
+        assert "sk_test_123" not in str(_config(stripe))
+
+    def test_minimum_price_and_errors(self, stripe):
+        """Test a minimum-price top-up gets its own item and Stripe errors raise OSError."""
+        cost = calculate_quote_rust(5, 2.0, "PLA", 25.0, 0.0, 1.1, 5.0)
+        assert cost.minimum_applied
+
+        create_payment_link(cost, _config(stripe))
+
+        form = stripe.requests[0][2]
+        names = [v[0] for k, v in form.items() if k.endswith("[product_data][name]")]
+        assert "Minimum order adjustment" in names
+        assert sum(_amounts(form)) == 500
+
+        stripe.status = 401
+        with pytest.raises(OSError, match="HTTP 401.*Invalid API Key"):
+            create_payment_link(cost, _config(stripe, key="sk_bad"))
+        with pytest.raises(ValueError):
+            create_stripe_config("sk_test_123", "https://example.com", currency="dollars")
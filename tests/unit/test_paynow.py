@@ -0,0 +1,79 @@
+"""Unit tests for PayNow QR codes.
+
+Focus: Test the EMVCo payload banking apps read and the rendered images.
+"""
+
+import binascii
+import struct
+import zlib
+
+import pytest
+
+from orca_quote_machine._rust_core import generate_paynow_qr, paynow_payload
+
+
+def _fields(payload):
+    """Split an EMV payload into {id: value}."""
+    fields = {}
+    while payload:
+        tag, length = payload[:2], int(payload[2:4])
+        fields[tag] = payload[4 : 4 + length]
+        payload = payload[4 + length :]
+    return fields
+
+
+class TestPayNowPayload:
+    """Tests for paynow_payload."""
+
+    def test_uen_payload_fields_and_checksum(self):
+        """Test the PayNow account, amount, reference and CRC of a UEN payload."""
+        payload = paynow_payload(
+            42.5, "Q-1A2B3C", uen="201403121w", merchant_name="Orca Prints", expiry="20261231"
+        )
+
+        fields = _fields(payload)
+        account = _fields(fields["26"])
+        assert account == {
+            "00": "SG.PAYNOW",
+            "01": "2",
+            "02": "201403121W",
+            "03": "0",
+            "04": "20261231",
+        }
+        assert fields["53"] == "702" and fields["54"] == "42.50"
+        assert fields["59"] == "Orca Prints"
+        assert _fields(fields["62"]) == {"01": "Q-1A2B3C"}
+        crc = binascii.crc_hqx(payload[:-4].encode(), 0xFFFF)
+        assert fields["63"] == f"{crc:04X}"
+
+    def test_invalid_requests_raise(self):
+        """Test bad recipients, amounts and references raise ValueError."""
+        assert _fields(_fields(paynow_payload(5, "Q1", mobile="9123 4567"))["26"])["02"] == "+6591234567"
+        with pytest.raises(ValueError, match="exactly one"):
+            paynow_payload(5, "Q1", uen="201403121W", mobile="91234567")
+        with pytest.raises(ValueError, match="mobile"):
+            paynow_payload(5, "Q1", mobile="61234567")
+        with pytest.raises(ValueError, match="amount"):
+            paynow_payload(0, "Q1", mobile="91234567")
+        with pytest.raises(ValueError, match="reference"):
+            paynow_payload(5, "Q" * 26, mobile="91234567")
+
+
+class TestGeneratePayNowQr:
+    """Tests for generate_paynow_qr."""
+
+    def test_png_and_svg_output(self):
+        """Test a valid greyscale PNG and an SVG of the same symbol are produced."""
+        png = generate_paynow_qr(12.5, "Q-1", mobile="91234567", scale=4)
+
+        assert png[:8] == b"\x89PNG\r\n\x1a\n"
+        width, height = struct.unpack(">II", png[16:24])
+        assert width == height and width % 4 == 0
+        idat_len = struct.unpack(">I", png[33:37])[0]
+        pixels = zlib.decompress(png[41 : 41 + idat_len])
+        assert len(pixels) == height * (width + 1)
+
+        svg = generate_paynow_qr(12.5, "Q-1", mobile="91234567", image_format="svg")
+        assert svg.startswith(b"<svg") and f'viewBox="0 0 {width // 4} '.encode() in svg
+        with pytest.raises(ValueError, match="image format"):
+            generate_paynow_qr(12.5, "Q-1", mobile="91234567", image_format="gif")
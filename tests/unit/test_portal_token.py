@@ -0,0 +1,58 @@
+"""Unit tests for customer portal tokens.
+
+Focus: Test tokens round-trip and reject tampering, other secrets and expiry.
+"""
+
+import base64
+import json
+import time
+
+import pytest
+
+from orca_quote_machine._rust_core import sign_quote_token, verify_quote_token
+
+SECRET = "portal-test-secret-0123456789"
+
+
+def _b64url(data: bytes) -> str:
+    return base64.urlsafe_b64encode(data).rstrip(b"=").decode()
+
+
+class TestQuoteTokens:
+    """Tests for sign_quote_token and verify_quote_token."""
+
+    def test_round_trip(self):
+        """Test a signed token verifies to its quote ID and expiry."""
+        before = time.time()
+        token = sign_quote_token("task-123", 3600, SECRET)
+
+        access = verify_quote_token(token, SECRET)
+
+        assert token.startswith("v1.") and "/" not in token and "+" not in token
+        assert access.quote_id == "task-123"
+        assert before + 3599 <= access.expires_at <= time.time() + 3600
+
+    def test_tampered_foreign_and_expired_tokens_rejected(self):
+        """Test a changed payload, another secret and an old token all fail."""
+        token = sign_quote_token("task-123", 3600, SECRET)
+        version, _, signature = token.split(".")
+        forged = _b64url(json.dumps({"qid": "task-999", "exp": 4102444800}).encode())
+
+        with pytest.raises(ValueError, match="bad signature"):
+            verify_quote_token(f"{version}.{forged}.{signature}", SECRET)
+        with pytest.raises(ValueError, match="bad signature"):
+            verify_quote_token(token, "another-secret-0123456789")
+        with pytest.raises(ValueError, match="malformed"):
+            verify_quote_token("not-a-token", SECRET)
+
+        short = sign_quote_token("task-123", 0.001, SECRET)
+        time.sleep(1.1)
+        with pytest.raises(ValueError, match="expired"):
+            verify_quote_token(short, SECRET)
+
+    def test_weak_secret_and_bad_expiry_rejected(self):
+        """Test short secrets and non-positive lifetimes raise ValueError."""
+        with pytest.raises(ValueError, match="at least 16 bytes"):
+            sign_quote_token("task-123", 3600, "short")
+        with pytest.raises(ValueError, match="expiry"):
+            sign_quote_token("task-123", 0, SECRET)
@@ -0,0 +1,90 @@
+"""Unit tests for G-code post-processing.
+
+Focus: Test snippets, thumbnail stripping and scripts change the file as configured and are audited.
+"""
+
+import hashlib
+import json
+import stat
+
+import pytest
+
+from orca_quote_machine._rust_core import create_postprocess_config, postprocess_gcode
+
+GCODE = """; HEADER_BLOCK_START
+; estimated printing time = 1h 0m
+; HEADER_BLOCK_END
+
+; THUMBNAIL_BLOCK_START
+; thumbnail begin 16x16 8
+; aGVsbG8=
+; thumbnail end
+; THUMBNAIL_BLOCK_END
+
+G28
+G1 X10 Y10
+; layer end
+M104 S0
+
+; CONFIG_BLOCK_START
+; layer_height = 0.2
+; CONFIG_BLOCK_END
+"""
+
+
+class TestPostprocessGcode:
+    """Tests for postprocess_gcode."""
+
+    def test_snippets_inserted_and_thumbnails_stripped(self, tmp_path):
+        """Test snippets go around the commands, the comments stay put, and the run is audited."""
+        gcode = tmp_path / "plate_1.gcode"
+        gcode.write_text(GCODE)
+        audit_log = tmp_path / "audit.jsonl"
+        config = create_postprocess_config(
+            start_gcode="M117 Printing quote\n",
+            end_gcode="M84",
+            strip_thumbnails=True,
+            audit_log_path=str(audit_log),
+        )
+
+        transforms = postprocess_gcode(str(tmp_path), config, quote_id="q-1")
+
+        lines = gcode.read_text().splitlines()
+        assert transforms == ["strip_thumbnails", "start_gcode", "end_gcode"]
+        assert not any("thumbnail" in line for line in lines)
+        assert lines[:3] == GCODE.splitlines()[:3]
+        assert lines[lines.index("G28") - 1] == "M117 Printing quote"
+        assert lines[lines.index("M104 S0") + 1] == "M84"
+        assert lines[-1] == "; CONFIG_BLOCK_END"
+        (record,) = [json.loads(line) for line in audit_log.read_text().splitlines()]
+        assert record["event"] == "gcode_postprocessed"
+        assert (record["quote_id"], record["file"]) == ("q-1", str(gcode))
+        assert record["transforms"] == transforms
+        assert record["bytes_before"] == len(GCODE)
+        assert record["sha256"] == hashlib.sha256(gcode.read_bytes()).hexdigest()
+
+    def test_script_edits_file_and_failures_raise(self, tmp_path):
+        """Test a script gets the file path last, and a failing or hung one raises."""
+        gcode = tmp_path / "plate_1.gcode"
+        gcode.write_text(GCODE)
+        script = tmp_path / "pp.sh"
+        script.write_text('#!/bin/sh\necho "; processed by $1" >> "$2"\n')
+        script.chmod(script.stat().st_mode | stat.S_IEXEC)
+
+        transforms = postprocess_gcode(
+            str(gcode), create_postprocess_config(script=[str(script), "tag"])
+        )
+
+        assert transforms == [f"script:{script}"]
+        assert gcode.read_text().endswith("; processed by tag\n")
+        with pytest.raises(RuntimeError, match="exited with"):
+            postprocess_gcode(str(gcode), create_postprocess_config(script=["false"]))
+        with pytest.raises(RuntimeError, match="did not finish"):
+            postprocess_gcode(
+                str(gcode),
+                create_postprocess_config(
+                    script=["sh", "-c", "sleep 5"], script_timeout_secs=0.2
+                ),
+            )
+        with pytest.raises(ValueError, match="script_timeout_secs"):
+            create_postprocess_config(script_timeout_secs=0)
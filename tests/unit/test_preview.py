@@ -0,0 +1,38 @@
+"""Unit tests for model preview rendering.
+
+Focus: Test the mesh renderer's image size and that models without a mesh
+are refused.
+"""
+
+import struct
+
+import pytest
+
+from orca_quote_machine._rust_core import render_preview
+
+
+def _png_size(png: bytes) -> tuple[int, int]:
+    return struct.unpack(">II", png[16:24])
+
+
+class TestRenderPreview:
+    """Tests for render_preview."""
+
+    def test_renders_requested_size(self, tmp_path):
+        """Test the PNG is as wide and tall as asked, within the 16..2048 bounds."""
+        model = tmp_path / "wedge.obj"
+        model.write_text("v 0 0 0\nv 40 0 0\nv 0 20 10\nf 1 2 3\n")
+
+        png = render_preview(str(model), 320, 180)
+
+        assert png.startswith(b"\x89PNG\r\n\x1a\n")
+        assert _png_size(png) == (320, 180)
+        assert _png_size(render_preview(str(model), 4, 5000)) == (16, 2048)
+
+    def test_step_has_no_mesh(self, tmp_path):
+        """Test a STEP model, which has no triangles, is a ValueError."""
+        step = tmp_path / "part.step"
+        step.write_text("ISO-10303-21;\nEND-ISO-10303-21;\n")
+
+        with pytest.raises(ValueError, match="no mesh to render"):
+            render_preview(str(step), 100, 100)
@@ -7,7 +7,8 @@ import asyncio
 import os
 import tempfile
 
-from orca_quote_machine._rust_core import parse_slicer_output
+from orca_quote_machine._rust_core import load_profile, parse_slicer_output
+from orca_quote_machine.core.config import Settings
 from orca_quote_machine.models.quote import MaterialType
 from orca_quote_machine.services.pricing import PricingService
 
@@ -96,3 +97,33 @@ class TestPricingServiceLogic:
         assert "Material:" in result
         assert "Time:" in result
         assert "Total:" in result
+
+    def test_filament_profile_cost_sets_price(self, tmp_path):
+        """Test a profile's filament_cost is used unless MATERIAL_PRICES sets a price."""
+        profile_path = tmp_path / "pla.json"
+        profile_path.write_text('{"type": "filament", "filament_cost": ["40"]}')
+        profile = load_profile(str(profile_path))
+        slicing_result = asyncio.run(self.create_test_slicing_result())
+
+        result = PricingService().calculate_quote(slicing_result, MaterialType.PLA, profile)
+        assert result.price_per_kg == 40.0
+
+        configured = PricingService(Settings(material_prices={"PLA": 22.0}))
+        result = configured.calculate_quote(slicing_result, MaterialType.PLA, profile)
+        assert result.price_per_kg == 22.0
+
+    def test_filament_length_converted_with_density(self):
+        """Test G-code reporting only a length gets its weight from the filament density."""
+
+        async def parse_length_only():
+            with tempfile.TemporaryDirectory() as temp_dir:
+                with open(os.path.join(temp_dir, "test.gcode"), "w") as f:
+                    f.write("; estimated printing time: 1h 0m\n")
+                    f.write("; filament used [mm] = 1000.0, 0.0\n")
+                return await parse_slicer_output(temp_dir, 1.24, 1.75)
+
+        result = asyncio.run(parse_length_only())
+
+        assert result.filament_length_mm == 1000.0
+        # π × 0.875² mm² × 1000 mm = 2.405 cm³ × 1.24 g/cm³
+        assert abs(result.filament_weight_grams - 2.98) < 0.01
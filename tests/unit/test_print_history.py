@@ -0,0 +1,62 @@
+"""Unit tests for estimate calibration from finished prints.
+
+Focus: Test correction factors and how they scale slicing estimates.
+"""
+
+import asyncio
+
+import pytest
+
+from orca_quote_machine._rust_core import create_print_history, parse_slicer_output
+
+
+async def _slicing_result(tmp_path):
+    (tmp_path / "plate_1.gcode").write_text(
+        "; estimated printing time: 2h 0m\n; filament used: 100.0g\n"
+    )
+    return await parse_slicer_output(str(tmp_path))
+
+
+class TestPrintHistory:
+    """Tests for PrintHistory."""
+
+    def test_factors_from_recent_prints(self, tmp_path):
+        """Test factors are summed actual over estimated, once there are enough prints."""
+        history = create_print_history(str(tmp_path / "history.jsonl"), window=2, min_samples=2)
+
+        history.record("PETG", 100, 50.0, 300, 60.0)  # outside the window
+        assert history.factor("petg") is None
+        history.record("PETG", 100, 50.0, 110, 55.0, quote_id="q-1")
+        history.record("petg", 300, 150.0, 330, 150.0)
+        history.record("PLA", 60, 20.0, 60, 20.0)
+
+        petg = history.factor("PETG")
+        assert petg.samples == 2
+        assert petg.time_factor == pytest.approx(440 / 400)
+        assert petg.filament_factor == pytest.approx(205 / 200)
+        assert [f.material for f in history.factors()] == ["PETG"]
+
+    def test_calibrate_scales_estimates(self, tmp_path):
+        """Test estimates are scaled and the slicer's own figures kept."""
+        history = create_print_history(str(tmp_path / "history.jsonl"), min_samples=1)
+        history.record("PLA", 100, 100.0, 125, 90.0)
+        slicing = asyncio.run(_slicing_result(tmp_path))
+
+        calibrated, calibration = history.calibrate(slicing, "PLA")
+        unchanged, none = history.calibrate(slicing, "ASA")
+
+        assert calibrated.print_time_minutes == 150
+        assert calibrated.filament_weight_grams == pytest.approx(90.0)
+        assert calibration.slicer_print_time_minutes == 120
+        assert calibration.slicer_filament_grams == pytest.approx(100.0)
+        assert unchanged.print_time_minutes == 120
+        assert none is None
+
+    def test_bad_records_rejected(self, tmp_path):
+        """Test negative actuals and windows smaller than the sample minimum."""
+        history = create_print_history(str(tmp_path / "history.jsonl"))
+
+        with pytest.raises(ValueError, match="actual_minutes"):
+            history.record("PLA", 100, 50.0, -1, 50.0)
+        with pytest.raises(ValueError, match="min_samples"):
+            create_print_history(str(tmp_path / "history.jsonl"), window=3, min_samples=5)
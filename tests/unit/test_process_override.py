@@ -0,0 +1,77 @@
+"""Unit tests for derived process profile generation.
+
+Focus: Test that print options become Orca settings and bad values are rejected.
+"""
+
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import generate_process_override
+
+
+@pytest.fixture
+def base_process(tmp_path):
+    """A vendor-style process profile inheriting from a system base."""
+    path = tmp_path / "standard.json"
+    path.write_text(
+        json.dumps(
+            {
+                "type": "process",
+                "name": "0.20mm Standard",
+                "inherits": "fdm_process_common",
+                "from": "system",
+                "setting_id": "GP004",
+                "layer_height": "0.2",
+                "sparse_infill_density": "15%",
+                "wall_loops": "2",
+            }
+        )
+    )
+    return path
+
+
+class TestGenerateProcessOverride:
+    """Tests for generate_process_override."""
+
+    def test_overrides_written_in_orca_format(self, tmp_path, base_process):
+        """Test layer height, infill, supports and raw keys land as Orca strings."""
+        out_dir = tmp_path / "out"
+
+        path = generate_process_override(
+            str(base_process),
+            {
+                "layer_height": 0.28,
+                "infill_percent": 40,
+                "supports": True,
+                "support_type": "tree",
+                "wall_loops": 3,
+            },
+            str(out_dir),
+        )
+        derived = json.loads(open(path).read())
+
+        assert path.startswith(str(out_dir))
+        assert derived["layer_height"] == "0.28"
+        assert derived["sparse_infill_density"] == "40%"
+        assert derived["enable_support"] == "1"
+        assert derived["support_type"] == "tree(auto)"
+        assert derived["wall_loops"] == "3"
+        assert derived["inherits"] == "fdm_process_common"
+        assert derived["name"] == "0.20mm Standard (0.28mm 40% supports)"
+        assert derived["from"] == "User"
+        assert "setting_id" not in derived
+        # The base profile is left untouched
+        assert json.loads(base_process.read_text())["layer_height"] == "0.2"
+
+    def test_invalid_overrides_rejected(self, tmp_path, base_process):
+        """Test out-of-range values and non-process bases raise ValueError."""
+        machine = tmp_path / "machine.json"
+        machine.write_text(json.dumps({"type": "machine"}))
+
+        with pytest.raises(ValueError, match="infill_percent"):
+            generate_process_override(str(base_process), {"infill_percent": 120})
+        with pytest.raises(ValueError, match="layer_height"):
+            generate_process_override(str(base_process), {"layer_height": 0})
+        with pytest.raises(ValueError, match="process profile"):
+            generate_process_override(str(machine), {"supports": False})
@@ -0,0 +1,60 @@
+"""Unit tests for OrcaSlicer profile bundles.
+
+Focus: Test exporting a profile set and importing it into a fresh profiles directory.
+"""
+
+import json
+import zipfile
+
+import pytest
+
+from orca_quote_machine._rust_core import export_profile_bundle, import_profile_bundle
+
+
+@pytest.fixture
+def profiles_dir(tmp_path):
+    """Profile tree with one machine, one process, and one filament."""
+    root = tmp_path / "profiles"
+    profiles = {
+        "machine/printer.json": {"type": "machine", "name": "Printer"},
+        "process/standard.json": {"type": "process", "name": "Standard"},
+        "filament/pla.json": {"type": "filament", "name": "PLA"},
+    }
+    for relative, settings in profiles.items():
+        path = root / relative
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(json.dumps(settings))
+    return root
+
+
+class TestProfileBundle:
+    """Tests for import_profile_bundle and export_profile_bundle."""
+
+    def test_export_then_import_round_trips(self, tmp_path, profiles_dir):
+        """Test an exported bundle restores every profile in its type directory."""
+        bundle = tmp_path / "fleet.orca_printer"
+        assert export_profile_bundle(str(profiles_dir), str(bundle)) == 3
+
+        target = tmp_path / "restored"
+        result = import_profile_bundle(str(bundle), str(target))
+
+        assert result.bundle_type == "printer config bundle"
+        assert len(result.imported) == 3
+        for relative in ("machine/printer.json", "process/standard.json", "filament/pla.json"):
+            assert (target / relative).read_text() == (profiles_dir / relative).read_text()
+
+        again = import_profile_bundle(str(bundle), str(target))
+        assert again.imported == []
+        assert len(again.skipped) == 3
+
+    def test_invalid_profile_aborts_import(self, tmp_path):
+        """Test a bundle with a non-JSON profile is rejected before anything is written."""
+        bundle = tmp_path / "broken.orca_filament"
+        with zipfile.ZipFile(bundle, "w") as archive:
+            archive.writestr("filament/good.json", json.dumps({"type": "filament"}))
+            archive.writestr("filament/bad.json", "not json")
+
+        target = tmp_path / "profiles"
+        with pytest.raises(ValueError, match="bad.json"):
+            import_profile_bundle(str(bundle), str(target))
+        assert not target.exists()
@@ -0,0 +1,57 @@
+"""Unit tests for the in-memory profile cache.
+
+Focus: Test cached listings and invalidation, manual and watcher-driven.
+"""
+
+import json
+import time
+
+from orca_quote_machine._rust_core import create_profile_cache
+
+
+def _write(path, settings: dict) -> None:
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(json.dumps(settings))
+
+
+class TestProfileCache:
+    """Tests for ProfileCache."""
+
+    def test_results_are_cached_until_invalidated(self, tmp_path):
+        """Test that changes are only picked up after invalidate()."""
+        _write(tmp_path / "filament" / "pla.json", {"filament_type": ["PLA"]})
+        cache = create_profile_cache(str(tmp_path), watch=False)
+
+        assert cache.filament_names() == ["pla"]
+        _write(tmp_path / "filament" / "tpu.json", {"filament_type": ["TPU"]})
+        _write(tmp_path / "filament" / "b petg.json", {"filament_type": ["PETG"]})
+        assert cache.filament_names() == ["pla"]
+
+        cache.invalidate()
+
+        assert cache.filament_names() == ["b petg", "pla", "tpu"]
+        assert cache.resolve_filament("PETG").path == str(
+            tmp_path / "filament" / "b petg.json"
+        )
+        assert cache.invalidations == 1
+        assert cache.is_watching is False
+
+    def test_watcher_invalidates_on_change(self, tmp_path):
+        """Test that writing a profile clears the cache without manual help."""
+        _write(
+            tmp_path / "machine" / "printer.json",
+            {"type": "machine", "name": "Printer", "nozzle_diameter": ["0.4"]},
+        )
+        cache = create_profile_cache(str(tmp_path))
+        assert [m.name for m in cache.machines()] == ["Printer"]
+
+        _write(
+            tmp_path / "machine" / "second.json",
+            {"type": "machine", "name": "Second", "nozzle_diameter": ["0.6"]},
+        )
+        deadline = time.monotonic() + 5
+        while cache.invalidations == 0 and time.monotonic() < deadline:
+            time.sleep(0.05)
+
+        assert cache.is_watching is True
+        assert [m.name for m in cache.machines()] == ["Printer", "Second"]
@@ -0,0 +1,99 @@
+"""Unit tests for profile compatibility checks.
+
+Focus: Test that mismatched machine, filament, and process profiles are reported before slicing.
+"""
+
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import check_compatibility
+
+
+@pytest.fixture
+def profiles_dir(tmp_path):
+    """Profile tree with a 0.4 mm printer and matching filament and process."""
+    root = tmp_path / "profiles"
+    profiles = {
+        "machine/printer.json": {
+            "type": "machine",
+            "name": "Printer 0.4 nozzle",
+            "nozzle_diameter": ["0.4"],
+            "nozzle_hrc": "20",
+        },
+        "process/standard.json": {
+            "type": "process",
+            "name": "0.20mm Standard @0.4 nozzle",
+            "layer_height": "0.2",
+            "compatible_printers": ["Printer 0.4 nozzle"],
+        },
+        "filament/pla.json": {
+            "type": "filament",
+            "name": "PLA",
+            "nozzle_temperature": ["215"],
+            "nozzle_temperature_range_low": ["190"],
+            "nozzle_temperature_range_high": ["230"],
+        },
+    }
+    for relative, settings in profiles.items():
+        path = root / relative
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(json.dumps(settings))
+    return root
+
+
+def _write(path, settings) -> str:
+    path.write_text(json.dumps(settings))
+    return str(path)
+
+
+class TestCheckCompatibility:
+    """Tests for check_compatibility."""
+
+    def test_matching_profiles_are_compatible(self, profiles_dir):
+        """Test a trio made for the same printer and nozzle has no issues."""
+        report = check_compatibility(
+            str(profiles_dir / "machine" / "printer.json"),
+            str(profiles_dir / "filament" / "pla.json"),
+            str(profiles_dir / "process" / "standard.json"),
+        )
+
+        assert report.is_compatible
+        assert report.issues == []
+
+    def test_mismatches_are_reported_with_codes(self, profiles_dir):
+        """Test printer, nozzle, hardness, and temperature mismatches are each reported."""
+        process = _write(
+            profiles_dir / "process" / "fine.json",
+            {
+                "type": "process",
+                "name": "0.08mm Fine @0.2 nozzle",
+                "layer_height": "0.08",
+                "compatible_printers": ["Other Printer"],
+            },
+        )
+        filament = _write(
+            profiles_dir / "filament" / "pla_cf.json",
+            {
+                "type": "filament",
+                "name": "PLA-CF",
+                "required_nozzle_HRC": ["40"],
+                "nozzle_temperature": ["250"],
+                "nozzle_temperature_range_low": ["190"],
+                "nozzle_temperature_range_high": ["230"],
+            },
+        )
+
+        report = check_compatibility(
+            str(profiles_dir / "machine" / "printer.json"), filament, process
+        )
+
+        assert not report.is_compatible
+        codes = {issue.code for issue in report.issues}
+        assert codes == {
+            "process_printer_mismatch",
+            "nozzle_mismatch",
+            "nozzle_too_soft",
+            "temperature_out_of_range",
+        }
+        assert "Other Printer" in report.summary()
@@ -0,0 +1,80 @@
+"""Unit tests for machine and process profile discovery.
+
+Focus: Test typed listings and nozzle filtering.
+"""
+
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import discover_machines, discover_processes
+
+
+def _write(path, settings: dict) -> None:
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(json.dumps(settings))
+
+
+@pytest.fixture
+def profiles_dir(tmp_path):
+    """Profile tree with two machines and three processes."""
+    _write(
+        tmp_path / "machine" / "base.json",
+        {"type": "machine", "name": "fdm_base", "instantiation": "false",
+         "printable_area": ["0x0", "400x0", "400x400", "0x400"],
+         "printable_height": "400", "min_layer_height": ["0.08"],
+         "max_layer_height": ["0.32"]},
+    )
+    _write(
+        tmp_path / "machine" / "v-core 0.4.json",
+        {"type": "machine", "name": "V-Core 0.4 nozzle", "inherits": "fdm_base",
+         "nozzle_diameter": ["0.4"]},
+    )
+    _write(
+        tmp_path / "machine" / "v-core 0.6.json",
+        {"type": "machine", "name": "V-Core 0.6 nozzle", "inherits": "fdm_base",
+         "nozzle_diameter": ["0.6"]},
+    )
+    _write(
+        tmp_path / "process" / "fine.json",
+        {"type": "process", "name": "0.12mm Fine", "layer_height": "0.12",
+         "compatible_printers": ["V-Core 0.4 nozzle"]},
+    )
+    _write(
+        tmp_path / "process" / "draft.json",
+        {"type": "process", "name": "0.30mm Draft 0.6mm nozzle", "layer_height": "0.3"},
+    )
+    _write(
+        tmp_path / "process" / "any.json",
+        {"type": "process", "name": "0.20mm Standard", "layer_height": "0.2"},
+    )
+    return tmp_path
+
+
+class TestDiscoverMachines:
+    """Tests for discover_machines."""
+
+    def test_lists_instantiable_machines_with_inherited_values(self, profiles_dir):
+        """Test that base profiles are hidden and inherited bed size is reported."""
+        machines = discover_machines(str(profiles_dir))
+
+        assert [m.name for m in machines] == ["V-Core 0.4 nozzle", "V-Core 0.6 nozzle"]
+        assert machines[0].file_name == "v-core 0.4.json"
+        assert machines[0].nozzle_diameter == pytest.approx(0.4)
+        assert machines[0].bed_size == (400.0, 400.0)
+        assert machines[0].max_layer_height == pytest.approx(0.32)
+
+
+class TestDiscoverProcesses:
+    """Tests for discover_processes."""
+
+    def test_filters_processes_by_nozzle(self, profiles_dir):
+        """Test nozzle matching via compatible printers and profile names."""
+        all_processes = discover_processes(str(profiles_dir))
+        for_04 = discover_processes(str(profiles_dir), 0.4)
+        for_06 = discover_processes(str(profiles_dir), 0.6)
+
+        assert len(all_processes) == 3
+        assert [p.name for p in for_04] == ["0.12mm Fine", "0.20mm Standard"]
+        assert [p.name for p in for_06] == ["0.20mm Standard", "0.30mm Draft 0.6mm nozzle"]
+        assert for_04[0].nozzle_diameters == [pytest.approx(0.4)]
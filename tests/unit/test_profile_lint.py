@@ -0,0 +1,82 @@
+"""Unit tests for profile linting.
+
+Focus: Test that misconfigured profile trees are reported with stable codes.
+"""
+
+import json
+
+from orca_quote_machine._rust_core import ProfileLintReport, lint_profiles
+
+
+def _write(path, settings) -> None:
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(settings if isinstance(settings, str) else json.dumps(settings))
+
+
+class TestLintProfiles:
+    """Tests for lint_profiles."""
+
+    def test_clean_tree_passes(self, tmp_path):
+        """Test that a consistent machine/filament/process tree has no issues."""
+        _write(
+            tmp_path / "machine" / "printer.json",
+            {
+                "type": "machine",
+                "name": "Printer",
+                "nozzle_diameter": ["0.4"],
+                "printable_area": ["0x0", "200x0", "200x200", "0x200"],
+            },
+        )
+        _write(
+            tmp_path / "filament" / "pla.json",
+            {"type": "filament", "name": "PLA", "filament_type": ["PLA"]},
+        )
+        _write(
+            tmp_path / "process" / "standard.json",
+            {
+                "type": "process",
+                "name": "Standard",
+                "layer_height": "0.2",
+                "compatible_printers": ["Printer"],
+            },
+        )
+
+        report = lint_profiles(str(tmp_path))
+
+        assert isinstance(report, ProfileLintReport)
+        assert report.files_checked == 3
+        assert report.issues == []
+        assert report.is_ok is True
+
+    def test_reports_broken_profiles(self, tmp_path):
+        """Test parse errors, broken inherits, missing keys, and compatibility."""
+        _write(tmp_path / "machine" / "broken.json", "{not json")
+        _write(
+            tmp_path / "machine" / "printer.json",
+            {"type": "machine", "name": "Printer", "nozzle_diameter": ["0.4"]},
+        )
+        _write(
+            tmp_path / "filament" / "orphan.json",
+            {"type": "filament", "name": "Orphan", "inherits": "Generic Base"},
+        )
+        _write(
+            tmp_path / "process" / "other.json",
+            {
+                "type": "process",
+                "name": "Other",
+                "layer_height": "0.2",
+                "compatible_printers": ["Someone Else's Printer"],
+            },
+        )
+
+        report = lint_profiles(str(tmp_path))
+        codes = sorted(issue.code for issue in report.issues)
+
+        assert codes == [
+            "broken_inherits",
+            "missing_key",
+            "no_compatible_machine",
+            "parse_error",
+        ]
+        assert report.error_count == 3
+        assert report.is_ok is False
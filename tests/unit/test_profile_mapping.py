@@ -0,0 +1,51 @@
+"""Unit tests for material to filament profile mapping.
+
+Focus: Test mapping file lookups, fallback rules, and unmapped listing.
+"""
+
+import json
+
+from orca_quote_machine._rust_core import (
+    list_unmapped_materials,
+    resolve_filament_profile,
+)
+
+
+class TestResolveFilamentProfile:
+    """Tests for resolve_filament_profile."""
+
+    def test_resolution_order(self, tmp_path):
+        """Test mapping entries win, then the filename convention, then filament_type."""
+        filament_dir = tmp_path / "filament"
+        filament_dir.mkdir()
+        (filament_dir / "tpu.json").write_text(json.dumps({"filament_type": ["TPU"]}))
+        (filament_dir / "b petg.json").write_text(json.dumps({"filament_type": ["PETG"]}))
+        (filament_dir / "a petg base.json").write_text(
+            json.dumps({"filament_type": ["PETG"], "instantiation": "false"})
+        )
+        mapping = tmp_path / "materials.toml"
+        mapping.write_text('[filament]\npla = "Matte PLA.json"\n')
+
+        pla = resolve_filament_profile(str(filament_dir), "PLA", str(mapping))
+        tpu = resolve_filament_profile(str(filament_dir), "TPU", str(mapping))
+        petg = resolve_filament_profile(str(filament_dir), "petg", str(mapping))
+
+        assert (pla.source, pla.path) == ("mapping", str(filament_dir / "Matte PLA.json"))
+        assert tpu.source == "convention"
+        assert (petg.source, petg.path) == ("filament_type", str(filament_dir / "b petg.json"))
+
+
+class TestListUnmappedMaterials:
+    """Tests for list_unmapped_materials."""
+
+    def test_list_unmapped_materials(self, tmp_path):
+        """Test that materials without any resolvable profile are listed."""
+        (tmp_path / "pla.json").write_text("{}")
+        mapping = tmp_path / "materials.json"
+        mapping.write_text(json.dumps({"filament": {"ASA": "missing.json"}}))
+
+        unmapped = list_unmapped_materials(
+            str(tmp_path), ["PLA", "PETG", "ASA"], str(mapping)
+        )
+
+        assert unmapped == ["PETG", "ASA"]
@@ -0,0 +1,83 @@
+"""Unit tests for nozzle-aware profile resolution.
+
+Focus: Test machine, process and filament choice for a requested nozzle size.
+"""
+
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import resolve_profile_paths
+
+
+def _write(path, settings: dict) -> None:
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(json.dumps(settings))
+
+
+@pytest.fixture
+def profiles_dir(tmp_path):
+    """Two nozzle variants of one printer with matching processes and filaments."""
+    for nozzle in ("0.4", "0.6"):
+        _write(
+            tmp_path / "machine" / f"printer {nozzle}.json",
+            {"type": "machine", "name": f"Printer {nozzle} nozzle",
+             "nozzle_diameter": [nozzle]},
+        )
+    _write(
+        tmp_path / "process" / "standard.json",
+        {"type": "process", "name": "0.20mm Standard",
+         "compatible_printers": ["Printer 0.4 nozzle"]},
+    )
+    _write(
+        tmp_path / "process" / "draft.json",
+        {"type": "process", "name": "0.30mm Draft 0.6mm nozzle"},
+    )
+    _write(
+        tmp_path / "filament" / "pla.json",
+        {"type": "filament", "filament_type": ["PLA"],
+         "compatible_printers": ["Printer 0.4 nozzle"]},
+    )
+    _write(
+        tmp_path / "filament" / "pla 0.6.json",
+        {"type": "filament", "filament_type": ["PLA"],
+         "compatible_printers": ["Printer 0.6 nozzle"]},
+    )
+    return tmp_path
+
+
+class TestResolveProfilePaths:
+    """Tests for resolve_profile_paths."""
+
+    def test_profiles_follow_requested_nozzle(self, profiles_dir):
+        """Test defaults are kept for their nozzle and swapped for another one."""
+        default = resolve_profile_paths(
+            str(profiles_dir), "PLA", 0.4, "printer 0.4.json", "standard.json"
+        )
+        large = resolve_profile_paths(
+            str(profiles_dir), "PLA", 0.6, "printer 0.4.json", "standard.json"
+        )
+
+        assert default.machine == str(profiles_dir / "machine" / "printer 0.4.json")
+        assert default.process == str(profiles_dir / "process" / "standard.json")
+        assert default.filament == str(profiles_dir / "filament" / "pla.json")
+        assert large.machine == str(profiles_dir / "machine" / "printer 0.6.json")
+        assert large.process == str(profiles_dir / "process" / "draft.json")
+        assert large.filament == str(profiles_dir / "filament" / "pla 0.6.json")
+        assert large.nozzle_diameter == pytest.approx(0.6)
+
+    def test_unavailable_nozzle_lists_alternatives(self, profiles_dir):
+        """Test that an unknown nozzle size fails with the sizes on offer."""
+        with pytest.raises(ValueError, match=r"available: \[0.4, 0.6\]"):
+            resolve_profile_paths(str(profiles_dir), "PLA", 0.8)
+
+    def test_material_mapped_process_is_preferred(self, profiles_dir, tmp_path):
+        """Test a process mapped to the material replaces the configured default."""
+        mapping = tmp_path / "materials.toml"
+        mapping.write_text('[process]\nPLA = "draft.json"\n')
+
+        paths = resolve_profile_paths(
+            str(profiles_dir), "PLA", None, "printer 0.6.json", "standard.json", str(mapping)
+        )
+
+        assert paths.process == str(profiles_dir / "process" / "draft.json")
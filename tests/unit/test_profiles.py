@@ -0,0 +1,139 @@
+"""Unit tests for OrcaSlicer profile parsing.
+
+Focus: Test that profile JSON is read into typed fields.
+"""
+
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import Profile, load_profile, resolve_profile
+
+
+def _write_profile(path, settings: dict) -> str:
+    path.write_text(json.dumps(settings))
+    return str(path)
+
+
+class TestLoadProfile:
+    """Tests for load_profile."""
+
+    def test_load_machine_profile(self, tmp_path):
+        """Test machine fields, including bed size derived from printable_area."""
+        path = _write_profile(
+            tmp_path / "machine.json",
+            {
+                "type": "machine",
+                "name": "RatRig V-Core 3 400 0.4 nozzle",
+                "inherits": "fdm_ratrig_common",
+                "from": "system",
+                "printer_model": "RatRig V-Core 3 400",
+                "nozzle_diameter": ["0.4"],
+                "printable_area": ["0x0", "400x0", "400x400", "0x400"],
+                "printable_height": "400",
+            },
+        )
+
+        profile = load_profile(path)
+
+        assert isinstance(profile, Profile)
+        assert profile.profile_type == "machine"
+        assert profile.inherits == "fdm_ratrig_common"
+        assert profile.nozzle_diameter == pytest.approx(0.4)
+        assert profile.bed_size == (400.0, 400.0)
+        assert profile.printable_height == 400.0
+        assert profile.instantiation is True
+
+    def test_load_filament_profile(self, tmp_path):
+        """Test filament density, diameter, and cost parsing."""
+        path = _write_profile(
+            tmp_path / "pla.json",
+            {
+                "type": "filament",
+                "name": "Generic PLA",
+                "filament_type": ["PLA"],
+                "filament_density": ["1.24"],
+                "filament_diameter": ["1.75"],
+                "filament_cost": ["25"],
+                "compatible_printers": ["Printer A", "Printer B"],
+            },
+        )
+
+        profile = load_profile(path)
+
+        assert profile.filament_type == "PLA"
+        assert profile.filament_density == pytest.approx(1.24)
+        assert profile.filament_diameter == pytest.approx(1.75)
+        assert profile.filament_cost == 25.0
+        assert profile.compatible_printers == ["Printer A", "Printer B"]
+        assert profile.get("filament_type") == "PLA"
+
+    def test_load_profile_errors(self, tmp_path):
+        """Test missing files and malformed JSON are rejected."""
+        broken = tmp_path / "broken.json"
+        broken.write_text("{not json")
+
+        with pytest.raises(FileNotFoundError):
+            load_profile(str(tmp_path / "missing.json"))
+        with pytest.raises(ValueError):
+            load_profile(str(broken))
+
+
+class TestResolveProfile:
+    """Tests for inheritance chain resolution."""
+
+    def test_resolve_merges_inherited_settings(self, tmp_path):
+        """Test that parent values fill in settings the leaf does not override."""
+        system_dir = tmp_path / "system" / "machine"
+        system_dir.mkdir(parents=True)
+        user_dir = tmp_path / "user"
+        user_dir.mkdir()
+        _write_profile(
+            system_dir / "common.json",
+            {
+                "type": "machine",
+                "name": "fdm_common",
+                "instantiation": "false",
+                "nozzle_diameter": ["0.4"],
+                "printable_area": ["0x0", "300x0", "300x300", "0x300"],
+                "printable_height": "300",
+            },
+        )
+        leaf = _write_profile(
+            user_dir / "my_printer.json",
+            {"name": "My Printer", "inherits": "fdm_common", "printable_height": "250"},
+        )
+
+        profile = resolve_profile(leaf, [str(tmp_path / "system")])
+
+        assert profile.inheritance_chain == ["My Printer", "fdm_common"]
+        assert profile.bed_size == (300.0, 300.0)
+        assert profile.printable_height == 250.0
+        assert profile.profile_type == "machine"
+        assert profile.instantiation is True
+
+    def test_grandparent_found_beside_its_child(self, tmp_path):
+        """Test each ancestor's sibling file is looked for in that ancestor's directory."""
+        vendor_dir = tmp_path / "system" / "Vendor"
+        vendor_dir.mkdir(parents=True)
+        user_dir = tmp_path / "user"
+        user_dir.mkdir()
+        _write_profile(vendor_dir / "base.json", {"name": "base", "printable_height": "300"})
+        _write_profile(vendor_dir / "mid.json", {"name": "mid", "inherits": "base"})
+        # Beside the leaf, but not the base its parent means
+        _write_profile(user_dir / "base.json", {"name": "base", "printable_height": "100"})
+        leaf = _write_profile(user_dir / "leaf.json", {"name": "Leaf", "inherits": "mid"})
+
+        profile = resolve_profile(leaf, [str(tmp_path / "system")])
+
+        assert profile.inheritance_chain == ["Leaf", "mid", "base"]
+        assert profile.printable_height == 300.0
+
+    def test_resolve_missing_parent(self, tmp_path):
+        """Test that an unknown parent profile raises FileNotFoundError."""
+        leaf = _write_profile(
+            tmp_path / "orphan.json", {"name": "Orphan", "inherits": "does_not_exist"}
+        )
+
+        with pytest.raises(FileNotFoundError):
+            resolve_profile(leaf, [str(tmp_path)])
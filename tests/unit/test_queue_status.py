@@ -0,0 +1,49 @@
+"""Unit tests for queue status and wait estimation.
+
+Focus: Test the wait estimate from job counts and recent slice durations.
+"""
+
+import asyncio
+
+from orca_quote_machine._rust_core import (
+    acquire_slicer_slot,
+    queue_status,
+    record_quote_metric,
+    set_slicer_concurrency,
+)
+
+
+class TestQueueStatus:
+    """Tests for queue_status."""
+
+    def test_wait_from_reported_counts_and_durations(self):
+        """Test each full round of jobs ahead adds one average slice before the new job's own."""
+        status = queue_status(
+            queued=3, running=2, parallelism=2, recent_slice_seconds=[50.0, 70.0]
+        )
+
+        assert status.avg_slice_seconds == 60.0
+        # 5 jobs ahead on 2 slots: 2 full rounds, then the new job itself.
+        assert status.estimated_wait_seconds == 180.0
+
+        unknown = queue_status(queued=3, recent_slice_seconds=[])
+        assert unknown.avg_slice_seconds is None
+        assert unknown.estimated_wait_seconds is None
+
+    def test_defaults_to_this_process_slots_and_slices(self):
+        """Test counts come from the slicer semaphore and the average from recorded slices."""
+        record_quote_metric("PLA", "success", slice_seconds=30.0)
+        set_slicer_concurrency(1)
+        try:
+
+            async def hold_slot():
+                return await acquire_slicer_slot()
+
+            with asyncio.run(hold_slot()):
+                status = queue_status()
+        finally:
+            set_slicer_concurrency(0)
+
+        assert (status.queued, status.running, status.parallelism) == (0, 1, 1)
+        assert status.avg_slice_seconds is not None
+        assert status.estimated_wait_seconds == 2 * status.avg_slice_seconds
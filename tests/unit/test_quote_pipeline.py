@@ -0,0 +1,1298 @@
+"""Unit tests for the Rust quote pipeline.
+
+Focus: Test printer selection, slicing, and pricing end to end against a stub slicer.
+"""
+
+import asyncio
+import base64
+import gzip
+import json
+import math
+import os
+import stat
+import struct
+import threading
+import time
+import zipfile
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    acquire_slicer_slot,
+    create_cancellation_token,
+    create_inventory,
+    create_malware_scanner,
+    create_pipeline_config,
+    create_postprocess_config,
+    create_print_history,
+    create_quote_store,
+    create_slicer_retry_policy,
+    create_time_of_use_pricing,
+    create_validation_limits,
+    execute_slicer_async,
+    export_job_bundle,
+    export_schemas,
+    init_json_logging,
+    plan_plates,
+    quote_materials,
+    requote,
+    run_order_pipeline,
+    run_quote_pipeline,
+    set_slicer_concurrency,
+    split_obj_objects,
+    to_dict,
+)
+
+STUB_SLICER = """#!/bin/sh
+# Write a G-code file into the directory passed after --outputdir.
+while [ $# -gt 0 ]; do
+    if [ "$1" = "--outputdir" ]; then out="$2"; fi
+    shift
+done
+printf '; estimated printing time = 2h 0m\\n; filament used [g] = 100.0\\n' > "$out/plate_1.gcode"
+"""
+
+
+@pytest.fixture
+def profiles_dir(tmp_path):
+    """Profile tree with one machine, one process, and a PLA filament."""
+    root = tmp_path / "profiles"
+    for profile_type in ("machine", "process", "filament"):
+        (root / profile_type).mkdir(parents=True)
+    (root / "machine" / "printer.json").write_text(
+        json.dumps(
+            {
+                "type": "machine",
+                "name": "Printer",
+                "printable_area": ["0x0", "200x0", "200x200", "0x200"],
+                "printable_height": "200",
+            }
+        )
+    )
+    (root / "process" / "standard.json").write_text(json.dumps({"type": "process"}))
+    (root / "filament" / "pla.json").write_text(json.dumps({"filament_type": ["PLA"]}))
+    return root
+
+
+def _write_stub_slicer(path, script: str = STUB_SLICER) -> str:
+    path.write_text(script)
+    path.chmod(path.stat().st_mode | stat.S_IEXEC)
+    return str(path)
+
+
+def _running(pid: int) -> bool:
+    """Whether `pid` is alive after a moment; a zombie awaiting its reaper is not."""
+    time.sleep(0.1)
+    try:
+        with open(f"/proc/{pid}/stat") as stat_file:
+            return stat_file.read().rsplit(")", 1)[1].split()[0] != "Z"
+    except FileNotFoundError:
+        return False
+
+
+def _write_model(path) -> str:
+    path.write_text(
+        "solid cube\n  facet normal 0 0 1\n    outer loop\n"
+        "      vertex 0 0 0\n      vertex 20 0 0\n      vertex 0 20 10\n"
+        "    endloop\n  endfacet\nendsolid cube\n"
+    )
+    return str(path)
+
+
+class TestRunQuotePipeline:
+    """Tests for run_quote_pipeline."""
+
+    def test_quote_records_selected_printer(self, tmp_path, profiles_dir):
+        """Test the fleet printer, profiles, slicing output, and price are recorded."""
+        fleet = tmp_path / "fleet.toml"
+        fleet.write_text('[[printer]]\nname = "Bench"\nmachine = "printer.json"\n')
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            fleet_path=str(fleet),
+            material_prices={"PLA": 20.0},
+            work_dir=str(tmp_path / "work"),
+        )
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "pla+", config)
+
+        assert quote.printer == "Bench"
+        assert quote.material == "PLA"
+        assert quote.dimensions == (20.0, 20.0, 10.0)
+        assert quote.filament_profile == str(profiles_dir / "filament" / "pla.json")
+        assert quote.process_profile == str(profiles_dir / "process" / "standard.json")
+        assert quote.slicing.print_time_minutes == 120
+        assert quote.cost.price_per_kg == 20.0
+        assert list((tmp_path / "work").iterdir()) == []
+
+    def test_validation_limits_refuse_model(self, tmp_path, profiles_dir):
+        """Test a model over the configured limits is refused before it is sliced."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            validation_limits=create_validation_limits(max_lines=5),
+        )
+
+        with pytest.raises(ValueError, match="Model has 9 lines, over the limit of 5"):
+            run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+        assert config.validation_limits.max_triangles == 20_000_000
+
+    def test_infected_upload_refused(self, tmp_path, profiles_dir):
+        """Test an upload the malware scanner flags is refused before it is validated."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            malware_scanner=create_malware_scanner(
+                command=["sh", "-c", 'echo "$0: Eicar-Signature FOUND"; exit 1']
+            ),
+        )
+
+        with pytest.raises(ValueError, match="cube.stl is infected: Eicar-Signature"):
+            run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+    def test_mesh_formats_quoted(self, tmp_path, profiles_dir):
+        """Test 3MF, AMF and PLY models are validated and sized from their meshes, in mm."""
+        model = tmp_path / "part.3mf"
+        with zipfile.ZipFile(model, "w") as archive:
+            archive.writestr(
+                "[Content_Types].xml",
+                '<Types><Default Extension="model" '
+                'ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/></Types>',
+            )
+            archive.writestr(
+                "3D/3dmodel.model",
+                '<model unit="centimeter"><resources><object id="1"><mesh><vertices>'
+                '<vertex x="0" y="0" z="0"/><vertex x="2" y="0" z="0"/>'
+                '<vertex x="0" y="2" z="1"/></vertices><triangles>'
+                '<triangle v1="0" v2="1" v3="2"/></triangles></mesh></object></resources>'
+                '<build><item objectid="1"/></build></model>',
+            )
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+        )
+
+        amf = tmp_path / "part.amf"
+        amf.write_bytes(
+            gzip.compress(
+                b'<amf unit="centimeter"><object id="0"><mesh><vertices>'
+                b"<vertex><coordinates><x>0</x><y>0</y><z>0</z></coordinates></vertex>"
+                b"<vertex><coordinates><x>2</x><y>0</y><z>0</z></coordinates></vertex>"
+                b"<vertex><coordinates><x>0</x><y>2</y><z>1</z></coordinates></vertex>"
+                b"</vertices><volume><triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle>"
+                b"</volume></mesh></object></amf>"
+            )
+        )
+
+        ply = tmp_path / "part.ply"
+        ply.write_bytes(
+            b"ply\nformat binary_little_endian 1.0\nelement vertex 3\n"
+            b"property float x\nproperty float y\nproperty float z\n"
+            b"element face 1\nproperty list uchar uint vertex_indices\nend_header\n"
+            + struct.pack("<9fB3I", 0, 0, 0, 20, 0, 0, 0, 20, 10, 3, 0, 1, 2)
+        )
+
+        for path, file_type in ((model, "3mf"), (amf, "amf"), (ply, "ply")):
+            quote = run_quote_pipeline(str(path), "PLA", config)
+            assert quote.model.file_type == file_type
+            assert quote.dimensions == (20.0, 20.0, 10.0)
+
+    def test_zipped_model_quoted(self, tmp_path, profiles_dir):
+        """Test the model is taken out of a ZIP upload and its entry recorded."""
+        upload = tmp_path / "upload.zip"
+        with zipfile.ZipFile(upload, "w", zipfile.ZIP_DEFLATED) as archive:
+            archive.writestr("notes.txt", "two copies please")
+            archive.write(_write_model(tmp_path / "cube.stl"), "export/cube.stl")
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+            work_dir=str(tmp_path / "work"),
+        )
+
+        quote = run_quote_pipeline(str(upload), "PLA", config)
+
+        assert quote.model.file_type == "stl"
+        assert quote.model.archive_entry == "export/cube.stl"
+        assert quote.dimensions == (20.0, 20.0, 10.0)
+        assert list((tmp_path / "work").iterdir()) == []
+
+    def test_open_mesh_refused_when_watertight_required(self, tmp_path, profiles_dir):
+        """Test a single open triangle is refused before slicing when watertight meshes are required."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+            require_watertight=True,
+        )
+
+        with pytest.raises(ValueError, match=r"Mesh is not watertight: 1 hole \(3 open edges\)"):
+            run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+    def test_repaired_mesh_quoted(self, tmp_path, profiles_dir):
+        """Test repair closes an open mesh so a watertight-only config still quotes it."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+            work_dir=str(tmp_path / "work"),
+            require_watertight=True,
+        )
+
+        # A tetrahedron missing its base
+        model = tmp_path / "open.stl"
+        facets = [
+            ((0, 0, 0), (10, 0, 0), (0, 0, 10)),
+            ((10, 0, 0), (0, 10, 0), (0, 0, 10)),
+            ((0, 0, 0), (0, 0, 10), (0, 10, 0)),
+        ]
+        model.write_text(
+            "solid open\n"
+            + "".join(
+                "facet normal 0 0 0\nouter loop\n"
+                + "".join(f"vertex {x} {y} {z}\n" for x, y, z in facet)
+                + "endloop\nendfacet\n"
+                for facet in facets
+            )
+            + "endsolid open\n"
+        )
+        with pytest.raises(ValueError, match="Mesh is not watertight"):
+            run_quote_pipeline(str(model), "PLA", config)
+
+        quote = run_quote_pipeline(str(model), "PLA", config, repair=True)
+
+        assert "repair" in quote.stage_timings_ms
+        assert quote.model.file_type == "stl"
+        assert list((tmp_path / "work").iterdir()) == []
+
+    def test_tall_model_warns_of_tipping(self, tmp_path, profiles_dir):
+        """Test a narrow pillar's quote recommends a raft and warns the operator."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+        )
+        # A 10 x 10 x 60 mm pillar, faces wound outwards
+        model = tmp_path / "pillar.obj"
+        model.write_text(
+            "v 0 0 0\nv 10 0 0\nv 10 10 0\nv 0 10 0\n"
+            "v 0 0 60\nv 10 0 60\nv 10 10 60\nv 0 10 60\n"
+            "f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 4 8 7 3\nf 1 5 8 4\nf 2 3 7 6\n"
+        )
+
+        quote = run_quote_pipeline(str(model), "PLA", config)
+
+        assert quote.stability.recommendation == "raft"
+        assert quote.stability.center_of_mass_mm == pytest.approx((5.0, 5.0, 30.0))
+        assert quote.warnings == [quote.stability.warning]
+        assert set(quote.stage_timings_ms) >= {"validation", "slicing", "pricing"}
+
+    def test_obj_materials_warned_as_ignored(self, tmp_path, profiles_dir):
+        """Test a coloured OBJ's quote warns that its materials are not used."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+        )
+        # A 20 mm cube with red sides and a missing library
+        model = tmp_path / "cube.obj"
+        model.write_text(
+            "mtllib cube.mtl\n"
+            "v 0 0 0\nv 20 0 0\nv 20 20 0\nv 0 20 0\n"
+            "v 0 0 20\nv 20 0 20\nv 20 20 20\nv 0 20 20\n"
+            "usemtl red\nf 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 4 8 7 3\nf 1 5 8 4\nf 2 3 7 6\n"
+        )
+
+        quote = run_quote_pipeline(str(model), "PLA", config)
+
+        assert quote.model.obj_materials.missing_libraries == ["cube.mtl"]
+        assert quote.warnings == [quote.model.obj_materials.warning]
+
+    def test_oversized_model_refused_before_slicing(self, tmp_path, profiles_dir):
+        """Test a model larger than the machine's build volume never reaches the slicer."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh", "#!/bin/sh\nexit 1\n"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+        )
+        model = tmp_path / "long.stl"
+        model.write_text(
+            "solid long\nfacet normal 0 0 1\nouter loop\n"
+            "vertex 0 0 0\nvertex 250 0 0\nvertex 0 20 10\n"
+            "endloop\nendfacet\nendsolid long\n"
+        )
+
+        with pytest.raises(ValueError, match="larger than the Printer build volume of 200.0 x 200.0"):
+            run_quote_pipeline(str(model), "PLA", config)
+
+    def test_ascii_stl_sliced_from_binary_copy(self, tmp_path, profiles_dir):
+        """Test an ASCII STL is converted in the workspace before slicing when asked."""
+        # Fails unless the slicer is handed a binary STL (84-byte header, one facet)
+        slicer = STUB_SLICER.replace(
+            "while", '[ "$(wc -c < "$1")" -eq 134 ] || exit 1\nwhile', 1
+        )
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh", slicer),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+            work_dir=str(tmp_path / "work"),
+            convert_ascii_stl=True,
+        )
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        assert "conversion" in quote.stage_timings_ms
+        assert quote.dimensions == (20.0, 20.0, 10.0)
+
+    def test_dense_mesh_sliced_from_decimated_copy(self, tmp_path, profiles_dir):
+        """Test a mesh over max_triangles is simplified in the workspace before slicing."""
+        # Fails unless the slicer is handed a binary STL of at most 200 facets
+        slicer = STUB_SLICER.replace(
+            "while", '[ "$(wc -c < "$1")" -le 10084 ] || exit 1\nwhile', 1
+        )
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh", slicer),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+            work_dir=str(tmp_path / "work"),
+            max_triangles=200,
+        )
+
+        # A 20 mm sphere of 3120 facets
+        def point(ring, step):
+            polar, azimuth = math.pi * ring / 40, 2 * math.pi * step / 40
+            return (
+                10 * math.sin(polar) * math.cos(azimuth),
+                10 * math.sin(polar) * math.sin(azimuth),
+                10 * math.cos(polar),
+            )
+
+        facets = []
+        for ring in range(40):
+            for step in range(40):
+                a, b = point(ring, step), point(ring, step + 1)
+                c, d = point(ring + 1, step), point(ring + 1, step + 1)
+                facets += [(a, c, b)] if ring > 0 else []
+                facets += [(b, c, d)] if ring < 39 else []
+        model = tmp_path / "scan.stl"
+        model.write_bytes(
+            bytes(80)
+            + struct.pack("<I", len(facets))
+            + b"".join(struct.pack("<12fH", 0, 0, 0, *a, *b, *c, 0) for a, b, c in facets)
+        )
+
+        quote = run_quote_pipeline(str(model), "PLA", config)
+
+        assert "decimation" in quote.stage_timings_ms
+        assert quote.model.file_type == "stl"
+
+        with pytest.raises(ValueError, match="max_triangles"):
+            create_pipeline_config(
+                "slicer", str(profiles_dir), "printer.json", "standard.json", max_triangles=2
+            )
+
+    def test_off_peak_price_next_to_anytime_price(self, tmp_path, profiles_dir):
+        """Test an always-open half-rate window halves the print time's cost."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+            time_of_use=create_time_of_use_pricing([(0, 24, 0.5)]),
+        )
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        # Two hours of printing at half of S$20/h, with the 10% markup
+        assert quote.off_peak.rate_factor == 0.5
+        assert quote.off_peak.savings == pytest.approx(2 * 10.0 * 1.1)
+        assert quote.off_peak.total_cost == pytest.approx(quote.cost.total_cost - 22.0)
+        assert to_dict(quote)["off_peak"]["start_time"] == quote.off_peak.start_time
+
+    def test_estimates_calibrated_from_print_history(self, tmp_path, profiles_dir):
+        """Test slicing estimates are scaled by past prints before pricing."""
+        history = create_print_history(str(tmp_path / "history.jsonl"), min_samples=1)
+        history.record("PLA", 100, 10.0, 150, 10.0)
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            print_history=history,
+        )
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+        history.record_quote(quote, 170, 20.0)
+
+        assert quote.slicing.print_time_minutes == 180
+        assert quote.cost.print_time_minutes == 180
+        assert quote.calibration.slicer_print_time_minutes == 120
+        # Recorded against the slicer's 120 minutes, not the corrected 180
+        assert history.factor("PLA").time_factor == pytest.approx(320 / 220)
+
+    def test_gcode_kept_in_cache(self, tmp_path, profiles_dir):
+        """Test the sliced G-code outlives the workspace under the key returned with the quote."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            gcode_cache_dir=str(tmp_path / "gcode"),
+        )
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        files = config.gcode_cache.get(quote.slicing.gcode_cache_key)
+        assert [os.path.basename(f) for f in files] == ["plate_1.gcode"]
+        assert "estimated printing time = 2h 0m" in open(files[0]).read()
+
+    def test_gcode_postprocessed_before_caching(self, tmp_path, profiles_dir):
+        """Test the cached G-code is the processed file and the quote lists the transforms."""
+        cache_dir = tmp_path / "cache"
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            gcode_cache_dir=str(cache_dir),
+            postprocess=create_postprocess_config(start_gcode="G28", end_gcode="M84"),
+        )
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        assert quote.gcode_transforms == ["start_gcode", "end_gcode"]
+        assert quote.stage_timings_ms["postprocess"] >= 0
+        cached = cache_dir / quote.slicing.gcode_cache_key / "plate_1.gcode"
+        assert cached.read_text().splitlines()[-2:] == ["G28", "M84"]
+        assert quote.slicing.print_time_minutes == 120
+
+    def test_weight_from_catalog_density(self, tmp_path, profiles_dir):
+        """Test a filament length is weighed with the catalog's density when the profile has none."""
+        slicer = _write_stub_slicer(
+            tmp_path / "slicer.sh",
+            STUB_SLICER.replace("; filament used [g] = 100.0", "; filament used [mm] = 1000.0"),
+        )
+        config = create_pipeline_config(slicer, str(profiles_dir), "printer.json", "standard.json")
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        # 1 m of 1.75 mm filament is ~2.405 cm³, at PLA's 1.24 g/cm³
+        assert quote.slicing.filament_weight_grams == pytest.approx(2.982, rel=1e-3)
+
+    def test_slicer_failure_raises(self, tmp_path, profiles_dir):
+        """Test a failing slicer surfaces its stderr as a RuntimeError."""
+        failing = _write_stub_slicer(
+            tmp_path / "slicer.sh", "#!/bin/sh\necho 'bad model' >&2\nexit 1\n"
+        )
+        config = create_pipeline_config(
+            failing, str(profiles_dir), "printer.json", "standard.json"
+        )
+
+        with pytest.raises(RuntimeError, match="bad model"):
+            run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+    def test_hung_slicer_killed_after_timeout(self, tmp_path, profiles_dir):
+        """Test a slicer past its timeout is killed with its children and raises TimeoutError."""
+        child_pid = tmp_path / "child.pid"
+        hung = _write_stub_slicer(
+            tmp_path / "slicer.sh",
+            f"#!/bin/sh\necho 'loading model' >&2\nsleep 30 &\necho $! > {child_pid}\nwait\n",
+        )
+        config = create_pipeline_config(
+            hung,
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            slicer_timeout_secs=0.5,
+        )
+
+        started = time.monotonic()
+        with pytest.raises(TimeoutError, match="within 0.5s: loading model"):
+            run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        assert time.monotonic() - started < 10
+        assert not _running(int(child_pid.read_text()))
+
+    def test_slicer_output_streamed(self, tmp_path, profiles_dir):
+        """Test on_slicer_output sees the slicer's lines, and a failing callback is dropped."""
+        chatty = _write_stub_slicer(
+            tmp_path / "slicer.sh",
+            STUB_SLICER + "echo 'Slicing plate 1' >&2\necho 'Exporting G-code' >&2\n",
+        )
+        config = create_pipeline_config(
+            chatty, str(profiles_dir), "printer.json", "standard.json"
+        )
+        lines = []
+
+        def broken(line):
+            raise ValueError("callback bug")
+
+        run_quote_pipeline(
+            _write_model(tmp_path / "cube.stl"), "PLA", config, on_slicer_output=lines.append
+        )
+        quote = run_quote_pipeline(
+            _write_model(tmp_path / "cube.stl"), "PLA", config, on_slicer_output=broken
+        )
+
+        assert lines == ["Slicing plate 1", "Exporting G-code"]
+        assert quote.slicing.print_time_minutes == 120
+
+    def test_out_of_stock_material_refused(self, tmp_path, profiles_dir):
+        """Test stock is checked before slicing, then against the sliced weight."""
+        stock = tmp_path / "stock.toml"
+        stock.write_text(
+            '[[stock]]\nmaterial = "PETG"\nremaining_grams = 0\n'
+            '[[stock]]\nmaterial = "PLA"\nremaining_grams = 10\n'
+        )
+        failing = _write_stub_slicer(tmp_path / "failing.sh", "#!/bin/sh\nexit 1\n")
+        model = _write_model(tmp_path / "cube.stl")
+
+        def config(slicer):
+            return create_pipeline_config(
+                slicer,
+                str(profiles_dir),
+                "printer.json",
+                "standard.json",
+                inventory=create_inventory(table_path=str(stock)),
+            )
+
+        # The failing slicer is never run
+        with pytest.raises(ValueError, match="PETG out of stock"):
+            run_quote_pipeline(model, "PETG", config(failing))
+        slicer = _write_stub_slicer(tmp_path / "slicer.sh")
+        with pytest.raises(ValueError, match="20 g needed, 10 g left"):
+            run_quote_pipeline(model, "PLA", config(slicer))
+
+    def test_material_process_mapping_overrides_default(self, tmp_path, profiles_dir):
+        """Test a process mapped to the material replaces the default process."""
+        (profiles_dir / "process" / "slow.json").write_text(json.dumps({"type": "process"}))
+        mapping = tmp_path / "materials.toml"
+        mapping.write_text('[process]\ntpu = "slow.json"\n')
+        (profiles_dir / "filament" / "tpu.json").write_text(
+            json.dumps({"filament_type": ["TPU"]})
+        )
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_map=str(mapping),
+        )
+
+        tpu = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "TPU", config)
+        pla = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        assert tpu.process_profile == str(profiles_dir / "process" / "slow.json")
+        assert pla.process_profile == str(profiles_dir / "process" / "standard.json")
+
+    def test_print_options_change_the_slice(self, tmp_path, profiles_dir):
+        """Test options reach the slicer's process profile and are echoed with the quote."""
+        seen = tmp_path / "process-seen.json"
+        # Keep the process profile the slicer was given; coarse layers print in half the time
+        slicer = _write_stub_slicer(tmp_path / "slicer.sh", f"""#!/bin/sh
+while [ $# -gt 0 ]; do
+    if [ "$1" = "--outputdir" ]; then out="$2"; fi
+    if [ "$1" = "--load-settings" ]; then process="${{2#*;}}"; fi
+    shift
+done
+cp "$process" {seen}
+hours=2; if grep -q '"0.28"' "$process"; then hours=1; fi
+printf "; estimated printing time = ${{hours}}h 0m\\n" > "$out/plate_1.gcode"
+""")
+        config = create_pipeline_config(slicer, str(profiles_dir), "printer.json", "standard.json")
+        model = _write_model(tmp_path / "cube.stl")
+
+        standard = run_quote_pipeline(model, "PLA", config)
+        draft = run_quote_pipeline(
+            model,
+            "PLA",
+            config,
+            options={"layer_height": 0.28, "infill_percent": 10, "supports": True, "color": "red"},
+        )
+
+        process = json.loads(seen.read_text())
+        assert (process["layer_height"], process["sparse_infill_density"]) == ("0.28", "10%")
+        assert process["enable_support"] == "1"
+        assert (standard.slicing.print_time_minutes, draft.slicing.print_time_minutes) == (120, 60)
+        assert draft.cost.total_cost < standard.cost.total_cost
+        assert (draft.options.layer_height, draft.options.color) == (0.28, "Red")
+        assert standard.options.layer_height is None
+        assert draft.process_profile == str(profiles_dir / "process" / "standard.json")
+        with pytest.raises(ValueError, match="not stocked in PLA"):
+            run_quote_pipeline(model, "PLA", config, options={"color": "Chartreuse"})
+        with pytest.raises(ValueError, match="unknown print option 'wall_loops'"):
+            run_quote_pipeline(model, "PLA", config, options={"wall_loops": 3})
+
+    def test_quote_reports_stage_timings(self, tmp_path, profiles_dir):
+        """Test each pipeline stage records a non-negative duration."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+        )
+
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+
+        assert set(quote.stage_timings_ms) == {
+            "validation",
+            "profile_selection",
+            "slicer_queue",
+            "slicing",
+            "parsing",
+            "pricing",
+        }
+        assert all(ms >= 0 for ms in quote.stage_timings_ms.values())
+
+    def test_preview_prefers_gcode_thumbnail_over_mesh(self, tmp_path, profiles_dir):
+        """Test the preview is the slicer's thumbnail when present, else a mesh render."""
+        model = _write_model(tmp_path / "cube.stl")
+        plain = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            preview_size=64,
+        )
+
+        rendered = run_quote_pipeline(model, "PLA", plain)
+
+        assert rendered.preview_source == "mesh"
+        assert rendered.preview_png.startswith(b"\x89PNG\r\n\x1a\n")
+        assert int.from_bytes(rendered.preview_png[16:20], "big") == 64
+        assert "preview" in rendered.stage_timings_ms
+
+        thumbnail = base64.b64encode(rendered.preview_png).decode()
+        lines = [thumbnail[i : i + 78] for i in range(0, len(thumbnail), 78)]
+        block = "\\n".join(
+            ["; thumbnail begin 64x64 %d" % len(thumbnail)]
+            + [f"; {line}" for line in lines]
+            + ["; thumbnail end"]
+        )
+        with_thumbnail = create_pipeline_config(
+            _write_stub_slicer(
+                tmp_path / "thumb.sh",
+                STUB_SLICER.replace("printf '", f"printf '{block}\\n"),
+            ),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            preview_size=300,
+        )
+
+        embedded = run_quote_pipeline(model, "PLA", with_thumbnail)
+
+        assert embedded.preview_source == "gcode"
+        assert embedded.preview_png == rendered.preview_png
+
+    def test_quote_serializes_with_every_schema_field(self, tmp_path, profiles_dir):
+        """Test to_dict keeps every field, recursively, under its schema name."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+        )
+        quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+        schema = export_schemas()["QuoteResult"]
+
+        data = json.loads(json.dumps(to_dict(quote)))
+
+        assert set(data) == set(schema["properties"])
+        for field in ("model", "slicing", "cost", "options"):
+            ref = schema["properties"][field]["$ref"].rsplit("/", 1)[1]
+            assert set(data[field]) == set(schema["$defs"][ref]["properties"]), field
+        assert data["cost"]["total_cost"] == quote.cost.total_cost
+        assert data["dimensions"] == [20.0, 20.0, 10.0]
+        assert data["stage_timings_ms"] == quote.stage_timings_ms
+        assert data["lead_time"] is None
+        assert data["preview_png"] is None
+        with pytest.raises(TypeError, match="has no schema"):
+            to_dict(object())
+
+
+class TestQuoteStore:
+    """Tests for QuoteStore and manual price adjustments."""
+
+    def test_adjustments_kept_as_line_items(self, tmp_path, profiles_dir):
+        """Test a stored quote is revised by delta and new total, each audited."""
+        audit_log = tmp_path / "audit.jsonl"
+        store = create_quote_store(str(tmp_path / "quotes"), audit_log_path=str(audit_log))
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            quote_store=store,
+        )
+        quote = run_quote_pipeline(
+            _write_model(tmp_path / "cube.stl"), "PLA", config, quote_id="q-1"
+        )
+
+        discounted = store.apply_manual_adjustment("q-1", "repeat customer", delta=-2.5)
+        revised = store.apply_manual_adjustment(
+            "q-1", "rush job", new_total=80.0, operator="ops"
+        )
+
+        assert store.get("q-1").cost.total_cost == revised.cost.total_cost == 80.0
+        assert discounted.cost.total_cost == pytest.approx(quote.cost.total_cost - 2.5)
+        assert [(a.reason, a.operator) for a in revised.adjustments] == [
+            ("repeat customer", None),
+            ("rush job", "ops"),
+        ]
+        assert revised.adjustments[1].amount == pytest.approx(80.0 - discounted.cost.total_cost)
+        assert revised.cost.subtotal == quote.cost.subtotal
+        assert revised.slicing.print_time_minutes == quote.slicing.print_time_minutes
+        records = [json.loads(line) for line in audit_log.read_text().splitlines()]
+        assert [(r["event"], r["total_after"]) for r in records][-1] == ("quote_adjusted", 80.0)
+
+    def test_adjustment_clears_payment_link_and_off_peak(self, tmp_path, profiles_dir):
+        """Test an adjusted quote drops the checkout link and off-peak price of its old total."""
+        store = create_quote_store(str(tmp_path / "quotes"))
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+            time_of_use=create_time_of_use_pricing([(0, 24, 0.5)]),
+            preview_size=64,
+            quote_store=store,
+        )
+        quote = run_quote_pipeline(
+            _write_model(tmp_path / "cube.stl"), "PLA", config, quote_id="q-1"
+        )
+        quote.payment_url = "https://checkout.example/old"
+        store.save("q-1", quote)
+        assert store.get("q-1").off_peak is not None
+
+        revised = store.apply_manual_adjustment("q-1", "rush job", delta=5.0)
+
+        assert revised.payment_url is None and revised.off_peak is None
+        assert store.get("q-1").payment_url is None and store.get("q-1").off_peak is None
+        assert sorted(os.listdir(tmp_path / "quotes")) == ["q-1.json", "q-1.png"]
+
+    def test_bad_adjustments_rejected(self, tmp_path):
+        """Test unknown quotes, unsafe IDs and ambiguous or negative changes raise."""
+        store = create_quote_store(str(tmp_path / "quotes"))
+
+        with pytest.raises(FileNotFoundError, match="no stored quote"):
+            store.apply_manual_adjustment("missing", "typo", delta=1.0)
+        with pytest.raises(ValueError, match="not a quote ID"):
+            store.get("../escape")
+        with pytest.raises(ValueError, match="either delta or new_total"):
+            store.apply_manual_adjustment("q-1", "typo", delta=1.0, new_total=5.0)
+        with pytest.raises(ValueError, match="reason"):
+            store.apply_manual_adjustment("q-1", " ", delta=1.0)
+        assert store.get("q-1") is None
+
+    def test_job_bundle_holds_gcode_sheet_preview_and_manifest(self, tmp_path, profiles_dir):
+        """Test an accepted quote is zipped with its G-code, job sheet, preview and manifest."""
+        store = create_quote_store(str(tmp_path / "quotes"))
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            gcode_cache_dir=str(tmp_path / "gcode"),
+            preview_size=64,
+            quote_store=store,
+        )
+        run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config, quote_id="q-1")
+        store.apply_manual_adjustment("q-1", "<rush> job", delta=5.0)
+
+        path = export_job_bundle("q-1", config)
+
+        assert path == str(tmp_path / "quotes" / "q-1.zip")
+        with zipfile.ZipFile(path) as bundle:
+            names = bundle.namelist()
+            manifest = json.loads(bundle.read("manifest.json"))
+            sheet = bundle.read("job_sheet.html").decode()
+            gcode = bundle.read("gcode/plate_1.gcode")
+            preview = bundle.read("preview.png")
+        assert names == ["gcode/plate_1.gcode", "preview.png", "job_sheet.html", "manifest.json"]
+        assert gcode.startswith(b"; estimated printing time = 2h 0m")
+        assert preview.startswith(b"\x89PNG")
+        assert "PLA" in sheet and "2h 0m" in sheet and '<img src="preview.png"' in sheet
+        assert "&lt;rush&gt; job" in sheet and "<rush>" not in sheet
+        assert manifest["quote_id"] == "q-1"
+        assert manifest["quote"]["cost"]["total_cost"] == store.get("q-1").cost.total_cost
+        assert [f["name"] for f in manifest["files"]] == names[:3]
+        assert manifest["files"][0]["bytes"] == len(gcode)
+        with pytest.raises(FileNotFoundError, match="no stored quote"):
+            export_job_bundle("missing", config)
+
+
+class TestRunOrderPipeline:
+    """Tests for run_order_pipeline."""
+
+    def test_order_totals_count_setup_once(self, tmp_path, profiles_dir):
+        """Test copies share a plate's overhead and setup is charged once at the dearest rate."""
+        (profiles_dir / "filament" / "tpu.json").write_text(
+            json.dumps({"filament_type": ["TPU"]})
+        )
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0, "TPU": 30.0},
+        )
+        bracket = _write_model(tmp_path / "bracket.stl")
+        gasket = _write_model(tmp_path / "gasket.stl")
+
+        order = run_order_pipeline([(bracket, 3, None), (gasket, 1, "tpu")], config)
+
+        pla, tpu = order.parts
+        kg = pla.quote.slicing.filament_weight_grams / 1000
+        # (filament kg + 2 h) x price x 1.1 markup, without the 0.5 h setup
+        pla_unit, tpu_unit = (kg * 20.0 + 2 * 20.0) * 1.1, (kg * 30.0 + 2 * 30.0) * 1.1
+        # Three 20 mm cubes share one 200 mm plate and its 10 minute overhead
+        pla_line = (3 * kg * 20.0 + 340 / 60 * 20.0) * 1.1
+        assert pla.unit_cost == pytest.approx(pla_unit)
+        assert (pla.plating.plates, pla.print_time_minutes) == (1, 10 + 3 * 110)
+        assert pla.line_total == pytest.approx(pla_line)
+        assert tpu.quote.material == "TPU" and tpu.line_total == pytest.approx(tpu_unit)
+        assert pla.quote.cost.total_cost == pytest.approx(pla_unit + 0.5 * 20.0 * 1.1)
+        assert order.setup_cost == pytest.approx(0.5 * 30.0 * 1.1)
+        assert order.total_cost == pytest.approx(pla_line + tpu_unit + 0.5 * 30.0 * 1.1)
+        assert (order.total_quantity, order.print_time_minutes) == (4, 340 + 120)
+        assert order.filament_grams == pytest.approx(4 * kg * 1000)
+        assert not order.minimum_applied
+
+    def test_obj_objects_quoted_as_line_items(self, tmp_path, profiles_dir):
+        """Test the objects of a multi-object OBJ can be split and quoted as separate parts."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0},
+        )
+        model = tmp_path / "kit.obj"
+        model.write_text(
+            "v 0 0 0\nv 20 0 0\nv 0 20 10\nv 40 0 0\nv 60 0 0\nv 40 20 10\n"
+            "o Bracket\nf 1 2 3\no Gasket\nf 4 5 6\n"
+        )
+
+        parts = split_obj_objects(str(model), str(tmp_path / "parts"))
+        order = run_order_pipeline([(parts[0], 2, None), (parts[1], 1, None)], config)
+
+        assert [part.model_path for part in order.parts] == parts
+        assert [part.quote.dimensions for part in order.parts] == [(20.0, 20.0, 10.0)] * 2
+        assert order.total_quantity == 3
+
+    def test_bad_parts_rejected(self, tmp_path, profiles_dir):
+        """Test empty orders and zero quantities are refused and a failing part is named."""
+        model = _write_model(tmp_path / "cube.stl")
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+        )
+
+        with pytest.raises(ValueError, match="no parts"):
+            run_order_pipeline([], config)
+        with pytest.raises(ValueError, match="quantity must be at least 1"):
+            run_order_pipeline([(model, 0, "PLA")], config)
+        with pytest.raises(ValueError, match=r"part 2 \(.*missing.stl\)"):
+            run_order_pipeline([(model, 1, "PLA"), (str(tmp_path / "missing.stl"), 1, "PLA")], config)
+
+
+class TestQuoteMaterials:
+    """Tests for quote_materials."""
+
+    def test_materials_compared_from_one_workspace(self, tmp_path, profiles_dir):
+        """Test each material is sliced once into one job, and unquotable ones are listed."""
+        (profiles_dir / "filament" / "petg.json").write_text(
+            json.dumps({"filament_type": ["PETG"]})
+        )
+        output_dirs = tmp_path / "output-dirs.txt"
+        slicer = _write_stub_slicer(
+            tmp_path / "slicer.sh",
+            STUB_SLICER.replace('> "$out/plate_1.gcode"', f'> "$out/plate_1.gcode"; echo "$out" >> {output_dirs}'),
+        )
+        config = create_pipeline_config(
+            slicer,
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            material_prices={"PLA": 20.0, "PETG": 30.0},
+        )
+        model = _write_model(tmp_path / "cube.stl")
+
+        comparison = quote_materials(model, ["petg", "PLA", "pla+", "ASA"], config)
+
+        assert [row.material for row in comparison.materials] == ["PETG", "PLA"]
+        petg, pla = comparison.materials
+        assert pla.total_cost < petg.total_cost and pla.total_cost == pla.quote.cost.total_cost
+        assert (comparison.cheapest, pla.print_time_minutes) == ("PLA", 120)
+        assert set(comparison.errors) == {"ASA"}
+        dirs = output_dirs.read_text().split()
+        assert len(dirs) == 2 and len({os.path.dirname(d) for d in dirs}) == 1
+        with pytest.raises(FileNotFoundError):
+            quote_materials(model, ["ASA"], config)
+        with pytest.raises(ValueError, match="materials"):
+            quote_materials(model, [], config)
+
+
+class TestRequote:
+    """Tests for requote."""
+
+    def test_stored_gcode_priced_at_new_rates(self, tmp_path, profiles_dir):
+        """Test a cached quote is re-priced with new rates, and the slicer is not run again."""
+        def config(slicer: str, price: float):
+            return create_pipeline_config(
+                slicer,
+                str(profiles_dir),
+                "printer.json",
+                "standard.json",
+                material_prices={"PLA": price},
+                gcode_cache_dir=str(tmp_path / "gcode"),
+            )
+
+        quote = run_quote_pipeline(
+            _write_model(tmp_path / "cube.stl"),
+            "PLA",
+            config(_write_stub_slicer(tmp_path / "slicer.sh"), 20.0),
+        )
+        key = quote.slicing.gcode_cache_key
+        # A slicer that would fail proves only pricing runs
+        discounted = config(str(tmp_path / "missing-slicer"), 10.0)
+
+        again = requote(key, "pla", discounted)
+
+        assert again.material == "PLA" and again.slicing.print_time_minutes == 120
+        assert again.cost.total_cost == pytest.approx(quote.cost.total_cost / 2)
+        with pytest.raises(FileNotFoundError, match="no cached G-code"):
+            requote("0" * 64, "PLA", discounted)
+        uncached = create_pipeline_config("slicer", str(profiles_dir), "printer.json", "standard.json")
+        with pytest.raises(ValueError, match="gcode_cache_dir"):
+            requote(key, "PLA", uncached)
+
+
+class TestPlanPlates:
+    """Tests for plan_plates."""
+
+    def test_copies_fill_plates_in_best_orientation(self):
+        """Test a footprint is turned when that fits more, and leftovers start a new plate."""
+        # 120 x 45 mm on a 250 x 130 bed with 6 mm gaps: 2 x 2 as is, 5 x 1 turned
+        plan = plan_plates(9, (120.0, 45.0, 10.0), (250.0, 130.0), 60)
+
+        assert (plan.copies_per_plate, plan.plates) == (5, 2)
+        assert plan.print_time_minutes == 2 * 10 + 9 * 50
+        # Larger than the bed still prints, one per plate; overhead never exceeds the print
+        big = plan_plates(2, (300.0, 300.0, 10.0), (200.0, 200.0), 5, plate_overhead_minutes=30.0)
+        assert (big.copies_per_plate, big.plates, big.print_time_minutes) == (1, 2, 10)
+        with pytest.raises(ValueError, match="bed_size"):
+            plan_plates(1, (10.0, 10.0, 10.0), (0.0, 200.0), 60)
+
+
+class TestSlicerConcurrency:
+    """Tests for the process-wide slicer semaphore."""
+
+    def test_second_slot_waits_for_release(self):
+        """Test a limit of one makes the next acquirer wait until the slot is released."""
+        set_slicer_concurrency(1)
+        try:
+
+            async def acquire_twice():
+                first = await acquire_slicer_slot()
+                threading.Timer(0.2, first.release).start()
+                with await acquire_slicer_slot() as second:
+                    return first.wait_ms, second.wait_ms
+
+            first_wait, second_wait = asyncio.run(acquire_twice())
+        finally:
+            set_slicer_concurrency(0)
+
+        assert first_wait < 100
+        assert second_wait >= 150
+
+    def test_pipeline_waits_for_slot(self, tmp_path, profiles_dir):
+        """Test the pipeline queues behind a held slot and reports the wait as slicer_queue."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+        )
+        set_slicer_concurrency(1)
+        try:
+
+            async def hold_slot():
+                return await acquire_slicer_slot()
+
+            held = asyncio.run(hold_slot())
+            threading.Timer(0.2, held.release).start()
+            quote = run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config)
+        finally:
+            set_slicer_concurrency(0)
+
+        assert quote.stage_timings_ms["slicer_queue"] >= 150
+        assert quote.slicing.print_time_minutes == 120
+
+
+class TestExecuteSlicerAsync:
+    """Tests for execute_slicer_async."""
+
+    def test_slices_into_output_dir(self, tmp_path):
+        """Test the awaitable runs the slicer with the job's profiles and output directory."""
+        slicer = _write_stub_slicer(tmp_path / "slicer.sh")
+        out = tmp_path / "out"
+        out.mkdir()
+
+        async def run():
+            await execute_slicer_async(
+                slicer, "cube.stl", "printer.json", "standard.json", "pla.json", str(out)
+            )
+
+        asyncio.run(run())
+
+        assert "estimated printing time = 2h 0m" in (out / "plate_1.gcode").read_text()
+
+    def test_failure_and_timeout_raise(self, tmp_path):
+        """Test a failing slicer raises RuntimeError and a hung one TimeoutError."""
+        failing = _write_stub_slicer(
+            tmp_path / "failing.sh", "#!/bin/sh\necho 'bad model' >&2\nexit 1\n"
+        )
+        hung = _write_stub_slicer(tmp_path / "hung.sh", "#!/bin/sh\nsleep 30\n")
+
+        async def run(slicer, **kwargs):
+            await execute_slicer_async(
+                slicer, "cube.stl", "p.json", "s.json", "f.json", str(tmp_path), **kwargs
+            )
+
+        with pytest.raises(RuntimeError, match="bad model"):
+            asyncio.run(run(failing))
+        with pytest.raises(TimeoutError, match="within 0.3s: no output"):
+            asyncio.run(run(hung, timeout_secs=0.3))
+        with pytest.raises(ValueError, match="timeout_secs"):
+            asyncio.run(run(hung, timeout_secs=0))
+
+    def test_output_lines_streamed_to_callback(self, tmp_path):
+        """Test each stdout and stderr line reaches on_output, progress redraws included."""
+        chatty = _write_stub_slicer(
+            tmp_path / "slicer.sh",
+            "#!/bin/sh\necho 'loading model'\nprintf '10%%\\r50%%\\r' >&2\necho 'done' >&2\n",
+        )
+        lines = []
+
+        async def run():
+            await execute_slicer_async(
+                chatty,
+                "cube.stl",
+                "p.json",
+                "s.json",
+                "f.json",
+                str(tmp_path),
+                on_output=lines.append,
+            )
+
+        asyncio.run(run())
+
+        assert "loading model" in lines
+        assert [line for line in lines if line != "loading model"] == ["10%", "50%", "done"]
+
+
+def _crash_once_slicer(tmp_path) -> str:
+    """Stub slicer that dies of SIGSEGV on its first run and slices on the next."""
+    marker = tmp_path / "crashed"
+    crash = f"if [ ! -e {marker} ]; then touch {marker}; kill -SEGV $$; fi\n"
+    return _write_stub_slicer(tmp_path / "flaky.sh", STUB_SLICER.replace("\n", "\n" + crash, 1))
+
+
+class TestSlicerRetry:
+    """Tests for running the slicer again after a crash."""
+
+    def test_crashed_slice_retried_in_pipeline(self, tmp_path, profiles_dir):
+        """Test a slicer killed by a signal is run again, with both runs in the result."""
+        slicer = _crash_once_slicer(tmp_path)
+        model = _write_model(tmp_path / "cube.stl")
+        config = create_pipeline_config(slicer, str(profiles_dir), "printer.json", "standard.json")
+        with pytest.raises(RuntimeError, match="SIGSEGV"):
+            run_quote_pipeline(model, "PLA", config)
+        (tmp_path / "crashed").unlink()
+
+        config = create_pipeline_config(
+            slicer,
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+            slicer_retry=create_slicer_retry_policy(backoff_secs=0.1),
+        )
+        quote = run_quote_pipeline(model, "PLA", config)
+
+        attempts = quote.slicing.slicer_attempts
+        assert [a.attempt for a in attempts] == [1, 2]
+        assert "SIGSEGV" in attempts[0].error
+        assert attempts[1].error is None
+        assert quote.stage_timings_ms["slicing"] >= 100
+        assert to_dict(quote)["slicing"]["slicer_attempts"][1]["attempt"] == 2
+
+    def test_only_crashes_retried(self, tmp_path):
+        """Test a model the slicer rejects fails at once, and a crash up to max_attempts."""
+        runs = tmp_path / "runs"
+        failing = _write_stub_slicer(
+            tmp_path / "failing.sh", f"#!/bin/sh\necho run >> {runs}\necho 'bad model' >&2\nexit 1\n"
+        )
+        crashing = _write_stub_slicer(
+            tmp_path / "crashing.sh",
+            f"#!/bin/sh\necho run >> {runs}\necho 'X Error of failed request' >&2\nexit 1\n",
+        )
+        retry = create_slicer_retry_policy(max_attempts=3, backoff_secs=0)
+
+        async def run(slicer):
+            return await execute_slicer_async(
+                slicer, "cube.stl", "p.json", "s.json", "f.json", str(tmp_path), retry=retry
+            )
+
+        with pytest.raises(RuntimeError, match="bad model$"):
+            asyncio.run(run(failing))
+        assert len(runs.read_text().splitlines()) == 1
+        runs.unlink()
+        with pytest.raises(RuntimeError, match="X Error of failed request \\(after 3 attempts\\)"):
+            asyncio.run(run(crashing))
+        assert len(runs.read_text().splitlines()) == 3
+        attempts = asyncio.run(run(_write_stub_slicer(tmp_path / "slicer.sh")))
+        assert [(a.attempt, a.error) for a in attempts] == [(1, None)]
+
+    def test_policy_validated(self):
+        """Test a policy runs the slicer at least once and never shortens its waits."""
+        with pytest.raises(ValueError, match="at least once"):
+            create_slicer_retry_policy(max_attempts=0)
+        with pytest.raises(ValueError, match="backoff_secs"):
+            create_slicer_retry_policy(backoff_secs=-1)
+        with pytest.raises(ValueError, match="backoff_multiplier"):
+            create_slicer_retry_policy(backoff_multiplier=0.5)
+        policy = create_slicer_retry_policy(transient_patterns=["Vulkan"])
+        assert (policy.max_attempts, policy.transient_patterns) == (3, ["vulkan"])
+
+
+class TestCancellation:
+    """Tests for CancellationToken with slicer runs and the quote pipeline."""
+
+    def test_cancel_kills_async_slicer(self, tmp_path):
+        """Test cancelling mid-slice kills the slicer and its children and raises CancelledError."""
+        child_pid = tmp_path / "child.pid"
+        hung = _write_stub_slicer(
+            tmp_path / "slicer.sh", f"#!/bin/sh\nsleep 30 &\necho $! > {child_pid}\nwait\n"
+        )
+        token = create_cancellation_token()
+        threading.Timer(0.3, token.cancel).start()
+
+        async def run():
+            await execute_slicer_async(
+                hung, "cube.stl", "p.json", "s.json", "f.json", str(tmp_path), cancel=token
+            )
+
+        started = time.monotonic()
+        with pytest.raises(asyncio.CancelledError, match="slicer run"):
+            asyncio.run(run())
+
+        assert time.monotonic() - started < 10
+        assert token.cancelled
+        assert not _running(int(child_pid.read_text()))
+
+    def test_cancel_stops_pipeline_and_removes_workspace(self, tmp_path, profiles_dir):
+        """Test a cancelled quote kills the slicer and leaves no workspace behind."""
+        hung = _write_stub_slicer(tmp_path / "slicer.sh", "#!/bin/sh\nsleep 30\n")
+        work_dir = tmp_path / "work"
+        config = create_pipeline_config(
+            hung, str(profiles_dir), "printer.json", "standard.json", work_dir=str(work_dir)
+        )
+        model = _write_model(tmp_path / "cube.stl")
+        token = create_cancellation_token()
+        threading.Timer(0.3, token.cancel).start()
+
+        with pytest.raises(asyncio.CancelledError, match="slicer run"):
+            run_quote_pipeline(model, "PLA", config, cancel=token)
+
+        assert list(work_dir.iterdir()) == []
+        # Already cancelled: refused before a slicer is started or a slot awaited
+        with pytest.raises(asyncio.CancelledError, match="quote"):
+            run_quote_pipeline(model, "PLA", config, cancel=token)
+
+    def test_cancel_while_waiting_for_slot(self, tmp_path, profiles_dir):
+        """Test a quote queued behind a held slot gives up when cancelled."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+        )
+        token = create_cancellation_token()
+        set_slicer_concurrency(1)
+        try:
+
+            async def hold_slot_and_quote():
+                with await acquire_slicer_slot():
+                    threading.Timer(0.2, token.cancel).start()
+                    run_quote_pipeline(
+                        _write_model(tmp_path / "cube.stl"), "PLA", config, cancel=token
+                    )
+
+            with pytest.raises(asyncio.CancelledError, match="waiting for a slicer"):
+                asyncio.run(hold_slot_and_quote())
+        finally:
+            set_slicer_concurrency(0)
+
+
+class TestJsonLogging:
+    """Tests for init_json_logging."""
+
+    def test_pipeline_events_written_as_json_lines(self, tmp_path, profiles_dir):
+        """Test each stage logs a flat JSON object carrying the quote id, duration, and outcome."""
+        config = create_pipeline_config(
+            _write_stub_slicer(tmp_path / "slicer.sh"),
+            str(profiles_dir),
+            "printer.json",
+            "standard.json",
+        )
+        log_path = tmp_path / "rust.jsonl"
+        init_json_logging(str(log_path))
+        try:
+            run_quote_pipeline(_write_model(tmp_path / "cube.stl"), "PLA", config, quote_id="q-123")
+        finally:
+            init_json_logging("stderr", level="off")
+
+        events = [json.loads(line) for line in log_path.read_text().splitlines()]
+        stages = {e["stage"]: e for e in events if e.get("message") == "stage finished"}
+        assert set(stages) >= {"validation", "slicing", "parsing", "pricing"}
+        assert stages["slicing"]["quote_id"] == "q-123"
+        assert stages["slicing"]["outcome"] == "success"
+        assert stages["slicing"]["duration_ms"] >= 0
+        finished = [e for e in events if e.get("message") == "quote finished"]
+        assert finished[0]["material"] == "PLA"
+
+    def test_unknown_level_rejected(self):
+        """Test an unknown log level raises ValueError."""
+        with pytest.raises(ValueError, match="unknown level"):
+            init_json_logging("stderr", level="loud")
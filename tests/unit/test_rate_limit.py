@@ -0,0 +1,67 @@
+"""Unit tests for the token-bucket rate limiter.
+
+Focus: Test per-key buckets drain, refill over time and are shared safely between threads.
+"""
+
+import threading
+import time
+
+import pytest
+
+from orca_quote_machine._rust_core import create_rate_limiter
+
+
+class TestRateLimiter:
+    """Tests for RateLimiter."""
+
+    def test_keys_drain_separately(self):
+        """Test each key gets its own burst, and a drained key waits for a refill."""
+        limiter = create_rate_limiter(2, 1 / 60)
+
+        assert limiter.acquire("+6591234567")
+        assert limiter.acquire("+6591234567")
+        assert not limiter.acquire("+6591234567")
+        assert limiter.acquire("+6598765432")
+        assert 59 < limiter.retry_after("+6591234567") <= 60
+        assert limiter.retry_after("+6598765432") == 0
+        assert limiter.remaining("+6500000000") == 2
+        assert not limiter.acquire("+6598765432", cost=3)
+        assert limiter.retry_after("+6598765432", cost=3) == float("inf")
+
+        limiter.reset("+6591234567")
+        assert limiter.acquire("+6591234567")
+
+    def test_tokens_refill(self):
+        """Test a drained key gets tokens back at the refill rate, up to capacity."""
+        limiter = create_rate_limiter(1, 20)
+
+        assert limiter.acquire("key")
+        assert not limiter.acquire("key")
+        time.sleep(0.1)
+        assert limiter.acquire("key")
+        time.sleep(0.2)
+        assert limiter.remaining("key") == 1
+
+    def test_shared_between_threads(self):
+        """Test concurrent acquires never hand out more tokens than the bucket holds."""
+        limiter = create_rate_limiter(100, 1e-6)
+        granted = []
+
+        def worker():
+            granted.append(sum(limiter.acquire("shared") for _ in range(50)))
+
+        threads = [threading.Thread(target=worker) for _ in range(8)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+
+        assert sum(granted) == 100
+        assert len(limiter) == 1
+
+    def test_config_validated(self):
+        """Test capacity and refill must be positive."""
+        with pytest.raises(ValueError, match="capacity"):
+            create_rate_limiter(0, 1)
+        with pytest.raises(ValueError, match="refill_per_sec"):
+            create_rate_limiter(5, float("nan"))
@@ -0,0 +1,61 @@
+"""Unit tests for JSON Schema export.
+
+Focus: Test the schemas match the Rust classes and validate real payloads.
+"""
+
+import inspect
+import json
+
+from orca_quote_machine import _rust_core
+from orca_quote_machine._rust_core import calculate_quote_rust, export_schemas
+
+
+def _attributes(cls):
+    """Public attributes a pyclass exposes, i.e. its getters."""
+    return {
+        name
+        for name, member in vars(cls).items()
+        if inspect.isgetsetdescriptor(member) and not name.startswith("_")
+    }
+
+
+class TestExportSchemas:
+    """Tests for export_schemas."""
+
+    def test_schemas_match_classes(self):
+        """Test every schema lists exactly the attributes of its class and resolves its refs."""
+        schemas = export_schemas()
+
+        assert {"QuoteResult", "CostBreakdown", "PipelineConfig", "QuoteEvent"} <= set(schemas)
+        for name, schema in schemas.items():
+            assert set(schema["properties"]) == _attributes(getattr(_rust_core, name)), name
+            assert schema["$schema"] == "https://json-schema.org/draft/2020-12/schema"
+            refs = {
+                ref.rsplit("/", 1)[1]
+                for ref in json.dumps(schema).split('"$ref": "')[1:]
+                for ref in [ref.split('"', 1)[0]]
+            }
+            assert refs <= set(schema.get("$defs", {})), name
+        quote = schemas["QuoteResult"]
+        assert set(quote["$defs"]) == {
+            "ModelInfo", "SlicingResult", "CostBreakdown", "LeadTime", "PrinterStatus",
+            "ShippingRate", "PrintOptions", "PriceAdjustment", "OffPeakPrice",
+            "EstimateCalibration", "CorrectionFactor", "Stability", "ObjMaterials",
+            "StepSummary", "SlicerAttempt",
+        }
+        assert quote["properties"]["printer"]["type"] == ["string", "null"]
+        assert quote["properties"]["dimensions"]["maxItems"] == 3
+        assert schemas["PipelineConfig"]["properties"]["fleet"]["anyOf"][1] == {"type": "null"}
+
+    def test_written_documents_describe_payloads(self, tmp_path):
+        """Test the written files match the return value and describe a real cost breakdown."""
+        schemas = export_schemas(str(tmp_path / "schemas"))
+
+        written = json.loads((tmp_path / "schemas" / "CostBreakdown.schema.json").read_text())
+        assert written == schemas["CostBreakdown"]
+        cost = calculate_quote_rust(90, 25.0, "PLA", 25.0, 0.5, 1.1, 5.0)
+        kinds = {"string": str, "number": (int, float), "integer": int, "boolean": bool}
+        for field, spec in written["properties"].items():
+            assert isinstance(getattr(cost, field), kinds[spec["type"]]), field
+        assert set(written["required"]) == set(written["properties"])
+        assert len(list((tmp_path / "schemas").glob("*.schema.json"))) == len(schemas)
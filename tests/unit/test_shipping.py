@@ -0,0 +1,127 @@
+"""Unit tests for carrier shipping rates.
+
+Focus: Test the parcel sent to the rate provider and how its rates are filtered and sorted.
+"""
+
+import base64
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    create_callback_shipping,
+    create_easypost_shipping,
+    get_shipping_rates,
+)
+
+FROM_ADDRESS = {"street1": "1 Print Lane", "zip": "018956", "country": "SG"}
+
+
+def _write_model(path) -> str:
+    path.write_text(
+        "solid cube\n  facet normal 0 0 1\n    outer loop\n"
+        "      vertex 0 0 0\n      vertex 20 0 0\n      vertex 0 120 10\n"
+        "    endloop\n  endfacet\nendsolid cube\n"
+    )
+    return str(path)
+
+
+@pytest.fixture
+def easypost():
+    """Fake EasyPost /shipments endpoint answering with `rates`, or `status` when set."""
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            length = int(self.headers.get("Content-Length", 0))
+            server.requests.append(
+                (self.path, self.headers.get("Authorization"), json.loads(self.rfile.read(length)))
+            )
+            body = json.dumps(
+                {"error": {"message": "bad address"}} if server.status else {"rates": server.rates}
+            ).encode()
+            self.send_response(server.status or 201)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    server.requests = []
+    server.rates = []
+    server.status = None
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield server
+    server.shutdown()
+
+
+class TestShippingRates:
+    """Tests for get_shipping_rates."""
+
+    def test_easypost_rates_for_padded_parcel(self, tmp_path, easypost):
+        """Test the parcel is padded and converted, and rates come back cheapest first."""
+        easypost.rates = [
+            {"carrier": "UPS", "service": "Express", "rate": "24.10", "currency": "SGD",
+             "delivery_days": 1},
+            {"carrier": "SingPost", "service": "Registered", "rate": "6.50", "currency": "SGD",
+             "delivery_days": None},
+            {"carrier": "DHLExpress", "service": "Worldwide", "rate": "18.00", "currency": "SGD"},
+        ]
+        config = create_easypost_shipping(
+            "ep-test-key",
+            FROM_ADDRESS,
+            api_base=f"http://127.0.0.1:{easypost.server_port}/v2",
+            carriers=["singpost", "UPS"],
+            padding_mm=10.0,
+            packaging_grams=100.0,
+        )
+
+        rates = get_shipping_rates(
+            config, {"zip": "520123", "country": "SG"}, 50.0, _write_model(tmp_path / "part.stl")
+        )
+
+        path, auth, body = easypost.requests[0]
+        parcel = body["shipment"]["parcel"]
+        assert path == "/v2/shipments"
+        assert auth == "Basic " + base64.b64encode(b"ep-test-key:").decode()
+        assert body["shipment"]["to_address"] == {"zip": "520123", "country": "SG"}
+        assert body["shipment"]["from_address"] == FROM_ADDRESS
+        # 120 x 20 x 10 mm plus 10 mm a side, in inches; 150 g in ounces, rounded up
+        assert (parcel["length"], parcel["width"], parcel["height"]) == (5.6, 1.6, 1.2)
+        assert parcel["weight"] == 5.3
+        assert [(r.carrier, r.amount, r.delivery_days) for r in rates] == [
+            ("SingPost", 6.5, None),
+            ("UPS", 24.1, 1),
+        ]
+        assert "ep-test-key" not in repr(config) and config.provider == "easypost"
+
+    def test_callback_provider_and_errors(self, tmp_path, easypost):
+        """Test a Python provider gets the parcel, and provider failures raise OSError."""
+        seen = []
+
+        def provider(parcel, destination):
+            seen.append((parcel, destination))
+            return [{"carrier": "Courier", "amount": 9.5, "currency": "sgd"}]
+
+        model = _write_model(tmp_path / "part.stl")
+        rates = get_shipping_rates(create_callback_shipping(provider), {"zip": "1"}, 40.0, model)
+
+        parcel, destination = seen[0]
+        assert (parcel.length_mm, parcel.width_mm, parcel.height_mm) == (160.0, 60.0, 50.0)
+        assert parcel.weight_grams == 190.0 and destination == {"zip": "1"}
+        assert rates[0].currency == "SGD" and rates[0].service == ""
+
+        easypost.status = 422
+        config = create_easypost_shipping(
+            "ep-test-key", FROM_ADDRESS, api_base=f"http://127.0.0.1:{easypost.server_port}"
+        )
+        with pytest.raises(OSError, match="HTTP 422"):
+            get_shipping_rates(config, {"zip": "1"}, 40.0, model)
+        with pytest.raises(OSError, match="rate provider"):
+            get_shipping_rates(create_callback_shipping(lambda p, d: [{}]), {}, 40.0, model)
+        with pytest.raises(ValueError, match="from_address is empty"):
+            create_easypost_shipping("ep-test-key", {})
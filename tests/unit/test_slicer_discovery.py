@@ -0,0 +1,95 @@
+"""Unit tests for slicer binary discovery.
+
+Focus: Test the slicer is found where it is installed, and its version and flags are read from its help.
+"""
+
+import asyncio
+import stat
+
+import pytest
+
+from orca_quote_machine._rust_core import detect_slicer, execute_slicer_async
+
+HELP_2X = """OrcaSlicer-2.1.1:
+Usage: orca-slicer [ OPTIONS ] [ file.3mf/file.stl ... ]
+
+OPTIONS:
+ --export-slicedata      Export slicing data to a folder.
+ --load-filaments        Load filament settings from the specified file list.
+ --load-settings         Load process/machine settings from the specified file.
+ --outputdir             Output directory for the exported files.
+ --slice                 Slice the plates: 0-all plates, i-plate i.
+ --debug                 Debug level, 0-5. Default: 1.
+"""
+
+
+def _write_slicer(path, help_text: str, version: str = "") -> str:
+    """Stub slicer answering --help and --version, and recording its arguments otherwise."""
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(
+        "#!/bin/sh\n"
+        f'if [ "$1" = "--help" ]; then printf \'%s\' \'{help_text}\'; exit 0; fi\n'
+        f'if [ "$1" = "--version" ]; then echo "{version}"; exit 0; fi\n'
+        f'echo "$@" > "{path}.args"\n'
+    )
+    path.chmod(path.stat().st_mode | stat.S_IEXEC)
+    return str(path)
+
+
+class TestDetectSlicer:
+    """Tests for detect_slicer."""
+
+    def test_reports_version_and_flags(self, tmp_path):
+        """Test a configured binary's banner and options are read from --help."""
+        info = detect_slicer(_write_slicer(tmp_path / "orca-slicer", HELP_2X))
+
+        assert (info.source, info.version, info.version_info) == ("configured", "2.1.1", (2, 1, 1))
+        assert info.flags[:2] == ["--export-slicedata", "--load-filaments"]
+        assert info.supports("--slice")
+        assert not info.supports("--arrange")
+
+    def test_searches_path_then_appimages(self, tmp_path, monkeypatch):
+        """Test PATH is searched before AppImages, and --version answers when --help has no banner."""
+        monkeypatch.setenv("HOME", str(tmp_path / "home"))
+        monkeypatch.setenv("PATH", str(tmp_path / "bin"))
+        appimage = _write_slicer(
+            tmp_path / "home" / "Applications" / "OrcaSlicer_Linux_V2.0.0.AppImage",
+            " --slice   Slice the plates (default: 0.2mm layers)\n",
+            version="OrcaSlicer 02.00.00.51",
+        )
+
+        info = detect_slicer()
+        assert (info.path, info.source) == (appimage, "appimage")
+        assert (info.version, info.version_info) == ("02.00.00.51", (2, 0, 0))
+        assert info.flags == ["--slice"]
+
+        on_path = _write_slicer(tmp_path / "bin" / "orca-slicer", HELP_2X)
+        assert (detect_slicer().path, detect_slicer("orca-slicer").source) == (on_path, "path")
+
+    def test_missing_binary(self, tmp_path, monkeypatch):
+        """Test FileNotFoundError when the binary is not where it was said to be or anywhere."""
+        monkeypatch.setenv("HOME", str(tmp_path))
+        monkeypatch.setenv("PATH", str(tmp_path))
+
+        with pytest.raises(FileNotFoundError, match="not an executable file"):
+            detect_slicer(str(tmp_path / "missing"))
+        with pytest.raises(FileNotFoundError, match="not on PATH"):
+            detect_slicer("orca-slicer")
+        with pytest.raises(ValueError, match="timeout_secs"):
+            detect_slicer(timeout_secs=0)
+
+    def test_runs_leave_out_unlisted_flags(self, tmp_path):
+        """Test a detected binary that lists no --export-slicedata or --debug is run without them."""
+        slicer = tmp_path / "old-slicer"
+        detect_slicer(_write_slicer(slicer, " --slice\n --load-settings\n --outputdir\n"))
+
+        async def run():
+            await execute_slicer_async(
+                str(slicer), "cube.stl", "p.json", "s.json", "f.json", str(tmp_path)
+            )
+
+        asyncio.run(run())
+
+        args = (tmp_path / "old-slicer.args").read_text().split()
+        assert "--outputdir" in args
+        assert "--export-slicedata" not in args and "--debug" not in args
@@ -0,0 +1,70 @@
+"""Unit tests for classifying slicer failures.
+
+Focus: Test a slicer that exits with an error raises SlicerFailedError carrying a categorised SlicerFailure.
+"""
+
+import asyncio
+import stat
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    SlicerFailedError,
+    create_slicer_retry_policy,
+    execute_slicer_async,
+    to_dict,
+)
+
+
+def _fail_with(tmp_path, script: str):
+    """Run a stub slicer with body `script` and return the SlicerFailedError it raised."""
+    slicer = tmp_path / "slicer.sh"
+    slicer.write_text(f"#!/bin/sh\n{script}\n")
+    slicer.chmod(slicer.stat().st_mode | stat.S_IEXEC)
+
+    async def run(**kwargs):
+        await execute_slicer_async(
+            str(slicer), "cube.stl", "p.json", "s.json", "f.json", str(tmp_path), **kwargs
+        )
+
+    with pytest.raises(SlicerFailedError) as raised:
+        asyncio.run(run(retry=create_slicer_retry_policy(max_attempts=2, backoff_secs=0)))
+    return raised.value
+
+
+class TestSlicerFailure:
+    """Tests for the SlicerFailure raised when the slicer exits with an error."""
+
+    def test_categories_from_output(self, tmp_path):
+        """Test the slicer's output picks the category, and the raw text is still the message."""
+        cases = {
+            "echo 'Failed loading the input model' >&2; exit 1": "model_unloadable",
+            "echo 'Nothing to be sliced, either the print is empty' >&2; exit 1": "out_of_build_volume",
+            "echo 'process not compatible with printer' >&2; exit 1": "profile_incompatible",
+            "echo 'working' >&2; exit 1": "unknown",
+        }
+        for script, category in cases.items():
+            error = _fail_with(tmp_path, script)
+            assert error.failure.category == category, script
+            assert error.failure.attempts == 1
+
+        assert isinstance(error, RuntimeError)
+        assert "working" in str(error)
+        assert "We could not slice this model" in error.failure.message
+
+    def test_exit_code_when_output_says_nothing(self, tmp_path):
+        """Test OrcaSlicer's return codes classify a failure with no recognisable output."""
+        failure = _fail_with(tmp_path, "exit 239").failure
+
+        assert (failure.category, failure.detail) == ("profile_incompatible", "exit status: 239")
+        assert "do not work together" in failure.message
+        assert to_dict(failure)["exit_status"] == "exit status: 239"
+
+    def test_signal_is_a_retried_crash(self, tmp_path):
+        """Test a slicer killed by a signal is a crash, retried, with the runs counted."""
+        error = _fail_with(tmp_path, "echo 'loading model' >&2; kill -SEGV $$")
+
+        assert error.failure.category == "crash"
+        assert error.failure.detail.startswith("signal: 11")
+        assert error.failure.attempts == 2
+        assert str(error).endswith("(after 2 attempts)")
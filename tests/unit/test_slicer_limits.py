@@ -0,0 +1,93 @@
+"""Unit tests for slicer resource limits.
+
+Focus: Test the spawned slicer runs with its CPU, memory and priority limits, and fails for good past them.
+"""
+
+import asyncio
+import stat
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    SlicerFailedError,
+    create_slicer_limits,
+    create_slicer_pool,
+    create_slicer_retry_policy,
+    execute_slicer_async,
+)
+
+
+def _write_slicer(tmp_path, script: str) -> str:
+    slicer = tmp_path / "slicer.sh"
+    slicer.write_text(f"#!/bin/sh\n{script}\n")
+    slicer.chmod(slicer.stat().st_mode | stat.S_IEXEC)
+    return str(slicer)
+
+
+def _slice(slicer: str, tmp_path, **kwargs):
+    async def run():
+        return await execute_slicer_async(
+            slicer, "cube.stl", "p.json", "s.json", "f.json", str(tmp_path), **kwargs
+        )
+
+    return asyncio.run(run())
+
+
+class TestSlicerLimits:
+    """Tests for create_slicer_limits and the slicer runs given them."""
+
+    def test_limits_reach_the_slicer(self, tmp_path):
+        """Test the slicer sees its CPU and address space limits and niceness, via a pool too."""
+        seen = tmp_path / "limits.txt"
+        slicer = _write_slicer(
+            tmp_path,
+            'read -r stat < /proc/$$/stat; set -- $stat; shift 18\n'
+            f'echo "$(ulimit -t) $(ulimit -v) $1" > {seen}',
+        )
+        limits = create_slicer_limits(cpu_secs=60, memory_mb=2048, nice=5)
+
+        _slice(slicer, tmp_path, limits=limits)
+        assert seen.read_text().split() == ["60", str(2048 * 1024), "5"]
+
+        pool = create_slicer_pool(1, limits=limits)
+
+        async def run():
+            return await pool.submit(
+                slicer, "cube.stl", "p.json", "s.json", "f.json", str(tmp_path)
+            ).wait()
+
+        seen.unlink()
+        asyncio.run(run())
+        assert pool.limits.nice == 5
+        assert seen.read_text().split() == ["60", str(2048 * 1024), "5"]
+
+        _slice(slicer, tmp_path)
+        assert seen.read_text().split() == ["unlimited", "unlimited", "0"]
+
+    def test_cpu_limit_is_not_retried(self, tmp_path):
+        """Test a slicer past its CPU time fails as resource_limit, once despite the retry policy."""
+        spinning = _write_slicer(tmp_path, "echo 'slicing' >&2\nwhile :; do :; done")
+
+        with pytest.raises(SlicerFailedError) as raised:
+            _slice(
+                spinning,
+                tmp_path,
+                timeout_secs=30,
+                limits=create_slicer_limits(cpu_secs=1),
+                retry=create_slicer_retry_policy(max_attempts=3, backoff_secs=0),
+            )
+
+        failure = raised.value.failure
+        assert failure.category == "resource_limit"
+        assert failure.exit_status.startswith("signal: 24")
+        assert failure.attempts == 1
+        assert "more time or memory" in failure.message
+
+    def test_limits_validated(self, tmp_path):
+        """Test zero limits, an out of range niceness and a missing cgroup are rejected."""
+        with pytest.raises(ValueError, match="cpu_secs"):
+            create_slicer_limits(cpu_secs=0)
+        with pytest.raises(ValueError, match="not a niceness"):
+            create_slicer_limits(nice=20)
+        with pytest.raises(ValueError, match="not a cgroup v2 directory"):
+            create_slicer_limits(cgroup=str(tmp_path))
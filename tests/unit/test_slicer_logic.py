@@ -4,10 +4,11 @@ Focus: Test profile resolution logic, material discovery, and error handling.
 """
 
 from pathlib import Path
-from unittest.mock import MagicMock, patch
+from unittest.mock import patch
 
 import pytest
 
+from orca_quote_machine._rust_core import create_profile_cache
 from orca_quote_machine.models.quote import MaterialType
 from orca_quote_machine.services.slicer import OrcaSlicerService, SlicerError
 
@@ -60,26 +61,36 @@ class TestSlicerServiceLogic:
         assert "PETG" in materials
         assert "ASA" in materials
 
-    def test_get_available_materials_includes_custom(self):
+    def test_get_available_materials_includes_custom(self, tmp_path):
         """Test that custom materials are discovered from filesystem."""
         service = OrcaSlicerService()
+        filament_dir = tmp_path / "filament"
+        filament_dir.mkdir()
+        for name in ("TPU", "NYLON", "PLA"):  # PLA duplicates the catalog
+            (filament_dir / f"{name}.json").write_text('{"type": "filament"}')
+        service.profile_cache = create_profile_cache(str(tmp_path), watch=False)
 
-        # Mock the filament directory to include custom materials
-        with patch.object(Path, 'glob') as mock_glob:
-            mock_files = [
-                MagicMock(stem="TPU"),
-                MagicMock(stem="NYLON"),
-                MagicMock(stem="PLA"),  # Duplicate of enum
-            ]
-            mock_glob.return_value = mock_files
+        materials = service.get_available_materials()
+
+        # Should include both catalog and custom materials
+        assert "TPU" in materials
+        assert "NYLON" in materials
+        # Should not have duplicates
+        assert materials.count("PLA") == 1
 
-            materials = service.get_available_materials()
+    def test_get_available_materials_leaves_out_of_stock(self, tmp_path):
+        """Test materials the inventory has run out of drop off the list."""
+        stock = tmp_path / "stock.toml"
+        stock.write_text('[[stock]]\nmaterial = "PETG"\nremaining_grams = 0\n')
+        service = OrcaSlicerService()
+        service.settings = service.settings.model_copy(
+            update={"inventory_table_path": str(stock)}
+        )
 
-            # Should include both enum and custom materials
-            assert "TPU" in materials
-            assert "NYLON" in materials
-            # Should not have duplicates
-            assert materials.count("PLA") == 1
+        assert "PETG" not in service.get_available_materials()
+        assert "PETG" in service.get_available_materials(include_out_of_stock=True)
+        with pytest.raises(ValueError, match="PETG out of stock"):
+            service.check_stock("PETG")
 
     def test_get_filament_profile_path_with_override(self):
         """Test filament profile resolution with config override."""
@@ -91,21 +102,17 @@ class TestSlicerServiceLogic:
         assert isinstance(profile_path, Path)
         assert profile_path.name.endswith(".json")
 
-    def test_get_filament_profile_path_fallback_convention(self):
+    def test_get_filament_profile_path_fallback_convention(self, tmp_path):
         """Test filament profile fallback to naming convention."""
         service = OrcaSlicerService()
+        (tmp_path / "filament").mkdir()
+        (tmp_path / "filament" / "custom_material.json").write_text('{"type": "filament"}')
+        service.profile_cache = create_profile_cache(str(tmp_path), watch=False)
 
-        # Mock a material that doesn't have a config override
-        with patch.object(service.settings.slicer_profiles, '__dict__',
-                         {'base_dir': service.profiles_dir,
-                          'machine': 'test.json',
-                          'process': 'test.json'}):
-
-            # Mock file existence check
-            with patch.object(Path, 'exists', return_value=True):
-                profile_path = service._get_filament_profile_path("CUSTOM_MATERIAL")
+        # Materials missing from the mapping file fall back to `<material>.json`
+        profile_path = service._get_filament_profile_path("CUSTOM_MATERIAL")
 
-                assert profile_path.name == "custom_material.json"
+        assert profile_path.name == "custom_material.json"
 
     def test_get_filament_profile_path_raises_on_missing(self):
         """Test that missing profiles raise SlicerError."""
@@ -127,3 +134,18 @@ class TestSlicerServiceLogic:
             await service.slice_model("/nonexistent/file.stl", MaterialType.PLA)
 
         assert "Model file not found" in str(exc_info.value)
+
+
+    def test_check_build_plate_raises_slicer_error(self, tmp_path):
+        """Test an oversized model is reported as a SlicerError before slicing."""
+        machine = tmp_path / "machine.json"
+        machine.write_text(
+            '{"type": "machine", "name": "Mini", '
+            '"printable_area": ["0x0", "100x0", "100x100", "0x100"], "printable_height": "100"}'
+        )
+        model = tmp_path / "tall.obj"
+        model.write_text("v 0 0 0\nv 50 0 0\nv 0 50 150\nf 1 2 3\n")
+        service = OrcaSlicerService()
+
+        with pytest.raises(SlicerError, match="larger than the Mini build volume"):
+            service.check_build_plate(str(model), str(machine))
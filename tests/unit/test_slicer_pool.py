@@ -0,0 +1,85 @@
+"""Unit tests for the slicer pool.
+
+Focus: Test jobs queue for a bounded number of slicers, report their state, and can be cancelled.
+"""
+
+import asyncio
+import stat
+import time
+
+import pytest
+
+from orca_quote_machine._rust_core import create_cancellation_token, create_slicer_pool
+
+
+def _write_slicer(path, body: str) -> str:
+    path.write_text(f"#!/bin/sh\n{body}\n")
+    path.chmod(path.stat().st_mode | stat.S_IEXEC)
+    return str(path)
+
+
+def _submit(pool, slicer, out_dir, **kwargs):
+    return pool.submit(slicer, "cube.stl", "p.json", "s.json", "f.json", str(out_dir), **kwargs)
+
+
+async def _wait(job):
+    await job.wait()
+
+
+class TestSlicerPool:
+    """Tests for SlicerPool."""
+
+    def test_runs_at_most_max_concurrent(self, tmp_path):
+        """Test jobs beyond max_concurrent queue and never overlap with the running one."""
+        lock = tmp_path / "running"
+        overlaps = tmp_path / "overlaps"
+        slicer = _write_slicer(
+            tmp_path / "slicer.sh",
+            f"mkdir {lock} || echo overlap >> {overlaps}\nsleep 0.2\nrmdir {lock}",
+        )
+        pool = create_slicer_pool(1)
+
+        jobs = [_submit(pool, slicer, tmp_path) for _ in range(3)]
+        stats = pool.stats()
+        assert (stats.queued + stats.running, stats.completed) == (3, 0)
+        for job in jobs:
+            asyncio.run(_wait(job))
+
+        assert not overlaps.exists()
+        assert [job.status for job in jobs] == ["completed"] * 3
+        assert jobs[-1].wait_ms >= 300
+        assert jobs[-1].run_ms >= 150
+        stats = pool.stats()
+        assert (stats.queued, stats.running, stats.completed) == (0, 0, 3)
+
+    def test_failures_and_cancellation(self, tmp_path):
+        """Test a failing job raises, and cancelled ones stop whether queued or running."""
+        pool = create_slicer_pool(1, timeout_secs=30)
+        hung = _submit(pool, _write_slicer(tmp_path / "hung.sh", "sleep 30"), tmp_path)
+        token = create_cancellation_token()
+        queued = _submit(pool, _write_slicer(tmp_path / "ok.sh", "true"), tmp_path, cancel=token)
+        time.sleep(0.2)
+        assert (hung.status, queued.status) == ("running", "queued")
+
+        token.cancel()
+        with pytest.raises(asyncio.CancelledError, match="while queued"):
+            asyncio.run(_wait(queued))
+        hung.cancel()
+        with pytest.raises(asyncio.CancelledError, match="slicer run"):
+            asyncio.run(_wait(hung))
+        failing = _submit(
+            pool, _write_slicer(tmp_path / "failing.sh", "echo 'bad model' >&2; exit 1"), tmp_path
+        )
+        with pytest.raises(RuntimeError, match="bad model"):
+            asyncio.run(_wait(failing))
+
+        assert (queued.wait_ms, queued.status, failing.status) == (None, "cancelled", "failed")
+        stats = pool.stats()
+        assert (stats.completed, stats.failed, stats.cancelled) == (0, 1, 2)
+
+    def test_config_validated(self):
+        """Test a pool needs room for a slicer and a positive timeout."""
+        with pytest.raises(ValueError, match="at least one slicer"):
+            create_slicer_pool(0)
+        with pytest.raises(ValueError, match="timeout_secs"):
+            create_slicer_pool(2, timeout_secs=0)
@@ -3,50 +3,98 @@
 Focus: Test task orchestration logic, error handling, and cleanup behavior.
 """
 
+import json
 import os
+import stat
 import tempfile
-from unittest.mock import AsyncMock, MagicMock, patch
+import zipfile
+from unittest.mock import AsyncMock, patch
 
 import pytest
 
-from orca_quote_machine.tasks import cleanup_old_files, process_quote_request
+from orca_quote_machine.core.config import Settings, SlicerProfileSettings
+from orca_quote_machine.models.quote import MaterialType
+from orca_quote_machine.tasks import (
+    adjust_quote_price,
+    cleanup_old_files,
+    export_quote_job_bundle,
+    process_quote_request,
+)
+
+STUB_SLICER = """#!/bin/sh
+# Write a G-code file into the directory passed after --outputdir.
+while [ $# -gt 0 ]; do
+    if [ "$1" = "--outputdir" ]; then out="$2"; fi
+    shift
+done
+printf '; estimated printing time = 2h 0m\\n; filament used [g] = 100.0\\n' > "$out/plate_1.gcode"
+"""
+
+
+@pytest.fixture
+def pipeline_settings(tmp_path):
+    """Settings that quote against a stub slicer and one-printer profile tree."""
+    root = tmp_path / "profiles"
+    for profile_type in ("machine", "process", "filament"):
+        (root / profile_type).mkdir(parents=True)
+    (root / "machine" / "printer.json").write_text(
+        json.dumps(
+            {
+                "type": "machine",
+                "name": "Printer",
+                "printable_area": ["0x0", "200x0", "200x200", "0x200"],
+                "printable_height": "200",
+            }
+        )
+    )
+    (root / "process" / "standard.json").write_text(json.dumps({"type": "process"}))
+    (root / "filament" / "pla.json").write_text(json.dumps({"filament_type": ["PLA"]}))
+    slicer = tmp_path / "slicer.sh"
+    slicer.write_text(STUB_SLICER)
+    slicer.chmod(slicer.stat().st_mode | stat.S_IEXEC)
+    return Settings(
+        orcaslicer_cli_path=str(slicer),
+        slicer_profiles=SlicerProfileSettings(
+            base_dir=root,
+            machine="printer.json",
+            process="standard.json",
+            material_map=tmp_path / "material_profiles.toml",
+        ),
+        upload_dir=str(tmp_path / "uploads"),
+        quote_store_dir=str(tmp_path / "quotes"),
+        gcode_cache_dir=str(tmp_path / "gcode"),
+    )
+
+
+def _write_model(path) -> str:
+    path.write_text(
+        "solid cube\n  facet normal 0 0 1\n    outer loop\n"
+        "      vertex 0 0 0\n      vertex 20 0 0\n      vertex 0 20 10\n"
+        "    endloop\n  endfacet\nendsolid cube\n"
+    )
+    return str(path)
 
 
 class TestProcessQuoteRequestLogic:
     """Test the quote processing task logic."""
 
-    @patch('orca_quote_machine.tasks.validate_3d_model')
-    def test_task_validates_file_first(self, mock_validate):
-        """Test that task validates file before processing."""
-        # Setup invalid file validation
-        mock_result = MagicMock()
-        mock_result.file_type = "stl"
-        mock_result.file_size = 100
-        mock_result.is_valid = False
-        mock_result.error_message = "Invalid STL format"
-        mock_validate.return_value = mock_result
+    def test_task_validates_file_first(self, tmp_path, pipeline_settings):
+        """Test that an invalid upload fails before the slicer runs."""
+        model = tmp_path / "broken.stl"
+        model.write_bytes(b"solid")
 
-        with tempfile.NamedTemporaryFile(suffix=".stl") as temp_file:
+        with patch('orca_quote_machine.tasks.get_settings', return_value=pipeline_settings):
             result = process_quote_request(
-                temp_file.name,
+                str(model),
                 {"name": "Test", "mobile": "123"},
                 "PLA"
             )
 
-            assert result["success"] is False
-            assert "Invalid 3D model" in result["error"]
-            mock_validate.assert_called_once_with(temp_file.name)
+        assert result["success"] is False
+        assert "Invalid model" in result["error"]
 
-    @patch('orca_quote_machine.tasks.validate_3d_model')
-    def test_task_handles_unknown_material(self, mock_validate):
+    def test_task_handles_unknown_material(self):
         """Test that unknown materials default to PLA."""
-        mock_result = MagicMock()
-        mock_result.file_type = "stl"
-        mock_result.file_size = 100
-        mock_result.is_valid = True
-        mock_result.error_message = None
-        mock_validate.return_value = mock_result
-
         # Mock the async pipeline
         with patch('orca_quote_machine.tasks.asyncio.run') as mock_run:
             mock_run.return_value = {
@@ -74,27 +122,19 @@ class TestProcessQuoteRequestLogic:
         # Ensure file exists
         assert os.path.exists(temp_path)
 
-        with patch('orca_quote_machine.tasks.validate_3d_model') as mock_validate:
-            mock_result = MagicMock()
-            mock_result.file_type = "stl"
-            mock_result.file_size = 100
-            mock_result.is_valid = True
-            mock_result.error_message = None
-            mock_validate.return_value = mock_result
-
-            with patch('orca_quote_machine.tasks.asyncio.run') as mock_run:
-                mock_run.return_value = {
-                    "success": True,
-                    "quote_id": "test-id",
-                    "slicing_result": {"print_time_minutes": 120},
-                    "cost_breakdown": {"total_cost": 25.0}
-                }
-
-                process_quote_request(
-                    temp_path,
-                    {"name": "Test", "mobile": "123"},
-                    "PLA"
-                )
+        with patch('orca_quote_machine.tasks.asyncio.run') as mock_run:
+            mock_run.return_value = {
+                "success": True,
+                "quote_id": "test-id",
+                "slicing_result": {"print_time_minutes": 120},
+                "cost_breakdown": {"total_cost": 25.0}
+            }
+
+            process_quote_request(
+                temp_path,
+                {"name": "Test", "mobile": "123"},
+                "PLA"
+            )
 
         # File should be cleaned up
         assert not os.path.exists(temp_path)
@@ -107,8 +147,8 @@ class TestProcessQuoteRequestLogic:
         # Ensure file exists
         assert os.path.exists(temp_path)
 
-        with patch('orca_quote_machine.tasks.validate_3d_model') as mock_validate:
-            mock_validate.side_effect = Exception("Validation error")
+        with patch('orca_quote_machine.tasks.run_quote_pipeline') as mock_pipeline:
+            mock_pipeline.side_effect = ValueError("Invalid model: File too small")
 
             result = process_quote_request(
                 temp_path,
@@ -121,10 +161,10 @@ class TestProcessQuoteRequestLogic:
             assert not os.path.exists(temp_path)
 
     @patch('orca_quote_machine.tasks.send_failure_notification')
-    @patch('orca_quote_machine.tasks.validate_3d_model')
-    def test_task_sends_error_notification(self, mock_validate, mock_notify):
+    @patch('orca_quote_machine.tasks.run_quote_pipeline')
+    def test_task_sends_error_notification(self, mock_pipeline, mock_notify):
         """Test that errors trigger admin notification."""
-        mock_validate.side_effect = Exception("Critical error")
+        mock_pipeline.side_effect = Exception("Critical error")
 
         with tempfile.NamedTemporaryFile(suffix=".stl") as temp_file:
             result = process_quote_request(
@@ -141,46 +181,88 @@ class TestRunProcessingPipelineLogic:
     """Test the async processing pipeline orchestration."""
 
     @pytest.mark.asyncio
-    async def test_pipeline_orchestrates_services(self, sample_slicing_result, sample_cost_breakdown):
-        """Test that pipeline calls services in correct order."""
+    async def test_pipeline_orchestrates_services(self, tmp_path, pipeline_settings):
+        """Test that the Rust pipeline quotes the model before the admin is notified."""
         from orca_quote_machine.tasks import run_processing_pipeline
 
-        # Mock all services but use real Rust objects for return values
-        with patch('orca_quote_machine.tasks.OrcaSlicerService') as mock_slicer:
-            mock_slicer_instance = mock_slicer.return_value
-            # Return the real SlicingResult fixture
-            mock_slicer_instance.slice_model = AsyncMock(return_value=sample_slicing_result)
-
-            with patch('orca_quote_machine.tasks.PricingService') as mock_pricing:
-                mock_pricing_instance = mock_pricing.return_value
-                # Return the real CostBreakdown fixture
-                mock_pricing_instance.calculate_quote = MagicMock(return_value=sample_cost_breakdown)
-
-                with patch('orca_quote_machine.tasks.TelegramService') as mock_telegram:
-                    mock_telegram_instance = mock_telegram.return_value
-                    mock_telegram_instance.send_quote_notification = AsyncMock(return_value=True)
+        with patch('orca_quote_machine.tasks.get_settings', return_value=pipeline_settings):
+            with patch('orca_quote_machine.tasks.TelegramService') as mock_telegram:
+                mock_telegram_instance = mock_telegram.return_value
+                mock_telegram_instance.send_quote_notification = AsyncMock(return_value=True)
+
+                result = await run_processing_pipeline(
+                    _write_model(tmp_path / "cube.stl"),
+                    {"name": "Test", "mobile": "123", "filename": "cube.stl"},
+                    MaterialType.PLA,
+                    "quote-123",
+                    "quote-12"
+                )
 
-                    result = await run_processing_pipeline(
-                        "/test/file.stl",
-                        {"name": "Test", "mobile": "123", "filename": "test.stl"},
-                        None,  # Material
-                        "quote-123",
-                        "quote-12"
-                    )
+        assert result["success"] is True
+        assert result["notification_sent"] is True
+        assert result["slicing_result"]["print_time_minutes"] == 120
+        assert result["cost_breakdown"]["total_cost"] > 0
+        assert "slicing" in result["stage_timings_ms"]
+        assert os.path.exists(os.path.join(pipeline_settings.quote_store_dir, "quote-123.json"))
+        mock_telegram_instance.send_quote_notification.assert_called_once()
+
+
+class TestAdjustQuotePriceLogic:
+    """Test manual price adjustments of processed quotes."""
+
+    def test_processed_quote_can_be_adjusted(self, tmp_path, pipeline_settings):
+        """Test a quote from process_quote_request is kept, then revised by an operator."""
+        with patch('orca_quote_machine.tasks.get_settings', return_value=pipeline_settings), \
+                patch('orca_quote_machine.tasks.settings', pipeline_settings):
+            processed = process_quote_request(
+                _write_model(tmp_path / "cube.stl"),
+                {"name": "Test", "mobile": "123", "filename": "cube.stl"},
+                "PLA"
+            )
+            result = adjust_quote_price(
+                processed["quote_id"], "repeat customer", delta=-2.5, operator="ops"
+            )
 
-                    assert result["success"] is True
-                    assert result["notification_sent"] is True
+        assert processed["success"] is True
+        assert result["success"] is True
+        assert result["cost_breakdown"]["total_cost"] == pytest.approx(
+            processed["cost_breakdown"]["total_cost"] - 2.5
+        )
+        assert [(a["reason"], a["operator"]) for a in result["adjustments"]] == [
+            ("repeat customer", "ops")
+        ]
+        assert result["payment_url"] is None
+
+
+class TestExportQuoteJobBundleLogic:
+    """Test job bundles for processed quotes."""
+
+    def test_processed_quote_can_be_bundled(self, tmp_path, pipeline_settings):
+        """Test a quote from process_quote_request is zipped with its cached G-code."""
+        with patch('orca_quote_machine.tasks.get_settings', return_value=pipeline_settings), \
+                patch('orca_quote_machine.tasks.settings', pipeline_settings):
+            processed = process_quote_request(
+                _write_model(tmp_path / "cube.stl"),
+                {"name": "Test", "mobile": "123", "filename": "cube.stl"},
+                "PLA"
+            )
+            result = export_quote_job_bundle(processed["quote_id"])
 
-                    # Verify service call order
-                    mock_slicer_instance.slice_model.assert_called_once()
-                    mock_pricing_instance.calculate_quote.assert_called_once()
-                    mock_telegram_instance.send_quote_notification.assert_called_once()
+        assert processed["success"] is True
+        assert result["success"] is True
+        assert result["bundle_path"] == os.path.join(
+            pipeline_settings.quote_store_dir, f"{processed['quote_id']}.zip"
+        )
+        with zipfile.ZipFile(result["bundle_path"]) as bundle:
+            assert "gcode/plate_1.gcode" in bundle.namelist()
+            manifest = json.loads(bundle.read("manifest.json"))
+        assert manifest["quote"]["cost"]["total_cost"] == processed["cost_breakdown"]["total_cost"]
 
 
 class TestCleanupTaskLogic:
     """Test the file cleanup task logic."""
 
-    @patch('orca_quote_machine.tasks.cleanup_old_files_rust')
+    @patch('orca_quote_machine.tasks.cleanup_old_files_async', new_callable=AsyncMock)
     def test_cleanup_returns_success_stats(self, mock_cleanup, sample_cleanup_stats):
         """Test cleanup task formats Rust stats correctly."""
         # Use real CleanupStats object
@@ -192,7 +274,24 @@ class TestCleanupTaskLogic:
         assert result["files_cleaned"] == sample_cleanup_stats.files_cleaned
         assert result["bytes_freed"] == sample_cleanup_stats.bytes_freed
 
-    @patch('orca_quote_machine.tasks.cleanup_old_files_rust')
+    def test_cleanup_sweeps_upload_dir(self, tmp_path, pipeline_settings):
+        """Test the task runs the async Rust sweep over the upload directory."""
+        uploads = tmp_path / "uploads"
+        uploads.mkdir()
+        stale = uploads / "old.stl"
+        stale.write_bytes(b"x" * 10)
+        os.utime(stale, (0, 0))
+        (uploads / "new.stl").write_bytes(b"y")
+
+        with patch('orca_quote_machine.tasks.settings', pipeline_settings):
+            result = cleanup_old_files(max_age_hours=24)
+
+        assert result["success"] is True
+        assert result["files_cleaned"] == 1
+        assert result["bytes_freed"] == 10
+        assert os.listdir(uploads) == ["new.stl"]
+
+    @patch('orca_quote_machine.tasks.cleanup_old_files_async', new_callable=AsyncMock)
     def test_cleanup_handles_rust_errors(self, mock_cleanup):
         """Test cleanup task handles Rust function errors."""
         mock_cleanup.side_effect = Exception("Rust error")
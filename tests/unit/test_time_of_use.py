@@ -0,0 +1,66 @@
+"""Unit tests for time-of-use pricing.
+
+Focus: Test off-peak windows discount the print time that falls inside them.
+"""
+
+from datetime import datetime, timezone
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    calculate_quote_rust,
+    create_business_calendar,
+    create_time_of_use_pricing,
+    quote_off_peak,
+)
+
+# Monday 2025-03-03, 12:00 UTC
+MONDAY_NOON = datetime(2025, 3, 3, 12, tzinfo=timezone.utc).timestamp()
+
+
+class TestQuoteOffPeak:
+    """Tests for quote_off_peak."""
+
+    def test_print_time_in_window_discounted(self):
+        """Test an overnight window discounts the hours of the print inside it."""
+        pricing = create_time_of_use_pricing([(22, 6, 0.5)], utc_offset_hours=0.0)
+        # 10 h print + 0.5 h setup at S$20/h, 100 g at S$20/kg, 10% markup
+        cost = calculate_quote_rust(600, 100.0, "PLA", 20.0, 0.5, 1.1, 5.0)
+        calendar = create_business_calendar(utc_offset_hours=0.0)
+
+        off_peak = quote_off_peak(
+            pricing, cost, minimum_price=5.0, calendar=calendar, start=MONDAY_NOON
+        )
+
+        # 22:00-06:00 at half rate, 06:00-08:00 at full rate
+        assert off_peak.start_time == "2025-03-03 22:00"
+        assert off_peak.start_at == MONDAY_NOON + 10 * 3600
+        assert off_peak.rate_factor == pytest.approx(0.6)
+        assert off_peak.time_cost == pytest.approx(210.0 - 80.0)
+        assert off_peak.total_cost == pytest.approx((2.0 + 130.0) * 1.1)
+        assert off_peak.savings == pytest.approx(cost.total_cost - off_peak.total_cost)
+        assert off_peak.promised_date == "2025-03-04"
+
+    def test_off_peak_start_waits_for_window_days(self):
+        """Test a weekend-only window moves the off-peak start to Saturday."""
+        weekends = create_time_of_use_pricing([(0, 24, 0.5)], days=[5, 6], utc_offset_hours=0.0)
+        cost = calculate_quote_rust(600, 100.0, "PLA", 20.0, 0.5, 1.1, 5.0)
+
+        off_peak = quote_off_peak(weekends, cost, start=MONDAY_NOON)
+
+        assert off_peak.start_time == "2025-03-08 00:00"
+        assert off_peak.rate_factor == pytest.approx(0.5)
+        assert off_peak.promised_date is None
+
+    def test_no_off_peak_price_without_savings(self):
+        """Test a quote held at the minimum price gets no off-peak price, and bad windows raise."""
+        cheap = calculate_quote_rust(30, 5.0, "PLA", 20.0, 0.0, 1.1, 50.0)
+        pricing = create_time_of_use_pricing([(0, 24, 0.5)], utc_offset_hours=0.0)
+
+        assert quote_off_peak(pricing, cheap, minimum_price=50.0, start=MONDAY_NOON) is None
+        with pytest.raises(ValueError, match="rate multiplier"):
+            create_time_of_use_pricing([(22, 6, 1.5)])
+        with pytest.raises(ValueError, match="range of hours"):
+            create_time_of_use_pricing([(25, 6, 0.5)])
+        with pytest.raises(ValueError, match="days"):
+            create_time_of_use_pricing([(22, 6, 0.5)], days=[])
@@ -0,0 +1,46 @@
+"""Unit tests for the upload index.
+
+Focus: Test record_upload and find_duplicate match repeat uploads by content hash to their last quote.
+"""
+
+import hashlib
+
+import pytest
+
+from orca_quote_machine._rust_core import find_duplicate, record_upload
+
+FIRST = hashlib.sha256(b"first").hexdigest()
+SECOND = hashlib.sha256(b"second").hexdigest()
+
+
+class TestUploadIndex:
+    """Tests for record_upload and find_duplicate."""
+
+    def test_latest_quote_for_a_hash_is_found(self, tmp_path):
+        """Test each hash finds the quote last recorded for it, and unseen hashes none."""
+        index = str(tmp_path / "index" / "uploads.jsonl")
+
+        assert find_duplicate(FIRST, index) is None
+        record_upload(FIRST, "quote-1", index)
+        record_upload(SECOND, "quote-2", index)
+        record_upload(FIRST.upper(), "quote-3", index)
+
+        assert find_duplicate(FIRST, index) == "quote-3"
+        assert find_duplicate(SECOND.upper(), index) == "quote-2"
+        assert find_duplicate(hashlib.sha256(b"third").hexdigest(), index) is None
+
+    def test_damaged_lines_are_skipped(self, tmp_path):
+        """Test a line cut short does not hide the records around it."""
+        index = tmp_path / "uploads.jsonl"
+        record_upload(FIRST, "quote-1", str(index))
+        with open(index, "a") as f:
+            f.write('{"event": "upload_indexed", "sha256": "' + FIRST)
+
+        assert find_duplicate(FIRST, str(index)) == "quote-1"
+
+    def test_malformed_hash_rejected(self, tmp_path):
+        """Test a value that is not a hex SHA-256 raises ValueError."""
+        with pytest.raises(ValueError, match="not a hex SHA-256"):
+            record_upload("abc", "quote-1", str(tmp_path / "uploads.jsonl"))
+        with pytest.raises(ValueError, match="not a hex SHA-256"):
+            find_duplicate("z" * 64, str(tmp_path / "uploads.jsonl"))
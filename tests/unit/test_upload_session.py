@@ -0,0 +1,120 @@
+"""Unit tests for resumable upload sessions.
+
+Focus: Test chunks append at their offsets, survive a restart and finish into a validated, hashed upload.
+"""
+
+import hashlib
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    create_upload_session_store,
+    create_validation_limits,
+)
+
+ASCII_STL = (
+    "solid cube\n  facet normal 0 0 1\n    outer loop\n"
+    "      vertex 0 0 0\n      vertex 20 0 0\n      vertex 0 20 10\n"
+    "    endloop\n  endfacet\nendsolid cube\n"
+).encode()
+
+
+class TestUploadSessions:
+    """Tests for UploadSessionStore."""
+
+    def test_chunks_finish_into_validated_upload(self, tmp_path):
+        """Test chunks are appended, hashed as they arrive and validated on finish."""
+        store = create_upload_session_store(str(tmp_path / "sessions"))
+        session = store.start("../cube.stl", total_bytes=len(ASCII_STL))
+        assert session.file_name == "..cube.stl"
+        assert session.received_bytes == 0
+
+        first = store.append(session.session_id, 0, ASCII_STL[:50])
+        assert first.received_bytes == 50
+        assert first.sha256 == hashlib.sha256(ASCII_STL[:50]).hexdigest()
+        store.append(session.session_id, 50, ASCII_STL[50:])
+
+        dest = tmp_path / "cube.stl"
+        info = store.finish(
+            session.session_id, str(dest), sha256=hashlib.sha256(ASCII_STL).hexdigest()
+        )
+
+        assert info.is_valid, info.error_message
+        assert info.sha256 == hashlib.sha256(ASCII_STL).hexdigest()
+        assert dest.read_bytes() == ASCII_STL
+        with pytest.raises(FileNotFoundError, match="upload session"):
+            store.status(session.session_id)
+
+    def test_resent_and_overlapping_chunks(self, tmp_path):
+        """Test a chunk sent again adds only what is new, and a gap is refused."""
+        store = create_upload_session_store(str(tmp_path))
+        session_id = store.start("cube.stl").session_id
+        store.append(session_id, 0, ASCII_STL[:40])
+
+        assert store.append(session_id, 0, ASCII_STL[:40]).received_bytes == 40
+        assert store.append(session_id, 20, ASCII_STL[20:60]).received_bytes == 60
+        with pytest.raises(ValueError, match="chunk starts at byte 100 but only 60"):
+            store.append(session_id, 100, ASCII_STL[100:])
+        store.append(session_id, 60, ASCII_STL[60:])
+
+        assert store.status(session_id).sha256 == hashlib.sha256(ASCII_STL).hexdigest()
+
+    def test_resumed_by_another_store(self, tmp_path):
+        """Test a session started before a restart carries on from the part file."""
+        session_id = create_upload_session_store(str(tmp_path)).start("cube.stl").session_id
+        create_upload_session_store(str(tmp_path)).append(session_id, 0, ASCII_STL[:30])
+
+        restarted = create_upload_session_store(str(tmp_path))
+        status = restarted.status(session_id)
+        restarted.append(session_id, status.received_bytes, ASCII_STL[30:])
+
+        assert restarted.status(session_id).sha256 == hashlib.sha256(ASCII_STL).hexdigest()
+
+    def test_incomplete_or_mismatched_uploads_kept(self, tmp_path):
+        """Test finishing early or with the wrong hash raises and keeps the session."""
+        store = create_upload_session_store(str(tmp_path / "sessions"))
+        session_id = store.start("cube.stl", total_bytes=len(ASCII_STL)).session_id
+        store.append(session_id, 0, ASCII_STL[:10])
+        dest = str(tmp_path / "cube.stl")
+
+        with pytest.raises(ValueError, match=f"incomplete, 10 of {len(ASCII_STL)} bytes"):
+            store.finish(session_id, dest)
+        store.append(session_id, 10, ASCII_STL[10:])
+        with pytest.raises(ValueError, match="Checksum mismatch"):
+            store.finish(session_id, dest, sha256="0" * 64)
+        with pytest.raises(ValueError, match="more than the .* announced"):
+            store.append(session_id, len(ASCII_STL), b"extra")
+
+        assert store.status(session_id).received_bytes == len(ASCII_STL)
+
+    def test_size_limit_and_unknown_sessions(self, tmp_path):
+        """Test uploads over the size limit are refused, and unknown IDs are not found."""
+        store = create_upload_session_store(
+            str(tmp_path), limits=create_validation_limits(max_file_bytes=100)
+        )
+
+        with pytest.raises(ValueError, match="File is 1000 bytes, over the limit of 100"):
+            store.start("big.stl", total_bytes=1000)
+        session_id = store.start("big.stl").session_id
+        with pytest.raises(ValueError, match="File is 200 bytes"):
+            store.append(session_id, 0, bytes(200))
+        for unknown in ("0" * 32, "../../etc"):
+            with pytest.raises(FileNotFoundError):
+                store.append(unknown, 0, b"x")
+
+    def test_stale_sessions_expire(self, tmp_path):
+        """Test sessions older than max_age_secs are removed, and aborted ones at once."""
+        store = create_upload_session_store(str(tmp_path), max_age_secs=3600)
+        stale = store.start("old.stl").session_id
+        meta = tmp_path / stale / "session.json"
+        meta.write_text(json.dumps({**json.loads(meta.read_text()), "created_at": 0}))
+        dropped = store.start("dropped.stl").session_id
+        kept = store.start("new.stl").session_id
+
+        store.abort(dropped)
+
+        assert not (tmp_path / stale).exists()
+        assert not (tmp_path / dropped).exists()
+        assert store.status(kept).file_name == "new.stl"
+        assert store.expire() == 0
@@ -0,0 +1,783 @@
+"""Unit tests for batch model validation.
+
+Focus: Test validate_many keeps input order and reports bad files without failing the batch,
+that validation lets other Python threads run, that repeated content hits the cache,
+that STL validation measures the model, its overhangs and its stability on the bed, that
+OBJ validation reports the materials it names, that STEP validation tells parts from
+assemblies, that STL and OBJ validation warn of careless exports, that memory-mapped
+and streamed validation give the same results, that the async validators match the
+synchronous ones, that valid uploads carry their SHA-256, that content is
+checked against the extension, that size, triangle and line limits are enforced, and that STLs convert between ASCII and binary.
+"""
+
+import asyncio
+import gzip
+import hashlib
+import io
+import struct
+import threading
+import time
+import zipfile
+
+import pytest
+
+from orca_quote_machine._rust_core import (
+    configure_validation_cache,
+    convert_stl,
+    create_streaming_validator,
+    create_validation_limits,
+    detect_file_type,
+    set_mmap_threshold,
+    to_dict,
+    validate_3d_model,
+    validate_3d_model_async,
+    validate_many,
+    validate_obj,
+    validate_obj_async,
+    validate_ply_async,
+    validate_step_async,
+    validate_stl_async,
+    validation_cache_stats,
+)
+
+
+class TestValidateMany:
+    """Tests for validate_many."""
+
+    def test_results_match_single_validation_in_order(self, tmp_path):
+        """Test each result equals validate_3d_model for the same path, in input order."""
+        cube = tmp_path / "cube.stl"
+        cube.write_text("solid cube\nendsolid cube\n")
+        mesh = tmp_path / "mesh.obj"
+        mesh.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+        broken = tmp_path / "broken.obj"
+        broken.write_text("# no geometry\n")
+        paths = [str(cube), str(mesh), str(broken)] * 20
+
+        results = validate_many(paths)
+
+        assert len(results) == len(paths)
+        for path, result in zip(paths, results, strict=True):
+            expected = validate_3d_model(path)
+            assert (result.file_type, result.is_valid) == (expected.file_type, expected.is_valid)
+        assert [r.is_valid for r in results[:3]] == [True, True, False]
+
+    def test_missing_and_unsupported_files_are_invalid(self, tmp_path):
+        """Test missing or unsupported files come back invalid rather than raising."""
+        text = tmp_path / "notes.txt"
+        text.write_text("hello")
+
+        missing, unsupported = validate_many([str(tmp_path / "gone.stl"), str(text)])
+
+        assert not missing.is_valid
+        assert missing.error_message == "File not found"
+        assert unsupported.file_type == "unknown"
+        assert validate_many([]) == []
+
+
+class TestValidationReleasesGil:
+    """Tests that validation runs without holding the GIL."""
+
+    def test_python_thread_progresses_during_validation(self, tmp_path):
+        """Test a Python thread is never stalled for most of a long validation."""
+        # ASCII STL without endsolid forces a scan of the whole file.
+        large = tmp_path / "large.stl"
+        large.write_text("solid big\n" + "  vertex 1.0 2.0 3.0\n" * 2_000_000)
+        longest_stall = 0.0
+        stop = threading.Event()
+
+        def tick() -> None:
+            nonlocal longest_stall
+            last = time.perf_counter()
+            while not stop.is_set():
+                now = time.perf_counter()
+                longest_stall = max(longest_stall, now - last)
+                last = now
+
+        ticker = threading.Thread(target=tick)
+        ticker.start()
+        try:
+            started = time.perf_counter()
+            result = validate_3d_model(str(large))
+            elapsed = time.perf_counter() - started
+        finally:
+            stop.set()
+            ticker.join()
+
+        assert not result.is_valid
+        # Holding the GIL would stall the ticker for the whole scan.
+        assert longest_stall < elapsed / 2
+
+
+class TestValidationCache:
+    """Tests for configure_validation_cache and validation_cache_stats."""
+
+    def test_same_content_is_served_from_cache(self, tmp_path):
+        """Test a copy of an already validated upload is a hit and edited content a miss."""
+        original = tmp_path / "preview.stl"
+        original.write_text("solid cube\nendsolid cube\n")
+        copy = tmp_path / "submit.stl"
+        copy.write_text(original.read_text())
+
+        configure_validation_cache(8)
+        try:
+            before = validation_cache_stats()
+            assert validate_3d_model(str(original)).is_valid
+            assert validate_3d_model(str(copy)).is_valid
+            copy.write_text("solid cube\n")
+            assert not validate_3d_model(str(copy)).is_valid
+            after = validation_cache_stats()
+        finally:
+            configure_validation_cache(0)
+
+        assert after.hits - before.hits == 1
+        assert after.misses - before.misses == 2
+        assert validation_cache_stats().entries == 0
+
+    def test_capacity_evicts_least_recently_used(self, tmp_path):
+        """Test the cache never holds more entries than its capacity."""
+        configure_validation_cache(2)
+        try:
+            for index in range(4):
+                model = tmp_path / f"part{index}.obj"
+                model.write_text(f"v 0 0 {index}\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+                validate_3d_model(str(model))
+            stats = validation_cache_stats()
+        finally:
+            configure_validation_cache(0)
+
+        assert stats.entries == 2
+        assert stats.capacity == 2
+
+
+class TestStlDimensions:
+    """Tests for ModelInfo.dimensions_mm from STL validation."""
+
+    def test_ascii_and_binary_dimensions(self, tmp_path):
+        """Test both STL encodings report their bounding box in mm."""
+        ascii_stl = tmp_path / "ascii.stl"
+        ascii_stl.write_text(
+            "solid part\n  facet normal 0 0 1\n    outer loop\n"
+            "      vertex -5 0 0\n      vertex 15 2.5 0\n      vertex 0 20 7.5\n"
+            "    endloop\n  endfacet\nendsolid part\n"
+        )
+        binary_stl = tmp_path / "binary.stl"
+        binary_stl.write_bytes(
+            b"\0" * 80
+            + struct.pack("<I", 1)
+            + struct.pack("<12fH", 0, 0, 1, -5, 0, 0, 15, 2.5, 0, 0, 20, 7.5, 0)
+        )
+
+        for path in (ascii_stl, binary_stl):
+            assert validate_3d_model(str(path)).dimensions_mm == (20.0, 20.0, 7.5)
+
+    def test_invalid_and_other_formats_have_none(self, tmp_path):
+        """Test dimensions are only reported for valid STL files."""
+        truncated = tmp_path / "truncated.stl"
+        truncated.write_text("solid part\n  facet normal 0 0 1\n      vertex 1 2 3\n")
+        obj = tmp_path / "part.obj"
+        obj.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+
+        assert validate_3d_model(str(truncated)).dimensions_mm is None
+        assert validate_3d_model(str(obj)).dimensions_mm is None
+
+
+def _box_facets(low, high) -> list:
+    """The 12 triangles of an axis-aligned box, wound outwards."""
+    center = [(lo + hi) / 2 for lo, hi in zip(low, high)]
+    facets = []
+    for axis in range(3):
+        u, v = [a for a in range(3) if a != axis]
+        for side in (low, high):
+            corners = []
+            for du, dv in ((0, 0), (1, 0), (1, 1), (0, 1)):
+                corner = list(side)
+                corner[u] = (low, high)[du][u]
+                corner[v] = (low, high)[dv][v]
+                corners.append(corner)
+            # Wind each face so its normal points away from the centre
+            a, b, c = corners[:3]
+            normal = [
+                (b[1] - a[1]) * (c[2] - a[2]) - (b[2] - a[2]) * (c[1] - a[1]),
+                (b[2] - a[2]) * (c[0] - a[0]) - (b[0] - a[0]) * (c[2] - a[2]),
+                (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]),
+            ]
+            if sum(n * (p - q) for n, p, q in zip(normal, a, center)) < 0:
+                corners.reverse()
+            facets += [corners[:3], [corners[0], corners[2], corners[3]]]
+    return facets
+
+
+def _write_binary_stl(path, facets) -> str:
+    path.write_bytes(
+        b"\0" * 80
+        + struct.pack("<I", len(facets))
+        + b"".join(struct.pack("<12fH", 0, 0, 0, *a, *b, *c, 0) for a, b, c in facets)
+    )
+    return str(path)
+
+
+class TestStlOverhangs:
+    """Tests for ModelInfo.overhang_area_mm2 and needs_supports from STL validation."""
+
+    def test_box_on_the_bed_needs_no_supports(self, tmp_path):
+        """Test a box's underside rests on the bed and is not counted."""
+        box = _box_facets((0, 0, 0), (20, 20, 10))
+        info = validate_3d_model(_write_binary_stl(tmp_path / "box.stl", box))
+
+        assert info.overhang_area_mm2 == 0.0
+        assert info.needs_supports is False
+
+    def test_raised_underside_needs_supports(self, tmp_path):
+        """Test a ledge 5 mm up counts its 20 x 20 mm underside, in either STL encoding."""
+        facets = _box_facets((0, 0, 0), (10, 10, 10)) + _box_facets((20, 0, 5), (40, 20, 10))
+        ascii_stl = tmp_path / "ascii.stl"
+        ascii_stl.write_text(
+            "solid part\n"
+            + "".join(
+                "facet normal 0 0 0\nouter loop\n"
+                + "".join(f"vertex {x} {y} {z}\n" for x, y, z in facet)
+                + "endloop\nendfacet\n"
+                for facet in facets
+            )
+            + "endsolid part\n"
+        )
+
+        for path in (str(ascii_stl), _write_binary_stl(tmp_path / "binary.stl", facets)):
+            info = validate_3d_model(path)
+            assert info.overhang_area_mm2 == 400.0
+            assert info.needs_supports is True
+
+    def test_other_formats_have_none(self, tmp_path):
+        """Test overhangs are only reported for valid STL files."""
+        obj = tmp_path / "part.obj"
+        obj.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+
+        info = validate_3d_model(str(obj))
+        assert info.overhang_area_mm2 is None
+        assert info.needs_supports is None
+
+
+class TestStlStability:
+    """Tests for ModelInfo.stability from STL validation."""
+
+    def test_cube_is_stable(self, tmp_path):
+        """Test a cube has its centre of mass in the middle and needs no brim or raft."""
+        box = _box_facets((0, 0, 0), (20, 20, 20))
+        stability = validate_3d_model(_write_binary_stl(tmp_path / "cube.stl", box)).stability
+
+        assert stability.center_of_mass_mm == pytest.approx((10.0, 10.0, 10.0))
+        assert stability.footprint_area_mm2 == pytest.approx(400.0)
+        assert stability.risk_score == 0.0
+        assert stability.recommendation is None
+        assert stability.warning is None
+
+    def test_tall_pillar_needs_a_raft(self, tmp_path):
+        """Test a pillar six times as tall as it is wide is flagged for a raft."""
+        pillar = _box_facets((0, 0, 0), (10, 10, 60))
+        stability = validate_3d_model(_write_binary_stl(tmp_path / "pillar.stl", pillar)).stability
+
+        assert stability.center_of_mass_mm[2] == pytest.approx(30.0)
+        assert stability.risk_score == 1.0
+        assert stability.recommendation == "raft"
+        assert "print with a raft" in stability.warning
+
+    def test_centre_of_mass_beyond_the_footprint(self, tmp_path):
+        """Test a short post under a large block to one side counts as top-heavy."""
+        facets = _box_facets((0, 0, 0), (10, 10, 10)) + _box_facets((10, 0, 10), (50, 40, 20))
+        stability = validate_3d_model(_write_binary_stl(tmp_path / "t.stl", facets)).stability
+
+        assert stability.footprint_area_mm2 == pytest.approx(100.0)
+        assert stability.risk_score == 1.0
+
+
+class TestObjMaterials:
+    """Tests for ModelInfo.obj_materials from OBJ validation."""
+
+    def test_materials_and_library_are_reported(self, tmp_path):
+        """Test mtllib and usemtl names are listed once each, in order, wherever they appear."""
+        (tmp_path / "part.mtl").write_text("newmtl red\nKd 1 0 0\n")
+        obj = tmp_path / "part.obj"
+        obj.write_text(
+            "mtllib part.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\n"
+            "usemtl red\nf 1 2 3\nusemtl Dark Blue\nf 1 2 4\nusemtl red\nf 1 3 4\n"
+        )
+
+        materials = validate_3d_model(str(obj)).obj_materials
+        assert materials.libraries == ["part.mtl"]
+        assert materials.missing_libraries == []
+        assert materials.materials == ["red", "Dark Blue"]
+        assert "(red, Dark Blue) are ignored" in materials.warning
+
+    def test_missing_library_is_flagged(self, tmp_path):
+        """Test a library not beside the OBJ, or outside its directory, is reported missing."""
+        (tmp_path / "shared.mtl").write_text("newmtl red\n")
+        (tmp_path / "upload").mkdir()
+        obj = tmp_path / "upload" / "part.obj"
+        obj.write_text("mtllib part.mtl\nmtllib ../shared.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+
+        materials = validate_3d_model(str(obj)).obj_materials
+        assert materials.missing_libraries == ["part.mtl", "../shared.mtl"]
+        assert materials.materials == []
+        assert "part.mtl, ../shared.mtl was not uploaded" in materials.warning
+
+    def test_plain_obj_has_none(self, tmp_path):
+        """Test an OBJ that names no materials has no obj_materials."""
+        obj = tmp_path / "part.obj"
+        obj.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+
+        assert validate_3d_model(str(obj)).obj_materials is None
+
+
+def _write_step(path, entities: str) -> str:
+    path.write_text(f"ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n{entities}ENDSEC;\nEND-ISO-10303-21;\n")
+    return str(path)
+
+
+class TestStepSummary:
+    """Tests for ModelInfo.step_summary from STEP validation."""
+
+    def test_single_part(self, tmp_path):
+        """Test one product with one solid is not an assembly."""
+        path = _write_step(
+            tmp_path / "bracket.step",
+            "#1 = PRODUCT('bracket','bracket','',(#2));\n#10 = MANIFOLD_SOLID_BREP('',#11);\n"
+            "#20 = PRODUCT_DEFINITION('design','',#3,#4);\n",
+        )
+
+        summary = validate_3d_model(path).step_summary
+        assert (summary.products, summary.assembly_usages, summary.solids) == (1, 0, 1)
+        assert summary.is_assembly is False
+
+    def test_assembly_counts_usages_and_solids(self, tmp_path):
+        """Test an assembly of two parts, one placed twice, with entities split across lines."""
+        path = _write_step(
+            tmp_path / "hinge.stp",
+            "#1=PRODUCT('hinge','hinge','',(#9));\n#2=PRODUCT('leaf','leaf','',(#9));\n"
+            "#3=PRODUCT('pin','pin','',(#9));\n"
+            "#4=NEXT_ASSEMBLY_USAGE_OCCURRENCE('1','leaf 1','',#5,#6,$);"
+            "#7=NEXT_ASSEMBLY_USAGE_OCCURRENCE('2','leaf 2','',#5,#6,$);\n"
+            "#8=NEXT_ASSEMBLY_USAGE_OCCURRENCE(\n  '3','pin','',#5,#6,$);\n"
+            "#11=MANIFOLD_SOLID_BREP('',#12);\n#13=BREP_WITH_VOIDS('',#14,(#15));\n",
+        )
+
+        summary = validate_3d_model(path).step_summary
+        assert (summary.products, summary.assembly_usages, summary.solids) == (3, 3, 2)
+        assert summary.is_assembly is True
+
+    def test_invalid_step_and_other_formats_have_none(self, tmp_path):
+        """Test only a valid STEP file has a summary."""
+        broken = tmp_path / "broken.step"
+        broken.write_text("ISO-10303-21;\n#1=PRODUCT('a','a','',(#2));\n")
+        obj = tmp_path / "part.obj"
+        obj.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+
+        assert validate_3d_model(str(broken)).step_summary is None
+        assert validate_3d_model(str(obj)).step_summary is None
+
+
+class TestMeshWarnings:
+    """Tests for ModelInfo.warnings from STL and OBJ validation."""
+
+    def test_clean_mesh_has_no_warnings(self, tmp_path):
+        """Test a closed box validates without warnings."""
+        path = _write_binary_stl(tmp_path / "box.stl", _box_facets((0, 0, 0), (10, 10, 10)))
+
+        assert validate_3d_model(path).warnings == []
+
+    def test_stl_zero_area_triangles(self, tmp_path):
+        """Test collapsed and sliver triangles are counted in both STL encodings."""
+        facets = _box_facets((0, 0, 0), (10, 10, 10)) + [
+            ((0, 0, 0), (5, 5, 5), (5, 5, 5)),
+            ((0, 0, 0), (1, 1, 1), (2, 2, 2)),
+        ]
+        ascii_stl = tmp_path / "ascii.stl"
+        ascii_stl.write_text(
+            "solid part\n"
+            + "".join(
+                "facet normal 0 0 0\nouter loop\n"
+                + "".join(f"vertex {x} {y} {z}\n" for x, y, z in facet)
+                + "endloop\nendfacet\n"
+                for facet in facets
+            )
+            + "endsolid part\n"
+        )
+
+        for path in (str(ascii_stl), _write_binary_stl(tmp_path / "binary.stl", facets)):
+            info = validate_3d_model(path)
+            assert info.is_valid
+            assert info.warnings == ["2 triangles have no area"]
+
+    def test_obj_repeated_and_unused_vertices(self, tmp_path):
+        """Test an OBJ's repeated, unused and zero-area geometry is reported line by line."""
+        obj = tmp_path / "part.obj"
+        obj.write_text(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv -0.0 0 0\nv 5 5 5\nv 9 9 9\n"
+            "f 1 2 3\nf 1 4 2\nf -2 -2 -2\n"
+        )
+
+        assert validate_3d_model(str(obj)).warnings == [
+            "2 triangles have no area",
+            "1 vertex repeats another's position",
+            "1 vertex is not used by any face",
+        ]
+
+
+class TestMappedValidation:
+    """Tests for validating from a memory map with set_mmap_threshold."""
+
+    def test_mapped_results_match_buffered(self, tmp_path):
+        """Test every STL, OBJ and STEP variant validates the same from a map as from a buffer."""
+        box = _box_facets((0, 0, 0), (10, 10, 10))
+        ascii_stl = tmp_path / "ascii.stl"
+        ascii_stl.write_text(
+            "solid box\r\n"
+            + "".join(
+                "facet normal 0 0 0\r\nouter loop\r\n"
+                + "".join(f"vertex {x} {y} {z}\r\n" for x, y, z in facet)
+                + "endloop\r\nendfacet\r\n"
+                for facet in box
+            )
+            + "endsolid box"
+        )
+        obj = tmp_path / "part.obj"
+        obj.write_text("mtllib part.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nv 5 5 5\nf 1 2 3\n")
+        truncated = tmp_path / "truncated.stl"
+        truncated.write_bytes(b"\0" * 80 + struct.pack("<I", 2) + b"\0" * 50)
+        paths = [
+            str(ascii_stl),
+            _write_binary_stl(tmp_path / "binary.stl", box),
+            str(truncated),
+            str(obj),
+            _write_step(tmp_path / "part.step", "#1=PRODUCT('a','a','',(#2));\n"),
+        ]
+
+        def summary(path):
+            info = validate_3d_model(path)
+            return (
+                info.is_valid,
+                info.error_message,
+                info.dimensions_mm,
+                info.overhang_area_mm2,
+                info.warnings,
+                info.obj_materials and info.obj_materials.materials,
+                info.step_summary and info.step_summary.products,
+            )
+
+        buffered = [summary(path) for path in paths]
+        set_mmap_threshold(1)
+        try:
+            mapped = [summary(path) for path in paths]
+        finally:
+            set_mmap_threshold()
+
+        assert mapped == buffered
+        assert [result[0] for result in mapped] == [True, True, False, True, True]
+        assert mapped[0][2] == (10.0, 10.0, 10.0)
+
+
+class TestStreamingValidator:
+    """Tests for create_streaming_validator."""
+
+    @staticmethod
+    def _stream(path, chunk_size):
+        validator = create_streaming_validator(path)
+        with open(path, "rb") as f:
+            data = f.read()
+        for start in range(0, len(data), chunk_size):
+            validator.feed(data[start : start + chunk_size])
+        return validator.finish()
+
+    def test_streamed_results_match_file_validation(self, tmp_path):
+        """Test every format streamed in uneven chunks validates as the saved file does."""
+        ascii_stl = tmp_path / "ascii.stl"
+        ascii_stl.write_text(
+            "solid t\r\nfacet normal 0 0 1\r\nouter loop\r\nvertex 0 0 0\r\n"
+            "vertex 20 0 0\r\nvertex 0 20 10\r\nendloop\r\nendfacet\r\nendsolid t"
+        )
+        obj = tmp_path / "part.obj"
+        obj.write_text("mtllib part.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 1 0\nf 1 2 3\nusemtl red")
+        ply = tmp_path / "part.ply"
+        ply.write_bytes(
+            b"ply\nformat binary_little_endian 1.0\nelement vertex 3\n"
+            b"property float x\nproperty float y\nproperty float z\n"
+            b"element face 1\nproperty list uchar uint vertex_indices\nend_header\n"
+            + struct.pack("<9fB3I", 0, 0, 0, 20, 0, 0, 0, 20, 10, 3, 0, 1, 2)
+        )
+        truncated = tmp_path / "truncated.stl"
+        truncated.write_bytes(b"\0" * 80 + struct.pack("<I", 3) + b"\0" * 50)
+        paths = [
+            str(ascii_stl),
+            _write_binary_stl(tmp_path / "box.stl", _box_facets((0, 0, 0), (10, 10, 10))),
+            str(truncated),
+            str(obj),
+            _write_step(tmp_path / "part.step", "#1=PRODUCT('a','a','',(#2));\n"),
+            str(ply),
+        ]
+
+        for path in paths:
+            expected = to_dict(validate_3d_model(path))
+            for chunk_size in (1, 7, 64, 1 << 20):
+                assert to_dict(self._stream(path, chunk_size)) == expected, (path, chunk_size)
+
+    def test_oversized_binary_stl_rejected_early(self, tmp_path):
+        """Test a binary STL longer than its triangle count allows fails on the chunk that overruns it."""
+        validator = create_streaming_validator(str(tmp_path / "part.stl"))
+        validator.feed(b"\0" * 80 + struct.pack("<I", 1) + b"\0" * 50)
+
+        with pytest.raises(ValueError, match="Expected 134, got at least 135"):
+            validator.feed(b"\0")
+        with pytest.raises(ValueError):
+            validator.feed(b"\0" * 100)
+        info = validator.finish()
+        assert not info.is_valid
+        assert validator.bytes_received == 135
+
+    def test_bad_step_header_and_unsupported_type_rejected(self, tmp_path):
+        """Test a STEP file is refused on its first line, and an unknown extension on its first chunk."""
+        step = create_streaming_validator(str(tmp_path / "part.step"))
+        with pytest.raises(ValueError, match="missing: ISO header"):
+            step.feed(b"<html><body>Not found</body></html>\n")
+
+        exe = create_streaming_validator(str(tmp_path / "setup.exe"))
+        with pytest.raises(ValueError, match="Unsupported file type"):
+            exe.feed(b"MZ")
+        assert exe.finish().error_message == "Unsupported file type"
+
+
+class TestAsyncValidation:
+    """Tests for the awaitable validators."""
+
+    def test_async_results_match_sync(self, tmp_path):
+        """Test each async validator returns what validate_3d_model does, for valid and missing files."""
+        obj = tmp_path / "part.obj"
+        obj.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+        ply = tmp_path / "part.ply"
+        ply.write_text("ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n"
+                       "property float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n"
+                       "0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n")
+        cases = [
+            (validate_stl_async, _write_binary_stl(tmp_path / "box.stl", _box_facets((0, 0, 0), (10, 10, 10)))),
+            (validate_obj_async, str(obj)),
+            (validate_step_async, _write_step(tmp_path / "part.step", "#1=PRODUCT('a','a','',(#2));\n")),
+            (validate_ply_async, str(ply)),
+            (validate_stl_async, str(tmp_path / "missing.stl")),
+        ]
+
+        async def validate_all():
+            return await asyncio.gather(*(validate(path) for validate, path in cases))
+
+        results = asyncio.run(validate_all())
+        for (_, path), info in zip(cases, results):
+            assert to_dict(info) == to_dict(validate_3d_model(path)), path
+        assert results[0].is_valid and not results[-1].is_valid
+
+    def test_errors_raise_from_await(self, tmp_path):
+        """Test an unreadable model raises from the await the error the sync validator raises."""
+        folder = tmp_path / "folder.obj"
+        folder.mkdir()
+
+        async def validate():
+            return await validate_3d_model_async(str(folder))
+
+        with pytest.raises(OSError, match="Is a directory"):
+            validate_3d_model(str(folder))
+        with pytest.raises(OSError, match="Is a directory"):
+            asyncio.run(validate())
+
+
+class TestContentHash:
+    """Tests for ModelInfo.sha256."""
+
+    def test_every_path_hashes_the_uploaded_bytes(self, tmp_path):
+        """Test file, cached, streamed and async validation give the hex SHA-256 of the upload."""
+        path = _write_binary_stl(tmp_path / "box.stl", _box_facets((0, 0, 0), (10, 10, 10)))
+        with open(path, "rb") as f:
+            data = f.read()
+        expected = hashlib.sha256(data).hexdigest()
+
+        async def validate():
+            return await validate_stl_async(path)
+
+        streamed = create_streaming_validator(path)
+        streamed.feed(data)
+        assert validate_3d_model(path).sha256 == expected
+        assert asyncio.run(validate()).sha256 == expected
+        assert streamed.finish().sha256 == expected
+        configure_validation_cache(8)
+        try:
+            assert validate_3d_model(path).sha256 == expected
+            assert validate_3d_model(path).sha256 == expected
+        finally:
+            configure_validation_cache(0)
+
+    def test_invalid_and_missing_files_have_no_hash(self, tmp_path):
+        """Test only valid models are hashed."""
+        broken = tmp_path / "broken.stl"
+        broken.write_text("solid cube\n")
+
+        assert validate_3d_model(str(broken)).sha256 is None
+        assert validate_3d_model(str(tmp_path / "missing.stl")).sha256 is None
+
+
+class TestFileTypeSniffing:
+    """Tests for detect_file_type and the extension check in validate_3d_model."""
+
+    @staticmethod
+    def _zip(name):
+        buffer = io.BytesIO()
+        with zipfile.ZipFile(buffer, "w") as archive:
+            archive.writestr(name, "<model/>")
+        return buffer.getvalue()
+
+    def test_types_told_from_content(self, tmp_path):
+        """Test each format is recognised by its content alone."""
+        binary_stl = tmp_path / "box.stl"
+        _write_binary_stl(binary_stl, _box_facets((0, 0, 0), (10, 10, 10)))
+        samples = {
+            b"solid cube\nendsolid cube\n": "stl",
+            binary_stl.read_bytes(): "stl",
+            b"# Blender\nmtllib part.mtl\nv 0 0 0\n": "obj",
+            b"ISO-10303-21;\nHEADER;\n": "step",
+            self._zip("3D/3dmodel.model"): "3mf",
+            self._zip("readme.txt"): "zip",
+            gzip.compress(b"solid"): "gzip",
+            b"; generated by OrcaSlicer\nG28\n": "gcode",
+            b"G28\nM104 S200\n": "gcode",
+            b"ply\nformat ascii 1.0\n": "ply",
+            b'<?xml version="1.0"?>\n<amf unit="millimeter">': "amf",
+            b"<!DOCTYPE html><html><body>Not found</body></html>": "html",
+            b"MZ\x90\x00\x03": "exe",
+            b"\x7fELF\x02\x01": "elf",
+            b"just some notes\n": "unknown",
+            b"": "unknown",
+        }
+
+        for contents, expected in samples.items():
+            assert detect_file_type(contents) == expected, contents[:40]
+
+    def test_extension_mismatch_rejected(self, tmp_path):
+        """Test an error page or a renamed program is refused for what it is, and matching content passes."""
+        page = tmp_path / "part.stl"
+        page.write_text("<html><body>502 Bad Gateway</body></html>\n")
+        program = tmp_path / "part.step"
+        program.write_bytes(b"MZ" + bytes(1000))
+        obj_as_stl = tmp_path / "mesh.stl"
+        obj_as_stl.write_text("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+
+        info = validate_3d_model(str(page))
+        assert not info.is_valid
+        assert info.file_type == "stl"
+        assert info.error_message == "File is named .stl but its content is an HTML page"
+        assert validate_3d_model(str(program)).error_message == (
+            "File is named .step but its content is a Windows executable"
+        )
+        assert validate_3d_model(str(obj_as_stl)).error_message == (
+            "File is named .stl but its content is an OBJ model"
+        )
+        assert validate_3d_model(str(obj_as_stl.rename(tmp_path / "mesh.obj"))).is_valid
+
+    def test_streamed_mismatch_rejected_early(self, tmp_path):
+        """Test a renamed program streamed as an STL is refused once its first bytes are in."""
+        path = tmp_path / "part.stl"
+        path.write_bytes(b"MZ" + bytes(4094))
+        validator = create_streaming_validator(str(path))
+
+        with pytest.raises(ValueError, match="content is a Windows executable"):
+            validator.feed(path.read_bytes()[:1024])
+        assert to_dict(validator.finish()) == to_dict(validate_3d_model(str(path))) | {"file_size": 1024}
+
+
+class TestValidationLimits:
+    """Tests for create_validation_limits."""
+
+    def test_counts_reported(self, tmp_path):
+        """Test valid meshes report their triangles, and text formats their lines."""
+        box = _write_binary_stl(tmp_path / "box.stl", _box_facets((0, 0, 0), (10, 10, 10)))
+        quad = tmp_path / "quad.obj"
+        quad.write_text("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n")
+
+        stl, obj = validate_3d_model(box), validate_3d_model(str(quad))
+
+        assert (stl.triangle_count, stl.line_count) == (12, None)
+        assert (obj.triangle_count, obj.line_count) == (2, 5)
+
+    def test_models_over_a_limit_rejected(self, tmp_path):
+        """Test each limit fails a model over it, naming the limit, and 0 lifts it."""
+        box = _write_binary_stl(tmp_path / "box.stl", _box_facets((0, 0, 0), (10, 10, 10)))
+        quad = tmp_path / "quad.obj"
+        quad.write_text("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n")
+
+        small = validate_3d_model(box, create_validation_limits(max_file_bytes=100))
+        assert not small.is_valid
+        assert small.error_message == "File is 684 bytes, over the limit of 100"
+        assert validate_many([box], create_validation_limits(max_triangles=11))[0].error_message == (
+            "Model has 12 triangles, over the limit of 11"
+        )
+        assert validate_obj(str(quad), create_validation_limits(max_lines=4)).error_message == (
+            "Model has 5 lines, over the limit of 4"
+        )
+        assert validate_3d_model(box, create_validation_limits(0, 0, 0)).is_valid
+
+    def test_streamed_upload_refused_once_over(self, tmp_path):
+        """Test a binary STL is refused on the header that declares too many triangles."""
+        box = _write_binary_stl(tmp_path / "box.stl", _box_facets((0, 0, 0), (10, 10, 10)))
+        with open(box, "rb") as f:
+            data = f.read()
+        validator = create_streaming_validator(box, create_validation_limits(max_triangles=11))
+
+        with pytest.raises(ValueError, match="Model has 12 triangles, over the limit of 11"):
+            validator.feed(data[:84])
+        info = validator.finish()
+        assert not info.is_valid
+        assert info.error_message == "Model has 12 triangles, over the limit of 11"
+        assert info.file_size == 84
+
+
+class TestConvertStl:
+    """Tests for convert_stl."""
+
+    def test_ascii_binary_round_trip(self, tmp_path):
+        """Test ASCII converts to a valid binary STL and back with the same size and facets."""
+        ascii_stl = tmp_path / "wedge.stl"
+        ascii_stl.write_text(
+            "solid wedge\n"
+            "facet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 20 0 0\nvertex 0 10 5\n"
+            "endloop\nendfacet\n"
+            "facet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 0 10 5\nvertex -4 0 0\n"
+            "endloop\nendfacet\nendsolid wedge\n"
+        )
+        binary_stl = tmp_path / "wedge-binary.stl"
+        back = tmp_path / "wedge-back.stl"
+
+        assert convert_stl(str(ascii_stl), str(binary_stl)) == 2
+        assert binary_stl.stat().st_size == 84 + 2 * 50
+        assert convert_stl(str(binary_stl), str(back), to_binary=False) == 2
+
+        for path in (ascii_stl, binary_stl, back):
+            info = validate_3d_model(str(path))
+            assert info.is_valid
+            assert info.dimensions_mm == (24.0, 10.0, 5.0)
+        assert back.read_text().startswith("solid wedge-binary\n")
+
+    def test_already_binary_is_copied(self, tmp_path):
+        """Test a binary STL asked to become binary is copied unchanged."""
+        source = tmp_path / "part.stl"
+        source.write_bytes(b"\0" * 80 + struct.pack("<I", 1) + b"\0" * 50)
+        copy = tmp_path / "copy.stl"
+
+        assert convert_stl(str(source), str(copy)) == 1
+        assert copy.read_bytes() == source.read_bytes()
+
+    def test_output_over_input_refused(self, tmp_path):
+        """Test converting a file onto itself, under any name, raises and leaves it intact."""
+        source = tmp_path / "part.stl"
+        original = b"\0" * 80 + struct.pack("<I", 1) + b"\0" * 50
+        source.write_bytes(original)
+        alias = tmp_path / "alias.stl"
+        alias.symlink_to(source)
+
+        for output in (source, tmp_path / "." / "part.stl", alias):
+            with pytest.raises(ValueError, match="is the input file"):
+                convert_stl(str(source), str(output), to_binary=False)
+        assert source.read_bytes() == original
@@ -0,0 +1,68 @@
+"""Unit tests for vendor profile sync.
+
+Focus: Test pinning and checksum verification against a local copy of the vendor profiles.
+"""
+
+import json
+
+import pytest
+
+from orca_quote_machine._rust_core import sync_vendor_profiles
+
+
+@pytest.fixture
+def vendor_source(tmp_path):
+    """OrcaSlicer-style profiles tree with an index and two Acme profiles."""
+    root = tmp_path / "upstream"
+    (root / "Acme" / "filament").mkdir(parents=True)
+    (root / "Acme" / "machine").mkdir(parents=True)
+    (root / "Acme.json").write_text(
+        json.dumps(
+            {
+                "name": "Acme",
+                "version": "01.00.00.01",
+                "machine_list": [{"name": "Acme One", "sub_path": "machine/Acme One.json"}],
+                "filament_list": [{"name": "Acme PLA", "sub_path": "filament/Acme PLA.json"}],
+            }
+        )
+    )
+    (root / "Acme" / "machine" / "Acme One.json").write_text(json.dumps({"type": "machine"}))
+    (root / "Acme" / "filament" / "Acme PLA.json").write_text(json.dumps({"type": "filament"}))
+    return root
+
+
+class TestSyncVendorProfiles:
+    """Tests for sync_vendor_profiles."""
+
+    def test_sync_writes_profiles_and_pins_ref(self, tmp_path, vendor_source):
+        """Test the first sync copies every indexed file and records a lock."""
+        dest = tmp_path / "vendor"
+        result = sync_vendor_profiles("Acme", str(dest), git_ref="v1", source=str(vendor_source))
+
+        assert result.version == "01.00.00.01"
+        assert len(result.updated) == 3
+        assert (dest / "Acme" / "filament" / "Acme PLA.json").exists()
+        lock = json.loads((dest / "Acme.lock").read_text())
+        assert lock["git_ref"] == "v1"
+        assert set(lock["files"]) == {"Acme.json", "machine/Acme One.json", "filament/Acme PLA.json"}
+
+        again = sync_vendor_profiles("Acme", str(dest), source=str(vendor_source))
+        assert again.git_ref == "v1"
+        assert again.updated == []
+        assert again.unchanged == 3
+
+    def test_changed_upstream_file_fails_checksum_until_update(self, tmp_path, vendor_source):
+        """Test a pinned sync rejects changed files and update re-pins them."""
+        dest = tmp_path / "vendor"
+        sync_vendor_profiles("Acme", str(dest), source=str(vendor_source))
+        (dest / "Acme" / "filament" / "Acme PLA.json").unlink()
+        upstream = vendor_source / "Acme" / "filament" / "Acme PLA.json"
+        upstream.write_text(json.dumps({"type": "filament", "filament_cost": ["30"]}))
+
+        with pytest.raises(ValueError, match="Checksum mismatch"):
+            sync_vendor_profiles("Acme", str(dest), source=str(vendor_source))
+        assert not (dest / "Acme" / "filament" / "Acme PLA.json").exists()
+
+        result = sync_vendor_profiles("Acme", str(dest), source=str(vendor_source), update=True)
+        assert result.updated == ["filament/Acme PLA.json"]
+        assert (dest / "Acme" / "filament" / "Acme PLA.json").read_text() == upstream.read_text()
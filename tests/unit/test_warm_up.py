@@ -0,0 +1,75 @@
+"""Unit tests for worker warm-up.
+
+Focus: Test profiles are parsed up front and the calibration slice runs through the pipeline.
+"""
+
+import json
+import stat
+
+from orca_quote_machine._rust_core import (
+    create_pipeline_config,
+    create_profile_cache,
+    warm_up,
+)
+
+STUB_SLICER = """#!/bin/sh
+while [ $# -gt 0 ]; do
+    if [ "$1" = "--outputdir" ]; then out="$2"; fi
+    shift
+done
+printf '; estimated printing time = 0h 12m\\n; filament used = 2.5g\\n' > "$out/plate_1.gcode"
+"""
+
+
+def _setup(tmp_path):
+    """Profile tree, stub slicer and pipeline config for one printer."""
+    root = tmp_path / "profiles"
+    for profile_type in ("machine", "process", "filament"):
+        (root / profile_type).mkdir(parents=True)
+    (root / "machine" / "printer.json").write_text(
+        json.dumps(
+            {
+                "type": "machine",
+                "name": "Printer",
+                "printable_area": ["0x0", "200x0", "200x200", "0x200"],
+                "printable_height": "200",
+            }
+        )
+    )
+    (root / "process" / "standard.json").write_text(json.dumps({"type": "process"}))
+    (root / "filament" / "pla.json").write_text(json.dumps({"filament_type": ["PLA"]}))
+    slicer = tmp_path / "slicer.sh"
+    slicer.write_text(STUB_SLICER)
+    slicer.chmod(slicer.stat().st_mode | stat.S_IEXEC)
+    config = create_pipeline_config(
+        str(slicer),
+        str(root),
+        "printer.json",
+        "standard.json",
+        material_prices={"PLA": 20.0},
+        work_dir=str(tmp_path / "work"),
+    )
+    return root, config
+
+
+class TestWarmUp:
+    """Tests for warm_up."""
+
+    def test_profiles_loaded_without_calibration(self, tmp_path):
+        """Test every profile in the cache is counted and no slice runs by default."""
+        root, config = _setup(tmp_path)
+
+        report = warm_up(config, create_profile_cache(str(root), watch=False))
+
+        assert report.profiles_loaded == 3
+        assert report.calibration_ms is None
+
+    def test_calibration_slice_timed(self, tmp_path):
+        """Test the calibration cube is quoted and its workspace removed afterwards."""
+        _, config = _setup(tmp_path)
+
+        report = warm_up(config, calibration=True)
+
+        assert report.profiles_loaded == 0
+        assert report.calibration_ms is not None and report.calibration_ms >= 0
+        assert list((tmp_path / "work").iterdir()) == []
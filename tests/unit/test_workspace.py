@@ -0,0 +1,68 @@
+"""Unit tests for the Rust job workspace.
+
+Focus: Test directory layout, artifact tracking, and cleanup guarantees.
+"""
+
+import os
+
+import pytest
+
+from orca_quote_machine._rust_core import JobWorkspace, create_job_workspace
+
+
+class TestJobWorkspace:
+    """Tests for create_job_workspace and JobWorkspace lifecycle."""
+
+    def test_create_job_workspace_layout(self, tmp_path):
+        """Test that the workspace creates model and output directories."""
+        workspace = create_job_workspace(str(tmp_path), "quote-1")
+
+        assert isinstance(workspace, JobWorkspace)
+        assert workspace.job_id == "quote-1"
+        assert os.path.isdir(workspace.model_dir)
+        assert os.path.isdir(workspace.output_dir)
+        assert workspace.is_active
+
+        workspace.cleanup()
+        assert not os.path.exists(workspace.root)
+        assert not workspace.is_active
+
+    def test_job_id_in_use_refused(self, tmp_path):
+        """Test a second workspace for the same, or same once sanitized, job id is refused."""
+        workspace = create_job_workspace(str(tmp_path), "ab")
+
+        # Sanitizing drops the slash
+        for job_id in ("ab", "a/b"):
+            with pytest.raises(FileExistsError, match="already in use"):
+                create_job_workspace(str(tmp_path), job_id)
+        assert os.path.isdir(workspace.model_dir)
+
+        workspace.cleanup()
+        create_job_workspace(str(tmp_path), "ab").cleanup()
+
+    def test_cleanup_removes_tracked_artifacts(self, tmp_path):
+        """Test that cleanup removes outputs and artifacts outside the root."""
+        upload = tmp_path / "upload.stl"
+        upload.write_text("solid x\nendsolid x\n")
+
+        workspace = create_job_workspace(str(tmp_path / "jobs"))
+        staged = workspace.stage_model(str(upload))
+        with open(os.path.join(workspace.output_dir, "plate_1.gcode"), "w") as f:
+            f.write("; gcode")
+        workspace.track_artifact(str(upload))
+
+        stats = workspace.cleanup()
+
+        assert stats.files_cleaned == 3
+        assert not os.path.exists(staged)
+        assert not upload.exists()
+        # Second cleanup is a no-op
+        assert workspace.cleanup().files_cleaned == 0
+
+    def test_context_manager_cleans_up_on_error(self, tmp_path):
+        """Test that leaving the context via an exception still cleans up."""
+        with pytest.raises(RuntimeError), create_job_workspace(str(tmp_path)) as workspace:
+            root = workspace.root
+            raise RuntimeError("slicer crashed")
+
+        assert not os.path.exists(root)